@@ -0,0 +1,260 @@
+// Licensed under the Apache-2.0 license
+
+use caliptra_api::{
+    mailbox::{
+        AddSubjectAltNameReq, AuthorizeAndStashReq, CapabilitiesResp, CertifyKeyExtendedFlags,
+        CertifyKeyExtendedReq, CertifyKeyExtendedResp, CommandId, EcdsaVerifyReq, ExtendPcrReq,
+        FipsVersionResp, FwInfoResp, GetCertChainReq, GetCertChainResp, GetFmcAliasCertReq,
+        GetFmcAliasCertResp, GetFmcAliasCsrReq, GetFmcAliasCsrResp, GetIdevCertReq,
+        GetIdevCertResp, GetIdevCsrReq, GetIdevCsrResp, GetIdevInfoResp, GetLdevCertReq,
+        GetLdevCertResp, GetRtAliasCertReq, GetRtAliasCertResp, GetTaggedTciResp,
+        IncrementPcrResetCounterReq, LmsVerifyReq, MailboxReqHeader, MailboxRespHeader,
+        PopulateIdevCertReq, QuotePcrsReq, QuotePcrsResp, SetAuthManifestReq, StashMeasurementReq,
+        StashMeasurementResp,
+    },
+    SocManager,
+};
+use zerocopy::IntoBytes;
+
+use crate::{CaliptraClient, ClientError, MailboxOp};
+
+fn check_len(data: &[u8], max: usize) -> Result<[u8; 4], ClientError> {
+    if data.len() > max {
+        return Err(ClientError::RequestDataTooLarge {
+            max: max as u32,
+            actual: data.len() as u32,
+        });
+    }
+    Ok((data.len() as u32).to_le_bytes())
+}
+
+impl<T: SocManager> CaliptraClient<T> {
+    /// GET_IDEV_CERT: signs `tbs` with the IDevID key signature supplied by
+    /// the SoC and returns the resulting certificate.
+    pub fn get_idev_cert(
+        &mut self,
+        tbs: &[u8],
+        signature_r: &[u8; 48],
+        signature_s: &[u8; 48],
+    ) -> Result<MailboxOp<'_, T, GetIdevCertResp>, ClientError> {
+        let tbs_size = check_len(tbs, GetIdevCertReq::DATA_MAX_SIZE)?;
+        self.exec_parts(
+            CommandId::GET_IDEV_CERT,
+            &[&tbs_size, signature_r, signature_s, tbs],
+        )
+    }
+
+    /// GET_IDEV_INFO: returns the IDevID public key.
+    pub fn get_idev_info(&mut self) -> Result<MailboxOp<'_, T, GetIdevInfoResp>, ClientError> {
+        self.exec_parts(CommandId::GET_IDEV_INFO, &[])
+    }
+
+    /// POPULATE_IDEV_CERT: adds the DER-encoded IDevID certificate to the
+    /// certificate chain.
+    pub fn populate_idev_cert(
+        &mut self,
+        cert: &[u8],
+    ) -> Result<MailboxOp<'_, T, MailboxRespHeader>, ClientError> {
+        let cert_size = check_len(cert, PopulateIdevCertReq::MAX_CERT_SIZE)?;
+        self.exec_parts(CommandId::POPULATE_IDEV_CERT, &[&cert_size, cert])
+    }
+
+    /// GET_LDEV_CERT
+    pub fn get_ldev_cert(&mut self) -> Result<MailboxOp<'_, T, GetLdevCertResp>, ClientError> {
+        self.exec_req(GetLdevCertReq::default())
+    }
+
+    /// GET_FMC_ALIAS_CERT
+    pub fn get_fmc_alias_cert(
+        &mut self,
+    ) -> Result<MailboxOp<'_, T, GetFmcAliasCertResp>, ClientError> {
+        self.exec_req(GetFmcAliasCertReq::default())
+    }
+
+    /// GET_RT_ALIAS_CERT
+    pub fn get_rt_alias_cert(
+        &mut self,
+    ) -> Result<MailboxOp<'_, T, GetRtAliasCertResp>, ClientError> {
+        self.exec_req(GetRtAliasCertReq::default())
+    }
+
+    /// ECDSA384_VERIFY: verifies a signature over the hash in the SHA-512
+    /// accelerator.
+    pub fn ecdsa384_verify(
+        &mut self,
+        req: EcdsaVerifyReq,
+    ) -> Result<MailboxOp<'_, T, MailboxRespHeader>, ClientError> {
+        self.exec_req(req)
+    }
+
+    /// LMS_VERIFY: verifies a signature over the hash in the SHA-512
+    /// accelerator.
+    pub fn lms_verify(
+        &mut self,
+        req: LmsVerifyReq,
+    ) -> Result<MailboxOp<'_, T, MailboxRespHeader>, ClientError> {
+        self.exec_req(req)
+    }
+
+    /// STASH_MEASUREMENT
+    pub fn stash_measurement(
+        &mut self,
+        req: StashMeasurementReq,
+    ) -> Result<MailboxOp<'_, T, StashMeasurementResp>, ClientError> {
+        self.exec_req(req)
+    }
+
+    /// DISABLE_ATTESTATION
+    pub fn disable_attestation(
+        &mut self,
+    ) -> Result<MailboxOp<'_, T, MailboxRespHeader>, ClientError> {
+        self.exec_parts(CommandId::DISABLE_ATTESTATION, &[])
+    }
+
+    /// FW_INFO
+    pub fn fw_info(&mut self) -> Result<MailboxOp<'_, T, FwInfoResp>, ClientError> {
+        self.exec_parts(CommandId::FW_INFO, &[])
+    }
+
+    /// DPE_TAG_TCI: tags the DPE context identified by `handle`.
+    pub fn dpe_tag_tci(
+        &mut self,
+        handle: &[u8; 16],
+        tag: u32,
+    ) -> Result<MailboxOp<'_, T, MailboxRespHeader>, ClientError> {
+        self.exec_parts(CommandId::DPE_TAG_TCI, &[handle, tag.as_bytes()])
+    }
+
+    /// DPE_GET_TAGGED_TCI
+    pub fn dpe_get_tagged_tci(
+        &mut self,
+        tag: u32,
+    ) -> Result<MailboxOp<'_, T, GetTaggedTciResp>, ClientError> {
+        self.exec_parts(CommandId::DPE_GET_TAGGED_TCI, &[tag.as_bytes()])
+    }
+
+    /// INCREMENT_PCR_RESET_COUNTER
+    pub fn increment_pcr_reset_counter(
+        &mut self,
+        index: u32,
+    ) -> Result<MailboxOp<'_, T, MailboxRespHeader>, ClientError> {
+        self.exec_req(IncrementPcrResetCounterReq {
+            hdr: MailboxReqHeader::default(),
+            index,
+        })
+    }
+
+    /// QUOTE_PCRS
+    pub fn quote_pcrs(
+        &mut self,
+        nonce: [u8; 32],
+    ) -> Result<MailboxOp<'_, T, QuotePcrsResp>, ClientError> {
+        self.exec_req(QuotePcrsReq {
+            hdr: MailboxReqHeader::default(),
+            nonce,
+        })
+    }
+
+    /// EXTEND_PCR
+    pub fn extend_pcr(
+        &mut self,
+        pcr_idx: u32,
+        data: [u8; 48],
+    ) -> Result<MailboxOp<'_, T, MailboxRespHeader>, ClientError> {
+        self.exec_req(ExtendPcrReq {
+            hdr: MailboxReqHeader::default(),
+            pcr_idx,
+            data,
+        })
+    }
+
+    /// ADD_SUBJECT_ALT_NAME: sets the DMTF device info used in the DPE leaf
+    /// certificate.
+    pub fn add_subject_alt_name(
+        &mut self,
+        dmtf_device_info: &[u8],
+    ) -> Result<MailboxOp<'_, T, MailboxRespHeader>, ClientError> {
+        let size = check_len(dmtf_device_info, AddSubjectAltNameReq::MAX_DEVICE_INFO_LEN)?;
+        self.exec_parts(CommandId::ADD_SUBJECT_ALT_NAME, &[&size, dmtf_device_info])
+    }
+
+    /// CERTIFY_KEY_EXTENDED: `certify_key_req` is a serialized DPE
+    /// CertifyKey command.
+    pub fn certify_key_extended(
+        &mut self,
+        flags: CertifyKeyExtendedFlags,
+        certify_key_req: [u8; CertifyKeyExtendedReq::CERTIFY_KEY_REQ_SIZE],
+    ) -> Result<MailboxOp<'_, T, CertifyKeyExtendedResp>, ClientError> {
+        self.exec_req(CertifyKeyExtendedReq {
+            hdr: MailboxReqHeader::default(),
+            flags,
+            certify_key_req,
+        })
+    }
+
+    /// VERSION: returns the FIPS module version.
+    pub fn fips_version(&mut self) -> Result<MailboxOp<'_, T, FipsVersionResp>, ClientError> {
+        self.exec_parts(CommandId::VERSION, &[])
+    }
+
+    /// SELF_TEST_START
+    pub fn self_test_start(&mut self) -> Result<MailboxOp<'_, T, MailboxRespHeader>, ClientError> {
+        self.exec_parts(CommandId::SELF_TEST_START, &[])
+    }
+
+    /// SELF_TEST_GET_RESULTS
+    pub fn self_test_get_results(
+        &mut self,
+    ) -> Result<MailboxOp<'_, T, MailboxRespHeader>, ClientError> {
+        self.exec_parts(CommandId::SELF_TEST_GET_RESULTS, &[])
+    }
+
+    /// SHUTDOWN: zeroizes the FIPS module; no further commands are accepted.
+    pub fn shutdown(&mut self) -> Result<MailboxOp<'_, T, MailboxRespHeader>, ClientError> {
+        self.exec_parts(CommandId::SHUTDOWN, &[])
+    }
+
+    /// CAPABILITIES
+    pub fn capabilities(&mut self) -> Result<MailboxOp<'_, T, CapabilitiesResp>, ClientError> {
+        self.exec_parts(CommandId::CAPABILITIES, &[])
+    }
+
+    /// SET_AUTH_MANIFEST: `manifest` is a serialized image authorization
+    /// manifest.
+    pub fn set_auth_manifest(
+        &mut self,
+        manifest: &[u8],
+    ) -> Result<MailboxOp<'_, T, MailboxRespHeader>, ClientError> {
+        let size = check_len(manifest, SetAuthManifestReq::MAX_MAN_SIZE)?;
+        self.exec_parts(CommandId::SET_AUTH_MANIFEST, &[&size, manifest])
+    }
+
+    /// AUTHORIZE_AND_STASH
+    pub fn authorize_and_stash(
+        &mut self,
+        req: AuthorizeAndStashReq,
+    ) -> Result<MailboxOp<'_, T, StashMeasurementResp>, ClientError> {
+        self.exec_req(req)
+    }
+
+    /// GET_IDEV_CSR: returns the IDevID CSR generated during manufacturing.
+    pub fn get_idev_csr(&mut self) -> Result<MailboxOp<'_, T, GetIdevCsrResp>, ClientError> {
+        self.exec_req(GetIdevCsrReq::default())
+    }
+
+    /// GET_CERT_CHAIN: returns up to `size` bytes of the certificate chain
+    /// starting at `offset`.
+    pub fn get_cert_chain(
+        &mut self,
+        req: GetCertChainReq,
+    ) -> Result<MailboxOp<'_, T, GetCertChainResp>, ClientError> {
+        self.exec_req(req)
+    }
+
+    /// GET_FMC_ALIAS_CSR
+    pub fn get_fmc_alias_csr(
+        &mut self,
+    ) -> Result<MailboxOp<'_, T, GetFmcAliasCsrResp>, ClientError> {
+        self.exec_req(GetFmcAliasCsrReq::default())
+    }
+}
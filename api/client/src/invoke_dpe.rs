@@ -0,0 +1,92 @@
+// Licensed under the Apache-2.0 license
+
+use caliptra_api::{
+    mailbox::{CommandId, InvokeDpeReq, InvokeDpeResp, ResponseVarSize},
+    SocManager,
+};
+use dpe::{
+    commands::{Command, CommandHdr},
+    response::{
+        CertifyKeyResp, DeriveContextResp, GetCertificateChainResp, GetProfileResp, NewHandleResp,
+        Response, ResponseHdr, SignResp,
+    },
+};
+use zerocopy::{FromBytes, IntoBytes};
+
+use crate::{CaliptraClient, ClientError, MailboxOp};
+
+/// Returns the DPE command ID and the serialized command (without header).
+fn serialize<'a>(cmd: &Command<'a>) -> (u32, &'a [u8]) {
+    match cmd {
+        Command::GetProfile => (Command::GET_PROFILE, &[]),
+        Command::InitCtx(cmd) => (Command::INITIALIZE_CONTEXT, cmd.as_bytes()),
+        Command::DeriveContext(cmd) => (Command::DERIVE_CONTEXT, cmd.as_bytes()),
+        Command::CertifyKey(cmd) => (Command::CERTIFY_KEY, cmd.as_bytes()),
+        Command::Sign(cmd) => (Command::SIGN, cmd.as_bytes()),
+        Command::RotateCtx(cmd) => (Command::ROTATE_CONTEXT_HANDLE, cmd.as_bytes()),
+        Command::DestroyCtx(cmd) => (Command::DESTROY_CONTEXT, cmd.as_bytes()),
+        Command::GetCertificateChain(cmd) => (Command::GET_CERTIFICATE_CHAIN, cmd.as_bytes()),
+    }
+}
+
+/// Parses the response to DPE command `cmd_id`. Failed commands return
+/// `Response::Error`.
+fn parse_response(cmd_id: u32, data: &[u8]) -> Result<Response, ClientError> {
+    let (hdr, _) =
+        ResponseHdr::read_from_prefix(data).map_err(|_| ClientError::InvalidDpeResponse)?;
+    if hdr.status != 0 {
+        return Ok(Response::Error(hdr));
+    }
+    let resp = match cmd_id {
+        Command::GET_PROFILE => GetProfileResp::read_from_bytes(data)
+            .ok()
+            .map(Response::GetProfile),
+        Command::INITIALIZE_CONTEXT => NewHandleResp::read_from_bytes(data)
+            .ok()
+            .map(Response::InitCtx),
+        Command::DERIVE_CONTEXT => DeriveContextResp::read_from_bytes(data)
+            .ok()
+            .map(Response::DeriveContext),
+        Command::CERTIFY_KEY => CertifyKeyResp::read_from_bytes(data)
+            .ok()
+            .map(Response::CertifyKey),
+        Command::SIGN => SignResp::read_from_bytes(data).ok().map(Response::Sign),
+        Command::ROTATE_CONTEXT_HANDLE => NewHandleResp::read_from_bytes(data)
+            .ok()
+            .map(Response::RotateCtx),
+        Command::DESTROY_CONTEXT => Some(Response::DestroyCtx(hdr)),
+        Command::GET_CERTIFICATE_CHAIN => GetCertificateChainResp::read_from_bytes(data)
+            .ok()
+            .map(Response::GetCertificateChain),
+        _ => None,
+    };
+    resp.ok_or(ClientError::InvalidDpeResponse)
+}
+
+impl<T: SocManager> CaliptraClient<T> {
+    /// INVOKE_DPE: executes `cmd` on the runtime firmware's DPE instance. DPE
+    /// errors are returned as `Response::Error`, not as a `ClientError`.
+    pub fn invoke_dpe(&mut self, cmd: &Command) -> Result<MailboxOp<'_, T, Response>, ClientError> {
+        let (cmd_id, cmd_bytes) = serialize(cmd);
+        let hdr = CommandHdr::new_for_test(cmd_id);
+        let data_size = hdr.as_bytes().len() + cmd_bytes.len();
+        if data_size > InvokeDpeReq::DATA_MAX_SIZE {
+            return Err(ClientError::RequestDataTooLarge {
+                max: InvokeDpeReq::DATA_MAX_SIZE as u32,
+                actual: data_size as u32,
+            });
+        }
+        self.exec_parts_with(
+            CommandId::INVOKE_DPE,
+            &[&(data_size as u32).to_le_bytes(), hdr.as_bytes(), cmd_bytes],
+            Self::finish_dpe,
+            cmd_id,
+        )
+    }
+
+    fn finish_dpe(&mut self, cmd_id: u32) -> Result<Response, ClientError> {
+        let resp: InvokeDpeResp = self.finish_resp(0)?;
+        let data = resp.data().map_err(|_| ClientError::InvalidDpeResponse)?;
+        parse_response(cmd_id, data)
+    }
+}
@@ -0,0 +1,480 @@
+// Licensed under the Apache-2.0 license
+
+//! A SoC-side driver for the Caliptra mailbox.
+//!
+//! [`CaliptraClient`] wraps a [`SocManager`] and exposes a typed method for
+//! every mailbox command. Each method starts the command and returns a
+//! [`MailboxOp`], which can either be waited on with [`MailboxOp::wait()`] or
+//! awaited as a [`Future`] by firmware that runs an executor.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # fn example<T: caliptra_api::SocManager>(soc: T, image: &[u8]) -> Result<(), caliptra_api_client::ClientError> {
+//! use caliptra_api_client::CaliptraClient;
+//!
+//! let mut client = CaliptraClient::new(soc);
+//! let mut upload = client.start_firmware_upload(image.len())?;
+//! for chunk in image.chunks(256) {
+//!     upload.write(chunk)?;
+//! }
+//! upload.finish()?.wait()?;
+//!
+//! let fw_info = client.fw_info()?.wait()?;
+//! # Ok(())
+//! # }
+//! ```
+#![cfg_attr(not(test), no_std)]
+
+mod commands;
+mod invoke_dpe;
+
+use caliptra_api::{
+    calc_checksum,
+    mailbox::{mbox_read_response, CommandId, MailboxReqHeader, MailboxRespHeader, Response},
+    CaliptraApiError, SocManager,
+};
+use core::{
+    future::Future,
+    mem::size_of,
+    pin::Pin,
+    task::{Context, Poll},
+};
+use zerocopy::FromBytes;
+
+/// Size of the mailbox SRAM in bytes.
+const MAILBOX_SIZE: usize = 128 * 1024;
+
+/// Bit in CPTRA_DBG_MANUF_SERVICE_REG that asks the ROM to generate the
+/// IDevID CSR.
+const GENERATE_IDEVID_CSR: u32 = 1 << 0;
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum ClientError {
+    Api(CaliptraApiError),
+    RequestDataTooLarge { max: u32, actual: u32 },
+    MailboxWriteOverflow { dlen: u32 },
+    MailboxWriteIncomplete { dlen: u32, written: u32 },
+    IdevCsrNotReady,
+    InvalidDpeResponse,
+}
+
+impl From<CaliptraApiError> for ClientError {
+    fn from(error: CaliptraApiError) -> Self {
+        ClientError::Api(error)
+    }
+}
+
+/// Checks the header of a mailbox response: the checksum must match the
+/// rest of the response and the FIPS status must be approved.
+pub fn verify_response(data: &[u8]) -> Result<(), CaliptraApiError> {
+    let Ok((hdr, _)) = MailboxRespHeader::read_from_prefix(data) else {
+        return Err(CaliptraApiError::MailboxUnexpectedResponseLen {
+            expected_min: size_of::<MailboxRespHeader>() as u32,
+            expected_max: MAILBOX_SIZE as u32,
+            actual: data.len() as u32,
+        });
+    };
+    let actual = calc_checksum(0, &data[size_of::<u32>()..]);
+    if actual != hdr.chksum {
+        return Err(CaliptraApiError::MailboxRespInvalidChecksum {
+            expected: hdr.chksum,
+            actual,
+        });
+    }
+    if hdr.fips_status != MailboxRespHeader::FIPS_STATUS_APPROVED {
+        return Err(CaliptraApiError::MailboxRespInvalidFipsStatus(
+            hdr.fips_status,
+        ));
+    }
+    Ok(())
+}
+
+/// Reads the result of a command once the mailbox is no longer busy.
+type Finish<T, O> = fn(&mut CaliptraClient<T>, u32) -> Result<O, ClientError>;
+
+/// A mailbox command that has been handed to Caliptra.
+///
+/// The mailbox stays locked until the command completes, so the operation
+/// must be driven to completion with [`MailboxOp::wait()`],
+/// [`MailboxOp::poll_complete()`] or by awaiting it. Dropping an operation
+/// before it completes clears execute, which releases the mailbox and
+/// discards any response.
+#[must_use = "the mailbox stays locked until the command is completed"]
+pub struct MailboxOp<'a, T: SocManager, O> {
+    client: &'a mut CaliptraClient<T>,
+    finish: Option<Finish<T, O>>,
+    // Command-specific value passed to `finish`.
+    ctx: u32,
+}
+
+impl<'a, T: SocManager, O> MailboxOp<'a, T, O> {
+    /// Returns `Poll::Pending` (after calling [`SocManager::delay()`]) while
+    /// Caliptra is still executing the command, or the result once it is
+    /// done.
+    ///
+    /// # Panics
+    ///
+    /// If called again after the result has been returned.
+    pub fn poll_complete(&mut self) -> Poll<Result<O, ClientError>> {
+        let finish = self.finish.expect("MailboxOp polled after it completed");
+        if self
+            .client
+            .soc
+            .soc_mbox()
+            .status()
+            .read()
+            .status()
+            .cmd_busy()
+        {
+            self.client.soc.delay();
+            return Poll::Pending;
+        }
+        self.finish = None;
+        Poll::Ready(finish(self.client, self.ctx))
+    }
+
+    /// Blocks until the command completes, giving up after
+    /// [`SocManager::MAX_WAIT_CYCLES`] polls.
+    pub fn wait(mut self) -> Result<O, ClientError> {
+        for _ in 0..T::MAX_WAIT_CYCLES {
+            if let Poll::Ready(result) = self.poll_complete() {
+                return result;
+            }
+        }
+        Err(CaliptraApiError::MailboxTimeout.into())
+    }
+}
+
+impl<'a, T: SocManager, O> Future for MailboxOp<'a, T, O> {
+    type Output = Result<O, ClientError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let result = self.get_mut().poll_complete();
+        if result.is_pending() {
+            // There is no completion interrupt on the SoC side; ask to be
+            // polled again.
+            cx.waker().wake_by_ref();
+        }
+        result
+    }
+}
+
+impl<'a, T: SocManager, O> Drop for MailboxOp<'a, T, O> {
+    fn drop(&mut self) {
+        if self.finish.is_some() {
+            self.client
+                .soc
+                .soc_mbox()
+                .execute()
+                .write(|w| w.execute(false));
+        }
+    }
+}
+
+/// Streams a request into the mailbox FIFO, packing bytes into words across
+/// calls to `write()`.
+///
+/// If the writer is dropped before the request is handed to Caliptra, the
+/// mailbox lock is released with [`MailboxWriter::abort()`].
+struct MailboxWriter<'a, T: SocManager> {
+    // None once the request has been handed to a `MailboxOp`.
+    client: Option<&'a mut CaliptraClient<T>>,
+    dlen: usize,
+    written: usize,
+    word: [u8; 4],
+}
+
+impl<'a, T: SocManager> MailboxWriter<'a, T> {
+    fn write(&mut self, mut data: &[u8]) -> Result<(), ClientError> {
+        if data.len() > self.dlen - self.written {
+            return Err(ClientError::MailboxWriteOverflow {
+                dlen: self.dlen as u32,
+            });
+        }
+        let mbox = self.client.as_mut().unwrap().soc.soc_mbox();
+        while !data.is_empty() {
+            let offset = self.written % 4;
+            let len = usize::min(4 - offset, data.len());
+            self.word[offset..offset + len].copy_from_slice(&data[..len]);
+            self.written += len;
+            data = &data[len..];
+            if self.written % 4 == 0 {
+                let word = u32::from_le_bytes(self.word);
+                mbox.datain().write(|_| word);
+            }
+        }
+        Ok(())
+    }
+
+    fn execute<O>(
+        mut self,
+        finish: Finish<T, O>,
+        ctx: u32,
+    ) -> Result<MailboxOp<'a, T, O>, ClientError> {
+        if self.written != self.dlen {
+            return Err(ClientError::MailboxWriteIncomplete {
+                dlen: self.dlen as u32,
+                written: self.written as u32,
+            });
+        }
+        let client = self.client.take().unwrap();
+        let offset = self.written % 4;
+        if offset != 0 {
+            self.word[offset..].fill(0);
+            let word = u32::from_le_bytes(self.word);
+            client.soc.soc_mbox().datain().write(|_| word);
+        }
+
+        // Ask the microcontroller to execute this command
+        client.soc.soc_mbox().execute().write(|w| w.execute(true));
+
+        Ok(MailboxOp {
+            client,
+            finish: Some(finish),
+            ctx,
+        })
+    }
+
+    /// Gives up on a partially written request and releases the mailbox
+    /// lock.
+    ///
+    /// Only the microcontroller may write the unlock register, so the SoC
+    /// releases the lock the same way it ends any other command: the
+    /// truncated request is handed to Caliptra, which is expected to reject
+    /// it, and execute is cleared once Caliptra is done with it (or after
+    /// [`SocManager::MAX_WAIT_CYCLES`] polls).
+    fn abort(&mut self) {
+        let Some(client) = self.client.take() else {
+            return;
+        };
+        client.soc.soc_mbox().execute().write(|w| w.execute(true));
+        for _ in 0..T::MAX_WAIT_CYCLES {
+            if !client.soc.soc_mbox().status().read().status().cmd_busy() {
+                break;
+            }
+            client.soc.delay();
+        }
+        client.soc.soc_mbox().execute().write(|w| w.execute(false));
+    }
+}
+
+impl<'a, T: SocManager> Drop for MailboxWriter<'a, T> {
+    fn drop(&mut self) {
+        self.abort();
+    }
+}
+
+/// A FIRMWARE_LOAD command whose image is being streamed into the mailbox.
+///
+/// Dropping the upload, or finishing it before the whole image has been
+/// written, releases the mailbox lock; Caliptra sees a truncated image.
+pub struct FirmwareUpload<'a, T: SocManager>(MailboxWriter<'a, T>);
+
+impl<'a, T: SocManager> FirmwareUpload<'a, T> {
+    /// Appends `chunk` to the image. Chunks may be any length.
+    pub fn write(&mut self, chunk: &[u8]) -> Result<(), ClientError> {
+        self.0.write(chunk)
+    }
+
+    /// Starts executing the command once the whole image has been written.
+    pub fn finish(self) -> Result<MailboxOp<'a, T, ()>, ClientError> {
+        self.0.execute(CaliptraClient::finish_fw_load, 0)
+    }
+}
+
+/// Drives the Caliptra mailbox from the SoC through a [`SocManager`].
+pub struct CaliptraClient<T: SocManager> {
+    soc: T,
+}
+
+impl<T: SocManager> CaliptraClient<T> {
+    pub fn new(soc: T) -> Self {
+        Self { soc }
+    }
+
+    /// The underlying SoC manager, for fuse programming and other register
+    /// access.
+    pub fn soc_manager(&mut self) -> &mut T {
+        &mut self.soc
+    }
+
+    pub fn into_soc_manager(self) -> T {
+        self.soc
+    }
+
+    /// Returns true if the ROM is waiting for a firmware image, or (after
+    /// the image is loaded) if the runtime firmware is ready for commands.
+    pub fn ready_for_firmware(&mut self) -> bool {
+        self.soc.soc_ifc().cptra_flow_status().read().ready_for_fw()
+    }
+
+    /// Uploads a complete firmware image.
+    pub fn upload_firmware(&mut self, image: &[u8]) -> Result<MailboxOp<'_, T, ()>, ClientError> {
+        let mut upload = self.start_firmware_upload(image.len())?;
+        upload.write(image)?;
+        upload.finish()
+    }
+
+    /// Starts a FIRMWARE_LOAD command for an image of `len` bytes, which is
+    /// then written in chunks with [`FirmwareUpload::write()`]. This allows
+    /// the image to be streamed from flash without buffering it.
+    pub fn start_firmware_upload(
+        &mut self,
+        len: usize,
+    ) -> Result<FirmwareUpload<'_, T>, ClientError> {
+        self.start_write(CommandId::FIRMWARE_LOAD, len)
+            .map(FirmwareUpload)
+    }
+
+    /// Asks the ROM to generate the IDevID CSR during the next cold boot.
+    /// Must be called before the boot FSM is started.
+    pub fn request_idev_csr(&mut self) {
+        let reg = self.soc.soc_ifc().cptra_dbg_manuf_service_reg().read();
+        self.soc
+            .soc_ifc()
+            .cptra_dbg_manuf_service_reg()
+            .write(|_| reg | GENERATE_IDEVID_CSR);
+    }
+
+    /// Returns true once the ROM has placed the IDevID CSR in the mailbox.
+    pub fn idev_csr_ready(&mut self) -> bool {
+        self.soc
+            .soc_ifc()
+            .cptra_flow_status()
+            .read()
+            .idevid_csr_ready()
+    }
+
+    /// Reads the IDevID CSR out of the mailbox and lets the ROM continue
+    /// booting. Returns the DER-encoded CSR.
+    pub fn retrieve_idev_csr<'r>(&mut self, buf: &'r mut [u8]) -> Result<&'r [u8], ClientError> {
+        if !self.idev_csr_ready() {
+            return Err(ClientError::IdevCsrNotReady);
+        }
+        let csr = mbox_read_response(self.soc.soc_mbox(), buf)?;
+        self.soc
+            .soc_mbox()
+            .status()
+            .write(|w| w.status(|w| w.cmd_complete()));
+
+        let reg = self.soc.soc_ifc().cptra_dbg_manuf_service_reg().read();
+        self.soc
+            .soc_ifc()
+            .cptra_dbg_manuf_service_reg()
+            .write(|_| reg & !GENERATE_IDEVID_CSR);
+        Ok(csr)
+    }
+
+    /// Executes a typed request. The checksum field of the request is
+    /// calculated, and the checksum of the response is validated.
+    pub fn exec_req<R: caliptra_api::mailbox::Request>(
+        &mut self,
+        req: R,
+    ) -> Result<MailboxOp<'_, T, R::Resp>, ClientError> {
+        let Some(payload) = req.as_bytes().get(size_of::<MailboxReqHeader>()..) else {
+            return Err(CaliptraApiError::MailboxReqTypeTooSmall.into());
+        };
+        self.exec_parts(R::ID, &[payload])
+    }
+
+    /// Executes `cmd` with a request made of a checksum followed by `parts`.
+    fn exec_parts<Resp: Response>(
+        &mut self,
+        cmd: CommandId,
+        parts: &[&[u8]],
+    ) -> Result<MailboxOp<'_, T, Resp>, ClientError> {
+        if size_of::<Resp>() < size_of::<MailboxRespHeader>()
+            || Resp::MIN_SIZE < size_of::<MailboxRespHeader>()
+        {
+            return Err(CaliptraApiError::MailboxRespTypeTooSmall.into());
+        }
+        self.exec_parts_with(cmd, parts, Self::finish_resp::<Resp>, 0)
+    }
+
+    fn exec_parts_with<O>(
+        &mut self,
+        cmd: CommandId,
+        parts: &[&[u8]],
+        finish: Finish<T, O>,
+        ctx: u32,
+    ) -> Result<MailboxOp<'_, T, O>, ClientError> {
+        // calc_checksum() negates the byte sum, so the checksums of the
+        // parts add up to the checksum of the whole request.
+        let chksum = parts
+            .iter()
+            .fold(calc_checksum(cmd.0, &[]), |chksum, part| {
+                chksum.wrapping_add(calc_checksum(0, part))
+            });
+        let len = size_of::<MailboxReqHeader>() + parts.iter().map(|p| p.len()).sum::<usize>();
+
+        let mut writer = self.start_write(cmd, len)?;
+        writer.write(&chksum.to_le_bytes())?;
+        for part in parts {
+            writer.write(part)?;
+        }
+        writer.execute(finish, ctx)
+    }
+
+    fn start_write(
+        &mut self,
+        cmd: CommandId,
+        len: usize,
+    ) -> Result<MailboxWriter<'_, T>, ClientError> {
+        if len > MAILBOX_SIZE {
+            return Err(CaliptraApiError::BufferTooLargeForMailbox.into());
+        }
+
+        // Read a 0 to get the lock
+        if self.soc.soc_mbox().lock().read().lock() {
+            return Err(CaliptraApiError::UnableToLockMailbox.into());
+        }
+
+        // Mailbox lock value should read 1 now
+        // If not, the reads are likely being blocked by the PAUSER check or some other issue
+        if !(self.soc.soc_mbox().lock().read().lock()) {
+            return Err(CaliptraApiError::UnableToReadMailbox.into());
+        }
+
+        self.soc.soc_mbox().cmd().write(|_| cmd.0);
+        self.soc.soc_mbox().dlen().write(|_| len as u32);
+
+        Ok(MailboxWriter {
+            client: Some(self),
+            dlen: len,
+            written: 0,
+            word: [0; 4],
+        })
+    }
+
+    fn finish_resp<Resp: Response>(&mut self, _ctx: u32) -> Result<Resp, ClientError> {
+        if self.soc.soc_mbox().status().read().status().data_ready() {
+            let dlen = self.soc.soc_mbox().dlen().read();
+            if (dlen as usize) < Resp::MIN_SIZE || dlen as usize > size_of::<Resp>() {
+                self.soc.soc_mbox().execute().write(|w| w.execute(false));
+                return Err(CaliptraApiError::MailboxUnexpectedResponseLen {
+                    expected_min: Resp::MIN_SIZE as u32,
+                    expected_max: size_of::<Resp>() as u32,
+                    actual: dlen,
+                }
+                .into());
+            }
+        }
+
+        let mut resp = Resp::new_zeroed();
+        let Some(data) = self.soc.finish_mailbox_exec(resp.as_mut_bytes())? else {
+            return Err(CaliptraApiError::MailboxNoResponseData.into());
+        };
+        verify_response(data)?;
+        Ok(resp)
+    }
+
+    fn finish_fw_load(&mut self, _ctx: u32) -> Result<(), ClientError> {
+        if self.soc.soc_mbox().status().read().status().data_ready() {
+            self.soc.soc_mbox().execute().write(|w| w.execute(false));
+            return Err(CaliptraApiError::UploadFirmwareUnexpectedResponse.into());
+        }
+        self.soc.finish_mailbox_exec(&mut [])?;
+        Ok(())
+    }
+}
@@ -0,0 +1,261 @@
+// Licensed under the Apache-2.0 license
+
+use caliptra_api::{
+    mailbox::{CommandId, MailboxReqHeader, MailboxRespHeader, Request, Response, ResponseVarSize},
+    CaliptraApiError, Capabilities, SocManager,
+};
+use caliptra_api_client::{CaliptraClient, ClientError};
+use caliptra_builder::{
+    firmware::{self, APP_WITH_UART, FMC_WITH_UART},
+    ImageOptions,
+};
+use caliptra_hw_model::{BootParams, HwModel, InitParams, ModelEmulated};
+use dpe::{commands::Command, response::Response as DpeResponse, DPE_PROFILE};
+use std::{
+    future::Future,
+    pin::pin,
+    task::{Context, Poll, RawWaker, RawWakerVTable, Waker},
+};
+use zerocopy::{FromBytes, Immutable, IntoBytes, KnownLayout};
+
+const GENERATE_IDEVID_CSR: u32 = 1;
+
+fn boot(rom: &[u8], initial_dbg_manuf_service_reg: u32) -> CaliptraClient<ModelEmulated> {
+    let model = ModelEmulated::new(
+        InitParams {
+            rom,
+            ..Default::default()
+        },
+        BootParams {
+            initial_dbg_manuf_service_reg,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    CaliptraClient::new(model)
+}
+
+fn wait_for_fw_ready(client: &mut CaliptraClient<ModelEmulated>) {
+    client
+        .soc_manager()
+        .step_until(|m| m.soc_ifc().cptra_flow_status().read().ready_for_fw());
+}
+
+/// Polls `fut` to completion; MailboxOp never needs a real waker.
+fn block_on<F: Future>(fut: F) -> F::Output {
+    fn noop_raw_waker() -> RawWaker {
+        fn clone(_: *const ()) -> RawWaker {
+            noop_raw_waker()
+        }
+        fn noop(_: *const ()) {}
+        static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, noop, noop, noop);
+        RawWaker::new(std::ptr::null(), &VTABLE)
+    }
+    let waker = unsafe { Waker::from_raw(noop_raw_waker()) };
+    let mut cx = Context::from_waker(&waker);
+    let mut fut = pin!(fut);
+    loop {
+        if let Poll::Ready(output) = fut.as_mut().poll(&mut cx) {
+            return output;
+        }
+    }
+}
+
+#[test]
+fn test_chunked_firmware_upload_and_runtime_commands() {
+    let rom = caliptra_builder::rom_for_fw_integration_tests().unwrap();
+    let image = caliptra_builder::build_and_sign_image(
+        &FMC_WITH_UART,
+        &APP_WITH_UART,
+        ImageOptions::default(),
+    )
+    .unwrap()
+    .to_bytes()
+    .unwrap();
+
+    let mut client = boot(&rom, 0);
+    wait_for_fw_ready(&mut client);
+
+    // Use an odd chunk size so words straddle chunk boundaries.
+    let mut upload = client.start_firmware_upload(image.len()).unwrap();
+    for chunk in image.chunks(1001) {
+        upload.write(chunk).unwrap();
+    }
+    upload.finish().unwrap().wait().unwrap();
+    wait_for_fw_ready(&mut client);
+
+    let fw_info = client.fw_info().unwrap().wait().unwrap();
+    assert_eq!(fw_info.attestation_disabled, 0);
+
+    let caps = block_on(client.capabilities().unwrap()).unwrap();
+    let caps = Capabilities::try_from(&caps.capabilities[..]).unwrap();
+    assert!(caps.contains(Capabilities::RT_BASE));
+
+    let ldev_cert = client.get_ldev_cert().unwrap().wait().unwrap();
+    assert_eq!(ldev_cert.data().unwrap()[0], 0x30);
+
+    let nonce = [0xa5; 32];
+    let quote = client.quote_pcrs(nonce).unwrap().wait().unwrap();
+    assert_eq!(quote.nonce, nonce);
+
+    let resp = block_on(client.invoke_dpe(&Command::GetProfile).unwrap()).unwrap();
+    let DpeResponse::GetProfile(profile) = resp else {
+        panic!("Wrong response type!");
+    };
+    assert_eq!(profile.resp_hdr.profile, DPE_PROFILE as u32);
+}
+
+#[test]
+fn test_retrieve_idev_csr() {
+    let rom = caliptra_builder::rom_for_fw_integration_tests().unwrap();
+    let mut client = boot(&rom, GENERATE_IDEVID_CSR);
+
+    let mut csr = [0u8; 1024];
+    assert_eq!(
+        client.retrieve_idev_csr(&mut csr),
+        Err(ClientError::IdevCsrNotReady)
+    );
+
+    client
+        .soc_manager()
+        .step_until(|m| m.soc_ifc().cptra_flow_status().read().idevid_csr_ready());
+    let csr = client.retrieve_idev_csr(&mut csr).unwrap();
+    // DER SEQUENCE
+    assert_eq!(csr[0], 0x30);
+
+    // The ROM continues booting once the CSR has been consumed.
+    wait_for_fw_ready(&mut client);
+}
+
+#[test]
+fn test_firmware_upload_length_mismatch() {
+    let rom =
+        caliptra_builder::build_firmware_rom(&firmware::hw_model_tests::MAILBOX_RESPONDER).unwrap();
+    let mut client = boot(&rom, 0);
+
+    let mut upload = client.start_firmware_upload(8).unwrap();
+    upload.write(&[0; 5]).unwrap();
+    assert_eq!(
+        upload.write(&[0; 4]),
+        Err(ClientError::MailboxWriteOverflow { dlen: 8 })
+    );
+    assert_eq!(
+        upload.finish().err(),
+        Some(ClientError::MailboxWriteIncomplete {
+            dlen: 8,
+            written: 5
+        })
+    );
+
+    // The incomplete upload released the mailbox, as does a dropped one.
+    let mut upload = client.start_firmware_upload(8).unwrap();
+    upload.write(&[0; 3]).unwrap();
+    drop(upload);
+    assert_eq!(
+        client
+            .soc_manager()
+            .mailbox_execute(0x1000_1000, &[])
+            .unwrap(),
+        Some(vec![0x01, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd])
+    );
+}
+
+#[test]
+fn test_response_validation() {
+    const SET_RESPONSE_CMD: u32 = 0x3000_0000;
+    const GET_RESPONSE_CMD: u32 = 0x3000_0001;
+
+    #[repr(C)]
+    #[derive(IntoBytes, FromBytes, Immutable, KnownLayout, Default)]
+    struct TestReq {
+        hdr: MailboxReqHeader,
+        data: [u8; 4],
+    }
+    impl Request for TestReq {
+        const ID: CommandId = CommandId(GET_RESPONSE_CMD);
+        type Resp = TestResp;
+    }
+    #[repr(C)]
+    #[derive(IntoBytes, FromBytes, Immutable, KnownLayout, Debug, PartialEq, Eq)]
+    struct TestResp {
+        hdr: MailboxRespHeader,
+        data: [u8; 4],
+    }
+    impl Response for TestResp {}
+
+    fn exec(
+        client: &mut CaliptraClient<ModelEmulated>,
+        resp: &[u8],
+    ) -> Result<TestResp, ClientError> {
+        client
+            .soc_manager()
+            .mailbox_execute(SET_RESPONSE_CMD, resp)
+            .unwrap();
+        client
+            .exec_req(TestReq {
+                data: *b"Hi!!",
+                ..Default::default()
+            })?
+            .wait()
+    }
+
+    let rom =
+        caliptra_builder::build_firmware_rom(&firmware::hw_model_tests::MAILBOX_RESPONDER).unwrap();
+    let mut client = boot(&rom, 0);
+
+    assert_eq!(
+        exec(
+            &mut client,
+            &[0x2d, 0xff, 0xff, 0xff, 0x00, 0x00, 0x00, 0x00, b'H', b'I', b'!', b'!'],
+        ),
+        Ok(TestResp {
+            hdr: MailboxRespHeader {
+                chksum: 0xffffff2d,
+                fips_status: 0
+            },
+            data: *b"HI!!",
+        })
+    );
+    // The firmware echoes the request it received, including the checksum.
+    client
+        .soc_manager()
+        .step_until_output_and_take("|dcfeffff48692121|")
+        .unwrap();
+
+    assert_eq!(
+        exec(
+            &mut client,
+            &[0x2d, 0xff, 0xff, 0xff, 0x00, 0x00, 0x00, 0x00, b'H', b'I', b'!'],
+        ),
+        Err(ClientError::Api(
+            CaliptraApiError::MailboxUnexpectedResponseLen {
+                expected_min: 12,
+                expected_max: 12,
+                actual: 11
+            }
+        ))
+    );
+
+    assert_eq!(
+        exec(
+            &mut client,
+            &[0x2e, 0xff, 0xff, 0xff, 0x00, 0x00, 0x00, 0x00, b'H', b'I', b'!', b'!'],
+        ),
+        Err(ClientError::Api(
+            CaliptraApiError::MailboxRespInvalidChecksum {
+                expected: 0xffffff2e,
+                actual: 0xffffff2d
+            }
+        ))
+    );
+
+    assert_eq!(
+        exec(
+            &mut client,
+            &[0x0c, 0xff, 0xff, 0xff, 0x01, 0x20, 0x00, 0x00, b'H', b'I', b'!', b'!'],
+        ),
+        Err(ClientError::Api(
+            CaliptraApiError::MailboxRespInvalidFipsStatus(0x2001)
+        ))
+    );
+}
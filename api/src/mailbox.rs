@@ -54,6 +54,12 @@ impl CommandId {
 
     // The get IDevID CSR command.
     pub const GET_IDEV_CSR: Self = Self(0x4944_4352); // "IDCR"
+
+    // The get certificate chain command.
+    pub const GET_CERT_CHAIN: Self = Self(0x4348_4E43); // "CHNC"
+
+    // The get FMC Alias CSR command.
+    pub const GET_FMC_ALIAS_CSR: Self = Self(0x464D_4352); // "FMCR"
 }
 
 impl From<u32> for CommandId {
@@ -153,6 +159,8 @@ pub enum MailboxResp {
     CertifyKeyExtended(CertifyKeyExtendedResp),
     AuthorizeAndStash(AuthorizeAndStashResp),
     GetIdevCsr(GetIdevCsrResp),
+    GetCertChain(GetCertChainResp),
+    GetFmcAliasCsr(GetFmcAliasCsrResp),
 }
 
 impl MailboxResp {
@@ -174,6 +182,8 @@ impl MailboxResp {
             MailboxResp::CertifyKeyExtended(resp) => Ok(resp.as_bytes()),
             MailboxResp::AuthorizeAndStash(resp) => Ok(resp.as_bytes()),
             MailboxResp::GetIdevCsr(resp) => Ok(resp.as_bytes()),
+            MailboxResp::GetCertChain(resp) => resp.as_bytes_partial(),
+            MailboxResp::GetFmcAliasCsr(resp) => Ok(resp.as_bytes()),
         }
     }
 
@@ -195,6 +205,8 @@ impl MailboxResp {
             MailboxResp::CertifyKeyExtended(resp) => Ok(resp.as_mut_bytes()),
             MailboxResp::AuthorizeAndStash(resp) => Ok(resp.as_mut_bytes()),
             MailboxResp::GetIdevCsr(resp) => Ok(resp.as_mut_bytes()),
+            MailboxResp::GetCertChain(resp) => resp.as_bytes_partial_mut(),
+            MailboxResp::GetFmcAliasCsr(resp) => Ok(resp.as_mut_bytes()),
         }
     }
 
@@ -253,6 +265,7 @@ pub enum MailboxReq {
     CertifyKeyExtended(CertifyKeyExtendedReq),
     SetAuthManifest(SetAuthManifestReq),
     AuthorizeAndStash(AuthorizeAndStashReq),
+    GetCertChain(GetCertChainReq),
 }
 
 impl MailboxReq {
@@ -278,6 +291,7 @@ impl MailboxReq {
             MailboxReq::CertifyKeyExtended(req) => Ok(req.as_bytes()),
             MailboxReq::SetAuthManifest(req) => Ok(req.as_bytes()),
             MailboxReq::AuthorizeAndStash(req) => Ok(req.as_bytes()),
+            MailboxReq::GetCertChain(req) => Ok(req.as_bytes()),
         }
     }
 
@@ -303,6 +317,7 @@ impl MailboxReq {
             MailboxReq::CertifyKeyExtended(req) => Ok(req.as_mut_bytes()),
             MailboxReq::SetAuthManifest(req) => Ok(req.as_mut_bytes()),
             MailboxReq::AuthorizeAndStash(req) => Ok(req.as_mut_bytes()),
+            MailboxReq::GetCertChain(req) => Ok(req.as_mut_bytes()),
         }
     }
 
@@ -328,6 +343,7 @@ impl MailboxReq {
             MailboxReq::CertifyKeyExtended(_) => CommandId::CERTIFY_KEY_EXTENDED,
             MailboxReq::SetAuthManifest(_) => CommandId::SET_AUTH_MANIFEST,
             MailboxReq::AuthorizeAndStash(_) => CommandId::AUTHORIZE_AND_STASH,
+            MailboxReq::GetCertChain(_) => CommandId::GET_CERT_CHAIN,
         }
     }
 
@@ -451,6 +467,7 @@ pub struct GetIdevInfoResp {
     pub idev_pub_x: [u8; 48],
     pub idev_pub_y: [u8; 48],
 }
+impl Response for GetIdevInfoResp {}
 
 // GET_LDEV_CERT
 #[repr(C)]
@@ -782,6 +799,7 @@ pub struct FwInfoResp {
     pub runtime_sha384_digest: [u32; 12],
     pub owner_pub_key_hash: [u32; 12],
 }
+impl Response for FwInfoResp {}
 
 // CAPABILITIES
 // No command-specific input args
@@ -893,6 +911,7 @@ pub struct GetTaggedTciResp {
     pub tci_cumulative: [u8; 48],
     pub tci_current: [u8; 48],
 }
+impl Response for GetTaggedTciResp {}
 
 // INCREMENT_PCR_RESET_COUNTER request
 // No command specific output
@@ -1010,6 +1029,40 @@ impl Default for GetIdevCsrResp {
     }
 }
 
+// GET_FMC_ALIAS_CSR
+#[repr(C)]
+#[derive(Default, Debug, IntoBytes, FromBytes, KnownLayout, Immutable, PartialEq, Eq)]
+pub struct GetFmcAliasCsrReq {
+    pub hdr: MailboxReqHeader,
+}
+
+impl Request for GetFmcAliasCsrReq {
+    const ID: CommandId = CommandId::GET_FMC_ALIAS_CSR;
+    type Resp = GetFmcAliasCsrResp;
+}
+
+#[repr(C)]
+#[derive(Debug, IntoBytes, FromBytes, KnownLayout, Immutable, PartialEq, Eq)]
+pub struct GetFmcAliasCsrResp {
+    pub hdr: MailboxRespHeader,
+    pub data_size: u32,
+    pub data: [u8; Self::DATA_MAX_SIZE],
+}
+impl GetFmcAliasCsrResp {
+    pub const DATA_MAX_SIZE: usize = 512;
+}
+impl ResponseVarSize for GetFmcAliasCsrResp {}
+
+impl Default for GetFmcAliasCsrResp {
+    fn default() -> Self {
+        Self {
+            hdr: MailboxRespHeader::default(),
+            data_size: 0,
+            data: [0u8; Self::DATA_MAX_SIZE],
+        }
+    }
+}
+
 #[repr(u32)]
 #[derive(Debug, PartialEq, Eq)]
 pub enum ImageHashSource {
@@ -1085,6 +1138,64 @@ pub struct AuthorizeAndStashResp {
 }
 impl Response for AuthorizeAndStashResp {}
 
+// GET_CERT_CHAIN
+#[repr(C)]
+#[derive(Debug, IntoBytes, FromBytes, Immutable, KnownLayout, PartialEq, Eq)]
+pub struct GetCertChainReq {
+    pub hdr: MailboxReqHeader,
+    pub offset: u32,
+    pub size: u32,
+    pub flags: GetCertChainFlags,
+    pub label: [u8; 48],
+}
+impl Default for GetCertChainReq {
+    fn default() -> Self {
+        Self {
+            hdr: MailboxReqHeader::default(),
+            offset: 0,
+            size: GetCertChainResp::DATA_MAX_SIZE as u32,
+            flags: GetCertChainFlags::empty(),
+            label: [0u8; 48],
+        }
+    }
+}
+impl Request for GetCertChainReq {
+    const ID: CommandId = CommandId::GET_CERT_CHAIN;
+    type Resp = GetCertChainResp;
+}
+
+#[repr(C)]
+#[derive(Debug, PartialEq, Eq, FromBytes, Immutable, KnownLayout, IntoBytes)]
+pub struct GetCertChainFlags(pub u32);
+
+bitflags! {
+    impl GetCertChainFlags: u32 {
+        const INCLUDE_DPE_LEAF = 1u32 << 31;
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, IntoBytes, FromBytes, Immutable, KnownLayout, PartialEq, Eq)]
+pub struct GetCertChainResp {
+    pub hdr: MailboxRespHeader,
+    pub data_size: u32,
+    pub data: [u8; GetCertChainResp::DATA_MAX_SIZE], // variable length
+}
+impl GetCertChainResp {
+    pub const DATA_MAX_SIZE: usize = 2048;
+}
+impl ResponseVarSize for GetCertChainResp {}
+
+impl Default for GetCertChainResp {
+    fn default() -> Self {
+        Self {
+            hdr: MailboxRespHeader::default(),
+            data_size: 0,
+            data: [0u8; GetCertChainResp::DATA_MAX_SIZE],
+        }
+    }
+}
+
 /// Retrieves dlen bytes  from the mailbox.
 pub fn mbox_read_response(
     mbox: mbox::RegisterBlock<impl MmioMut>,
@@ -0,0 +1,112 @@
+// Licensed under the Apache-2.0 license
+
+#[cfg(not(feature = "struct-aware"))]
+use std::mem::size_of;
+
+use caliptra_auth_man_types::*;
+use caliptra_auth_man_verify::*;
+use caliptra_drivers::*;
+use caliptra_image_types::*;
+#[cfg(not(feature = "struct-aware"))]
+use zerocopy::FromBytes;
+use zerocopy::IntoBytes;
+
+/*
+ * NOTE: Signature checks always pass, so the fuzzer can reach the image
+ * metadata collection without forging signatures.
+ */
+struct TestEnv {
+    digest: ImageDigest,
+    verify_result: bool,
+    verify_lms_result: bool,
+    lms_verify_enabled: bool,
+}
+
+impl TestEnv {
+    fn new(lms_verify_enabled: bool) -> Self {
+        TestEnv {
+            digest: ImageDigest::default(),
+            verify_result: true,
+            verify_lms_result: true,
+            lms_verify_enabled,
+        }
+    }
+}
+
+impl AuthManifestVerificationEnv for TestEnv {
+    fn sha384_digest(&mut self, _data: &[u8]) -> CaliptraResult<ImageDigest> {
+        Ok(self.digest)
+    }
+
+    fn ecc384_verify(
+        &mut self,
+        _digest: &ImageDigest,
+        _pub_key: &ImageEccPubKey,
+        sig: &ImageEccSignature,
+    ) -> CaliptraResult<Array4xN<12, 48>> {
+        if self.verify_result {
+            Ok(Array4x12::from(sig.r))
+        } else {
+            Ok(Array4x12::from(&[0xFF; 48]))
+        }
+    }
+
+    fn lms_verify(
+        &mut self,
+        _digest: &ImageDigest,
+        pub_key: &ImageLmsPublicKey,
+        _sig: &ImageLmsSignature,
+    ) -> CaliptraResult<HashValue<SHA192_DIGEST_WORD_SIZE>> {
+        if self.verify_lms_result {
+            Ok(HashValue::from(pub_key.digest))
+        } else {
+            Ok(HashValue::from(&[0xDEADBEEF; 6]))
+        }
+    }
+
+    fn lms_verify_enabled(&self) -> bool {
+        self.lms_verify_enabled
+    }
+}
+
+fn harness(lms_verify_enabled: bool, preamble: &AuthManifestPreamble, metadata_col: &[u8]) {
+    let fw_preamble = ImagePreamble::default();
+    let mut verifier = AuthManifestVerifier::new(TestEnv::new(lms_verify_enabled));
+
+    if verifier.verify_preamble(preamble, &fw_preamble).is_err() {
+        return;
+    }
+    let Ok(buf) = verifier.verify_image_metadata_col(preamble, metadata_col) else {
+        return;
+    };
+
+    // Mirror the runtime: sort the verified entries in place.
+    let mut col = AuthManifestImageMetadataCollection::default();
+    col.as_mut_bytes()[..buf.len()].copy_from_slice(buf);
+    let _result =
+        sort_and_check_duplicate_fwid(&mut col.image_metadata_list[..col.entry_count as usize]);
+}
+
+#[cfg(feature = "struct-aware")]
+pub fn harness_structured(lms_verify_enabled: bool, manifest: AuthorizationManifest) {
+    harness(
+        lms_verify_enabled,
+        &manifest.preamble,
+        manifest.image_metadata_col.as_bytes(),
+    );
+}
+
+#[cfg(not(feature = "struct-aware"))]
+pub fn harness_unstructured(lms_verify_enabled: bool, data: &[u8]) {
+    // The null-case is too hard to fuzz (better statically)
+    if data.len() < size_of::<AuthManifestPreamble>() {
+        return;
+    }
+
+    // Copy the preamble out, as the fuzzer input is not aligned
+    let Ok((preamble, metadata_col)) = AuthManifestPreamble::read_from_prefix(data) else {
+        return;
+    };
+
+    harness(lms_verify_enabled, &preamble, metadata_col);
+}
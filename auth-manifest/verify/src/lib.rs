@@ -0,0 +1,46 @@
+/*++
+
+Licensed under the Apache-2.0 license.
+
+File Name:
+
+   lib.rs
+
+Abstract:
+
+    Caliptra Authorization Manifest Verification library.
+
+--*/
+#![cfg_attr(not(feature = "std"), no_std)]
+
+mod verifier;
+
+use caliptra_drivers::*;
+use caliptra_image_types::*;
+
+pub use verifier::{sort_and_check_duplicate_fwid, AuthManifestVerifier};
+
+/// Authorization Manifest Verification Environment
+pub trait AuthManifestVerificationEnv {
+    /// Calculate SHA-384 Digest
+    fn sha384_digest(&mut self, data: &[u8]) -> CaliptraResult<ImageDigest>;
+
+    /// Perform ECC-384 Verification
+    fn ecc384_verify(
+        &mut self,
+        digest: &ImageDigest,
+        pub_key: &ImageEccPubKey,
+        sig: &ImageEccSignature,
+    ) -> CaliptraResult<Array4xN<12, 48>>;
+
+    /// Perform LMS Verification
+    fn lms_verify(
+        &mut self,
+        digest: &ImageDigest,
+        pub_key: &ImageLmsPublicKey,
+        sig: &ImageLmsSignature,
+    ) -> CaliptraResult<HashValue<SHA192_DIGEST_WORD_SIZE>>;
+
+    // LMS Verification enabled
+    fn lms_verify_enabled(&self) -> bool;
+}
@@ -0,0 +1,381 @@
+/*++
+
+Licensed under the Apache-2.0 license.
+
+File Name:
+
+    verifier.rs
+
+Abstract:
+
+    This file is the main implementation of the Authorization Manifest Verifier.
+
+--*/
+
+use core::cmp::min;
+use core::mem::size_of;
+
+use crate::*;
+use caliptra_auth_man_types::{
+    AuthManifestFlags, AuthManifestImageMetadata, AuthManifestImageMetadataCollection,
+    AuthManifestPreamble, AUTH_MANIFEST_IMAGE_METADATA_MAX_COUNT,
+};
+use caliptra_cfi_lib::{
+    cfi_assert_eq_6_words, cfi_assert_eq_with, cfi_eq, cfi_launder, CfiEqOptions,
+};
+use zerocopy::IntoBytes;
+
+/// Authorization Manifest Verifier
+pub struct AuthManifestVerifier<Env: AuthManifestVerificationEnv> {
+    /// Verification Environment
+    env: Env,
+}
+
+impl<Env: AuthManifestVerificationEnv> AuthManifestVerifier<Env> {
+    /// Create a new instance `AuthManifestVerifier`
+    ///
+    /// # Arguments
+    ///
+    /// * `env` - Environment
+    pub fn new(env: Env) -> Self {
+        Self { env }
+    }
+
+    /// Verify the manifest preamble against the firmware image preamble
+    ///
+    /// The vendor signed data must be signed by the active vendor keys of the
+    /// firmware image and the owner public keys by the firmware owner keys.
+    ///
+    /// # Arguments
+    ///
+    /// * `preamble` - Authorization manifest preamble
+    /// * `fw_preamble` - Preamble of the running firmware image
+    pub fn verify_preamble(
+        &mut self,
+        preamble: &AuthManifestPreamble,
+        fw_preamble: &ImagePreamble,
+    ) -> CaliptraResult<()> {
+        self.verify_vendor_signed_data(preamble, fw_preamble)?;
+        self.verify_owner_pub_keys(preamble, fw_preamble)
+    }
+
+    /// Verify an image metadata collection against the manifest preamble
+    ///
+    /// # Arguments
+    ///
+    /// * `preamble` - Authorization manifest preamble, already verified
+    /// * `buf` - Image metadata collection following the preamble
+    ///
+    /// # Returns
+    ///
+    /// * `&[u8]` - The part of `buf` covered by the signatures, which holds
+    ///   the entry count and at least `entry_count` entries
+    pub fn verify_image_metadata_col<'a>(
+        &mut self,
+        preamble: &AuthManifestPreamble,
+        buf: &'a [u8],
+    ) -> CaliptraResult<&'a [u8]> {
+        let err = CaliptraError::RUNTIME_AUTH_MANIFEST_IMAGE_METADATA_LIST_INVALID_SIZE;
+
+        // Resize the buffer to the metadata size.
+        let metadata_size = min(buf.len(), size_of::<AuthManifestImageMetadataCollection>());
+        let buf = buf.get(..metadata_size).ok_or(err)?;
+
+        let entry_count = buf
+            .get(..size_of::<u32>())
+            .and_then(|b| b.try_into().ok())
+            .map(u32::from_le_bytes)
+            .ok_or(err)?;
+
+        if entry_count == 0 || entry_count > AUTH_MANIFEST_IMAGE_METADATA_MAX_COUNT as u32 {
+            Err(CaliptraError::RUNTIME_AUTH_MANIFEST_IMAGE_METADATA_LIST_INVALID_ENTRY_COUNT)?;
+        }
+
+        // Check if the buffer contains the entry count and all the image metadata entries specified by the entry count.
+        if buf.len()
+            < (size_of::<u32>() + entry_count as usize * size_of::<AuthManifestImageMetadata>())
+        {
+            Err(err)?;
+        }
+
+        // Calculate the digest of the image metadata collection.
+        let digest_metadata_col = self.env.sha384_digest(buf)?;
+
+        self.verify_vendor_image_metadata_col(preamble, &digest_metadata_col)?;
+        self.verify_owner_image_metadata_col(preamble, &digest_metadata_col)?;
+
+        Ok(buf)
+    }
+
+    /// Calculate the digest of `range` within `preamble`
+    fn preamble_digest(
+        &mut self,
+        preamble: &AuthManifestPreamble,
+        range: core::ops::Range<u32>,
+    ) -> CaliptraResult<ImageDigest> {
+        let data = preamble
+            .as_bytes()
+            .get(range.start as usize..range.end as usize)
+            .ok_or(CaliptraError::IMAGE_VERIFIER_ERR_DIGEST_OUT_OF_BOUNDS)?;
+        self.env.sha384_digest(data)
+    }
+
+    /// Verify an ECC-384 signature, reporting any failure as `err`
+    fn verify_ecc_sig(
+        &mut self,
+        digest: &ImageDigest,
+        pub_key: &ImageEccPubKey,
+        sig: &ImageEccSignature,
+        err: CaliptraError,
+    ) -> CaliptraResult<()> {
+        let verify_r = self
+            .env
+            .ecc384_verify(digest, pub_key, sig)
+            .map_err(|_| err)?;
+        let sig_r = caliptra_drivers::Array4xN(sig.r);
+        if !cfi_eq(&verify_r, &sig_r) {
+            Err(err)?;
+        } else {
+            cfi_assert_eq_with(&verify_r, &sig_r, CfiEqOptions::HARDENED);
+        }
+        Ok(())
+    }
+
+    /// Verify an LMS signature if LMS verification is enabled, reporting any
+    /// failure as `err`
+    fn verify_lms_sig(
+        &mut self,
+        digest: &ImageDigest,
+        pub_key: &ImageLmsPublicKey,
+        sig: &ImageLmsSignature,
+        err: CaliptraError,
+    ) -> CaliptraResult<()> {
+        if cfi_launder(self.env.lms_verify_enabled()) {
+            let candidate_key = self.env.lms_verify(digest, pub_key, sig).map_err(|_| err)?;
+            let pub_key_digest = HashValue::from(pub_key.digest);
+            if candidate_key != pub_key_digest {
+                Err(err)?;
+            } else {
+                cfi_assert_eq_6_words(&candidate_key.0, &pub_key_digest.0);
+            }
+        }
+        Ok(())
+    }
+
+    fn verify_vendor_signed_data(
+        &mut self,
+        preamble: &AuthManifestPreamble,
+        fw_preamble: &ImagePreamble,
+    ) -> CaliptraResult<()> {
+        let digest_vendor =
+            self.preamble_digest(preamble, AuthManifestPreamble::vendor_signed_data_range())?;
+
+        // Verify the vendor ECC signature.
+        let err = CaliptraError::RUNTIME_AUTH_MANIFEST_VENDOR_ECC_SIGNATURE_INVALID;
+        let vendor_fw_ecc_key = fw_preamble
+            .vendor_pub_keys
+            .ecc_pub_keys
+            .get(fw_preamble.vendor_ecc_pub_key_idx as usize)
+            .ok_or(err)?;
+        self.verify_ecc_sig(
+            &digest_vendor,
+            vendor_fw_ecc_key,
+            &preamble.vendor_pub_keys_signatures.ecc_sig,
+            err,
+        )?;
+
+        // Verify vendor LMS signature.
+        let err = CaliptraError::RUNTIME_AUTH_MANIFEST_VENDOR_LMS_SIGNATURE_INVALID;
+        if cfi_launder(self.env.lms_verify_enabled()) {
+            let vendor_fw_lms_key = fw_preamble
+                .vendor_pub_keys
+                .lms_pub_keys
+                .get(fw_preamble.vendor_lms_pub_key_idx as usize)
+                .ok_or(err)?;
+            self.verify_lms_sig(
+                &digest_vendor,
+                vendor_fw_lms_key,
+                &preamble.vendor_pub_keys_signatures.lms_sig,
+                err,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn verify_owner_pub_keys(
+        &mut self,
+        preamble: &AuthManifestPreamble,
+        fw_preamble: &ImagePreamble,
+    ) -> CaliptraResult<()> {
+        let digest_owner =
+            self.preamble_digest(preamble, AuthManifestPreamble::owner_pub_keys_range())?;
+
+        // Verify the owner ECC signature.
+        self.verify_ecc_sig(
+            &digest_owner,
+            &fw_preamble.owner_pub_keys.ecc_pub_key,
+            &preamble.owner_pub_keys_signatures.ecc_sig,
+            CaliptraError::RUNTIME_AUTH_MANIFEST_OWNER_ECC_SIGNATURE_INVALID,
+        )?;
+
+        // Verify owner LMS signature.
+        self.verify_lms_sig(
+            &digest_owner,
+            &fw_preamble.owner_pub_keys.lms_pub_key,
+            &preamble.owner_pub_keys_signatures.lms_sig,
+            CaliptraError::RUNTIME_AUTH_MANIFEST_OWNER_LMS_SIGNATURE_INVALID,
+        )
+    }
+
+    fn verify_vendor_image_metadata_col(
+        &mut self,
+        preamble: &AuthManifestPreamble,
+        image_metadata_col_digest: &ImageDigest,
+    ) -> CaliptraResult<()> {
+        let flags = AuthManifestFlags::from(preamble.flags);
+        if !flags.contains(AuthManifestFlags::VENDOR_SIGNATURE_REQUIRED) {
+            return Ok(());
+        }
+
+        // Verify the vendor ECC signature over the image metadata collection.
+        self.verify_ecc_sig(
+            image_metadata_col_digest,
+            &preamble.vendor_pub_keys.ecc_pub_key,
+            &preamble.vendor_image_metdata_signatures.ecc_sig,
+            CaliptraError::RUNTIME_AUTH_MANIFEST_VENDOR_ECC_SIGNATURE_INVALID,
+        )?;
+
+        // Verify vendor LMS signature over the image metadata collection.
+        self.verify_lms_sig(
+            image_metadata_col_digest,
+            &preamble.vendor_pub_keys.lms_pub_key,
+            &preamble.vendor_image_metdata_signatures.lms_sig,
+            CaliptraError::RUNTIME_AUTH_MANIFEST_VENDOR_LMS_SIGNATURE_INVALID,
+        )
+    }
+
+    fn verify_owner_image_metadata_col(
+        &mut self,
+        preamble: &AuthManifestPreamble,
+        image_metadata_col_digest: &ImageDigest,
+    ) -> CaliptraResult<()> {
+        // Verify the owner ECC signature.
+        self.verify_ecc_sig(
+            image_metadata_col_digest,
+            &preamble.owner_pub_keys.ecc_pub_key,
+            &preamble.owner_image_metdata_signatures.ecc_sig,
+            CaliptraError::RUNTIME_AUTH_MANIFEST_OWNER_ECC_SIGNATURE_INVALID,
+        )?;
+
+        // Verify owner LMS signature.
+        self.verify_lms_sig(
+            image_metadata_col_digest,
+            &preamble.owner_pub_keys.lms_pub_key,
+            &preamble.owner_image_metdata_signatures.lms_sig,
+            CaliptraError::RUNTIME_AUTH_MANIFEST_OWNER_LMS_SIGNATURE_INVALID,
+        )
+    }
+}
+
+/// Sort the image metadata list by firmware ID in place
+///
+/// Fails if two entries share a firmware ID. The list may be partially
+/// sorted when that happens.
+pub fn sort_and_check_duplicate_fwid(
+    slice: &mut [AuthManifestImageMetadata],
+) -> CaliptraResult<()> {
+    for i in 1..slice.len() {
+        let mut j = i;
+        while j > 0 {
+            if j >= slice.len() {
+                break;
+            }
+
+            match slice[j - 1].fw_id.cmp(&slice[j].fw_id) {
+                core::cmp::Ordering::Greater => {
+                    slice.swap(j - 1, j);
+                    j -= 1;
+                }
+                core::cmp::Ordering::Equal => {
+                    Err(CaliptraError::RUNTIME_AUTH_MANIFEST_IMAGE_METADATA_LIST_DUPLICATE_FIRMWARE_ID)?;
+                }
+                _ => {
+                    break;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn is_sorted(slice: &[AuthManifestImageMetadata]) -> bool {
+        for i in 0..slice.len() - 1 {
+            if slice[i].fw_id > slice[i + 1].fw_id {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    #[test]
+    fn test_sort_and_duplicate_empty() {
+        let resp = sort_and_check_duplicate_fwid(&mut []);
+        assert!(resp.is_ok());
+    }
+
+    #[test]
+    fn test_sort_and_duplicate_sort() {
+        let mut list = [
+            AuthManifestImageMetadata {
+                fw_id: 5,
+                flags: 0,
+                digest: [0u8; 48],
+            },
+            AuthManifestImageMetadata {
+                fw_id: 127,
+                flags: 0,
+                digest: [0u8; 48],
+            },
+            AuthManifestImageMetadata {
+                fw_id: 48,
+                flags: 0,
+                digest: [0u8; 48],
+            },
+        ];
+        let resp = sort_and_check_duplicate_fwid(&mut list);
+        assert!(resp.is_ok());
+        assert!(is_sorted(&list));
+    }
+
+    #[test]
+    fn test_sort_and_duplicate_dupe() {
+        let mut list = [
+            AuthManifestImageMetadata {
+                fw_id: 127,
+                flags: 0,
+                digest: [0u8; 48],
+            },
+            AuthManifestImageMetadata {
+                fw_id: 5,
+                flags: 0,
+                digest: [0u8; 48],
+            },
+            AuthManifestImageMetadata {
+                fw_id: 127,
+                flags: 0,
+                digest: [0u8; 48],
+            },
+        ];
+        let resp = sort_and_check_duplicate_fwid(&mut list);
+        assert_eq!(
+            resp.unwrap_err(),
+            CaliptraError::RUNTIME_AUTH_MANIFEST_IMAGE_METADATA_LIST_DUPLICATE_FIRMWARE_ID
+        );
+    }
+}
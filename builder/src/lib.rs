@@ -28,10 +28,12 @@ use zerocopy::IntoBytes;
 mod elf_symbols;
 pub mod firmware;
 mod sha256;
+mod stack_usage;
 pub mod version;
 
 pub use elf_symbols::{elf_symbols, Symbol, SymbolBind, SymbolType, SymbolVisibility};
 use once_cell::sync::Lazy;
+pub use stack_usage::{elf_functions, FunctionCode, StackFrame, StackPath, StackUsage};
 
 pub const THIS_WORKSPACE_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/..");
 
@@ -154,9 +156,26 @@ pub fn build_firmware_elf_uncached(workspace_dir: Option<&Path>, id: &FwId) -> i
 pub fn build_firmware_elfs_uncached<'a>(
     workspace_dir: Option<&Path>,
     fwids: &'a [&'a FwId<'a>],
+) -> io::Result<Vec<(&'a FwId<'a>, Vec<u8>)>> {
+    build_firmware_elfs_with_profile(workspace_dir, fwids, "firmware")
+}
+
+/// Builds firmware elf files with the `firmware-coverage` profile, combining
+/// targets like [`build_firmware_elfs_uncached`]. The code is the same as
+/// with the `firmware` profile, but the elfs also carry the line tables
+/// caliptra-coverage needs to map PCs back to source.
+pub fn build_firmware_coverage_elfs<'a>(
+    fwids: &'a [&'a FwId<'a>],
+) -> io::Result<Vec<(&'a FwId<'a>, Vec<u8>)>> {
+    build_firmware_elfs_with_profile(None, fwids, "firmware-coverage")
+}
+
+fn build_firmware_elfs_with_profile<'a>(
+    workspace_dir: Option<&Path>,
+    fwids: &'a [&'a FwId<'a>],
+    profile: &str,
 ) -> io::Result<Vec<(&'a FwId<'a>, Vec<u8>)>> {
     const TARGET: &str = "riscv32imc-unknown-none-elf";
-    const PROFILE: &str = "firmware";
 
     let cargo_invocations = cargo_invocations_from_fwids(fwids)?;
 
@@ -211,7 +230,7 @@ pub fn build_firmware_elfs_uncached<'a>(
             .arg(features_csv)
             .arg("--no-default-features")
             .arg("--profile")
-            .arg(PROFILE);
+            .arg(profile);
 
         cmd.arg("-p").arg(invocation.crate_name);
         for &fwid in invocation.fwids.iter() {
@@ -228,7 +247,7 @@ pub fn build_firmware_elfs_uncached<'a>(
         for &fwid in invocation.fwids.iter() {
             result_map.insert(
                 fwid,
-                fs::read(target_dir.join(TARGET).join(PROFILE).join(fwid.bin_name))?,
+                fs::read(target_dir.join(TARGET).join(profile).join(fwid.bin_name))?,
             );
         }
     }
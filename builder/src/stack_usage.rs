@@ -0,0 +1,840 @@
+// Licensed under the Apache-2.0 license
+
+//! Static worst-case stack depth analysis for RV32IMC firmware ELFs.
+//!
+//! Every function's code is decoded linearly to find how far it moves the
+//! stack pointer down and which functions it calls. The analysis is
+//! intentionally conservative:
+//!
+//! * Stack pointer increments (epilogues) are ignored, so code after an
+//!   early return is assumed to still have the full frame allocated. Tail
+//!   calls are therefore counted as if the caller's frame were still live.
+//! * Writing an absolute address to sp (`la sp, _sestack`) starts a new
+//!   stack, as done by the trap and NMI handlers.
+//!
+//! Calls through function pointers and non-constant stack adjustments can't
+//! be followed; they are reported alongside the result so the caller can
+//! decide whether the bound is trustworthy. Indirect jumps that are not
+//! calls (`jr a0`) are assumed to be jump tables within the same function.
+
+use std::collections::{BTreeSet, HashMap};
+use std::io;
+
+use elf::abi::{SHF_EXECINSTR, SHT_PROGBITS, STT_FUNC, STT_NOTYPE};
+use elf::endian::LittleEndian;
+
+use crate::other_err;
+
+const REG_ZERO: u32 = 0;
+const REG_RA: u32 = 1;
+const REG_SP: u32 = 2;
+const REG_GP: u32 = 3;
+
+/// A function's machine code, as found in the ELF
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FunctionCode<'a> {
+    pub name: String,
+    pub addr: u32,
+    pub code: &'a [u8],
+}
+
+#[derive(Debug)]
+struct CallSite {
+    target: u32,
+
+    // Stack depth of the caller when the call is made
+    depth: u32,
+}
+
+#[derive(Debug)]
+struct Function {
+    name: String,
+    start: u32,
+    end: u32,
+    frame_size: u32,
+    calls: Vec<CallSite>,
+    indirect_calls: Vec<u32>,
+    dynamic_stack: bool,
+}
+
+/// One function on the deepest call path
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StackFrame {
+    pub name: String,
+
+    /// Bytes of stack this function has allocated when it calls the next
+    /// function on the path, or its whole frame if it is the last function.
+    pub size: u32,
+}
+
+/// Worst-case stack usage from an entry point
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct StackPath {
+    /// Worst-case stack depth in bytes
+    pub depth: u32,
+
+    /// Functions on the deepest path, starting with the entry point
+    pub frames: Vec<StackFrame>,
+
+    /// Reachable functions that call through a function pointer. The callees
+    /// are not included in `depth`.
+    pub indirect_calls: Vec<String>,
+
+    /// Reachable functions that are part of a call cycle. Only one iteration
+    /// of the cycle is included in `depth`.
+    pub recursion: Vec<String>,
+
+    /// Reachable functions that adjust sp by a non-constant amount
+    pub dynamic_stack: Vec<String>,
+
+    /// Call targets that are not inside any known function
+    pub unknown_calls: Vec<u32>,
+}
+impl StackPath {
+    /// Returns true if `depth` is a true upper bound for the entry point.
+    pub fn is_bounded(&self) -> bool {
+        self.indirect_calls.is_empty()
+            && self.recursion.is_empty()
+            && self.dynamic_stack.is_empty()
+            && self.unknown_calls.is_empty()
+    }
+}
+
+/// Call graph and frame sizes of a firmware image
+pub struct StackUsage {
+    // Sorted by start address
+    functions: Vec<Function>,
+    by_name: HashMap<String, usize>,
+}
+impl StackUsage {
+    /// Analyze every function in the executable sections of an ELF file.
+    pub fn from_elf(elf_bytes: &[u8]) -> io::Result<Self> {
+        Ok(Self::from_functions(&elf_functions(elf_bytes)?))
+    }
+
+    pub fn from_functions(functions: &[FunctionCode]) -> Self {
+        let mut functions: Vec<Function> = functions.iter().map(analyze_function).collect();
+        functions.sort_by_key(|f| f.start);
+        let by_name = functions
+            .iter()
+            .enumerate()
+            .map(|(i, f)| (f.name.clone(), i))
+            .collect();
+        Self { functions, by_name }
+    }
+
+    /// Returns the frame size of a function, if it exists.
+    pub fn frame_size(&self, name: &str) -> Option<u32> {
+        self.by_name
+            .get(name)
+            .map(|&i| self.functions[i].frame_size)
+    }
+
+    fn function_at(&self, addr: u32) -> Option<usize> {
+        let idx = self.functions.partition_point(|f| f.start <= addr);
+        let idx = idx.checked_sub(1)?;
+        (addr < self.functions[idx].end).then_some(idx)
+    }
+
+    /// Find the deepest call path starting at the function named `entry`.
+    pub fn worst_path(&self, entry: &str) -> io::Result<StackPath> {
+        let Some(&entry_idx) = self.by_name.get(entry) else {
+            return Err(other_err(format!("Entry point {entry:?} not found")));
+        };
+        let mut walk = Walk {
+            usage: self,
+            state: vec![VisitState::Unvisited; self.functions.len()],
+            recursion: BTreeSet::new(),
+            unknown_calls: BTreeSet::new(),
+        };
+        let depth = walk.visit(entry_idx);
+
+        let mut result = StackPath {
+            depth,
+            ..Default::default()
+        };
+        let mut idx = Some(entry_idx);
+        while let Some(i) = idx {
+            let VisitState::Done(worst) = walk.state[i] else {
+                unreachable!();
+            };
+            let (size, next) = match worst.call {
+                Some((site_depth, callee)) => (site_depth, Some(callee)),
+                None => (self.functions[i].frame_size, None),
+            };
+            result.frames.push(StackFrame {
+                name: self.functions[i].name.clone(),
+                size,
+            });
+            idx = next;
+        }
+
+        for (i, state) in walk.state.iter().enumerate() {
+            if matches!(state, VisitState::Unvisited) {
+                continue;
+            }
+            let func = &self.functions[i];
+            if !func.indirect_calls.is_empty() {
+                result.indirect_calls.push(func.name.clone());
+            }
+            if func.dynamic_stack {
+                result.dynamic_stack.push(func.name.clone());
+            }
+        }
+        result.recursion = walk
+            .recursion
+            .into_iter()
+            .map(|i| self.functions[i].name.clone())
+            .collect();
+        result.unknown_calls = walk.unknown_calls.into_iter().collect();
+        Ok(result)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Worst {
+    depth: u32,
+
+    // (caller depth at the call site, callee) of the deepest call
+    call: Option<(u32, usize)>,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum VisitState {
+    Unvisited,
+    InProgress,
+    Done(Worst),
+}
+
+struct Walk<'a> {
+    usage: &'a StackUsage,
+    state: Vec<VisitState>,
+    recursion: BTreeSet<usize>,
+    unknown_calls: BTreeSet<u32>,
+}
+impl Walk<'_> {
+    fn visit(&mut self, idx: usize) -> u32 {
+        self.state[idx] = VisitState::InProgress;
+        let func = &self.usage.functions[idx];
+        let mut worst = Worst {
+            depth: func.frame_size,
+            call: None,
+        };
+        for site in func.calls.iter() {
+            let Some(callee) = self.usage.function_at(site.target) else {
+                self.unknown_calls.insert(site.target);
+                continue;
+            };
+            let callee_depth = match self.state[callee] {
+                VisitState::Unvisited => self.visit(callee),
+                VisitState::InProgress => {
+                    self.recursion.insert(callee);
+                    continue;
+                }
+                VisitState::Done(worst) => worst.depth,
+            };
+            let depth = site.depth + callee_depth;
+            if depth > worst.depth || worst.call.is_none() && depth == worst.depth {
+                worst = Worst {
+                    depth,
+                    call: Some((site.depth, callee)),
+                };
+            }
+        }
+        self.state[idx] = VisitState::Done(worst);
+        worst.depth
+    }
+}
+
+/// Returns the code of every function-like symbol in the executable sections.
+/// Assembly labels have no size, so they extend to the next symbol.
+pub fn elf_functions(elf_bytes: &[u8]) -> io::Result<Vec<FunctionCode<'_>>> {
+    let elf = elf::ElfBytes::<LittleEndian>::minimal_parse(elf_bytes).map_err(other_err)?;
+    let Some(sections) = elf.section_headers() else {
+        return Err(other_err("ELF file has no section headers"));
+    };
+    let Some((symbols, strings)) = elf.symbol_table().map_err(other_err)? else {
+        return Err(other_err("ELF file has no symbol table"));
+    };
+
+    let mut result = vec![];
+    for (shndx, section) in sections.iter().enumerate() {
+        if section.sh_type != SHT_PROGBITS || section.sh_flags & u64::from(SHF_EXECINSTR) == 0 {
+            continue;
+        }
+        let (data, _) = elf.section_data(&section).map_err(other_err)?;
+        let section_start = section.sh_addr;
+        let section_end = section.sh_addr + section.sh_size;
+
+        let mut syms = vec![];
+        for sym in symbols.iter() {
+            if usize::from(sym.st_shndx) != shndx
+                || !matches!(sym.st_symtype(), STT_FUNC | STT_NOTYPE)
+                || sym.st_value < section_start
+                || sym.st_value >= section_end
+            {
+                continue;
+            }
+            let name = strings.get(sym.st_name as usize).map_err(other_err)?;
+            // Skip mapping symbols ($x) and assembler local labels
+            if name.is_empty() || name.starts_with('$') || name.starts_with(".L") {
+                continue;
+            }
+            syms.push((
+                sym.st_value,
+                sym.st_size,
+                sym.st_symtype() == STT_FUNC,
+                name,
+            ));
+        }
+        // Prefer function symbols over labels at the same address
+        syms.sort_by_key(|&(addr, _, is_func, _)| (addr, !is_func));
+        syms.dedup_by_key(|&mut (addr, ..)| addr);
+
+        for (i, &(addr, size, _, name)) in syms.iter().enumerate() {
+            let next = syms.get(i + 1).map_or(section_end, |s| s.0);
+            let end = if size > 0 { addr + size } else { next };
+            let start_offset = (addr - section_start) as usize;
+            let end_offset = (end.min(section_end) - section_start) as usize;
+            result.push(FunctionCode {
+                name: format!("{:#}", rustc_demangle::demangle(name)),
+                addr: addr as u32,
+                code: &data[start_offset..end_offset],
+            });
+        }
+    }
+    Ok(result)
+}
+
+#[derive(Debug, Eq, PartialEq)]
+enum Op {
+    /// sp += n
+    SpAdjust(i32),
+
+    /// sp set to an absolute address
+    SpLoad,
+
+    /// sp adjusted by a value that isn't known statically
+    SpDynamic,
+
+    Call(u32),
+    IndirectCall,
+
+    /// Jump to a known address; a tail call if outside the function
+    Jump(u32),
+
+    Other,
+}
+
+/// Tracks registers holding values known at build time (from `lui`,
+/// `auipc`, `li` and `addi`) so `la`/`call` pairs and large frame sizes can
+/// be resolved.
+struct Decoder {
+    regs: [Option<u32>; 32],
+
+    // Address of the instruction right after a `lui sp` or `auipc sp`, the
+    // only place the `addi sp, sp, imm` completing `la sp, sym` can be
+    la_sp_next: Option<u32>,
+}
+impl Decoder {
+    fn new() -> Self {
+        let mut regs = [None; 32];
+        regs[REG_ZERO as usize] = Some(0);
+        Self {
+            regs,
+            la_sp_next: None,
+        }
+    }
+
+    fn get(&self, reg: u32) -> Option<u32> {
+        self.regs[reg as usize]
+    }
+
+    fn set(&mut self, reg: u32, val: Option<u32>) {
+        if reg != REG_ZERO {
+            self.regs[reg as usize] = val;
+        }
+    }
+
+    /// `addi rd, rs1, imm` and friends
+    fn addi(&mut self, pc: u32, rd: u32, rs1: u32, imm: i32) -> Op {
+        let val = self.get(rs1).map(|v| v.wrapping_add(imm as u32));
+        if rd != REG_SP {
+            self.set(rd, val);
+            return Op::Other;
+        }
+        if rs1 == REG_SP {
+            if self.la_sp_next == Some(pc) {
+                // Second half of `la sp, sym`
+                self.set(REG_SP, val);
+                return Op::Other;
+            }
+            self.set(REG_SP, None);
+            return Op::SpAdjust(imm);
+        }
+        self.set(REG_SP, None);
+        if rs1 == REG_GP || val.is_some() {
+            // gp-relative or constant address
+            return Op::SpLoad;
+        }
+        // Probably restoring sp from a frame pointer
+        Op::Other
+    }
+
+    /// `add rd, rd, rs2` or `sub rd, rd, rs2`
+    fn add_reg(&mut self, rd: u32, rs1: u32, rs2: u32, negate: bool) -> Op {
+        let rhs = self
+            .get(rs2)
+            .map(|v| if negate { v.wrapping_neg() } else { v });
+        if rd == REG_SP && rs1 == REG_SP {
+            return match rhs {
+                Some(v) => Op::SpAdjust(v as i32),
+                None => Op::SpDynamic,
+            };
+        }
+        let val = self.get(rs1).zip(rhs).map(|(a, b)| a.wrapping_add(b));
+        self.set(rd, val);
+        Op::Other
+    }
+
+    fn decode(&mut self, pc: u32, instr: u32, len: u32) -> Op {
+        if len == 2 {
+            self.decode_compressed(pc, instr)
+        } else {
+            self.decode_32(pc, instr)
+        }
+    }
+
+    fn decode_32(&mut self, pc: u32, instr: u32) -> Op {
+        let opcode = instr & 0x7f;
+        let rd = (instr >> 7) & 0x1f;
+        let funct3 = (instr >> 12) & 0x7;
+        let rs1 = (instr >> 15) & 0x1f;
+        let rs2 = (instr >> 20) & 0x1f;
+        let funct7 = instr >> 25;
+        let imm_i = (instr as i32) >> 20;
+        let imm_u = instr & 0xffff_f000;
+
+        match opcode {
+            // lui
+            0b011_0111 => {
+                self.set(rd, Some(imm_u));
+                if rd == REG_SP {
+                    self.la_sp_next = Some(pc.wrapping_add(4));
+                    return Op::SpLoad;
+                }
+                Op::Other
+            }
+            // auipc
+            0b001_0111 => {
+                self.set(rd, Some(pc.wrapping_add(imm_u)));
+                if rd == REG_SP {
+                    self.la_sp_next = Some(pc.wrapping_add(4));
+                    return Op::SpLoad;
+                }
+                Op::Other
+            }
+            // addi
+            0b001_0011 if funct3 == 0 => self.addi(pc, rd, rs1, imm_i),
+            // add / sub
+            0b011_0011 if funct3 == 0 && (funct7 == 0 || funct7 == 0b010_0000) => {
+                self.add_reg(rd, rs1, rs2, funct7 != 0)
+            }
+            // jal
+            0b110_1111 => {
+                let imm = ((instr & 0x8000_0000) as i32 >> 11) as u32
+                    | (instr & 0x000f_f000)
+                    | ((instr >> 9) & 0x800)
+                    | ((instr >> 20) & 0x7fe);
+                let target = pc.wrapping_add(imm);
+                self.set(rd, None);
+                match rd {
+                    REG_ZERO => Op::Jump(target),
+                    _ => Op::Call(target),
+                }
+            }
+            // jalr
+            0b110_0111 => {
+                let target = self.get(rs1).map(|v| v.wrapping_add(imm_i as u32) & !1);
+                self.set(rd, None);
+                match (rd, target) {
+                    (REG_ZERO, Some(target)) => Op::Jump(target),
+                    (REG_ZERO, None) => Op::Other,
+                    (_, Some(target)) => Op::Call(target),
+                    (_, None) => Op::IndirectCall,
+                }
+            }
+            // Branches and stores don't write a register
+            0b110_0011 | 0b010_0011 => Op::Other,
+            _ => {
+                self.set(rd, None);
+                Op::Other
+            }
+        }
+    }
+
+    fn decode_compressed(&mut self, pc: u32, instr: u32) -> Op {
+        let funct3 = (instr >> 13) & 0x7;
+        let rd = (instr >> 7) & 0x1f;
+        let rs2 = (instr >> 2) & 0x1f;
+        let rd_prime = 8 + ((instr >> 7) & 0x7);
+        let bit12 = (instr >> 12) & 1;
+        // 6-bit signed immediate used by c.addi, c.li
+        let imm6 = (((bit12 << 5) | rs2) << 26) as i32 >> 26;
+        // Jump offset used by c.j and c.jal
+        let imm_cj = {
+            let imm = ((instr >> 1) & 0x800)
+                | ((instr << 2) & 0x400)
+                | ((instr >> 1) & 0x300)
+                | ((instr << 1) & 0x80)
+                | ((instr >> 1) & 0x40)
+                | ((instr << 3) & 0x20)
+                | ((instr >> 7) & 0x10)
+                | ((instr >> 2) & 0xe);
+            ((imm << 20) as i32 >> 20) as u32
+        };
+
+        match (instr & 0b11, funct3) {
+            // c.addi4spn, c.lw
+            (0b00, 0b000) | (0b00, 0b010) => {
+                self.set(8 + ((instr >> 2) & 0x7), None);
+                Op::Other
+            }
+            (0b00, _) => Op::Other,
+
+            // c.addi
+            (0b01, 0b000) => self.addi(pc, rd, rd, imm6),
+            // c.jal
+            (0b01, 0b001) => {
+                self.set(REG_RA, None);
+                Op::Call(pc.wrapping_add(imm_cj))
+            }
+            // c.li
+            (0b01, 0b010) => {
+                self.set(rd, Some(imm6 as u32));
+                Op::Other
+            }
+            // c.addi16sp
+            (0b01, 0b011) if rd == REG_SP => {
+                let imm = (bit12 << 9)
+                    | ((instr >> 2) & 0x10)
+                    | ((instr << 1) & 0x40)
+                    | ((instr << 4) & 0x180)
+                    | ((instr << 3) & 0x20);
+                Op::SpAdjust(((imm << 22) as i32) >> 22)
+            }
+            // c.lui
+            (0b01, 0b011) => {
+                self.set(rd, Some((imm6 as u32) << 12));
+                Op::Other
+            }
+            // c.srli, c.srai, c.andi, c.sub, c.xor, c.or, c.and
+            (0b01, 0b100) => {
+                self.set(rd_prime, None);
+                Op::Other
+            }
+            // c.j
+            (0b01, 0b101) => Op::Jump(pc.wrapping_add(imm_cj)),
+            // c.beqz, c.bnez
+            (0b01, _) => Op::Other,
+
+            // c.slli, c.lwsp
+            (0b10, 0b000) | (0b10, 0b010) => {
+                self.set(rd, None);
+                Op::Other
+            }
+            // c.jr
+            (0b10, 0b100) if bit12 == 0 && rs2 == 0 => match self.get(rd) {
+                Some(target) if rd != REG_RA => Op::Jump(target & !1),
+                _ => Op::Other,
+            },
+            // c.mv
+            (0b10, 0b100) if bit12 == 0 => self.addi(pc, rd, rs2, 0),
+            // c.ebreak
+            (0b10, 0b100) if rd == 0 && rs2 == 0 => Op::Other,
+            // c.jalr
+            (0b10, 0b100) if rs2 == 0 => {
+                let target = self.get(rd);
+                self.set(REG_RA, None);
+                match target {
+                    Some(target) => Op::Call(target & !1),
+                    None => Op::IndirectCall,
+                }
+            }
+            // c.add
+            (0b10, 0b100) => self.add_reg(rd, rd, rs2, false),
+            (0b10, _) => Op::Other,
+
+            _ => Op::Other,
+        }
+    }
+}
+
+fn analyze_function(func: &FunctionCode) -> Function {
+    let start = func.addr;
+    let end = func.addr + func.code.len() as u32;
+    let mut result = Function {
+        name: func.name.clone(),
+        start,
+        end,
+        frame_size: 0,
+        calls: vec![],
+        indirect_calls: vec![],
+        dynamic_stack: false,
+    };
+
+    let mut decoder = Decoder::new();
+    let mut depth = 0_u32;
+    let mut offset = 0;
+    while offset + 2 <= func.code.len() {
+        let pc = start + offset as u32;
+        let low = u16::from_le_bytes([func.code[offset], func.code[offset + 1]]);
+        let (instr, len) = if low & 0b11 != 0b11 {
+            (u32::from(low), 2)
+        } else if let Some(high) = func.code.get(offset + 2..offset + 4) {
+            (
+                u32::from(low) | (u32::from(u16::from_le_bytes([high[0], high[1]])) << 16),
+                4,
+            )
+        } else {
+            break;
+        };
+        offset += len as usize;
+
+        match decoder.decode(pc, instr, len) {
+            // Epilogues are ignored; see the module docs
+            Op::SpAdjust(n) if n < 0 => depth = depth.saturating_add(n.unsigned_abs()),
+            Op::SpAdjust(_) | Op::Other => {}
+            Op::SpLoad => depth = 0,
+            Op::SpDynamic => result.dynamic_stack = true,
+            Op::Call(target) => result.calls.push(CallSite { target, depth }),
+            Op::Jump(target) if !(start..end).contains(&target) => {
+                result.calls.push(CallSite { target, depth })
+            }
+            Op::Jump(_) => {}
+            Op::IndirectCall => result.indirect_calls.push(pc),
+        }
+        result.frame_size = result.frame_size.max(depth);
+    }
+    result
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn code(instrs: &[u32]) -> Vec<u8> {
+        let mut result = vec![];
+        for &instr in instrs {
+            if instr & 0b11 == 0b11 {
+                result.extend_from_slice(&instr.to_le_bytes());
+            } else {
+                result.extend_from_slice(&(instr as u16).to_le_bytes());
+            }
+        }
+        result
+    }
+
+    // addi sp, sp, imm
+    fn addi_sp(imm: i32) -> u32 {
+        ((imm as u32) << 20) | (REG_SP << 15) | (REG_SP << 7) | 0b001_0011
+    }
+
+    // jal ra, offset
+    fn jal(rd: u32, offset: i32) -> u32 {
+        let o = offset as u32;
+        ((o & 0x10_0000) << 11)
+            | ((o & 0x7fe) << 20)
+            | ((o & 0x800) << 9)
+            | (o & 0xf_f000)
+            | (rd << 7)
+            | 0b110_1111
+    }
+
+    const RET: u32 = 0x8082;
+    // c.jalr a5
+    const C_JALR_A5: u32 = 0x9782;
+
+    #[test]
+    fn test_decode_sp_adjust() {
+        let mut d = Decoder::new();
+        assert_eq!(d.decode(0, addi_sp(-48), 4), Op::SpAdjust(-48));
+        // c.addi sp, -16
+        assert_eq!(d.decode(0, 0x1141, 2), Op::SpAdjust(-16));
+        // c.addi16sp sp, -64
+        assert_eq!(d.decode(0, 0x7139, 2), Op::SpAdjust(-64));
+        // c.addi16sp sp, 496
+        assert_eq!(d.decode(0, 0x617d, 2), Op::SpAdjust(496));
+
+        // lui t0, 1; addi t0, t0, -256; sub sp, sp, t0
+        assert_eq!(d.decode(0, 0x0000_12b7, 4), Op::Other);
+        assert_eq!(d.decode(0, 0xf002_8293, 4), Op::Other);
+        assert_eq!(d.decode(0, 0x4051_0133, 4), Op::SpAdjust(-3840));
+
+        // sub sp, sp, a0
+        assert_eq!(d.decode(0, 0x40a1_0133, 4), Op::SpDynamic);
+    }
+
+    #[test]
+    fn test_decode_calls() {
+        let mut d = Decoder::new();
+        assert_eq!(d.decode(0x100, jal(REG_RA, 0x40), 4), Op::Call(0x140));
+        assert_eq!(d.decode(0x100, jal(REG_RA, -0x40), 4), Op::Call(0xc0));
+        assert_eq!(d.decode(0x100, jal(REG_ZERO, 0x800), 4), Op::Jump(0x900));
+
+        // auipc ra, 0x1; jalr ra, -4(ra)
+        assert_eq!(d.decode(0x100, 0x0000_1097, 4), Op::Other);
+        assert_eq!(d.decode(0x104, 0xffc0_80e7, 4), Op::Call(0x10fc));
+
+        // auipc t1, 0; jr 16(t1)  (tail)
+        assert_eq!(d.decode(0x200, 0x0000_0317, 4), Op::Other);
+        assert_eq!(d.decode(0x204, 0x0103_0067, 4), Op::Jump(0x210));
+
+        // c.j +8, c.jal -8
+        assert_eq!(d.decode(0x300, 0xa021, 2), Op::Jump(0x308));
+        assert_eq!(d.decode(0x300, 0x3fe5, 2), Op::Call(0x2f8));
+
+        // jalr a5 / c.jalr a5 with an unknown a5
+        assert_eq!(d.decode(0x400, 0x0007_80e7, 4), Op::IndirectCall);
+        assert_eq!(d.decode(0x400, C_JALR_A5, 2), Op::IndirectCall);
+
+        // ret
+        assert_eq!(d.decode(0x500, RET, 2), Op::Other);
+    }
+
+    #[test]
+    fn test_decode_la_sp() {
+        let mut d = Decoder::new();
+        // auipc sp, 0x50020; addi sp, sp, -2048
+        assert_eq!(d.decode(0x1000, 0x5002_0117, 4), Op::SpLoad);
+        assert_eq!(d.decode(0x1004, addi_sp(-2048), 4), Op::Other);
+        // addi sp, sp, -88 is a real allocation again
+        assert_eq!(d.decode(0x1008, addi_sp(-88), 4), Op::SpAdjust(-88));
+
+        // lui sp, 0x50000; li a0, 1; addi sp, sp, -88 is a new stack followed
+        // by an allocation
+        assert_eq!(d.decode(0x2000, 0x5000_0137, 4), Op::SpLoad);
+        assert_eq!(d.decode(0x2004, 0x4505, 2), Op::Other);
+        assert_eq!(d.decode(0x2006, addi_sp(-88), 4), Op::SpAdjust(-88));
+    }
+
+    #[test]
+    fn test_sp_load_then_allocation() {
+        // lui sp, 0x50000; li a0, 1; addi sp, sp, -88; jal ra, leaf
+        let entry = code(&[
+            0x5000_0137,
+            0x4505,
+            addi_sp(-88),
+            jal(REG_RA, 0x100 - 0x100a),
+        ]);
+        let leaf = code(&[addi_sp(-16), addi_sp(16), RET]);
+        let usage = StackUsage::from_functions(&[
+            FunctionCode {
+                name: "entry".into(),
+                addr: 0x1000,
+                code: &entry,
+            },
+            FunctionCode {
+                name: "leaf".into(),
+                addr: 0x100,
+                code: &leaf,
+            },
+        ]);
+        assert_eq!(usage.frame_size("entry"), Some(88));
+        assert_eq!(usage.worst_path("entry").unwrap().depth, 104);
+    }
+
+    #[test]
+    fn test_worst_path() {
+        // entry -> a (48) -> leaf (16)
+        //       -> b (128)
+        //       -> indirect
+        let entry = code(&[
+            addi_sp(-32),
+            jal(REG_RA, 0x100 - 0x1004),
+            jal(REG_RA, 0x200 - 0x1008),
+            C_JALR_A5,
+            addi_sp(32),
+            RET,
+        ]);
+        let a = code(&[addi_sp(-48), jal(REG_RA, 0x300 - 0x104), addi_sp(48), RET]);
+        let b = code(&[addi_sp(-128), addi_sp(128), RET]);
+        let leaf = code(&[addi_sp(-16), addi_sp(16), RET]);
+        let usage = StackUsage::from_functions(&[
+            FunctionCode {
+                name: "entry".into(),
+                addr: 0x1000,
+                code: &entry,
+            },
+            FunctionCode {
+                name: "a".into(),
+                addr: 0x100,
+                code: &a,
+            },
+            FunctionCode {
+                name: "b".into(),
+                addr: 0x200,
+                code: &b,
+            },
+            FunctionCode {
+                name: "leaf".into(),
+                addr: 0x300,
+                code: &leaf,
+            },
+        ]);
+        assert_eq!(usage.frame_size("entry"), Some(32));
+        assert_eq!(usage.frame_size("b"), Some(128));
+
+        let path = usage.worst_path("entry").unwrap();
+        assert_eq!(path.depth, 160);
+        assert_eq!(
+            path.frames,
+            vec![
+                StackFrame {
+                    name: "entry".into(),
+                    size: 32
+                },
+                StackFrame {
+                    name: "b".into(),
+                    size: 128
+                },
+            ]
+        );
+        assert_eq!(path.indirect_calls, vec!["entry".to_string()]);
+        assert!(path.recursion.is_empty());
+        assert!(!path.is_bounded());
+
+        let path = usage.worst_path("a").unwrap();
+        assert_eq!(path.depth, 64);
+        assert!(path.is_bounded());
+
+        assert!(usage.worst_path("missing").is_err());
+    }
+
+    #[test]
+    fn test_recursion() {
+        // a -> b -> a, and a call into nothing
+        let a = code(&[
+            addi_sp(-16),
+            jal(REG_RA, 0x200 - 0x104),
+            jal(REG_RA, 0x4000),
+        ]);
+        let b = code(&[addi_sp(-32), jal(REG_ZERO, 0x100 - 0x204)]);
+        let usage = StackUsage::from_functions(&[
+            FunctionCode {
+                name: "a".into(),
+                addr: 0x100,
+                code: &a,
+            },
+            FunctionCode {
+                name: "b".into(),
+                addr: 0x200,
+                code: &b,
+            },
+        ]);
+        let path = usage.worst_path("a").unwrap();
+        assert_eq!(path.depth, 48);
+        assert_eq!(path.recursion, vec!["a".to_string()]);
+        assert_eq!(path.unknown_calls, vec![0x4108]);
+    }
+}
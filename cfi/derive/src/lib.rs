@@ -109,3 +109,54 @@ pub fn derive_launder_trait(input: TokenStream) -> TokenStream {
     };
     TokenStream::from(expanded)
 }
+
+#[proc_macro_derive(CfiEq)]
+pub fn derive_cfi_eq(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    let name = input.ident;
+
+    let fields = match input.data {
+        syn::Data::Struct(data) => data.fields,
+        _ => {
+            return syn::Error::new(name.span(), "CfiEq can only be derived for structs")
+                .to_compile_error()
+                .into()
+        }
+    };
+
+    let mut generics = input.generics;
+    let where_clause = generics.make_where_clause();
+    for field in fields.iter() {
+        let ty = &field.ty;
+        where_clause
+            .predicates
+            .push(parse_quote!(#ty: caliptra_cfi_lib::CfiEq));
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    // Every field is compared even after a difference has been found.
+    let diffs = fields.iter().enumerate().map(|(i, field)| {
+        let member = match &field.ident {
+            Some(ident) => syn::Member::Named(ident.clone()),
+            None => syn::Member::Unnamed(i.into()),
+        };
+        quote! {
+            diff |= caliptra_cfi_lib::cfi_launder(
+                caliptra_cfi_lib::CfiEq::cfi_diff_from(&self.#member, &other.#member, start)
+            );
+        }
+    });
+
+    let expanded = quote! {
+        impl #impl_generics caliptra_cfi_lib::CfiEq for #name #ty_generics #where_clause {
+            #[inline(always)]
+            #[allow(unused_mut, unused_variables)]
+            fn cfi_diff_from(&self, other: &Self, start: usize) -> u32 {
+                let mut diff = 0u32;
+                #(#diffs)*
+                diff
+            }
+        }
+    };
+    TokenStream::from(expanded)
+}
@@ -63,7 +63,7 @@ impl Default for CfiInt {
     }
 }
 
-fn prng() -> &'static Xoshiro128 {
+pub(crate) fn prng() -> &'static Xoshiro128 {
     unsafe { &CFI_STATE.prng }
 }
 
@@ -0,0 +1,253 @@
+/*++
+
+Licensed under the Apache-2.0 license.
+
+File Name:
+
+    cfi_eq.rs
+
+Abstract:
+
+    File contains constant-time, glitch-hardened equality comparisons.
+
+References:
+    https://github.com/lowRISC/opentitan/blob/7a61300cf7c409fa68fd892942c1d7b58a7cd4c0/sw/device/lib/base/hardened_memory.c
+
+--*/
+
+use crate::cfi::{cfi_launder, cfi_panic, CfiPanicInfo};
+use crate::cfi_counter::prng;
+use crate::CfiCounter;
+use core::cfg;
+
+/// Constant-time equality
+///
+/// Implementations must visit every element of the value regardless of
+/// where the first difference is. Use `#[derive(CfiEq)]` for structs.
+pub trait CfiEq {
+    /// Accumulate the difference between two values
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - Value to compare against
+    /// * `start` - Element to start the comparison from. Only meaningful for
+    ///   arrays and slices; every element is still visited.
+    ///
+    /// # Returns
+    ///
+    /// `u32` - Zero if and only if the values are equal
+    fn cfi_diff_from(&self, other: &Self, start: usize) -> u32;
+
+    /// Accumulate the difference between two values, starting with the first
+    /// element
+    ///
+    /// # Returns
+    ///
+    /// `u32` - Zero if and only if the values are equal
+    #[inline(always)]
+    fn cfi_diff(&self, other: &Self) -> u32 {
+        self.cfi_diff_from(other, 0)
+    }
+}
+
+macro_rules! cfi_eq_int_impl {
+    ($($ty: ty),*) => {
+        $(
+            impl CfiEq for $ty {
+                #[inline(always)]
+                #[allow(clippy::unnecessary_cast)]
+                fn cfi_diff_from(&self, other: &Self, _start: usize) -> u32 {
+                    // Fold wide integers so no differing bit is truncated
+                    let diff = (*self ^ *other) as u64;
+                    (diff | (diff >> 32)) as u32
+                }
+            }
+        )*
+    };
+}
+
+cfi_eq_int_impl!(u8, u16, u32, u64, usize);
+
+impl CfiEq for bool {
+    #[inline(always)]
+    fn cfi_diff_from(&self, other: &Self, _start: usize) -> u32 {
+        (*self as u32) ^ (*other as u32)
+    }
+}
+
+impl<T: CfiEq> CfiEq for [T] {
+    #[inline(always)]
+    fn cfi_diff_from(&self, other: &Self, start: usize) -> u32 {
+        let len = core::cmp::min(self.len(), other.len());
+        let mut diff = (self.len() != other.len()) as u32;
+        if len == 0 {
+            return diff;
+        }
+
+        // Visit every element exactly once, wrapping around from `start`.
+        let mut idx = start % len;
+        for _ in 0..len {
+            diff |= cfi_launder(self[idx].cfi_diff(&other[idx]));
+            idx += 1;
+            if idx == len {
+                idx = 0;
+            }
+        }
+        diff
+    }
+}
+
+impl<T: CfiEq, const N: usize> CfiEq for [T; N] {
+    #[inline(always)]
+    fn cfi_diff_from(&self, other: &Self, start: usize) -> u32 {
+        self[..].cfi_diff_from(&other[..], start)
+    }
+}
+
+impl<T: CfiEq + ?Sized> CfiEq for &T {
+    #[inline(always)]
+    fn cfi_diff_from(&self, other: &Self, start: usize) -> u32 {
+        (**self).cfi_diff_from(*other, start)
+    }
+}
+
+/// Hardening applied by `cfi_eq_with` and `cfi_assert_eq_with`
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct CfiEqOptions {
+    /// Compare twice and panic if the two results disagree
+    pub double_eval: bool,
+
+    /// Insert a random delay before each comparison
+    pub random_delay: bool,
+
+    /// Start each comparison at a random element
+    pub random_start: bool,
+}
+
+impl CfiEqOptions {
+    /// Single pass without any delays
+    pub const FAST: Self = Self {
+        double_eval: false,
+        random_delay: false,
+        random_start: false,
+    };
+
+    /// All hardening enabled
+    pub const HARDENED: Self = Self {
+        double_eval: true,
+        random_delay: true,
+        random_start: true,
+    };
+}
+
+impl Default for CfiEqOptions {
+    /// Returns the "default value" for a type.
+    fn default() -> Self {
+        Self::HARDENED
+    }
+}
+
+#[inline(always)]
+fn cfi_diff_once<T: CfiEq + ?Sized>(lhs: &T, rhs: &T, options: CfiEqOptions) -> u32 {
+    if options.random_delay {
+        CfiCounter::delay();
+    }
+    let start = if options.random_start {
+        prng().next() as usize
+    } else {
+        0
+    };
+    cfi_launder(lhs.cfi_diff_from(rhs, start))
+}
+
+/// Compare two values in constant time
+///
+/// # Arguments
+///
+/// * `lhs` - Left hand side
+/// * `rhs` - Right hand side
+/// * `options` - Hardening to apply. Ignored if the `cfi` feature is off.
+///
+/// # Returns
+///
+/// `bool` - True if the values are equal
+#[inline(always)]
+pub fn cfi_eq_with<T: CfiEq + ?Sized>(lhs: &T, rhs: &T, options: CfiEqOptions) -> bool {
+    if !cfg!(feature = "cfi") {
+        return lhs.cfi_diff(rhs) == 0;
+    }
+
+    let diff = cfi_diff_once(lhs, rhs, options);
+    if options.double_eval {
+        // Second evaluation for glitch protection
+        let diff2 = cfi_diff_once(lhs, rhs, options);
+        if (diff == 0) != (diff2 == 0) {
+            cfi_panic(CfiPanicInfo::AssertEqFail);
+        }
+    }
+    cfi_launder(diff == 0)
+}
+
+/// Compare two values in constant time with all hardening enabled
+///
+/// # Arguments
+///
+/// * `lhs` - Left hand side
+/// * `rhs` - Right hand side
+///
+/// # Returns
+///
+/// `bool` - True if the values are equal
+#[inline(always)]
+pub fn cfi_eq<T: CfiEq + ?Sized>(lhs: &T, rhs: &T) -> bool {
+    cfi_eq_with(lhs, rhs, CfiEqOptions::HARDENED)
+}
+
+/// Compare two slices in constant time with all hardening enabled
+///
+/// Slices of different lengths are never equal.
+///
+/// # Arguments
+///
+/// * `lhs` - Left hand side
+/// * `rhs` - Right hand side
+///
+/// # Returns
+///
+/// `bool` - True if the slices are equal
+#[inline(always)]
+pub fn cfi_eq_slice<T: CfiEq>(lhs: &[T], rhs: &[T]) -> bool {
+    cfi_eq_with(lhs, rhs, CfiEqOptions::HARDENED)
+}
+
+/// CFI Constant-time Equality Assertion
+///
+/// # Arguments
+///
+/// * `lhs` - Left hand side
+/// * `rhs` - Right hand side
+/// * `options` - Hardening to apply
+#[inline(always)]
+pub fn cfi_assert_eq_with<T: CfiEq + ?Sized>(lhs: &T, rhs: &T, options: CfiEqOptions) {
+    if cfg!(feature = "cfi") {
+        if !cfi_eq_with(lhs, rhs, options) {
+            cfi_panic(CfiPanicInfo::AssertEqFail);
+        }
+
+        // Second check for glitch protection
+        if options.double_eval && !cfi_launder(cfi_diff_once(lhs, rhs, options) == 0) {
+            cfi_panic(CfiPanicInfo::AssertEqFail);
+        }
+    }
+}
+
+/// CFI Constant-time Slice Equality Assertion
+///
+/// # Arguments
+///
+/// * `lhs` - Left hand side
+/// * `rhs` - Right hand side
+#[inline(always)]
+pub fn cfi_assert_eq_slice<T: CfiEq>(lhs: &[T], rhs: &[T]) {
+    cfi_assert_eq_with(lhs, rhs, CfiEqOptions::HARDENED)
+}
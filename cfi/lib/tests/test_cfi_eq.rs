@@ -0,0 +1,131 @@
+/*++
+
+Licensed under the Apache-2.0 license.
+
+File Name:
+
+    test_cfi_eq.rs
+
+--*/
+
+use caliptra_cfi_derive::CfiEq;
+use caliptra_cfi_lib::{
+    cfi_assert_eq_slice, cfi_assert_eq_with, cfi_eq, cfi_eq_slice, cfi_eq_with, CfiEq,
+    CfiEqOptions,
+};
+use serial_test::serial;
+
+#[derive(CfiEq, Clone, Copy)]
+struct Digest<const N: usize>(pub [u32; N]);
+
+#[derive(CfiEq, Clone, Copy)]
+struct Measurement {
+    digest: Digest<12>,
+    svn: u32,
+    flags: u8,
+}
+
+#[derive(CfiEq)]
+struct Empty;
+
+#[test]
+#[serial]
+fn test_eq_slice() {
+    let a = [1u8, 2, 3, 4, 5];
+    let mut b = a;
+    assert!(cfi_eq_slice(&a, &b));
+
+    for i in 0..a.len() {
+        b[i] ^= 0x80;
+        assert!(!cfi_eq_slice(&a, &b));
+        b[i] = a[i];
+    }
+
+    assert!(!cfi_eq_slice(&a[..4], &b));
+    assert!(cfi_eq_slice::<u8>(&[], &[]));
+}
+
+#[test]
+#[serial]
+fn test_eq_start_offset() {
+    let a = [0u32; 12];
+    let mut b = a;
+    b[11] = 1;
+    for start in 0..24 {
+        assert_ne!(a.cfi_diff_from(&b, start), 0);
+        assert_eq!(a.cfi_diff_from(&a, start), 0);
+    }
+}
+
+#[test]
+#[serial]
+fn test_eq_wide_int() {
+    assert!(!cfi_eq(&0u64, &(1u64 << 63)));
+    assert!(cfi_eq(&u64::MAX, &u64::MAX));
+    assert!(!cfi_eq(&true, &false));
+}
+
+#[test]
+#[serial]
+fn test_eq_options() {
+    let a = [0xdeadbeefu32; 8];
+    let mut b = a;
+    assert!(cfi_eq_with(&a, &b, CfiEqOptions::FAST));
+    assert!(cfi_eq_with(&a, &b, CfiEqOptions::default()));
+    b[3] = 0;
+    assert!(!cfi_eq_with(&a, &b, CfiEqOptions::FAST));
+    assert!(!cfi_eq_with(&a, &b, CfiEqOptions::HARDENED));
+}
+
+#[test]
+#[serial]
+fn test_derive() {
+    let a = Measurement {
+        digest: Digest([0x1234_5678; 12]),
+        svn: 3,
+        flags: 1,
+    };
+    assert!(cfi_eq(&a, &a.clone()));
+
+    let mut b = a;
+    b.digest.0[7] = 0;
+    assert!(!cfi_eq(&a, &b));
+
+    let mut b = a;
+    b.svn = 4;
+    assert!(!cfi_eq(&a, &b));
+
+    let mut b = a;
+    b.flags = 0;
+    assert!(!cfi_eq(&a, &b));
+
+    assert!(cfi_eq(&Empty, &Empty));
+}
+
+#[test]
+#[serial]
+fn test_assert_eq_slice() {
+    let a = Digest([0xa5a5_a5a5; 12]);
+    cfi_assert_eq_slice(&a.0, &a.0);
+    cfi_assert_eq_with(&a, &a, CfiEqOptions::FAST);
+}
+
+#[test]
+#[serial]
+#[should_panic(expected = "CFI Panic = AssertEqFail")]
+fn test_assert_eq_slice_fail() {
+    let a = [0u8; 48];
+    let mut b = a;
+    b[47] = 1;
+    cfi_assert_eq_slice(&a, &b);
+}
+
+#[test]
+#[serial]
+#[should_panic(expected = "CFI Panic = AssertEqFail")]
+fn test_assert_eq_derive_fail() {
+    let a = Digest([0u32; 12]);
+    let mut b = a;
+    b.0[0] = 1;
+    cfi_assert_eq_with(&a, &b, CfiEqOptions::HARDENED);
+}
@@ -0,0 +1,473 @@
+// Licensed under the Apache-2.0 license
+
+use std::{
+    collections::BTreeSet,
+    fmt,
+    io::{Error, ErrorKind},
+    path::{Path, PathBuf},
+};
+
+use syn::{
+    visit::{self, Visit},
+    Attribute, Block, ImplItemMethod, ItemFn, ItemImpl, ItemMod, Macro, Meta, NestedMeta,
+    ReturnType, Signature, Type,
+};
+
+/// Directories scanned when none are given on the command line
+const DEFAULT_DIRS: &[&str] = &[
+    "./rom/dev/src",
+    "./fmc/src",
+    "./runtime/src",
+    "./image/verify/src",
+];
+
+const IGNORED_DIRS: &[&str] = &[".git", "fuzz", "out", "target", "tests"];
+
+/// Words in a function name that mark it as security-relevant
+const SENSITIVE_WORDS: &[&str] = &[
+    "attest",
+    "authenticate",
+    "authorize",
+    "derive",
+    "keygen",
+    "sign",
+    "validate",
+    "verify",
+];
+
+/// Attributes that add CFI instrumentation to a function
+const CFI_ATTRS: &[&str] = &["cfi_impl_fn", "cfi_mod_fn"];
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct Finding {
+    path: PathBuf,
+    line: usize,
+    name: String,
+}
+
+impl Finding {
+    /// Key used to match the finding against the allowlist
+    fn key(&self) -> String {
+        format!("{}:{}", self.path.display(), self.name)
+    }
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}: {} returns CaliptraResult but has neither cfi_impl_fn/cfi_mod_fn nor cfi_assert",
+            self.path.display(),
+            self.line,
+            self.name
+        )
+    }
+}
+
+fn add_path(path: &Path) -> impl Fn(Error) -> Error + Copy + '_ {
+    move |e: Error| Error::new(e.kind(), format!("{path:?}: {e}"))
+}
+
+fn is_sensitive_name(name: &str) -> bool {
+    name.split('_').any(|word| SENSITIVE_WORDS.contains(&word))
+}
+
+fn returns_caliptra_result(sig: &Signature) -> bool {
+    match &sig.output {
+        ReturnType::Type(_, ty) => match ty.as_ref() {
+            Type::Path(p) => p
+                .path
+                .segments
+                .last()
+                .is_some_and(|s| s.ident == "CaliptraResult"),
+            _ => false,
+        },
+        ReturnType::Default => false,
+    }
+}
+
+/// Name used for the scope of an impl on `ty`
+fn type_name(ty: &Type) -> String {
+    match ty {
+        Type::Path(p) => p
+            .path
+            .segments
+            .last()
+            .map(|s| s.ident.to_string())
+            .unwrap_or_default(),
+        Type::Reference(r) => type_name(&r.elem),
+        _ => String::from("_"),
+    }
+}
+
+/// Check if `attrs` contain a CFI attribute, directly or inside `cfg_attr`
+fn has_cfi_attr(attrs: &[Attribute]) -> bool {
+    attrs.iter().any(|attr| {
+        let path = &attr.path;
+        if CFI_ATTRS.iter().any(|a| path.is_ident(a)) {
+            return true;
+        }
+        path.is_ident("cfg_attr") && {
+            let tokens = attr.tokens.to_string();
+            CFI_ATTRS.iter().any(|a| tokens.contains(a))
+        }
+    })
+}
+
+/// Check if the `cfg` predicate `meta` only holds in test builds: `test`, or
+/// an `all(...)` that includes `test`
+fn cfg_requires_test(meta: &Meta) -> bool {
+    match meta {
+        Meta::Path(path) => path.is_ident("test"),
+        Meta::List(list) if list.path.is_ident("all") => list
+            .nested
+            .iter()
+            .any(|nested| matches!(nested, NestedMeta::Meta(meta) if cfg_requires_test(meta))),
+        _ => false,
+    }
+}
+
+/// Check if `attrs` exclude the item from the scan: `#[test]` and
+/// `#[cfg(test)]`, but not other `cfg`s that mention tests (e.g.
+/// `#[cfg(not(test))]` or `#[cfg(feature = "test-only")]`)
+fn is_test_only(attrs: &[Attribute]) -> bool {
+    attrs.iter().any(|attr| {
+        if attr.path.is_ident("test") {
+            return true;
+        }
+        if !attr.path.is_ident("cfg") {
+            return false;
+        }
+        match attr.parse_meta() {
+            Ok(Meta::List(list)) => matches!(
+                list.nested.first(),
+                Some(NestedMeta::Meta(meta)) if list.nested.len() == 1 && cfg_requires_test(meta)
+            ),
+            _ => false,
+        }
+    })
+}
+
+/// Looks for `cfi_assert*` macros and calls inside a function body
+#[derive(Default)]
+struct CfiAssertFinder {
+    found: bool,
+}
+
+impl<'ast> Visit<'ast> for CfiAssertFinder {
+    fn visit_macro(&mut self, mac: &'ast Macro) {
+        if let Some(seg) = mac.path.segments.last() {
+            if seg.ident.to_string().starts_with("cfi_assert") {
+                self.found = true;
+            }
+        }
+        visit::visit_macro(self, mac);
+    }
+
+    fn visit_expr_call(&mut self, call: &'ast syn::ExprCall) {
+        if let syn::Expr::Path(p) = call.func.as_ref() {
+            if let Some(seg) = p.path.segments.last() {
+                if seg.ident.to_string().starts_with("cfi_assert") {
+                    self.found = true;
+                }
+            }
+        }
+        visit::visit_expr_call(self, call);
+    }
+}
+
+fn body_has_cfi_assert(block: &Block) -> bool {
+    let mut finder = CfiAssertFinder::default();
+    finder.visit_block(block);
+    finder.found
+}
+
+/// Walks a single file and records unprotected security-relevant functions
+struct FileVisitor<'a> {
+    path: &'a Path,
+    scope: Vec<String>,
+    findings: Vec<Finding>,
+}
+
+impl FileVisitor<'_> {
+    fn check_fn(&mut self, attrs: &[Attribute], sig: &Signature, block: &Block) {
+        let name = sig.ident.to_string();
+        if !is_sensitive_name(&name) || !returns_caliptra_result(sig) {
+            return;
+        }
+        if has_cfi_attr(attrs) || body_has_cfi_assert(block) {
+            return;
+        }
+        let mut qualified = self.scope.join("::");
+        if !qualified.is_empty() {
+            qualified.push_str("::");
+        }
+        qualified.push_str(&name);
+        self.findings.push(Finding {
+            path: self.path.into(),
+            line: sig.ident.span().start().line,
+            name: qualified,
+        });
+    }
+}
+
+impl<'ast> Visit<'ast> for FileVisitor<'_> {
+    fn visit_item_mod(&mut self, item: &'ast ItemMod) {
+        if is_test_only(&item.attrs) {
+            return;
+        }
+        self.scope.push(item.ident.to_string());
+        visit::visit_item_mod(self, item);
+        self.scope.pop();
+    }
+
+    fn visit_item_impl(&mut self, item: &'ast ItemImpl) {
+        if is_test_only(&item.attrs) {
+            return;
+        }
+        self.scope.push(type_name(&item.self_ty));
+        visit::visit_item_impl(self, item);
+        self.scope.pop();
+    }
+
+    fn visit_item_fn(&mut self, item: &'ast ItemFn) {
+        if is_test_only(&item.attrs) {
+            return;
+        }
+        self.check_fn(&item.attrs, &item.sig, &item.block);
+        visit::visit_item_fn(self, item);
+    }
+
+    fn visit_impl_item_method(&mut self, item: &'ast ImplItemMethod) {
+        if is_test_only(&item.attrs) {
+            return;
+        }
+        self.check_fn(&item.attrs, &item.sig, &item.block);
+        visit::visit_impl_item_method(self, item);
+    }
+}
+
+fn check_source(path: &Path, source: &str) -> Result<Vec<Finding>, Error> {
+    let file = syn::parse_file(source)
+        .map_err(|e| Error::new(ErrorKind::InvalidData, format!("{path:?}: {e}")))?;
+    let mut visitor = FileVisitor {
+        path,
+        scope: vec![],
+        findings: vec![],
+    };
+    visitor.visit_file(&file);
+    Ok(visitor.findings)
+}
+
+fn check_file(path: &Path) -> Result<Vec<Finding>, Error> {
+    let source = std::fs::read_to_string(path).map_err(add_path(path))?;
+    check_source(path, &source)
+}
+
+fn find_files(dir: &Path, result: &mut Vec<PathBuf>) -> Result<(), Error> {
+    let wrap_err = add_path(dir);
+    for file in std::fs::read_dir(dir).map_err(wrap_err)? {
+        let file = file.map_err(wrap_err)?;
+        let file_path = &file.path();
+        let file_type = file.file_type().map_err(add_path(file_path))?;
+        if file_type.is_dir() {
+            if let Some(file_name) = file.file_name().to_str() {
+                if IGNORED_DIRS.contains(&file_name) {
+                    continue;
+                }
+            }
+            find_files(file_path, result)?;
+        } else if file_type.is_file() && file_path.extension().is_some_and(|e| e == "rs") {
+            result.push(file_path.into());
+        }
+    }
+    Ok(())
+}
+
+/// Parse an allowlist
+///
+/// Each non-empty line is `<path>:<function>`, where function is qualified
+/// with its enclosing modules and impl type, e.g.
+/// `./runtime/src/verify.rs:LmsVerifyCmd::parse`. A `<path>:<prefix>::*`
+/// entry allows every function in the impl or module. Text after `#` is a
+/// comment.
+fn parse_allowlist(contents: &str) -> BTreeSet<String> {
+    contents
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or_default().trim())
+        .filter(|line| !line.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+fn is_allowed(allowlist: &BTreeSet<String>, finding: &Finding) -> bool {
+    let key = finding.key();
+    if allowlist.contains(&key) {
+        return true;
+    }
+    // Check wildcard entries for every enclosing scope
+    let mut prefix = key.as_str();
+    while let Some(idx) = prefix.rfind("::") {
+        prefix = &prefix[..idx];
+        if allowlist.contains(&format!("{prefix}::*")) {
+            return true;
+        }
+    }
+    false
+}
+
+fn usage() -> ! {
+    println!("Usage: cfi-lint [--allowlist <file>] [--print-allowlist] [<dir>...]");
+    std::process::exit(1);
+}
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let mut allowlist = BTreeSet::new();
+    let mut print_allowlist = false;
+    let mut dirs = vec![];
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--allowlist" => {
+                let Some(path) = args.next() else { usage() };
+                let contents = std::fs::read_to_string(&path)
+                    .map_err(add_path(Path::new(&path)))
+                    .unwrap();
+                allowlist = parse_allowlist(&contents);
+            }
+            "--print-allowlist" => print_allowlist = true,
+            arg if arg.starts_with('-') => usage(),
+            _ => dirs.push(PathBuf::from(arg)),
+        }
+    }
+    if dirs.is_empty() {
+        dirs = DEFAULT_DIRS.iter().map(PathBuf::from).collect();
+    }
+
+    let mut files = Vec::new();
+    for dir in dirs.iter() {
+        find_files(dir, &mut files).unwrap();
+    }
+    files.sort();
+
+    let mut findings = vec![];
+    for file in files.iter() {
+        findings.extend(check_file(file).unwrap());
+    }
+    findings.retain(|f| !is_allowed(&allowlist, f));
+
+    if print_allowlist {
+        for finding in findings.iter() {
+            println!("{}", finding.key());
+        }
+        return;
+    }
+
+    for finding in findings.iter() {
+        println!("{finding}");
+    }
+    if !findings.is_empty() {
+        println!(
+            "To fix, add #[cfg_attr(not(feature = \"no-cfi\"), cfi_impl_fn)] (or cfi_mod_fn) or \
+             cfi_assert the security-relevant results, or add the function to the allowlist."
+        );
+        std::process::exit(2);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+
+    fn names(source: &str) -> Vec<String> {
+        check_source(Path::new("foo.rs"), source)
+            .unwrap()
+            .into_iter()
+            .map(|f| f.name)
+            .collect()
+    }
+
+    #[test]
+    fn test_unprotected() {
+        let source = r#"
+            fn verify_sig() -> CaliptraResult<()> { Ok(()) }
+            fn verify_bool() -> bool { true }
+            fn load_image() -> CaliptraResult<()> { Ok(()) }
+            fn resign() -> CaliptraResult<()> { Ok(()) }
+            mod m {
+                struct Verifier;
+                impl Verifier {
+                    fn derive_key(&self) -> caliptra_drivers::CaliptraResult<u32> { Ok(0) }
+                }
+            }
+        "#;
+        assert_eq!(names(source), ["verify_sig", "m::Verifier::derive_key"]);
+    }
+
+    #[test]
+    fn test_only_cfg_test_is_skipped() {
+        let source = r#"
+            #[cfg(not(test))]
+            fn verify_a() -> CaliptraResult<()> { Ok(()) }
+            #[cfg(feature = "test-hooks")]
+            fn verify_b() -> CaliptraResult<()> { Ok(()) }
+            #[cfg(any(test, feature = "verilator"))]
+            fn verify_c() -> CaliptraResult<()> { Ok(()) }
+            #[cfg(test)]
+            fn verify_d() -> CaliptraResult<()> { Ok(()) }
+            #[cfg(all(test, target_family = "unix"))]
+            fn verify_e() -> CaliptraResult<()> { Ok(()) }
+            #[test]
+            fn verify_f() -> CaliptraResult<()> { Ok(()) }
+        "#;
+        assert_eq!(names(source), ["verify_a", "verify_b", "verify_c"]);
+    }
+
+    #[test]
+    fn test_protected() {
+        let source = r#"
+            #[cfg_attr(not(feature = "no-cfi"), cfi_mod_fn)]
+            fn verify_a() -> CaliptraResult<()> { Ok(()) }
+            #[cfi_impl_fn]
+            fn verify_b() -> CaliptraResult<()> { Ok(()) }
+            fn verify_c() -> CaliptraResult<()> {
+                cfi_assert_eq(1, 1);
+                Ok(())
+            }
+            fn verify_d() -> CaliptraResult<()> {
+                cfi_assert!(true);
+                Ok(())
+            }
+            #[cfg(test)]
+            mod tests {
+                fn verify_e() -> CaliptraResult<()> { Ok(()) }
+            }
+        "#;
+        assert!(names(source).is_empty());
+    }
+
+    #[test]
+    fn test_allowlist() {
+        let allowlist = parse_allowlist(
+            "# comment\n\
+             foo.rs:verify_sig\n\
+             foo.rs:m::Verifier::*  # whole impl\n",
+        );
+        let source = r#"
+            fn verify_sig() -> CaliptraResult<()> { Ok(()) }
+            fn verify_other() -> CaliptraResult<()> { Ok(()) }
+            mod m {
+                impl Verifier {
+                    fn derive_key(&self) -> CaliptraResult<u32> { Ok(0) }
+                }
+            }
+        "#;
+        let findings: Vec<_> = check_source(Path::new("foo.rs"), source)
+            .unwrap()
+            .into_iter()
+            .filter(|f| !is_allowed(&allowlist, f))
+            .map(|f| f.name)
+            .collect();
+        assert_eq!(findings, ["verify_other"]);
+    }
+}
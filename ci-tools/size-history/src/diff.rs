@@ -0,0 +1,333 @@
+// Licensed under the Apache-2.0 license
+
+//! Compares firmware sizes between two builds and enforces the ROM and ICCM
+//! size budgets. Unlike the size history, this never touches a cache, so it
+//! can be run locally before sending a PR:
+//!
+//! ```text
+//! cargo run -p caliptra-size-history -- diff --base origin/main
+//! ```
+
+use std::{collections::BTreeMap, io, path::Path};
+
+use caliptra_builder::{elf_size, elf_symbols, firmware, FwId, SymbolType};
+use caliptra_common::{FMC_SIZE, RUNTIME_SIZE};
+use caliptra_drivers::memory_layout::{ROM_RELAXATION_PADDING, ROM_SIZE};
+
+use crate::{git, util::other_err};
+
+const USAGE: &str =
+    "Usage: caliptra-size-history diff [--base <rev>] [--head <rev>] [--symbols <n>]
+
+Builds ROM, FMC and runtime at two revisions and prints the total, per-crate
+and per-symbol size deltas. Fails if the head build exceeds a size budget.
+
+  --base <rev>     Revision to compare against (default: origin/main)
+  --head <rev>     Revision to measure (default: the current working tree)
+  --symbols <n>    Number of symbol deltas to print per image (default: 20)";
+
+struct Image {
+    name: &'static str,
+    fwid: FwId<'static>,
+    budget: u64,
+    budget_name: &'static str,
+}
+
+const IMAGES: [Image; 3] = [
+    Image {
+        name: "ROM",
+        fwid: firmware::ROM,
+        budget: (ROM_SIZE - ROM_RELAXATION_PADDING) as u64,
+        budget_name: "ROM_SIZE - ROM_RELAXATION_PADDING",
+    },
+    Image {
+        name: "FMC",
+        fwid: firmware::FMC_WITH_UART,
+        budget: FMC_SIZE as u64,
+        budget_name: "FMC_SIZE",
+    },
+    Image {
+        name: "Runtime",
+        fwid: firmware::APP_WITH_UART,
+        budget: RUNTIME_SIZE as u64,
+        budget_name: "RUNTIME_SIZE",
+    },
+];
+
+struct Args {
+    base: String,
+    head: Option<String>,
+    symbols: usize,
+}
+impl Args {
+    fn parse(mut args: impl Iterator<Item = String>) -> io::Result<Self> {
+        let mut result = Self {
+            base: "origin/main".into(),
+            head: None,
+            symbols: 20,
+        };
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .ok_or_else(|| other_err(format!("{arg} requires a value\n\n{USAGE}")))
+            };
+            match arg.as_str() {
+                "--base" => result.base = value()?,
+                "--head" => result.head = Some(value()?),
+                "--symbols" => {
+                    result.symbols = value()?
+                        .parse()
+                        .map_err(|e| other_err(format!("Invalid --symbols value: {e}")))?
+                }
+                "-h" | "--help" => {
+                    println!("{USAGE}");
+                    std::process::exit(0);
+                }
+                _ => return Err(other_err(format!("Unknown argument {arg:?}\n\n{USAGE}"))),
+            }
+        }
+        Ok(result)
+    }
+}
+
+/// Size information extracted from one firmware ELF
+#[derive(Default)]
+struct ElfSizes {
+    total: u64,
+
+    // Demangled symbol name -> size in bytes
+    symbols: BTreeMap<String, u64>,
+}
+impl ElfSizes {
+    fn from_elf(elf_bytes: &[u8]) -> io::Result<Self> {
+        let mut symbols = BTreeMap::new();
+        for sym in elf_symbols(elf_bytes)? {
+            if sym.size == 0 || !matches!(sym.ty, SymbolType::Func | SymbolType::Object) {
+                continue;
+            }
+            let name = format!("{:#}", rustc_demangle::demangle(sym.name));
+            *symbols.entry(name).or_default() += sym.size;
+        }
+        Ok(Self {
+            total: elf_size(elf_bytes)?,
+            symbols,
+        })
+    }
+
+    fn crates(&self) -> BTreeMap<String, u64> {
+        let mut result = BTreeMap::new();
+        for (name, size) in self.symbols.iter() {
+            *result.entry(symbol_crate(name).to_string()).or_default() += size;
+        }
+        result
+    }
+}
+
+#[derive(Debug, Eq, PartialEq)]
+struct Delta<'a> {
+    name: &'a str,
+    base: u64,
+    head: u64,
+}
+impl Delta<'_> {
+    fn change(&self) -> i64 {
+        self.head as i64 - self.base as i64
+    }
+}
+
+/// Returns the entries that changed size, largest change first.
+fn deltas<'a>(base: &'a BTreeMap<String, u64>, head: &'a BTreeMap<String, u64>) -> Vec<Delta<'a>> {
+    let mut result: Vec<Delta> = base
+        .keys()
+        .chain(head.keys().filter(|name| !base.contains_key(*name)))
+        .map(|name| Delta {
+            name,
+            base: base.get(name).copied().unwrap_or(0),
+            head: head.get(name).copied().unwrap_or(0),
+        })
+        .filter(|delta| delta.change() != 0)
+        .collect();
+    result.sort_by(|a, b| {
+        b.change()
+            .abs()
+            .cmp(&a.change().abs())
+            .then_with(|| a.name.cmp(b.name))
+    });
+    result
+}
+
+/// Returns the crate a demangled symbol belongs to, or "[unknown]" for
+/// symbols that don't come from Rust (memcpy, assembly entry points, ...).
+fn symbol_crate(name: &str) -> &str {
+    let name = name.trim_start_matches(['<', '&', '[', '*']);
+    let name = name.strip_prefix("mut ").unwrap_or(name);
+    let name = name.strip_prefix("const ").unwrap_or(name);
+    let end = name
+        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+        .unwrap_or(name.len());
+    if end == 0 || !name[end..].starts_with("::") {
+        return "[unknown]";
+    }
+    &name[..end]
+}
+
+fn build_sizes(workspace_dir: Option<&Path>) -> io::Result<Vec<ElfSizes>> {
+    let fwids: Vec<&FwId> = IMAGES.iter().map(|image| &image.fwid).collect();
+    caliptra_builder::build_firmware_elfs_uncached(workspace_dir, &fwids)?
+        .iter()
+        .map(|(_, elf_bytes)| ElfSizes::from_elf(elf_bytes))
+        .collect()
+}
+
+fn build_sizes_at(rev: &str, worktree_path: &Path) -> io::Result<Vec<ElfSizes>> {
+    println!("Building firmware at {rev}");
+    let worktree = git::WorkTree::new(worktree_path)?;
+    worktree.checkout(rev)?;
+    worktree.submodule_update()?;
+    build_sizes(Some(worktree.path))
+}
+
+fn format_change(change: i64) -> String {
+    if change > 0 {
+        format!("+{change}")
+    } else {
+        change.to_string()
+    }
+}
+
+pub fn run(args: impl Iterator<Item = String>) -> io::Result<()> {
+    let args = Args::parse(args)?;
+
+    let base = build_sizes_at(&args.base, Path::new("/tmp/caliptra-size-diff-base-wt"))?;
+    let head = match &args.head {
+        Some(rev) => build_sizes_at(rev, Path::new("/tmp/caliptra-size-diff-head-wt"))?,
+        None => {
+            println!("Building firmware in the current working tree");
+            build_sizes(None)?
+        }
+    };
+
+    let mut over_budget = vec![];
+    for ((image, base), head) in IMAGES.iter().zip(base.iter()).zip(head.iter()) {
+        println!();
+        println!("== {} ({}) ==", image.name, image.fwid.crate_name);
+        println!(
+            "Total: {} -> {} ({}), budget {} ({})",
+            base.total,
+            head.total,
+            format_change(head.total as i64 - base.total as i64),
+            image.budget,
+            image.budget_name
+        );
+        if head.total > image.budget {
+            over_budget.push(format!(
+                "{} is {} bytes, which exceeds {} ({}) by {} bytes",
+                image.name,
+                head.total,
+                image.budget_name,
+                image.budget,
+                head.total - image.budget
+            ));
+        }
+
+        let (base_crates, head_crates) = (base.crates(), head.crates());
+        let crate_deltas = deltas(&base_crates, &head_crates);
+        if !crate_deltas.is_empty() {
+            println!("Crates:");
+            for delta in crate_deltas.iter() {
+                println!(
+                    "  {:>8}  {} ({} -> {})",
+                    format_change(delta.change()),
+                    delta.name,
+                    delta.base,
+                    delta.head
+                );
+            }
+        }
+
+        let symbol_deltas = deltas(&base.symbols, &head.symbols);
+        if !symbol_deltas.is_empty() {
+            println!("Symbols:");
+            for delta in symbol_deltas.iter().take(args.symbols) {
+                println!(
+                    "  {:>8}  {} ({} -> {})",
+                    format_change(delta.change()),
+                    delta.name,
+                    delta.base,
+                    delta.head
+                );
+            }
+            if symbol_deltas.len() > args.symbols {
+                println!("  ... and {} more", symbol_deltas.len() - args.symbols);
+            }
+        }
+    }
+
+    if !over_budget.is_empty() {
+        println!();
+        for msg in over_budget.iter() {
+            println!("{msg}");
+        }
+        return Err(other_err("firmware exceeds its size budget"));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_symbol_crate() {
+        assert_eq!(
+            symbol_crate("caliptra_drivers::sha384::Sha384::digest"),
+            "caliptra_drivers"
+        );
+        assert_eq!(
+            symbol_crate("<caliptra_drivers::CaliptraError as core::fmt::Debug>::fmt"),
+            "caliptra_drivers"
+        );
+        assert_eq!(
+            symbol_crate("<&mut caliptra_rom::Foo as core::fmt::Write>::write_str"),
+            "caliptra_rom"
+        );
+        assert_eq!(
+            symbol_crate("<[u8] as zerocopy::AsBytes>::as_bytes"),
+            "[unknown]"
+        );
+        assert_eq!(symbol_crate("memcpy"), "[unknown]");
+        assert_eq!(symbol_crate("_start"), "[unknown]");
+    }
+
+    #[test]
+    fn test_deltas() {
+        let base: BTreeMap<String, u64> = [("a", 10), ("b", 20), ("c", 30)]
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v))
+            .collect();
+        let head: BTreeMap<String, u64> = [("a", 10), ("b", 25), ("d", 100)]
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v))
+            .collect();
+        assert_eq!(
+            deltas(&base, &head),
+            vec![
+                Delta {
+                    name: "d",
+                    base: 0,
+                    head: 100
+                },
+                Delta {
+                    name: "c",
+                    base: 30,
+                    head: 0
+                },
+                Delta {
+                    name: "b",
+                    base: 20,
+                    head: 25
+                },
+            ]
+        );
+    }
+}
@@ -11,6 +11,7 @@ use serde::{Deserialize, Serialize};
 
 mod cache;
 mod cache_gha;
+mod diff;
 mod git;
 mod html;
 mod http;
@@ -54,6 +55,12 @@ fn main() {
 }
 
 fn real_main() -> io::Result<()> {
+    let mut args = env::args().skip(1).peekable();
+    if args.peek().map(String::as_str) == Some("diff") {
+        args.next();
+        return diff::run(args);
+    }
+
     let cache = GithubActionCache::new().map(box_cache).or_else(|e| {
         let fs_cache_path = "/tmp/caliptra-size-cache";
         println!(
@@ -0,0 +1,179 @@
+// Licensed under the Apache-2.0 license
+
+//! Reports the static worst-case stack depth of the ROM, FMC and runtime
+//! for each stack they use, and fails if any exceeds the size reserved for it
+//! in memory_layout.rs.
+
+use caliptra_builder::{firmware, FwId, StackPath, StackUsage};
+use caliptra_drivers::memory_layout::{
+    ESTACK_SIZE, NSTACK_SIZE, ROM_ESTACK_SIZE, ROM_NSTACK_SIZE, ROM_STACK_SIZE, STACK_SIZE,
+};
+
+struct Stack {
+    /// Symbol that switches to this stack
+    entry: &'static str,
+    size: u32,
+    size_name: &'static str,
+}
+
+struct Image {
+    name: &'static str,
+    fwid: &'static FwId<'static>,
+    stacks: [Stack; 3],
+}
+
+const IMAGES: [Image; 3] = [
+    Image {
+        name: "ROM",
+        fwid: &firmware::ROM_WITH_UART,
+        stacks: [
+            Stack {
+                entry: "_start",
+                size: ROM_STACK_SIZE,
+                size_name: "ROM_STACK_SIZE",
+            },
+            Stack {
+                entry: "_exception_handler",
+                size: ROM_ESTACK_SIZE,
+                size_name: "ROM_ESTACK_SIZE",
+            },
+            Stack {
+                entry: "_nmi_handler",
+                size: ROM_NSTACK_SIZE,
+                size_name: "ROM_NSTACK_SIZE",
+            },
+        ],
+    },
+    Image {
+        name: "FMC",
+        fwid: &firmware::FMC_WITH_UART,
+        stacks: [
+            Stack {
+                entry: "_start",
+                size: STACK_SIZE,
+                size_name: "STACK_SIZE",
+            },
+            Stack {
+                entry: "_trap_handler",
+                size: ESTACK_SIZE,
+                size_name: "ESTACK_SIZE",
+            },
+            Stack {
+                entry: "_nmi_handler",
+                size: NSTACK_SIZE,
+                size_name: "NSTACK_SIZE",
+            },
+        ],
+    },
+    Image {
+        name: "Runtime",
+        fwid: &firmware::APP_WITH_UART,
+        stacks: [
+            Stack {
+                entry: "_start",
+                size: STACK_SIZE,
+                size_name: "STACK_SIZE",
+            },
+            Stack {
+                entry: "_trap_handler",
+                size: ESTACK_SIZE,
+                size_name: "ESTACK_SIZE",
+            },
+            Stack {
+                entry: "_nmi_handler",
+                size: NSTACK_SIZE,
+                size_name: "NSTACK_SIZE",
+            },
+        ],
+    },
+];
+
+fn print_list(title: &str, items: &[String]) {
+    if items.is_empty() {
+        return;
+    }
+    println!("  {title}:");
+    for item in items {
+        println!("    {item}");
+    }
+}
+
+fn print_path(stack: &Stack, path: &StackPath) {
+    println!(
+        "{}: {} of {} bytes ({}){}",
+        stack.entry,
+        path.depth,
+        stack.size,
+        stack.size_name,
+        if path.is_bounded() {
+            ""
+        } else {
+            ", lower bound"
+        }
+    );
+    for frame in path.frames.iter() {
+        println!("  {:>6}  {}", frame.size, frame.name);
+    }
+    print_list("Indirect calls not followed", &path.indirect_calls);
+    print_list("Recursion", &path.recursion);
+    print_list("Non-constant stack adjustments", &path.dynamic_stack);
+    let unknown_calls: Vec<String> = path
+        .unknown_calls
+        .iter()
+        .map(|addr| format!("0x{addr:08x}"))
+        .collect();
+    print_list("Calls to unknown addresses", &unknown_calls);
+}
+
+fn usage() -> ! {
+    println!("Usage: stack-usage [--elf <name> <file>]...");
+    std::process::exit(1);
+}
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    // Prebuilt ELF files, keyed by image name
+    let mut elf_files = vec![];
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--elf" => {
+                let (Some(name), Some(path)) = (args.next(), args.next()) else {
+                    usage()
+                };
+                elf_files.push((name, path));
+            }
+            _ => usage(),
+        }
+    }
+
+    let mut overflows = vec![];
+    for image in IMAGES.iter() {
+        let elf_bytes = match elf_files.iter().find(|(name, _)| name == image.name) {
+            Some((_, path)) => std::fs::read(path).unwrap(),
+            None => caliptra_builder::build_firmware_elf(image.fwid)
+                .unwrap()
+                .to_vec(),
+        };
+        let usage = StackUsage::from_elf(&elf_bytes).unwrap();
+
+        println!("== {} ({}) ==", image.name, image.fwid.crate_name);
+        for stack in image.stacks.iter() {
+            let path = usage.worst_path(stack.entry).unwrap();
+            print_path(stack, &path);
+            if path.depth > stack.size {
+                overflows.push(format!(
+                    "{} {} needs {} bytes, but {} is {}",
+                    image.name, stack.entry, path.depth, stack.size_name, stack.size
+                ));
+            }
+        }
+        println!();
+    }
+
+    for overflow in overflows.iter() {
+        println!("{overflow}");
+    }
+    if !overflows.is_empty() {
+        std::process::exit(2);
+    }
+}
@@ -16,3 +16,14 @@ pub const FLAG_BIT_NOT_CONFIGURED: u32 = 1 << 0;
 pub const FLAG_BIT_NOT_SECURE: u32 = 1 << 1;
 pub const FLAG_BIT_DEBUG: u32 = 1 << 3;
 pub const FLAG_BIT_FIXED_WIDTH: u32 = 1 << 31;
+
+/// Encode a firmware version as the fixed-width hex string used in the
+/// TcbInfo `version` field of the FMC and RT alias certificates.
+pub fn tcb_info_version(version: u32) -> [u8; 8] {
+    const HEX_DIGITS: &[u8; 16] = b"0123456789ABCDEF";
+    let mut result = [0u8; 8];
+    for (i, digit) in result.iter_mut().enumerate() {
+        *digit = HEX_DIGITS[((version >> (28 - 4 * i)) & 0xF) as usize];
+    }
+    result
+}
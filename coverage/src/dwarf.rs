@@ -0,0 +1,151 @@
+// Licensed under the Apache-2.0 license
+
+use anyhow::Context;
+use elf::endian::AnyEndian;
+use elf::ElfBytes;
+use gimli::{EndianSlice, RunTimeEndian};
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+struct LineRange {
+    start: u64,
+    end: u64,
+    file: usize,
+    line: u32,
+}
+
+/// A source line an instruction was generated from
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct SourceLocation<'a> {
+    pub file: &'a str,
+    pub line: u32,
+}
+
+/// Maps instruction addresses back to source lines using the DWARF line
+/// programs of a firmware ELF.
+pub struct LineTable {
+    files: Vec<String>,
+
+    // Sorted by start address
+    ranges: Vec<LineRange>,
+}
+
+impl LineTable {
+    pub fn from_elf(elf_bytes: &[u8]) -> anyhow::Result<Self> {
+        let elf_file = ElfBytes::<AnyEndian>::minimal_parse(elf_bytes)
+            .with_context(|| "Failed to parse elf file")?;
+        let endian = match elf_file.ehdr.endianness {
+            AnyEndian::Big => RunTimeEndian::Big,
+            AnyEndian::Little => RunTimeEndian::Little,
+        };
+
+        // Code removed by the linker keeps its line info with a tombstone
+        // address, so only trust rows that land in .text.
+        let text = elf_file
+            .section_header_by_name(".text")
+            .with_context(|| "Failed to find .text section")?
+            .with_context(|| ".text section not found")?;
+        let text_range = text.sh_addr..text.sh_addr + text.sh_size;
+
+        let dwarf = gimli::Dwarf::load(|id| -> anyhow::Result<_> {
+            let data = match elf_file
+                .section_header_by_name(id.name())
+                .with_context(|| format!("Failed to find {} section", id.name()))?
+            {
+                Some(section) => {
+                    elf_file
+                        .section_data(&section)
+                        .with_context(|| format!("Failed to read {} section", id.name()))?
+                        .0
+                }
+                None => &[],
+            };
+            Ok(EndianSlice::new(data, endian))
+        })?;
+
+        let mut files = Vec::<String>::new();
+        let mut file_ids = HashMap::<String, usize>::new();
+        let mut ranges = Vec::<LineRange>::new();
+
+        let mut units = dwarf.units();
+        while let Some(header) = units.next()? {
+            let unit = dwarf.unit(header)?;
+            let Some(program) = unit.line_program.clone() else {
+                continue;
+            };
+            let comp_dir = unit
+                .comp_dir
+                .map(|dir| dir.to_string_lossy().into_owned())
+                .unwrap_or_default();
+
+            // Line program file index -> index into `files`
+            let mut unit_files = HashMap::<u64, usize>::new();
+            let mut prev: Option<(u64, usize, u32)> = None;
+
+            let mut rows = program.rows();
+            while let Some((header, row)) = rows.next_row()? {
+                if let Some((start, file, line)) = prev.take() {
+                    let end = row.address();
+                    if end > start && text_range.contains(&start) {
+                        ranges.push(LineRange {
+                            start,
+                            end,
+                            file,
+                            line,
+                        });
+                    }
+                }
+                if row.end_sequence() {
+                    continue;
+                }
+                // Line 0 marks compiler generated code with no source line
+                let Some(line) = row.line() else {
+                    continue;
+                };
+
+                let file = match unit_files.entry(row.file_index()) {
+                    Entry::Occupied(e) => *e.get(),
+                    Entry::Vacant(e) => {
+                        let Some(entry) = row.file(header) else {
+                            continue;
+                        };
+                        let mut path = PathBuf::from(&comp_dir);
+                        if let Some(dir) = entry.directory(header) {
+                            path.push(&*dwarf.attr_string(&unit, dir)?.to_string_lossy());
+                        }
+                        path.push(
+                            &*dwarf
+                                .attr_string(&unit, entry.path_name())?
+                                .to_string_lossy(),
+                        );
+                        let path = path.to_string_lossy().into_owned();
+
+                        let id = *file_ids.entry(path.clone()).or_insert_with(|| {
+                            files.push(path);
+                            files.len() - 1
+                        });
+                        *e.insert(id)
+                    }
+                };
+                prev = Some((row.address(), file, line.get() as u32));
+            }
+        }
+        ranges.sort_by_key(|range| range.start);
+
+        Ok(Self { files, ranges })
+    }
+
+    /// Find the source line an instruction was generated from
+    pub fn lookup(&self, pc: u64) -> Option<SourceLocation<'_>> {
+        let idx = self.ranges.partition_point(|range| range.start <= pc);
+        let range = self.ranges.get(idx.checked_sub(1)?)?;
+        if pc >= range.end {
+            return None;
+        }
+        Some(SourceLocation {
+            file: &self.files[range.file],
+            line: range.line,
+        })
+    }
+}
@@ -3,8 +3,12 @@
 use anyhow::Context;
 use bit_vec::BitVec;
 use caliptra_builder::{build_firmware_elf, FwId, SymbolType};
+use caliptra_drivers::memory_layout::{ICCM_ORG, ICCM_SIZE};
+use caliptra_image_elf::ElfExecutable;
+use caliptra_image_gen::ImageGeneratorExecutable;
 use elf::endian::AnyEndian;
 use elf::ElfBytes;
+use serde::{Deserialize, Serialize};
 use std::collections::hash_map::{DefaultHasher, Entry};
 use std::collections::{HashMap, HashSet};
 use std::fs::File;
@@ -13,10 +17,53 @@ use std::io::{BufRead, BufReader, BufWriter, Write};
 use std::path::{Path, PathBuf};
 
 mod disasm;
+mod dwarf;
+mod report;
 pub use disasm::invoke_objdump;
+pub use dwarf::{LineTable, SourceLocation};
+pub use report::{CoverageSummary, SourceCoverage};
 
 pub const CPTRA_COVERAGE_PATH: &str = "CPTRA_COVERAGE_PATH";
 
+/// Directory to write `lcov.info` and `cobertura.xml` to
+pub const CPTRA_COVERAGE_REPORT_PATH: &str = "CPTRA_COVERAGE_REPORT_PATH";
+
+/// Conditional branch outcomes recorded by the emulator, indexed by the byte
+/// offset of the branch instruction from the start of the region.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct BranchCoverage {
+    pub taken: BitVec,
+    pub not_taken: BitVec,
+}
+
+pub struct BranchCoverageMap {
+    pub map: HashMap<u64, BranchCoverage>,
+}
+
+impl BranchCoverageMap {
+    pub fn new(paths: Vec<PathBuf>) -> Self {
+        let mut map = HashMap::<u64, BranchCoverage>::default();
+        for path in paths {
+            let Some(tag) = get_tag_from_path(&path, ".branches") else {
+                continue;
+            };
+            let Ok(branches) = read_branch_coverage_from_file(&path) else {
+                continue;
+            };
+            match map.entry(tag) {
+                Entry::Vacant(e) => {
+                    e.insert(branches);
+                }
+                Entry::Occupied(mut e) => {
+                    e.get_mut().taken.or(&branches.taken);
+                    e.get_mut().not_taken.or(&branches.not_taken);
+                }
+            }
+        }
+        Self { map }
+    }
+}
+
 pub struct CoverageMap {
     pub map: HashMap<u64, BitVec>,
 }
@@ -41,42 +88,56 @@ impl CoverageMap {
 }
 pub struct CoverageMapEntry(u64, BitVec);
 pub fn get_entry_from_path(path: &PathBuf) -> Option<CoverageMapEntry> {
-    let filename = path.file_name().and_then(|val| val.to_str());
-    if let Some(filename) = filename {
-        let prefix = filename
-            .split('-')
-            .nth(1)
-            .and_then(|val| val.strip_suffix(".bitvec"));
-
-        if let Some(prefix) = prefix {
-            if let Ok(tag) = prefix.parse() {
-                if let Ok(bitmap) = read_bitvec_from_file(path) {
-                    return Some(CoverageMapEntry(tag, bitmap));
-                }
-            }
-        }
-    }
-    None
+    let tag = get_tag_from_path(path, ".bitvec")?;
+    let bitmap = read_bitvec_from_file(path).ok()?;
+    Some(CoverageMapEntry(tag, bitmap))
 }
 
-pub fn dump_emu_coverage_to_file(
+fn get_tag_from_path(path: &Path, suffix: &str) -> Option<u64> {
+    let filename = path.file_name().and_then(|val| val.to_str())?;
+    filename
+        .split('-')
+        .nth(1)
+        .and_then(|val| val.strip_suffix(suffix))
+        .and_then(|val| val.parse().ok())
+}
+
+fn dump_to_file(
     coverage_path: &str,
+    prefix: &str,
     tag: u64,
-    bitmap: &BitVec,
+    suffix: &str,
+    value: &impl serde::Serialize,
 ) -> std::io::Result<()> {
-    let mut filename = format!("CovData{}", hex::encode(rand::random::<[u8; 16]>()));
+    let mut filename = format!("{prefix}{}", hex::encode(rand::random::<[u8; 16]>()));
     filename.push_str(&'-'.to_string());
     filename.push_str(&tag.to_string());
-    filename.push_str(".bitvec");
+    filename.push_str(suffix);
 
     let path = std::path::Path::new(coverage_path).join(filename);
 
     let file = File::create(path)?;
     let mut writer = BufWriter::new(file);
-    serde_json::to_writer(&mut writer, &bitmap)?;
+    serde_json::to_writer(&mut writer, value)?;
     writer.flush()?;
     Ok(())
 }
+
+pub fn dump_emu_coverage_to_file(
+    coverage_path: &str,
+    tag: u64,
+    bitmap: &BitVec,
+) -> std::io::Result<()> {
+    dump_to_file(coverage_path, "CovData", tag, ".bitvec", bitmap)
+}
+
+pub fn dump_emu_branch_coverage_to_file(
+    coverage_path: &str,
+    tag: u64,
+    branches: &BranchCoverage,
+) -> std::io::Result<()> {
+    dump_to_file(coverage_path, "BranchData", tag, ".branches", branches)
+}
 pub fn uncovered_functions<'a>(
     base_addr: usize,
     elf_bytes: &'a [u8],
@@ -103,14 +164,25 @@ pub fn uncovered_functions<'a>(
 }
 
 pub fn get_bitvec_paths(dir: &str) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
+    get_paths_with_extension(dir, "bitvec")
+}
+
+pub fn get_branch_paths(dir: &str) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
+    get_paths_with_extension(dir, "branches")
+}
+
+fn get_paths_with_extension(
+    dir: &str,
+    extension: &str,
+) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
     let paths = std::fs::read_dir(dir)?
         // Filter out all those directory entries which couldn't be read
         .filter_map(|res| res.ok())
         // Map the directory entries to paths
         .map(|dir_entry| dir_entry.path())
-        // Filter out all paths with extensions other than `bitvec`
+        // Filter out all paths with other extensions
         .filter_map(|path| {
-            if path.extension().map_or(false, |ext| ext == "bitvec") {
+            if path.extension().map_or(false, |ext| ext == extension) {
                 Some(path)
             } else {
                 None
@@ -134,19 +206,78 @@ pub fn read_bitvec_from_file<P: AsRef<Path>>(
     Ok(coverage)
 }
 
+pub fn read_branch_coverage_from_file<P: AsRef<Path>>(
+    path: P,
+) -> Result<BranchCoverage, Box<dyn std::error::Error>> {
+    let file = File::open(path)?;
+    let reader = BufReader::new(file);
+    Ok(serde_json::from_reader(reader)?)
+}
+
 pub fn get_tag_from_image(image: &[u8]) -> u64 {
     let mut hasher = DefaultHasher::new();
     std::hash::Hash::hash_slice(image, &mut hasher);
     hasher.finish()
 }
 
+/// Returns the tag the emulator records coverage under for a firmware ID
+///
+/// Images that run from ROM are tagged with the hash of the ROM. FMC and
+/// runtime images are tagged with the hash of their content as it appears
+/// in a firmware bundle.
 pub fn get_tag_from_fw_id(id: &FwId<'static>) -> Option<u64> {
-    if let Ok(rom) = caliptra_builder::build_firmware_rom(id) {
+    let elf_bytes = build_firmware_elf(id).ok()?;
+    get_tag_from_elf(&elf_bytes)
+}
+
+/// Tag of the image the emulator runs for this ELF. Only the loaded bytes
+/// count, so ELFs that differ only in debug info have the same tag.
+pub fn get_tag_from_elf(elf_bytes: &[u8]) -> Option<u64> {
+    if is_iccm_image(elf_bytes) {
+        let executable = ElfExecutable::new(elf_bytes, 0, 0, Default::default()).ok()?;
+        return Some(get_tag_from_image(executable.content()));
+    }
+    if let Ok(rom) = caliptra_builder::elf2rom(elf_bytes) {
         return Some(get_tag_from_image(&rom));
     }
     None
 }
 
+/// Returns true if the ELF is loaded into ICCM rather than ROM
+pub fn is_iccm_image(elf_bytes: &[u8]) -> bool {
+    let Ok(elf_file) = ElfBytes::<AnyEndian>::minimal_parse(elf_bytes) else {
+        return false;
+    };
+    let load_addr = elf_file.segments().and_then(|segments| {
+        segments
+            .iter()
+            .filter(|s| s.p_type == elf::abi::PT_LOAD)
+            .map(|s| s.p_paddr)
+            .min()
+    });
+    load_addr
+        .is_some_and(|addr| (ICCM_ORG as u64..ICCM_ORG as u64 + ICCM_SIZE as u64).contains(&addr))
+}
+
+/// Clear the bits of an ICCM bitmap outside of one loaded image
+///
+/// # Arguments
+///
+/// * `bitmap` - Bitmap covering all of ICCM
+/// * `load_addr` - Address the image was loaded to
+/// * `size` - Size of the image
+pub fn mask_iccm_bitmap(bitmap: &BitVec, load_addr: u32, size: u32) -> BitVec {
+    let start = (load_addr as usize).saturating_sub(ICCM_ORG as usize);
+    let end = start.saturating_add(size as usize).min(bitmap.len());
+    let mut result = BitVec::from_elem(bitmap.len(), false);
+    for index in start..end {
+        if bitmap[index] {
+            result.set(index, true);
+        }
+    }
+    result
+}
+
 pub fn collect_instr_pcs(id: &FwId<'static>) -> anyhow::Result<Vec<u32>> {
     let elf_bytes = build_firmware_elf(id)?;
 
@@ -176,6 +307,55 @@ pub fn collect_instr_pcs(id: &FwId<'static>) -> anyhow::Result<Vec<u32>> {
     Ok(instr_pcs)
 }
 
+/// An instruction in the .text section of a firmware ELF
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct TextInstr {
+    pub pc: u32,
+    pub len: u32,
+    pub is_cond_branch: bool,
+}
+
+/// Returns true for `beq`, `bne`, `blt`, `bge`, `bltu`, `bgeu`, `c.beqz` and
+/// `c.bnez`. Must match `CodeCoverage::log_branch` in the emulator.
+pub fn is_cond_branch(instr: u32, len: u32) -> bool {
+    if len == 2 {
+        instr & 0b11 == 0b01 && (instr >> 13) & 0b111 >= 0b110
+    } else {
+        instr & 0x7f == 0b110_0011
+    }
+}
+
+pub fn decode_text_instrs(elf_bytes: &[u8]) -> anyhow::Result<Vec<TextInstr>> {
+    let elf_file = ElfBytes::<AnyEndian>::minimal_parse(elf_bytes)
+        .with_context(|| "Failed to parse elf file")?;
+
+    let (load_addr, text_section) = read_section(&elf_file, ".text", true)?;
+
+    let mut index = 0_usize;
+    let mut instrs = Vec::<TextInstr>::new();
+
+    while index + 2 <= text_section.len() {
+        let low = u16::from_le_bytes([text_section[index], text_section[index + 1]]);
+        let (instr, len) = match low & 0b11 {
+            0..=2 => (low.into(), 2),
+            _ => match text_section.get(index + 2..index + 4) {
+                Some(high) => (
+                    u32::from(low) | (u32::from(u16::from_le_bytes([high[0], high[1]])) << 16),
+                    4,
+                ),
+                None => break,
+            },
+        };
+        instrs.push(TextInstr {
+            pc: load_addr + index as u32,
+            len,
+            is_cond_branch: is_cond_branch(instr, len),
+        });
+        index += len as usize;
+    }
+    Ok(instrs)
+}
+
 /// Read a section from ELF file
 fn read_section<'a>(
     elf_file: &'a ElfBytes<AnyEndian>,
@@ -314,3 +494,70 @@ fn test_coverage_map_creation_data_files() {
     let cv = CoverageMap::new(paths);
     assert!(cv.map.get(&tag).is_some());
 }
+
+#[test]
+fn test_is_cond_branch() {
+    // beq x0, x0, 8
+    assert!(is_cond_branch(0x0000_0463, 4));
+    // bgeu a0, a1, -4
+    assert!(is_cond_branch(0xfeb5_7ee3, 4));
+    // jal x0, 8
+    assert!(!is_cond_branch(0x0080_006f, 4));
+    // c.beqz a0, 4
+    assert!(is_cond_branch(0xc111, 2));
+    // c.bnez x8, 4
+    assert!(is_cond_branch(0xe011, 2));
+    // c.j 4
+    assert!(!is_cond_branch(0xa011, 2));
+}
+
+#[test]
+fn test_branch_coverage_map() {
+    let dir = std::env::temp_dir().join(format!(
+        "caliptra-branch-coverage-{}",
+        hex::encode(rand::random::<[u8; 8]>())
+    ));
+    std::fs::create_dir(&dir).unwrap();
+    let dir_str = dir.to_str().unwrap();
+
+    let tag = 456_u64;
+    let mut branches = BranchCoverage {
+        taken: BitVec::from_elem(16, false),
+        not_taken: BitVec::from_elem(16, false),
+    };
+    branches.taken.set(4, true);
+    dump_emu_branch_coverage_to_file(dir_str, tag, &branches).unwrap();
+    branches.taken.set(4, false);
+    branches.not_taken.set(8, true);
+    dump_emu_branch_coverage_to_file(dir_str, tag, &branches).unwrap();
+    dump_emu_coverage_to_file(dir_str, tag, &BitVec::from_elem(16, true)).unwrap();
+
+    let paths = get_branch_paths(dir_str).unwrap();
+    assert_eq!(paths.len(), 2);
+    let map = BranchCoverageMap::new(paths);
+    let merged = &map.map[&tag];
+    assert!(merged.taken[4]);
+    assert!(merged.not_taken[8]);
+    assert!(!merged.taken[8]);
+
+    // Branch data must not be mistaken for instruction bitmaps
+    assert!(CoverageMap::new(get_bitvec_paths(dir_str).unwrap())
+        .map
+        .contains_key(&tag));
+    assert_eq!(get_bitvec_paths(dir_str).unwrap().len(), 1);
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_mask_iccm_bitmap() {
+    let bitmap = BitVec::from_elem(32, true);
+    let masked = mask_iccm_bitmap(&bitmap, ICCM_ORG + 8, 4);
+    let set: Vec<usize> = (0..32).filter(|&i| masked[i]).collect();
+    assert_eq!(set, vec![8, 9, 10, 11]);
+
+    // Images running past the end of ICCM are clipped
+    let masked = mask_iccm_bitmap(&bitmap, ICCM_ORG + 30, 16);
+    assert_eq!(masked.len(), 32);
+    assert!(masked[31] && !masked[29]);
+}
@@ -1,22 +1,30 @@
 // Licensed under the Apache-2.0 license
 
+use std::collections::HashMap;
+
 use bit_vec::BitVec;
+use caliptra_builder::build_firmware_coverage_elfs;
 use caliptra_builder::build_firmware_elf;
-use caliptra_builder::firmware::APP_WITH_UART;
-use caliptra_builder::firmware::FMC_WITH_UART;
+use caliptra_builder::firmware::REGISTERED_FW;
+use caliptra_builder::FwId;
 use caliptra_coverage::calculator;
 use caliptra_coverage::collect_instr_pcs;
 use caliptra_coverage::get_bitvec_paths;
+use caliptra_coverage::get_branch_paths;
+use caliptra_coverage::is_iccm_image;
+use caliptra_coverage::BranchCoverage;
+use caliptra_coverage::BranchCoverageMap;
 use caliptra_coverage::CoverageMap;
+use caliptra_coverage::SourceCoverage;
 use caliptra_coverage::CPTRA_COVERAGE_PATH;
+use caliptra_coverage::CPTRA_COVERAGE_REPORT_PATH;
 
-use caliptra_builder::firmware::ROM_WITH_UART;
+use caliptra_coverage::get_tag_from_elf;
 use caliptra_coverage::get_tag_from_fw_id;
 use caliptra_coverage::invoke_objdump;
 use caliptra_coverage::uncovered_functions;
 use caliptra_drivers::memory_layout::ICCM_ORG;
 use caliptra_drivers::memory_layout::ROM_ORG;
-use caliptra_image_types::IMAGE_MANIFEST_BYTE_SIZE;
 
 pub fn highlight_covered_instructions_in_objdump_output(
     base_address: usize,
@@ -49,105 +57,184 @@ pub fn highlight_covered_instructions_in_objdump_output(
     }
 }
 
-fn main() -> std::io::Result<()> {
-    let cov_path = std::env::var(CPTRA_COVERAGE_PATH).unwrap_or_else(|_| "".into());
-    if cov_path.is_empty() {
-        return Ok(());
-    }
+fn print_source_coverage(name: &str, coverage: &SourceCoverage) {
+    let summary = coverage.summary();
+    println!(
+        "{name} line coverage is {:.2}% ({}/{})",
+        100.0 * summary.line_rate(),
+        summary.lines_hit,
+        summary.lines_found
+    );
+    println!(
+        "{name} branch coverage is {:.2}% ({}/{} outcomes)",
+        100.0 * summary.branch_rate(),
+        summary.branches_hit,
+        summary.branches_found
+    );
+    println!(
+        "{name} function coverage is {:.2}% ({}/{})",
+        100.0 * summary.function_rate(),
+        summary.functions_hit,
+        summary.functions_found
+    );
+}
 
-    let paths = get_bitvec_paths(cov_path.as_str()).unwrap();
-    if paths.is_empty() {
-        println!("{} coverage files found", paths.len());
-        return Ok(());
+fn write_reports(report_path: &str, mut coverage: SourceCoverage) -> std::io::Result<()> {
+    if let Some(workspace_dir) = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).parent() {
+        coverage.strip_prefix(workspace_dir);
     }
+    std::fs::create_dir_all(report_path)?;
+    let report_path = std::path::Path::new(report_path);
 
-    let tag = get_tag_from_fw_id(&ROM_WITH_UART).unwrap();
-
-    println!("{} coverage files found", paths.len());
-    let instr_pcs = collect_instr_pcs(&ROM_WITH_UART).unwrap();
-    println!("ROM instruction count = {}", instr_pcs.len());
+    let mut lcov = std::io::BufWriter::new(std::fs::File::create(report_path.join("lcov.info"))?);
+    coverage.write_lcov("caliptra", &mut lcov)?;
 
-    let cv = CoverageMap::new(paths);
-    let bv = cv
-        .map
-        .get(&tag)
-        .expect("Coverage data  not found for image");
+    let mut cobertura =
+        std::io::BufWriter::new(std::fs::File::create(report_path.join("cobertura.xml"))?);
+    coverage.write_cobertura("caliptra", &mut cobertura)?;
 
-    let elf_bytes = build_firmware_elf(&ROM_WITH_UART)?;
+    println!("Coverage reports written to {}", report_path.display());
+    Ok(())
+}
 
-    uncovered_functions(ROM_ORG as usize, &elf_bytes, bv)?;
+fn report_image(
+    tag: u64,
+    id: &FwId<'static>,
+    bitmap: &BitVec,
+    branches: Option<&BranchCoverage>,
+    coverage_elf: Option<&[u8]>,
+) -> std::io::Result<SourceCoverage> {
+    let name = id.elf_filename();
+    println!("////////////////////////////////////");
+    println!("Coverage report for {name}");
+    println!("////////////////////////////////////");
+
+    let elf_bytes = build_firmware_elf(id)?;
+    let base_addr = if is_iccm_image(&elf_bytes) {
+        ICCM_ORG
+    } else {
+        ROM_ORG
+    } as usize;
 
+    let instr_pcs = collect_instr_pcs(id).unwrap();
+    println!("{name} instruction count = {}", instr_pcs.len());
     println!(
-        "Coverage for ROM_WITH_UART is {}%",
-        (100 * calculator::coverage_from_bitmap(ROM_ORG as usize, bv, &instr_pcs)) as f32
+        "Coverage % is {}%",
+        (100 * calculator::coverage_from_bitmap(base_addr, bitmap, &instr_pcs)) as f32
             / instr_pcs.len() as f32
     );
 
+    uncovered_functions(base_addr, &elf_bytes, bitmap)?;
+
+    // The firmware profile has no line tables, so map PCs to source with the
+    // firmware-coverage build, provided it loads the exact same image.
+    let coverage = match coverage_elf {
+        Some(coverage_elf) if get_tag_from_elf(coverage_elf) == Some(tag) => {
+            let coverage =
+                SourceCoverage::from_image(tag, coverage_elf, base_addr, bitmap, branches).unwrap();
+            print_source_coverage(&name, &coverage);
+            coverage
+        }
+        Some(_) => {
+            println!(
+                "{name} firmware-coverage build differs from the tested image; no source coverage"
+            );
+            SourceCoverage::default()
+        }
+        None => {
+            println!("{name} has no firmware-coverage build; no source coverage");
+            SourceCoverage::default()
+        }
+    };
+
     if let Some(fw_dir) = std::env::var_os("CALIPTRA_PREBUILT_FW_DIR") {
-        let path = std::path::PathBuf::from(fw_dir).join(ROM_WITH_UART.elf_filename());
+        let path = std::path::PathBuf::from(fw_dir).join(&name);
 
         let objdump_output = invoke_objdump(&path.to_string_lossy());
         highlight_covered_instructions_in_objdump_output(
-            caliptra_drivers::memory_layout::ROM_ORG as usize,
-            bv,
+            base_addr,
+            bitmap,
             objdump_output.unwrap(),
         );
     } else {
         println!("Prebuilt firmware not found");
     }
 
-    let iccm_image_tag = {
-        let image = caliptra_builder::build_and_sign_image(
-            &FMC_WITH_UART,
-            &APP_WITH_UART,
-            caliptra_builder::ImageOptions {
-                fmc_version: caliptra_builder::version::get_fmc_version(),
-                app_version: caliptra_builder::version::get_runtime_version(),
-                ..Default::default()
-            },
-        )
-        .unwrap();
+    Ok(coverage)
+}
 
-        let image = image.to_bytes().unwrap();
-        let iccm_image = &image.as_slice()[IMAGE_MANIFEST_BYTE_SIZE..];
+fn main() -> std::io::Result<()> {
+    let cov_path = std::env::var(CPTRA_COVERAGE_PATH).unwrap_or_else(|_| "".into());
+    if cov_path.is_empty() {
+        return Ok(());
+    }
 
-        caliptra_coverage::get_tag_from_image(iccm_image)
+    let paths = get_bitvec_paths(cov_path.as_str()).unwrap();
+    println!("{} coverage files found", paths.len());
+    if paths.is_empty() {
+        return Ok(());
+    }
+
+    let cv = CoverageMap::new(paths);
+    let branches = BranchCoverageMap::new(get_branch_paths(cov_path.as_str()).unwrap());
+
+    // Work out which ROM, FMC or runtime image each set of coverage data came
+    // from. Bitmaps from every test that ran the same image are already
+    // merged by CoverageMap.
+    let mut fw_ids = Vec::<(u64, &FwId<'static>)>::new();
+    for &id in REGISTERED_FW {
+        if let Some(tag) = get_tag_from_fw_id(id) {
+            if !fw_ids.iter().any(|(t, _)| *t == tag) {
+                fw_ids.push((tag, id));
+            }
+        }
+    }
+
+    // Build every covered image with line tables in one go, so cargo only
+    // runs once per crate and feature set.
+    let covered: Vec<(u64, &FwId<'static>)> = fw_ids
+        .iter()
+        .copied()
+        .filter(|(tag, _)| cv.map.contains_key(tag))
+        .collect();
+    let covered_ids: Vec<&FwId> = covered.iter().map(|(_, id)| *id).collect();
+    let coverage_elfs: HashMap<u64, Vec<u8>> = match build_firmware_coverage_elfs(&covered_ids) {
+        Ok(elfs) => covered
+            .iter()
+            .map(|(tag, _)| *tag)
+            .zip(elfs.into_iter().map(|(_, elf)| elf))
+            .collect(),
+        Err(e) => {
+            println!("firmware-coverage build failed: {e}; no source coverage");
+            HashMap::new()
+        }
     };
-    let iccm_bitmap = cv
-        .map
-        .get(&iccm_image_tag)
-        .expect("Coverage data not found for ICCM image");
-
-    let iccm_images = vec![&FMC_WITH_UART, &APP_WITH_UART];
-
-    for e in iccm_images {
-        println!("////////////////////////////////////");
-        println!("Coverage report for {}", e.bin_name);
-        println!("////////////////////////////////////");
-        let instr_pcs = collect_instr_pcs(e).unwrap();
-        println!("{} instruction count = {}", e.bin_name, instr_pcs.len());
-        println!(
-            "Coverage % is {}%",
-            (100 * calculator::coverage_from_bitmap(ICCM_ORG as usize, iccm_bitmap, &instr_pcs))
-                as f32
-                / instr_pcs.len() as f32
-        );
 
-        let elf_bytes = build_firmware_elf(e)?;
-        uncovered_functions(ICCM_ORG as usize, &elf_bytes, iccm_bitmap)?;
+    let mut suite_coverage = SourceCoverage::default();
+    for (tag, id) in covered.iter() {
+        suite_coverage.merge(report_image(
+            *tag,
+            id,
+            &cv.map[tag],
+            branches.map.get(tag),
+            coverage_elfs.get(tag).map(Vec::as_slice),
+        )?);
+    }
 
-        if let Some(fw_dir) = std::env::var_os("CALIPTRA_PREBUILT_FW_DIR") {
-            let path = std::path::PathBuf::from(fw_dir).join(e.elf_filename());
+    let unknown = cv
+        .map
+        .keys()
+        .filter(|tag| !fw_ids.iter().any(|(t, _)| t == *tag))
+        .count();
+    if unknown > 0 {
+        println!("{unknown} images with coverage data are not in REGISTERED_FW");
+    }
 
-            let objdump_output = invoke_objdump(&path.to_string_lossy());
-            highlight_covered_instructions_in_objdump_output(
-                ICCM_ORG as usize,
-                iccm_bitmap,
-                objdump_output.unwrap(),
-            );
-        } else {
-            println!("Prebuilt firmware not found");
-        }
+    println!("////////////////////////////////////");
+    print_source_coverage("Test suite", &suite_coverage);
+
+    if let Ok(report_path) = std::env::var(CPTRA_COVERAGE_REPORT_PATH) {
+        write_reports(&report_path, suite_coverage)?;
     }
 
     Ok(())
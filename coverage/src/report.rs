@@ -0,0 +1,491 @@
+// Licensed under the Apache-2.0 license
+
+use crate::dwarf::LineTable;
+use crate::{decode_text_instrs, BranchCoverage};
+use bit_vec::BitVec;
+use caliptra_builder::SymbolType;
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+struct BranchOutcome {
+    taken: bool,
+    not_taken: bool,
+}
+
+#[derive(Debug, Default, Eq, PartialEq)]
+struct FunctionCoverage {
+    line: u32,
+    hits: u64,
+}
+
+#[derive(Debug, Default, Eq, PartialEq)]
+struct FileCoverage {
+    // Line -> hits
+    lines: BTreeMap<u32, u64>,
+
+    // Line -> (image tag, branch instruction address) -> outcomes seen. The
+    // same address can hold different code in different images.
+    branches: BTreeMap<u32, BTreeMap<(u64, u32), BranchOutcome>>,
+
+    // Demangled name -> coverage
+    functions: BTreeMap<String, FunctionCoverage>,
+}
+
+impl FileCoverage {
+    fn summary(&self) -> CoverageSummary {
+        let outcomes = self.branches.values().flat_map(|b| b.values());
+        CoverageSummary {
+            lines_found: self.lines.len(),
+            lines_hit: self.lines.values().filter(|&&hits| hits > 0).count(),
+            branches_found: 2 * outcomes.clone().count(),
+            branches_hit: outcomes
+                .map(|o| o.taken as usize + o.not_taken as usize)
+                .sum(),
+            functions_found: self.functions.len(),
+            functions_hit: self.functions.values().filter(|f| f.hits > 0).count(),
+        }
+    }
+}
+
+/// Totals across the files of a `SourceCoverage`. Each conditional branch
+/// counts as two outcomes: taken and not taken.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct CoverageSummary {
+    pub lines_found: usize,
+    pub lines_hit: usize,
+    pub branches_found: usize,
+    pub branches_hit: usize,
+    pub functions_found: usize,
+    pub functions_hit: usize,
+}
+
+impl CoverageSummary {
+    fn add(&mut self, other: &Self) {
+        self.lines_found += other.lines_found;
+        self.lines_hit += other.lines_hit;
+        self.branches_found += other.branches_found;
+        self.branches_hit += other.branches_hit;
+        self.functions_found += other.functions_found;
+        self.functions_hit += other.functions_hit;
+    }
+
+    pub fn line_rate(&self) -> f64 {
+        rate(self.lines_hit, self.lines_found)
+    }
+
+    pub fn branch_rate(&self) -> f64 {
+        rate(self.branches_hit, self.branches_found)
+    }
+
+    pub fn function_rate(&self) -> f64 {
+        rate(self.functions_hit, self.functions_found)
+    }
+}
+
+fn rate(hit: usize, found: usize) -> f64 {
+    if found == 0 {
+        1.0
+    } else {
+        hit as f64 / found as f64
+    }
+}
+
+fn is_set(bitmap: &BitVec, base_addr: usize, pc: u64) -> bool {
+    (pc as usize)
+        .checked_sub(base_addr)
+        .and_then(|index| bitmap.get(index))
+        .unwrap_or(false)
+}
+
+fn xml_escape(s: &str) -> String {
+    let mut result = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => result.push_str("&amp;"),
+            '<' => result.push_str("&lt;"),
+            '>' => result.push_str("&gt;"),
+            '"' => result.push_str("&quot;"),
+            '\'' => result.push_str("&apos;"),
+            _ => result.push(c),
+        }
+    }
+    result
+}
+
+/// Emulator coverage of one or more firmware images, mapped back to source
+/// lines through the DWARF info in the firmware ELFs.
+#[derive(Debug, Default, Eq, PartialEq)]
+pub struct SourceCoverage {
+    files: BTreeMap<String, FileCoverage>,
+}
+
+impl SourceCoverage {
+    /// Map the coverage bitmaps of a firmware image back to its source
+    ///
+    /// # Arguments
+    ///
+    /// * `tag` - Tag of the image, see `get_tag_from_elf`
+    /// * `elf_bytes` - Firmware ELF, built with line tables
+    /// * `base_addr` - Address of the first bit of the bitmaps
+    /// * `bitmap` - Executed instruction bytes
+    /// * `branches` - Conditional branch outcomes, if they were recorded
+    pub fn from_image(
+        tag: u64,
+        elf_bytes: &[u8],
+        base_addr: usize,
+        bitmap: &BitVec,
+        branches: Option<&BranchCoverage>,
+    ) -> anyhow::Result<Self> {
+        let line_table = LineTable::from_elf(elf_bytes)?;
+        let mut result = Self::default();
+
+        for instr in decode_text_instrs(elf_bytes)? {
+            let Some(loc) = line_table.lookup(instr.pc.into()) else {
+                continue;
+            };
+            let file = result.files.entry(loc.file.into()).or_default();
+
+            let hits = file.lines.entry(loc.line).or_default();
+            *hits = (*hits).max(is_set(bitmap, base_addr, instr.pc.into()) as u64);
+
+            if instr.is_cond_branch {
+                let outcome = file
+                    .branches
+                    .entry(loc.line)
+                    .or_default()
+                    .entry((tag, instr.pc))
+                    .or_default();
+                if let Some(branches) = branches {
+                    outcome.taken |= is_set(&branches.taken, base_addr, instr.pc.into());
+                    outcome.not_taken |= is_set(&branches.not_taken, base_addr, instr.pc.into());
+                }
+            }
+        }
+
+        let symbols = caliptra_builder::elf_symbols(elf_bytes)?;
+        for sym in symbols
+            .iter()
+            .filter(|sym| sym.ty == SymbolType::Func && sym.size > 0)
+        {
+            let Some(loc) = line_table.lookup(sym.value) else {
+                continue;
+            };
+            let hit = (sym.value..sym.value + sym.size).any(|pc| is_set(bitmap, base_addr, pc));
+            let name = format!("{:#}", rustc_demangle::demangle(sym.name));
+            let file = result.files.entry(loc.file.into()).or_default();
+            file.functions
+                .entry(name)
+                .or_insert(FunctionCoverage {
+                    line: loc.line,
+                    hits: 0,
+                })
+                .hits += hit as u64;
+        }
+
+        Ok(result)
+    }
+
+    /// Add the coverage of another image, or another run of the same image
+    pub fn merge(&mut self, other: SourceCoverage) {
+        for (path, other_file) in other.files {
+            let file = self.files.entry(path).or_default();
+            for (line, hits) in other_file.lines {
+                *file.lines.entry(line).or_default() += hits;
+            }
+            for (line, branches) in other_file.branches {
+                let line_branches = file.branches.entry(line).or_default();
+                for (key, outcome) in branches {
+                    let merged = line_branches.entry(key).or_default();
+                    merged.taken |= outcome.taken;
+                    merged.not_taken |= outcome.not_taken;
+                }
+            }
+            for (name, function) in other_file.functions {
+                file.functions
+                    .entry(name)
+                    .or_insert(FunctionCoverage {
+                        line: function.line,
+                        hits: 0,
+                    })
+                    .hits += function.hits;
+            }
+        }
+    }
+
+    /// Make source paths under `root` relative to it, so reports from
+    /// different checkouts line up.
+    pub fn strip_prefix(&mut self, root: &Path) {
+        let files = std::mem::take(&mut self.files);
+        for (path, file) in files {
+            let path = match Path::new(&path).strip_prefix(root) {
+                Ok(relative) => relative.to_string_lossy().into_owned(),
+                Err(_) => path,
+            };
+            let mut single = SourceCoverage::default();
+            single.files.insert(path, file);
+            self.merge(single);
+        }
+    }
+
+    pub fn summary(&self) -> CoverageSummary {
+        let mut summary = CoverageSummary::default();
+        for file in self.files.values() {
+            summary.add(&file.summary());
+        }
+        summary
+    }
+
+    /// Write an LCOV tracefile, as consumed by `genhtml` and most coverage
+    /// services.
+    pub fn write_lcov(&self, test_name: &str, w: &mut impl Write) -> std::io::Result<()> {
+        writeln!(w, "TN:{test_name}")?;
+        for (path, file) in self.files.iter() {
+            let summary = file.summary();
+            writeln!(w, "SF:{path}")?;
+
+            for (name, function) in file.functions.iter() {
+                writeln!(w, "FN:{},{name}", function.line)?;
+            }
+            for (name, function) in file.functions.iter() {
+                writeln!(w, "FNDA:{},{name}", function.hits)?;
+            }
+            writeln!(w, "FNF:{}", summary.functions_found)?;
+            writeln!(w, "FNH:{}", summary.functions_hit)?;
+
+            for (line, branches) in file.branches.iter() {
+                let line_hit = file.lines.get(line).is_some_and(|&hits| hits > 0);
+                for (block, outcome) in branches.values().enumerate() {
+                    for (branch, taken) in [outcome.taken, outcome.not_taken].iter().enumerate() {
+                        if line_hit {
+                            writeln!(w, "BRDA:{line},{block},{branch},{}", *taken as u32)?;
+                        } else {
+                            writeln!(w, "BRDA:{line},{block},{branch},-")?;
+                        }
+                    }
+                }
+            }
+            writeln!(w, "BRF:{}", summary.branches_found)?;
+            writeln!(w, "BRH:{}", summary.branches_hit)?;
+
+            for (line, hits) in file.lines.iter() {
+                writeln!(w, "DA:{line},{hits}")?;
+            }
+            writeln!(w, "LF:{}", summary.lines_found)?;
+            writeln!(w, "LH:{}", summary.lines_hit)?;
+            writeln!(w, "end_of_record")?;
+        }
+        Ok(())
+    }
+
+    /// Write a Cobertura XML report, as consumed by CI dashboards.
+    pub fn write_cobertura(&self, package_name: &str, w: &mut impl Write) -> std::io::Result<()> {
+        let summary = self.summary();
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+
+        writeln!(w, r#"<?xml version="1.0" ?>"#)?;
+        writeln!(
+            w,
+            r#"<!DOCTYPE coverage SYSTEM "http://cobertura.sourceforge.net/xml/coverage-04.dtd">"#
+        )?;
+        writeln!(
+            w,
+            r#"<coverage line-rate="{:.4}" branch-rate="{:.4}" lines-covered="{}" lines-valid="{}" branches-covered="{}" branches-valid="{}" complexity="0" version="{}" timestamp="{timestamp}">"#,
+            summary.line_rate(),
+            summary.branch_rate(),
+            summary.lines_hit,
+            summary.lines_found,
+            summary.branches_hit,
+            summary.branches_found,
+            env!("CARGO_PKG_VERSION"),
+        )?;
+        writeln!(w, "  <sources>")?;
+        writeln!(w, "    <source>.</source>")?;
+        writeln!(w, "  </sources>")?;
+        writeln!(w, "  <packages>")?;
+        writeln!(
+            w,
+            r#"    <package name="{}" line-rate="{:.4}" branch-rate="{:.4}" complexity="0">"#,
+            xml_escape(package_name),
+            summary.line_rate(),
+            summary.branch_rate(),
+        )?;
+        writeln!(w, "      <classes>")?;
+        for (path, file) in self.files.iter() {
+            let file_summary = file.summary();
+            let path = xml_escape(path);
+            writeln!(
+                w,
+                r#"        <class name="{path}" filename="{path}" line-rate="{:.4}" branch-rate="{:.4}" complexity="0">"#,
+                file_summary.line_rate(),
+                file_summary.branch_rate(),
+            )?;
+
+            writeln!(w, "          <methods>")?;
+            for (name, function) in file.functions.iter() {
+                writeln!(
+                    w,
+                    r#"            <method name="{}" signature="" line-rate="{}" branch-rate="1">"#,
+                    xml_escape(name),
+                    (function.hits > 0) as u32,
+                )?;
+                writeln!(w, "              <lines>")?;
+                writeln!(
+                    w,
+                    r#"                <line number="{}" hits="{}" branch="false"/>"#,
+                    function.line, function.hits
+                )?;
+                writeln!(w, "              </lines>")?;
+                writeln!(w, "            </method>")?;
+            }
+            writeln!(w, "          </methods>")?;
+
+            writeln!(w, "          <lines>")?;
+            for (line, hits) in file.lines.iter() {
+                match file.branches.get(line) {
+                    Some(branches) => {
+                        let found = 2 * branches.len();
+                        let hit: usize = branches
+                            .values()
+                            .map(|o| o.taken as usize + o.not_taken as usize)
+                            .sum();
+                        writeln!(
+                            w,
+                            r#"            <line number="{line}" hits="{hits}" branch="true" condition-coverage="{}% ({hit}/{found})"/>"#,
+                            100 * hit / found,
+                        )?;
+                    }
+                    None => writeln!(
+                        w,
+                        r#"            <line number="{line}" hits="{hits}" branch="false"/>"#
+                    )?,
+                }
+            }
+            writeln!(w, "          </lines>")?;
+            writeln!(w, "        </class>")?;
+        }
+        writeln!(w, "      </classes>")?;
+        writeln!(w, "    </package>")?;
+        writeln!(w, "  </packages>")?;
+        writeln!(w, "</coverage>")?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+fn test_coverage() -> SourceCoverage {
+    let mut file = FileCoverage::default();
+    file.lines.insert(10, 1);
+    file.lines.insert(11, 0);
+    file.lines.insert(12, 1);
+    file.branches.entry(10).or_default().insert(
+        (1, 0x100),
+        BranchOutcome {
+            taken: true,
+            not_taken: false,
+        },
+    );
+    file.functions
+        .insert("rom::main".into(), FunctionCoverage { line: 9, hits: 1 });
+
+    let mut result = SourceCoverage::default();
+    result.files.insert("/ws/rom/src/main.rs".into(), file);
+    result
+}
+
+#[test]
+fn test_summary_and_merge() {
+    let mut cov = test_coverage();
+    assert_eq!(
+        cov.summary(),
+        CoverageSummary {
+            lines_found: 3,
+            lines_hit: 2,
+            branches_found: 2,
+            branches_hit: 1,
+            functions_found: 1,
+            functions_hit: 1,
+        }
+    );
+
+    let mut other = test_coverage();
+    let file = other.files.get_mut("/ws/rom/src/main.rs").unwrap();
+    file.lines.insert(11, 1);
+    file.branches
+        .get_mut(&10)
+        .unwrap()
+        .get_mut(&(1, 0x100))
+        .unwrap()
+        .not_taken = true;
+    cov.merge(other);
+
+    let summary = cov.summary();
+    assert_eq!(summary.lines_hit, 3);
+    assert_eq!(summary.branches_hit, 2);
+    assert_eq!(cov.files["/ws/rom/src/main.rs"].lines[&10], 2);
+}
+
+#[test]
+fn test_merge_keeps_images_apart() {
+    let mut cov = test_coverage();
+
+    // A different image with a branch at the same address and source line
+    let mut other = test_coverage();
+    let file = other.files.get_mut("/ws/rom/src/main.rs").unwrap();
+    let branches = file.branches.get_mut(&10).unwrap();
+    let outcome = branches.remove(&(1, 0x100)).unwrap();
+    branches.insert((2, 0x100), outcome);
+    cov.merge(other);
+
+    let summary = cov.summary();
+    assert_eq!(summary.branches_found, 4);
+    assert_eq!(summary.branches_hit, 2);
+}
+
+#[test]
+fn test_write_lcov() {
+    let mut cov = test_coverage();
+    cov.strip_prefix(Path::new("/ws"));
+
+    let mut out = vec![];
+    cov.write_lcov("caliptra", &mut out).unwrap();
+    assert_eq!(
+        std::str::from_utf8(&out).unwrap(),
+        "TN:caliptra\n\
+         SF:rom/src/main.rs\n\
+         FN:9,rom::main\n\
+         FNDA:1,rom::main\n\
+         FNF:1\n\
+         FNH:1\n\
+         BRDA:10,0,0,1\n\
+         BRDA:10,0,1,0\n\
+         BRF:2\n\
+         BRH:1\n\
+         DA:10,1\n\
+         DA:11,0\n\
+         DA:12,1\n\
+         LF:3\n\
+         LH:2\n\
+         end_of_record\n"
+    );
+}
+
+#[test]
+fn test_write_cobertura() {
+    let cov = test_coverage();
+    let mut out = vec![];
+    cov.write_cobertura("rom<uart>", &mut out).unwrap();
+    let out = String::from_utf8(out).unwrap();
+    assert!(
+        out.contains(r#"<package name="rom&lt;uart&gt;" line-rate="0.6667" branch-rate="0.5000""#)
+    );
+    assert!(out
+        .contains(r#"<line number="10" hits="1" branch="true" condition-coverage="50% (1/2)"/>"#));
+    assert!(out.contains(r#"<line number="11" hits="0" branch="false"/>"#));
+    assert!(out.ends_with("</coverage>\n"));
+}
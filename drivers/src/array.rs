@@ -13,7 +13,7 @@ Abstract:
 
 --*/
 
-use caliptra_cfi_derive::Launder;
+use caliptra_cfi_derive::{CfiEq, Launder};
 use core::mem::MaybeUninit;
 use zerocopy::{FromBytes, Immutable, IntoBytes, KnownLayout};
 use zeroize::Zeroize;
@@ -37,6 +37,7 @@ macro_rules! static_assert {
     KnownLayout,
     PartialEq,
     Eq,
+    CfiEq,
     Launder,
     Zeroize,
 )]
@@ -296,9 +296,13 @@ pub struct FirmwareHandoffTable {
     #[cfg(any(feature = "fmc", feature = "runtime"))]
     pub rt_hash_chain_kv_hdl: HandOffDataHandle,
 
+    /// Number of RT Alias certificates issued since the last cold reset.
+    #[cfg(any(feature = "fmc", feature = "runtime"))]
+    pub rt_alias_cert_count: u32,
+
     /// Reserved for future use.
     #[cfg(any(feature = "fmc", feature = "runtime"))]
-    pub reserved: [u8; 1636],
+    pub reserved: [u8; 1632],
 
     #[cfg(not(any(feature = "fmc", feature = "runtime")))]
     pub reserved: [u8; 1642],
@@ -348,7 +352,9 @@ impl Default for FirmwareHandoffTable {
             #[cfg(any(feature = "fmc", feature = "runtime"))]
             rt_hash_chain_kv_hdl: HandOffDataHandle(0),
             #[cfg(any(feature = "fmc", feature = "runtime"))]
-            reserved: [0u8; 1636],
+            rt_alias_cert_count: 0,
+            #[cfg(any(feature = "fmc", feature = "runtime"))]
+            reserved: [0u8; 1632],
 
             #[cfg(not(any(feature = "fmc", feature = "runtime")))]
             reserved: [0u8; 1642],
@@ -89,7 +89,7 @@ pub use pcr_reset::PcrResetCounter;
 #[cfg(feature = "runtime")]
 pub use persistent::AuthManifestImageMetadataList;
 pub use persistent::{
-    FuseLogArray, IdevIdCsr, PcrLogArray, PersistentData, PersistentDataAccessor,
+    FmcAliasCsr, FuseLogArray, IdevIdCsr, PcrLogArray, PersistentData, PersistentDataAccessor,
     StashMeasurementArray, FUSE_LOG_MAX_COUNT, MAX_CSR_SIZE, MEASUREMENT_MAX_COUNT,
     PCR_LOG_MAX_COUNT,
 };
@@ -101,19 +101,6 @@ pub use sha384::{Sha384, Sha384Digest, Sha384DigestOp};
 pub use soc_ifc::{report_boot_status, Lifecycle, MfgFlags, ResetReason, SocIfc};
 pub use trng::Trng;
 
-#[allow(unused_imports)]
-#[cfg(not(feature = "runtime"))]
-use caliptra_cfi_derive;
-#[allow(unused_imports)]
-#[cfg(feature = "runtime")]
-use caliptra_cfi_derive_git as caliptra_cfi_derive;
-#[allow(unused_imports)]
-#[cfg(not(feature = "runtime"))]
-use caliptra_cfi_lib;
-#[allow(unused_imports)]
-#[cfg(feature = "runtime")]
-use caliptra_cfi_lib_git as caliptra_cfi_lib;
-
 cfg_if::cfg_if! {
     if #[cfg(feature = "emu")] {
         mod uart;
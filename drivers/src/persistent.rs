@@ -38,7 +38,8 @@ pub const DPE_SIZE: u32 = 5 * 1024;
 pub const PCR_RESET_COUNTER_SIZE: u32 = 1024;
 pub const AUTH_MAN_IMAGE_METADATA_MAX_SIZE: u32 = 7 * 1024;
 pub const IDEVID_CSR_SIZE: u32 = 1024;
-pub const RESERVED_MEMORY_SIZE: u32 = 19 * 1024;
+pub const FMC_ALIAS_CSR_SIZE: u32 = 1024;
+pub const RESERVED_MEMORY_SIZE: u32 = 18 * 1024;
 
 pub const PCR_LOG_MAX_COUNT: usize = 17;
 pub const FUSE_LOG_MAX_COUNT: usize = 62;
@@ -119,6 +120,53 @@ impl IdevIdCsr {
     }
 }
 
+const _: () = assert!(MAX_CSR_SIZE < FMC_ALIAS_CSR_SIZE as usize);
+
+#[derive(Clone, TryFromBytes, IntoBytes, Zeroize)]
+#[repr(C)]
+pub struct FmcAliasCsr {
+    csr_len: u32,
+    csr: [u8; MAX_CSR_SIZE],
+}
+
+impl Default for FmcAliasCsr {
+    fn default() -> Self {
+        Self {
+            csr_len: 0,
+            csr: [0; MAX_CSR_SIZE],
+        }
+    }
+}
+
+impl FmcAliasCsr {
+    /// Get the CSR buffer
+    pub fn get(&self) -> Option<&[u8]> {
+        self.csr.get(..self.csr_len as usize)
+    }
+
+    /// Create `Self` from a csr slice. `csr_len` MUST be the actual length of the csr.
+    pub fn new(csr_buf: &[u8], csr_len: usize) -> CaliptraResult<Self> {
+        if csr_len >= MAX_CSR_SIZE {
+            return Err(CaliptraError::FMC_ALIAS_INVALID_CSR);
+        }
+
+        let mut _self = Self {
+            csr_len: csr_len as u32,
+            csr: [0; MAX_CSR_SIZE],
+        };
+        _self.csr[..csr_len].copy_from_slice(&csr_buf[..csr_len]);
+
+        Ok(_self)
+    }
+
+    /// Get the length of the CSR in bytes.
+    ///
+    /// A length of zero means the FMC that booted this runtime did not generate a CSR.
+    pub fn get_csr_len(&self) -> u32 {
+        self.csr_len
+    }
+}
+
 const _: () = assert!(size_of::<PersistentData>() == memory_layout::PERSISTENT_DATA_SIZE as usize);
 const _: () = assert!(size_of::<ImageManifest>() <= MAN1_SIZE as usize);
 const _: () = assert!(size_of::<ImageManifest>() <= MAN2_SIZE as usize);
@@ -133,6 +181,7 @@ const _: () = assert!(
     size_of::<AuthManifestImageMetadataCollection>() <= AUTH_MAN_IMAGE_METADATA_MAX_SIZE as usize
 );
 const _: () = assert!(size_of::<IdevIdCsr>() <= IDEVID_CSR_SIZE as usize);
+const _: () = assert!(size_of::<FmcAliasCsr>() <= FMC_ALIAS_CSR_SIZE as usize);
 
 #[derive(TryFromBytes, IntoBytes, KnownLayout, Zeroize)]
 #[repr(C)]
@@ -193,6 +242,9 @@ pub struct PersistentData {
     pub idevid_csr: IdevIdCsr,
     reserved10: [u8; IDEVID_CSR_SIZE as usize - size_of::<IdevIdCsr>()],
 
+    pub fmc_alias_csr: FmcAliasCsr,
+    reserved11: [u8; FMC_ALIAS_CSR_SIZE as usize - size_of::<FmcAliasCsr>()],
+
     // Reserved memory for future objects.
     // New objects should always source memory from this range.
     // Taking memory from this reserve does NOT break hitless updates.
@@ -282,6 +334,12 @@ impl PersistentData {
             );
 
             persistent_data_offset += IDEVID_CSR_SIZE;
+            assert_eq!(
+                addr_of!((*P).fmc_alias_csr) as u32,
+                memory_layout::PERSISTENT_DATA_ORG + persistent_data_offset
+            );
+
+            persistent_data_offset += FMC_ALIAS_CSR_SIZE;
             assert_eq!(
                 addr_of!((*P).reserved_memory) as u32,
                 memory_layout::PERSISTENT_DATA_ORG + persistent_data_offset
@@ -0,0 +1,91 @@
+/*++
+
+Licensed under the Apache-2.0 license.
+
+File Name:
+
+    fmc_alias_csr.rs
+
+Abstract:
+
+    FMC Alias Certificate Signing Request generation.
+
+--*/
+use caliptra_cfi_derive::cfi_impl_fn;
+
+use crate::flow::crypto::Crypto;
+use crate::flow::dice::DiceInput;
+use crate::flow::x509::X509;
+use crate::fmc_env::FmcEnv;
+use caliptra_common::cprintln;
+use caliptra_drivers::{
+    okref, CaliptraError, CaliptraResult, Ecc384Result, FmcAliasCsr, MAX_CSR_SIZE,
+};
+use caliptra_x509::{Ecdsa384CsrBuilder, Ecdsa384Signature, FmcAliasCsrTbs, FmcAliasCsrTbsParams};
+
+pub struct FmcAliasCsrLayer {}
+
+impl FmcAliasCsrLayer {
+    /// Generate the FMC Alias CSR and store it in persistent data
+    ///
+    /// The FMC Alias private key is locked before control is transferred to
+    /// the runtime, so the CSR has to be signed here. The runtime only serves
+    /// the stored copy.
+    ///
+    /// # Arguments
+    ///
+    /// * `env`   - FMC Environment
+    /// * `input` - DICE Input holding the FMC Alias key pair
+    #[cfg_attr(not(feature = "no-cfi"), cfi_impl_fn)]
+    pub fn make_csr(env: &mut FmcEnv, input: &DiceInput) -> CaliptraResult<()> {
+        let key_pair = &input.auth_key_pair;
+
+        // CSR `To Be Signed` Parameters
+        let params = FmcAliasCsrTbsParams {
+            // Unique Endpoint Identifier
+            ueid: &X509::ueid(env)?,
+
+            // Subject Name
+            subject_sn: &input.auth_sn,
+
+            // Public Key
+            public_key: &key_pair.pub_key.to_der(),
+        };
+
+        // Generate the `To Be Signed` portion of the CSR
+        let tbs =
+            FmcAliasCsrTbs::new(&params).ok_or(CaliptraError::FMC_ALIAS_CSR_TBS_BUILD_FAILURE)?;
+
+        cprintln!(
+            "[fmc csr] Sign CSR w/ SUBJECT.KEYID = {}",
+            key_pair.priv_key as u8
+        );
+
+        // Sign the `To Be Signed` portion
+        let sig = Crypto::ecdsa384_sign(env, key_pair.priv_key, &key_pair.pub_key, tbs.tbs());
+        let sig = okref(&sig)?;
+
+        // Verify the signature of the `To Be Signed` portion
+        if Crypto::ecdsa384_verify(env, &key_pair.pub_key, tbs.tbs(), sig)? != Ecc384Result::Success
+        {
+            return Err(CaliptraError::FMC_ALIAS_CSR_VERIFY);
+        }
+
+        let sig = Ecdsa384Signature {
+            r: (&sig.r).into(),
+            s: (&sig.s).into(),
+        };
+
+        // Build the CSR with `To Be Signed` & `Signature`
+        let mut csr_buf = [0; MAX_CSR_SIZE];
+        let csr_bldr = Ecdsa384CsrBuilder::new(tbs.tbs(), &sig)
+            .ok_or(CaliptraError::FMC_ALIAS_CSR_BUILDER_INIT_FAILURE)?;
+        let csr_len = csr_bldr
+            .build(&mut csr_buf)
+            .ok_or(CaliptraError::FMC_ALIAS_CSR_BUILDER_BUILD_FAILURE)?;
+
+        env.persistent_data.get_mut().fmc_alias_csr = FmcAliasCsr::new(&csr_buf, csr_len)?;
+
+        Ok(())
+    }
+}
@@ -12,10 +12,11 @@ Abstract:
 
 --*/
 use caliptra_cfi_derive::cfi_impl_fn;
-use caliptra_cfi_lib::{cfi_assert, cfi_assert_bool, cfi_assert_eq, cfi_launder};
+use caliptra_cfi_lib::{cfi_assert, cfi_assert_bool, cfi_assert_eq, cfi_assert_ne, cfi_launder};
 
 use crate::flow::crypto::Crypto;
 use crate::flow::dice::{DiceInput, DiceOutput};
+use crate::flow::fmc_alias_csr::FmcAliasCsrLayer;
 use crate::flow::pcr::extend_pcr_common;
 use crate::flow::tci::Tci;
 use crate::flow::x509::X509;
@@ -24,16 +25,20 @@ use crate::FmcBootStatus;
 use crate::HandOff;
 use caliptra_common::cprintln;
 use caliptra_common::crypto::Ecc384KeyPair;
+use caliptra_common::dice;
 use caliptra_common::keyids::{KEY_ID_RT_CDI, KEY_ID_RT_PRIV_KEY, KEY_ID_TMP};
 use caliptra_common::HexBytes;
 use caliptra_drivers::{
-    okref, report_boot_status, CaliptraError, CaliptraResult, Ecc384Result, KeyId, PersistentData,
-    ResetReason,
+    okref, report_boot_status, report_fw_error_non_fatal, CaliptraError, CaliptraResult,
+    Ecc384Result, FmcAliasCsr, KeyId, PersistentData, ResetReason,
 };
 use caliptra_x509::{NotAfter, NotBefore, RtAliasCertTbs, RtAliasCertTbsParams};
 
 const SHA384_HASH_SIZE: usize = 48;
 
+/// Manifest header flag selecting a serial number that includes `rt_alias_cert_count`.
+const RT_ALIAS_SN_COUNTER_FLAG: u32 = 1 << 1;
+
 #[derive(Default)]
 pub struct RtAliasLayer {}
 
@@ -116,9 +121,31 @@ impl RtAliasLayer {
         cprintln!("[alias rt] Populate DV Done");
         report_boot_status(crate::FmcBootStatus::RtMeasurementComplete as u32);
 
+        Self::update_cert_count(env);
+
         // Retrieve Dice Input Layer from Hand Off and Derive Key
         match Self::dice_input_from_hand_off(env) {
             Ok(input) => {
+                // The FMC Alias private key is locked once the RT Alias
+                // certificate is signed, so the CSR must be generated first.
+                let reset_reason = env.soc_ifc.reset_reason();
+                if reset_reason == ResetReason::ColdReset {
+                    cfi_assert_eq(reset_reason, ResetReason::ColdReset);
+                    cprintln!("[alias rt] Generate FMC Alias CSR");
+                    // Without a CSR only GET_FMC_ALIAS_CSR is unavailable, so
+                    // don't fail the boot over it.
+                    if let Err(e) = FmcAliasCsrLayer::make_csr(env, &input) {
+                        cprintln!(
+                            "[alias rt] FMC Alias CSR generation failed: {}",
+                            u32::from(e)
+                        );
+                        report_fw_error_non_fatal(e.into());
+                        env.persistent_data.get_mut().fmc_alias_csr = FmcAliasCsr::default();
+                    }
+                } else {
+                    cfi_assert_ne(reset_reason, ResetReason::ColdReset);
+                }
+
                 let out = Self::derive(env, &input)?;
                 report_boot_status(crate::FmcBootStatus::RtAliasDerivationComplete as u32);
                 HandOff::update(env, out)
@@ -204,6 +231,23 @@ impl RtAliasLayer {
         HandOff::set_and_lock_rt_min_svn(env, rt_min_svn)
     }
 
+    /// Update the number of RT Alias certificates issued since cold reset
+    ///
+    /// # Arguments
+    ///
+    /// * `env` - FMC Environment
+    fn update_cert_count(env: &mut FmcEnv) {
+        let reset_reason = env.soc_ifc.reset_reason();
+        let count = if reset_reason == ResetReason::ColdReset {
+            cfi_assert_eq(reset_reason, ResetReason::ColdReset);
+            0
+        } else {
+            cfi_assert_ne(reset_reason, ResetReason::ColdReset);
+            HandOff::rt_alias_cert_count(env).wrapping_add(1)
+        };
+        HandOff::set_rt_alias_cert_count(env, count);
+    }
+
     fn get_cert_validity_info(
         manifest: &caliptra_image_types::ImageManifest,
     ) -> (NotBefore, NotAfter) {
@@ -228,6 +272,16 @@ impl RtAliasLayer {
             nb.value = manifest.header.owner_data.owner_not_before;
         }
 
+        // The owner may shorten or extend the RT Alias certificate window
+        // independently of the FMC Alias certificate.
+        if let Some(rt_nf) =
+            NotAfter::from_yyyymmdd(manifest.header.owner_data.owner_rt_alias_not_after)
+        {
+            if rt_nf.value > nb.value {
+                nf = rt_nf;
+            }
+        }
+
         (nb, nf)
     }
 
@@ -302,11 +356,30 @@ impl RtAliasLayer {
         let auth_pub_key = &input.auth_key_pair.pub_key;
         let pub_key = &output.subj_key_pair.pub_key;
 
-        let serial_number = &X509::cert_sn(env, pub_key)?;
+        let mut serial_number = X509::cert_sn(env, pub_key)?;
+        if env.persistent_data.get().manifest1.header.flags & RT_ALIAS_SN_COUNTER_FLAG != 0 {
+            // Certificates re-issued for the same key after an update or warm
+            // reset get distinct serial numbers.
+            let count = HandOff::rt_alias_cert_count(env).to_be_bytes();
+            let len = serial_number.len();
+            serial_number[len - count.len()..].copy_from_slice(&count);
+        }
 
         let rt_tci: [u8; 48] = HandOff::rt_tci(env).into();
         let rt_svn = HandOff::rt_svn(env) as u8;
 
+        let rt_toc = &env.persistent_data.get().manifest1.runtime;
+        let rt_version = dice::tcb_info_version(rt_toc.version);
+        let rt_revision = rt_toc.revision;
+
+        // The ROM-built FMC alias TcbInfo carries only the FMC digest and SVN,
+        // so the complete FMC entry is published here instead.
+        let fmc_tci: [u8; 48] = env.data_vault.fmc_tci().into();
+        let fmc_svn = env.data_vault.fmc_svn() as u8;
+        let fmc_toc = &env.persistent_data.get().manifest1.fmc;
+        let fmc_version = dice::tcb_info_version(fmc_toc.version);
+        let fmc_revision = fmc_toc.revision;
+
         // Certificate `To Be Signed` Parameters
         let params = RtAliasCertTbsParams {
             // Do we need the UEID here?
@@ -315,12 +388,18 @@ impl RtAliasLayer {
             subject_key_id: &output.subj_key_id,
             issuer_sn: &input.auth_sn,
             authority_key_id: &input.auth_key_id,
-            serial_number,
+            serial_number: &serial_number,
             public_key: &pub_key.to_der(),
             not_before,
             not_after,
             tcb_info_rt_svn: &rt_svn.to_be_bytes(),
             tcb_info_rt_tci: &rt_tci,
+            tcb_info_rt_version: &rt_version,
+            tcb_info_rt_vendor_info: &rt_revision,
+            tcb_info_fmc_svn: &fmc_svn.to_be_bytes(),
+            tcb_info_fmc_tci: &fmc_tci,
+            tcb_info_fmc_version: &fmc_version,
+            tcb_info_fmc_vendor_info: &fmc_revision,
             // Are there any fields missing?
         };
 
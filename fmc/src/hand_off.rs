@@ -282,6 +282,16 @@ impl HandOff {
         Self::fht_mut(env).rt_hash_chain_kv_hdl = Self::key_id_to_handle(kv_slot)
     }
 
+    /// Retrieve the number of RT Alias certificates issued since cold reset.
+    pub fn rt_alias_cert_count(env: &FmcEnv) -> u32 {
+        Self::fht(env).rt_alias_cert_count
+    }
+
+    /// Store the number of RT Alias certificates issued since cold reset.
+    pub fn set_rt_alias_cert_count(env: &mut FmcEnv, count: u32) {
+        Self::fht_mut(env).rt_alias_cert_count = count;
+    }
+
     /// The FMC CDI is stored in a 32-bit DataVault sticky register.
     fn key_id_to_handle(key_id: KeyId) -> HandOffDataHandle {
         HandOffDataHandle(((Vault::KeyVault as u32) << 12) | key_id as u32)
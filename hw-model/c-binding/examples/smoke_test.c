@@ -8,6 +8,17 @@
 #include "api/caliptra_api.h"
 
 static const uint32_t RT_READY_FOR_COMMANDS = 0x600;
+static const uint32_t OP_CAPABILITIES = 0x43415053; // "CAPS"
+
+// Checksum of a request with no payload: 0 - (sum of the command bytes)
+static uint32_t empty_req_checksum(uint32_t cmd)
+{
+    uint32_t sum = 0;
+    for (int i = 0; i < 4; i++) {
+        sum += (cmd >> (8 * i)) & 0xff;
+    }
+    return 0 - sum;
+}
 
 static struct caliptra_buffer read_file_or_die(const char* path)
 {
@@ -111,6 +122,20 @@ int main(int argc, char *argv[])
     // Run Until RT is ready to receive commands
     caliptra_model_step_until_boot_status(model, RT_READY_FOR_COMMANDS);
 
+    // Query the runtime capabilities
+    uint32_t caps_req = empty_req_checksum(OP_CAPABILITIES);
+    struct caliptra_buffer caps_req_buffer = {
+      .data = (const uint8_t *)&caps_req,
+      .len = sizeof(caps_req),
+    };
+    uint8_t caps_resp[24];
+    size_t caps_resp_len = sizeof(caps_resp);
+    int status = caliptra_model_mailbox_execute(model, OP_CAPABILITIES, caps_req_buffer, caps_resp, &caps_resp_len);
+    if (status != CALIPTRA_MODEL_STATUS_OK || caps_resp_len != sizeof(caps_resp)) {
+        printf("CAPABILITIES failed: status %d, response length %ld\n", status, caps_resp_len);
+        exit(-EIO);
+    }
+
     // Free the model
     caliptra_model_destroy(model);
 
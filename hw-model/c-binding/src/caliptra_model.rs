@@ -2,8 +2,11 @@
 
 use caliptra_api::soc_mgr::SocManager;
 use caliptra_emu_bus::Bus;
-use caliptra_hw_model::{DefaultHwModel, HwModel, InitParams, SecurityState};
+use caliptra_hw_model::{DefaultHwModel, HwModel, InitParams, ModelError, SecurityState};
+use caliptra_hw_model_types::{RandomEtrngResponses, RandomNibbles};
+use rand::{rngs::StdRng, SeedableRng};
 use std::ffi::*;
+use std::path::PathBuf;
 use std::slice;
 
 use caliptra_emu_types::RvSize;
@@ -28,6 +31,16 @@ pub struct caliptra_model_init_params {
     pub dccm: caliptra_buffer,
     pub iccm: caliptra_buffer,
     pub security_state: u8,
+    // The silicon obfuscation key; ignored unless use_cptra_obf_key is set.
+    pub cptra_obf_key: [u32; 8],
+    pub use_cptra_obf_key: bool,
+    // Seed for the internal and external TRNG; ignored unless use_trng_seed is
+    // set, in which case CPTRA_TRNG_SEED is ignored too.
+    pub trng_seed: u64,
+    pub use_trng_seed: bool,
+    // NUL-terminated path of the trace file. If NULL, the CPTRA_TRACE_PATH
+    // environment variable will be used.
+    pub trace_path: *const c_char,
 }
 
 pub const CALIPTRA_SEC_STATE_DBG_UNLOCKED_UNPROVISIONED: c_int = 0b000;
@@ -36,6 +49,36 @@ pub const CALIPTRA_SEC_STATE_DBG_UNLOCKED_PRODUCTION: c_int = 0b011;
 pub const CALIPTRA_SEC_STATE_DBG_LOCKED_PRODUCTION: c_int = 0b111;
 
 pub const CALIPTRA_MODEL_STATUS_OK: c_int = 0;
+// The firmware failed the mailbox command; the reason is in
+// CPTRA_FW_ERROR_FATAL or CPTRA_FW_ERROR_NON_FATAL.
+pub const CALIPTRA_MODEL_STATUS_MAILBOX_CMD_FAILED: c_int = 1;
+// The mailbox could not be locked, timed out or returned an unexpected
+// status.
+pub const CALIPTRA_MODEL_STATUS_MAILBOX_ERROR: c_int = 2;
+// The response does not fit in the caller's buffer.
+pub const CALIPTRA_MODEL_STATUS_BUFFER_TOO_SMALL: c_int = 3;
+// The firmware responded to the firmware upload with data.
+pub const CALIPTRA_MODEL_STATUS_UNEXPECTED_RESPONSE: c_int = 4;
+
+fn model_error_status(err: &ModelError) -> c_int {
+    match err {
+        ModelError::MailboxCmdFailed(_) => CALIPTRA_MODEL_STATUS_MAILBOX_CMD_FAILED,
+        ModelError::UploadFirmwareUnexpectedResponse => CALIPTRA_MODEL_STATUS_UNEXPECTED_RESPONSE,
+        _ => CALIPTRA_MODEL_STATUS_MAILBOX_ERROR,
+    }
+}
+
+/// # Safety
+///
+/// Unless `buffer.len` is 0, `buffer.data` must point to `buffer.len`
+/// readable bytes.
+unsafe fn buffer_slice<'a>(buffer: caliptra_buffer) -> &'a [u8] {
+    if buffer.len == 0 {
+        // slice::from_raw_parts doesn't accept NULL
+        return &[];
+    }
+    slice::from_raw_parts(buffer.data, buffer.len)
+}
 
 /// # Safety
 #[no_mangle]
@@ -45,21 +88,42 @@ pub unsafe extern "C" fn caliptra_model_init_default(
 ) -> c_int {
     // Parameter check
     assert!(!model.is_null());
+    let mut init_params = InitParams {
+        rom: buffer_slice(params.rom),
+        dccm: buffer_slice(params.dccm),
+        iccm: buffer_slice(params.iccm),
+        security_state: SecurityState::from(params.security_state as u32),
+        ..Default::default()
+    };
+    if params.use_cptra_obf_key {
+        init_params.cptra_obf_key = params.cptra_obf_key;
+    }
+    if params.use_trng_seed {
+        init_params.itrng_nibbles =
+            Box::new(RandomNibbles(StdRng::seed_from_u64(params.trng_seed)));
+        init_params.etrng_responses = Box::new(RandomEtrngResponses(StdRng::seed_from_u64(
+            params.trng_seed,
+        )));
+        init_params.trng_seed = Some(params.trng_seed);
+    }
+    if !params.trace_path.is_null() {
+        init_params.trace_path = Some(PathBuf::from(
+            CStr::from_ptr(params.trace_path)
+                .to_string_lossy()
+                .into_owned(),
+        ));
+    }
     // Generate Model and cast to caliptra_model
     *model = Box::into_raw(Box::new(
-        caliptra_hw_model::new_unbooted(InitParams {
-            rom: slice::from_raw_parts(params.rom.data, params.rom.len),
-            dccm: slice::from_raw_parts(params.dccm.data, params.dccm.len),
-            iccm: slice::from_raw_parts(params.iccm.data, params.iccm.len),
-            security_state: SecurityState::from(params.security_state as u32),
-            ..Default::default()
-        })
-        .unwrap(),
+        caliptra_hw_model::new_unbooted(init_params).unwrap(),
     )) as *mut caliptra_model;
 
     CALIPTRA_MODEL_STATUS_OK
 }
 
+/// Frees the model. When built with the `coverage` feature, the emulator
+/// writes its code coverage to the directory named by CPTRA_COVERAGE_PATH.
+///
 /// # Safety
 #[no_mangle]
 pub unsafe extern "C" fn caliptra_model_destroy(model: *mut caliptra_model) {
@@ -166,3 +230,95 @@ pub unsafe extern "C" fn caliptra_model_step_until_boot_status(
     assert!(!model.is_null());
     (*{ model as *mut DefaultHwModel }).step_until_boot_status(boot_status, true);
 }
+
+/// Toggles the reset and pwrgood pins and waits for ready_for_fuses. Models
+/// that don't support cold reset panic.
+///
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn caliptra_model_cold_reset(model: *mut caliptra_model) -> c_int {
+    // Parameter check
+    assert!(!model.is_null());
+    (*{ model as *mut DefaultHwModel }).cold_reset();
+
+    CALIPTRA_MODEL_STATUS_OK
+}
+
+/// Toggles the reset pins and waits for ready_for_fuses.
+///
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn caliptra_model_warm_reset(model: *mut caliptra_model) -> c_int {
+    // Parameter check
+    assert!(!model.is_null());
+    (*{ model as *mut DefaultHwModel }).warm_reset();
+
+    CALIPTRA_MODEL_STATUS_OK
+}
+
+/// Executes mailbox command `cmd` with request data `req`, stepping the model
+/// until the firmware responds. On entry `*resp_len` is the size of `resp`; on
+/// return it is the length of the response data, which is 0 if the firmware
+/// completed the command without data. If the response doesn't fit,
+/// CALIPTRA_MODEL_STATUS_BUFFER_TOO_SMALL is returned and `*resp_len` is the
+/// required size.
+///
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn caliptra_model_mailbox_execute(
+    model: *mut caliptra_model,
+    cmd: c_uint,
+    req: caliptra_buffer,
+    resp: *mut u8,
+    resp_len: *mut usize,
+) -> c_int {
+    // Parameter check
+    assert!(!model.is_null() && !resp_len.is_null());
+    let result = (*{ model as *mut DefaultHwModel }).mailbox_execute(cmd, buffer_slice(req));
+    let data = match result {
+        Ok(data) => data.unwrap_or_default(),
+        Err(err) => {
+            *resp_len = 0;
+            return model_error_status(&err);
+        }
+    };
+    if data.len() > *resp_len {
+        *resp_len = data.len();
+        return CALIPTRA_MODEL_STATUS_BUFFER_TOO_SMALL;
+    }
+    if !data.is_empty() {
+        assert!(!resp.is_null());
+        slice::from_raw_parts_mut(resp, data.len()).copy_from_slice(&data);
+    }
+    *resp_len = data.len();
+
+    CALIPTRA_MODEL_STATUS_OK
+}
+
+/// Uploads a firmware image bundle through the mailbox. The model must be
+/// ready_for_fw.
+///
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn caliptra_model_upload_firmware(
+    model: *mut caliptra_model,
+    firmware: caliptra_buffer,
+) -> c_int {
+    // Parameter check
+    assert!(!model.is_null());
+    match (*{ model as *mut DefaultHwModel }).upload_firmware(buffer_slice(firmware)) {
+        Ok(()) => CALIPTRA_MODEL_STATUS_OK,
+        Err(err) => model_error_status(&err),
+    }
+}
+
+/// Starts or stops writing the instruction and bus trace to the trace path
+/// given at init (or CPTRA_TRACE_PATH). Does nothing if neither was set.
+///
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn caliptra_model_tracing_hint(model: *mut caliptra_model, enable: bool) {
+    // Parameter check
+    assert!(!model.is_null());
+    (*{ model as *mut DefaultHwModel }).tracing_hint(enable);
+}
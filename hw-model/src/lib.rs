@@ -26,7 +26,7 @@ use caliptra_registers::soc_ifc::regs::{
     CptraItrngEntropyConfig0WriteVal, CptraItrngEntropyConfig1WriteVal,
 };
 
-use rand::{rngs::StdRng, SeedableRng};
+use rand::{rngs::StdRng, RngCore, SeedableRng};
 use sha2::Digest;
 
 pub mod mmio;
@@ -39,7 +39,11 @@ mod model_verilated;
 #[cfg(feature = "fpga_realtime")]
 mod model_fpga_realtime;
 
+mod model_remote;
 mod output;
+mod remote_protocol;
+mod remote_server;
+mod repro;
 mod rv32_builder;
 
 pub use api::mailbox::mbox_write_fifo;
@@ -50,6 +54,10 @@ use output::ExitStatus;
 pub use output::Output;
 
 pub use model_emulated::ModelEmulated;
+pub use model_remote::{ModelRemote, REMOTE_MODEL_ADDR_ENV};
+pub use remote_protocol::RemoteAddr;
+pub use remote_server::serve_remote_model;
+pub use repro::{ReproBundle, REPRO_DIR_ENV};
 
 #[cfg(feature = "verilator")]
 pub use model_verilated::ModelVerilated;
@@ -71,7 +79,11 @@ pub use model_fpga_realtime::OpenOcdError;
 /// (used by IDEs) can't fully resolve associated types from `impl Trait`, so
 /// such functions should use `DefaultHwModel` until they fix that. Users should
 /// treat `DefaultHwModel` as if it were `impl HwModel`.
-#[cfg(all(not(feature = "verilator"), not(feature = "fpga_realtime")))]
+#[cfg(all(
+    not(feature = "verilator"),
+    not(feature = "fpga_realtime"),
+    not(feature = "remote")
+))]
 pub type DefaultHwModel = ModelEmulated;
 
 #[cfg(feature = "verilator")]
@@ -80,6 +92,9 @@ pub type DefaultHwModel = ModelVerilated;
 #[cfg(feature = "fpga_realtime")]
 pub type DefaultHwModel = ModelFpgaRealtime;
 
+#[cfg(feature = "remote")]
+pub type DefaultHwModel = ModelRemote;
+
 pub const DEFAULT_APB_PAUSER: u32 = 0x01;
 
 /// Constructs an HwModel based on the cargo features and environment
@@ -89,9 +104,10 @@ pub const DEFAULT_APB_PAUSER: u32 = 0x01;
 /// The model returned by this function does not have any fuses programmed and
 /// is not yet ready to execute code in the microcontroller. Most test cases
 /// should use [`new`] instead.
-pub fn new_unbooted(params: InitParams) -> Result<DefaultHwModel, Box<dyn Error>> {
+pub fn new_unbooted(mut params: InitParams) -> Result<DefaultHwModel, Box<dyn Error>> {
+    repro::derive_trng_seed(&mut params);
     let summary = params.summary();
-    DefaultHwModel::new_unbooted(params).map(|hw| {
+    repro::new_unbooted::<DefaultHwModel>(params).map(|hw| {
         println!(
             "Using hardware-model {} trng={:?}",
             hw.type_name(),
@@ -181,23 +197,24 @@ pub struct InitParams<'a> {
     // Information about the stack Caliptra is using. When set the emulator will check if the stack
     // overflows.
     pub stack_info: Option<StackInfo>,
+
+    // The seed the default itrng_nibbles and etrng_responses were generated
+    // from; CPTRA_TRNG_SEED if set, otherwise random. Printed with the
+    // InitParams summary and saved in reproducer bundles so a failing test
+    // can be re-run with the same entropy. Dropped when creating the model if
+    // the iterators were replaced with ones that don't come from this seed.
+    pub trng_seed: Option<u64>,
 }
 impl<'a> Default for InitParams<'a> {
     fn default() -> Self {
         let seed = std::env::var("CPTRA_TRNG_SEED")
             .ok()
-            .and_then(|s| u64::from_str(&s).ok());
-        let itrng_nibbles: Box<dyn Iterator<Item = u8> + Send> = if let Some(seed) = seed {
-            Box::new(RandomNibbles(StdRng::seed_from_u64(seed)))
-        } else {
-            Box::new(RandomNibbles(StdRng::from_entropy()))
-        };
+            .and_then(|s| u64::from_str(&s).ok())
+            .unwrap_or_else(|| StdRng::from_entropy().next_u64());
+        let itrng_nibbles: Box<dyn Iterator<Item = u8> + Send> =
+            Box::new(RandomNibbles(StdRng::seed_from_u64(seed)));
         let etrng_responses: Box<dyn Iterator<Item = EtrngResponse> + Send> =
-            if let Some(seed) = seed {
-                Box::new(RandomEtrngResponses(StdRng::seed_from_u64(seed)))
-            } else {
-                Box::new(RandomEtrngResponses::new_from_stdrng())
-            };
+            Box::new(RandomEtrngResponses(StdRng::seed_from_u64(seed)));
         Self {
             rom: Default::default(),
             dccm: Default::default(),
@@ -216,6 +233,7 @@ impl<'a> Default for InitParams<'a> {
             random_sram_puf: true,
             trace_path: None,
             stack_info: None,
+            trng_seed: Some(seed),
         }
     }
 }
@@ -226,6 +244,7 @@ impl<'a> InitParams<'a> {
             rom_sha384: sha2::Sha384::digest(self.rom).into(),
             obf_key: self.cptra_obf_key,
             security_state: self.security_state,
+            trng_seed: self.trng_seed,
         }
     }
 }
@@ -234,6 +253,7 @@ pub struct InitParamsSummary {
     rom_sha384: [u8; 48],
     obf_key: [u32; 8],
     security_state: SecurityState,
+    trng_seed: Option<u64>,
 }
 impl std::fmt::Debug for InitParamsSummary {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -241,6 +261,7 @@ impl std::fmt::Debug for InitParamsSummary {
             .field("rom_sha384", &HexBytes(&self.rom_sha384))
             .field("obf_key", &HexSlice(&self.obf_key))
             .field("security_state", &self.security_state)
+            .field("trng_seed", &self.trng_seed)
             .finish()
     }
 }
@@ -510,6 +531,11 @@ const FW_LOAD_CMD_OPCODE: u32 = 0x4657_4C44;
 /// Stash Measurement Command Opcode.
 const STASH_MEASUREMENT_CMD_OPCODE: u32 = 0x4D45_4153;
 
+/// Upper bound on the cycles the step_until_* helpers pass to
+/// [`HwModel::step_until_event()`], so they still check their conditions
+/// regularly when nothing happens.
+const EVENT_STEP_CYCLES: u32 = 10_000;
+
 // Represents a emulator or simulation of the caliptra hardware, to be called
 // from tests. Typically, test cases should use [`crate::new()`] to create a model
 // based on the cargo features (and any model-specific environment variables).
@@ -527,13 +553,14 @@ pub trait HwModel: SocManager {
     /// Create a model, and boot it to the point where CPU execution can
     /// occur. This includes programming the fuses, initializing the
     /// boot_fsm state machine, and (optionally) uploading firmware.
-    fn new(init_params: InitParams, boot_params: BootParams) -> Result<Self, Box<dyn Error>>
+    fn new(mut init_params: InitParams, boot_params: BootParams) -> Result<Self, Box<dyn Error>>
     where
         Self: Sized,
     {
+        repro::derive_trng_seed(&mut init_params);
         let init_params_summary = init_params.summary();
 
-        let mut hw: Self = HwModel::new_unbooted(init_params)?;
+        let mut hw: Self = repro::new_unbooted(init_params)?;
         let hw_rev_id = hw.soc_ifc().cptra_hw_rev_id().read();
         println!(
             "Using hardware-model {} trng={:?} hw_rev_id={{cptra_generation=0x{:04x}, soc_stepping_id={:04x}}}",
@@ -550,6 +577,11 @@ pub trait HwModel: SocManager {
     where
         Self: Sized,
     {
+        let cycle = self.output().sink().now();
+        repro::record_boot(&boot_params, cycle);
+        // The firmware upload is part of the recorded boot
+        let _pause = repro::pause();
+
         HwModel::init_fuses(self, &boot_params.fuses);
 
         self.soc_ifc()
@@ -600,14 +632,12 @@ pub trait HwModel: SocManager {
                     self.soc_ifc().cptra_dbg_manuf_service_reg().write(|_| 0);
                 }
 
-                self.step();
-                cycles += 1;
+                cycles += self.step_until_event(EVENT_STEP_CYCLES);
                 if cycles > MAX_WAIT_CYCLES {
                     return Err(ModelError::ReadyForFirmwareTimeout { cycles }.into());
                 }
             }
             writeln!(self.output().logger(), "ready_for_fw is high")?;
-            self.cover_fw_mage(fw_image);
             self.upload_firmware(fw_image)?;
         }
 
@@ -622,6 +652,8 @@ pub trait HwModel: SocManager {
 
     /// Trigger a warm reset and advance the boot
     fn warm_reset_flow(&mut self, fuses: &Fuses) {
+        let cycle = self.output().sink().now();
+        repro::record_warm_reset(fuses, cycle);
         self.warm_reset();
 
         HwModel::init_fuses(self, fuses);
@@ -640,6 +672,20 @@ pub trait HwModel: SocManager {
     /// Any UART-ish output written by the microcontroller will be available here.
     fn output(&mut self) -> &mut Output;
 
+    /// Step until ready_for_fw changes, the microcontroller writes to the UART
+    /// or exits, or `max_cycles` have elapsed. Returns the number of cycles
+    /// stepped.
+    ///
+    /// The default implementation steps a single cycle; models where every
+    /// step is expensive (such as [`ModelRemote`]) run many cycles at once.
+    fn step_until_event(&mut self, max_cycles: u32) -> u32 {
+        if max_cycles == 0 {
+            return 0;
+        }
+        self.step();
+        1
+    }
+
     /// Execute until the result of `predicate` becomes true.
     fn step_until(&mut self, mut predicate: impl FnMut(&mut Self) -> bool) {
         while !predicate(self) {
@@ -704,7 +750,7 @@ pub trait HwModel: SocManager {
                 }
                 None => {}
             }
-            self.step();
+            self.step_until_event(EVENT_STEP_CYCLES);
         }
     }
 
@@ -720,13 +766,15 @@ pub trait HwModel: SocManager {
                 }
                 None => {}
             }
-            self.step();
+            self.step_until_event(EVENT_STEP_CYCLES);
         }
     }
 
     /// Execute until the output buffer starts with `expected_output`
     fn step_until_output(&mut self, expected_output: &str) -> Result<(), Box<dyn Error>> {
-        self.step_until(|m| m.output().peek().len() >= expected_output.len());
+        while self.output().peek().len() < expected_output.len() {
+            self.step_until_event(EVENT_STEP_CYCLES);
+        }
         if &self.output().peek()[..expected_output.len()] != expected_output {
             return Err(format!(
                 "expected output {:?}, was {:?}",
@@ -754,7 +802,9 @@ pub trait HwModel: SocManager {
     // before this function was called.
     fn step_until_output_contains(&mut self, substr: &str) -> Result<(), Box<dyn Error>> {
         self.output().set_search_term(substr);
-        self.step_until(|m| m.output().search_matched());
+        while !self.output().search_matched() {
+            self.step_until_event(EVENT_STEP_CYCLES);
+        }
         Ok(())
     }
 
@@ -872,6 +922,9 @@ pub trait HwModel: SocManager {
             return Err(ModelError::UnableToReadMailbox);
         }
 
+        let cycle = self.output().sink().now();
+        repro::record_mailbox(cmd, buf, cycle);
+
         writeln!(
             self.output().logger(),
             "<<< Executing mbox cmd 0x{cmd:08x} ({} bytes) from SoC",
@@ -1001,6 +1054,7 @@ pub trait HwModel: SocManager {
 
     /// Upload firmware to the mailbox.
     fn upload_firmware(&mut self, firmware: &[u8]) -> Result<(), ModelError> {
+        self.cover_fw_mage(firmware);
         let response = self.mailbox_execute(FW_LOAD_CMD_OPCODE, firmware)?;
         if response.is_some() {
             return Err(ModelError::UploadFirmwareUnexpectedResponse);
@@ -1906,4 +1960,115 @@ mod tests {
 
         model.step_until_output("hii").unwrap();
     }
+
+    #[test]
+    fn test_remote_execution() {
+        use crate::{serve_remote_model, ModelEmulated, ModelRemote, RemoteAddr};
+
+        let addr = RemoteAddr::Unix(std::env::temp_dir().join(format!(
+            "caliptra-hw-model-test-{}.sock",
+            std::process::id()
+        )));
+        let server_addr = addr.clone();
+        std::thread::spawn(move || serve_remote_model::<ModelEmulated>(&server_addr));
+
+        let rom = gen_image_hi();
+        let mut attempts = 0;
+        // Retry until the server is listening
+        let mut model = loop {
+            match ModelRemote::connect(
+                &addr,
+                InitParams {
+                    rom: &rom,
+                    ..Default::default()
+                },
+            ) {
+                Ok(model) => break model,
+                Err(e) if attempts == 100 => panic!("{e}"),
+                Err(_) => {
+                    attempts += 1;
+                    std::thread::sleep(std::time::Duration::from_millis(10));
+                }
+            }
+        };
+        // Connections are served concurrently
+        let mut model2 = ModelRemote::connect(
+            &addr,
+            InitParams {
+                rom: &rom,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        model.boot(BootParams::default()).unwrap();
+        model2.boot(BootParams::default()).unwrap();
+
+        // The server stops stepping as soon as the ROM writes to the UART
+        assert!(model.step_until_event(1_000_000) < 1_000_000);
+        assert!(!model.output().peek().is_empty());
+
+        for model in [&mut model, &mut model2] {
+            let mut output = vec![];
+            model.copy_output_until_exit_success(&mut output).unwrap();
+            assert_eq!(output, b"hii");
+            assert!(model.soc_ifc().cptra_fuse_wr_done().read().done());
+        }
+    }
+
+    #[test]
+    fn test_repro_replay() {
+        use crate::ReproBundle;
+        use caliptra_hw_model_types::{RandomEtrngResponses, RandomNibbles};
+        use rand::{rngs::StdRng, SeedableRng};
+
+        let mut model = caliptra_hw_model::new(
+            InitParams {
+                rom: &gen_image_hi(),
+                ..Default::default()
+            },
+            BootParams {
+                fuses: crate::Fuses {
+                    fmc_key_manifest_svn: 0x5,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        model.step_until_output("hii").unwrap();
+
+        let dir = std::env::temp_dir().join(format!(
+            "caliptra-hw-model-repro-test-{}",
+            std::process::id()
+        ));
+        let bundle = ReproBundle::capture().unwrap();
+        assert_eq!(bundle.model(), model.type_name());
+        assert!(bundle.trng_seed().is_some());
+        bundle.save(&dir).unwrap();
+        let bundle = ReproBundle::load(&dir).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let mut replayed: DefaultHwModel = bundle.replay().unwrap();
+        replayed.step_until_output("hii").unwrap();
+        assert!(replayed.soc_ifc().cptra_fuse_wr_done().read().done());
+        assert_eq!(replayed.soc_ifc().fuse_fmc_key_manifest_svn().read(), 0x5);
+
+        // Entropy supplied by the test isn't attributed to the default seed
+        let new_model = |itrng_seed: u64, etrng_seed: u64, trng_seed: Option<u64>| {
+            caliptra_hw_model::new_unbooted(InitParams {
+                rom: &gen_image_hi(),
+                itrng_nibbles: Box::new(RandomNibbles(StdRng::seed_from_u64(itrng_seed))),
+                etrng_responses: Box::new(RandomEtrngResponses(StdRng::seed_from_u64(etrng_seed))),
+                trng_seed,
+                ..Default::default()
+            })
+            .unwrap()
+        };
+        let _model = new_model(0, 0, Some(1234));
+        assert_eq!(ReproBundle::capture().unwrap().trng_seed(), None);
+        let _model = new_model(1234, 0, Some(1234));
+        assert_eq!(ReproBundle::capture().unwrap().trng_seed(), None);
+        let _model = new_model(1234, 1234, Some(1234));
+        assert_eq!(ReproBundle::capture().unwrap().trng_seed(), Some(1234));
+    }
 }
@@ -10,14 +10,15 @@ use std::rc::Rc;
 
 use caliptra_emu_bus::Clock;
 #[cfg(feature = "coverage")]
-use caliptra_emu_cpu::CoverageBitmaps;
+use caliptra_emu_cpu::{BranchCoverageBitmaps, CoverageBitmaps};
 use caliptra_emu_cpu::{Cpu, InstrTracer};
 use caliptra_emu_periph::ActionCb;
 use caliptra_emu_periph::ReadyForFwCb;
 use caliptra_emu_periph::{CaliptraRootBus, CaliptraRootBusArgs, SocToCaliptraBus, TbServicesCb};
 use caliptra_emu_types::{RvAddr, RvData, RvSize};
 use caliptra_hw_model_types::ErrorInjectionMode;
-use caliptra_image_types::IMAGE_MANIFEST_BYTE_SIZE;
+use caliptra_image_types::ImageManifest;
+use zerocopy::FromBytes;
 
 use crate::bus_logger::BusLogger;
 use crate::bus_logger::LogFile;
@@ -65,10 +66,19 @@ pub struct ModelEmulated {
     // Keep this even when not including the coverage feature to keep the
     // interface consistent
     _rom_image_tag: u64,
-    iccm_image_tag: Option<u64>,
+    _iccm_images: Vec<IccmImage>,
     trng_mode: TrngMode,
 }
 
+/// An FMC or runtime image uploaded to the model, identified the same way as
+/// `caliptra_coverage::get_tag_from_fw_id` identifies its firmware ID.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+struct IccmImage {
+    tag: u64,
+    load_addr: u32,
+    size: u32,
+}
+
 #[cfg(feature = "coverage")]
 impl Drop for ModelEmulated {
     fn drop(&mut self) {
@@ -79,17 +89,42 @@ impl Drop for ModelEmulated {
         }
 
         let CoverageBitmaps { rom, iccm } = self.code_coverage_bitmap();
+        let BranchCoverageBitmaps {
+            rom: rom_branches,
+            iccm: iccm_branches,
+        } = self.cpu.code_coverage.branch_coverage_bitmaps();
         let _ = caliptra_coverage::dump_emu_coverage_to_file(
             cov_path.as_str(),
             self._rom_image_tag,
             rom,
         );
+        let _ = caliptra_coverage::dump_emu_branch_coverage_to_file(
+            cov_path.as_str(),
+            self._rom_image_tag,
+            &caliptra_coverage::BranchCoverage {
+                taken: rom_branches.taken.clone(),
+                not_taken: rom_branches.not_taken.clone(),
+            },
+        );
 
-        if let Some(iccm_image_tag) = self.iccm_image_tag {
+        // Split ICCM hits between the images that were loaded. If an update
+        // reset replaced an image in place, both images get the hits.
+        for image in self._iccm_images.iter() {
+            let mask = |bitmap: &bit_vec::BitVec| {
+                caliptra_coverage::mask_iccm_bitmap(bitmap, image.load_addr, image.size)
+            };
             let _ = caliptra_coverage::dump_emu_coverage_to_file(
                 cov_path.as_str(),
-                iccm_image_tag,
-                iccm,
+                image.tag,
+                &mask(iccm),
+            );
+            let _ = caliptra_coverage::dump_emu_branch_coverage_to_file(
+                cov_path.as_str(),
+                image.tag,
+                &caliptra_coverage::BranchCoverage {
+                    taken: mask(iccm_branches.taken),
+                    not_taken: mask(iccm_branches.not_taken),
+                },
             );
         }
     }
@@ -208,7 +243,7 @@ impl HwModel for ModelEmulated {
             cpu_enabled,
             trace_path: trace_path_or_env(params.trace_path),
             _rom_image_tag: image_tag,
-            iccm_image_tag: None,
+            _iccm_images: vec![],
             trng_mode,
         };
         // Turn tracing on if the trace path was set
@@ -246,8 +281,26 @@ impl HwModel for ModelEmulated {
     }
 
     fn cover_fw_mage(&mut self, fw_image: &[u8]) {
-        let iccm_image = &fw_image[IMAGE_MANIFEST_BYTE_SIZE..];
-        self.iccm_image_tag = Some(hash_slice(iccm_image));
+        let Ok((manifest, _)) = ImageManifest::read_from_prefix(fw_image) else {
+            return;
+        };
+        for toc in [&manifest.fmc, &manifest.runtime] {
+            let Some(image) = toc
+                .image_range()
+                .ok()
+                .and_then(|range| fw_image.get(range.start as usize..range.end as usize))
+            else {
+                continue;
+            };
+            let image = IccmImage {
+                tag: hash_slice(image),
+                load_addr: toc.load_addr,
+                size: toc.size,
+            };
+            if !self._iccm_images.contains(&image) {
+                self._iccm_images.push(image);
+            }
+        }
     }
     fn tracing_hint(&mut self, enable: bool) {
         if enable == self.trace_fn.is_some() {
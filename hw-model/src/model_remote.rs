@@ -0,0 +1,246 @@
+// Licensed under the Apache-2.0 license
+
+use std::error::Error;
+use std::io;
+
+use caliptra_emu_bus::{Bus, BusError, BusMmio};
+use caliptra_emu_types::{RvAddr, RvData, RvSize};
+use caliptra_hw_model_types::{ErrorInjectionMode, EtrngResponse};
+
+use crate::output::ExitStatus;
+use crate::remote_protocol::{
+    ModelStatus, RemoteAddr, RemoteInitParams, RemoteStream, Request, RequestResult, Response,
+    ETRNG_LOW_WATER, ITRNG_LOW_WATER,
+};
+use crate::{trace_path_or_env, HwModel, InitParams, Output, SocManager, TrngMode};
+
+/// Environment variable holding the address of the model server that
+/// [`ModelRemote`] connects to when created with [`HwModel::new_unbooted`],
+/// either `host:port` or `unix:<path>`.
+pub const REMOTE_MODEL_ADDR_ENV: &str = "CPTRA_REMOTE_MODEL_ADDR";
+
+const ITRNG_BATCH: usize = 8192;
+const ETRNG_BATCH: usize = 16;
+
+pub struct RemoteApbBus<'a> {
+    model: &'a mut ModelRemote,
+}
+
+impl<'a> Bus for RemoteApbBus<'a> {
+    fn read(&mut self, size: RvSize, addr: RvAddr) -> Result<RvData, BusError> {
+        self.model.call(Request::ApbRead { size, addr })
+    }
+    fn write(&mut self, size: RvSize, addr: RvAddr, val: RvData) -> Result<(), BusError> {
+        self.model
+            .call(Request::ApbWrite { size, addr, val })
+            .map(|_| ())
+    }
+}
+
+/// A model running in another process, such as `caliptra-emu --serve`,
+/// reached over TCP or a Unix socket. Every APB access is a round trip to the
+/// server; [`HwModel::step_until_event()`] runs many cycles per round trip.
+pub struct ModelRemote {
+    stream: RemoteStream,
+    output: Output,
+    ready_for_fw: bool,
+    trng_mode: TrngMode,
+
+    // None once exhausted, or if the TRNG mode doesn't use them
+    itrng_nibbles: Option<Box<dyn Iterator<Item = u8> + Send>>,
+    etrng_responses: Option<Box<dyn Iterator<Item = EtrngResponse> + Send>>,
+}
+
+impl ModelRemote {
+    /// Connects to the model server at `addr`, which creates a new model
+    /// from `params`.
+    pub fn connect(addr: &RemoteAddr, params: InitParams) -> Result<Self, Box<dyn Error>> {
+        let stream = RemoteStream::connect(addr)
+            .map_err(|e| format!("Unable to connect to model server {addr}: {e}"))?;
+        let trng_mode = TrngMode::resolve(params.trng_mode);
+        let init = RemoteInitParams {
+            rom: params.rom.to_vec(),
+            dccm: params.dccm.to_vec(),
+            iccm: params.iccm.to_vec(),
+            security_state: params.security_state.into(),
+            cptra_obf_key: params.cptra_obf_key,
+            trng_mode,
+            random_sram_puf: params.random_sram_puf,
+            trace_path: trace_path_or_env(params.trace_path)
+                .map(|path| path.to_string_lossy().into_owned()),
+        };
+        let mut m = ModelRemote {
+            stream,
+            output: Output::new(params.log_writer),
+            ready_for_fw: false,
+            trng_mode,
+            itrng_nibbles: (trng_mode == TrngMode::Internal).then_some(params.itrng_nibbles),
+            etrng_responses: (trng_mode == TrngMode::External).then_some(params.etrng_responses),
+        };
+        match m.transact(&Request::Init(init))? {
+            RequestResult::Ok(_) => Ok(m),
+            RequestResult::BusError(e) => Err(format!("Model server init failed: {e:?}").into()),
+            RequestResult::Failed(msg) => Err(format!("Model server init failed: {msg}").into()),
+        }
+    }
+
+    /// Sends `req`, applies the status from the response, and tops up the
+    /// server's TRNG entropy if it is running low.
+    fn transact(&mut self, req: &Request) -> io::Result<RequestResult> {
+        let resp = self.send(req)?;
+        self.feed_trng(&resp.status)?;
+        Ok(resp.result)
+    }
+
+    fn send(&mut self, req: &Request) -> io::Result<Response> {
+        req.write_to(&mut self.stream)?;
+        let resp = Response::read_from(&mut self.stream)?;
+        self.apply_status(&resp.status);
+        Ok(resp)
+    }
+
+    fn apply_status(&mut self, status: &ModelStatus) {
+        self.ready_for_fw = status.ready_for_fw;
+        let sink = self.output.sink();
+        sink.set_now(status.cycle);
+        for &ch in &status.uart_output {
+            sink.push_uart_char(ch);
+        }
+        if self.output.exit_status().is_none() {
+            match status.exit_status {
+                Some(ExitStatus::Passed) => sink.push_uart_char(0xff),
+                Some(ExitStatus::Failed) => sink.push_uart_char(0x01),
+                None => {}
+            }
+        }
+    }
+
+    fn feed_trng(&mut self, status: &ModelStatus) -> io::Result<()> {
+        let mut itrng_nibbles = vec![];
+        if status.itrng_nibbles_queued < ITRNG_LOW_WATER {
+            if let Some(iter) = &mut self.itrng_nibbles {
+                itrng_nibbles.extend(iter.take(ITRNG_BATCH));
+                if itrng_nibbles.len() < ITRNG_BATCH {
+                    self.itrng_nibbles = None;
+                }
+            }
+        }
+        let mut etrng_responses = vec![];
+        if status.etrng_responses_queued < ETRNG_LOW_WATER {
+            if let Some(iter) = &mut self.etrng_responses {
+                etrng_responses.extend(iter.take(ETRNG_BATCH));
+                if etrng_responses.len() < ETRNG_BATCH {
+                    self.etrng_responses = None;
+                }
+            }
+        }
+        if itrng_nibbles.is_empty() && etrng_responses.is_empty() {
+            return Ok(());
+        }
+        self.send(&Request::FeedTrng {
+            itrng_nibbles,
+            etrng_responses,
+        })?;
+        Ok(())
+    }
+
+    /// Executes a request the HwModel trait has no way to report failure
+    /// for; panics if the server couldn't carry it out.
+    fn call(&mut self, req: Request) -> Result<u32, BusError> {
+        match self.transact(&req) {
+            Ok(RequestResult::Ok(val)) => Ok(val),
+            Ok(RequestResult::BusError(e)) => Err(e),
+            Ok(RequestResult::Failed(msg)) => panic!("Model server failed {req:?}: {msg}"),
+            Err(e) => panic!("Lost connection to model server: {e}"),
+        }
+    }
+
+    fn call_infallible(&mut self, req: Request) -> u32 {
+        match self.call(req) {
+            Ok(val) => val,
+            Err(e) => panic!("Unexpected bus error from model server: {e:?}"),
+        }
+    }
+}
+
+impl SocManager for ModelRemote {
+    type TMmio<'a> = BusMmio<RemoteApbBus<'a>>;
+
+    fn delay(&mut self) {
+        self.step();
+    }
+
+    fn mmio_mut(&mut self) -> Self::TMmio<'_> {
+        BusMmio::new(self.apb_bus())
+    }
+
+    const SOC_IFC_ADDR: u32 = 0x3003_0000;
+    const SOC_IFC_TRNG_ADDR: u32 = 0x3003_0000;
+    const SOC_SHA512_ACC_ADDR: u32 = 0x3002_1000;
+    const SOC_MBOX_ADDR: u32 = 0x3002_0000;
+
+    const MAX_WAIT_CYCLES: u32 = 20_000_000;
+}
+
+impl HwModel for ModelRemote {
+    type TBus<'a> = RemoteApbBus<'a>;
+
+    fn new_unbooted(params: InitParams) -> Result<Self, Box<dyn Error>>
+    where
+        Self: Sized,
+    {
+        let addr = std::env::var(REMOTE_MODEL_ADDR_ENV)
+            .map_err(|_| format!("{REMOTE_MODEL_ADDR_ENV} must be set to use ModelRemote"))?;
+        // Infallible
+        let addr: RemoteAddr = addr.parse().unwrap();
+        Self::connect(&addr, params)
+    }
+
+    fn type_name(&self) -> &'static str {
+        "ModelRemote"
+    }
+
+    fn trng_mode(&self) -> TrngMode {
+        self.trng_mode
+    }
+
+    fn apb_bus(&mut self) -> Self::TBus<'_> {
+        RemoteApbBus { model: self }
+    }
+
+    fn step(&mut self) {
+        self.call_infallible(Request::Step { cycles: 1 });
+    }
+
+    fn step_until_event(&mut self, max_cycles: u32) -> u32 {
+        self.call_infallible(Request::StepUntilEvent { max_cycles })
+    }
+
+    fn output(&mut self) -> &mut Output {
+        &mut self.output
+    }
+
+    fn ready_for_fw(&self) -> bool {
+        self.ready_for_fw
+    }
+
+    fn warm_reset(&mut self) {
+        self.call_infallible(Request::WarmReset);
+    }
+
+    fn cold_reset(&mut self) {
+        self.call_infallible(Request::ColdReset);
+    }
+
+    fn tracing_hint(&mut self, enable: bool) {
+        self.call_infallible(Request::TracingHint { enable });
+    }
+
+    fn ecc_error_injection(&mut self, mode: ErrorInjectionMode) {
+        self.call_infallible(Request::EccErrorInjection(mode));
+    }
+
+    fn set_apb_pauser(&mut self, pauser: u32) {
+        self.call_infallible(Request::SetApbPauser(pauser));
+    }
+}
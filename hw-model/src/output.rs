@@ -17,6 +17,9 @@ pub enum ExitStatus {
 struct OutputSinkImpl {
     exit_status: Cell<Option<ExitStatus>>,
     new_uart_output: Cell<String>,
+    // Every byte written to the UART, for forwarding to a remote client.
+    // None unless enabled with `record_raw_uart()`.
+    raw_uart_output: RefCell<Option<Vec<u8>>>,
     log_writer: RefCell<LineWriter<Box<dyn std::io::Write>>>,
     at_start_of_line: Cell<bool>,
     now: Cell<u64>,
@@ -82,12 +85,33 @@ impl OutputSink {
     pub fn now(&self) -> u64 {
         self.0.now.get()
     }
+    /// Starts keeping a copy of every byte written to the UART, including
+    /// the bytes that end a test case.
+    pub(crate) fn record_raw_uart(&self) {
+        self.0.raw_uart_output.replace(Some(vec![]));
+    }
+    /// Number of raw UART bytes recorded since the last `take_raw_uart()`.
+    pub(crate) fn raw_uart_len(&self) -> usize {
+        self.0.raw_uart_output.borrow().as_ref().map_or(0, Vec::len)
+    }
+    /// Takes the raw UART bytes recorded since the last call.
+    pub(crate) fn take_raw_uart(&self) -> Vec<u8> {
+        self.0
+            .raw_uart_output
+            .borrow_mut()
+            .as_mut()
+            .map(std::mem::take)
+            .unwrap_or_default()
+    }
     pub fn push_uart_char(&self, ch: u8) {
         const UART_LOG_PREFIX: &[u8] = b"UART: ";
 
         const TESTCASE_FAILED: u8 = 0x01;
         const TESTCASE_PASSED: u8 = 0xff;
 
+        if let Some(raw) = self.0.raw_uart_output.borrow_mut().as_mut() {
+            raw.push(ch);
+        }
         match ch {
             TESTCASE_PASSED => {
                 // This is the same string as printed by the verilog test-bench
@@ -173,6 +197,7 @@ impl Output {
             output: "".into(),
             sink: OutputSink(Rc::new(OutputSinkImpl {
                 new_uart_output: Default::default(),
+                raw_uart_output: RefCell::new(None),
                 log_writer: RefCell::new(LineWriter::new(log_writer)),
                 exit_status: Cell::new(None),
                 at_start_of_line: Cell::new(true),
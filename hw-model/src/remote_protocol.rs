@@ -0,0 +1,660 @@
+// Licensed under the Apache-2.0 license
+
+//! Framed protocol spoken between [`crate::ModelRemote`] and a model server
+//! started with [`crate::serve_remote_model`] (for example `caliptra-emu
+//! --serve`).
+//!
+//! Each frame is a little-endian u32 length followed by that many bytes of
+//! message: a one-byte tag followed by the message fields, all integers
+//! little-endian and all byte strings prefixed with a u32 length. The client
+//! sends one [`Request`] and waits for exactly one [`Response`]. Every
+//! response carries a [`ModelStatus`] so the client can track UART output
+//! and the ready_for_fw wire without extra round trips.
+
+use std::io::{self, ErrorKind, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::PathBuf;
+
+use caliptra_emu_bus::BusError;
+use caliptra_emu_types::RvSize;
+use caliptra_hw_model_types::{ErrorInjectionMode, EtrngResponse};
+
+use crate::output::ExitStatus;
+use crate::TrngMode;
+
+/// Frames larger than this are rejected rather than allocated.
+const MAX_FRAME_LEN: u32 = 64 * 1024 * 1024;
+
+// The emulator can draw over a thousand nibbles in a single register access,
+// so the client keeps plenty queued on the server. A [`Request::StepUntilEvent`]
+// stops early when a queue drops below its low-water mark so it can be
+// topped up.
+pub(crate) const ITRNG_LOW_WATER: u32 = 4096;
+pub(crate) const ETRNG_LOW_WATER: u32 = 4;
+
+/// Where a model server listens: `unix:<path>` for a Unix socket, anything
+/// else is a TCP `host:port`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum RemoteAddr {
+    Tcp(String),
+    Unix(PathBuf),
+}
+impl std::str::FromStr for RemoteAddr {
+    type Err = std::convert::Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.strip_prefix("unix:") {
+            Some(path) => Self::Unix(path.into()),
+            None => Self::Tcp(s.into()),
+        })
+    }
+}
+impl std::fmt::Display for RemoteAddr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Tcp(addr) => write!(f, "{addr}"),
+            Self::Unix(path) => write!(f, "unix:{}", path.display()),
+        }
+    }
+}
+
+pub(crate) enum RemoteStream {
+    Tcp(TcpStream),
+    Unix(UnixStream),
+}
+impl RemoteStream {
+    pub(crate) fn connect(addr: &RemoteAddr) -> io::Result<Self> {
+        Ok(match addr {
+            RemoteAddr::Tcp(addr) => {
+                let stream = TcpStream::connect(addr)?;
+                // Almost every message is tiny and waits for a reply.
+                stream.set_nodelay(true)?;
+                Self::Tcp(stream)
+            }
+            RemoteAddr::Unix(path) => Self::Unix(UnixStream::connect(path)?),
+        })
+    }
+}
+impl Read for RemoteStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Self::Tcp(s) => s.read(buf),
+            Self::Unix(s) => s.read(buf),
+        }
+    }
+}
+impl Write for RemoteStream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Self::Tcp(s) => s.write(buf),
+            Self::Unix(s) => s.write(buf),
+        }
+    }
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Self::Tcp(s) => s.flush(),
+            Self::Unix(s) => s.flush(),
+        }
+    }
+}
+
+pub(crate) enum RemoteListener {
+    Tcp(TcpListener),
+    Unix(UnixListener),
+}
+impl RemoteListener {
+    pub(crate) fn bind(addr: &RemoteAddr) -> io::Result<Self> {
+        Ok(match addr {
+            RemoteAddr::Tcp(addr) => Self::Tcp(TcpListener::bind(addr)?),
+            RemoteAddr::Unix(path) => {
+                // Remove the socket left behind by a previous server
+                if path.exists() {
+                    std::fs::remove_file(path)?;
+                }
+                Self::Unix(UnixListener::bind(path)?)
+            }
+        })
+    }
+    pub(crate) fn accept(&self) -> io::Result<RemoteStream> {
+        Ok(match self {
+            Self::Tcp(l) => {
+                let (stream, _) = l.accept()?;
+                stream.set_nodelay(true)?;
+                RemoteStream::Tcp(stream)
+            }
+            Self::Unix(l) => RemoteStream::Unix(l.accept()?.0),
+        })
+    }
+}
+
+fn invalid_data(msg: impl Into<String>) -> io::Error {
+    io::Error::new(ErrorKind::InvalidData, msg.into())
+}
+
+#[derive(Default)]
+struct Encoder(Vec<u8>);
+impl Encoder {
+    fn u8(&mut self, val: u8) -> &mut Self {
+        self.0.push(val);
+        self
+    }
+    fn u32(&mut self, val: u32) -> &mut Self {
+        self.0.extend_from_slice(&val.to_le_bytes());
+        self
+    }
+    fn u64(&mut self, val: u64) -> &mut Self {
+        self.0.extend_from_slice(&val.to_le_bytes());
+        self
+    }
+    fn bytes(&mut self, val: &[u8]) -> &mut Self {
+        self.u32(val.len() as u32);
+        self.0.extend_from_slice(val);
+        self
+    }
+}
+
+struct Decoder<'a>(&'a [u8]);
+impl<'a> Decoder<'a> {
+    fn take(&mut self, len: usize) -> io::Result<&'a [u8]> {
+        if self.0.len() < len {
+            return Err(invalid_data("truncated message"));
+        }
+        let (result, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Ok(result)
+    }
+    fn u8(&mut self) -> io::Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+    fn bool(&mut self) -> io::Result<bool> {
+        Ok(self.u8()? != 0)
+    }
+    fn u32(&mut self) -> io::Result<u32> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+    fn u64(&mut self) -> io::Result<u64> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+    fn bytes(&mut self) -> io::Result<&'a [u8]> {
+        let len = self.u32()?;
+        self.take(len as usize)
+    }
+    fn string(&mut self) -> io::Result<String> {
+        String::from_utf8(self.bytes()?.to_vec()).map_err(|_| invalid_data("invalid UTF-8"))
+    }
+    fn finish(&self) -> io::Result<()> {
+        if !self.0.is_empty() {
+            return Err(invalid_data("trailing bytes in message"));
+        }
+        Ok(())
+    }
+}
+
+fn write_frame(w: &mut impl Write, msg: &[u8]) -> io::Result<()> {
+    w.write_all(&(msg.len() as u32).to_le_bytes())?;
+    w.write_all(msg)?;
+    w.flush()
+}
+
+/// Returns None if the peer closed the connection between frames.
+fn read_frame(r: &mut impl Read) -> io::Result<Option<Vec<u8>>> {
+    let mut len = [0u8; 4];
+    match r.read_exact(&mut len) {
+        Ok(()) => {}
+        Err(e) if e.kind() == ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e),
+    }
+    let len = u32::from_le_bytes(len);
+    if len > MAX_FRAME_LEN {
+        return Err(invalid_data(format!("frame too large: {len} bytes")));
+    }
+    let mut msg = vec![0u8; len as usize];
+    r.read_exact(&mut msg)?;
+    Ok(Some(msg))
+}
+
+/// The parts of [`crate::InitParams`] that are sent to the server. TRNG
+/// entropy is streamed separately with [`Request::FeedTrng`].
+#[derive(Debug, Eq, PartialEq)]
+pub(crate) struct RemoteInitParams {
+    pub rom: Vec<u8>,
+    pub dccm: Vec<u8>,
+    pub iccm: Vec<u8>,
+    pub security_state: u32,
+    pub cptra_obf_key: [u32; 8],
+    pub trng_mode: TrngMode,
+    pub random_sram_puf: bool,
+    pub trace_path: Option<String>,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub(crate) enum Request {
+    /// Must be the first request on a connection.
+    Init(RemoteInitParams),
+    ApbRead {
+        size: RvSize,
+        addr: u32,
+    },
+    ApbWrite {
+        size: RvSize,
+        addr: u32,
+        val: u32,
+    },
+    Step {
+        cycles: u32,
+    },
+    /// Steps until ready_for_fw changes, the UART is written, the firmware
+    /// exits, TRNG entropy runs low, or `max_cycles` have elapsed. The result
+    /// is the number of cycles stepped.
+    StepUntilEvent {
+        max_cycles: u32,
+    },
+    WarmReset,
+    ColdReset,
+    TracingHint {
+        enable: bool,
+    },
+    EccErrorInjection(ErrorInjectionMode),
+    SetApbPauser(u32),
+    FeedTrng {
+        itrng_nibbles: Vec<u8>,
+        etrng_responses: Vec<EtrngResponse>,
+    },
+}
+
+// Request tags
+const INIT: u8 = 0;
+const APB_READ: u8 = 1;
+const APB_WRITE: u8 = 2;
+const STEP: u8 = 3;
+const WARM_RESET: u8 = 4;
+const COLD_RESET: u8 = 5;
+const TRACING_HINT: u8 = 6;
+const ECC_ERROR_INJECTION: u8 = 7;
+const SET_APB_PAUSER: u8 = 8;
+const FEED_TRNG: u8 = 9;
+const STEP_UNTIL_EVENT: u8 = 10;
+
+fn trng_mode_to_u8(mode: TrngMode) -> u8 {
+    match mode {
+        TrngMode::Internal => 0,
+        TrngMode::External => 1,
+    }
+}
+
+fn trng_mode_from_u8(val: u8) -> io::Result<TrngMode> {
+    match val {
+        0 => Ok(TrngMode::Internal),
+        1 => Ok(TrngMode::External),
+        _ => Err(invalid_data(format!("unknown TRNG mode {val}"))),
+    }
+}
+
+fn error_injection_to_u8(mode: ErrorInjectionMode) -> u8 {
+    match mode {
+        ErrorInjectionMode::None => 0,
+        ErrorInjectionMode::IccmDoubleBitEcc => 1,
+        ErrorInjectionMode::DccmDoubleBitEcc => 2,
+    }
+}
+
+fn error_injection_from_u8(val: u8) -> io::Result<ErrorInjectionMode> {
+    match val {
+        0 => Ok(ErrorInjectionMode::None),
+        1 => Ok(ErrorInjectionMode::IccmDoubleBitEcc),
+        2 => Ok(ErrorInjectionMode::DccmDoubleBitEcc),
+        _ => Err(invalid_data(format!("unknown error injection mode {val}"))),
+    }
+}
+
+fn rv_size_from_u8(val: u8) -> io::Result<RvSize> {
+    match RvSize::from(usize::from(val)) {
+        RvSize::Invalid => Err(invalid_data(format!("invalid access size {val}"))),
+        size => Ok(size),
+    }
+}
+
+impl Request {
+    pub(crate) fn write_to(&self, w: &mut impl Write) -> io::Result<()> {
+        let mut e = Encoder::default();
+        match self {
+            Self::Init(params) => {
+                e.u8(INIT)
+                    .bytes(&params.rom)
+                    .bytes(&params.dccm)
+                    .bytes(&params.iccm)
+                    .u32(params.security_state);
+                for word in params.cptra_obf_key {
+                    e.u32(word);
+                }
+                e.u8(trng_mode_to_u8(params.trng_mode))
+                    .u8(params.random_sram_puf.into())
+                    .bytes(params.trace_path.as_deref().unwrap_or("").as_bytes());
+            }
+            Self::ApbRead { size, addr } => {
+                e.u8(APB_READ).u8(usize::from(*size) as u8).u32(*addr);
+            }
+            Self::ApbWrite { size, addr, val } => {
+                e.u8(APB_WRITE)
+                    .u8(usize::from(*size) as u8)
+                    .u32(*addr)
+                    .u32(*val);
+            }
+            Self::Step { cycles } => {
+                e.u8(STEP).u32(*cycles);
+            }
+            Self::StepUntilEvent { max_cycles } => {
+                e.u8(STEP_UNTIL_EVENT).u32(*max_cycles);
+            }
+            Self::WarmReset => {
+                e.u8(WARM_RESET);
+            }
+            Self::ColdReset => {
+                e.u8(COLD_RESET);
+            }
+            Self::TracingHint { enable } => {
+                e.u8(TRACING_HINT).u8((*enable).into());
+            }
+            Self::EccErrorInjection(mode) => {
+                e.u8(ECC_ERROR_INJECTION).u8(error_injection_to_u8(*mode));
+            }
+            Self::SetApbPauser(pauser) => {
+                e.u8(SET_APB_PAUSER).u32(*pauser);
+            }
+            Self::FeedTrng {
+                itrng_nibbles,
+                etrng_responses,
+            } => {
+                e.u8(FEED_TRNG)
+                    .bytes(itrng_nibbles)
+                    .u32(etrng_responses.len() as u32);
+                for response in etrng_responses {
+                    e.u32(response.delay);
+                    for word in response.data {
+                        e.u32(word);
+                    }
+                }
+            }
+        }
+        write_frame(w, &e.0)
+    }
+
+    /// Returns None if the client closed the connection.
+    pub(crate) fn read_from(r: &mut impl Read) -> io::Result<Option<Self>> {
+        let Some(msg) = read_frame(r)? else {
+            return Ok(None);
+        };
+        let mut d = Decoder(&msg);
+        let result = match d.u8()? {
+            INIT => {
+                let rom = d.bytes()?.to_vec();
+                let dccm = d.bytes()?.to_vec();
+                let iccm = d.bytes()?.to_vec();
+                let security_state = d.u32()?;
+                let mut cptra_obf_key = [0u32; 8];
+                for word in cptra_obf_key.iter_mut() {
+                    *word = d.u32()?;
+                }
+                let trng_mode = trng_mode_from_u8(d.u8()?)?;
+                let random_sram_puf = d.bool()?;
+                let trace_path = Some(d.string()?).filter(|s| !s.is_empty());
+                Self::Init(RemoteInitParams {
+                    rom,
+                    dccm,
+                    iccm,
+                    security_state,
+                    cptra_obf_key,
+                    trng_mode,
+                    random_sram_puf,
+                    trace_path,
+                })
+            }
+            APB_READ => Self::ApbRead {
+                size: rv_size_from_u8(d.u8()?)?,
+                addr: d.u32()?,
+            },
+            APB_WRITE => Self::ApbWrite {
+                size: rv_size_from_u8(d.u8()?)?,
+                addr: d.u32()?,
+                val: d.u32()?,
+            },
+            STEP => Self::Step { cycles: d.u32()? },
+            STEP_UNTIL_EVENT => Self::StepUntilEvent {
+                max_cycles: d.u32()?,
+            },
+            WARM_RESET => Self::WarmReset,
+            COLD_RESET => Self::ColdReset,
+            TRACING_HINT => Self::TracingHint { enable: d.bool()? },
+            ECC_ERROR_INJECTION => Self::EccErrorInjection(error_injection_from_u8(d.u8()?)?),
+            SET_APB_PAUSER => Self::SetApbPauser(d.u32()?),
+            FEED_TRNG => {
+                let itrng_nibbles = d.bytes()?.to_vec();
+                let count = d.u32()?;
+                let mut etrng_responses = vec![];
+                for _ in 0..count {
+                    let delay = d.u32()?;
+                    let mut data = [0u32; 12];
+                    for word in data.iter_mut() {
+                        *word = d.u32()?;
+                    }
+                    etrng_responses.push(EtrngResponse { delay, data });
+                }
+                Self::FeedTrng {
+                    itrng_nibbles,
+                    etrng_responses,
+                }
+            }
+            tag => return Err(invalid_data(format!("unknown request tag {tag}"))),
+        };
+        d.finish()?;
+        Ok(Some(result))
+    }
+}
+
+/// State the server reports after every request.
+#[derive(Debug, Default, Eq, PartialEq)]
+pub(crate) struct ModelStatus {
+    /// The model's cycle count, used to timestamp the client's log.
+    pub cycle: u64,
+    pub ready_for_fw: bool,
+    pub exit_status: Option<ExitStatus>,
+    /// Bytes written to the UART since the previous response, including the
+    /// bytes that end a test case.
+    pub uart_output: Vec<u8>,
+    /// TRNG entropy sent with FeedTrng that the model hasn't consumed yet.
+    pub itrng_nibbles_queued: u32,
+    pub etrng_responses_queued: u32,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub(crate) enum RequestResult {
+    /// The request succeeded; holds the data for ApbRead, the number of
+    /// cycles stepped for StepUntilEvent, and 0 otherwise.
+    Ok(u32),
+    BusError(BusError),
+    /// The server couldn't carry out the request (for example, the model
+    /// doesn't support it).
+    Failed(String),
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub(crate) struct Response {
+    pub result: RequestResult,
+    pub status: ModelStatus,
+}
+
+// RequestResult tags
+const OK: u8 = 0;
+const BUS_ERROR: u8 = 1;
+const FAILED: u8 = 2;
+
+fn bus_error_to_u8(err: BusError) -> u8 {
+    match err {
+        BusError::InstrAccessFault => 0,
+        BusError::LoadAddrMisaligned => 1,
+        BusError::LoadAccessFault => 2,
+        BusError::StoreAddrMisaligned => 3,
+        BusError::StoreAccessFault => 4,
+    }
+}
+
+fn bus_error_from_u8(val: u8) -> io::Result<BusError> {
+    match val {
+        0 => Ok(BusError::InstrAccessFault),
+        1 => Ok(BusError::LoadAddrMisaligned),
+        2 => Ok(BusError::LoadAccessFault),
+        3 => Ok(BusError::StoreAddrMisaligned),
+        4 => Ok(BusError::StoreAccessFault),
+        _ => Err(invalid_data(format!("unknown bus error {val}"))),
+    }
+}
+
+impl Response {
+    pub(crate) fn write_to(&self, w: &mut impl Write) -> io::Result<()> {
+        let mut e = Encoder::default();
+        match &self.result {
+            RequestResult::Ok(val) => e.u8(OK).u32(*val),
+            RequestResult::BusError(err) => e.u8(BUS_ERROR).u8(bus_error_to_u8(*err)),
+            RequestResult::Failed(msg) => e.u8(FAILED).bytes(msg.as_bytes()),
+        };
+        let status = &self.status;
+        e.u64(status.cycle)
+            .u8(status.ready_for_fw.into())
+            .u8(match status.exit_status {
+                None => 0,
+                Some(ExitStatus::Passed) => 1,
+                Some(ExitStatus::Failed) => 2,
+            })
+            .bytes(&status.uart_output)
+            .u32(status.itrng_nibbles_queued)
+            .u32(status.etrng_responses_queued);
+        write_frame(w, &e.0)
+    }
+
+    pub(crate) fn read_from(r: &mut impl Read) -> io::Result<Self> {
+        let Some(msg) = read_frame(r)? else {
+            return Err(io::Error::new(
+                ErrorKind::UnexpectedEof,
+                "model server closed the connection",
+            ));
+        };
+        let mut d = Decoder(&msg);
+        let result = match d.u8()? {
+            OK => RequestResult::Ok(d.u32()?),
+            BUS_ERROR => RequestResult::BusError(bus_error_from_u8(d.u8()?)?),
+            FAILED => RequestResult::Failed(d.string()?),
+            tag => return Err(invalid_data(format!("unknown result tag {tag}"))),
+        };
+        let status = ModelStatus {
+            cycle: d.u64()?,
+            ready_for_fw: d.bool()?,
+            exit_status: match d.u8()? {
+                0 => None,
+                1 => Some(ExitStatus::Passed),
+                2 => Some(ExitStatus::Failed),
+                val => return Err(invalid_data(format!("unknown exit status {val}"))),
+            },
+            uart_output: d.bytes()?.to_vec(),
+            itrng_nibbles_queued: d.u32()?,
+            etrng_responses_queued: d.u32()?,
+        };
+        d.finish()?;
+        Ok(Self { result, status })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip_request(req: Request) {
+        let mut buf = vec![];
+        req.write_to(&mut buf).unwrap();
+        let mut r = buf.as_slice();
+        assert_eq!(Request::read_from(&mut r).unwrap(), Some(req));
+        assert_eq!(Request::read_from(&mut r).unwrap(), None);
+    }
+
+    #[test]
+    fn test_request_round_trip() {
+        round_trip_request(Request::Init(RemoteInitParams {
+            rom: vec![1, 2, 3],
+            dccm: vec![],
+            iccm: vec![4; 17],
+            security_state: 0x5,
+            cptra_obf_key: [0x1111_2222; 8],
+            trng_mode: TrngMode::Internal,
+            random_sram_puf: true,
+            trace_path: Some("/tmp/trace.txt".into()),
+        }));
+        round_trip_request(Request::ApbRead {
+            size: RvSize::HalfWord,
+            addr: 0x3003_0000,
+        });
+        round_trip_request(Request::ApbWrite {
+            size: RvSize::Word,
+            addr: 0x3002_0008,
+            val: 0xdead_beef,
+        });
+        round_trip_request(Request::Step { cycles: 1 });
+        round_trip_request(Request::StepUntilEvent {
+            max_cycles: 100_000,
+        });
+        round_trip_request(Request::ColdReset);
+        round_trip_request(Request::EccErrorInjection(
+            ErrorInjectionMode::DccmDoubleBitEcc,
+        ));
+        round_trip_request(Request::FeedTrng {
+            itrng_nibbles: vec![0xa, 0x5],
+            etrng_responses: vec![EtrngResponse {
+                delay: 3,
+                data: [7; 12],
+            }],
+        });
+    }
+
+    #[test]
+    fn test_response_round_trip() {
+        let resp = Response {
+            result: RequestResult::BusError(BusError::StoreAccessFault),
+            status: ModelStatus {
+                cycle: 1_000_000_000_000,
+                ready_for_fw: true,
+                exit_status: Some(ExitStatus::Failed),
+                uart_output: b"hello\n\x80\x01".to_vec(),
+                itrng_nibbles_queued: 12,
+                etrng_responses_queued: 2,
+            },
+        };
+        let mut buf = vec![];
+        resp.write_to(&mut buf).unwrap();
+        assert_eq!(Response::read_from(&mut buf.as_slice()).unwrap(), resp);
+    }
+
+    #[test]
+    fn test_truncated_frame() {
+        let mut buf = vec![];
+        Request::Step { cycles: 5 }.write_to(&mut buf).unwrap();
+        buf[0] -= 1;
+        buf.pop();
+        assert_eq!(
+            Request::read_from(&mut buf.as_slice())
+                .err()
+                .map(|e| e.kind()),
+            Some(ErrorKind::InvalidData)
+        );
+    }
+
+    #[test]
+    fn test_remote_addr() {
+        assert_eq!(
+            "unix:/tmp/caliptra.sock".parse(),
+            Ok(RemoteAddr::Unix("/tmp/caliptra.sock".into()))
+        );
+        assert_eq!(
+            "localhost:5000".parse(),
+            Ok(RemoteAddr::Tcp("localhost:5000".into()))
+        );
+    }
+}
@@ -0,0 +1,196 @@
+// Licensed under the Apache-2.0 license
+
+use std::collections::VecDeque;
+use std::error::Error;
+use std::io;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use caliptra_emu_bus::Bus;
+use caliptra_hw_model_types::EtrngResponse;
+
+use crate::remote_protocol::{
+    ModelStatus, RemoteAddr, RemoteListener, RemoteStream, Request, RequestResult, Response,
+    ETRNG_LOW_WATER, ITRNG_LOW_WATER,
+};
+use crate::{HwModel, InitParams, SecurityState};
+
+/// TRNG entropy fed by the client, shared with the model's iterators.
+#[derive(Default)]
+struct TrngQueues {
+    itrng_nibbles: Arc<Mutex<VecDeque<u8>>>,
+    etrng_responses: Arc<Mutex<VecDeque<EtrngResponse>>>,
+}
+impl TrngQueues {
+    /// Returns which queues are below the level at which the client tops
+    /// them up.
+    fn low(&self) -> (bool, bool) {
+        (
+            self.itrng_nibbles.lock().unwrap().len() < ITRNG_LOW_WATER as usize,
+            self.etrng_responses.lock().unwrap().len() < ETRNG_LOW_WATER as usize,
+        )
+    }
+}
+
+/// Listens on `addr` and serves [`crate::ModelRemote`] clients, each on its
+/// own thread. Each connection gets a fresh `M`, created from the client's
+/// InitParams and dropped when the client disconnects, so one server can run
+/// a whole (parallel) test suite.
+pub fn serve_remote_model<M: HwModel + 'static>(addr: &RemoteAddr) -> io::Result<()> {
+    let listener = RemoteListener::bind(addr)?;
+    println!("Serving remote hw-model on {addr}");
+    loop {
+        let mut stream = listener.accept()?;
+        std::thread::spawn(move || {
+            if let Err(e) = serve_connection::<M>(&mut stream) {
+                eprintln!("Remote hw-model connection failed: {e}");
+            }
+        });
+    }
+}
+
+fn serve_connection<M: HwModel>(stream: &mut RemoteStream) -> Result<(), Box<dyn Error>> {
+    let Some(Request::Init(params)) = Request::read_from(stream)? else {
+        return Err("expected Init as the first request".into());
+    };
+    let trng = TrngQueues::default();
+    let init_result = M::new_unbooted(InitParams {
+        rom: &params.rom,
+        dccm: &params.dccm,
+        iccm: &params.iccm,
+        security_state: SecurityState::from(params.security_state),
+        cptra_obf_key: params.cptra_obf_key,
+        itrng_nibbles: {
+            let queue = trng.itrng_nibbles.clone();
+            Box::new(std::iter::from_fn(move || {
+                queue.lock().unwrap().pop_front()
+            }))
+        },
+        etrng_responses: {
+            let queue = trng.etrng_responses.clone();
+            Box::new(std::iter::from_fn(move || {
+                queue.lock().unwrap().pop_front()
+            }))
+        },
+        trng_mode: Some(params.trng_mode),
+        random_sram_puf: params.random_sram_puf,
+        trace_path: params.trace_path.map(PathBuf::from),
+        ..Default::default()
+    });
+    let mut model = match init_result {
+        Ok(mut model) => {
+            model.output().sink().record_raw_uart();
+            model
+        }
+        Err(e) => {
+            Response {
+                result: RequestResult::Failed(e.to_string()),
+                status: ModelStatus::default(),
+            }
+            .write_to(stream)?;
+            return Ok(());
+        }
+    };
+    Response {
+        result: RequestResult::Ok(0),
+        status: model_status(&mut model, &trng),
+    }
+    .write_to(stream)?;
+
+    while let Some(req) = Request::read_from(stream)? {
+        // Report panics (such as a reset the model doesn't implement) to the
+        // client instead of taking the server down.
+        let result = panic::catch_unwind(AssertUnwindSafe(|| handle(&mut model, req, &trng)))
+            .unwrap_or_else(|payload| {
+                RequestResult::Failed(
+                    payload
+                        .downcast_ref::<&str>()
+                        .map(|s| s.to_string())
+                        .or_else(|| payload.downcast_ref::<String>().cloned())
+                        .unwrap_or_else(|| "model panicked".into()),
+                )
+            });
+        Response {
+            result,
+            status: model_status(&mut model, &trng),
+        }
+        .write_to(stream)?;
+    }
+    Ok(())
+}
+
+fn handle<M: HwModel>(model: &mut M, req: Request, trng: &TrngQueues) -> RequestResult {
+    match req {
+        Request::Init(_) => return RequestResult::Failed("model already initialized".into()),
+        Request::ApbRead { size, addr } => {
+            return match model.apb_bus().read(size, addr) {
+                Ok(val) => RequestResult::Ok(val),
+                Err(e) => RequestResult::BusError(e),
+            }
+        }
+        Request::ApbWrite { size, addr, val } => {
+            if let Err(e) = model.apb_bus().write(size, addr, val) {
+                return RequestResult::BusError(e);
+            }
+        }
+        Request::Step { cycles } => {
+            for _ in 0..cycles {
+                model.step();
+            }
+        }
+        Request::StepUntilEvent { max_cycles } => {
+            return RequestResult::Ok(step_until_event(model, max_cycles, trng))
+        }
+        Request::WarmReset => model.warm_reset(),
+        Request::ColdReset => model.cold_reset(),
+        Request::TracingHint { enable } => model.tracing_hint(enable),
+        Request::EccErrorInjection(mode) => model.ecc_error_injection(mode),
+        Request::SetApbPauser(pauser) => model.set_apb_pauser(pauser),
+        Request::FeedTrng {
+            itrng_nibbles,
+            etrng_responses,
+        } => {
+            trng.itrng_nibbles.lock().unwrap().extend(itrng_nibbles);
+            trng.etrng_responses.lock().unwrap().extend(etrng_responses);
+        }
+    }
+    RequestResult::Ok(0)
+}
+
+fn step_until_event<M: HwModel>(model: &mut M, max_cycles: u32, trng: &TrngQueues) -> u32 {
+    let ready_for_fw = model.ready_for_fw();
+    let exit_status = model.output().exit_status();
+    let uart_len = model.output().sink().raw_uart_len();
+    // Only stop for a queue that runs low during this request; one that is
+    // already low is one the client has nothing more for.
+    let (itrng_low, etrng_low) = trng.low();
+    for cycles in 1..=max_cycles {
+        model.step();
+        if model.ready_for_fw() != ready_for_fw
+            || model.output().exit_status() != exit_status
+            || model.output().sink().raw_uart_len() != uart_len
+            || trng.low() != (itrng_low, etrng_low)
+        {
+            return cycles;
+        }
+    }
+    max_cycles
+}
+
+fn model_status<M: HwModel>(model: &mut M, trng: &TrngQueues) -> ModelStatus {
+    let output = model.output();
+    let cycle = output.sink().now();
+    let exit_status = output.exit_status();
+    // The client gets the raw bytes; drop the server's copy of the text.
+    output.take(usize::MAX);
+    let uart_output = output.sink().take_raw_uart();
+    ModelStatus {
+        cycle,
+        ready_for_fw: model.ready_for_fw(),
+        exit_status,
+        uart_output,
+        itrng_nibbles_queued: trng.itrng_nibbles.lock().unwrap().len() as u32,
+        etrng_responses_queued: trng.etrng_responses.lock().unwrap().len() as u32,
+    }
+}
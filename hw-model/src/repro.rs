@@ -0,0 +1,912 @@
+// Licensed under the Apache-2.0 license
+
+//! Reproducer bundles for failed tests.
+//!
+//! Models created with [`crate::new`], [`crate::new_unbooted`] or
+//! [`HwModel::new`] record everything needed to re-run the test: the
+//! InitParams, the entropy the model consumed, every [`HwModel::boot`] and
+//! every mailbox command sent by the test. If [`REPRO_DIR_ENV`] is set and
+//! the test thread panics, the recording is written to a bundle directory
+//! that `caliptra-emu --replay` can re-run.
+//!
+//! Only those operations are recorded (along with warm resets made with
+//! [`HwModel::warm_reset_flow`]). Bare [`HwModel::cold_reset`] and
+//! [`HwModel::warm_reset`] calls, and registers the test reads or writes
+//! directly over the APB bus, are not recorded and so aren't replayed.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::Write as _;
+use std::io::{self, Write as _};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, Once};
+use std::{fs, panic, process, thread};
+
+use caliptra_api_types::{DeviceLifecycle, Fuses, SecurityState, U4};
+use caliptra_hw_model_types::{EtrngResponse, RandomEtrngResponses, RandomNibbles};
+use rand::{rngs::StdRng, SeedableRng};
+use sha2::Digest;
+
+use crate::output::OutputSink;
+use crate::{BootParams, HwModel, InitParams, TrngMode, FW_LOAD_CMD_OPCODE};
+
+/// Environment variable holding the directory reproducer bundles are written
+/// to. Bundles are only written when it is set.
+pub const REPRO_DIR_ENV: &str = "CPTRA_REPRO_DIR";
+
+const MANIFEST_FILE: &str = "manifest.txt";
+const ETRNG_RESPONSE_WORDS: usize = 13;
+
+/// Number of items from each TRNG iterator compared against the seed.
+const SEED_CHECK_LEN: usize = 8;
+
+#[derive(Clone, Debug)]
+struct RecordedBoot {
+    fuses: Fuses,
+    fw_image: Option<Vec<u8>>,
+    initial_dbg_manuf_service_reg: u32,
+    initial_repcnt_thresh_reg: Option<u32>,
+    initial_adaptp_thresh_reg: Option<u32>,
+    valid_pauser: Vec<u32>,
+    wdt_timeout_cycles: u64,
+}
+
+#[derive(Clone, Debug)]
+enum ReproEvent {
+    Boot { cycle: u64, params: RecordedBoot },
+    Mailbox { cycle: u64, cmd: u32, data: Vec<u8> },
+    WarmReset { cycle: u64, fuses: Fuses },
+}
+impl ReproEvent {
+    fn cycle(&self) -> u64 {
+        match self {
+            Self::Boot { cycle, .. } => *cycle,
+            Self::Mailbox { cycle, .. } => *cycle,
+            Self::WarmReset { cycle, .. } => *cycle,
+        }
+    }
+}
+
+/// Everything needed to re-run a test scenario on a fresh model: the
+/// InitParams, the TRNG entropy the model consumed, the fuses and firmware it
+/// was booted with, and the mailbox commands sent to it.
+#[derive(Clone, Debug)]
+pub struct ReproBundle {
+    model: String,
+    rom: Vec<u8>,
+    dccm: Vec<u8>,
+    iccm: Vec<u8>,
+    security_state: SecurityState,
+    cptra_obf_key: [u32; 8],
+    trng_mode: TrngMode,
+    trng_seed: Option<u64>,
+    random_sram_puf: bool,
+    itrng_nibbles: Vec<u8>,
+    etrng_responses: Vec<EtrngResponse>,
+    events: Vec<ReproEvent>,
+    failure_cycle: u64,
+}
+
+struct Recorder {
+    bundle: ReproBundle,
+    itrng_nibbles: Arc<Mutex<Vec<u8>>>,
+    etrng_responses: Arc<Mutex<Vec<EtrngResponse>>>,
+    sink: Option<OutputSink>,
+    paused: bool,
+}
+impl Recorder {
+    fn capture(&self) -> ReproBundle {
+        let mut bundle = self.bundle.clone();
+        bundle.itrng_nibbles = lock(&self.itrng_nibbles).clone();
+        bundle.etrng_responses = lock(&self.etrng_responses).clone();
+        if let Some(sink) = &self.sink {
+            bundle.failure_cycle = sink.now();
+        }
+        bundle
+    }
+}
+
+thread_local! {
+    static RECORDER: RefCell<Option<Recorder>> = const { RefCell::new(None) };
+}
+
+fn lock<T>(m: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    // The recording is still useful if a panic poisoned the mutex.
+    m.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn with_recorder(f: impl FnOnce(&mut Recorder)) {
+    RECORDER.with(|r| {
+        if let Some(recorder) = r.borrow_mut().as_mut() {
+            if !recorder.paused {
+                f(recorder);
+            }
+        }
+    });
+}
+
+fn recording_iter<T: Clone + Send + 'static>(
+    inner: Box<dyn Iterator<Item = T> + Send>,
+    log: Arc<Mutex<Vec<T>>>,
+) -> Box<dyn Iterator<Item = T> + Send> {
+    Box::new(inner.inspect(move |val| lock(&log).push(val.clone())))
+}
+
+/// Returns true if `iter` starts with the first items of `expected`. The
+/// items read from `iter` are put back.
+fn starts_with<T: Clone + PartialEq + Send + 'static>(
+    iter: &mut Box<dyn Iterator<Item = T> + Send>,
+    expected: impl Iterator<Item = T>,
+) -> bool {
+    let mut inner = std::mem::replace(iter, Box::new(std::iter::empty()));
+    let head: Vec<T> = inner.by_ref().take(SEED_CHECK_LEN).collect();
+    let matches = head.iter().cloned().eq(expected.take(SEED_CHECK_LEN));
+    *iter = Box::new(head.into_iter().chain(inner));
+    matches
+}
+
+/// Clears `params.trng_seed` unless the TRNG iterators are the ones
+/// [`InitParams::default()`] generates from it; a test that supplies its own
+/// entropy doesn't get it from the seed.
+pub(crate) fn derive_trng_seed(params: &mut InitParams) {
+    let Some(seed) = params.trng_seed else {
+        return;
+    };
+    if !starts_with(
+        &mut params.itrng_nibbles,
+        RandomNibbles(StdRng::seed_from_u64(seed)),
+    ) || !starts_with(
+        &mut params.etrng_responses,
+        RandomEtrngResponses(StdRng::seed_from_u64(seed)),
+    ) {
+        params.trng_seed = None;
+    }
+}
+
+/// Creates a model with `M::new_unbooted()`, and starts recording it for a
+/// reproducer bundle, replacing any recording previously made on this thread.
+/// If [`REPRO_DIR_ENV`] is set, a panic hook that writes the bundle is
+/// installed.
+pub(crate) fn new_unbooted<M: HwModel>(mut params: InitParams) -> Result<M, Box<dyn Error>> {
+    if std::env::var_os(REPRO_DIR_ENV).is_some() {
+        static INSTALL_PANIC_HOOK: Once = Once::new();
+        INSTALL_PANIC_HOOK.call_once(|| {
+            let prev_hook = panic::take_hook();
+            panic::set_hook(Box::new(move |info| {
+                prev_hook(info);
+                write_bundle_on_panic();
+            }));
+        });
+    }
+
+    let itrng_nibbles = Arc::new(Mutex::new(vec![]));
+    let etrng_responses = Arc::new(Mutex::new(vec![]));
+    params.itrng_nibbles = recording_iter(
+        std::mem::replace(&mut params.itrng_nibbles, Box::new(std::iter::empty())),
+        itrng_nibbles.clone(),
+    );
+    params.etrng_responses = recording_iter(
+        std::mem::replace(&mut params.etrng_responses, Box::new(std::iter::empty())),
+        etrng_responses.clone(),
+    );
+    let bundle = ReproBundle {
+        model: String::new(),
+        rom: params.rom.to_vec(),
+        dccm: params.dccm.to_vec(),
+        iccm: params.iccm.to_vec(),
+        security_state: params.security_state,
+        cptra_obf_key: params.cptra_obf_key,
+        trng_mode: TrngMode::resolve(params.trng_mode),
+        trng_seed: params.trng_seed,
+        random_sram_puf: params.random_sram_puf,
+        itrng_nibbles: vec![],
+        etrng_responses: vec![],
+        events: vec![],
+        failure_cycle: 0,
+    };
+    let mut hw = M::new_unbooted(params)?;
+    let recorder = Recorder {
+        bundle: ReproBundle {
+            model: hw.type_name().into(),
+            ..bundle
+        },
+        itrng_nibbles,
+        etrng_responses,
+        sink: Some(hw.output().sink().clone()),
+        paused: false,
+    };
+    RECORDER.with(|r| *r.borrow_mut() = Some(recorder));
+    Ok(hw)
+}
+
+pub(crate) fn record_boot(params: &BootParams, cycle: u64) {
+    with_recorder(|r| {
+        r.bundle.events.push(ReproEvent::Boot {
+            cycle,
+            params: RecordedBoot {
+                fuses: params.fuses.clone(),
+                fw_image: params.fw_image.map(|image| image.to_vec()),
+                initial_dbg_manuf_service_reg: params.initial_dbg_manuf_service_reg,
+                initial_repcnt_thresh_reg: params.initial_repcnt_thresh_reg.map(u32::from),
+                initial_adaptp_thresh_reg: params.initial_adaptp_thresh_reg.map(u32::from),
+                valid_pauser: params.valid_pauser.clone(),
+                wdt_timeout_cycles: params.wdt_timeout_cycles,
+            },
+        })
+    });
+}
+
+pub(crate) fn record_mailbox(cmd: u32, data: &[u8], cycle: u64) {
+    with_recorder(|r| {
+        r.bundle.events.push(ReproEvent::Mailbox {
+            cycle,
+            cmd,
+            data: data.to_vec(),
+        })
+    });
+}
+
+pub(crate) fn record_warm_reset(fuses: &Fuses, cycle: u64) {
+    with_recorder(|r| {
+        r.bundle.events.push(ReproEvent::WarmReset {
+            cycle,
+            fuses: fuses.clone(),
+        })
+    });
+}
+
+/// Stops recording on this thread until the returned guard is dropped, so
+/// that the steps of a recorded operation aren't recorded separately.
+pub(crate) fn pause() -> PauseGuard {
+    let mut was_paused = false;
+    RECORDER.with(|r| {
+        if let Some(recorder) = r.borrow_mut().as_mut() {
+            was_paused = std::mem::replace(&mut recorder.paused, true);
+        }
+    });
+    PauseGuard { was_paused }
+}
+
+pub(crate) struct PauseGuard {
+    was_paused: bool,
+}
+impl Drop for PauseGuard {
+    fn drop(&mut self) {
+        RECORDER.with(|r| {
+            if let Some(recorder) = r.borrow_mut().as_mut() {
+                recorder.paused = self.was_paused;
+            }
+        });
+    }
+}
+
+fn write_bundle_on_panic() {
+    let bundle = RECORDER
+        .try_with(|r| {
+            r.try_borrow()
+                .ok()
+                .and_then(|r| r.as_ref().map(Recorder::capture))
+        })
+        .ok()
+        .flatten();
+    let (Some(bundle), Some(dir)) = (bundle, bundle_dir()) else {
+        return;
+    };
+    match bundle.save(&dir) {
+        Ok(()) => eprintln!(
+            "Wrote reproducer bundle to {}; re-run with `caliptra-emu --replay {}`",
+            dir.display(),
+            dir.display()
+        ),
+        Err(e) => eprintln!(
+            "Unable to write reproducer bundle to {}: {e}",
+            dir.display()
+        ),
+    }
+}
+
+fn bundle_dir() -> Option<PathBuf> {
+    let base = PathBuf::from(std::env::var_os(REPRO_DIR_ENV)?);
+    // libtest names each test thread after the test
+    let name: String = thread::current()
+        .name()
+        .unwrap_or("unnamed")
+        .replace("::", ".")
+        .chars()
+        .map(|ch| {
+            if ch.is_ascii_alphanumeric() || "._-".contains(ch) {
+                ch
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    Some(base.join(format!("{name}-{}", process::id())))
+}
+
+fn invalid_data(msg: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.into())
+}
+
+fn words_to_string(words: &[u32]) -> String {
+    let words: Vec<String> = words.iter().map(|w| format!("{w:08x}")).collect();
+    words.join(" ")
+}
+
+fn option_to_string<T: std::fmt::Display>(val: Option<T>) -> String {
+    val.map_or_else(|| "none".into(), |v| v.to_string())
+}
+
+fn write_fuses(m: &mut String, fuses: &Fuses) {
+    let Fuses {
+        uds_seed,
+        field_entropy,
+        key_manifest_pk_hash,
+        key_manifest_pk_hash_mask,
+        owner_pk_hash,
+        fmc_key_manifest_svn,
+        runtime_svn,
+        anti_rollback_disable,
+        idevid_cert_attr,
+        idevid_manuf_hsm_id,
+        life_cycle,
+        lms_verify,
+        fuse_lms_revocation,
+        soc_stepping_id,
+    } = fuses;
+    let mask = u32::from(*key_manifest_pk_hash_mask);
+    let life_cycle = u32::from(*life_cycle);
+    writeln!(m, "fuses.uds_seed = {}", words_to_string(uds_seed)).unwrap();
+    writeln!(
+        m,
+        "fuses.field_entropy = {}",
+        words_to_string(field_entropy)
+    )
+    .unwrap();
+    writeln!(
+        m,
+        "fuses.key_manifest_pk_hash = {}",
+        words_to_string(key_manifest_pk_hash)
+    )
+    .unwrap();
+    writeln!(m, "fuses.key_manifest_pk_hash_mask = {mask:#x}").unwrap();
+    writeln!(
+        m,
+        "fuses.owner_pk_hash = {}",
+        words_to_string(owner_pk_hash)
+    )
+    .unwrap();
+    writeln!(m, "fuses.fmc_key_manifest_svn = {fmc_key_manifest_svn:#x}").unwrap();
+    writeln!(m, "fuses.runtime_svn = {}", words_to_string(runtime_svn)).unwrap();
+    writeln!(m, "fuses.anti_rollback_disable = {anti_rollback_disable}").unwrap();
+    writeln!(
+        m,
+        "fuses.idevid_cert_attr = {}",
+        words_to_string(idevid_cert_attr)
+    )
+    .unwrap();
+    writeln!(
+        m,
+        "fuses.idevid_manuf_hsm_id = {}",
+        words_to_string(idevid_manuf_hsm_id)
+    )
+    .unwrap();
+    writeln!(m, "fuses.life_cycle = {life_cycle:#x}").unwrap();
+    writeln!(m, "fuses.lms_verify = {lms_verify}").unwrap();
+    writeln!(m, "fuses.fuse_lms_revocation = {fuse_lms_revocation:#x}").unwrap();
+    writeln!(m, "fuses.soc_stepping_id = {soc_stepping_id:#x}").unwrap();
+}
+
+/// One `[name]` section of the manifest (the header has an empty name).
+struct Section {
+    name: String,
+    line: usize,
+    values: HashMap<String, String>,
+}
+impl Section {
+    fn get(&self, key: &str) -> io::Result<&str> {
+        self.values.get(key).map(String::as_str).ok_or_else(|| {
+            invalid_data(format!(
+                "manifest section starting at line {} is missing {key:?}",
+                self.line
+            ))
+        })
+    }
+    fn parse<T>(&self, key: &str, f: impl FnOnce(&str) -> Option<T>) -> io::Result<T> {
+        let val = self.get(key)?;
+        f(val).ok_or_else(|| invalid_data(format!("invalid value for {key:?}: {val:?}")))
+    }
+    fn u32(&self, key: &str) -> io::Result<u32> {
+        self.parse(key, parse_u32)
+    }
+    fn u64(&self, key: &str) -> io::Result<u64> {
+        self.parse(key, |s| s.parse().ok())
+    }
+    fn bool(&self, key: &str) -> io::Result<bool> {
+        self.parse(key, |s| s.parse().ok())
+    }
+    fn words<const N: usize>(&self, key: &str) -> io::Result<[u32; N]> {
+        self.parse(key, |s| parse_words(s)?.try_into().ok())
+    }
+    fn optional<T>(&self, key: &str, f: impl FnOnce(&str) -> Option<T>) -> io::Result<Option<T>> {
+        self.parse(key, |s| {
+            if s == "none" {
+                Some(None)
+            } else {
+                f(s).map(Some)
+            }
+        })
+    }
+    fn fuses(&self) -> io::Result<Fuses> {
+        Ok(Fuses {
+            uds_seed: self.words("fuses.uds_seed")?,
+            field_entropy: self.words("fuses.field_entropy")?,
+            key_manifest_pk_hash: self.words("fuses.key_manifest_pk_hash")?,
+            key_manifest_pk_hash_mask: self.parse("fuses.key_manifest_pk_hash_mask", |s| {
+                U4::try_from(parse_u32(s)?).ok()
+            })?,
+            owner_pk_hash: self.words("fuses.owner_pk_hash")?,
+            fmc_key_manifest_svn: self.u32("fuses.fmc_key_manifest_svn")?,
+            runtime_svn: self.words("fuses.runtime_svn")?,
+            anti_rollback_disable: self.bool("fuses.anti_rollback_disable")?,
+            idevid_cert_attr: self.words("fuses.idevid_cert_attr")?,
+            idevid_manuf_hsm_id: self.words("fuses.idevid_manuf_hsm_id")?,
+            life_cycle: self.parse("fuses.life_cycle", |s| {
+                DeviceLifecycle::try_from(parse_u32(s)?).ok()
+            })?,
+            lms_verify: self.bool("fuses.lms_verify")?,
+            fuse_lms_revocation: self.u32("fuses.fuse_lms_revocation")?,
+            soc_stepping_id: self
+                .parse("fuses.soc_stepping_id", |s| parse_u32(s)?.try_into().ok())?,
+        })
+    }
+}
+
+fn parse_u32(s: &str) -> Option<u32> {
+    match s.strip_prefix("0x") {
+        Some(hex) => u32::from_str_radix(hex, 16).ok(),
+        None => s.parse().ok(),
+    }
+}
+
+fn parse_words(s: &str) -> Option<Vec<u32>> {
+    s.split_whitespace()
+        .map(|w| u32::from_str_radix(w, 16).ok())
+        .collect()
+}
+
+fn parse_manifest(manifest: &str) -> io::Result<Vec<Section>> {
+    let mut sections = vec![Section {
+        name: String::new(),
+        line: 1,
+        values: HashMap::new(),
+    }];
+    for (i, line) in manifest.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            sections.push(Section {
+                name: name.into(),
+                line: i + 1,
+                values: HashMap::new(),
+            });
+            continue;
+        }
+        let Some((key, val)) = line.split_once('=') else {
+            return Err(invalid_data(format!(
+                "manifest line {}: expected key = value",
+                i + 1
+            )));
+        };
+        // Infallible; sections always has the header
+        let section = sections.last_mut().unwrap();
+        section.values.insert(key.trim().into(), val.trim().into());
+    }
+    Ok(sections)
+}
+
+fn trng_mode_name(mode: TrngMode) -> &'static str {
+    match mode {
+        TrngMode::Internal => "internal",
+        TrngMode::External => "external",
+    }
+}
+
+impl ReproBundle {
+    /// Returns a snapshot of the recording made on this thread, if a model
+    /// has been created on it.
+    pub fn capture() -> Option<Self> {
+        RECORDER.with(|r| r.borrow().as_ref().map(Recorder::capture))
+    }
+
+    /// Writes the bundle to `dir`: a `manifest.txt` describing the scenario,
+    /// plus the ROM, SRAM contents, entropy, firmware images and mailbox
+    /// requests it refers to.
+    pub fn save(&self, dir: &Path) -> io::Result<()> {
+        fs::create_dir_all(dir)?;
+        fs::write(dir.join("rom.bin"), &self.rom)?;
+        fs::write(dir.join("dccm.bin"), &self.dccm)?;
+        fs::write(dir.join("iccm.bin"), &self.iccm)?;
+        fs::write(dir.join("itrng_nibbles.bin"), &self.itrng_nibbles)?;
+        let mut etrng_responses = vec![];
+        for resp in self.etrng_responses.iter() {
+            etrng_responses.extend_from_slice(&resp.delay.to_le_bytes());
+            for word in resp.data {
+                etrng_responses.extend_from_slice(&word.to_le_bytes());
+            }
+        }
+        fs::write(dir.join("etrng_responses.bin"), etrng_responses)?;
+
+        let mut m = String::new();
+        writeln!(m, "# Caliptra hw-model reproducer bundle").unwrap();
+        writeln!(m, "# Re-run with: caliptra-emu --replay <this directory>").unwrap();
+        writeln!(
+            m,
+            "# Bare cold/warm resets and direct APB register accesses are not recorded."
+        )
+        .unwrap();
+        writeln!(m, "model = {}", self.model).unwrap();
+        writeln!(
+            m,
+            "rom_sha384 = {}",
+            hex_string(&sha2::Sha384::digest(&self.rom))
+        )
+        .unwrap();
+        writeln!(m, "security_state = {:#x}", u32::from(self.security_state)).unwrap();
+        writeln!(
+            m,
+            "cptra_obf_key = {}",
+            words_to_string(&self.cptra_obf_key)
+        )
+        .unwrap();
+        writeln!(m, "trng_mode = {}", trng_mode_name(self.trng_mode)).unwrap();
+        writeln!(m, "trng_seed = {}", option_to_string(self.trng_seed)).unwrap();
+        writeln!(m, "random_sram_puf = {}", self.random_sram_puf).unwrap();
+        writeln!(m, "failure_cycle = {}", self.failure_cycle).unwrap();
+
+        for (i, event) in self.events.iter().enumerate() {
+            writeln!(m).unwrap();
+            match event {
+                ReproEvent::Boot { cycle, params } => {
+                    writeln!(m, "[boot]").unwrap();
+                    writeln!(m, "cycle = {cycle}").unwrap();
+                    write_fuses(&mut m, &params.fuses);
+                    writeln!(
+                        m,
+                        "initial_dbg_manuf_service_reg = {:#x}",
+                        params.initial_dbg_manuf_service_reg
+                    )
+                    .unwrap();
+                    writeln!(
+                        m,
+                        "initial_repcnt_thresh_reg = {}",
+                        option_to_string(
+                            params.initial_repcnt_thresh_reg.map(|v| format!("{v:#x}"))
+                        )
+                    )
+                    .unwrap();
+                    writeln!(
+                        m,
+                        "initial_adaptp_thresh_reg = {}",
+                        option_to_string(
+                            params.initial_adaptp_thresh_reg.map(|v| format!("{v:#x}"))
+                        )
+                    )
+                    .unwrap();
+                    writeln!(
+                        m,
+                        "valid_pauser = {}",
+                        words_to_string(&params.valid_pauser)
+                    )
+                    .unwrap();
+                    writeln!(m, "wdt_timeout_cycles = {}", params.wdt_timeout_cycles).unwrap();
+                    let fw_image = match &params.fw_image {
+                        Some(image) => {
+                            let name = format!("firmware-{i:03}.bin");
+                            fs::write(dir.join(&name), image)?;
+                            name
+                        }
+                        None => "none".into(),
+                    };
+                    writeln!(m, "fw_image = {fw_image}").unwrap();
+                }
+                ReproEvent::Mailbox { cycle, cmd, data } => {
+                    let name = if *cmd == FW_LOAD_CMD_OPCODE {
+                        format!("firmware-{i:03}.bin")
+                    } else {
+                        format!("mbox-{i:03}-{cmd:08x}.bin")
+                    };
+                    fs::write(dir.join(&name), data)?;
+                    writeln!(m, "[mailbox]").unwrap();
+                    writeln!(m, "cycle = {cycle}").unwrap();
+                    writeln!(m, "cmd = {cmd:#010x}").unwrap();
+                    writeln!(m, "data = {name}").unwrap();
+                }
+                ReproEvent::WarmReset { cycle, fuses } => {
+                    writeln!(m, "[warm_reset]").unwrap();
+                    writeln!(m, "cycle = {cycle}").unwrap();
+                    write_fuses(&mut m, fuses);
+                }
+            }
+        }
+        fs::write(dir.join(MANIFEST_FILE), m)
+    }
+
+    /// Reads a bundle previously written with [`ReproBundle::save`].
+    pub fn load(dir: &Path) -> io::Result<Self> {
+        let read = |name: &str| {
+            fs::read(dir.join(name))
+                .map_err(|e| io::Error::new(e.kind(), format!("{}: {e}", dir.join(name).display())))
+        };
+        let manifest = String::from_utf8(read(MANIFEST_FILE)?)
+            .map_err(|_| invalid_data("manifest is not UTF-8"))?;
+        let sections = parse_manifest(&manifest)?;
+        let header = &sections[0];
+
+        let rom = read("rom.bin")?;
+        let rom_sha384 = hex_string(&sha2::Sha384::digest(&rom));
+        if header.get("rom_sha384")? != rom_sha384 {
+            return Err(invalid_data(
+                "rom.bin does not match rom_sha384 from the manifest",
+            ));
+        }
+        let etrng_bytes = read("etrng_responses.bin")?;
+        if etrng_bytes.len() % (ETRNG_RESPONSE_WORDS * 4) != 0 {
+            return Err(invalid_data("etrng_responses.bin is truncated"));
+        }
+        let etrng_responses = etrng_bytes
+            .chunks_exact(ETRNG_RESPONSE_WORDS * 4)
+            .map(|chunk| {
+                let mut words = chunk
+                    .chunks_exact(4)
+                    .map(|w| u32::from_le_bytes(w.try_into().unwrap()));
+                EtrngResponse {
+                    delay: words.next().unwrap(),
+                    data: std::array::from_fn(|_| words.next().unwrap()),
+                }
+            })
+            .collect();
+
+        let mut events = vec![];
+        for section in sections[1..].iter() {
+            let cycle = section.u64("cycle")?;
+            let event = match section.name.as_str() {
+                "boot" => ReproEvent::Boot {
+                    cycle,
+                    params: RecordedBoot {
+                        fuses: section.fuses()?,
+                        fw_image: match section.get("fw_image")? {
+                            "none" => None,
+                            name => Some(read(name)?),
+                        },
+                        initial_dbg_manuf_service_reg: section
+                            .u32("initial_dbg_manuf_service_reg")?,
+                        initial_repcnt_thresh_reg: section
+                            .optional("initial_repcnt_thresh_reg", parse_u32)?,
+                        initial_adaptp_thresh_reg: section
+                            .optional("initial_adaptp_thresh_reg", parse_u32)?,
+                        valid_pauser: section.parse("valid_pauser", parse_words)?,
+                        wdt_timeout_cycles: section.u64("wdt_timeout_cycles")?,
+                    },
+                },
+                "mailbox" => ReproEvent::Mailbox {
+                    cycle,
+                    cmd: section.u32("cmd")?,
+                    data: read(section.get("data")?)?,
+                },
+                "warm_reset" => ReproEvent::WarmReset {
+                    cycle,
+                    fuses: section.fuses()?,
+                },
+                name => {
+                    return Err(invalid_data(format!(
+                        "manifest line {}: unknown section [{name}]",
+                        section.line
+                    )))
+                }
+            };
+            events.push(event);
+        }
+
+        Ok(Self {
+            model: header.get("model")?.into(),
+            rom,
+            dccm: read("dccm.bin")?,
+            iccm: read("iccm.bin")?,
+            security_state: SecurityState::from(header.u32("security_state")?),
+            cptra_obf_key: header.words("cptra_obf_key")?,
+            trng_mode: header.parse("trng_mode", |s| match s {
+                "internal" => Some(TrngMode::Internal),
+                "external" => Some(TrngMode::External),
+                _ => None,
+            })?,
+            trng_seed: header.optional("trng_seed", |s| s.parse().ok())?,
+            random_sram_puf: header.bool("random_sram_puf")?,
+            itrng_nibbles: read("itrng_nibbles.bin")?,
+            etrng_responses,
+            events,
+            failure_cycle: header.u64("failure_cycle")?,
+        })
+    }
+
+    /// The type name of the model the bundle was recorded from.
+    pub fn model(&self) -> &str {
+        &self.model
+    }
+
+    /// The last cycle the recorded model reported before the test failed.
+    pub fn failure_cycle(&self) -> u64 {
+        self.failure_cycle
+    }
+
+    /// The seed the recorded model's TRNG entropy was generated from, if it
+    /// came from the default InitParams.
+    pub fn trng_seed(&self) -> Option<u64> {
+        self.trng_seed
+    }
+
+    /// Creates an `M` and re-runs the recorded scenario on it, stopping at the
+    /// cycle the test failed at (or when the firmware exits). Boots, mailbox
+    /// commands and warm reset flows are replayed at the cycles they were
+    /// issued; failures are logged rather than returned, as the original test
+    /// may have expected them. Bare resets and direct APB register accesses
+    /// aren't in the recording, so a test that relies on them may diverge.
+    pub fn replay<M: HwModel>(&self) -> Result<M, Box<dyn Error>> {
+        // Don't record the replay over the recording being replayed.
+        let _pause = pause();
+
+        // Once the recorded entropy runs out the model has gone further than
+        // the original run did, so any entropy will do.
+        let seed = self.trng_seed.unwrap_or_default();
+        let mut hw = M::new_unbooted(InitParams {
+            rom: &self.rom,
+            dccm: &self.dccm,
+            iccm: &self.iccm,
+            security_state: self.security_state,
+            cptra_obf_key: self.cptra_obf_key,
+            itrng_nibbles: Box::new(
+                self.itrng_nibbles
+                    .clone()
+                    .into_iter()
+                    .chain(RandomNibbles(StdRng::seed_from_u64(seed))),
+            ),
+            etrng_responses: Box::new(
+                self.etrng_responses
+                    .clone()
+                    .into_iter()
+                    .chain(RandomEtrngResponses(StdRng::seed_from_u64(seed))),
+            ),
+            trng_mode: Some(self.trng_mode),
+            random_sram_puf: self.random_sram_puf,
+            trng_seed: self.trng_seed,
+            ..Default::default()
+        })?;
+
+        let mut booted = false;
+        for event in self.events.iter() {
+            hw.step_until(|hw| hw.output().sink().now() >= event.cycle());
+            match event {
+                ReproEvent::Boot { params, .. } => {
+                    // The recording only has a second boot if the test cold
+                    // reset the model.
+                    if booted {
+                        hw.cold_reset();
+                    }
+                    booted = true;
+                    let result = hw.boot(BootParams {
+                        fuses: params.fuses.clone(),
+                        fw_image: params.fw_image.as_deref(),
+                        initial_dbg_manuf_service_reg: params.initial_dbg_manuf_service_reg,
+                        initial_repcnt_thresh_reg: params.initial_repcnt_thresh_reg.map(From::from),
+                        initial_adaptp_thresh_reg: params.initial_adaptp_thresh_reg.map(From::from),
+                        valid_pauser: params.valid_pauser.clone(),
+                        wdt_timeout_cycles: params.wdt_timeout_cycles,
+                    });
+                    if let Err(e) = result {
+                        writeln!(hw.output().logger(), "Replayed boot failed: {e}")?;
+                    }
+                }
+                ReproEvent::Mailbox { cmd, data, .. } => {
+                    if let Err(e) = hw.mailbox_execute(*cmd, data) {
+                        writeln!(
+                            hw.output().logger(),
+                            "Replayed mbox cmd 0x{cmd:08x} failed: {e}"
+                        )?;
+                    }
+                }
+                ReproEvent::WarmReset { fuses, .. } => hw.warm_reset_flow(fuses),
+            }
+        }
+        hw.step_until(|hw| {
+            hw.output().exit_requested() || hw.output().sink().now() >= self.failure_cycle
+        });
+        Ok(hw)
+    }
+}
+
+fn hex_string(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_bundle() -> ReproBundle {
+        ReproBundle {
+            model: "ModelEmulated".into(),
+            rom: vec![0x6f, 0x00, 0x00, 0x00],
+            dccm: vec![],
+            iccm: vec![1, 2, 3],
+            security_state: SecurityState::from(0x5),
+            cptra_obf_key: [1, 2, 3, 4, 5, 6, 7, 8],
+            trng_mode: TrngMode::External,
+            trng_seed: Some(1234),
+            random_sram_puf: false,
+            itrng_nibbles: vec![0x3, 0xf, 0x0],
+            etrng_responses: vec![EtrngResponse {
+                delay: 7,
+                data: std::array::from_fn(|i| i as u32 * 0x1111_1111),
+            }],
+            events: vec![
+                ReproEvent::Boot {
+                    cycle: 0,
+                    params: RecordedBoot {
+                        fuses: Fuses {
+                            key_manifest_pk_hash_mask: U4::X5,
+                            life_cycle: DeviceLifecycle::Production,
+                            fmc_key_manifest_svn: 0x3,
+                            soc_stepping_id: 0x1234,
+                            ..Default::default()
+                        },
+                        fw_image: Some(vec![0xaa; 16]),
+                        initial_dbg_manuf_service_reg: 0x1,
+                        initial_repcnt_thresh_reg: Some(0x41),
+                        initial_adaptp_thresh_reg: None,
+                        valid_pauser: vec![0, 1, 2],
+                        wdt_timeout_cycles: 40_000_000,
+                    },
+                },
+                ReproEvent::Mailbox {
+                    cycle: 1_000_000,
+                    cmd: 0x4341_5053,
+                    data: vec![0xfc, 0xfe, 0xfe, 0xfe],
+                },
+                ReproEvent::WarmReset {
+                    cycle: 2_000_000,
+                    fuses: Default::default(),
+                },
+            ],
+            failure_cycle: 2_500_000,
+        }
+    }
+
+    #[test]
+    fn test_save_load_round_trip() {
+        let dir = std::env::temp_dir().join(format!("caliptra-repro-test-{}", process::id()));
+        let bundle = test_bundle();
+        bundle.save(&dir).unwrap();
+        let loaded = ReproBundle::load(&dir).unwrap();
+        assert_eq!(format!("{loaded:?}"), format!("{bundle:?}"));
+
+        fs::write(dir.join("rom.bin"), [0u8; 4]).unwrap();
+        assert_eq!(
+            ReproBundle::load(&dir).unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_parse_manifest_errors() {
+        assert!(parse_manifest("model = ModelEmulated\nbogus\n").is_err());
+        let sections = parse_manifest("# comment\nmodel = x\n\n[mailbox]\ncycle = 5\n").unwrap();
+        assert_eq!(sections.len(), 2);
+        assert_eq!(sections[1].name, "mailbox");
+        assert_eq!(sections[1].u64("cycle").unwrap(), 5);
+        assert!(sections[1].u32("cmd").is_err());
+    }
+}
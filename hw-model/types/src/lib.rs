@@ -185,7 +185,7 @@ impl<R: RngCore> Iterator for RandomNibbles<R> {
     }
 }
 
-#[derive(Copy, Clone, Eq, PartialEq)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub struct EtrngResponse {
     pub delay: u32,
     pub data: [u32; 12],
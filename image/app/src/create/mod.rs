@@ -72,6 +72,14 @@ fn check_date(from_date: &str, to_date: &str) -> anyhow::Result<bool> {
     Ok(true)
 }
 
+/// Parse a `YYYYMMDD` certificate end date
+fn parse_layer_date(date: &str) -> anyhow::Result<u32> {
+    if NaiveDate::parse_from_str(date, "%Y%m%d").is_err() {
+        return Err(anyhow!("Invalid Date Input Format"));
+    }
+    Ok(date.parse()?)
+}
+
 /// Run the command
 pub(crate) fn run_cmd(args: &ArgMatches) -> anyhow::Result<()> {
     let config_path: &PathBuf = args
@@ -148,6 +156,22 @@ pub(crate) fn run_cmd(args: &ArgMatches) -> anyhow::Result<()> {
         }
     }
 
+    let own_rt_alias_to_date = args
+        .get_one::<String>("own-rt-alias-to-date")
+        .map(|date| parse_layer_date(date))
+        .transpose()?
+        .unwrap_or_default();
+    let own_dpe_leaf_to_date = args
+        .get_one::<String>("own-dpe-leaf-to-date")
+        .map(|date| parse_layer_date(date))
+        .transpose()?
+        .unwrap_or_default();
+
+    let rt_alias_sn_counter = args
+        .get_one::<bool>("rt-alias-sn-counter")
+        .copied()
+        .unwrap_or_default();
+
     let config = config::load_key_config(config_path)?;
 
     let fmc_rev = hex::decode(fmc_rev)?;
@@ -170,16 +194,29 @@ pub(crate) fn run_cmd(args: &ArgMatches) -> anyhow::Result<()> {
         .parent()
         .with_context(|| "Invalid parent path")?;
 
+    let mut owner_config = owner_config(config_dir, &config.owner, own_from_date, own_to_date)?;
+    if let Some(owner_config) = &mut owner_config {
+        owner_config.rt_alias_not_after = own_rt_alias_to_date;
+        owner_config.dpe_leaf_not_after = own_dpe_leaf_to_date;
+        owner_config.rt_alias_sn_counter = rt_alias_sn_counter;
+    } else if rt_alias_sn_counter {
+        return Err(anyhow!(
+            "RT Alias serial number counter requires an owner key config"
+        ));
+    }
+
+    let vendor_config = vendor_config(
+        config_dir,
+        &config.vendor,
+        *ecc_key_idx,
+        *lms_key_idx,
+        mfg_from_date,
+        mfg_to_date,
+    )?;
+
     let gen_config = ImageGeneratorConfig::<ElfExecutable> {
-        vendor_config: vendor_config(
-            config_dir,
-            &config.vendor,
-            *ecc_key_idx,
-            *lms_key_idx,
-            mfg_from_date,
-            mfg_to_date,
-        )?,
-        owner_config: owner_config(config_dir, &config.owner, own_from_date, own_to_date)?,
+        vendor_config,
+        owner_config,
         fmc,
         runtime,
     };
@@ -91,6 +91,21 @@ fn main() {
                 .required(false)
                 .value_parser(value_parser!(String)),
         )
+        .arg(
+            arg!(--"own-rt-alias-to-date" <String> "RT Alias Certificate Validity End Date By Owner [YYYYMMDD]")
+                .required(false)
+                .value_parser(value_parser!(String)),
+        )
+        .arg(
+            arg!(--"own-dpe-leaf-to-date" <String> "DPE Leaf Certificate Validity End Date By Owner [YYYYMMDD]")
+                .required(false)
+                .value_parser(value_parser!(String)),
+        )
+        .arg(
+            arg!(--"rt-alias-sn-counter" "Include the RT Alias certificate issuance count in its serial number (requires owner keys)")
+                .required(false)
+                .action(clap::ArgAction::SetTrue),
+        )
         .arg(
             arg!(--"mfg-from-date" <String> "Certificate Validity Start Date By Manufacturer [YYYYMMDDHHMMSS - Zulu Time]")
                 .required(false)
@@ -372,6 +372,9 @@ pub const OWNER_CONFIG: ImageGeneratorOwnerConfig = ImageGeneratorOwnerConfig {
     not_before: [0u8; 15],
     not_after: [0u8; 15],
     epoch: [0u8; 2],
+    rt_alias_not_after: 0,
+    dpe_leaf_not_after: 0,
+    rt_alias_sn_counter: false,
 };
 
 #[test]
@@ -26,6 +26,7 @@ pub struct ImageGenerator<Crypto: ImageGeneratorCrypto> {
 impl<Crypto: ImageGeneratorCrypto> ImageGenerator<Crypto> {
     const DEFAULT_FLAGS: u32 = 0;
     const PL0_PAUSER_FLAG: u32 = (1 << 0);
+    const RT_ALIAS_SN_COUNTER_FLAG: u32 = (1 << 1);
 
     /// Create an instance `ImageGenerator`
     pub fn new(crypto: Crypto) -> Self {
@@ -205,6 +206,12 @@ impl<Crypto: ImageGeneratorCrypto> ImageGenerator<Crypto> {
             header.owner_data.owner_not_before = owner_config.not_before;
             header.owner_data.owner_not_after = owner_config.not_after;
             header.owner_data.epoch = owner_config.epoch;
+            header.owner_data.owner_rt_alias_not_after = owner_config.rt_alias_not_after;
+            header.owner_data.owner_dpe_leaf_not_after = owner_config.dpe_leaf_not_after;
+
+            if owner_config.rt_alias_sn_counter {
+                header.flags |= Self::RT_ALIAS_SN_COUNTER_FLAG;
+            }
         }
 
         Ok(header)
@@ -119,6 +119,15 @@ pub struct ImageGeneratorOwnerConfig {
     pub not_after: [u8; 15],
 
     pub epoch: [u8; 2],
+
+    /// RT Alias certificate end date [YYYYMMDD]. Zero to use `not_after`.
+    pub rt_alias_not_after: u32,
+
+    /// DPE leaf certificate end date [YYYYMMDD]. Zero to use `not_after`.
+    pub dpe_leaf_not_after: u32,
+
+    /// Include the RT Alias certificate issuance count in its serial number.
+    pub rt_alias_sn_counter: bool,
 }
 
 /// Image Generator Configuration
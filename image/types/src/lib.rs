@@ -313,7 +313,13 @@ pub struct OwnerSignedData {
     /// Owner epoch, used to diversify stable SVN keys.
     pub epoch: [u8; 2],
 
-    reserved: [u8; 8],
+    /// Owner End Date [YYYYMMDD] For RT alias certificate: Takes preference over the FMC alias
+    /// certificate end date. Zero if not set.
+    pub owner_rt_alias_not_after: u32,
+
+    /// Owner End Date [YYYYMMDD] For DPE leaf certificates: Takes preference over the FMC alias
+    /// certificate end date. Zero if not set.
+    pub owner_dpe_leaf_not_after: u32,
 }
 
 /// Caliptra Image header
@@ -332,6 +338,8 @@ pub struct ImageHeader {
 
     /// Flags
     /// Bit 0: Interpret the pl0_pauser field. If not set, all PAUSERs are PL1.
+    /// Bit 1: Include the issuance count in the RT Alias certificate serial number.
+    ///        Set from the owner configuration.
     pub flags: u32,
 
     /// TOC Entry Count
@@ -0,0 +1,87 @@
+// Licensed under the Apache-2.0 license
+
+// Checks that the C headers written by caliptra_registers_generator
+// (--c-header-dir) agree with the caliptra_top_reg.h from caliptra-rtl that
+// libcaliptra is built against, for every register libcaliptra uses. Built
+// with -fsyntax-only by `make check-generated-regs`; nothing is linked.
+
+#include <caliptra_top_reg.h>
+#include "mbox.h"
+#include "soc_ifc.h"
+
+#define CHECK_EQ(rtl, generated) \
+    _Static_assert((rtl) == (generated), #rtl " != " #generated)
+
+// Base addresses
+CHECK_EQ(CALIPTRA_TOP_REG_MBOX_CSR_BASE_ADDR, MBOX_CSR_BASE_ADDR);
+CHECK_EQ(CALIPTRA_TOP_REG_GENERIC_AND_FUSE_REG_BASE_ADDR, SOC_IFC_REG_BASE_ADDR);
+
+// Mailbox
+CHECK_EQ(MBOX_CSR_MBOX_LOCK, MBOX_LOCK);
+CHECK_EQ(MBOX_CSR_MBOX_LOCK_LOCK_MASK, MBOX_LOCK_LOCK_MASK);
+CHECK_EQ(MBOX_CSR_MBOX_CMD, MBOX_CMD);
+CHECK_EQ(MBOX_CSR_MBOX_DLEN, MBOX_DLEN);
+CHECK_EQ(MBOX_CSR_MBOX_DATAIN, MBOX_DATAIN);
+CHECK_EQ(MBOX_CSR_MBOX_DATAOUT, MBOX_DATAOUT);
+CHECK_EQ(MBOX_CSR_MBOX_EXECUTE, MBOX_EXECUTE);
+CHECK_EQ(MBOX_CSR_MBOX_STATUS, MBOX_STATUS);
+CHECK_EQ(MBOX_CSR_MBOX_STATUS_STATUS_MASK, MBOX_STATUS_STATUS_MASK);
+CHECK_EQ(MBOX_CSR_MBOX_STATUS_MBOX_FSM_PS_LOW, MBOX_STATUS_MBOX_FSM_PS_SHIFT);
+CHECK_EQ(MBOX_CSR_MBOX_STATUS_MBOX_FSM_PS_MASK, MBOX_STATUS_MBOX_FSM_PS_MASK);
+
+// Absolute addresses
+CHECK_EQ(CALIPTRA_TOP_REG_GENERIC_AND_FUSE_REG_CPTRA_BOOTFSM_GO,
+         SOC_IFC_REG_BASE_ADDR + SOC_IFC_CPTRA_BOOTFSM_GO);
+CHECK_EQ(CALIPTRA_TOP_REG_GENERIC_AND_FUSE_REG_CPTRA_DBG_MANUF_SERVICE_REG,
+         SOC_IFC_REG_BASE_ADDR + SOC_IFC_CPTRA_DBG_MANUF_SERVICE_REG);
+CHECK_EQ(CALIPTRA_TOP_REG_GENERIC_AND_FUSE_REG_CPTRA_FLOW_STATUS,
+         SOC_IFC_REG_BASE_ADDR + SOC_IFC_CPTRA_FLOW_STATUS);
+CHECK_EQ(CALIPTRA_TOP_REG_GENERIC_AND_FUSE_REG_CPTRA_FUSE_WR_DONE,
+         SOC_IFC_REG_BASE_ADDR + SOC_IFC_CPTRA_FUSE_WR_DONE);
+
+// SoC interface
+CHECK_EQ(GENERIC_AND_FUSE_REG_CPTRA_FLOW_STATUS, SOC_IFC_CPTRA_FLOW_STATUS);
+CHECK_EQ(GENERIC_AND_FUSE_REG_CPTRA_FLOW_STATUS_IDEVID_CSR_READY_MASK,
+         SOC_IFC_CPTRA_FLOW_STATUS_IDEVID_CSR_READY_MASK);
+CHECK_EQ(GENERIC_AND_FUSE_REG_CPTRA_FLOW_STATUS_READY_FOR_FUSES_MASK,
+         SOC_IFC_CPTRA_FLOW_STATUS_READY_FOR_FUSES_MASK);
+CHECK_EQ(GENERIC_AND_FUSE_REG_CPTRA_FLOW_STATUS_READY_FOR_FW_MASK,
+         SOC_IFC_CPTRA_FLOW_STATUS_READY_FOR_FW_MASK);
+CHECK_EQ(GENERIC_AND_FUSE_REG_CPTRA_DBG_MANUF_SERVICE_REG, SOC_IFC_CPTRA_DBG_MANUF_SERVICE_REG);
+CHECK_EQ(GENERIC_AND_FUSE_REG_CPTRA_FW_ERROR_FATAL, SOC_IFC_CPTRA_FW_ERROR_FATAL);
+CHECK_EQ(GENERIC_AND_FUSE_REG_CPTRA_FW_ERROR_NON_FATAL, SOC_IFC_CPTRA_FW_ERROR_NON_FATAL);
+CHECK_EQ(GENERIC_AND_FUSE_REG_CPTRA_WDT_CFG_0, SOC_IFC_CPTRA_WDT_CFG);
+CHECK_EQ(GENERIC_AND_FUSE_REG_CPTRA_WDT_CFG_1, SOC_IFC_CPTRA_WDT_CFG + 4);
+CHECK_EQ(GENERIC_AND_FUSE_REG_CPTRA_MBOX_VALID_PAUSER_0, SOC_IFC_CPTRA_MBOX_VALID_PAUSER);
+CHECK_EQ(GENERIC_AND_FUSE_REG_CPTRA_MBOX_PAUSER_LOCK_0, SOC_IFC_CPTRA_MBOX_PAUSER_LOCK);
+CHECK_EQ(GENERIC_AND_FUSE_REG_CPTRA_FUSE_VALID_PAUSER, SOC_IFC_CPTRA_FUSE_VALID_PAUSER);
+CHECK_EQ(GENERIC_AND_FUSE_REG_CPTRA_FUSE_PAUSER_LOCK, SOC_IFC_CPTRA_FUSE_PAUSER_LOCK);
+CHECK_EQ(GENERIC_AND_FUSE_REG_CPTRA_ITRNG_ENTROPY_CONFIG_0,
+         SOC_IFC_CPTRA_I_TRNG_ENTROPY_CONFIG_0);
+CHECK_EQ(GENERIC_AND_FUSE_REG_CPTRA_ITRNG_ENTROPY_CONFIG_0_HIGH_THRESHOLD_LOW,
+         SOC_IFC_CPTRA_I_TRNG_ENTROPY_CONFIG_0_HIGH_THRESHOLD_SHIFT);
+CHECK_EQ(GENERIC_AND_FUSE_REG_CPTRA_ITRNG_ENTROPY_CONFIG_0_HIGH_THRESHOLD_MASK,
+         SOC_IFC_CPTRA_I_TRNG_ENTROPY_CONFIG_0_HIGH_THRESHOLD_MASK);
+CHECK_EQ(GENERIC_AND_FUSE_REG_CPTRA_ITRNG_ENTROPY_CONFIG_0_LOW_THRESHOLD_MASK,
+         SOC_IFC_CPTRA_I_TRNG_ENTROPY_CONFIG_0_LOW_THRESHOLD_MASK);
+CHECK_EQ(GENERIC_AND_FUSE_REG_CPTRA_ITRNG_ENTROPY_CONFIG_1,
+         SOC_IFC_CPTRA_I_TRNG_ENTROPY_CONFIG_1);
+CHECK_EQ(GENERIC_AND_FUSE_REG_CPTRA_ITRNG_ENTROPY_CONFIG_1_REPETITION_COUNT_MASK,
+         SOC_IFC_CPTRA_I_TRNG_ENTROPY_CONFIG_1_REPETITION_COUNT_MASK);
+
+// Fuses
+CHECK_EQ(GENERIC_AND_FUSE_REG_FUSE_UDS_SEED_0, SOC_IFC_FUSE_UDS_SEED);
+CHECK_EQ(GENERIC_AND_FUSE_REG_FUSE_FIELD_ENTROPY_0, SOC_IFC_FUSE_FIELD_ENTROPY);
+CHECK_EQ(GENERIC_AND_FUSE_REG_FUSE_KEY_MANIFEST_PK_HASH_0, SOC_IFC_FUSE_KEY_MANIFEST_PK_HASH);
+CHECK_EQ(GENERIC_AND_FUSE_REG_FUSE_KEY_MANIFEST_PK_HASH_MASK,
+         SOC_IFC_FUSE_KEY_MANIFEST_PK_HASH_MASK);
+CHECK_EQ(GENERIC_AND_FUSE_REG_FUSE_OWNER_PK_HASH_0, SOC_IFC_FUSE_OWNER_PK_HASH);
+CHECK_EQ(GENERIC_AND_FUSE_REG_FUSE_FMC_KEY_MANIFEST_SVN, SOC_IFC_FUSE_FMC_KEY_MANIFEST_SVN);
+CHECK_EQ(GENERIC_AND_FUSE_REG_FUSE_RUNTIME_SVN_0, SOC_IFC_FUSE_RUNTIME_SVN);
+CHECK_EQ(GENERIC_AND_FUSE_REG_FUSE_ANTI_ROLLBACK_DISABLE, SOC_IFC_FUSE_ANTI_ROLLBACK_DISABLE);
+CHECK_EQ(GENERIC_AND_FUSE_REG_FUSE_IDEVID_CERT_ATTR_0, SOC_IFC_FUSE_IDEVID_CERT_ATTR);
+CHECK_EQ(GENERIC_AND_FUSE_REG_FUSE_IDEVID_MANUF_HSM_ID_0, SOC_IFC_FUSE_IDEVID_MANUF_HSM_ID);
+CHECK_EQ(GENERIC_AND_FUSE_REG_FUSE_LIFE_CYCLE, SOC_IFC_FUSE_LIFE_CYCLE);
+CHECK_EQ(GENERIC_AND_FUSE_REG_FUSE_LMS_VERIFY, SOC_IFC_FUSE_LMS_VERIFY);
+CHECK_EQ(GENERIC_AND_FUSE_REG_FUSE_LMS_REVOCATION, SOC_IFC_FUSE_LMS_REVOCATION);
+CHECK_EQ(GENERIC_AND_FUSE_REG_FUSE_SOC_STEPPING_ID, SOC_IFC_FUSE_SOC_STEPPING_ID);
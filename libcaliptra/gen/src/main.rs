@@ -0,0 +1,884 @@
+// Licensed under the Apache-2.0 license
+
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Write,
+    io::{Error, ErrorKind},
+    path::Path,
+};
+
+use quote::ToTokens;
+use syn::{
+    braced,
+    parse::{Parse, ParseStream},
+    Attribute, BinOp, Expr, Fields, ImplItem, Item, ItemEnum, ItemImpl, ItemStruct, Lit, Meta,
+    Token, Type,
+};
+
+/// Rust sources describing the mailbox protocol
+const SOURCES: &[&str] = &["./api/src/mailbox.rs", "./api/src/capabilities.rs"];
+
+/// Header generated from SOURCES
+const OUTPUT: &str = "./libcaliptra/inc/caliptra_mbox_types.h";
+
+/// C names that predate the generator and don't follow the naming scheme
+const RENAMES: &[(&str, &str)] = &[
+    ("MailboxReqHeader", "caliptra_req_header"),
+    ("MailboxRespHeader", "caliptra_resp_header"),
+    ("MailboxRespHeaderVarSize", "caliptra_resp_header_var_size"),
+    ("TagTciReq", "caliptra_dpe_tag_tci_req"),
+];
+
+/// Types declared by hand in caliptra_types.h, which overlays the DPE command
+/// structures on their data buffers
+const HAND_WRITTEN: &[&str] = &["InvokeDpeReq", "InvokeDpeResp"];
+
+const PREAMBLE: &str = r#"// Licensed under the Apache-2.0 license
+//
+// Generated by caliptra-libcaliptra-gen from api/src/mailbox.rs.
+// Do not edit; run "cargo run -p caliptra-libcaliptra-gen" to regenerate.
+#pragma once
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+#define CALIPTRA_STATIC_ASSERT static_assert
+#else
+#define CALIPTRA_STATIC_ASSERT _Static_assert
+#endif
+"#;
+
+/// Mirrors api/src/checksum.rs and the populate_chksum() methods of the
+/// mailbox types
+const CHECKSUM_HELPERS: &str = r#"// Calculates a mailbox checksum: 0 - (SUM(command code bytes) + SUM(data bytes)).
+// Requests are checksummed with their command ID and responses with 0.
+static inline uint32_t caliptra_calc_checksum(uint32_t cmd, const uint8_t *data, uint32_t len)
+{
+    uint32_t i, sum = 0;
+
+    if ((data == NULL) && (len != 0))
+    {
+        // Don't respect bad parameters
+        return 0;
+    }
+
+    for (i = 0; i < sizeof(uint32_t); i++)
+    {
+        sum += (cmd >> (8 * i)) & 0xff;
+    }
+
+    for (i = 0; i < len; i++)
+    {
+        sum += data[i];
+    }
+
+    return (0 - sum);
+}
+
+static inline bool caliptra_verify_checksum(uint32_t checksum, uint32_t cmd, const uint8_t *data, uint32_t len)
+{
+    return caliptra_calc_checksum(cmd, data, len) == checksum;
+}
+
+// Fills in the chksum field of the len-byte request at req
+static inline void caliptra_populate_req_checksum(uint32_t cmd, void *req, uint32_t len)
+{
+    struct caliptra_req_header *hdr = (struct caliptra_req_header *)req;
+    const uint8_t *data = (const uint8_t *)req + sizeof(hdr->chksum);
+
+    if (len < sizeof(*hdr))
+    {
+        return;
+    }
+
+    hdr->chksum = caliptra_calc_checksum(cmd, data, len - sizeof(hdr->chksum));
+}
+
+// Checks the chksum field of the len-byte response at resp
+static inline bool caliptra_verify_resp_checksum(const void *resp, uint32_t len)
+{
+    const struct caliptra_resp_header *hdr = (const struct caliptra_resp_header *)resp;
+    const uint8_t *data = (const uint8_t *)resp + sizeof(hdr->chksum);
+
+    if (len < sizeof(*hdr))
+    {
+        return false;
+    }
+
+    return caliptra_verify_checksum(hdr->chksum, 0, data, len - sizeof(hdr->chksum));
+}
+"#;
+
+fn invalid(msg: impl Into<String>) -> Error {
+    Error::new(ErrorKind::InvalidData, msg.into())
+}
+
+fn add_path(path: &Path) -> impl Fn(Error) -> Error + Copy + '_ {
+    move |e: Error| Error::new(e.kind(), format!("{path:?}: {e}"))
+}
+
+fn snake_case(name: &str) -> String {
+    let chars: Vec<char> = name.chars().collect();
+    let mut result = String::new();
+    for (i, &c) in chars.iter().enumerate() {
+        if i > 0 && c.is_ascii_uppercase() {
+            let prev = chars[i - 1];
+            let next_is_lower = chars.get(i + 1).is_some_and(|n| n.is_ascii_lowercase());
+            if prev.is_ascii_lowercase()
+                || prev.is_ascii_digit()
+                || (prev.is_ascii_uppercase() && next_is_lower)
+            {
+                result.push('_');
+            }
+        }
+        result.push(c.to_ascii_lowercase());
+    }
+    result
+}
+
+/// C struct name of the Rust type `name`
+fn c_struct_name(name: &str) -> String {
+    match RENAMES.iter().find(|(rust, _)| *rust == name) {
+        Some((_, c)) => c.to_string(),
+        None => format!("caliptra_{}", snake_case(name)),
+    }
+}
+
+/// Prefix of the C constants derived from the Rust type `name`
+fn c_const_prefix(name: &str) -> String {
+    c_struct_name(name).to_ascii_uppercase()
+}
+
+fn last_ident(path: &syn::Path) -> String {
+    path.segments
+        .last()
+        .map(|s| s.ident.to_string())
+        .unwrap_or_default()
+}
+
+fn type_name(ty: &Type) -> Option<String> {
+    match ty {
+        Type::Path(p) if p.qself.is_none() => Some(last_ident(&p.path)),
+        _ => None,
+    }
+}
+
+fn doc_lines(attrs: &[Attribute]) -> Vec<String> {
+    attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("doc"))
+        .filter_map(|attr| match attr.parse_meta() {
+            Ok(Meta::NameValue(nv)) => match nv.lit {
+                Lit::Str(s) => Some(s.value().trim().to_string()),
+                _ => None,
+            },
+            _ => None,
+        })
+        .filter(|line| !line.is_empty())
+        .collect()
+}
+
+/// Check if `attrs` contain `#[repr(<repr>)]`
+fn has_repr(attrs: &[Attribute], repr: &str) -> bool {
+    attrs.iter().any(|attr| {
+        attr.path.is_ident("repr")
+            && attr
+                .parse_args_with(
+                    syn::punctuated::Punctuated::<syn::Ident, Token![,]>::parse_terminated,
+                )
+                .is_ok_and(|args| args.iter().any(|a| a == repr))
+    })
+}
+
+/// Check if `attrs` exclude the item from non-test builds
+fn is_test_only(attrs: &[Attribute]) -> bool {
+    attrs
+        .iter()
+        .any(|attr| attr.path.is_ident("cfg") && attr.tokens.to_string().contains("test"))
+}
+
+/// A single type declared with the `bitflags!` macro, in either the
+/// `struct Name: ty { ... }` or `impl Name: ty { ... }` form
+struct Flags {
+    name: String,
+    ty: Type,
+    values: Vec<(String, Expr)>,
+}
+
+impl Parse for Flags {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        Attribute::parse_outer(input)?;
+        input.parse::<syn::Visibility>()?;
+        if input.peek(Token![impl]) {
+            input.parse::<Token![impl]>()?;
+        } else {
+            input.parse::<Token![struct]>()?;
+        }
+        let name: syn::Ident = input.parse()?;
+        input.parse::<Token![:]>()?;
+        let ty: Type = input.parse()?;
+        let content;
+        braced!(content in input);
+        let mut values = vec![];
+        while !content.is_empty() {
+            Attribute::parse_outer(&content)?;
+            content.parse::<Token![const]>()?;
+            let value_name: syn::Ident = content.parse()?;
+            content.parse::<Token![=]>()?;
+            let value: Expr = content.parse()?;
+            content.parse::<Token![;]>()?;
+            values.push((value_name.to_string(), value));
+        }
+        Ok(Flags {
+            name: name.to_string(),
+            ty,
+            values,
+        })
+    }
+}
+
+#[derive(Clone)]
+enum CType {
+    Int {
+        name: &'static str,
+        size: u64,
+    },
+    Struct(String),
+    /// `len` is the length as written in the header, either `count` or the
+    /// name of a constant
+    Array {
+        elem: Box<CType>,
+        len: String,
+        count: u64,
+    },
+}
+
+struct CField {
+    name: String,
+    ty: CType,
+    docs: Vec<String>,
+}
+
+struct CStruct {
+    rust_name: String,
+    docs: Vec<String>,
+    fields: Vec<CField>,
+}
+
+/// The mailbox protocol as declared by the Rust sources
+#[derive(Default)]
+struct Model {
+    /// `CommandId` constants, in declaration order
+    commands: Vec<(String, u32)>,
+    /// Constants keyed by `Type::NAME` (or `NAME` for free constants), with
+    /// the type `Self` refers to in their value
+    consts: HashMap<String, (String, Expr)>,
+    /// Names of the integer associated constants of each type, in
+    /// declaration order
+    type_consts: HashMap<String, Vec<String>>,
+    aliases: HashMap<String, Type>,
+    /// `#[repr(C)]` single-field tuple structs, such as bitflags wrappers
+    newtypes: HashMap<String, Type>,
+    structs: Vec<ItemStruct>,
+    enums: Vec<ItemEnum>,
+    flags: Vec<Flags>,
+    /// Command ID and response type of each `Request` implementation
+    requests: HashMap<String, (String, String)>,
+}
+
+impl Model {
+    fn add_source(&mut self, path: &Path, source: &str) -> Result<(), Error> {
+        let file = syn::parse_file(source).map_err(|e| invalid(format!("{path:?}: {e}")))?;
+        for item in file.items {
+            self.add_item(item).map_err(add_path(path))?;
+        }
+        Ok(())
+    }
+
+    fn add_item(&mut self, item: Item) -> Result<(), Error> {
+        match item {
+            Item::Const(c) if !is_test_only(&c.attrs) => {
+                self.consts
+                    .insert(c.ident.to_string(), (String::new(), *c.expr));
+            }
+            Item::Type(t) => {
+                self.aliases.insert(t.ident.to_string(), *t.ty);
+            }
+            Item::Struct(s) if has_repr(&s.attrs, "C") => match &s.fields {
+                Fields::Named(_) => self.structs.push(s),
+                Fields::Unnamed(f) if f.unnamed.len() == 1 => {
+                    let inner = f.unnamed[0].ty.clone();
+                    self.newtypes.insert(s.ident.to_string(), inner);
+                }
+                _ => {}
+            },
+            Item::Enum(e) if has_repr(&e.attrs, "u32") => self.enums.push(e),
+            Item::Impl(i) if !is_test_only(&i.attrs) => self.add_impl(i)?,
+            Item::Macro(m) if last_ident(&m.mac.path) == "bitflags" => {
+                let flags: Flags = m.mac.parse_body().map_err(|e| invalid(e.to_string()))?;
+                if type_name(&flags.ty).as_deref() == Some("u32") {
+                    self.newtypes
+                        .entry(flags.name.clone())
+                        .or_insert_with(|| flags.ty.clone());
+                    self.flags.push(flags);
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn add_impl(&mut self, item: ItemImpl) -> Result<(), Error> {
+        let Some(self_ty) = type_name(&item.self_ty) else {
+            return Ok(());
+        };
+        if let Some((_, trait_path, _)) = &item.trait_ {
+            if last_ident(trait_path) != "Request" {
+                return Ok(());
+            }
+            let mut id = None;
+            let mut resp = None;
+            for impl_item in item.items.iter() {
+                match impl_item {
+                    ImplItem::Const(c) if c.ident == "ID" => {
+                        if let Expr::Path(p) = &c.expr {
+                            id = Some(last_ident(&p.path));
+                        }
+                    }
+                    ImplItem::Type(t) if t.ident == "Resp" => resp = type_name(&t.ty),
+                    _ => {}
+                }
+            }
+            match (id, resp) {
+                (Some(id), Some(resp)) => {
+                    self.requests.insert(self_ty, (id, resp));
+                }
+                _ => return Err(invalid(format!("unsupported Request impl for {self_ty}"))),
+            }
+            return Ok(());
+        }
+        for impl_item in item.items {
+            let ImplItem::Const(c) = impl_item else {
+                continue;
+            };
+            let name = c.ident.to_string();
+            if self_ty == "CommandId" {
+                let Expr::Call(call) = &c.expr else {
+                    return Err(invalid(format!("unsupported CommandId::{name} value")));
+                };
+                let value = self.eval(&call.args[0], &self_ty)?;
+                let value = u32::try_from(value)
+                    .map_err(|_| invalid(format!("CommandId::{name} doesn't fit in a u32")))?;
+                self.commands.push((name, value));
+                continue;
+            }
+            if !matches!(
+                type_name(&c.ty).as_deref(),
+                Some("u8" | "u16" | "u32" | "u64" | "usize")
+            ) {
+                continue;
+            }
+            self.consts
+                .insert(format!("{self_ty}::{name}"), (self_ty.clone(), c.expr));
+            self.type_consts
+                .entry(self_ty.clone())
+                .or_default()
+                .push(name);
+        }
+        Ok(())
+    }
+
+    /// Returns the key in `consts` of the constant `path`, as used in the
+    /// context of `self_ty`
+    fn const_key(&self, path: &syn::Path, self_ty: &str) -> Option<String> {
+        let segments: Vec<String> = path.segments.iter().map(|s| s.ident.to_string()).collect();
+        let key = match segments.as_slice() {
+            [.., ty, name] if ty == "Self" => format!("{self_ty}::{name}"),
+            [.., ty, name] => format!("{ty}::{name}"),
+            [name] => name.clone(),
+            [] => return None,
+        };
+        self.consts.contains_key(&key).then_some(key)
+    }
+
+    fn eval(&self, expr: &Expr, self_ty: &str) -> Result<u64, Error> {
+        let unsupported = || {
+            invalid(format!(
+                "unsupported constant expression `{}`",
+                expr.to_token_stream()
+            ))
+        };
+        match expr {
+            Expr::Lit(lit) => match &lit.lit {
+                Lit::Int(i) => i.base10_parse::<u64>().map_err(|e| invalid(e.to_string())),
+                _ => Err(unsupported()),
+            },
+            Expr::Paren(p) => self.eval(&p.expr, self_ty),
+            Expr::Group(g) => self.eval(&g.expr, self_ty),
+            Expr::Cast(c) => self.eval(&c.expr, self_ty),
+            Expr::Binary(b) => {
+                let left = self.eval(&b.left, self_ty)?;
+                let right = self.eval(&b.right, self_ty)?;
+                match b.op {
+                    BinOp::Add(_) => left.checked_add(right),
+                    BinOp::Sub(_) => left.checked_sub(right),
+                    BinOp::Mul(_) => left.checked_mul(right),
+                    BinOp::Div(_) => left.checked_div(right),
+                    BinOp::Shl(_) => u32::try_from(right)
+                        .ok()
+                        .and_then(|right| left.checked_shl(right)),
+                    BinOp::BitOr(_) => Some(left | right),
+                    BinOp::BitAnd(_) => Some(left & right),
+                    _ => None,
+                }
+                .ok_or_else(unsupported)
+            }
+            Expr::Path(p) => {
+                let key = self.const_key(&p.path, self_ty).ok_or_else(unsupported)?;
+                let (owner, value) = &self.consts[&key];
+                self.eval(value, owner)
+            }
+            _ => Err(unsupported()),
+        }
+    }
+
+    fn is_generated_struct(&self, name: &str) -> bool {
+        !HAND_WRITTEN.contains(&name) && self.structs.iter().any(|s| s.ident == name)
+    }
+
+    fn c_type(&self, ty: &Type, self_ty: &str) -> Result<CType, Error> {
+        match ty {
+            Type::Array(a) => {
+                let elem = Box::new(self.c_type(&a.elem, self_ty)?);
+                let count = self.eval(&a.len, self_ty)?;
+                // Refer to the length by name if the header defines it
+                let len = match &a.len {
+                    Expr::Path(p) => match self.const_key(&p.path, self_ty) {
+                        Some(key) if self.is_generated_struct(&self.consts[&key].0) => {
+                            let (ty, name) = key.split_once("::").unwrap();
+                            format!("{}_{name}", c_const_prefix(ty))
+                        }
+                        _ => count.to_string(),
+                    },
+                    _ => count.to_string(),
+                };
+                Ok(CType::Array { elem, len, count })
+            }
+            Type::Path(_) => {
+                let name = type_name(ty).unwrap_or_default();
+                let (c_name, size) = match name.as_str() {
+                    "u8" => ("uint8_t", 1),
+                    "u16" => ("uint16_t", 2),
+                    "u32" => ("uint32_t", 4),
+                    "u64" => ("uint64_t", 8),
+                    "i8" => ("int8_t", 1),
+                    "i16" => ("int16_t", 2),
+                    "i32" => ("int32_t", 4),
+                    "i64" => ("int64_t", 8),
+                    _ => {
+                        if let Some(alias) = self.aliases.get(&name) {
+                            return self.c_type(alias, self_ty);
+                        }
+                        if let Some(inner) = self.newtypes.get(&name) {
+                            return self.c_type(inner, self_ty);
+                        }
+                        if self.is_generated_struct(&name) {
+                            return Ok(CType::Struct(name));
+                        }
+                        return Err(invalid(format!("unsupported field type {name}")));
+                    }
+                };
+                Ok(CType::Int { name: c_name, size })
+            }
+            _ => Err(invalid(format!(
+                "unsupported field type {}",
+                ty.to_token_stream()
+            ))),
+        }
+    }
+
+    /// Lowers the generated structs to C, with every struct following the
+    /// structs it contains
+    fn c_structs(&self) -> Result<Vec<CStruct>, Error> {
+        fn visit(
+            model: &Model,
+            item: &ItemStruct,
+            visited: &mut HashSet<String>,
+            result: &mut Vec<CStruct>,
+        ) -> Result<(), Error> {
+            let rust_name = item.ident.to_string();
+            if !visited.insert(rust_name.clone()) {
+                return Ok(());
+            }
+            let mut fields = vec![];
+            for field in item.fields.iter() {
+                let ty = model
+                    .c_type(&field.ty, &rust_name)
+                    .map_err(|e| invalid(format!("{rust_name}: {e}")))?;
+                let mut inner = &ty;
+                while let CType::Array { elem, .. } = inner {
+                    inner = elem;
+                }
+                if let CType::Struct(dep) = inner {
+                    let dep = model.structs.iter().find(|s| s.ident == dep).unwrap();
+                    visit(model, dep, visited, result)?;
+                }
+                fields.push(CField {
+                    name: field.ident.as_ref().unwrap().to_string(),
+                    ty,
+                    docs: doc_lines(&field.attrs),
+                });
+            }
+            result.push(CStruct {
+                rust_name,
+                docs: doc_lines(&item.attrs),
+                fields,
+            });
+            Ok(())
+        }
+
+        let mut visited = HashSet::new();
+        let mut result = vec![];
+        for item in self.structs.iter() {
+            if self.is_generated_struct(&item.ident.to_string()) {
+                visit(self, item, &mut visited, &mut result)?;
+            }
+        }
+        Ok(result)
+    }
+
+    fn eval_const(&self, ty: &str, name: &str) -> Result<u64, Error> {
+        let (owner, value) = &self.consts[&format!("{ty}::{name}")];
+        self.eval(value, owner)
+    }
+}
+
+/// Returns the size and alignment of `ty`, following the layout rules of
+/// `#[repr(C)]`
+fn size_align(ty: &CType, structs: &[CStruct]) -> (u64, u64) {
+    match ty {
+        CType::Int { size, .. } => (*size, *size),
+        CType::Array { elem, count, .. } => {
+            let (size, align) = size_align(elem, structs);
+            (size * count, align)
+        }
+        CType::Struct(name) => {
+            let s = structs.iter().find(|s| s.rust_name == *name).unwrap();
+            let mut offset = 0;
+            let mut struct_align = 1;
+            for field in s.fields.iter() {
+                let (size, align) = size_align(&field.ty, structs);
+                offset = (offset + align - 1) / align * align + size;
+                struct_align = struct_align.max(align);
+            }
+            let size = (offset + struct_align - 1) / struct_align * struct_align;
+            (size, struct_align)
+        }
+    }
+}
+
+fn declaration(ty: &CType, name: &str) -> String {
+    let mut dims = String::new();
+    let mut base = ty;
+    while let CType::Array { elem, len, .. } = base {
+        write!(dims, "[{len}]").unwrap();
+        base = elem;
+    }
+    match base {
+        CType::Int { name: c_name, .. } => format!("{c_name} {name}{dims}"),
+        CType::Struct(rust_name) => format!("struct {} {name}{dims}", c_struct_name(rust_name)),
+        CType::Array { .. } => unreachable!(),
+    }
+}
+
+/// Returns the ASCII mnemonic of a command ID, such as "FWLD"
+fn command_mnemonic(value: u32) -> Option<String> {
+    let bytes = value.to_be_bytes();
+    bytes
+        .iter()
+        .all(|b| b.is_ascii_graphic())
+        .then(|| bytes.iter().map(|&b| char::from(b)).collect())
+}
+
+fn generate(model: &Model) -> Result<String, Error> {
+    let mut out = String::from(PREAMBLE);
+
+    out.push_str("\n// Mailbox command IDs\n");
+    out.push_str("enum caliptra_command_id {\n");
+    let width = model
+        .commands
+        .iter()
+        .map(|(name, _)| name.len())
+        .max()
+        .unwrap_or_default();
+    for (name, value) in model.commands.iter() {
+        let entry = format!("CALIPTRA_CMD_{name:<width$} = 0x{value:08X},");
+        match command_mnemonic(*value) {
+            Some(mnemonic) => writeln!(out, "    {entry} // \"{mnemonic}\"").unwrap(),
+            None => writeln!(out, "    {entry}").unwrap(),
+        }
+    }
+    out.push_str("};\n");
+
+    for e in model.enums.iter() {
+        let prefix = c_const_prefix(&e.ident.to_string());
+        out.push('\n');
+        for line in doc_lines(&e.attrs) {
+            writeln!(out, "// {line}").unwrap();
+        }
+        writeln!(out, "enum {} {{", c_struct_name(&e.ident.to_string())).unwrap();
+        let mut next = 0;
+        for variant in e.variants.iter() {
+            if let Some((_, value)) = &variant.discriminant {
+                next = model.eval(value, "")?;
+            }
+            let name = snake_case(&variant.ident.to_string()).to_ascii_uppercase();
+            writeln!(out, "    {prefix}_{name} = {next},").unwrap();
+            next += 1;
+        }
+        out.push_str("};\n");
+    }
+
+    for flags in model.flags.iter() {
+        let prefix = c_const_prefix(&flags.name);
+        out.push('\n');
+        for (name, value) in flags.values.iter() {
+            let value = model.eval(value, &flags.name)?;
+            writeln!(out, "#define {prefix}_{name} ({value:#x}u)").unwrap();
+        }
+    }
+
+    let structs = model.c_structs()?;
+    for s in structs.iter() {
+        let c_name = c_struct_name(&s.rust_name);
+        out.push('\n');
+        for line in s.docs.iter() {
+            writeln!(out, "// {line}").unwrap();
+        }
+        if let Some((id, resp)) = model.requests.get(&s.rust_name) {
+            writeln!(
+                out,
+                "// Request for CALIPTRA_CMD_{id}; the response is struct {}",
+                c_struct_name(resp)
+            )
+            .unwrap();
+        }
+        let prefix = c_const_prefix(&s.rust_name);
+        for name in model.type_consts.get(&s.rust_name).into_iter().flatten() {
+            let value = model.eval_const(&s.rust_name, name)?;
+            writeln!(out, "#define {prefix}_{name} ({value}u)").unwrap();
+        }
+        writeln!(out, "struct {c_name} {{").unwrap();
+        for field in s.fields.iter() {
+            for line in field.docs.iter() {
+                writeln!(out, "    // {line}").unwrap();
+            }
+            writeln!(out, "    {};", declaration(&field.ty, &field.name)).unwrap();
+        }
+        out.push_str("};\n");
+        let (size, _) = size_align(&CType::Struct(s.rust_name.clone()), &structs);
+        writeln!(
+            out,
+            "CALIPTRA_STATIC_ASSERT(sizeof(struct {c_name}) == {size}, \"struct {c_name} doesn't match {}\");",
+            s.rust_name
+        )
+        .unwrap();
+    }
+
+    out.push('\n');
+    out.push_str(CHECKSUM_HELPERS);
+    Ok(out)
+}
+
+fn usage() -> ! {
+    println!("Usage: caliptra-libcaliptra-gen [--check]");
+    std::process::exit(1);
+}
+
+fn main() {
+    let mut check = false;
+    for arg in std::env::args().skip(1) {
+        match arg.as_str() {
+            "--check" => check = true,
+            _ => usage(),
+        }
+    }
+
+    let mut model = Model::default();
+    for path in SOURCES.iter().map(Path::new) {
+        let source = std::fs::read_to_string(path)
+            .map_err(add_path(path))
+            .unwrap();
+        model.add_source(path, &source).unwrap();
+    }
+    let header = generate(&model).unwrap();
+
+    if check {
+        let existing = std::fs::read_to_string(OUTPUT).unwrap_or_default();
+        if existing != header {
+            println!(
+                "{OUTPUT} is out of date with api/src/mailbox.rs. To fix, run \
+                 \"cargo run -p caliptra-libcaliptra-gen\"."
+            );
+            std::process::exit(2);
+        }
+    } else {
+        std::fs::write(OUTPUT, header)
+            .map_err(add_path(Path::new(OUTPUT)))
+            .unwrap();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+
+    fn generate_from(source: &str) -> String {
+        let mut model = Model::default();
+        model.add_source(Path::new("foo.rs"), source).unwrap();
+        generate(&model).unwrap()
+    }
+
+    #[test]
+    fn test_snake_case() {
+        assert_eq!(snake_case("GetIdevCertReq"), "get_idev_cert_req");
+        assert_eq!(snake_case("Ecdsa384VerifyReq"), "ecdsa384_verify_req");
+        assert_eq!(snake_case("DPEHandle"), "dpe_handle");
+        assert_eq!(c_struct_name("MailboxReqHeader"), "caliptra_req_header");
+    }
+
+    #[test]
+    fn test_generate() {
+        let header = generate_from(
+            r#"
+            pub struct CommandId(pub u32);
+            impl CommandId {
+                pub const FOO: Self = Self(0x464F_4F21); // "FOO!"
+            }
+            #[repr(C)]
+            pub struct MailboxReqHeader {
+                pub chksum: u32,
+            }
+            #[repr(C)]
+            pub struct MailboxRespHeader {
+                pub chksum: u32,
+                pub fips_status: u32,
+            }
+            pub type Digest = [u8; 48];
+            #[repr(C)]
+            pub struct FooFlags(pub u32);
+            bitflags! {
+                impl FooFlags: u32 {
+                    const BAR = 1u32 << 31;
+                }
+            }
+            /// FOO input arguments
+            #[repr(C)]
+            pub struct FooReq {
+                pub hdr: MailboxReqHeader,
+                pub flags: FooFlags,
+                pub len: u16,
+                /// The digests
+                pub digests: [Digest; 2],
+                pub data: [u8; Self::MAX_LEN],
+            }
+            impl FooReq {
+                pub const MAX_LEN: usize = 2 * 3;
+            }
+            impl Request for FooReq {
+                const ID: CommandId = CommandId::FOO;
+                type Resp = MailboxRespHeader;
+            }
+            #[repr(u32)]
+            pub enum FooMode {
+                Slow = 1,
+                Fast,
+            }
+            #[cfg(test)]
+            impl FooReq {
+                pub const TEST_ONLY: usize = 1;
+            }
+        "#,
+        );
+        assert!(header.contains(
+            "enum caliptra_command_id {\n    CALIPTRA_CMD_FOO = 0x464F4F21, // \"FOO!\"\n};\n"
+        ));
+        assert!(header.contains(
+            "enum caliptra_foo_mode {\n    CALIPTRA_FOO_MODE_SLOW = 1,\n    CALIPTRA_FOO_MODE_FAST = 2,\n};\n"
+        ));
+        assert!(header.contains("#define CALIPTRA_FOO_FLAGS_BAR (0x80000000u)\n"));
+        assert!(header.contains(
+            r#"// FOO input arguments
+// Request for CALIPTRA_CMD_FOO; the response is struct caliptra_resp_header
+#define CALIPTRA_FOO_REQ_MAX_LEN (6u)
+struct caliptra_foo_req {
+    struct caliptra_req_header hdr;
+    uint32_t flags;
+    uint16_t len;
+    // The digests
+    uint8_t digests[2][48];
+    uint8_t data[CALIPTRA_FOO_REQ_MAX_LEN];
+};
+CALIPTRA_STATIC_ASSERT(sizeof(struct caliptra_foo_req) == 112, "struct caliptra_foo_req doesn't match FooReq");
+"#
+        ));
+        assert!(!header.contains("TEST_ONLY"));
+    }
+
+    #[test]
+    fn test_struct_dependencies_first() {
+        let header = generate_from(
+            r#"
+            #[repr(C)]
+            pub struct OuterResp {
+                pub inner: InnerResp,
+            }
+            #[repr(C)]
+            pub struct InnerResp {
+                pub a: u8,
+                pub b: u32,
+                pub c: u8,
+            }
+        "#,
+        );
+        let inner = header.find("struct caliptra_inner_resp {").unwrap();
+        let outer = header.find("struct caliptra_outer_resp {").unwrap();
+        assert!(inner < outer);
+        assert!(header.contains("sizeof(struct caliptra_inner_resp) == 12,"));
+        assert!(header.contains("sizeof(struct caliptra_outer_resp) == 12,"));
+    }
+
+    #[test]
+    fn test_unsupported_field_type() {
+        let mut model = Model::default();
+        model
+            .add_source(
+                Path::new("foo.rs"),
+                "#[repr(C)] pub struct FooReq { pub hdr: Option<u32> }",
+            )
+            .unwrap();
+        assert_eq!(
+            generate(&model).unwrap_err().to_string(),
+            "FooReq: unsupported field type Option"
+        );
+    }
+
+    #[test]
+    fn test_checked_in_header_is_current() {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("../..");
+        let mut model = Model::default();
+        for path in SOURCES.iter() {
+            let path = root.join(path);
+            model
+                .add_source(&path, &std::fs::read_to_string(&path).unwrap())
+                .unwrap();
+        }
+        let expected = std::fs::read_to_string(root.join(OUTPUT)).unwrap();
+        assert!(
+            generate(&model).unwrap() == expected,
+            "{OUTPUT} is out of date; run \"cargo run -p caliptra-libcaliptra-gen\""
+        );
+    }
+}
@@ -195,6 +195,19 @@ int caliptra_shutdown(bool async);
 // Capabilities
 int caliptra_capabilities(struct caliptra_capabilities_resp *resp, bool async);
 
+// Set auth manifest
+// Only the first manifest_size bytes of the manifest are sent
+int caliptra_set_auth_manifest(struct caliptra_set_auth_manifest_req *req, bool async);
+
+// Authorize and stash
+int caliptra_authorize_and_stash(struct caliptra_authorize_and_stash_req *req, struct caliptra_authorize_and_stash_resp *resp, bool async);
+
+// Get cert chain
+int caliptra_get_cert_chain(struct caliptra_get_cert_chain_req *req, struct caliptra_get_cert_chain_resp *resp, bool async);
+
+// Get FMC alias CSR
+int caliptra_get_fmc_alias_csr(struct caliptra_get_fmc_alias_csr_resp *resp, bool async);
+
 // Query if IDevID CSR is ready.
 bool caliptra_is_idevid_csr_ready();
 
@@ -0,0 +1,435 @@
+// Licensed under the Apache-2.0 license
+//
+// Generated by caliptra-libcaliptra-gen from api/src/mailbox.rs.
+// Do not edit; run "cargo run -p caliptra-libcaliptra-gen" to regenerate.
+#pragma once
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+#define CALIPTRA_STATIC_ASSERT static_assert
+#else
+#define CALIPTRA_STATIC_ASSERT _Static_assert
+#endif
+
+// Mailbox command IDs
+enum caliptra_command_id {
+    CALIPTRA_CMD_FIRMWARE_LOAD               = 0x46574C44, // "FWLD"
+    CALIPTRA_CMD_GET_IDEV_CERT               = 0x49444543, // "IDEC"
+    CALIPTRA_CMD_GET_IDEV_INFO               = 0x49444549, // "IDEI"
+    CALIPTRA_CMD_POPULATE_IDEV_CERT          = 0x49444550, // "IDEP"
+    CALIPTRA_CMD_GET_LDEV_CERT               = 0x4C444556, // "LDEV"
+    CALIPTRA_CMD_GET_FMC_ALIAS_CERT          = 0x43455246, // "CERF"
+    CALIPTRA_CMD_GET_RT_ALIAS_CERT           = 0x43455252, // "CERR"
+    CALIPTRA_CMD_ECDSA384_VERIFY             = 0x53494756, // "SIGV"
+    CALIPTRA_CMD_LMS_VERIFY                  = 0x4C4D5356, // "LMSV"
+    CALIPTRA_CMD_STASH_MEASUREMENT           = 0x4D454153, // "MEAS"
+    CALIPTRA_CMD_INVOKE_DPE                  = 0x44504543, // "DPEC"
+    CALIPTRA_CMD_DISABLE_ATTESTATION         = 0x4453424C, // "DSBL"
+    CALIPTRA_CMD_FW_INFO                     = 0x494E464F, // "INFO"
+    CALIPTRA_CMD_DPE_TAG_TCI                 = 0x54514754, // "TQGT"
+    CALIPTRA_CMD_DPE_GET_TAGGED_TCI          = 0x47544744, // "GTGD"
+    CALIPTRA_CMD_INCREMENT_PCR_RESET_COUNTER = 0x50435252, // "PCRR"
+    CALIPTRA_CMD_QUOTE_PCRS                  = 0x50435251, // "PCRQ"
+    CALIPTRA_CMD_EXTEND_PCR                  = 0x50435245, // "PCRE"
+    CALIPTRA_CMD_ADD_SUBJECT_ALT_NAME        = 0x414C544E, // "ALTN"
+    CALIPTRA_CMD_CERTIFY_KEY_EXTENDED        = 0x434B4558, // "CKEX"
+    CALIPTRA_CMD_VERSION                     = 0x46505652, // "FPVR"
+    CALIPTRA_CMD_SELF_TEST_START             = 0x46504C54, // "FPLT"
+    CALIPTRA_CMD_SELF_TEST_GET_RESULTS       = 0x46504C67, // "FPLg"
+    CALIPTRA_CMD_SHUTDOWN                    = 0x46505344, // "FPSD"
+    CALIPTRA_CMD_CAPABILITIES                = 0x43415053, // "CAPS"
+    CALIPTRA_CMD_SET_AUTH_MANIFEST           = 0x41544D4E, // "ATMN"
+    CALIPTRA_CMD_AUTHORIZE_AND_STASH         = 0x41545348, // "ATSH"
+    CALIPTRA_CMD_GET_IDEV_CSR                = 0x49444352, // "IDCR"
+    CALIPTRA_CMD_GET_CERT_CHAIN              = 0x43484E43, // "CHNC"
+    CALIPTRA_CMD_GET_FMC_ALIAS_CSR           = 0x464D4352, // "FMCR"
+};
+
+enum caliptra_image_hash_source {
+    CALIPTRA_IMAGE_HASH_SOURCE_INVALID = 0,
+    CALIPTRA_IMAGE_HASH_SOURCE_IN_REQUEST = 1,
+    CALIPTRA_IMAGE_HASH_SOURCE_SHA_ACC = 2,
+};
+
+#define CALIPTRA_CERTIFY_KEY_EXTENDED_FLAGS_DMTF_OTHER_NAME (0x80000000u)
+
+#define CALIPTRA_AUTH_AND_STASH_FLAGS_SKIP_STASH (0x1u)
+
+#define CALIPTRA_GET_CERT_CHAIN_FLAGS_INCLUDE_DPE_LEAF (0x80000000u)
+
+#define CALIPTRA_RESP_HEADER_FIPS_STATUS_APPROVED (0u)
+struct caliptra_resp_header {
+    uint32_t chksum;
+    uint32_t fips_status;
+};
+CALIPTRA_STATIC_ASSERT(sizeof(struct caliptra_resp_header) == 8, "struct caliptra_resp_header doesn't match MailboxRespHeader");
+
+struct caliptra_resp_header_var_size {
+    struct caliptra_resp_header hdr;
+    uint32_t data_len;
+};
+CALIPTRA_STATIC_ASSERT(sizeof(struct caliptra_resp_header_var_size) == 12, "struct caliptra_resp_header_var_size doesn't match MailboxRespHeaderVarSize");
+
+struct caliptra_req_header {
+    uint32_t chksum;
+};
+CALIPTRA_STATIC_ASSERT(sizeof(struct caliptra_req_header) == 4, "struct caliptra_req_header doesn't match MailboxReqHeader");
+
+#define CALIPTRA_GET_IDEV_CERT_REQ_DATA_MAX_SIZE (916u)
+struct caliptra_get_idev_cert_req {
+    struct caliptra_req_header hdr;
+    uint32_t tbs_size;
+    uint8_t signature_r[48];
+    uint8_t signature_s[48];
+    uint8_t tbs[CALIPTRA_GET_IDEV_CERT_REQ_DATA_MAX_SIZE];
+};
+CALIPTRA_STATIC_ASSERT(sizeof(struct caliptra_get_idev_cert_req) == 1020, "struct caliptra_get_idev_cert_req doesn't match GetIdevCertReq");
+
+#define CALIPTRA_GET_IDEV_CERT_RESP_DATA_MAX_SIZE (1024u)
+struct caliptra_get_idev_cert_resp {
+    struct caliptra_resp_header hdr;
+    uint32_t cert_size;
+    uint8_t cert[CALIPTRA_GET_IDEV_CERT_RESP_DATA_MAX_SIZE];
+};
+CALIPTRA_STATIC_ASSERT(sizeof(struct caliptra_get_idev_cert_resp) == 1036, "struct caliptra_get_idev_cert_resp doesn't match GetIdevCertResp");
+
+struct caliptra_get_idev_info_resp {
+    struct caliptra_resp_header hdr;
+    uint8_t idev_pub_x[48];
+    uint8_t idev_pub_y[48];
+};
+CALIPTRA_STATIC_ASSERT(sizeof(struct caliptra_get_idev_info_resp) == 104, "struct caliptra_get_idev_info_resp doesn't match GetIdevInfoResp");
+
+// Request for CALIPTRA_CMD_GET_LDEV_CERT; the response is struct caliptra_get_ldev_cert_resp
+struct caliptra_get_ldev_cert_req {
+    struct caliptra_req_header header;
+};
+CALIPTRA_STATIC_ASSERT(sizeof(struct caliptra_get_ldev_cert_req) == 4, "struct caliptra_get_ldev_cert_req doesn't match GetLdevCertReq");
+
+#define CALIPTRA_GET_LDEV_CERT_RESP_DATA_MAX_SIZE (1024u)
+struct caliptra_get_ldev_cert_resp {
+    struct caliptra_resp_header hdr;
+    uint32_t data_size;
+    uint8_t data[CALIPTRA_GET_LDEV_CERT_RESP_DATA_MAX_SIZE];
+};
+CALIPTRA_STATIC_ASSERT(sizeof(struct caliptra_get_ldev_cert_resp) == 1036, "struct caliptra_get_ldev_cert_resp doesn't match GetLdevCertResp");
+
+// Request for CALIPTRA_CMD_GET_RT_ALIAS_CERT; the response is struct caliptra_get_rt_alias_cert_resp
+struct caliptra_get_rt_alias_cert_req {
+    struct caliptra_req_header header;
+};
+CALIPTRA_STATIC_ASSERT(sizeof(struct caliptra_get_rt_alias_cert_req) == 4, "struct caliptra_get_rt_alias_cert_req doesn't match GetRtAliasCertReq");
+
+#define CALIPTRA_GET_RT_ALIAS_CERT_RESP_DATA_MAX_SIZE (1024u)
+struct caliptra_get_rt_alias_cert_resp {
+    struct caliptra_resp_header hdr;
+    uint32_t data_size;
+    uint8_t data[CALIPTRA_GET_RT_ALIAS_CERT_RESP_DATA_MAX_SIZE];
+};
+CALIPTRA_STATIC_ASSERT(sizeof(struct caliptra_get_rt_alias_cert_resp) == 1036, "struct caliptra_get_rt_alias_cert_resp doesn't match GetRtAliasCertResp");
+
+// Request for CALIPTRA_CMD_ECDSA384_VERIFY; the response is struct caliptra_resp_header
+struct caliptra_ecdsa_verify_req {
+    struct caliptra_req_header hdr;
+    uint8_t pub_key_x[48];
+    uint8_t pub_key_y[48];
+    uint8_t signature_r[48];
+    uint8_t signature_s[48];
+};
+CALIPTRA_STATIC_ASSERT(sizeof(struct caliptra_ecdsa_verify_req) == 196, "struct caliptra_ecdsa_verify_req doesn't match EcdsaVerifyReq");
+
+// Request for CALIPTRA_CMD_LMS_VERIFY; the response is struct caliptra_resp_header
+struct caliptra_lms_verify_req {
+    struct caliptra_req_header hdr;
+    uint32_t pub_key_tree_type;
+    uint32_t pub_key_ots_type;
+    uint8_t pub_key_id[16];
+    uint8_t pub_key_digest[24];
+    uint32_t signature_q;
+    uint8_t signature_ots[1252];
+    uint32_t signature_tree_type;
+    uint8_t signature_tree_path[360];
+};
+CALIPTRA_STATIC_ASSERT(sizeof(struct caliptra_lms_verify_req) == 1672, "struct caliptra_lms_verify_req doesn't match LmsVerifyReq");
+
+// Request for CALIPTRA_CMD_STASH_MEASUREMENT; the response is struct caliptra_stash_measurement_resp
+struct caliptra_stash_measurement_req {
+    struct caliptra_req_header hdr;
+    uint8_t metadata[4];
+    uint8_t measurement[48];
+    uint8_t context[48];
+    uint32_t svn;
+};
+CALIPTRA_STATIC_ASSERT(sizeof(struct caliptra_stash_measurement_req) == 108, "struct caliptra_stash_measurement_req doesn't match StashMeasurementReq");
+
+struct caliptra_stash_measurement_resp {
+    struct caliptra_resp_header hdr;
+    uint32_t dpe_result;
+};
+CALIPTRA_STATIC_ASSERT(sizeof(struct caliptra_stash_measurement_resp) == 12, "struct caliptra_stash_measurement_resp doesn't match StashMeasurementResp");
+
+// Request for CALIPTRA_CMD_CERTIFY_KEY_EXTENDED; the response is struct caliptra_certify_key_extended_resp
+#define CALIPTRA_CERTIFY_KEY_EXTENDED_REQ_CERTIFY_KEY_REQ_SIZE (72u)
+struct caliptra_certify_key_extended_req {
+    struct caliptra_req_header hdr;
+    uint32_t flags;
+    uint8_t certify_key_req[CALIPTRA_CERTIFY_KEY_EXTENDED_REQ_CERTIFY_KEY_REQ_SIZE];
+};
+CALIPTRA_STATIC_ASSERT(sizeof(struct caliptra_certify_key_extended_req) == 80, "struct caliptra_certify_key_extended_req doesn't match CertifyKeyExtendedReq");
+
+#define CALIPTRA_CERTIFY_KEY_EXTENDED_RESP_CERTIFY_KEY_RESP_SIZE (2176u)
+struct caliptra_certify_key_extended_resp {
+    struct caliptra_resp_header hdr;
+    uint8_t certify_key_resp[CALIPTRA_CERTIFY_KEY_EXTENDED_RESP_CERTIFY_KEY_RESP_SIZE];
+};
+CALIPTRA_STATIC_ASSERT(sizeof(struct caliptra_certify_key_extended_resp) == 2184, "struct caliptra_certify_key_extended_resp doesn't match CertifyKeyExtendedResp");
+
+// Request for CALIPTRA_CMD_EXTEND_PCR; the response is struct caliptra_resp_header
+struct caliptra_extend_pcr_req {
+    struct caliptra_req_header hdr;
+    uint32_t pcr_idx;
+    uint8_t data[48];
+};
+CALIPTRA_STATIC_ASSERT(sizeof(struct caliptra_extend_pcr_req) == 56, "struct caliptra_extend_pcr_req doesn't match ExtendPcrReq");
+
+// Request for CALIPTRA_CMD_GET_FMC_ALIAS_CERT; the response is struct caliptra_get_fmc_alias_cert_resp
+struct caliptra_get_fmc_alias_cert_req {
+    struct caliptra_req_header header;
+};
+CALIPTRA_STATIC_ASSERT(sizeof(struct caliptra_get_fmc_alias_cert_req) == 4, "struct caliptra_get_fmc_alias_cert_req doesn't match GetFmcAliasCertReq");
+
+#define CALIPTRA_GET_FMC_ALIAS_CERT_RESP_DATA_MAX_SIZE (1024u)
+struct caliptra_get_fmc_alias_cert_resp {
+    struct caliptra_resp_header hdr;
+    uint32_t data_size;
+    uint8_t data[CALIPTRA_GET_FMC_ALIAS_CERT_RESP_DATA_MAX_SIZE];
+};
+CALIPTRA_STATIC_ASSERT(sizeof(struct caliptra_get_fmc_alias_cert_resp) == 1036, "struct caliptra_get_fmc_alias_cert_resp doesn't match GetFmcAliasCertResp");
+
+struct caliptra_fips_version_resp {
+    struct caliptra_resp_header hdr;
+    uint32_t mode;
+    uint32_t fips_rev[3];
+    uint8_t name[12];
+};
+CALIPTRA_STATIC_ASSERT(sizeof(struct caliptra_fips_version_resp) == 36, "struct caliptra_fips_version_resp doesn't match FipsVersionResp");
+
+struct caliptra_fw_info_resp {
+    struct caliptra_resp_header hdr;
+    uint32_t pl0_pauser;
+    uint32_t runtime_svn;
+    uint32_t min_runtime_svn;
+    uint32_t fmc_manifest_svn;
+    uint32_t attestation_disabled;
+    uint8_t rom_revision[20];
+    uint8_t fmc_revision[20];
+    uint8_t runtime_revision[20];
+    uint32_t rom_sha256_digest[8];
+    uint32_t fmc_sha384_digest[12];
+    uint32_t runtime_sha384_digest[12];
+    uint32_t owner_pub_key_hash[12];
+};
+CALIPTRA_STATIC_ASSERT(sizeof(struct caliptra_fw_info_resp) == 264, "struct caliptra_fw_info_resp doesn't match FwInfoResp");
+
+struct caliptra_capabilities_resp {
+    struct caliptra_resp_header hdr;
+    uint8_t capabilities[16];
+};
+CALIPTRA_STATIC_ASSERT(sizeof(struct caliptra_capabilities_resp) == 24, "struct caliptra_capabilities_resp doesn't match CapabilitiesResp");
+
+#define CALIPTRA_ADD_SUBJECT_ALT_NAME_REQ_MAX_DEVICE_INFO_LEN (128u)
+struct caliptra_add_subject_alt_name_req {
+    struct caliptra_req_header hdr;
+    uint32_t dmtf_device_info_size;
+    uint8_t dmtf_device_info[CALIPTRA_ADD_SUBJECT_ALT_NAME_REQ_MAX_DEVICE_INFO_LEN];
+};
+CALIPTRA_STATIC_ASSERT(sizeof(struct caliptra_add_subject_alt_name_req) == 136, "struct caliptra_add_subject_alt_name_req doesn't match AddSubjectAltNameReq");
+
+#define CALIPTRA_POPULATE_IDEV_CERT_REQ_MAX_CERT_SIZE (1024u)
+struct caliptra_populate_idev_cert_req {
+    struct caliptra_req_header hdr;
+    uint32_t cert_size;
+    uint8_t cert[CALIPTRA_POPULATE_IDEV_CERT_REQ_MAX_CERT_SIZE];
+};
+CALIPTRA_STATIC_ASSERT(sizeof(struct caliptra_populate_idev_cert_req) == 1032, "struct caliptra_populate_idev_cert_req doesn't match PopulateIdevCertReq");
+
+struct caliptra_dpe_tag_tci_req {
+    struct caliptra_req_header hdr;
+    uint8_t handle[16];
+    uint32_t tag;
+};
+CALIPTRA_STATIC_ASSERT(sizeof(struct caliptra_dpe_tag_tci_req) == 24, "struct caliptra_dpe_tag_tci_req doesn't match TagTciReq");
+
+struct caliptra_get_tagged_tci_req {
+    struct caliptra_req_header hdr;
+    uint32_t tag;
+};
+CALIPTRA_STATIC_ASSERT(sizeof(struct caliptra_get_tagged_tci_req) == 8, "struct caliptra_get_tagged_tci_req doesn't match GetTaggedTciReq");
+
+struct caliptra_get_tagged_tci_resp {
+    struct caliptra_resp_header hdr;
+    uint8_t tci_cumulative[48];
+    uint8_t tci_current[48];
+};
+CALIPTRA_STATIC_ASSERT(sizeof(struct caliptra_get_tagged_tci_resp) == 104, "struct caliptra_get_tagged_tci_resp doesn't match GetTaggedTciResp");
+
+// Request for CALIPTRA_CMD_INCREMENT_PCR_RESET_COUNTER; the response is struct caliptra_resp_header
+struct caliptra_increment_pcr_reset_counter_req {
+    struct caliptra_req_header hdr;
+    uint32_t index;
+};
+CALIPTRA_STATIC_ASSERT(sizeof(struct caliptra_increment_pcr_reset_counter_req) == 8, "struct caliptra_increment_pcr_reset_counter_req doesn't match IncrementPcrResetCounterReq");
+
+// QUOTE_PCRS input arguments
+// Request for CALIPTRA_CMD_QUOTE_PCRS; the response is struct caliptra_quote_pcrs_resp
+struct caliptra_quote_pcrs_req {
+    struct caliptra_req_header hdr;
+    uint8_t nonce[32];
+};
+CALIPTRA_STATIC_ASSERT(sizeof(struct caliptra_quote_pcrs_req) == 36, "struct caliptra_quote_pcrs_req doesn't match QuotePcrsReq");
+
+// QUOTE_PCRS output
+struct caliptra_quote_pcrs_resp {
+    struct caliptra_resp_header hdr;
+    // The PCR values
+    uint8_t pcrs[32][48];
+    uint8_t nonce[32];
+    uint8_t digest[48];
+    uint32_t reset_ctrs[32];
+    uint8_t signature_r[48];
+    uint8_t signature_s[48];
+};
+CALIPTRA_STATIC_ASSERT(sizeof(struct caliptra_quote_pcrs_resp) == 1848, "struct caliptra_quote_pcrs_resp doesn't match QuotePcrsResp");
+
+#define CALIPTRA_SET_AUTH_MANIFEST_REQ_MAX_MAN_SIZE (14336u)
+struct caliptra_set_auth_manifest_req {
+    struct caliptra_req_header hdr;
+    uint32_t manifest_size;
+    uint8_t manifest[CALIPTRA_SET_AUTH_MANIFEST_REQ_MAX_MAN_SIZE];
+};
+CALIPTRA_STATIC_ASSERT(sizeof(struct caliptra_set_auth_manifest_req) == 14344, "struct caliptra_set_auth_manifest_req doesn't match SetAuthManifestReq");
+
+// Request for CALIPTRA_CMD_GET_IDEV_CSR; the response is struct caliptra_get_idev_csr_resp
+struct caliptra_get_idev_csr_req {
+    struct caliptra_req_header hdr;
+};
+CALIPTRA_STATIC_ASSERT(sizeof(struct caliptra_get_idev_csr_req) == 4, "struct caliptra_get_idev_csr_req doesn't match GetIdevCsrReq");
+
+#define CALIPTRA_GET_IDEV_CSR_RESP_DATA_MAX_SIZE (512u)
+struct caliptra_get_idev_csr_resp {
+    struct caliptra_resp_header hdr;
+    uint32_t data_size;
+    uint8_t data[CALIPTRA_GET_IDEV_CSR_RESP_DATA_MAX_SIZE];
+};
+CALIPTRA_STATIC_ASSERT(sizeof(struct caliptra_get_idev_csr_resp) == 524, "struct caliptra_get_idev_csr_resp doesn't match GetIdevCsrResp");
+
+// Request for CALIPTRA_CMD_GET_FMC_ALIAS_CSR; the response is struct caliptra_get_fmc_alias_csr_resp
+struct caliptra_get_fmc_alias_csr_req {
+    struct caliptra_req_header hdr;
+};
+CALIPTRA_STATIC_ASSERT(sizeof(struct caliptra_get_fmc_alias_csr_req) == 4, "struct caliptra_get_fmc_alias_csr_req doesn't match GetFmcAliasCsrReq");
+
+#define CALIPTRA_GET_FMC_ALIAS_CSR_RESP_DATA_MAX_SIZE (512u)
+struct caliptra_get_fmc_alias_csr_resp {
+    struct caliptra_resp_header hdr;
+    uint32_t data_size;
+    uint8_t data[CALIPTRA_GET_FMC_ALIAS_CSR_RESP_DATA_MAX_SIZE];
+};
+CALIPTRA_STATIC_ASSERT(sizeof(struct caliptra_get_fmc_alias_csr_resp) == 524, "struct caliptra_get_fmc_alias_csr_resp doesn't match GetFmcAliasCsrResp");
+
+// Request for CALIPTRA_CMD_AUTHORIZE_AND_STASH; the response is struct caliptra_stash_measurement_resp
+struct caliptra_authorize_and_stash_req {
+    struct caliptra_req_header hdr;
+    uint8_t fw_id[4];
+    uint8_t measurement[48];
+    uint8_t context[48];
+    uint32_t svn;
+    uint32_t flags;
+    uint32_t source;
+};
+CALIPTRA_STATIC_ASSERT(sizeof(struct caliptra_authorize_and_stash_req) == 116, "struct caliptra_authorize_and_stash_req doesn't match AuthorizeAndStashReq");
+
+struct caliptra_authorize_and_stash_resp {
+    struct caliptra_resp_header hdr;
+    uint32_t auth_req_result;
+};
+CALIPTRA_STATIC_ASSERT(sizeof(struct caliptra_authorize_and_stash_resp) == 12, "struct caliptra_authorize_and_stash_resp doesn't match AuthorizeAndStashResp");
+
+// Request for CALIPTRA_CMD_GET_CERT_CHAIN; the response is struct caliptra_get_cert_chain_resp
+struct caliptra_get_cert_chain_req {
+    struct caliptra_req_header hdr;
+    uint32_t offset;
+    uint32_t size;
+    uint32_t flags;
+    uint8_t label[48];
+};
+CALIPTRA_STATIC_ASSERT(sizeof(struct caliptra_get_cert_chain_req) == 64, "struct caliptra_get_cert_chain_req doesn't match GetCertChainReq");
+
+#define CALIPTRA_GET_CERT_CHAIN_RESP_DATA_MAX_SIZE (2048u)
+struct caliptra_get_cert_chain_resp {
+    struct caliptra_resp_header hdr;
+    uint32_t data_size;
+    uint8_t data[CALIPTRA_GET_CERT_CHAIN_RESP_DATA_MAX_SIZE];
+};
+CALIPTRA_STATIC_ASSERT(sizeof(struct caliptra_get_cert_chain_resp) == 2060, "struct caliptra_get_cert_chain_resp doesn't match GetCertChainResp");
+
+// Calculates a mailbox checksum: 0 - (SUM(command code bytes) + SUM(data bytes)).
+// Requests are checksummed with their command ID and responses with 0.
+static inline uint32_t caliptra_calc_checksum(uint32_t cmd, const uint8_t *data, uint32_t len)
+{
+    uint32_t i, sum = 0;
+
+    if ((data == NULL) && (len != 0))
+    {
+        // Don't respect bad parameters
+        return 0;
+    }
+
+    for (i = 0; i < sizeof(uint32_t); i++)
+    {
+        sum += (cmd >> (8 * i)) & 0xff;
+    }
+
+    for (i = 0; i < len; i++)
+    {
+        sum += data[i];
+    }
+
+    return (0 - sum);
+}
+
+static inline bool caliptra_verify_checksum(uint32_t checksum, uint32_t cmd, const uint8_t *data, uint32_t len)
+{
+    return caliptra_calc_checksum(cmd, data, len) == checksum;
+}
+
+// Fills in the chksum field of the len-byte request at req
+static inline void caliptra_populate_req_checksum(uint32_t cmd, void *req, uint32_t len)
+{
+    struct caliptra_req_header *hdr = (struct caliptra_req_header *)req;
+    const uint8_t *data = (const uint8_t *)req + sizeof(hdr->chksum);
+
+    if (len < sizeof(*hdr))
+    {
+        return;
+    }
+
+    hdr->chksum = caliptra_calc_checksum(cmd, data, len - sizeof(hdr->chksum));
+}
+
+// Checks the chksum field of the len-byte response at resp
+static inline bool caliptra_verify_resp_checksum(const void *resp, uint32_t len)
+{
+    const struct caliptra_resp_header *hdr = (const struct caliptra_resp_header *)resp;
+    const uint8_t *data = (const uint8_t *)resp + sizeof(hdr->chksum);
+
+    if (len < sizeof(*hdr))
+    {
+        return false;
+    }
+
+    return caliptra_verify_checksum(hdr->chksum, 0, data, len - sizeof(hdr->chksum));
+}
@@ -5,6 +5,9 @@
 #include <stdbool.h>
 
 #include "caliptra_enums.h"
+// Mailbox request/response structs, command IDs and checksum helpers, generated
+// from caliptra-api
+#include "caliptra_mbox_types.h"
 
 typedef uint32_t caliptra_checksum;
 
@@ -44,188 +47,6 @@ struct caliptra_fuses {
     bool debug_locked;
 };
 
-//    Request/Response fields
-
-struct caliptra_req_header {
-    caliptra_checksum chksum;
-};
-
-struct caliptra_resp_header {
-    caliptra_checksum chksum;
-    uint32_t fips_status;
-};
-
-struct caliptra_get_idev_cert_req {
-    struct caliptra_req_header hdr;
-    uint32_t tbs_size;
-    uint8_t signature_r[48];
-    uint8_t signature_s[48];
-    uint8_t tbs[916];
-};
-
-struct caliptra_get_idev_cert_resp {
-    struct caliptra_resp_header hdr;
-    uint32_t cert_size;
-    uint8_t cert[1024];
-};
-
-struct caliptra_get_idev_info_resp {
-    struct caliptra_resp_header hdr;
-    uint8_t idev_pub_x[48];
-    uint8_t idev_pub_y[48];
-};
-
-struct caliptra_populate_idev_cert_req {
-    struct caliptra_req_header hdr;
-    uint32_t cert_size;
-    uint8_t cert[1024];
-};
-
-struct caliptra_get_ldev_cert_resp {
-    struct caliptra_resp_header hdr;
-    uint32_t data_size;
-    uint8_t data[1024];
-};
-
-struct caliptra_get_fmc_alias_cert_resp {
-    struct caliptra_resp_header hdr;
-    uint32_t data_size;
-    uint8_t data[1024];
-};
-
-struct caliptra_get_rt_alias_cert_resp {
-    struct caliptra_resp_header hdr;
-    uint32_t data_size;
-    uint8_t data[1024];
-};
-
-struct caliptra_ecdsa_verify_req {
-    struct caliptra_req_header hdr;
-    uint8_t pub_key_x[48];
-    uint8_t pub_key_y[48];
-    uint8_t signature_r[48];
-    uint8_t signature_s[48];
-};
-
-struct caliptra_lms_verify_req {
-    struct caliptra_req_header hdr;
-    uint32_t pub_key_tree_type;
-    uint32_t pub_key_ots_type;
-    uint8_t pub_key_id[16];
-    uint8_t pub_key_digest[24];
-    uint32_t signature_q;
-    uint8_t signature_ots[1252];
-    uint32_t signature_tree_type;
-    uint8_t signature_tree_path[360];
-};
-
-struct caliptra_stash_measurement_req {
-    struct caliptra_req_header hdr;
-    uint8_t metadata[4];
-    uint8_t measurement[48];
-    uint8_t context[48];
-    uint32_t svn;
-};
-
-struct caliptra_stash_measurement_resp {
-    struct caliptra_resp_header hdr;
-    uint32_t dpe_result;
-};
-
-struct caliptra_fw_info_resp {
-    struct caliptra_resp_header hdr;
-    uint32_t pl0_pauser;
-    uint32_t runtime_svn;
-    uint32_t min_runtime_svn;
-    uint32_t fmc_manifest_svn;
-    uint32_t attestation_disabled;
-    uint8_t rom_revision[20];
-    uint8_t fmc_revision[20];
-    uint8_t runtime_revision[20];
-    uint32_t rom_sha256_digest[8];
-    uint32_t fmc_sha384_digest[12];
-    uint32_t runtime_sha384_digest[12];
-    uint32_t owner_pub_key_hash[12];
-};
-
-struct caliptra_dpe_tag_tci_req {
-    struct caliptra_req_header hdr;
-    uint8_t handle[16];
-    uint32_t tag;
-};
-
-struct caliptra_get_tagged_tci_req {
-    struct caliptra_req_header hdr;
-    uint32_t tag;
-};
-
-struct caliptra_get_tagged_tci_resp {
-    struct caliptra_resp_header hdr;
-    uint8_t tci_cumulative[48];
-    uint8_t tci_current[48];
-};
-
-struct caliptra_increment_pcr_reset_counter_req {
-    struct caliptra_req_header hdr;
-    uint32_t index;
-};
-
-struct caliptra_quote_pcrs_req {
-    struct caliptra_req_header hdr;
-    uint8_t nonce[32];
-};
-
-struct caliptra_quote_pcrs_resp {
-    struct caliptra_resp_header hdr;
-    uint8_t pcrs[32][48];
-    uint8_t nonce[32];
-    uint8_t digest[48];
-    uint32_t reset_ctrs[32];
-    uint8_t signature_r[48];
-    uint8_t signature_s[48];
-};
-
-struct caliptra_extend_pcr_req {
-    struct caliptra_req_header hdr;
-    uint32_t pcr_idx;
-    uint8_t data[48];
-};
-
-struct caliptra_add_subject_alt_name_req {
-    struct caliptra_req_header hdr;
-    uint32_t dmtf_device_info_size;
-    uint8_t dmtf_device_info[128];
-};
-
-struct caliptra_certify_key_extended_req {
-    struct caliptra_req_header hdr;
-    uint32_t flags;
-    uint8_t certify_key_req[72];
-};
-
-struct caliptra_certify_key_extended_resp {
-    struct caliptra_resp_header hdr;
-    uint8_t certify_key_resp[2176];
-};
-
-struct caliptra_fips_version_resp {
-    struct caliptra_resp_header hdr;
-    uint32_t mode;
-    uint32_t fips_rev[3];
-    uint8_t name[12];
-};
-
-struct caliptra_capabilities_resp {
-    struct caliptra_resp_header hdr;
-    uint8_t capabilities[16];
-};
-
-struct caliptra_get_idev_csr_resp {
-    struct caliptra_resp_header hdr;
-    uint32_t data_size;
-    uint8_t data[512];
-};
-
 // DPE commands
 
 #define DPE_MAGIC    0x44504543 // "DPEC"
@@ -87,41 +87,6 @@ int caliptra_read_reg(uint32_t address, uint32_t *data)
     return 0;
 }
 
-/**
- * calculate_caliptra_checksum
- *
- * This generates a checksum based on a sum of the command and the buffer, then
- * subtracted from zero.
- *
- * @param[in] cmd The command being sent to the caliptra device
- * @param[in] buffer A pointer, if applicable, to the buffer being sent
- * @param[in] len The size of the buffer
- *
- * @return Checksum value
- */
-static uint32_t calculate_caliptra_checksum(uint32_t cmd, const uint8_t *buffer, uint32_t len)
-{
-    uint32_t i, sum = 0;
-
-    if ((buffer == NULL) && (len != 0))
-    {
-        // Don't respect bad parameters
-        return 0;
-    }
-
-    for (i = 0; i < sizeof(uint32_t); i++)
-    {
-        sum += ((uint8_t*)(&cmd))[i];
-    }
-
-    for (i = 0; i < len; i++)
-    {
-        sum += buffer[i];
-    }
-
-    return (0 - sum);
-}
-
 /**
  * caliptra_read_status
  *
@@ -547,9 +512,7 @@ static inline int check_command_response(const uint8_t *buffer, const size_t res
     }
     struct caliptra_resp_header *resp_hdr = (struct caliptra_resp_header*)buffer;
 
-    uint32_t calc_checksum = calculate_caliptra_checksum(0, buffer + sizeof(uint32_t), response_size - sizeof(uint32_t));
-
-    bool checksum_valid = !(resp_hdr->chksum - calc_checksum);
+    bool checksum_valid = caliptra_verify_resp_checksum(buffer, response_size);
     bool fips_approved  = (resp_hdr->fips_status == FIPS_STATUS_APPROVED);
 
     if (checksum_valid == false) {
@@ -712,9 +675,7 @@ static int pack_and_execute_command(struct parcel *parcel, bool async)
     };
 
     // Calculate and populate the checksum field
-    // Clear the checksum field before calculating
-    *((caliptra_checksum*)tx_buf.data) = 0x0;
-    *((caliptra_checksum*)tx_buf.data) = calculate_caliptra_checksum(parcel->command, tx_buf.data, tx_buf.len);
+    caliptra_populate_req_checksum(parcel->command, parcel->tx_buffer, tx_buf.len);
 
     return caliptra_mailbox_execute(parcel->command, &tx_buf, &rx_buf, async);
 }
@@ -786,7 +747,7 @@ int caliptra_complete()
 int caliptra_upload_fw_start_req(uint32_t fw_size_in_bytes)
 {
     // Mailbox send start
-    int status = caliptra_mailbox_send_start(OP_CALIPTRA_FW_LOAD, fw_size_in_bytes);
+    int status = caliptra_mailbox_send_start(CALIPTRA_CMD_FIRMWARE_LOAD, fw_size_in_bytes);
     if (status) {
         return status;
     }
@@ -859,7 +820,7 @@ int caliptra_upload_fw(const struct caliptra_buffer *fw_buffer, bool async)
     if (fw_buffer == NULL)
         return INVALID_PARAMS;
 
-    return caliptra_mailbox_execute(OP_CALIPTRA_FW_LOAD, fw_buffer, NULL, async);
+    return caliptra_mailbox_execute(CALIPTRA_CMD_FIRMWARE_LOAD, fw_buffer, NULL, async);
 }
 
 // Generic info for all command wrapper functions below
@@ -885,7 +846,7 @@ int caliptra_get_idev_cert(struct caliptra_get_idev_cert_req *req, struct calipt
         return INVALID_PARAMS;
     }
 
-    CREATE_PARCEL(p, OP_GET_IDEV_CERT, req, resp);
+    CREATE_PARCEL(p, CALIPTRA_CMD_GET_IDEV_CERT, req, resp);
 
     return pack_and_execute_command(&p, async);
 }
@@ -900,7 +861,7 @@ int caliptra_get_idev_info(struct caliptra_get_idev_info_resp *resp, bool async)
 
     caliptra_checksum checksum = 0;
 
-    CREATE_PARCEL(p, OP_GET_IDEV_INFO, &checksum, resp);
+    CREATE_PARCEL(p, CALIPTRA_CMD_GET_IDEV_INFO, &checksum, resp);
 
     return pack_and_execute_command(&p, async);
 }
@@ -915,7 +876,7 @@ int caliptra_populate_idev_cert(struct caliptra_populate_idev_cert_req *req, boo
 
     struct caliptra_resp_header resp_hdr = {};
 
-    CREATE_PARCEL(p, OP_POPULATE_IDEV_CERT, req, &resp_hdr);
+    CREATE_PARCEL(p, CALIPTRA_CMD_POPULATE_IDEV_CERT, req, &resp_hdr);
 
     return pack_and_execute_command(&p, async);
 }
@@ -930,7 +891,7 @@ int caliptra_get_ldev_cert(struct caliptra_get_ldev_cert_resp *resp, bool async)
 
     caliptra_checksum checksum = 0;
 
-    CREATE_PARCEL(p, OP_GET_LDEV_CERT, &checksum, resp);
+    CREATE_PARCEL(p, CALIPTRA_CMD_GET_LDEV_CERT, &checksum, resp);
 
     return pack_and_execute_command(&p, async);
 }
@@ -945,7 +906,7 @@ int caliptra_get_fmc_alias_cert(struct caliptra_get_fmc_alias_cert_resp *resp, b
 
     caliptra_checksum checksum = 0;
 
-    CREATE_PARCEL(p, OP_GET_FMC_ALIAS_CERT, &checksum, resp);
+    CREATE_PARCEL(p, CALIPTRA_CMD_GET_FMC_ALIAS_CERT, &checksum, resp);
 
     return pack_and_execute_command(&p, async);
 }
@@ -960,7 +921,7 @@ int caliptra_get_rt_alias_cert(struct caliptra_get_rt_alias_cert_resp *resp, boo
 
     caliptra_checksum checksum = 0;
 
-    CREATE_PARCEL(p, OP_GET_RT_ALIAS_CERT, &checksum, resp);
+    CREATE_PARCEL(p, CALIPTRA_CMD_GET_RT_ALIAS_CERT, &checksum, resp);
 
     return pack_and_execute_command(&p, async);
 }
@@ -975,7 +936,7 @@ int caliptra_ecdsa384_verify(struct caliptra_ecdsa_verify_req *req, bool async)
 
     struct caliptra_resp_header resp_hdr = {};
 
-    CREATE_PARCEL(p, OP_ECDSA384_VERIFY, req, &resp_hdr);
+    CREATE_PARCEL(p, CALIPTRA_CMD_ECDSA384_VERIFY, req, &resp_hdr);
 
     return pack_and_execute_command(&p, async);
 }
@@ -990,7 +951,7 @@ int caliptra_lms_verify(struct caliptra_lms_verify_req *req, bool async)
 
     struct caliptra_resp_header resp_hdr = {};
 
-    CREATE_PARCEL(p, OP_LMS_VERIFY, req, &resp_hdr);
+    CREATE_PARCEL(p, CALIPTRA_CMD_LMS_VERIFY, req, &resp_hdr);
 
     return pack_and_execute_command(&p, async);
 }
@@ -1003,7 +964,7 @@ int caliptra_stash_measurement(struct caliptra_stash_measurement_req *req, struc
         return INVALID_PARAMS;
     }
 
-    CREATE_PARCEL(p, OP_STASH_MEASUREMENT, req, resp);
+    CREATE_PARCEL(p, CALIPTRA_CMD_STASH_MEASUREMENT, req, resp);
 
     return pack_and_execute_command(&p, async);
 }
@@ -1022,7 +983,7 @@ int caliptra_invoke_dpe_command(struct caliptra_invoke_dpe_req *req, struct cali
     uint32_t actual_bytes = sizeof(caliptra_checksum) + sizeof(uint32_t) + req->data_size;
 
     struct parcel p = {
-        .command   = OP_INVOKE_DPE_COMMAND,
+        .command   = CALIPTRA_CMD_INVOKE_DPE,
         .tx_buffer = (uint8_t*)req,
         .tx_bytes  = actual_bytes,
         .rx_buffer = (uint8_t*)resp,
@@ -1038,7 +999,7 @@ int caliptra_disable_attestation(bool async)
     struct caliptra_resp_header resp_hdr = {};
     caliptra_checksum checksum = 0;
 
-    CREATE_PARCEL(p, OP_DISABLE_ATTESTATION, &checksum, &resp_hdr);
+    CREATE_PARCEL(p, CALIPTRA_CMD_DISABLE_ATTESTATION, &checksum, &resp_hdr);
 
     return pack_and_execute_command(&p, async);
 }
@@ -1053,7 +1014,7 @@ int caliptra_fw_info(struct caliptra_fw_info_resp *resp, bool async)
 
     caliptra_checksum checksum = 0;
 
-    CREATE_PARCEL(p, OP_FW_INFO, &checksum, resp);
+    CREATE_PARCEL(p, CALIPTRA_CMD_FW_INFO, &checksum, resp);
 
     return pack_and_execute_command(&p, async);
 }
@@ -1068,7 +1029,7 @@ int caliptra_dpe_tag_tci(struct caliptra_dpe_tag_tci_req *req, bool async)
 
     struct caliptra_resp_header resp_hdr = {};
 
-    CREATE_PARCEL(p, OP_DPE_TAG_TCI, req, &resp_hdr);
+    CREATE_PARCEL(p, CALIPTRA_CMD_DPE_TAG_TCI, req, &resp_hdr);
 
     return pack_and_execute_command(&p, async);
 }
@@ -1081,7 +1042,7 @@ int caliptra_dpe_get_tagged_tci(struct caliptra_get_tagged_tci_req *req, struct
         return INVALID_PARAMS;
     }
 
-    CREATE_PARCEL(p, OP_DPE_GET_TAGGED_TCI, req, resp);
+    CREATE_PARCEL(p, CALIPTRA_CMD_DPE_GET_TAGGED_TCI, req, resp);
 
     return pack_and_execute_command(&p, async);
 }
@@ -1096,7 +1057,7 @@ int caliptra_increment_pcr_reset_counter(struct caliptra_increment_pcr_reset_cou
 
     struct caliptra_resp_header resp_hdr = {};
 
-    CREATE_PARCEL(p, OP_INCREMENT_PCR_RESET_COUNTER, req, &resp_hdr);
+    CREATE_PARCEL(p, CALIPTRA_CMD_INCREMENT_PCR_RESET_COUNTER, req, &resp_hdr);
 
     return pack_and_execute_command(&p, async);
 }
@@ -1109,7 +1070,7 @@ int caliptra_quote_pcrs(struct caliptra_quote_pcrs_req *req, struct caliptra_quo
         return INVALID_PARAMS;
     }
 
-    CREATE_PARCEL(p, OP_QUOTE_PCRS, req, resp);
+    CREATE_PARCEL(p, CALIPTRA_CMD_QUOTE_PCRS, req, resp);
 
     return pack_and_execute_command(&p, async);
 }
@@ -1124,7 +1085,7 @@ int caliptra_extend_pcr(struct caliptra_extend_pcr_req *req, bool async)
 
     struct caliptra_resp_header resp_hdr = {};
 
-    CREATE_PARCEL(p, OP_EXTEND_PCR, req, &resp_hdr);
+    CREATE_PARCEL(p, CALIPTRA_CMD_EXTEND_PCR, req, &resp_hdr);
 
     return pack_and_execute_command(&p, async);
 }
@@ -1139,7 +1100,7 @@ int caliptra_add_subject_alt_name(struct caliptra_add_subject_alt_name_req *req,
 
     struct caliptra_resp_header resp_hdr = {};
 
-    CREATE_PARCEL(p, OP_ADD_SUBJECT_ALT_NAME, req, &resp_hdr);
+    CREATE_PARCEL(p, CALIPTRA_CMD_ADD_SUBJECT_ALT_NAME, req, &resp_hdr);
 
     return pack_and_execute_command(&p, async);
 }
@@ -1152,7 +1113,7 @@ int caliptra_certify_key_extended(struct caliptra_certify_key_extended_req *req,
         return INVALID_PARAMS;
     }
 
-    CREATE_PARCEL(p, OP_CERTIFY_KEY_EXTENDED, req, resp);
+    CREATE_PARCEL(p, CALIPTRA_CMD_CERTIFY_KEY_EXTENDED, req, resp);
 
     return pack_and_execute_command(&p, async);
 }
@@ -1167,7 +1128,7 @@ int caliptra_fips_version(struct caliptra_fips_version_resp *resp, bool async)
 
     caliptra_checksum checksum = 0;
 
-    CREATE_PARCEL(p, OP_FIPS_VERSION, &checksum, resp);
+    CREATE_PARCEL(p, CALIPTRA_CMD_VERSION, &checksum, resp);
 
     return pack_and_execute_command(&p, async);
 }
@@ -1182,7 +1143,7 @@ int caliptra_get_idev_csr(struct caliptra_get_idev_csr_resp *resp, bool async)
 
     caliptra_checksum checksum = 0;
 
-    CREATE_PARCEL(p, OP_GET_IDEV_CSR, &checksum, resp);
+    CREATE_PARCEL(p, CALIPTRA_CMD_GET_IDEV_CSR, &checksum, resp);
 
     return pack_and_execute_command(&p, async);
 }
@@ -1193,7 +1154,7 @@ int caliptra_self_test_start(bool async)
     struct caliptra_resp_header resp_hdr = {};
     caliptra_checksum checksum = 0;
 
-    CREATE_PARCEL(p, OP_SELF_TEST_START, &checksum, &resp_hdr);
+    CREATE_PARCEL(p, CALIPTRA_CMD_SELF_TEST_START, &checksum, &resp_hdr);
 
     return pack_and_execute_command(&p, async);
 }
@@ -1204,7 +1165,7 @@ int caliptra_self_test_get_results(bool async)
     struct caliptra_resp_header resp_hdr = {};
     caliptra_checksum checksum = 0;
 
-    CREATE_PARCEL(p, OP_SELF_TEST_GET_RESULTS, &checksum, &resp_hdr);
+    CREATE_PARCEL(p, CALIPTRA_CMD_SELF_TEST_GET_RESULTS, &checksum, &resp_hdr);
 
     return pack_and_execute_command(&p, async);
 }
@@ -1215,7 +1176,7 @@ int caliptra_shutdown(bool async)
     struct caliptra_resp_header resp_hdr = {};
     caliptra_checksum checksum = 0;
 
-    CREATE_PARCEL(p, OP_SHUTDOWN, &checksum, &resp_hdr);
+    CREATE_PARCEL(p, CALIPTRA_CMD_SHUTDOWN, &checksum, &resp_hdr);
 
     return pack_and_execute_command(&p, async);
 }
@@ -1230,7 +1191,72 @@ int caliptra_capabilities(struct caliptra_capabilities_resp *resp, bool async)
 
     caliptra_checksum checksum = 0;
 
-    CREATE_PARCEL(p, OP_CAPABILITIES, &checksum, resp);
+    CREATE_PARCEL(p, CALIPTRA_CMD_CAPABILITIES, &checksum, resp);
+
+    return pack_and_execute_command(&p, async);
+}
+
+// Set auth manifest
+int caliptra_set_auth_manifest(struct caliptra_set_auth_manifest_req *req, bool async)
+{
+    if (!req || req->manifest_size > CALIPTRA_SET_AUTH_MANIFEST_REQ_MAX_MAN_SIZE)
+    {
+        return INVALID_PARAMS;
+    }
+
+    struct caliptra_resp_header resp_hdr = {};
+
+    // Only send the used portion of the manifest buffer
+    uint32_t actual_bytes = sizeof(caliptra_checksum) + sizeof(uint32_t) + req->manifest_size;
+
+    struct parcel p = {
+        .command   = CALIPTRA_CMD_SET_AUTH_MANIFEST,
+        .tx_buffer = (uint8_t*)req,
+        .tx_bytes  = actual_bytes,
+        .rx_buffer = (uint8_t*)&resp_hdr,
+        .rx_bytes  = sizeof(resp_hdr),
+    };
+
+    return pack_and_execute_command(&p, async);
+}
+
+// Authorize and stash
+int caliptra_authorize_and_stash(struct caliptra_authorize_and_stash_req *req, struct caliptra_authorize_and_stash_resp *resp, bool async)
+{
+    if (!req || !resp)
+    {
+        return INVALID_PARAMS;
+    }
+
+    CREATE_PARCEL(p, CALIPTRA_CMD_AUTHORIZE_AND_STASH, req, resp);
+
+    return pack_and_execute_command(&p, async);
+}
+
+// Get cert chain
+int caliptra_get_cert_chain(struct caliptra_get_cert_chain_req *req, struct caliptra_get_cert_chain_resp *resp, bool async)
+{
+    if (!req || !resp)
+    {
+        return INVALID_PARAMS;
+    }
+
+    CREATE_PARCEL(p, CALIPTRA_CMD_GET_CERT_CHAIN, req, resp);
+
+    return pack_and_execute_command(&p, async);
+}
+
+// Get FMC alias CSR
+int caliptra_get_fmc_alias_csr(struct caliptra_get_fmc_alias_csr_resp *resp, bool async)
+{
+    if (!resp)
+    {
+        return INVALID_PARAMS;
+    }
+
+    caliptra_checksum checksum = 0;
+
+    CREATE_PARCEL(p, CALIPTRA_CMD_GET_FMC_ALIAS_CSR, &checksum, resp);
 
     return pack_and_execute_command(&p, async);
 }
@@ -6,6 +6,7 @@
 
 #include <caliptra_top_reg.h>
 #include "caliptra_if.h"
+#include "caliptra_mbox_types.h"
 
 #define CALIPTRA_MAILBOX_MAX_SIZE (128u * 1024u)
 
@@ -25,41 +26,12 @@ enum caliptra_mailbox_fsm_states {
     CALIPTRA_MBOX_STATUS_FSM_EXECUTE_UC     = 6,
 };
 
-enum mailbox_command {
-    OP_CALIPTRA_FW_LOAD            = 0x46574C44, // "FWLD"
-    OP_GET_IDEV_CERT               = 0x49444543, // "IDEC"
-    OP_GET_IDEV_INFO               = 0x49444549, // "IDEI"
-    OP_POPULATE_IDEV_CERT          = 0x49444550, // "IDEP"
-    OP_GET_LDEV_CERT               = 0x4C444556, // "LDEV"
-    OP_GET_FMC_ALIAS_CERT          = 0x43455246, // "CERF"
-    OP_GET_RT_ALIAS_CERT           = 0x43455252, // "CERR"
-    OP_ECDSA384_VERIFY             = 0x53494756, // "SIGV"
-    OP_LMS_VERIFY                  = 0x4C4D5356, // "LMSV"
-    OP_STASH_MEASUREMENT           = 0x4D454153, // "MEAS"
-    OP_INVOKE_DPE_COMMAND          = 0x44504543, // "DPEC"
-    OP_DISABLE_ATTESTATION         = 0x4453424C, // "DSBL"
-    OP_FW_INFO                     = 0x494E464F, // "INFO"
-    OP_DPE_TAG_TCI                 = 0x54514754, // "TAGT"
-    OP_DPE_GET_TAGGED_TCI          = 0x47544744, // "GTGD"
-    OP_INCREMENT_PCR_RESET_COUNTER = 0x50435252, // "PCRR"
-    OP_QUOTE_PCRS                  = 0x50435251, // "PCRQ"
-    OP_EXTEND_PCR                  = 0x50435245, // "PCRE"
-    OP_ADD_SUBJECT_ALT_NAME        = 0x414C544E, // "ALTN"
-    OP_CERTIFY_KEY_EXTENDED        = 0x434B4558, // "CKEX"
-    OP_FIPS_VERSION                = 0x46505652, // "FPVR"
-    OP_SELF_TEST_START             = 0x46504C54, // "FPST"
-    OP_SELF_TEST_GET_RESULTS       = 0x46504C67, // "FPGR"
-    OP_SHUTDOWN                    = 0x46505344, // "FPSD"
-    OP_CAPABILITIES                = 0x43415053, // "CAPS"
-    OP_GET_IDEV_CSR                = 0x49444352, // "IDCR"
-};
-
 struct parcel {
-    enum mailbox_command  command;
-    uint8_t              *tx_buffer;
-    size_t                tx_bytes;
-    uint8_t              *rx_buffer;
-    size_t                rx_bytes;
+    enum caliptra_command_id  command;
+    uint8_t                  *tx_buffer;
+    size_t                    tx_bytes;
+    uint8_t                  *rx_buffer;
+    size_t                    rx_bytes;
 };
 
 enum mailbox_results {
@@ -105,17 +105,42 @@ fn file_check_contents(dest_file: &Path, expected_contents: &str) -> Result<(),
     Ok(())
 }
 
+/// Removes `flag` from `args`, returning whether it was present.
+fn take_flag(args: &mut Vec<String>, flag: &str) -> bool {
+    let Some(i) = args.iter().position(|arg| arg == flag) else {
+        return false;
+    };
+    args.remove(i);
+    true
+}
+
+/// Removes `flag` and the argument following it from `args`, returning the
+/// argument.
+fn take_flag_value(args: &mut Vec<String>, flag: &str) -> Result<Option<String>, Box<dyn Error>> {
+    let Some(i) = args.iter().position(|arg| arg == flag) else {
+        return Ok(None);
+    };
+    if i + 1 >= args.len() {
+        Err(format!("{flag} requires a value"))?;
+    }
+    let value = args.remove(i + 1);
+    args.remove(i);
+    Ok(Some(value))
+}
+
 fn real_main() -> Result<(), Box<dyn Error>> {
     let mut args: Vec<String> = std::env::args().collect();
-    let file_action = if args.get(1).map(String::as_str) == Some("--check") {
-        args.remove(1);
+    let file_action = if take_flag(&mut args, "--check") {
         file_check_contents
     } else {
         write_file
     };
+    let c_header_dir = take_flag_value(&mut args, "--c-header-dir")?;
+    let svd_file = take_flag_value(&mut args, "--svd")?;
+    let emulator_dir = take_flag_value(&mut args, "--emulator-dir")?;
 
     if args.len() < 4 {
-        Err("Usage: codegen [--check] <caliptra_rtl_dir> <extra_rdl_dir> <dest_dir>")?;
+        Err("Usage: codegen [--check] [--c-header-dir <dir>] [--svd <file>] [--emulator-dir <dir>] <caliptra_rtl_dir> <extra_rdl_dir> <dest_dir>")?;
     }
 
     let rtl_dir = Path::new(&args[1]);
@@ -275,6 +300,37 @@ fn real_main() -> Result<(), Box<dyn Error>> {
         .collect();
     ureg_schema::filter_unused_types(&mut all_blocks);
 
+    if let Some(c_header_dir) = &c_header_dir {
+        for block in validated_blocks.iter() {
+            let dest_file = Path::new(c_header_dir).join(format!("{}.h", block.block().name));
+            file_action(
+                &dest_file,
+                &(header.clone() + &ureg_codegen::generate_c_header(block)),
+            )?;
+        }
+    }
+    if let Some(svd_file) = &svd_file {
+        let svd = ureg_codegen::generate_svd("caliptra", validated_blocks.iter());
+        // The XML declaration must come first, so the header goes after it
+        let (xml_decl, svd) = svd.split_once('\n').unwrap();
+        let xml_header = header.replacen("/*", "<!--", 1).replacen("*/", "-->", 1);
+        file_action(
+            Path::new(svd_file),
+            &format!("{xml_decl}\n{xml_header}{svd}"),
+        )?;
+    }
+
+    if let Some(emulator_dir) = &emulator_dir {
+        for block in validated_blocks.iter() {
+            let dest_file = Path::new(emulator_dir).join(format!("{}.rs", block.block().name));
+            let tokens = ureg_codegen::generate_emulator_code(block);
+            file_action(
+                &dest_file,
+                &rustfmt(&(header.clone() + &tokens.to_string()))?,
+            )?;
+        }
+    }
+
     for block in validated_blocks {
         let module_ident = format_ident!("{}", block.block().name);
         let dest_file = dest_dir.join(format!("{}.rs", block.block().name));
@@ -0,0 +1,119 @@
+// Licensed under the Apache-2.0 license
+
+use std::cell::RefCell;
+
+use caliptra_api::SocManager;
+use caliptra_builder::{
+    firmware::{APP_WITH_UART, FMC_WITH_UART},
+    ImageOptions,
+};
+use caliptra_common::checksum::calc_checksum;
+use caliptra_common::mailbox_api::CommandId;
+use caliptra_hw_model::{BootParams, DefaultHwModel, HwModel, InitParams, ModelError};
+use caliptra_runtime::validate_command;
+use zerocopy::IntoBytes;
+
+/// Commands the fuzzer can select, indexed by the first input byte
+///
+/// FIRMWARE_LOAD is covered by the image verification fuzzer, and SHUTDOWN
+/// takes no request data and leaves the firmware unable to serve anything
+/// after it.
+pub const COMMANDS: &[CommandId] = &[
+    CommandId::GET_IDEV_CERT,
+    CommandId::GET_IDEV_INFO,
+    CommandId::POPULATE_IDEV_CERT,
+    CommandId::GET_LDEV_CERT,
+    CommandId::GET_FMC_ALIAS_CERT,
+    CommandId::GET_RT_ALIAS_CERT,
+    CommandId::ECDSA384_VERIFY,
+    CommandId::LMS_VERIFY,
+    CommandId::STASH_MEASUREMENT,
+    CommandId::INVOKE_DPE,
+    CommandId::DISABLE_ATTESTATION,
+    CommandId::FW_INFO,
+    CommandId::DPE_TAG_TCI,
+    CommandId::DPE_GET_TAGGED_TCI,
+    CommandId::INCREMENT_PCR_RESET_COUNTER,
+    CommandId::QUOTE_PCRS,
+    CommandId::EXTEND_PCR,
+    CommandId::ADD_SUBJECT_ALT_NAME,
+    CommandId::CERTIFY_KEY_EXTENDED,
+    CommandId::VERSION,
+    CommandId::CAPABILITIES,
+    CommandId::SET_AUTH_MANIFEST,
+    CommandId::AUTHORIZE_AND_STASH,
+    CommandId::GET_IDEV_CSR,
+    CommandId::GET_CERT_CHAIN,
+    CommandId::GET_FMC_ALIAS_CSR,
+];
+
+/// Runtime booted on the software emulator, shared by every input
+///
+/// Booting ROM, FMC and runtime takes far longer than a command, so the model
+/// is kept across inputs. A crash may therefore depend on earlier inputs, such
+/// as a DISABLE_ATTESTATION that ran before it.
+thread_local! {
+    static MODEL: RefCell<Option<DefaultHwModel>> = RefCell::new(None);
+}
+
+/// Boot the production runtime image with the PL0 PAUSER the runtime tests use
+fn boot_runtime() -> DefaultHwModel {
+    let rom = caliptra_builder::rom_for_fw_integration_tests().unwrap();
+    let mut image_options = ImageOptions::default();
+    image_options.vendor_config.pl0_pauser = Some(0x1);
+    let image =
+        caliptra_builder::build_and_sign_image(&FMC_WITH_UART, &APP_WITH_UART, image_options)
+            .unwrap();
+
+    let mut model = caliptra_hw_model::new(
+        InitParams {
+            rom: &rom,
+            ..Default::default()
+        },
+        BootParams {
+            fw_image: Some(&image.to_bytes().unwrap()),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    model.step_until(|m| m.soc_ifc().cptra_flow_status().read().ready_for_fw());
+    model
+}
+
+/// Run `data` through `validate_command` like the mailbox handler does and,
+/// if it is accepted, send it to the emulated runtime
+///
+/// The request is prefixed with a valid checksum so it reaches the command
+/// parser. Every register and memory access the command handler makes lands
+/// on the emulator's peripherals, so nothing is mapped at the hardware
+/// addresses in this process.
+pub fn harness(cmd: CommandId, data: &[u8]) {
+    let cmd = u32::from(cmd);
+
+    // Requests are decoded in place, so match the mailbox packet alignment
+    let len = data.len() + 4;
+    let mut words = vec![0u32; (len + 3) / 4];
+    let payload = &mut words.as_mut_bytes()[..len];
+    payload[..4].copy_from_slice(&calc_checksum(cmd, data).to_le_bytes());
+    payload[4..].copy_from_slice(data);
+
+    if validate_command(cmd, payload).is_err() {
+        return;
+    }
+
+    MODEL.with(|model| {
+        let mut model = model.borrow_mut();
+        let model = model.get_or_insert_with(boot_runtime);
+        match model.mailbox_execute(cmd, payload) {
+            Ok(_) | Err(ModelError::MailboxCmdFailed(_)) => (),
+            Err(e) => panic!("Mailbox command 0x{cmd:08x} failed: {e}"),
+        }
+
+        // A request that passed validation must never take down the runtime
+        let fatal = model.soc_ifc().cptra_fw_error_fatal().read();
+        assert_eq!(
+            fatal, 0,
+            "Fatal error 0x{fatal:08x} from command 0x{cmd:08x}"
+        );
+    });
+}
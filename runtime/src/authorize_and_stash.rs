@@ -20,16 +20,16 @@ use caliptra_auth_man_types::{
     AuthManifestImageMetadata, AuthManifestImageMetadataCollection, AuthManifestPreamble,
     ImageMetadataFlags, AUTH_MANIFEST_MARKER,
 };
-use caliptra_cfi_derive_git::cfi_impl_fn;
-use caliptra_cfi_lib_git::{cfi_assert, cfi_assert_eq, cfi_launder};
+use caliptra_cfi_derive::cfi_impl_fn;
+use caliptra_cfi_lib::{cfi_assert, cfi_assert_eq, cfi_assert_eq_slice, cfi_eq_slice, cfi_launder};
 use caliptra_common::mailbox_api::{
     AuthAndStashFlags, AuthorizeAndStashReq, AuthorizeAndStashResp, ImageHashSource, MailboxResp,
     MailboxRespHeader, SetAuthManifestReq,
 };
 use caliptra_drivers::{
-    pcr_log::PCR_ID_STASH_MEASUREMENT, Array4x12, Array4xN, AuthManifestImageMetadataList,
-    CaliptraError, CaliptraResult, Ecc384, Ecc384PubKey, Ecc384Signature, HashValue, Lms,
-    PersistentData, RomVerifyConfig, Sha256, Sha384, SocIfc,
+    pcr_log::PCR_ID_STASH_MEASUREMENT, Array4xN, AuthManifestImageMetadataList, CaliptraError,
+    CaliptraResult, Ecc384, Ecc384PubKey, Ecc384Signature, HashValue, Lms, PersistentData,
+    RomVerifyConfig, Sha256, Sha384, SocIfc,
 };
 use caliptra_image_types::{
     ImageDigest, ImageEccPubKey, ImageEccSignature, ImageLmsPublicKey, ImageLmsSignature,
@@ -51,66 +51,64 @@ pub const IMAGE_HASH_MISMATCH: u32 = 0x8BFB95CB; // FW ID matched, but image dig
 
 pub struct AuthorizeAndStashCmd;
 impl AuthorizeAndStashCmd {
+    /// Validate the request in `cmd_args`
+    pub(crate) fn parse(cmd_args: &[u8]) -> CaliptraResult<&AuthorizeAndStashReq> {
+        let cmd = AuthorizeAndStashReq::ref_from_bytes(cmd_args)
+            .map_err(|_| CaliptraError::RUNTIME_INSUFFICIENT_MEMORY)?;
+        if ImageHashSource::from(cmd.source) != ImageHashSource::InRequest {
+            Err(CaliptraError::RUNTIME_AUTH_AND_STASH_UNSUPPORTED_IMAGE_SOURCE)?;
+        }
+        Ok(cmd)
+    }
+
     #[cfg_attr(not(feature = "no-cfi"), cfi_impl_fn)]
     #[inline(never)]
-    pub(crate) fn execute(drivers: &mut Drivers, cmd_args: &[u8]) -> CaliptraResult<MailboxResp> {
-        if let Ok(cmd) = AuthorizeAndStashReq::ref_from_bytes(cmd_args) {
-            if ImageHashSource::from(cmd.source) != ImageHashSource::InRequest {
-                Err(CaliptraError::RUNTIME_AUTH_AND_STASH_UNSUPPORTED_IMAGE_SOURCE)?;
-            }
-
-            // Check if firmware id is present in the image metadata entry collection.
-            let persistent_data = drivers.persistent_data.get();
-            let auth_manifest_image_metadata_col =
-                &persistent_data.auth_manifest_image_metadata_col;
+    pub(crate) fn execute(
+        drivers: &mut Drivers,
+        cmd: &AuthorizeAndStashReq,
+    ) -> CaliptraResult<MailboxResp> {
+        // Check if firmware id is present in the image metadata entry collection.
+        let persistent_data = drivers.persistent_data.get();
+        let auth_manifest_image_metadata_col = &persistent_data.auth_manifest_image_metadata_col;
 
-            let cmd_fw_id = u32::from_le_bytes(cmd.fw_id);
-            let auth_result = if let Some(metadata_entry) =
-                Self::find_metadata_entry(auth_manifest_image_metadata_col, cmd_fw_id)
-            {
-                // If 'ignore_auth_check' is set, then skip the image digest comparison and authorize the image.
-                let flags = ImageMetadataFlags(metadata_entry.flags);
-                if flags.ignore_auth_check() {
-                    cfi_assert!(cfi_launder(flags.ignore_auth_check()));
-                    IMAGE_AUTHORIZED
-                } else if cfi_launder(metadata_entry.digest) == cmd.measurement {
-                    caliptra_cfi_lib_git::cfi_assert_eq_12_words(
-                        &Array4x12::from(metadata_entry.digest).0,
-                        &Array4x12::from(cmd.measurement).0,
-                    );
-                    IMAGE_AUTHORIZED
-                } else {
-                    IMAGE_HASH_MISMATCH
-                }
+        let cmd_fw_id = u32::from_le_bytes(cmd.fw_id);
+        let auth_result = if let Some(metadata_entry) =
+            Self::find_metadata_entry(auth_manifest_image_metadata_col, cmd_fw_id)
+        {
+            // If 'ignore_auth_check' is set, then skip the image digest comparison and authorize the image.
+            let flags = ImageMetadataFlags(metadata_entry.flags);
+            if flags.ignore_auth_check() {
+                cfi_assert!(cfi_launder(flags.ignore_auth_check()));
+                IMAGE_AUTHORIZED
+            } else if cfi_eq_slice(&metadata_entry.digest, &cmd.measurement) {
+                cfi_assert_eq_slice(&metadata_entry.digest, &cmd.measurement);
+                IMAGE_AUTHORIZED
             } else {
-                IMAGE_NOT_AUTHORIZED
-            };
+                IMAGE_HASH_MISMATCH
+            }
+        } else {
+            IMAGE_NOT_AUTHORIZED
+        };
 
-            // Stash the measurement if the image is authorized.
-            if auth_result == IMAGE_AUTHORIZED {
-                let flags: AuthAndStashFlags = cmd.flags.into();
-                if !flags.contains(AuthAndStashFlags::SKIP_STASH) {
-                    let dpe_result = StashMeasurementCmd::stash_measurement(
-                        drivers,
-                        &cmd.fw_id,
-                        &cmd.measurement,
-                    )?;
-                    if dpe_result != DpeErrorCode::NoError {
-                        drivers
-                            .soc_ifc
-                            .set_fw_extended_error(dpe_result.get_error_code());
-                        Err(CaliptraError::RUNTIME_AUTH_AND_STASH_MEASUREMENT_DPE_ERROR)?;
-                    }
+        // Stash the measurement if the image is authorized.
+        if auth_result == IMAGE_AUTHORIZED {
+            let flags: AuthAndStashFlags = cmd.flags.into();
+            if !flags.contains(AuthAndStashFlags::SKIP_STASH) {
+                let dpe_result =
+                    StashMeasurementCmd::stash_measurement(drivers, &cmd.fw_id, &cmd.measurement)?;
+                if dpe_result != DpeErrorCode::NoError {
+                    drivers
+                        .soc_ifc
+                        .set_fw_extended_error(dpe_result.get_error_code());
+                    Err(CaliptraError::RUNTIME_AUTH_AND_STASH_MEASUREMENT_DPE_ERROR)?;
                 }
             }
-
-            Ok(MailboxResp::AuthorizeAndStash(AuthorizeAndStashResp {
-                hdr: MailboxRespHeader::default(),
-                auth_req_result: auth_result,
-            }))
-        } else {
-            Err(CaliptraError::RUNTIME_INSUFFICIENT_MEMORY)
         }
+
+        Ok(MailboxResp::AuthorizeAndStash(AuthorizeAndStashResp {
+            hdr: MailboxRespHeader::default(),
+            auth_req_result: auth_result,
+        }))
     }
 
     /// Search for a metadata entry in the sorted `AuthManifestImageMetadataCollection` that matches the firmware ID.
@@ -34,11 +34,24 @@ use crate::{
 
 pub struct CertifyKeyExtendedCmd;
 impl CertifyKeyExtendedCmd {
-    #[inline(never)]
-    pub(crate) fn execute(drivers: &mut Drivers, cmd_args: &[u8]) -> CaliptraResult<MailboxResp> {
+    /// Validate the request in `cmd_args` and decode the DPE CertifyKey
+    /// command embedded in it
+    pub(crate) fn parse(
+        cmd_args: &[u8],
+    ) -> CaliptraResult<(&CertifyKeyExtendedReq, &CertifyKeyCmd)> {
         let cmd = CertifyKeyExtendedReq::ref_from_bytes(cmd_args)
             .map_err(|_| CaliptraError::RUNTIME_INSUFFICIENT_MEMORY)?;
+        let certify_key_cmd = CertifyKeyCmd::ref_from_bytes(&cmd.certify_key_req[..])
+            .map_err(|_| CaliptraError::RUNTIME_DPE_COMMAND_DESERIALIZATION_FAILED)?;
+        Ok((cmd, certify_key_cmd))
+    }
 
+    #[inline(never)]
+    pub(crate) fn execute(
+        drivers: &mut Drivers,
+        cmd: &CertifyKeyExtendedReq,
+        certify_key_cmd: &CertifyKeyCmd,
+    ) -> CaliptraResult<MailboxResp> {
         match drivers.caller_privilege_level() {
             // CERTIFY_KEY_EXTENDED MUST only be called from PL0
             PauserPrivileges::PL0 => (),
@@ -85,8 +98,6 @@ impl CertifyKeyExtendedCmd {
         };
 
         let mut dpe = &mut pdata.dpe;
-        let certify_key_cmd = CertifyKeyCmd::ref_from_bytes(&cmd.certify_key_req[..])
-            .map_err(|_| CaliptraError::RUNTIME_DPE_COMMAND_DESERIALIZATION_FAILED)?;
         let locality = drivers.mbox.user();
         let resp = certify_key_cmd.execute(dpe, &mut env, locality);
 
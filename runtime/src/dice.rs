@@ -24,43 +24,81 @@ use caliptra_drivers::{
     PersistentData,
 };
 use caliptra_x509::{Ecdsa384CertBuilder, Ecdsa384Signature};
-use zerocopy::IntoBytes;
+use memoffset::offset_of;
 
 pub struct IDevIdCertCmd;
 impl IDevIdCertCmd {
-    #[inline(never)]
-    pub(crate) fn execute(cmd_args: &[u8]) -> CaliptraResult<MailboxResp> {
-        if cmd_args.len() <= core::mem::size_of::<GetIdevCertReq>() {
-            let mut cmd = GetIdevCertReq::default();
-            cmd.as_mut_bytes()[..cmd_args.len()].copy_from_slice(cmd_args);
-
-            // Validate tbs
-            if cmd.tbs_size as usize > cmd.tbs.len() {
-                return Err(CaliptraError::RUNTIME_MAILBOX_INVALID_PARAMS);
-            }
-
-            let sig = Ecdsa384Signature {
-                r: cmd.signature_r,
-                s: cmd.signature_s,
-            };
-
-            let Some(builder) = Ecdsa384CertBuilder::new(&cmd.tbs[..cmd.tbs_size as usize], &sig) else {
-                return Err(CaliptraError::RUNTIME_GET_IDEVID_CERT_FAILED);
-            };
-
-            let mut cert = [0; GetIdevCertResp::DATA_MAX_SIZE];
-            let Some(cert_size) = builder.build(&mut cert) else {
-                return Err(CaliptraError::RUNTIME_GET_IDEVID_CERT_FAILED);
-            };
-
-            Ok(MailboxResp::GetIdevCert(GetIdevCertResp {
-                hdr: MailboxRespHeader::default(),
-                cert_size: cert_size as u32,
-                cert,
-            }))
-        } else {
-            Err(CaliptraError::RUNTIME_INSUFFICIENT_MEMORY)
+    /// Validate the variable length request in `cmd_args` and borrow its
+    /// signature and TBS
+    pub(crate) fn parse(cmd_args: &[u8]) -> CaliptraResult<(&[u8; 48], &[u8; 48], &[u8])> {
+        if cmd_args.len() > core::mem::size_of::<GetIdevCertReq>() {
+            return Err(CaliptraError::RUNTIME_INSUFFICIENT_MEMORY);
+        }
+
+        let err = CaliptraError::RUNTIME_MAILBOX_INVALID_PARAMS;
+        // Validate tbs
+        let tbs_size: usize = {
+            let offset = offset_of!(GetIdevCertReq, tbs_size);
+            u32::from_le_bytes(
+                cmd_args
+                    .get(offset..offset + 4)
+                    .ok_or(err)?
+                    .try_into()
+                    .map_err(|_| err)?,
+            ) as usize
+        };
+        if tbs_size > GetIdevCertReq::DATA_MAX_SIZE {
+            return Err(err);
         }
+        let signature_r = {
+            let offset = offset_of!(GetIdevCertReq, signature_r);
+            cmd_args
+                .get(offset..offset + 48)
+                .ok_or(err)?
+                .try_into()
+                .map_err(|_| err)?
+        };
+        let signature_s = {
+            let offset = offset_of!(GetIdevCertReq, signature_s);
+            cmd_args
+                .get(offset..offset + 48)
+                .ok_or(err)?
+                .try_into()
+                .map_err(|_| err)?
+        };
+        let tbs = {
+            let offset = offset_of!(GetIdevCertReq, tbs);
+            cmd_args.get(offset..offset + tbs_size).ok_or(err)?
+        };
+
+        Ok((signature_r, signature_s, tbs))
+    }
+
+    #[inline(never)]
+    pub(crate) fn execute(
+        signature_r: &[u8; 48],
+        signature_s: &[u8; 48],
+        tbs: &[u8],
+    ) -> CaliptraResult<MailboxResp> {
+        let sig = Ecdsa384Signature {
+            r: *signature_r,
+            s: *signature_s,
+        };
+
+        let Some(builder) = Ecdsa384CertBuilder::new(tbs, &sig) else {
+            return Err(CaliptraError::RUNTIME_GET_IDEVID_CERT_FAILED);
+        };
+
+        let mut cert = [0; GetIdevCertResp::DATA_MAX_SIZE];
+        let Some(cert_size) = builder.build(&mut cert) else {
+            return Err(CaliptraError::RUNTIME_GET_IDEVID_CERT_FAILED);
+        };
+
+        Ok(MailboxResp::GetIdevCert(GetIdevCertResp {
+            hdr: MailboxRespHeader::default(),
+            cert_size: cert_size as u32,
+            cert,
+        }))
     }
 }
 
@@ -0,0 +1,348 @@
+/*++
+
+Licensed under the Apache-2.0 license.
+
+File Name:
+
+    dispatch.rs
+
+Abstract:
+
+    File contains mailbox command parsing and dispatch.
+
+--*/
+
+use crate::{
+    authorize_and_stash::AuthorizeAndStashCmd,
+    capabilities::CapabilitiesCmd,
+    certify_key_extended::CertifyKeyExtendedCmd,
+    dice::{GetFmcAliasCertCmd, GetLdevCertCmd, GetRtAliasCertCmd, IDevIdCertCmd},
+    disable::DisableAttestationCmd,
+    get_cert_chain::GetCertChainCmd,
+    get_fmc_alias_csr::GetFmcAliasCsrCmd,
+    get_idev_csr::GetIdevCsrCmd,
+    info::{FwInfoCmd, IDevIdInfoCmd},
+    invoke_dpe::InvokeDpeCmd,
+    packet::Packet,
+    pcr::{ExtendPcrCmd, GetPcrQuoteCmd, IncrementPcrResetCounterCmd},
+    populate_idev::PopulateIDevIdCertCmd,
+    set_auth_manifest::SetAuthManifestCmd,
+    stash_measurement::StashMeasurementCmd,
+    subject_alt_name::AddSubjectAltNameCmd,
+    tagging::{GetTaggedTciCmd, TagTciCmd},
+    verify::{EcdsaVerifyCmd, LmsVerifyCmd},
+    Drivers, FipsShutdownCmd, FipsVersionCmd,
+};
+#[cfg(feature = "fips_self_test")]
+use crate::{fips_self_test_cmd, SelfTestStatus};
+use caliptra_auth_man_types::AuthManifestPreamble;
+use caliptra_common::mailbox_api::{
+    AuthorizeAndStashReq, CertifyKeyExtendedReq, CommandId, EcdsaVerifyReq, ExtendPcrReq,
+    GetCertChainReq, GetTaggedTciReq, LmsVerifyReq, MailboxResp, QuotePcrsReq, StashMeasurementReq,
+    TagTciReq,
+};
+use caliptra_drivers::{CaliptraError, CaliptraResult, PcrId};
+use dpe::commands::{CertifyKeyCmd, Command};
+
+/// Mailbox request decoded by the `parse` step of its command handler
+///
+/// This is the only place that maps a `CommandId` to a handler, so the checks
+/// run by `validate_command` are exactly the ones `handle_command` applies.
+/// Every variant borrows from the request, so parsing never copies it.
+pub(crate) enum ParsedCommand<'a> {
+    GetIdevCert(&'a [u8; 48], &'a [u8; 48], &'a [u8]),
+    GetIdevInfo,
+    GetLdevCert,
+    InvokeDpe(Command<'a>),
+    EcdsaVerify(&'a EcdsaVerifyReq),
+    LmsVerify(&'a LmsVerifyReq),
+    ExtendPcr(PcrId, &'a ExtendPcrReq),
+    StashMeasurement(&'a StashMeasurementReq),
+    DisableAttestation,
+    FwInfo,
+    TagTci(&'a TagTciReq),
+    GetTaggedTci(&'a GetTaggedTciReq),
+    PopulateIdevCert(&'a [u8]),
+    GetFmcAliasCert,
+    GetRtAliasCert,
+    AddSubjectAltName(&'a [u8]),
+    CertifyKeyExtended(&'a CertifyKeyExtendedReq, &'a CertifyKeyCmd),
+    IncrementPcrResetCounter(PcrId),
+    QuotePcrs(&'a QuotePcrsReq),
+    Version,
+    Capabilities,
+    #[cfg(feature = "fips_self_test")]
+    SelfTestStart,
+    #[cfg(feature = "fips_self_test")]
+    SelfTestGetResults,
+    Shutdown,
+    SetAuthManifest(&'a AuthManifestPreamble, &'a [u8]),
+    AuthorizeAndStash(&'a AuthorizeAndStashReq),
+    GetIdevCsr,
+    GetCertChain(&'a GetCertChainReq),
+    GetFmcAliasCsr,
+}
+
+impl<'a> ParsedCommand<'a> {
+    /// Decode the request for `cmd` without touching the drivers
+    ///
+    /// # Arguments
+    ///
+    /// * `cmd` - Command ID the request was sent with
+    /// * `cmd_bytes` - Request payload, starting with the checksum
+    pub(crate) fn parse(cmd: u32, cmd_bytes: &'a [u8]) -> CaliptraResult<Self> {
+        Ok(match CommandId::from(cmd) {
+            CommandId::GET_IDEV_CERT => {
+                let (signature_r, signature_s, tbs) = IDevIdCertCmd::parse(cmd_bytes)?;
+                Self::GetIdevCert(signature_r, signature_s, tbs)
+            }
+            CommandId::GET_IDEV_INFO => Self::GetIdevInfo,
+            CommandId::GET_LDEV_CERT => Self::GetLdevCert,
+            CommandId::INVOKE_DPE => Self::InvokeDpe(InvokeDpeCmd::parse(cmd_bytes)?),
+            CommandId::ECDSA384_VERIFY => Self::EcdsaVerify(EcdsaVerifyCmd::parse(cmd_bytes)?),
+            CommandId::LMS_VERIFY => Self::LmsVerify(LmsVerifyCmd::parse(cmd_bytes)?),
+            CommandId::EXTEND_PCR => {
+                let (pcr_index, req) = ExtendPcrCmd::parse(cmd_bytes)?;
+                Self::ExtendPcr(pcr_index, req)
+            }
+            CommandId::STASH_MEASUREMENT => {
+                Self::StashMeasurement(StashMeasurementCmd::parse(cmd_bytes)?)
+            }
+            CommandId::DISABLE_ATTESTATION => Self::DisableAttestation,
+            CommandId::FW_INFO => Self::FwInfo,
+            CommandId::DPE_TAG_TCI => Self::TagTci(TagTciCmd::parse(cmd_bytes)?),
+            CommandId::DPE_GET_TAGGED_TCI => Self::GetTaggedTci(GetTaggedTciCmd::parse(cmd_bytes)?),
+            CommandId::POPULATE_IDEV_CERT => {
+                Self::PopulateIdevCert(PopulateIDevIdCertCmd::parse(cmd_bytes)?)
+            }
+            CommandId::GET_FMC_ALIAS_CERT => Self::GetFmcAliasCert,
+            CommandId::GET_RT_ALIAS_CERT => Self::GetRtAliasCert,
+            CommandId::ADD_SUBJECT_ALT_NAME => {
+                Self::AddSubjectAltName(AddSubjectAltNameCmd::parse(cmd_bytes)?)
+            }
+            CommandId::CERTIFY_KEY_EXTENDED => {
+                let (req, certify_key_cmd) = CertifyKeyExtendedCmd::parse(cmd_bytes)?;
+                Self::CertifyKeyExtended(req, certify_key_cmd)
+            }
+            CommandId::INCREMENT_PCR_RESET_COUNTER => {
+                Self::IncrementPcrResetCounter(IncrementPcrResetCounterCmd::parse(cmd_bytes)?)
+            }
+            CommandId::QUOTE_PCRS => Self::QuotePcrs(GetPcrQuoteCmd::parse(cmd_bytes)?),
+            CommandId::VERSION => Self::Version,
+            CommandId::CAPABILITIES => Self::Capabilities,
+            #[cfg(feature = "fips_self_test")]
+            CommandId::SELF_TEST_START => Self::SelfTestStart,
+            #[cfg(feature = "fips_self_test")]
+            CommandId::SELF_TEST_GET_RESULTS => Self::SelfTestGetResults,
+            CommandId::SHUTDOWN => Self::Shutdown,
+            CommandId::SET_AUTH_MANIFEST => {
+                let (preamble, manifest_buf) = SetAuthManifestCmd::parse(cmd_bytes)?;
+                Self::SetAuthManifest(preamble, manifest_buf)
+            }
+            CommandId::AUTHORIZE_AND_STASH => {
+                Self::AuthorizeAndStash(AuthorizeAndStashCmd::parse(cmd_bytes)?)
+            }
+            CommandId::GET_IDEV_CSR => {
+                GetIdevCsrCmd::parse(cmd_bytes)?;
+                Self::GetIdevCsr
+            }
+            CommandId::GET_CERT_CHAIN => Self::GetCertChain(GetCertChainCmd::parse(cmd_bytes)?),
+            CommandId::GET_FMC_ALIAS_CSR => {
+                GetFmcAliasCsrCmd::parse(cmd_bytes)?;
+                Self::GetFmcAliasCsr
+            }
+            _ => return Err(CaliptraError::RUNTIME_UNIMPLEMENTED_COMMAND),
+        })
+    }
+
+    /// Run the command handler for the decoded request
+    pub(crate) fn execute(self, drivers: &mut Drivers) -> CaliptraResult<MailboxResp> {
+        match self {
+            Self::GetIdevCert(signature_r, signature_s, tbs) => {
+                IDevIdCertCmd::execute(signature_r, signature_s, tbs)
+            }
+            Self::GetIdevInfo => IDevIdInfoCmd::execute(drivers),
+            Self::GetLdevCert => GetLdevCertCmd::execute(drivers),
+            Self::InvokeDpe(command) => InvokeDpeCmd::execute(drivers, command),
+            Self::EcdsaVerify(req) => EcdsaVerifyCmd::execute(drivers, req),
+            Self::LmsVerify(req) => LmsVerifyCmd::execute(drivers, req),
+            Self::ExtendPcr(pcr_index, req) => ExtendPcrCmd::execute(drivers, pcr_index, req),
+            Self::StashMeasurement(req) => StashMeasurementCmd::execute(drivers, req),
+            Self::DisableAttestation => DisableAttestationCmd::execute(drivers),
+            Self::FwInfo => FwInfoCmd::execute(drivers),
+            Self::TagTci(req) => TagTciCmd::execute(drivers, req),
+            Self::GetTaggedTci(req) => GetTaggedTciCmd::execute(drivers, req),
+            Self::PopulateIdevCert(cert) => PopulateIDevIdCertCmd::execute(drivers, cert),
+            Self::GetFmcAliasCert => GetFmcAliasCertCmd::execute(drivers),
+            Self::GetRtAliasCert => GetRtAliasCertCmd::execute(drivers),
+            Self::AddSubjectAltName(dmtf_device_info) => {
+                AddSubjectAltNameCmd::execute(drivers, dmtf_device_info)
+            }
+            Self::CertifyKeyExtended(req, certify_key_cmd) => {
+                CertifyKeyExtendedCmd::execute(drivers, req, certify_key_cmd)
+            }
+            Self::IncrementPcrResetCounter(pcr) => {
+                IncrementPcrResetCounterCmd::execute(drivers, pcr)
+            }
+            Self::QuotePcrs(req) => GetPcrQuoteCmd::execute(drivers, req),
+            Self::Version => {
+                FipsVersionCmd::execute(&drivers.soc_ifc).map(MailboxResp::FipsVersion)
+            }
+            Self::Capabilities => CapabilitiesCmd::execute(),
+            #[cfg(feature = "fips_self_test")]
+            Self::SelfTestStart => match drivers.self_test_status {
+                SelfTestStatus::Idle => {
+                    drivers.self_test_status =
+                        SelfTestStatus::InProgress(fips_self_test_cmd::execute);
+                    Ok(MailboxResp::default())
+                }
+                _ => Err(CaliptraError::RUNTIME_SELF_TEST_IN_PROGRESS),
+            },
+            #[cfg(feature = "fips_self_test")]
+            Self::SelfTestGetResults => match drivers.self_test_status {
+                SelfTestStatus::Done => {
+                    drivers.self_test_status = SelfTestStatus::Idle;
+                    Ok(MailboxResp::default())
+                }
+                _ => Err(CaliptraError::RUNTIME_SELF_TEST_NOT_STARTED),
+            },
+            Self::Shutdown => FipsShutdownCmd::execute(drivers),
+            Self::SetAuthManifest(preamble, manifest_buf) => {
+                SetAuthManifestCmd::execute(drivers, preamble, manifest_buf)
+            }
+            Self::AuthorizeAndStash(req) => AuthorizeAndStashCmd::execute(drivers, req),
+            Self::GetIdevCsr => GetIdevCsrCmd::execute(drivers),
+            Self::GetCertChain(req) => GetCertChainCmd::execute(drivers, req),
+            Self::GetFmcAliasCsr => GetFmcAliasCsrCmd::execute(drivers),
+        }
+    }
+}
+
+/// Run every check `handle_command` applies to a request before it touches
+/// the drivers
+///
+/// This covers the checksum, the request length and any length, index or
+/// flag fields the command handler validates in its `parse` step.
+///
+/// # Arguments
+///
+/// * `cmd` - Command ID the request was sent with
+/// * `payload_bytes` - Request payload, starting with the checksum
+///
+/// # Returns
+///
+/// * `()` - The request would be passed on to the command handler
+pub fn validate_command(cmd: u32, payload_bytes: &[u8]) -> CaliptraResult<()> {
+    // Firmware updates never read the payload through a packet
+    if CommandId::from(cmd) == CommandId::FIRMWARE_LOAD {
+        return Ok(());
+    }
+
+    Packet::verify_checksum(cmd, payload_bytes)?;
+    ParsedCommand::parse(cmd, payload_bytes).map(|_| ())
+}
+
+/// Parse a mailbox request and run its command handler
+///
+/// The checksum is expected to have been verified already, as
+/// `Packet::copy_from_mbox` does.
+///
+/// # Arguments
+///
+/// * `drivers` - Drivers the command handler runs against
+/// * `cmd` - Command ID the request was sent with
+/// * `cmd_bytes` - Request payload, starting with the checksum
+///
+/// # Returns
+///
+/// * `MailboxResp` - Response to copy back to the mailbox
+pub(crate) fn execute_command(
+    drivers: &mut Drivers,
+    cmd: u32,
+    cmd_bytes: &[u8],
+) -> CaliptraResult<MailboxResp> {
+    ParsedCommand::parse(cmd, cmd_bytes)?.execute(drivers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use caliptra_common::checksum::calc_checksum;
+    use caliptra_common::mailbox_api::{ExtendPcrReq, MailboxReqHeader};
+    use zerocopy::IntoBytes;
+
+    fn extend_pcr_req(pcr_idx: u32) -> ExtendPcrReq {
+        let mut req = ExtendPcrReq {
+            hdr: MailboxReqHeader::default(),
+            pcr_idx,
+            data: [0u8; 48],
+        };
+        req.hdr.chksum = calc_checksum(
+            CommandId::EXTEND_PCR.into(),
+            &req.as_bytes()[core::mem::size_of::<MailboxReqHeader>()..],
+        );
+        req
+    }
+
+    #[test]
+    fn test_extend_pcr() {
+        let req = extend_pcr_req(4);
+        assert_eq!(
+            validate_command(CommandId::EXTEND_PCR.into(), req.as_bytes()),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_extend_pcr_reserved() {
+        let req = extend_pcr_req(0);
+        assert_eq!(
+            validate_command(CommandId::EXTEND_PCR.into(), req.as_bytes()),
+            Err(CaliptraError::RUNTIME_PCR_RESERVED)
+        );
+    }
+
+    #[test]
+    fn test_invalid_checksum() {
+        let mut req = extend_pcr_req(4);
+        req.hdr.chksum ^= 1;
+        assert_eq!(
+            validate_command(CommandId::EXTEND_PCR.into(), req.as_bytes()),
+            Err(CaliptraError::RUNTIME_INVALID_CHECKSUM)
+        );
+    }
+
+    #[test]
+    fn test_truncated() {
+        let req = extend_pcr_req(4);
+        let mut payload = [0u8; core::mem::size_of::<ExtendPcrReq>() - 1];
+        payload.copy_from_slice(&req.as_bytes()[..payload.len()]);
+        let chksum = calc_checksum(CommandId::EXTEND_PCR.into(), &payload[4..]);
+        payload[..4].copy_from_slice(&chksum.to_le_bytes());
+        assert_eq!(
+            validate_command(CommandId::EXTEND_PCR.into(), &payload),
+            Err(CaliptraError::RUNTIME_INSUFFICIENT_MEMORY)
+        );
+    }
+
+    #[test]
+    fn test_invoke_dpe_data_past_end() {
+        // data_size claims more bytes than the request carries
+        let mut payload = [0u32; 3];
+        payload[1] = 8;
+        payload[0] = calc_checksum(CommandId::INVOKE_DPE.into(), &payload.as_bytes()[4..]);
+        assert_eq!(
+            validate_command(CommandId::INVOKE_DPE.into(), payload.as_bytes()),
+            Err(CaliptraError::RUNTIME_MAILBOX_INVALID_PARAMS)
+        );
+    }
+
+    #[test]
+    fn test_unknown_command() {
+        let payload = calc_checksum(0x1234_5678, &[]).to_le_bytes();
+        assert_eq!(
+            validate_command(0x1234_5678, &payload),
+            Err(CaliptraError::RUNTIME_UNIMPLEMENTED_COMMAND)
+        );
+    }
+}
@@ -24,8 +24,8 @@ use crate::{
 };
 
 use arrayvec::ArrayVec;
-use caliptra_cfi_derive_git::{cfi_impl_fn, cfi_mod_fn};
-use caliptra_cfi_lib_git::{cfi_assert, cfi_assert_eq, cfi_assert_eq_12_words, cfi_launder};
+use caliptra_cfi_derive::{cfi_impl_fn, cfi_mod_fn};
+use caliptra_cfi_lib::{cfi_assert, cfi_assert_eq, cfi_assert_eq_12_words, cfi_launder};
 use caliptra_common::mailbox_api::AddSubjectAltNameReq;
 use caliptra_drivers::KeyId;
 use caliptra_drivers::{
@@ -680,6 +680,16 @@ impl Drivers {
             nb.value = manifest.header.owner_data.owner_not_before;
         }
 
+        // The owner may shorten or extend the DPE leaf certificate window
+        // independently of the FMC Alias certificate.
+        if let Some(leaf_nf) =
+            NotAfter::from_yyyymmdd(manifest.header.owner_data.owner_dpe_leaf_not_after)
+        {
+            if leaf_nf.value > nb.value {
+                nf = leaf_nf;
+            }
+        }
+
         (nb, nf)
     }
 }
@@ -0,0 +1,189 @@
+/*++
+
+Licensed under the Apache-2.0 license.
+
+File Name:
+
+    get_cert_chain.rs
+
+Abstract:
+
+    File contains GetCertChain mailbox command.
+
+--*/
+
+use core::cmp::min;
+
+use caliptra_cfi_derive::cfi_impl_fn;
+use caliptra_common::mailbox_api::{
+    GetCertChainFlags, GetCertChainReq, GetCertChainResp, MailboxResp,
+};
+use caliptra_error::{CaliptraError, CaliptraResult};
+use dpe::{
+    commands::{CertifyKeyCmd, CertifyKeyFlags, CommandExecution},
+    context::ContextHandle,
+    response::Response,
+};
+use zerocopy::FromBytes;
+
+use crate::{CptraDpeTypes, DpeCrypto, DpeEnv, DpePlatform, Drivers, PauserPrivileges};
+
+pub struct GetCertChainCmd;
+impl GetCertChainCmd {
+    /// Validate the request in `cmd_args`
+    pub(crate) fn parse(cmd_args: &[u8]) -> CaliptraResult<&GetCertChainReq> {
+        let cmd = GetCertChainReq::ref_from_bytes(cmd_args)
+            .map_err(|_| CaliptraError::RUNTIME_MAILBOX_INVALID_PARAMS)?;
+
+        if cmd.size as usize > GetCertChainResp::DATA_MAX_SIZE {
+            return Err(CaliptraError::RUNTIME_MAILBOX_INVALID_PARAMS);
+        }
+        Ok(cmd)
+    }
+
+    #[cfg_attr(not(feature = "no-cfi"), cfi_impl_fn)]
+    #[inline(never)]
+    pub(crate) fn execute(
+        drivers: &mut Drivers,
+        cmd: &GetCertChainReq,
+    ) -> CaliptraResult<MailboxResp> {
+        let size = cmd.size as usize;
+        let offset = cmd.offset as usize;
+        let include_leaf = cmd.flags.contains(GetCertChainFlags::INCLUDE_DPE_LEAF);
+
+        let mut resp = GetCertChainResp::default();
+
+        // Copy the part of the window that overlaps the LDevID -> RT alias chain.
+        // A window starting exactly at the end is empty, so callers paging
+        // through a chain that is a multiple of `size` see `data_size == 0`.
+        let chain = drivers.cert_chain.as_slice();
+        let mut written = 0;
+        if offset <= chain.len() {
+            let end = min(offset.saturating_add(size), chain.len());
+            written = end - offset;
+            resp.data
+                .get_mut(..written)
+                .ok_or(CaliptraError::RUNTIME_GET_CERT_CHAIN_FAILED)?
+                .copy_from_slice(
+                    chain
+                        .get(offset..end)
+                        .ok_or(CaliptraError::RUNTIME_GET_CERT_CHAIN_FAILED)?,
+                );
+        } else if !include_leaf {
+            return Err(CaliptraError::RUNTIME_GET_CERT_CHAIN_INVALID_OFFSET);
+        }
+
+        // The DPE leaf certificate is only generated when the window extends
+        // past the end of the chain.
+        if include_leaf && written < size {
+            let chain_len = chain.len();
+            written += Self::copy_dpe_leaf_cert(
+                drivers,
+                &cmd.label,
+                offset + written - chain_len,
+                &mut resp.data[written..size],
+            )?;
+        }
+
+        resp.data_size = written as u32;
+        Ok(MailboxResp::GetCertChain(resp))
+    }
+
+    /// Generate the DPE leaf certificate for the default context and copy the
+    /// bytes starting at `offset` into `out`
+    ///
+    /// The leaf is regenerated on every call. Key derivation and signing are
+    /// deterministic for a given context and label, so consecutive pages
+    /// always observe the same certificate.
+    ///
+    /// # Arguments
+    ///
+    /// * `drivers` - Drivers
+    /// * `label` - CertifyKey label used to derive the leaf key
+    /// * `offset` - Offset into the leaf certificate
+    /// * `out` - Buffer to copy the leaf certificate bytes to
+    ///
+    /// # Returns
+    ///
+    /// * `usize` - The number of bytes written to `out`
+    fn copy_dpe_leaf_cert(
+        drivers: &mut Drivers,
+        label: &[u8; 48],
+        offset: usize,
+        out: &mut [u8],
+    ) -> CaliptraResult<usize> {
+        // PL1 cannot request X509 certificates from DPE
+        match drivers.caller_privilege_level() {
+            PauserPrivileges::PL0 => (),
+            PauserPrivileges::PL1 => {
+                return Err(CaliptraError::RUNTIME_INCORRECT_PAUSER_PRIVILEGE_LEVEL);
+            }
+        }
+
+        let hashed_rt_pub_key = drivers.compute_rt_alias_sn()?;
+        let key_id_rt_cdi = Drivers::get_key_id_rt_cdi(drivers)?;
+        let key_id_rt_priv_key = Drivers::get_key_id_rt_priv_key(drivers)?;
+        let pdata = drivers.persistent_data.get_mut();
+        let crypto = DpeCrypto::new(
+            &mut drivers.sha384,
+            &mut drivers.trng,
+            &mut drivers.ecc384,
+            &mut drivers.hmac384,
+            &mut drivers.key_vault,
+            &mut pdata.fht.rt_dice_pub_key,
+            key_id_rt_cdi,
+            key_id_rt_priv_key,
+        );
+        let pl0_pauser = pdata.manifest1.header.pl0_pauser;
+        let (nb, nf) = Drivers::get_cert_validity_info(&pdata.manifest1);
+        let mut env = DpeEnv::<CptraDpeTypes> {
+            crypto,
+            platform: DpePlatform::new(
+                pl0_pauser,
+                &hashed_rt_pub_key,
+                &drivers.cert_chain,
+                &nb,
+                &nf,
+                None,
+            ),
+        };
+
+        let certify_key_cmd = CertifyKeyCmd {
+            handle: ContextHandle::default(),
+            label: *label,
+            flags: CertifyKeyFlags::empty(),
+            format: CertifyKeyCmd::FORMAT_X509,
+        };
+        let locality = drivers.mbox.user();
+        let resp = certify_key_cmd.execute(&mut pdata.dpe, &mut env, locality);
+
+        let certify_key_resp = match resp {
+            Ok(Response::CertifyKey(certify_key_resp)) => certify_key_resp,
+            Ok(_) => return Err(CaliptraError::RUNTIME_GET_CERT_CHAIN_FAILED),
+            Err(e) => {
+                // If there is extended error info, populate CPTRA_FW_EXTENDED_ERROR_INFO
+                if let Some(ext_err) = e.get_error_detail() {
+                    drivers.soc_ifc.set_fw_extended_error(ext_err);
+                }
+                return Err(CaliptraError::RUNTIME_GET_CERT_CHAIN_FAILED);
+            }
+        };
+
+        let leaf = certify_key_resp
+            .cert
+            .get(..certify_key_resp.cert_size as usize)
+            .ok_or(CaliptraError::RUNTIME_GET_CERT_CHAIN_FAILED)?;
+        if offset > leaf.len() {
+            return Err(CaliptraError::RUNTIME_GET_CERT_CHAIN_INVALID_OFFSET);
+        }
+
+        let leaf_written = min(out.len(), leaf.len() - offset);
+        out.get_mut(..leaf_written)
+            .ok_or(CaliptraError::RUNTIME_GET_CERT_CHAIN_FAILED)?
+            .copy_from_slice(
+                leaf.get(offset..offset + leaf_written)
+                    .ok_or(CaliptraError::RUNTIME_GET_CERT_CHAIN_FAILED)?,
+            );
+        Ok(leaf_written)
+    }
+}
@@ -0,0 +1,46 @@
+// Licensed under the Apache-2.0 license
+
+use crate::Drivers;
+
+use caliptra_cfi_derive::cfi_impl_fn;
+
+use caliptra_common::mailbox_api::{GetFmcAliasCsrReq, GetFmcAliasCsrResp, MailboxResp};
+use caliptra_error::{CaliptraError, CaliptraResult};
+
+use zerocopy::FromBytes;
+
+pub struct GetFmcAliasCsrCmd;
+impl GetFmcAliasCsrCmd {
+    /// Validate the request in `cmd_args`
+    pub(crate) fn parse(cmd_args: &[u8]) -> CaliptraResult<&GetFmcAliasCsrReq> {
+        let cmd = GetFmcAliasCsrReq::ref_from_bytes(cmd_args)
+            .map_err(|_| CaliptraError::RUNTIME_MAILBOX_INVALID_PARAMS)?;
+        Ok(cmd)
+    }
+
+    #[cfg_attr(not(feature = "no-cfi"), cfi_impl_fn)]
+    #[inline(never)]
+    pub(crate) fn execute(drivers: &mut Drivers) -> CaliptraResult<MailboxResp> {
+        let csr_persistent_mem = &drivers.persistent_data.get().fmc_alias_csr;
+
+        // The FMC Alias private key is locked before the runtime starts, so
+        // the CSR is generated by the FMC on cold reset. An FMC that predates
+        // CSR generation leaves the length at zero.
+        let csr = match csr_persistent_mem.get_csr_len() {
+            0 => None,
+            _ => csr_persistent_mem.get(),
+        }
+        .ok_or(CaliptraError::RUNTIME_GET_FMC_CSR_UNSUPPORTED_FMC)?;
+
+        let mut resp = GetFmcAliasCsrResp {
+            data_size: csr.len() as u32,
+            ..Default::default()
+        };
+        resp.data
+            .get_mut(..csr.len())
+            .ok_or(CaliptraError::RUNTIME_GET_FMC_CSR_UNSUPPORTED_FMC)?
+            .copy_from_slice(csr);
+
+        Ok(MailboxResp::GetFmcAliasCsr(resp))
+    }
+}
@@ -18,10 +18,13 @@ mod capabilities;
 mod certify_key_extended;
 pub mod dice;
 mod disable;
+mod dispatch;
 mod dpe_crypto;
 mod dpe_platform;
 mod drivers;
 pub mod fips;
+mod get_cert_chain;
+mod get_fmc_alias_csr;
 mod get_idev_csr;
 pub mod handoff;
 mod hmac;
@@ -37,13 +40,11 @@ mod verify;
 
 // Used by runtime tests
 pub mod mailbox;
-use authorize_and_stash::AuthorizeAndStashCmd;
-use caliptra_cfi_lib_git::{cfi_assert, cfi_assert_eq, cfi_assert_ne, cfi_launder, CfiCounter};
+use caliptra_cfi_lib::{cfi_assert, cfi_assert_eq, cfi_assert_ne, cfi_launder, CfiCounter};
 use caliptra_registers::soc_ifc::SocIfcReg;
 pub use drivers::{Drivers, PauserPrivileges};
 use mailbox::Mailbox;
 
-use crate::capabilities::CapabilitiesCmd;
 pub use crate::certify_key_extended::CertifyKeyExtendedCmd;
 pub use crate::hmac::Hmac;
 pub use crate::subject_alt_name::AddSubjectAltNameCmd;
@@ -51,6 +52,7 @@ pub use authorize_and_stash::{IMAGE_AUTHORIZED, IMAGE_HASH_MISMATCH, IMAGE_NOT_A
 pub use caliptra_common::fips::FipsVersionCmd;
 pub use dice::{GetFmcAliasCertCmd, GetLdevCertCmd, IDevIdCertCmd};
 pub use disable::DisableAttestationCmd;
+pub use dispatch::validate_command;
 use dpe_crypto::DpeCrypto;
 pub use dpe_platform::{DpePlatform, VENDOR_ID, VENDOR_SKU};
 pub use fips::FipsShutdownCmd;
@@ -58,6 +60,8 @@ pub use fips::FipsShutdownCmd;
 pub use fips::{fips_self_test_cmd, fips_self_test_cmd::SelfTestStatus};
 pub use populate_idev::PopulateIDevIdCertCmd;
 
+pub use get_cert_chain::GetCertChainCmd;
+pub use get_fmc_alias_csr::GetFmcAliasCsrCmd;
 pub use get_idev_csr::GetIdevCsrCmd;
 pub use info::{FwInfoCmd, IDevIdInfoCmd};
 pub use invoke_dpe::InvokeDpeCmd;
@@ -66,10 +70,9 @@ pub use set_auth_manifest::SetAuthManifestCmd;
 pub use stash_measurement::StashMeasurementCmd;
 pub use verify::{EcdsaVerifyCmd, LmsVerifyCmd};
 pub mod packet;
-use caliptra_common::mailbox_api::{CommandId, MailboxResp};
+use caliptra_common::mailbox_api::CommandId;
 use packet::Packet;
 pub mod tagging;
-use tagging::{GetTaggedTciCmd, TagTciCmd};
 
 use caliptra_common::cprintln;
 
@@ -84,11 +87,6 @@ use dpe::{
 };
 pub use dpe::{context::ContextState, tci::TciMeasurement, DpeInstance, U8Bool, MAX_HANDLES};
 
-use crate::{
-    dice::GetRtAliasCertCmd,
-    pcr::{ExtendPcrCmd, GetPcrQuoteCmd},
-};
-
 const RUNTIME_BOOT_STATUS_BASE: u32 = 0x600;
 
 /// Statuses used by ROM to log dice derivation progress.
@@ -182,55 +180,7 @@ fn handle_command(drivers: &mut Drivers) -> CaliptraResult<MboxStatusE> {
     );
 
     // Handle the request and generate the response
-    let mut resp = match CommandId::from(req_packet.cmd) {
-        CommandId::FIRMWARE_LOAD => Err(CaliptraError::RUNTIME_UNIMPLEMENTED_COMMAND),
-        CommandId::GET_IDEV_CERT => IDevIdCertCmd::execute(cmd_bytes),
-        CommandId::GET_IDEV_INFO => IDevIdInfoCmd::execute(drivers),
-        CommandId::GET_LDEV_CERT => GetLdevCertCmd::execute(drivers),
-        CommandId::INVOKE_DPE => InvokeDpeCmd::execute(drivers, cmd_bytes),
-        CommandId::ECDSA384_VERIFY => EcdsaVerifyCmd::execute(drivers, cmd_bytes),
-        CommandId::LMS_VERIFY => LmsVerifyCmd::execute(drivers, cmd_bytes),
-        CommandId::EXTEND_PCR => ExtendPcrCmd::execute(drivers, cmd_bytes),
-        CommandId::STASH_MEASUREMENT => StashMeasurementCmd::execute(drivers, cmd_bytes),
-        CommandId::DISABLE_ATTESTATION => DisableAttestationCmd::execute(drivers),
-        CommandId::FW_INFO => FwInfoCmd::execute(drivers),
-        CommandId::DPE_TAG_TCI => TagTciCmd::execute(drivers, cmd_bytes),
-        CommandId::DPE_GET_TAGGED_TCI => GetTaggedTciCmd::execute(drivers, cmd_bytes),
-        CommandId::POPULATE_IDEV_CERT => PopulateIDevIdCertCmd::execute(drivers, cmd_bytes),
-        CommandId::GET_FMC_ALIAS_CERT => GetFmcAliasCertCmd::execute(drivers),
-        CommandId::GET_RT_ALIAS_CERT => GetRtAliasCertCmd::execute(drivers),
-        CommandId::ADD_SUBJECT_ALT_NAME => AddSubjectAltNameCmd::execute(drivers, cmd_bytes),
-        CommandId::CERTIFY_KEY_EXTENDED => CertifyKeyExtendedCmd::execute(drivers, cmd_bytes),
-        CommandId::INCREMENT_PCR_RESET_COUNTER => {
-            IncrementPcrResetCounterCmd::execute(drivers, cmd_bytes)
-        }
-        CommandId::QUOTE_PCRS => GetPcrQuoteCmd::execute(drivers, cmd_bytes),
-        CommandId::VERSION => {
-            FipsVersionCmd::execute(&drivers.soc_ifc).map(MailboxResp::FipsVersion)
-        }
-        CommandId::CAPABILITIES => CapabilitiesCmd::execute(),
-        #[cfg(feature = "fips_self_test")]
-        CommandId::SELF_TEST_START => match drivers.self_test_status {
-            SelfTestStatus::Idle => {
-                drivers.self_test_status = SelfTestStatus::InProgress(fips_self_test_cmd::execute);
-                Ok(MailboxResp::default())
-            }
-            _ => Err(CaliptraError::RUNTIME_SELF_TEST_IN_PROGRESS),
-        },
-        #[cfg(feature = "fips_self_test")]
-        CommandId::SELF_TEST_GET_RESULTS => match drivers.self_test_status {
-            SelfTestStatus::Done => {
-                drivers.self_test_status = SelfTestStatus::Idle;
-                Ok(MailboxResp::default())
-            }
-            _ => Err(CaliptraError::RUNTIME_SELF_TEST_NOT_STARTED),
-        },
-        CommandId::SHUTDOWN => FipsShutdownCmd::execute(drivers),
-        CommandId::SET_AUTH_MANIFEST => SetAuthManifestCmd::execute(drivers, cmd_bytes),
-        CommandId::AUTHORIZE_AND_STASH => AuthorizeAndStashCmd::execute(drivers, cmd_bytes),
-        CommandId::GET_IDEV_CSR => GetIdevCsrCmd::execute(drivers, cmd_bytes),
-        _ => Err(CaliptraError::RUNTIME_UNIMPLEMENTED_COMMAND),
-    }?;
+    let mut resp = dispatch::execute_command(drivers, req_packet.cmd, cmd_bytes)?;
 
     // Send the response
     Packet::copy_to_mbox(drivers, &mut resp)?;
@@ -61,14 +61,25 @@ impl Packet {
                 .ok_or(CaliptraError::RUNTIME_INTERNAL)?,
         );
 
+        Self::verify_checksum(packet.cmd, packet.as_bytes()?)?;
+
+        Ok(packet)
+    }
+
+    /// Check the length and checksum of a request payload
+    ///
+    /// # Arguments
+    ///
+    /// * `cmd` - Command ID the payload was sent with
+    /// * `payload_bytes` - Request payload, starting with the checksum
+    pub fn verify_checksum(cmd: u32, payload_bytes: &[u8]) -> CaliptraResult<()> {
         // Verify incoming checksum
         // Make sure enough data was sent to even have a checksum
-        if packet.len < core::mem::size_of::<MailboxReqHeader>() {
+        if payload_bytes.len() < core::mem::size_of::<MailboxReqHeader>() {
             return Err(CaliptraError::RUNTIME_MAILBOX_INVALID_PARAMS);
         }
 
         // Assumes chksum is always offset 0
-        let payload_bytes = packet.as_bytes()?;
         let req_hdr: &MailboxReqHeader = MailboxReqHeader::ref_from_bytes(
             &payload_bytes[..core::mem::size_of::<MailboxReqHeader>()],
         )
@@ -76,13 +87,13 @@ impl Packet {
 
         if !caliptra_common::checksum::verify_checksum(
             req_hdr.chksum,
-            packet.cmd,
+            cmd,
             &payload_bytes[core::mem::size_of_val(&req_hdr.chksum)..],
         ) {
             return Err(CaliptraError::RUNTIME_INVALID_CHECKSUM);
         }
 
-        Ok(packet)
+        Ok(())
     }
 
     /// Writes `resp` to the mailbox
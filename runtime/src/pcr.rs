@@ -13,7 +13,7 @@ Abstract:
 --*/
 
 use crate::Drivers;
-use caliptra_cfi_derive_git::cfi_impl_fn;
+use caliptra_cfi_derive::cfi_impl_fn;
 use caliptra_common::mailbox_api::{
     ExtendPcrReq, IncrementPcrResetCounterReq, MailboxResp, MailboxRespHeader, QuotePcrsReq,
     QuotePcrsResp,
@@ -23,18 +23,20 @@ use zerocopy::FromBytes;
 
 pub struct IncrementPcrResetCounterCmd;
 impl IncrementPcrResetCounterCmd {
-    #[cfg_attr(not(feature = "no-cfi"), cfi_impl_fn)]
-    #[inline(never)]
-    pub(crate) fn execute(drivers: &mut Drivers, cmd_args: &[u8]) -> CaliptraResult<MailboxResp> {
+    /// Validate the request in `cmd_args` and return the PCR it selects
+    pub(crate) fn parse(cmd_args: &[u8]) -> CaliptraResult<PcrId> {
         let cmd = IncrementPcrResetCounterReq::ref_from_bytes(cmd_args)
             .map_err(|_| CaliptraError::RUNTIME_INSUFFICIENT_MEMORY)?;
 
         let index =
             u8::try_from(cmd.index).map_err(|_| CaliptraError::RUNTIME_MAILBOX_INVALID_PARAMS)?;
 
-        let pcr =
-            PcrId::try_from(index).map_err(|_| CaliptraError::RUNTIME_MAILBOX_INVALID_PARAMS)?;
+        PcrId::try_from(index).map_err(|_| CaliptraError::RUNTIME_MAILBOX_INVALID_PARAMS)
+    }
 
+    #[cfg_attr(not(feature = "no-cfi"), cfi_impl_fn)]
+    #[inline(never)]
+    pub(crate) fn execute(drivers: &mut Drivers, pcr: PcrId) -> CaliptraResult<MailboxResp> {
         if !drivers.persistent_data.get_mut().pcr_reset.increment(pcr) {
             return Err(CaliptraError::RUNTIME_INCREMENT_PCR_RESET_MAX_REACHED);
         }
@@ -45,12 +47,19 @@ impl IncrementPcrResetCounterCmd {
 
 pub struct GetPcrQuoteCmd;
 impl GetPcrQuoteCmd {
-    #[cfg_attr(not(feature = "no-cfi"), cfi_impl_fn)]
-    #[inline(never)]
-    pub(crate) fn execute(drivers: &mut Drivers, cmd_bytes: &[u8]) -> CaliptraResult<MailboxResp> {
-        let args: &QuotePcrsReq = QuotePcrsReq::ref_from_bytes(cmd_bytes)
+    /// Validate the request in `cmd_bytes`
+    pub(crate) fn parse(cmd_bytes: &[u8]) -> CaliptraResult<&QuotePcrsReq> {
+        let cmd = QuotePcrsReq::ref_from_bytes(cmd_bytes)
             .map_err(|_| CaliptraError::RUNTIME_MAILBOX_INVALID_PARAMS)?;
+        Ok(cmd)
+    }
 
+    #[cfg_attr(not(feature = "no-cfi"), cfi_impl_fn)]
+    #[inline(never)]
+    pub(crate) fn execute(
+        drivers: &mut Drivers,
+        args: &QuotePcrsReq,
+    ) -> CaliptraResult<MailboxResp> {
         let pcr_hash = drivers.sha384.gen_pcr_hash(args.nonce.into())?;
         let signature = drivers.ecc384.pcr_sign_flow(&mut drivers.trng)?;
         let raw_pcrs = drivers.pcr_bank.read_all_pcrs();
@@ -74,23 +83,30 @@ impl GetPcrQuoteCmd {
 
 pub struct ExtendPcrCmd;
 impl ExtendPcrCmd {
-    #[cfg_attr(not(feature = "no-cfi"), cfi_impl_fn)]
-    #[inline(never)]
-    pub(crate) fn execute(drivers: &mut Drivers, cmd_args: &[u8]) -> CaliptraResult<MailboxResp> {
+    /// Validate the request in `cmd_args` and return the PCR it selects,
+    /// rejecting the PCRs reserved for firmware
+    pub(crate) fn parse(cmd_args: &[u8]) -> CaliptraResult<(PcrId, &ExtendPcrReq)> {
         let cmd = ExtendPcrReq::ref_from_bytes(cmd_args)
             .map_err(|_| CaliptraError::RUNTIME_INSUFFICIENT_MEMORY)?;
 
         let idx =
             u8::try_from(cmd.pcr_idx).map_err(|_| CaliptraError::RUNTIME_MAILBOX_INVALID_PARAMS)?;
 
-        let pcr_index: PcrId =
-            match PcrId::try_from(idx).map_err(|_| CaliptraError::RUNTIME_PCR_INVALID_INDEX)? {
-                PcrId::PcrId0 | PcrId::PcrId1 | PcrId::PcrId2 | PcrId::PcrId3 => {
-                    return Err(CaliptraError::RUNTIME_PCR_RESERVED)
-                }
-                pcr_id => pcr_id,
-            };
+        match PcrId::try_from(idx).map_err(|_| CaliptraError::RUNTIME_PCR_INVALID_INDEX)? {
+            PcrId::PcrId0 | PcrId::PcrId1 | PcrId::PcrId2 | PcrId::PcrId3 => {
+                Err(CaliptraError::RUNTIME_PCR_RESERVED)
+            }
+            pcr_index => Ok((pcr_index, cmd)),
+        }
+    }
 
+    #[cfg_attr(not(feature = "no-cfi"), cfi_impl_fn)]
+    #[inline(never)]
+    pub(crate) fn execute(
+        drivers: &mut Drivers,
+        pcr_index: PcrId,
+        cmd: &ExtendPcrReq,
+    ) -> CaliptraResult<MailboxResp> {
         drivers
             .pcr_bank
             .extend_pcr(pcr_index, &mut drivers.sha384, &cmd.data)?;
@@ -15,41 +15,56 @@ Abstract:
 use arrayvec::ArrayVec;
 use caliptra_common::mailbox_api::{MailboxResp, PopulateIdevCertReq};
 use caliptra_error::{CaliptraError, CaliptraResult};
-use zerocopy::IntoBytes;
+use memoffset::offset_of;
 
 use crate::{Drivers, MAX_CERT_CHAIN_SIZE, PL0_PAUSER_FLAG};
 
 pub struct PopulateIDevIdCertCmd;
 impl PopulateIDevIdCertCmd {
+    /// Validate the variable length request in `cmd_args` and borrow its
+    /// certificate
+    pub(crate) fn parse(cmd_args: &[u8]) -> CaliptraResult<&[u8]> {
+        if cmd_args.len() > core::mem::size_of::<PopulateIdevCertReq>() {
+            return Err(CaliptraError::RUNTIME_INSUFFICIENT_MEMORY);
+        }
+
+        let cert_size: usize = {
+            let err = CaliptraError::RUNTIME_MAILBOX_INVALID_PARAMS;
+            let offset = offset_of!(PopulateIdevCertReq, cert_size);
+            u32::from_le_bytes(
+                cmd_args
+                    .get(offset..offset + 4)
+                    .ok_or(err)?
+                    .try_into()
+                    .map_err(|_| err)?,
+            ) as usize
+        };
+        if cert_size > PopulateIdevCertReq::MAX_CERT_SIZE {
+            return Err(CaliptraError::RUNTIME_MAILBOX_INVALID_PARAMS);
+        }
+        let offset = offset_of!(PopulateIdevCertReq, cert);
+        cmd_args
+            .get(offset..offset + cert_size)
+            .ok_or(CaliptraError::RUNTIME_MAILBOX_INVALID_PARAMS)
+    }
+
     #[inline(never)]
-    pub(crate) fn execute(drivers: &mut Drivers, cmd_args: &[u8]) -> CaliptraResult<MailboxResp> {
-        if cmd_args.len() <= core::mem::size_of::<PopulateIdevCertReq>() {
-            let mut cmd = PopulateIdevCertReq::default();
-            cmd.as_mut_bytes()[..cmd_args.len()].copy_from_slice(cmd_args);
-
-            let cert_size = cmd.cert_size as usize;
-            if cert_size > cmd.cert.len() {
-                return Err(CaliptraError::RUNTIME_MAILBOX_INVALID_PARAMS);
-            }
-
-            let flags = drivers.persistent_data.get().manifest1.header.flags;
-            // PL1 cannot call this mailbox command
-            if flags & PL0_PAUSER_FLAG == 0 {
-                return Err(CaliptraError::RUNTIME_INCORRECT_PAUSER_PRIVILEGE_LEVEL);
-            }
-
-            let mut tmp_chain = ArrayVec::<u8, MAX_CERT_CHAIN_SIZE>::new();
-            tmp_chain
-                .try_extend_from_slice(&cmd.cert[..cert_size])
-                .map_err(|_| CaliptraError::RUNTIME_IDEV_CERT_POPULATION_FAILED)?;
-            tmp_chain
-                .try_extend_from_slice(drivers.cert_chain.as_slice())
-                .map_err(|_| CaliptraError::RUNTIME_IDEV_CERT_POPULATION_FAILED)?;
-            drivers.cert_chain = tmp_chain;
-
-            Ok(MailboxResp::default())
-        } else {
-            Err(CaliptraError::RUNTIME_INSUFFICIENT_MEMORY)
+    pub(crate) fn execute(drivers: &mut Drivers, cert: &[u8]) -> CaliptraResult<MailboxResp> {
+        let flags = drivers.persistent_data.get().manifest1.header.flags;
+        // PL1 cannot call this mailbox command
+        if flags & PL0_PAUSER_FLAG == 0 {
+            return Err(CaliptraError::RUNTIME_INCORRECT_PAUSER_PRIVILEGE_LEVEL);
         }
+
+        let mut tmp_chain = ArrayVec::<u8, MAX_CERT_CHAIN_SIZE>::new();
+        tmp_chain
+            .try_extend_from_slice(cert)
+            .map_err(|_| CaliptraError::RUNTIME_IDEV_CERT_POPULATION_FAILED)?;
+        tmp_chain
+            .try_extend_from_slice(drivers.cert_chain.as_slice())
+            .map_err(|_| CaliptraError::RUNTIME_IDEV_CERT_POPULATION_FAILED)?;
+        drivers.cert_chain = tmp_chain;
+
+        Ok(MailboxResp::default())
     }
 }
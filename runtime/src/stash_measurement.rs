@@ -13,7 +13,7 @@ Abstract:
 --*/
 
 use crate::{dpe_crypto::DpeCrypto, CptraDpeTypes, DpePlatform, Drivers, PauserPrivileges};
-use caliptra_cfi_derive_git::cfi_impl_fn;
+use caliptra_cfi_derive::cfi_impl_fn;
 use caliptra_common::mailbox_api::{
     MailboxResp, MailboxRespHeader, StashMeasurementReq, StashMeasurementResp,
 };
@@ -29,6 +29,13 @@ use zerocopy::{FromBytes, IntoBytes};
 
 pub struct StashMeasurementCmd;
 impl StashMeasurementCmd {
+    /// Validate the request in `cmd_args`
+    pub(crate) fn parse(cmd_args: &[u8]) -> CaliptraResult<&StashMeasurementReq> {
+        let cmd = StashMeasurementReq::ref_from_bytes(cmd_args)
+            .map_err(|_| CaliptraError::RUNTIME_INSUFFICIENT_MEMORY)?;
+        Ok(cmd)
+    }
+
     #[cfg_attr(not(feature = "no-cfi"), cfi_impl_fn)]
     #[inline(never)]
     pub(crate) fn stash_measurement(
@@ -114,10 +121,10 @@ impl StashMeasurementCmd {
         Ok(dpe_result)
     }
 
-    pub(crate) fn execute(drivers: &mut Drivers, cmd_args: &[u8]) -> CaliptraResult<MailboxResp> {
-        let cmd = StashMeasurementReq::ref_from_bytes(cmd_args)
-            .map_err(|_| CaliptraError::RUNTIME_INSUFFICIENT_MEMORY)?;
-
+    pub(crate) fn execute(
+        drivers: &mut Drivers,
+        cmd: &StashMeasurementReq,
+    ) -> CaliptraResult<MailboxResp> {
         let dpe_result = Self::stash_measurement(drivers, &cmd.metadata, &cmd.measurement)?;
 
         Ok(MailboxResp::StashMeasurement(StashMeasurementResp {
@@ -17,7 +17,7 @@ use core::str::from_utf8;
 use arrayvec::ArrayVec;
 use caliptra_common::mailbox_api::{AddSubjectAltNameReq, MailboxResp};
 use caliptra_error::{CaliptraError, CaliptraResult};
-use zerocopy::IntoBytes;
+use memoffset::offset_of;
 
 use crate::{Drivers, MAX_CERT_CHAIN_SIZE, PL0_PAUSER_FLAG};
 
@@ -27,29 +27,50 @@ impl AddSubjectAltNameCmd {
     pub const DMTF_OID: &'static [u8] =
         &[0x2B, 0x06, 0x01, 0x04, 0x01, 0x83, 0x1C, 0x82, 0x12, 0x01];
 
-    #[inline(never)]
-    pub(crate) fn execute(drivers: &mut Drivers, cmd_args: &[u8]) -> CaliptraResult<MailboxResp> {
-        if cmd_args.len() <= core::mem::size_of::<AddSubjectAltNameReq>() {
-            let mut cmd = AddSubjectAltNameReq::default();
-            cmd.as_mut_bytes()[..cmd_args.len()].copy_from_slice(cmd_args);
-
-            let dmtf_device_info_size = cmd.dmtf_device_info_size as usize;
-            if dmtf_device_info_size > cmd.dmtf_device_info.len() {
-                return Err(CaliptraError::RUNTIME_MAILBOX_INVALID_PARAMS);
-            }
-
-            Self::validate_dmtf_device_info(&cmd.dmtf_device_info[..dmtf_device_info_size])?;
-
-            let mut dmtf_device_info = ArrayVec::new();
-            dmtf_device_info
-                .try_extend_from_slice(&cmd.dmtf_device_info[..dmtf_device_info_size])
-                .map_err(|_| CaliptraError::RUNTIME_STORE_DMTF_DEVICE_INFO_FAILED)?;
-            drivers.dmtf_device_info = Some(dmtf_device_info);
+    /// Validate the variable length request in `cmd_args` and borrow its
+    /// device info
+    pub(crate) fn parse(cmd_args: &[u8]) -> CaliptraResult<&[u8]> {
+        if cmd_args.len() > core::mem::size_of::<AddSubjectAltNameReq>() {
+            return Err(CaliptraError::RUNTIME_INSUFFICIENT_MEMORY);
+        }
 
-            Ok(MailboxResp::default())
-        } else {
-            Err(CaliptraError::RUNTIME_INSUFFICIENT_MEMORY)
+        let dmtf_device_info_size: usize = {
+            let err = CaliptraError::RUNTIME_MAILBOX_INVALID_PARAMS;
+            let offset = offset_of!(AddSubjectAltNameReq, dmtf_device_info_size);
+            u32::from_le_bytes(
+                cmd_args
+                    .get(offset..offset + 4)
+                    .ok_or(err)?
+                    .try_into()
+                    .map_err(|_| err)?,
+            ) as usize
+        };
+        if dmtf_device_info_size > AddSubjectAltNameReq::MAX_DEVICE_INFO_LEN {
+            return Err(CaliptraError::RUNTIME_MAILBOX_INVALID_PARAMS);
         }
+        let dmtf_device_info = {
+            let offset = offset_of!(AddSubjectAltNameReq, dmtf_device_info);
+            cmd_args
+                .get(offset..offset + dmtf_device_info_size)
+                .ok_or(CaliptraError::RUNTIME_MAILBOX_INVALID_PARAMS)?
+        };
+        Self::validate_dmtf_device_info(dmtf_device_info)?;
+
+        Ok(dmtf_device_info)
+    }
+
+    #[inline(never)]
+    pub(crate) fn execute(
+        drivers: &mut Drivers,
+        dmtf_device_info: &[u8],
+    ) -> CaliptraResult<MailboxResp> {
+        let mut device_info = ArrayVec::new();
+        device_info
+            .try_extend_from_slice(dmtf_device_info)
+            .map_err(|_| CaliptraError::RUNTIME_STORE_DMTF_DEVICE_INFO_FAILED)?;
+        drivers.dmtf_device_info = Some(device_info);
+
+        Ok(MailboxResp::default())
     }
 
     fn validate_dmtf_device_info(dmtf_device_info: &[u8]) -> CaliptraResult<()> {
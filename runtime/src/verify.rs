@@ -13,7 +13,7 @@ Abstract:
 --*/
 
 use crate::Drivers;
-use caliptra_cfi_derive_git::cfi_impl_fn;
+use caliptra_cfi_derive::cfi_impl_fn;
 use caliptra_common::mailbox_api::{EcdsaVerifyReq, LmsVerifyReq, MailboxResp};
 use caliptra_drivers::{
     Array4x12, CaliptraError, CaliptraResult, Ecc384PubKey, Ecc384Result, Ecc384Scalar,
@@ -27,11 +27,19 @@ use zerocopy::{BigEndian, FromBytes, LittleEndian, U32};
 
 pub struct EcdsaVerifyCmd;
 impl EcdsaVerifyCmd {
-    #[cfg_attr(not(feature = "no-cfi"), cfi_impl_fn)]
-    #[inline(never)]
-    pub(crate) fn execute(drivers: &mut Drivers, cmd_args: &[u8]) -> CaliptraResult<MailboxResp> {
+    /// Validate the request in `cmd_args`
+    pub(crate) fn parse(cmd_args: &[u8]) -> CaliptraResult<&EcdsaVerifyReq> {
         let cmd = EcdsaVerifyReq::ref_from_bytes(cmd_args)
             .map_err(|_| CaliptraError::RUNTIME_INSUFFICIENT_MEMORY)?;
+        Ok(cmd)
+    }
+
+    #[cfg_attr(not(feature = "no-cfi"), cfi_impl_fn)]
+    #[inline(never)]
+    pub(crate) fn execute(
+        drivers: &mut Drivers,
+        cmd: &EcdsaVerifyReq,
+    ) -> CaliptraResult<MailboxResp> {
         // Won't panic, full_digest is always larger than digest
         let full_digest = drivers.sha_acc.regs().digest().read();
         let mut digest = Array4x12::default();
@@ -58,11 +66,41 @@ impl EcdsaVerifyCmd {
     }
 }
 
+// Constants from fixed LMS param set
+const LMS_N: usize = 6;
+const LMS_P: usize = 51;
+const LMS_H: usize = 15;
+const LMS_ALGORITHM_TYPE: LmsAlgorithmType = LmsAlgorithmType::new(12);
+const LMOTS_ALGORITHM_TYPE: LmotsAlgorithmType = LmotsAlgorithmType::new(7);
+
 pub struct LmsVerifyCmd;
 impl LmsVerifyCmd {
+    /// Validate the request in `cmd_args` and check that it uses the fixed
+    /// LMS parameter set
+    pub(crate) fn parse(cmd_args: &[u8]) -> CaliptraResult<&LmsVerifyReq> {
+        let cmd = LmsVerifyReq::ref_from_bytes(cmd_args)
+            .map_err(|_| CaliptraError::RUNTIME_INSUFFICIENT_MEMORY)?;
+
+        // Check that fixed params are correct
+        if LmsAlgorithmType::new(cmd.pub_key_tree_type) != LMS_ALGORITHM_TYPE {
+            return Err(CaliptraError::RUNTIME_LMS_VERIFY_INVALID_LMS_ALGORITHM);
+        }
+        if LmotsAlgorithmType::new(cmd.pub_key_ots_type) != LMOTS_ALGORITHM_TYPE {
+            return Err(CaliptraError::RUNTIME_LMS_VERIFY_INVALID_LMOTS_ALGORITHM);
+        }
+        if LmsAlgorithmType::new(cmd.signature_tree_type) != LMS_ALGORITHM_TYPE {
+            return Err(CaliptraError::RUNTIME_LMS_VERIFY_INVALID_LMS_ALGORITHM);
+        }
+
+        Ok(cmd)
+    }
+
     #[cfg_attr(not(feature = "no-cfi"), cfi_impl_fn)]
     #[inline(never)]
-    pub(crate) fn execute(drivers: &mut Drivers, cmd_args: &[u8]) -> CaliptraResult<MailboxResp> {
+    pub(crate) fn execute(
+        drivers: &mut Drivers,
+        cmd: &LmsVerifyReq,
+    ) -> CaliptraResult<MailboxResp> {
         // Re-run LMS KAT once (since LMS is more SW-based than other crypto)
         if let Err(e) =
             caliptra_kat::LmsKat::default().execute_once(&mut drivers.sha256, &mut drivers.lms)
@@ -71,15 +109,6 @@ impl LmsVerifyCmd {
             caliptra_common::handle_fatal_error(e.into());
         }
 
-        // Constants from fixed LMS param set
-        const LMS_N: usize = 6;
-        const LMS_P: usize = 51;
-        const LMS_H: usize = 15;
-        const LMS_ALGORITHM_TYPE: LmsAlgorithmType = LmsAlgorithmType::new(12);
-        const LMOTS_ALGORITHM_TYPE: LmotsAlgorithmType = LmotsAlgorithmType::new(7);
-
-        let cmd = LmsVerifyReq::ref_from_bytes(cmd_args)
-            .map_err(|_| CaliptraError::RUNTIME_INSUFFICIENT_MEMORY)?;
         // Get the digest from the SHA accelerator
         let msg_digest_be = drivers.sha_acc.regs().digest().truncate::<12>().read();
         // Flip the endianness since LMS treats this as raw message bytes
@@ -107,17 +136,6 @@ impl LmsVerifyCmd {
             .map_err(|_| CaliptraError::RUNTIME_INSUFFICIENT_MEMORY)?,
         };
 
-        // Check that fixed params are correct
-        if lms_pub_key.tree_type != LMS_ALGORITHM_TYPE {
-            return Err(CaliptraError::RUNTIME_LMS_VERIFY_INVALID_LMS_ALGORITHM);
-        }
-        if lms_pub_key.otstype != LMOTS_ALGORITHM_TYPE {
-            return Err(CaliptraError::RUNTIME_LMS_VERIFY_INVALID_LMOTS_ALGORITHM);
-        }
-        if lms_sig.tree_type != LMS_ALGORITHM_TYPE {
-            return Err(CaliptraError::RUNTIME_LMS_VERIFY_INVALID_LMS_ALGORITHM);
-        }
-
         let success = drivers.lms.verify_lms_signature(
             &mut drivers.sha256,
             &msg_digest,
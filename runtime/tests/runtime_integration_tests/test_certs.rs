@@ -87,6 +87,77 @@ fn test_rt_cert_with_custom_dates() {
     assert!(rt_cert.not_after() == not_after);
 }
 
+#[test]
+// Check that the owner can set per-layer end dates for RT Alias and DPE leaf certs
+fn test_per_layer_cert_validity() {
+    const OWNER_CONFIG: (&str, &str) = ("20270101000000Z", "20280101000000Z");
+
+    let mut opts = ImageOptions::default();
+    let mut own_config = opts.owner_config.unwrap();
+    own_config
+        .not_before
+        .copy_from_slice(OWNER_CONFIG.0.as_bytes());
+    own_config
+        .not_after
+        .copy_from_slice(OWNER_CONFIG.1.as_bytes());
+    own_config.rt_alias_not_after = 20300630;
+    own_config.dpe_leaf_not_after = 20290101;
+    opts.owner_config = Some(own_config);
+
+    let args = RuntimeTestArgs {
+        test_image_options: Some(opts),
+        ..Default::default()
+    };
+    let mut model = run_rt_test(args);
+
+    let rt_resp = get_rt_alias_cert(&mut model);
+    let rt_cert: X509 = X509::from_der(&rt_resp.data[..rt_resp.data_size as usize]).unwrap();
+    assert!(rt_cert.not_before() == Asn1Time::from_str(OWNER_CONFIG.0).unwrap());
+    assert!(rt_cert.not_after() == Asn1Time::from_str("20300630235959Z").unwrap());
+
+    let leaf_resp = get_dpe_leaf_cert(&mut model);
+    let leaf_cert: X509 = X509::from_der(&leaf_resp.cert[..leaf_resp.cert_size as usize]).unwrap();
+    assert!(leaf_cert.not_before() == Asn1Time::from_str(OWNER_CONFIG.0).unwrap());
+    assert!(leaf_cert.not_after() == Asn1Time::from_str("20290101235959Z").unwrap());
+}
+
+#[test]
+// Check that the RT Alias serial number tracks re-issuance when the counter policy is selected
+fn test_rt_alias_serial_number_counter() {
+    let mut opts = ImageOptions::default();
+    opts.owner_config.as_mut().unwrap().rt_alias_sn_counter = true;
+
+    let args = RuntimeTestArgs {
+        test_image_options: Some(opts.clone()),
+        ..Default::default()
+    };
+    let mut model = run_rt_test(args);
+
+    let rt_serial = |model: &mut DefaultHwModel| {
+        let rt_resp = get_rt_alias_cert(model);
+        let rt_cert = X509::from_der(&rt_resp.data[..rt_resp.data_size as usize]).unwrap();
+        rt_cert.serial_number().to_bn().unwrap().to_vec()
+    };
+
+    let cold_serial = rt_serial(&mut model);
+    assert_eq!(cold_serial[cold_serial.len() - 4..], [0, 0, 0, 0]);
+
+    let fw_image = caliptra_builder::build_and_sign_image(&FMC_WITH_UART, &APP_WITH_UART, opts)
+        .unwrap()
+        .to_bytes()
+        .unwrap();
+    model
+        .mailbox_execute(u32::from(CommandId::FIRMWARE_LOAD), &fw_image)
+        .unwrap();
+
+    let update_serial = rt_serial(&mut model);
+    assert_eq!(update_serial[update_serial.len() - 4..], [0, 0, 0, 1]);
+    assert_eq!(
+        cold_serial[..cold_serial.len() - 4],
+        update_serial[..update_serial.len() - 4]
+    );
+}
+
 #[test]
 fn test_idev_id_cert() {
     let mut model = run_rt_test(RuntimeTestArgs::default());
@@ -0,0 +1,229 @@
+// Licensed under the Apache-2.0 license
+
+use crate::common::{
+    assert_error, execute_dpe_cmd, get_fmc_alias_cert, get_rt_alias_cert, run_rt_test, DpeResult,
+    RuntimeTestArgs, TEST_LABEL,
+};
+use caliptra_common::mailbox_api::{
+    CommandId, GetCertChainFlags, GetCertChainReq, GetCertChainResp, GetLdevCertResp, MailboxReq,
+    MailboxReqHeader,
+};
+use caliptra_error::CaliptraError;
+use caliptra_hw_model::{DefaultHwModel, HwModel};
+use caliptra_x509_verify::{verify_cert_chain, DiceCertChain, VerifyOptions};
+use dpe::{
+    commands::{CertifyKeyCmd, CertifyKeyFlags, Command},
+    context::ContextHandle,
+    response::Response,
+};
+use openssl::x509::X509;
+use x509_parser::{certificate::X509Certificate, oid_registry::asn1_rs::FromDer};
+use zerocopy::IntoBytes;
+
+fn get_cert_chain(
+    model: &mut DefaultHwModel,
+    offset: u32,
+    size: u32,
+    flags: GetCertChainFlags,
+) -> GetCertChainResp {
+    let mut cmd = MailboxReq::GetCertChain(GetCertChainReq {
+        hdr: MailboxReqHeader { chksum: 0 },
+        offset,
+        size,
+        flags,
+        label: TEST_LABEL,
+    });
+    cmd.populate_chksum().unwrap();
+
+    let resp = model
+        .mailbox_execute(
+            u32::from(CommandId::GET_CERT_CHAIN),
+            cmd.as_bytes().unwrap(),
+        )
+        .unwrap()
+        .expect("We expected a response");
+
+    assert!(resp.len() <= std::mem::size_of::<GetCertChainResp>());
+    let mut chain_resp = GetCertChainResp::default();
+    chain_resp.as_mut_bytes()[..resp.len()].copy_from_slice(&resp);
+
+    assert!(caliptra_common::checksum::verify_checksum(
+        chain_resp.hdr.chksum,
+        0x0,
+        &resp[core::mem::size_of_val(&chain_resp.hdr.chksum)..],
+    ));
+    chain_resp
+}
+
+fn get_full_cert_chain(model: &mut DefaultHwModel, flags: u32) -> Vec<u8> {
+    get_paged_cert_chain(model, flags, GetCertChainResp::DATA_MAX_SIZE).0
+}
+
+/// Reads the chain `size` bytes at a time, returning the chain and the
+/// `data_size` of the final response.
+fn get_paged_cert_chain(model: &mut DefaultHwModel, flags: u32, size: usize) -> (Vec<u8>, usize) {
+    let mut chain = vec![];
+    loop {
+        let resp = get_cert_chain(
+            model,
+            chain.len() as u32,
+            size as u32,
+            GetCertChainFlags(flags),
+        );
+        chain.extend_from_slice(&resp.data[..resp.data_size as usize]);
+        if (resp.data_size as usize) < size {
+            return (chain, resp.data_size as usize);
+        }
+    }
+}
+
+fn split_cert_chain(mut chain: &[u8]) -> Vec<X509> {
+    let mut certs = vec![];
+    while !chain.is_empty() {
+        let (rem, _) = X509Certificate::from_der(chain).unwrap();
+        certs.push(X509::from_der(&chain[..chain.len() - rem.len()]).unwrap());
+        chain = rem;
+    }
+    certs
+}
+
+fn get_ldev_cert(model: &mut DefaultHwModel) -> Vec<u8> {
+    let payload = MailboxReqHeader {
+        chksum: caliptra_common::checksum::calc_checksum(u32::from(CommandId::GET_LDEV_CERT), &[]),
+    };
+    let resp = model
+        .mailbox_execute(u32::from(CommandId::GET_LDEV_CERT), payload.as_bytes())
+        .unwrap()
+        .unwrap();
+    let mut ldev_resp = GetLdevCertResp::default();
+    ldev_resp.as_mut_bytes()[..resp.len()].copy_from_slice(&resp);
+    ldev_resp.data[..ldev_resp.data_size as usize].to_vec()
+}
+
+#[test]
+fn test_get_cert_chain() {
+    let mut model = run_rt_test(RuntimeTestArgs::default());
+
+    let chain = get_full_cert_chain(&mut model, 0);
+
+    let ldev_cert = get_ldev_cert(&mut model);
+    let fmc_resp = get_fmc_alias_cert(&mut model);
+    let rt_resp = get_rt_alias_cert(&mut model);
+    let expected = [
+        ldev_cert.as_slice(),
+        &fmc_resp.data[..fmc_resp.data_size as usize],
+        &rt_resp.data[..rt_resp.data_size as usize],
+    ]
+    .concat();
+    assert_eq!(chain, expected);
+
+    let certs = split_cert_chain(&chain);
+    assert_eq!(certs.len(), 3);
+
+    let chain = DiceCertChain::from_der(&chain, false, false).unwrap();
+    verify_cert_chain(&chain, &VerifyOptions::default()).unwrap();
+}
+
+#[test]
+fn test_get_cert_chain_with_dpe_leaf() {
+    let mut model = run_rt_test(RuntimeTestArgs::default());
+
+    let chain = get_full_cert_chain(&mut model, 0);
+    let chain_with_leaf =
+        get_full_cert_chain(&mut model, GetCertChainFlags::INCLUDE_DPE_LEAF.bits());
+    assert_eq!(&chain_with_leaf[..chain.len()], chain.as_slice());
+
+    let certify_key_cmd = CertifyKeyCmd {
+        handle: ContextHandle::default(),
+        label: TEST_LABEL,
+        flags: CertifyKeyFlags::empty(),
+        format: CertifyKeyCmd::FORMAT_X509,
+    };
+    let resp = execute_dpe_cmd(
+        &mut model,
+        &mut Command::CertifyKey(&certify_key_cmd),
+        DpeResult::Success,
+    );
+    let Some(Response::CertifyKey(certify_key_resp)) = resp else {
+        panic!("Wrong response type!");
+    };
+    assert_eq!(
+        &chain_with_leaf[chain.len()..],
+        &certify_key_resp.cert[..certify_key_resp.cert_size as usize]
+    );
+
+    let certs = split_cert_chain(&chain_with_leaf);
+    assert_eq!(certs.len(), 4);
+    let rt_alias_pub_key = certs[2].public_key().unwrap();
+    assert!(certs[3].verify(&rt_alias_pub_key).unwrap());
+}
+
+#[test]
+fn test_get_cert_chain_exact_multiple_of_size() {
+    let mut model = run_rt_test(RuntimeTestArgs::default());
+
+    for flags in [0, GetCertChainFlags::INCLUDE_DPE_LEAF.bits()] {
+        let chain = get_full_cert_chain(&mut model, flags);
+
+        // Page with a size that divides the chain length, so the last page
+        // ends exactly at the end of the chain
+        let size = (1..=GetCertChainResp::DATA_MAX_SIZE)
+            .rev()
+            .find(|size| chain.len() % size == 0)
+            .unwrap();
+        let (paged_chain, last_data_size) = get_paged_cert_chain(&mut model, flags, size);
+        assert_eq!(paged_chain, chain);
+        assert_eq!(last_data_size, 0);
+
+        let resp = get_cert_chain(
+            &mut model,
+            chain.len() as u32,
+            GetCertChainResp::DATA_MAX_SIZE as u32,
+            GetCertChainFlags(flags),
+        );
+        assert_eq!(resp.data_size, 0);
+    }
+}
+
+#[test]
+fn test_get_cert_chain_invalid_params() {
+    let mut model = run_rt_test(RuntimeTestArgs::default());
+
+    let chain = get_full_cert_chain(&mut model, 0);
+
+    // Offset past the end of the chain
+    let mut cmd = MailboxReq::GetCertChain(GetCertChainReq {
+        offset: chain.len() as u32 + 1,
+        ..Default::default()
+    });
+    cmd.populate_chksum().unwrap();
+    let resp = model
+        .mailbox_execute(
+            u32::from(CommandId::GET_CERT_CHAIN),
+            cmd.as_bytes().unwrap(),
+        )
+        .unwrap_err();
+    assert_error(
+        &mut model,
+        CaliptraError::RUNTIME_GET_CERT_CHAIN_INVALID_OFFSET,
+        resp,
+    );
+
+    // Size larger than the response buffer
+    let mut cmd = MailboxReq::GetCertChain(GetCertChainReq {
+        size: GetCertChainResp::DATA_MAX_SIZE as u32 + 1,
+        ..Default::default()
+    });
+    cmd.populate_chksum().unwrap();
+    let resp = model
+        .mailbox_execute(
+            u32::from(CommandId::GET_CERT_CHAIN),
+            cmd.as_bytes().unwrap(),
+        )
+        .unwrap_err();
+    assert_error(
+        &mut model,
+        CaliptraError::RUNTIME_MAILBOX_INVALID_PARAMS,
+        resp,
+    );
+}
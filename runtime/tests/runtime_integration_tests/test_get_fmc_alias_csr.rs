@@ -0,0 +1,92 @@
+// Licensed under the Apache-2.0 license
+
+use caliptra_builder::{
+    firmware::{APP_WITH_UART, FMC_WITH_UART},
+    ImageOptions,
+};
+use caliptra_common::mailbox_api::{
+    CommandId, GetFmcAliasCsrResp, MailboxReqHeader, MailboxRespHeader,
+};
+use caliptra_hw_model::{DefaultHwModel, HwModel};
+use openssl::{nid::Nid, x509::X509Req, x509::X509};
+use zerocopy::IntoBytes;
+
+use crate::common::{get_fmc_alias_cert, run_rt_test, RuntimeTestArgs};
+
+fn get_fmc_alias_csr(model: &mut DefaultHwModel) -> Vec<u8> {
+    let payload = MailboxReqHeader {
+        chksum: caliptra_common::checksum::calc_checksum(
+            u32::from(CommandId::GET_FMC_ALIAS_CSR),
+            &[],
+        ),
+    };
+
+    let response = model
+        .mailbox_execute(CommandId::GET_FMC_ALIAS_CSR.into(), payload.as_bytes())
+        .unwrap()
+        .unwrap();
+    assert!(response.len() <= std::mem::size_of::<GetFmcAliasCsrResp>());
+    let mut csr_resp = GetFmcAliasCsrResp::default();
+    csr_resp.as_mut_bytes()[..response.len()].copy_from_slice(&response);
+    assert_eq!(
+        csr_resp.hdr.fips_status,
+        MailboxRespHeader::FIPS_STATUS_APPROVED
+    );
+
+    csr_resp.data[..csr_resp.data_size as usize].to_vec()
+}
+
+#[test]
+fn test_get_fmc_alias_csr() {
+    let mut model = run_rt_test(RuntimeTestArgs::default());
+
+    let csr_bytes = get_fmc_alias_csr(&mut model);
+    let csr = X509Req::from_der(&csr_bytes).unwrap();
+
+    // The CSR is self-signed by the FMC Alias key
+    let csr_pub_key = csr.public_key().unwrap();
+    assert!(csr.verify(&csr_pub_key).unwrap());
+
+    // ... which is the subject key of the FMC Alias certificate
+    let fmc_resp = get_fmc_alias_cert(&mut model);
+    let fmc_cert = X509::from_der(&fmc_resp.data[..fmc_resp.data_size as usize]).unwrap();
+    assert!(csr_pub_key.public_eq(&fmc_cert.public_key().unwrap()));
+
+    let subject_sn = |name: &openssl::x509::X509NameRef| {
+        name.entries_by_nid(Nid::SERIALNUMBER)
+            .next()
+            .unwrap()
+            .data()
+            .as_slice()
+            .to_vec()
+    };
+    assert_eq!(
+        subject_sn(csr.subject_name()),
+        subject_sn(fmc_cert.subject_name())
+    );
+}
+
+#[test]
+fn test_get_fmc_alias_csr_after_update_reset() {
+    let mut model = run_rt_test(RuntimeTestArgs::default());
+
+    let csr_before = get_fmc_alias_csr(&mut model);
+
+    // The FMC Alias key is unchanged by an update reset, so the CSR
+    // generated on cold reset must still be served.
+    let image = caliptra_builder::build_and_sign_image(
+        &FMC_WITH_UART,
+        &APP_WITH_UART,
+        ImageOptions::default(),
+    )
+    .unwrap()
+    .to_bytes()
+    .unwrap();
+    model
+        .mailbox_execute(u32::from(CommandId::FIRMWARE_LOAD), &image)
+        .unwrap();
+
+    let csr_after = get_fmc_alias_csr(&mut model);
+    assert_eq!(csr_before, csr_after);
+    assert!(X509Req::from_der(&csr_after).is_ok());
+}
@@ -20,7 +20,9 @@ use caliptra_emu_periph::{
     CaliptraRootBus, CaliptraRootBusArgs, DownloadIdevidCsrCb, MailboxInternal, ReadyForFwCb,
     TbServicesCb, UploadUpdateFwCb,
 };
-use caliptra_hw_model::BusMmio;
+use caliptra_hw_model::{
+    serve_remote_model, BusMmio, HwModel, ModelEmulated, RemoteAddr, ReproBundle,
+};
 use clap::{arg, value_parser, ArgAction};
 use std::fs::File;
 use std::io;
@@ -67,6 +69,43 @@ fn free_run(mut cpu: Cpu<CaliptraRootBus>, trace_path: Option<PathBuf>) {
     };
 }
 
+// Re-run a failed hw-model test from its reproducer bundle
+fn replay(bundle_dir: &Path) {
+    let bundle = match ReproBundle::load(bundle_dir) {
+        Ok(bundle) => bundle,
+        Err(e) => {
+            println!(
+                "Unable to load reproducer bundle {}: {e}",
+                bundle_dir.display()
+            );
+            exit(-1);
+        }
+    };
+    println!(
+        "Replaying {} (recorded with {}) up to cycle {}",
+        bundle_dir.display(),
+        bundle.model(),
+        bundle.failure_cycle()
+    );
+    let mut model: ModelEmulated = match bundle.replay() {
+        Ok(model) => model,
+        Err(e) => {
+            println!("Unable to replay {}: {e}", bundle_dir.display());
+            exit(-1);
+        }
+    };
+    let output = model.output();
+    println!(
+        "Replay stopped at cycle {}{}",
+        output.sink().now(),
+        if output.exit_requested() {
+            " after the firmware exited"
+        } else {
+            ""
+        }
+    );
+}
+
 fn words_from_bytes_le(arr: &[u8; 48]) -> [u32; 12] {
     let mut result = [0u32; 12];
     for i in 0..result.len() {
@@ -80,6 +119,17 @@ fn main() -> io::Result<()> {
         .about("Caliptra emulator")
         .arg(
             arg!(--"rom" <FILE> "ROM binary path")
+                .required_unless_present_any(["serve", "replay"])
+                .value_parser(value_parser!(PathBuf))
+        )
+        .arg(
+            arg!(--"serve" <ADDR> "Serve remote hw-models on a TCP host:port or unix:<path> socket; the ROM and other settings come from each client")
+                .required(false)
+                .value_parser(value_parser!(RemoteAddr))
+        )
+        .arg(
+            arg!(--"replay" <DIR> "Re-run the reproducer bundle a failed hw-model test wrote to DIR")
+                .required(false)
                 .value_parser(value_parser!(PathBuf))
         )
         .arg(
@@ -154,6 +204,15 @@ fn main() -> io::Result<()> {
         )
         .get_matches();
 
+    if let Some(addr) = args.get_one::<RemoteAddr>("serve") {
+        return serve_remote_model::<ModelEmulated>(addr);
+    }
+
+    if let Some(bundle_dir) = args.get_one::<PathBuf>("replay") {
+        replay(bundle_dir);
+        return Ok(());
+    }
+
     let args_rom = args.get_one::<PathBuf>("rom").unwrap();
     let args_current_fw = args.get_one::<PathBuf>("firmware");
     let args_update_fw = args.get_one::<PathBuf>("update-firmware");
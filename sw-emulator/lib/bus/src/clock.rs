@@ -186,7 +186,8 @@ impl Clock {
                 | TimerAction::SetExtIntVec { .. }
                 | TimerAction::SetGlobalIntEn { .. }
                 | TimerAction::SetExtIntEn { .. }
-                | TimerAction::Halt => {}
+                | TimerAction::Halt
+                | TimerAction::InternalTimerExpired { .. } => {}
             }
         }
         fired_actions
@@ -251,6 +252,7 @@ pub enum TimerAction {
     SetGlobalIntEn { en: bool },
     SetExtIntEn { en: bool },
     Halt,
+    InternalTimerExpired { timer: u8 },
 }
 
 struct ClockImpl {
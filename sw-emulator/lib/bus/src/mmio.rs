@@ -6,7 +6,7 @@ use caliptra_emu_types::RvSize;
 
 use crate::Bus;
 
-const fn rvsize<T>() -> RvSize {
+pub(crate) const fn rvsize<T>() -> RvSize {
     match core::mem::size_of::<T>() {
         1 => RvSize::Byte,
         2 => RvSize::HalfWord,
@@ -15,7 +15,7 @@ const fn rvsize<T>() -> RvSize {
     }
 }
 
-unsafe fn transmute_to_u32<T>(src: &T) -> u32 {
+pub(crate) unsafe fn transmute_to_u32<T>(src: &T) -> u32 {
     match std::mem::size_of::<T>() {
         1 => std::mem::transmute_copy::<T, u8>(src).into(),
         2 => std::mem::transmute_copy::<T, u16>(src).into(),
@@ -24,6 +24,15 @@ unsafe fn transmute_to_u32<T>(src: &T) -> u32 {
     }
 }
 
+pub(crate) unsafe fn transmute_from_u32<T>(val: u32) -> T {
+    match std::mem::size_of::<T>() {
+        1 => std::mem::transmute_copy::<u8, T>(&(val as u8)),
+        2 => std::mem::transmute_copy::<u16, T>(&(val as u16)),
+        4 => std::mem::transmute_copy::<u32, T>(&val),
+        _ => panic!("Unsupported read size"),
+    }
+}
+
 /// An MMIO implementation that reads and writes to a `caliptra_emu_bus::Bus`.
 pub struct BusMmio<TBus: Bus> {
     bus: RefCell<TBus>,
@@ -55,12 +64,7 @@ impl<TBus: Bus> ureg::Mmio for BusMmio<TBus> {
             .borrow_mut()
             .read(rvsize::<T>(), src as usize as u32)
             .unwrap();
-        match std::mem::size_of::<T>() {
-            1 => std::mem::transmute_copy::<u8, T>(&(val_u32 as u8)),
-            2 => std::mem::transmute_copy::<u16, T>(&(val_u32 as u16)),
-            4 => std::mem::transmute_copy::<u32, T>(&val_u32),
-            _ => panic!("Unsupported read size"),
-        }
+        transmute_from_u32(val_u32)
     }
 }
 
@@ -0,0 +1,548 @@
+/*++
+
+Licensed under the Apache-2.0 license.
+
+File Name:
+
+    mmio_trace.rs
+
+Abstract:
+
+    File contains ureg Mmio implementations that record register accesses
+    and replay recorded accesses.
+
+--*/
+use std::{
+    cell::{Cell, RefCell},
+    collections::{BTreeMap, VecDeque},
+    fmt::{self, Write},
+    rc::Rc,
+};
+
+use caliptra_emu_types::{RvAddr, RvData, RvSize};
+use ureg_schema::{RegisterBlock, RegisterSubBlock};
+
+use crate::mmio::{rvsize, transmute_from_u32, transmute_to_u32};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum MmioAccessKind {
+    Read,
+    Write,
+}
+
+/// A single register read or write. For reads, `val` is the value returned
+/// to the driver.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MmioAccess {
+    pub kind: MmioAccessKind,
+    pub size: RvSize,
+    pub addr: RvAddr,
+    pub val: RvData,
+}
+impl fmt::Display for MmioAccess {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            MmioAccessKind::Read => write!(
+                f,
+                "read(RvSize::{:?}, {:#x}) -> {:#x}",
+                self.size, self.addr, self.val
+            ),
+            MmioAccessKind::Write => write!(
+                f,
+                "write(RvSize::{:?}, {:#x}, {:#x})",
+                self.size, self.addr, self.val
+            ),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct NamedRegister {
+    name: String,
+    len: u32,
+    stride: u32,
+}
+
+/// Maps register addresses to human-readable names, so traces and replay
+/// failures can be read without a register map at hand.
+///
+/// # Example
+///
+/// ```
+/// use caliptra_emu_bus::testing::RegisterNames;
+///
+/// let mut names = RegisterNames::new();
+/// names.insert(0x3002_0000, "mbox.lock", 1, 4);
+/// names.insert(0x3002_0100, "mbox.data", 4, 4);
+/// assert_eq!(names.lookup(0x3002_0000).as_deref(), Some("mbox.lock"));
+/// assert_eq!(names.lookup(0x3002_0108).as_deref(), Some("mbox.data[2]"));
+/// assert_eq!(names.lookup(0x3002_0110), None);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct RegisterNames {
+    regs: BTreeMap<RvAddr, NamedRegister>,
+}
+impl RegisterNames {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Names the register (or register array, if `len > 1`) at `addr`.
+    pub fn insert(&mut self, addr: RvAddr, name: impl Into<String>, len: u32, stride: u32) {
+        self.regs.insert(
+            addr,
+            NamedRegister {
+                name: name.into(),
+                len,
+                stride,
+            },
+        );
+    }
+
+    /// Names every register in `block`, as if it were mapped at `base`. Names
+    /// are of the form `block.REGISTER` or `block.sub_block[i].REGISTER`.
+    pub fn add_block(&mut self, block: &RegisterBlock, base: RvAddr) {
+        self.add_registers(block, base, &block.name);
+        for sb in block.sub_blocks.iter() {
+            match sb {
+                RegisterSubBlock::Single {
+                    block: sub_block,
+                    start_offset,
+                } => {
+                    let prefix = format!("{}.{}", block.name, sub_block.name);
+                    self.add_registers(sub_block, base + *start_offset as RvAddr, &prefix);
+                }
+                RegisterSubBlock::Array {
+                    block: sub_block,
+                    start_offset,
+                    stride,
+                    len,
+                } => {
+                    for i in 0..*len {
+                        let prefix = format!("{}.{}[{i}]", block.name, sub_block.name);
+                        let addr = base + (*start_offset + *stride * i as u64) as RvAddr;
+                        self.add_registers(sub_block, addr, &prefix);
+                    }
+                }
+            }
+        }
+    }
+
+    fn add_registers(&mut self, block: &RegisterBlock, base: RvAddr, prefix: &str) {
+        for reg in block.registers.iter() {
+            let len: u64 = reg.array_dimensions.iter().product();
+            self.insert(
+                base + reg.offset as RvAddr,
+                format!("{prefix}.{}", reg.name),
+                len as u32,
+                reg.ty.width.in_bytes() as u32,
+            );
+        }
+    }
+
+    /// Returns the name of the register at `addr`, if known.
+    pub fn lookup(&self, addr: RvAddr) -> Option<String> {
+        let (start, reg) = self.regs.range(..=addr).next_back()?;
+        let offset = addr - start;
+        let index = offset / reg.stride.max(1);
+        if offset % reg.stride.max(1) != 0 || index >= reg.len.max(1) {
+            return None;
+        }
+        if reg.len > 1 {
+            Some(format!("{}[{index}]", reg.name))
+        } else {
+            Some(reg.name.clone())
+        }
+    }
+
+    fn describe(&self, access: &MmioAccess) -> String {
+        match self.lookup(access.addr) {
+            Some(name) => format!("{access} // {name}"),
+            None => access.to_string(),
+        }
+    }
+}
+
+/// Formats `accesses` one per line, with register names from `names` as
+/// trailing comments. The result can be read back with [`parse_trace`].
+pub fn format_trace(accesses: &[MmioAccess], names: &RegisterNames) -> String {
+    let mut result = String::new();
+    for access in accesses {
+        writeln!(result, "{}", names.describe(access)).unwrap();
+    }
+    result
+}
+
+fn parse_size(s: &str) -> Option<RvSize> {
+    match s {
+        "RvSize::Byte" => Some(RvSize::Byte),
+        "RvSize::HalfWord" => Some(RvSize::HalfWord),
+        "RvSize::Word" => Some(RvSize::Word),
+        _ => None,
+    }
+}
+
+fn parse_u32(s: &str) -> Option<u32> {
+    u32::from_str_radix(s.trim().strip_prefix("0x")?, 16).ok()
+}
+
+fn parse_access(line: &str) -> Option<MmioAccess> {
+    let (kind, rest) = if let Some(rest) = line.strip_prefix("read(") {
+        (MmioAccessKind::Read, rest)
+    } else {
+        (MmioAccessKind::Write, line.strip_prefix("write(")?)
+    };
+    let (args, result) = rest.split_once(')')?;
+    let mut args = args.split(',').map(str::trim);
+    let size = parse_size(args.next()?)?;
+    let addr = parse_u32(args.next()?)?;
+    let val = match kind {
+        MmioAccessKind::Read => parse_u32(result.trim().strip_prefix("->")?)?,
+        MmioAccessKind::Write => parse_u32(args.next()?)?,
+    };
+    if args.next().is_some() {
+        return None;
+    }
+    Some(MmioAccess {
+        kind,
+        size,
+        addr,
+        val,
+    })
+}
+
+/// Parses a trace in the format produced by [`format_trace`]. Comments
+/// (starting with `//`) and blank lines are ignored.
+pub fn parse_trace(s: &str) -> Result<Vec<MmioAccess>, String> {
+    let mut result = vec![];
+    for (line_num, line) in s.lines().enumerate() {
+        let line = line.split("//").next().unwrap().trim();
+        if line.is_empty() {
+            continue;
+        }
+        let access = parse_access(line)
+            .ok_or_else(|| format!("Line {}: invalid MMIO access {line:?}", line_num + 1))?;
+        result.push(access);
+    }
+    Ok(result)
+}
+
+/// A ureg Mmio implementation that forwards all accesses to `TMmio` and
+/// records them.
+///
+/// When `RecordingMmio` is cloned, the clones all share the same recording,
+/// so a test can keep a clone to inspect the accesses made through a
+/// register block that owns the original.
+///
+/// # Example
+///
+/// ```
+/// use caliptra_emu_bus::{BusMmio, Ram};
+/// use caliptra_emu_bus::testing::{format_trace, RecordingMmio, RegisterNames};
+/// use ureg::{Mmio, MmioMut};
+///
+/// let mmio = RecordingMmio::new(BusMmio::new(Ram::new(vec![0u8; 8])));
+/// unsafe {
+///     mmio.write_volatile(4 as *mut u32, 0x1234);
+///     mmio.read_volatile(4 as *const u32);
+/// }
+/// let mut names = RegisterNames::new();
+/// names.insert(4, "foo.CTRL", 1, 4);
+/// assert_eq!(
+///     format_trace(&mmio.take(), &names),
+///     "write(RvSize::Word, 0x4, 0x1234) // foo.CTRL\n\
+///      read(RvSize::Word, 0x4) -> 0x1234 // foo.CTRL\n"
+/// );
+/// ```
+#[derive(Clone)]
+pub struct RecordingMmio<TMmio> {
+    inner: TMmio,
+    accesses: Rc<RefCell<Vec<MmioAccess>>>,
+}
+impl<TMmio> RecordingMmio<TMmio> {
+    pub fn new(inner: TMmio) -> Self {
+        Self {
+            inner,
+            accesses: Default::default(),
+        }
+    }
+
+    /// Returns the accesses recorded so far.
+    pub fn accesses(&self) -> Vec<MmioAccess> {
+        self.accesses.borrow().clone()
+    }
+
+    /// Returns the accesses recorded so far and clears the recording.
+    pub fn take(&self) -> Vec<MmioAccess> {
+        std::mem::take(&mut *self.accesses.borrow_mut())
+    }
+}
+impl<TMmio: ureg::Mmio> ureg::Mmio for RecordingMmio<TMmio> {
+    /// Reads from `src` via the inner Mmio and records the access.
+    ///
+    /// # Safety
+    ///
+    /// Same as the inner Mmio implementation.
+    unsafe fn read_volatile<T: ureg::Uint>(&self, src: *const T) -> T {
+        let result = self.inner.read_volatile(src);
+        self.accesses.borrow_mut().push(MmioAccess {
+            kind: MmioAccessKind::Read,
+            size: rvsize::<T>(),
+            addr: src as usize as RvAddr,
+            val: transmute_to_u32(&result),
+        });
+        result
+    }
+}
+impl<TMmio: ureg::MmioMut> ureg::MmioMut for RecordingMmio<TMmio> {
+    /// Writes `src` to `dst` via the inner Mmio and records the access.
+    ///
+    /// # Safety
+    ///
+    /// Same as the inner Mmio implementation.
+    unsafe fn write_volatile<T: ureg::Uint>(&self, dst: *mut T, src: T) {
+        self.accesses.borrow_mut().push(MmioAccess {
+            kind: MmioAccessKind::Write,
+            size: rvsize::<T>(),
+            addr: dst as usize as RvAddr,
+            val: transmute_to_u32(&src),
+        });
+        self.inner.write_volatile(dst, src)
+    }
+}
+
+/// A ureg Mmio implementation that serves reads from a recorded trace and
+/// panics if the driver's accesses deviate from the trace.
+///
+/// # Example
+///
+/// ```
+/// use caliptra_emu_bus::testing::{parse_trace, ReplayMmio};
+/// use ureg::{Mmio, MmioMut};
+///
+/// let mmio = ReplayMmio::new(parse_trace("
+///     write(RvSize::Word, 0x4, 0x1)
+///     read(RvSize::Word, 0x8) -> 0x3 // status
+/// ").unwrap());
+/// unsafe {
+///     mmio.write_volatile(4 as *mut u32, 1);
+///     assert_eq!(mmio.read_volatile(8 as *const u32), 3);
+/// }
+/// mmio.assert_finished();
+/// ```
+pub struct ReplayMmio {
+    expected: RefCell<VecDeque<MmioAccess>>,
+    names: RegisterNames,
+    count: Cell<usize>,
+}
+impl ReplayMmio {
+    pub fn new(accesses: Vec<MmioAccess>) -> Self {
+        Self {
+            expected: RefCell::new(accesses.into()),
+            names: RegisterNames::new(),
+            count: Cell::new(0),
+        }
+    }
+
+    /// Uses `names` to describe registers in failure messages.
+    pub fn with_names(mut self, names: RegisterNames) -> Self {
+        self.names = names;
+        self
+    }
+
+    /// Returns the number of recorded accesses that haven't been replayed.
+    pub fn remaining(&self) -> usize {
+        self.expected.borrow().len()
+    }
+
+    /// Panics if any recorded accesses haven't been replayed.
+    pub fn assert_finished(&self) {
+        if let Some(next) = self.expected.borrow().front() {
+            panic!(
+                "MMIO replay finished early after {} accesses; {} remaining, starting with {}",
+                self.count.get(),
+                self.remaining(),
+                self.names.describe(next)
+            );
+        }
+    }
+
+    /// Checks `actual` against the next recorded access and returns the
+    /// recorded value.
+    fn replay(&self, actual: MmioAccess) -> RvData {
+        let index = self.count.get();
+        self.count.set(index + 1);
+        let Some(expected) = self.expected.borrow_mut().pop_front() else {
+            panic!(
+                "MMIO access #{index} {} is past the end of the trace",
+                self.names.describe(&actual)
+            );
+        };
+        let matches = expected.kind == actual.kind
+            && expected.size == actual.size
+            && expected.addr == actual.addr
+            && (actual.kind == MmioAccessKind::Read || expected.val == actual.val);
+        if !matches {
+            panic!(
+                "MMIO access #{index} was {}, but the trace expected {}",
+                self.names.describe(&actual),
+                self.names.describe(&expected)
+            );
+        }
+        expected.val
+    }
+}
+impl ureg::Mmio for ReplayMmio {
+    /// Returns the value recorded for the next access.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the next recorded access isn't a read of the same size from
+    /// `src`.
+    ///
+    /// # Safety
+    ///
+    /// As the pointer isn't read from, this Mmio implementation isn't actually
+    /// unsafe for POD types like u8/u16/u32.
+    unsafe fn read_volatile<T: ureg::Uint>(&self, src: *const T) -> T {
+        transmute_from_u32(self.replay(MmioAccess {
+            kind: MmioAccessKind::Read,
+            size: rvsize::<T>(),
+            addr: src as usize as RvAddr,
+            val: 0,
+        }))
+    }
+}
+impl ureg::MmioMut for ReplayMmio {
+    /// Checks the write against the next recorded access.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the next recorded access isn't a write of `src` to `dst`.
+    ///
+    /// # Safety
+    ///
+    /// As the pointer isn't written to, this Mmio implementation isn't actually
+    /// unsafe for POD types like u8/u16/u32.
+    unsafe fn write_volatile<T: ureg::Uint>(&self, dst: *mut T, src: T) {
+        self.replay(MmioAccess {
+            kind: MmioAccessKind::Write,
+            size: rvsize::<T>(),
+            addr: dst as usize as RvAddr,
+            val: transmute_to_u32(&src),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BusMmio, Ram};
+    use ureg::{Mmio, MmioMut};
+    use ureg_schema::{Register, RegisterType};
+
+    fn names() -> RegisterNames {
+        let reg = |name: &str, offset, array_dimensions| {
+            Rc::new(Register {
+                name: name.into(),
+                offset,
+                array_dimensions,
+                ty: Rc::new(RegisterType::default()),
+                ..Default::default()
+            })
+        };
+        let mut names = RegisterNames::new();
+        names.add_block(
+            &RegisterBlock {
+                name: "foo".into(),
+                registers: vec![reg("CTRL", 0, vec![]), reg("DATA", 4, vec![2])],
+                sub_blocks: vec![RegisterSubBlock::Array {
+                    block: RegisterBlock {
+                        name: "chan".into(),
+                        registers: vec![reg("STATUS", 0, vec![])],
+                        ..Default::default()
+                    },
+                    start_offset: 0x10,
+                    stride: 8,
+                    len: 2,
+                }],
+                ..Default::default()
+            },
+            0x1000,
+        );
+        names
+    }
+
+    #[test]
+    fn test_register_names() {
+        let names = names();
+        assert_eq!(names.lookup(0x1000).as_deref(), Some("foo.CTRL"));
+        assert_eq!(names.lookup(0x1008).as_deref(), Some("foo.DATA[1]"));
+        assert_eq!(names.lookup(0x100c), None);
+        assert_eq!(names.lookup(0x1018).as_deref(), Some("foo.chan[1].STATUS"));
+        assert_eq!(names.lookup(0xfff), None);
+    }
+
+    #[test]
+    fn test_record_and_replay() {
+        let mmio = RecordingMmio::new(BusMmio::new(Ram::new(vec![0u8; 0x20])));
+        unsafe {
+            mmio.write_volatile(0x4 as *mut u32, 0x3abc_9321);
+            mmio.write_volatile(0x8 as *mut u16, 0x39af);
+            assert_eq!(mmio.read_volatile(0x4 as *const u32), 0x3abc_9321);
+            assert_eq!(mmio.read_volatile(0x8 as *const u8), 0xaf);
+        }
+        let trace = format_trace(&mmio.accesses(), &RegisterNames::new());
+        assert_eq!(
+            trace,
+            "write(RvSize::Word, 0x4, 0x3abc9321)\n\
+             write(RvSize::HalfWord, 0x8, 0x39af)\n\
+             read(RvSize::Word, 0x4) -> 0x3abc9321\n\
+             read(RvSize::Byte, 0x8) -> 0xaf\n"
+        );
+        assert_eq!(parse_trace(&trace).unwrap(), mmio.take());
+        assert!(mmio.accesses().is_empty());
+
+        let replay = ReplayMmio::new(parse_trace(&trace).unwrap());
+        unsafe {
+            replay.write_volatile(0x4 as *mut u32, 0x3abc_9321);
+            replay.write_volatile(0x8 as *mut u16, 0x39af);
+            assert_eq!(replay.read_volatile(0x4 as *const u32), 0x3abc_9321);
+            assert_eq!(replay.read_volatile(0x8 as *const u8), 0xaf);
+        }
+        replay.assert_finished();
+    }
+
+    #[test]
+    fn test_parse_trace_error() {
+        assert_eq!(
+            parse_trace("read(RvSize::Word, 0x4) -> 0x1\nread(RvSize::Word, 0x4)"),
+            Err("Line 2: invalid MMIO access \"read(RvSize::Word, 0x4)\"".into())
+        );
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "MMIO access #1 was write(RvSize::Word, 0x1000, 0x2) // foo.CTRL, but the trace expected write(RvSize::Word, 0x1000, 0x1) // foo.CTRL"
+    )]
+    fn test_replay_mismatch() {
+        let replay = ReplayMmio::new(
+            parse_trace(
+                "read(RvSize::Word, 0x1018) -> 0x0\n\
+                 write(RvSize::Word, 0x1000, 0x1)",
+            )
+            .unwrap(),
+        )
+        .with_names(names());
+        unsafe {
+            replay.read_volatile(0x1018 as *const u32);
+            replay.write_volatile(0x1000 as *mut u32, 2);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "MMIO replay finished early after 0 accesses; 1 remaining")]
+    fn test_replay_unfinished() {
+        ReplayMmio::new(parse_trace("read(RvSize::Word, 0x4) -> 0x1").unwrap()).assert_finished();
+    }
+}
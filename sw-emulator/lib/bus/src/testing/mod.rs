@@ -13,6 +13,10 @@ Abstract:
 --*/
 mod fake_bus;
 mod log;
+mod mmio_trace;
 
 pub use fake_bus::FakeBus;
 pub use log::Log;
+pub use mmio_trace::{
+    format_trace, parse_trace, MmioAccess, MmioAccessKind, RecordingMmio, RegisterNames, ReplayMmio,
+};
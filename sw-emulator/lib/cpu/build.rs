@@ -0,0 +1,36 @@
+/*++
+
+Licensed under the Apache-2.0 license.
+
+File Name:
+
+    build.rs
+
+Abstract:
+
+    Generates the register storage and bus dispatch of the PIC from
+    el2_pic_ctrl.rdl.
+
+--*/
+
+use std::path::PathBuf;
+
+fn main() {
+    let manifest_dir = PathBuf::from(std::env::var_os("CARGO_MANIFEST_DIR").unwrap());
+    let out_dir = PathBuf::from(std::env::var_os("OUT_DIR").unwrap());
+    let rdl_file = manifest_dir.join("../../../registers/bin/extra-rdl/el2_pic_ctrl.rdl");
+    println!("cargo:rerun-if-changed={}", rdl_file.display());
+
+    let file_source = caliptra_systemrdl::FsFileSource::new();
+    let scope =
+        caliptra_systemrdl::Scope::parse_root(&file_source, std::slice::from_ref(&rdl_file))
+            .unwrap_or_else(|e| panic!("{}: {e}", rdl_file.display()));
+    let scope = scope.as_parent();
+    let addrmap = scope.lookup_typedef("clp2").unwrap();
+    for block in ureg_systemrdl::translate_addrmap(addrmap).unwrap() {
+        let block = block.validate_and_dedup().unwrap();
+        let code = ureg_codegen::generate_emulator_code(&block);
+        let dest_file = out_dir.join(format!("{}.rs", block.block().name));
+        std::fs::write(dest_file, code.to_string()).unwrap();
+    }
+}
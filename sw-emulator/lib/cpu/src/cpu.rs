@@ -146,6 +146,10 @@ impl StackInfo {
 pub struct CodeCoverage {
     rom_bit_vec: BitVec,
     iccm_bit_vec: BitVec,
+    rom_branch_taken: BitVec,
+    rom_branch_not_taken: BitVec,
+    iccm_branch_taken: BitVec,
+    iccm_branch_not_taken: BitVec,
 }
 
 pub struct CoverageBitmaps<'a> {
@@ -153,6 +157,18 @@ pub struct CoverageBitmaps<'a> {
     pub iccm: &'a bit_vec::BitVec,
 }
 
+/// Outcomes seen for the conditional branches in one memory region, indexed
+/// by the byte offset of the branch instruction.
+pub struct BranchBitmaps<'a> {
+    pub taken: &'a bit_vec::BitVec,
+    pub not_taken: &'a bit_vec::BitVec,
+}
+
+pub struct BranchCoverageBitmaps<'a> {
+    pub rom: BranchBitmaps<'a>,
+    pub iccm: BranchBitmaps<'a>,
+}
+
 const ICCM_SIZE: usize = 128 * 1024;
 const ICCM_ORG: usize = 0x40000000;
 const ICCM_UPPER: usize = ICCM_ORG + ICCM_SIZE - 1;
@@ -166,6 +182,10 @@ impl CodeCoverage {
         Self {
             rom_bit_vec: BitVec::from_elem(rom_capacity_in_bytes, false),
             iccm_bit_vec: BitVec::from_elem(iccm_capacity_in_bytes, false),
+            rom_branch_taken: BitVec::from_elem(rom_capacity_in_bytes, false),
+            rom_branch_not_taken: BitVec::from_elem(rom_capacity_in_bytes, false),
+            iccm_branch_taken: BitVec::from_elem(iccm_capacity_in_bytes, false),
+            iccm_branch_not_taken: BitVec::from_elem(iccm_capacity_in_bytes, false),
         }
     }
 
@@ -198,12 +218,56 @@ impl CodeCoverage {
         }
     }
 
+    /// Record the outcome of an executed instruction if it is a conditional
+    /// branch (`beq`, `bne`, `blt`, `bge`, `bltu`, `bgeu`, `c.beqz` or
+    /// `c.bnez`).
+    ///
+    /// # Arguments
+    ///
+    /// * `pc` - Address of the instruction
+    /// * `instr` - The instruction
+    /// * `next_pc` - Address of the next instruction to execute
+    pub fn log_branch(&mut self, pc: RvData, instr: &Instr, next_pc: RvData) {
+        let (is_branch, num_bytes) = match instr {
+            Instr::Compressed(instr) => (instr & 0b11 == 0b01 && (instr >> 13) >= 0b110, 2),
+            Instr::General(instr) => (instr & 0x7f == 0b110_0011, 4),
+        };
+        if !is_branch {
+            return;
+        }
+        let taken = next_pc != pc.wrapping_add(num_bytes);
+
+        let (bit_vec, byte_index) = match pc as usize {
+            ROM_ORG..=ROM_UPPER if taken => (&mut self.rom_branch_taken, pc as usize - ROM_ORG),
+            ROM_ORG..=ROM_UPPER => (&mut self.rom_branch_not_taken, pc as usize - ROM_ORG),
+            ICCM_ORG..=ICCM_UPPER if taken => (&mut self.iccm_branch_taken, pc as usize - ICCM_ORG),
+            ICCM_ORG..=ICCM_UPPER => (&mut self.iccm_branch_not_taken, pc as usize - ICCM_ORG),
+            _ => return,
+        };
+        if byte_index < bit_vec.len() {
+            bit_vec.set(byte_index, true);
+        }
+    }
+
     pub fn code_coverage_bitmap(&self) -> CoverageBitmaps {
         CoverageBitmaps {
             rom: &self.rom_bit_vec,
             iccm: &self.iccm_bit_vec,
         }
     }
+
+    pub fn branch_coverage_bitmaps(&self) -> BranchCoverageBitmaps {
+        BranchCoverageBitmaps {
+            rom: BranchBitmaps {
+                taken: &self.rom_branch_taken,
+                not_taken: &self.rom_branch_not_taken,
+            },
+            iccm: BranchBitmaps {
+                taken: &self.iccm_branch_taken,
+                not_taken: &self.iccm_branch_not_taken,
+            },
+        }
+    }
 }
 
 #[derive(PartialEq)]
@@ -568,34 +632,44 @@ impl<TBus: Bus> Cpu<TBus> {
         for action_type in fired_action_types.iter() {
             match action_type {
                 TimerAction::WarmReset => {
-                    self.halted = false;
+                    self.set_halted(false);
                     self.reset_pc();
                     break;
                 }
                 TimerAction::UpdateReset => {
-                    self.halted = false;
+                    self.set_halted(false);
                     self.reset_pc();
                     break;
                 }
                 TimerAction::Nmi { mcause } => {
-                    self.halted = false;
+                    self.set_halted(false);
                     return self.handle_nmi(*mcause, 0);
                 }
                 TimerAction::SetNmiVec { addr } => self.nmivec = *addr,
                 TimerAction::ExtInt { irq, can_wake } => {
                     if self.global_int_en && self.ext_int_en && (!self.halted || *can_wake) {
-                        self.halted = false;
+                        self.set_halted(false);
                         return self.handle_external_int(*irq);
                     }
                 }
                 TimerAction::SetExtIntVec { addr } => self.ext_int_vec = *addr,
                 TimerAction::SetGlobalIntEn { en } => self.global_int_en = *en,
                 TimerAction::SetExtIntEn { en } => self.ext_int_en = *en,
-                TimerAction::Halt => self.halted = true,
+                TimerAction::Halt => self.set_halted(true),
+                TimerAction::InternalTimerExpired { timer } => {
+                    self.csrs.internal_timer_expired(*timer)
+                }
                 _ => {}
             }
         }
 
+        if self.global_int_en {
+            if let Some(cause) = self.csrs.take_internal_timer_int() {
+                self.set_halted(false);
+                return self.handle_internal_timer_int(cause);
+            }
+        }
+
         // We are in a halted state. Don't continue executing but poll the bus for interrupts
         if self.halted {
             self.set_next_pc(self.pc);
@@ -608,6 +682,11 @@ impl<TBus: Bus> Cpu<TBus> {
         }
     }
 
+    fn set_halted(&mut self, halted: bool) {
+        self.halted = halted;
+        self.csrs.set_halted(halted);
+    }
+
     /// Handle synchronous exception
     fn handle_exception(&mut self, exception: RvException) -> StepAction {
         let ret = self.handle_trap(
@@ -693,6 +772,22 @@ impl<TBus: Bus> Cpu<TBus> {
         }
     }
 
+    /// Handle internal timer interrupts (VeeR-specific)
+    fn handle_internal_timer_int(&mut self, cause: u32) -> StepAction {
+        // Cannot panic; mtvec is a valid CSR
+        let mtvec = self.read_csr(Csr::MTVEC).unwrap();
+        let mut next_pc = mtvec & !0b11;
+        if mtvec & 0b11 == 1 {
+            // Vectored mode
+            next_pc += 4 * (cause & !0x8000_0000);
+        }
+        let ret = self.handle_trap(self.read_pc(), cause, 0, next_pc);
+        match ret {
+            Ok(_) => StepAction::Continue,
+            Err(_) => StepAction::Fatal,
+        }
+    }
+
     //// Append WatchPointer
     pub fn add_watchptr(&mut self, addr: u32, len: u32, kind: WatchPtrKind) {
         for addr in addr..(addr + len) {
@@ -721,6 +816,7 @@ impl<TBus: Bus> Cpu<TBus> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::types::RvMIE;
     use caliptra_emu_bus::{testing::FakeBus, DynamicBus, Rom, Timer};
 
     #[test]
@@ -783,6 +879,42 @@ mod tests {
         assert_eq!(cpu.read_pc(), 31 * 4);
     }
 
+    #[test]
+    fn test_internal_timer_int() {
+        const RV32_NO_OP: u32 = 0x00000013;
+
+        let clock = Clock::new();
+        let mut bus = DynamicBus::new();
+        let rom = Rom::new(
+            std::iter::repeat(RV32_NO_OP)
+                .take(256)
+                .flat_map(u32::to_le_bytes)
+                .collect(),
+        );
+        bus.attach_dev("ROM", 0..=0x3ff, Box::new(rom)).unwrap();
+
+        let mut cpu = Cpu::new(bus, clock);
+        cpu.write_csr(Csr::MTVEC, 0x200).unwrap();
+        let mut mie = RvMIE(0);
+        mie.set_mitie0(1);
+        cpu.write_csr(Csr::MIE, mie.0).unwrap();
+        let mut mstatus = RvMStatus(cpu.read_csr(Csr::MSTATUS).unwrap());
+        mstatus.set_mie(1);
+        cpu.write_csr(Csr::MSTATUS, mstatus.0).unwrap();
+        cpu.write_csr(Csr::MITB0, 20).unwrap();
+        cpu.write_csr(Csr::MITCNT0, 0).unwrap();
+
+        for _ in 0..19 {
+            assert_eq!(cpu.step(None), StepAction::Continue);
+            assert!(cpu.read_pc() < 0x200);
+        }
+        assert_eq!(cpu.step(None), StepAction::Continue);
+        assert_eq!(cpu.read_pc(), 0x200);
+        assert_eq!(cpu.read_csr(Csr::MCAUSE).ok(), Some(0x8000_001D));
+        assert_eq!(cpu.read_csr(Csr::MEPC).ok(), Some(19 * 4));
+        assert_eq!(RvMStatus(cpu.read_csr(Csr::MSTATUS).unwrap()).mie(), 0);
+    }
+
     pub fn count_executed(coverage: &CodeCoverage) -> usize {
         coverage
             .rom_bit_vec
@@ -811,4 +943,26 @@ mod tests {
         // Check for expected values
         assert_eq!(count_executed(&coverage), 8);
     }
+
+    #[test]
+    fn test_branch_coverage() {
+        // beq x0, x0, 8
+        let beq = Instr::General(0x0000_0463);
+        // c.bnez x8, 4
+        let c_bnez = Instr::Compressed(0xe011);
+        // addi x0, x0, 0
+        let nop = Instr::General(0x0000_0013);
+
+        let mut coverage = CodeCoverage::new(16, 0);
+        coverage.log_branch(0, &beq, 8);
+        coverage.log_branch(4, &c_bnez, 6);
+        coverage.log_branch(8, &nop, 12);
+        coverage.log_branch(8, &c_bnez, 12);
+
+        let BranchBitmaps { taken, not_taken } = coverage.branch_coverage_bitmaps().rom;
+        let taken: Vec<usize> = (0..16).filter(|&i| taken[i]).collect();
+        let not_taken: Vec<usize> = (0..16).filter(|&i| not_taken[i]).collect();
+        assert_eq!(taken, vec![0, 8]);
+        assert_eq!(not_taken, vec![4]);
+    }
 }
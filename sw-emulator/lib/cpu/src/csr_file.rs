@@ -12,6 +12,7 @@ Abstract:
 
 --*/
 
+use crate::internal_timer::{InternalTimers, MIP_MITIP0, MIP_MITIP1};
 use crate::types::{RvMIE, RvMPMC, RvMStatus};
 use caliptra_emu_bus::{Clock, Timer, TimerAction};
 use caliptra_emu_types::{RvAddr, RvData, RvException};
@@ -69,6 +70,24 @@ impl Csr {
     /// Power management const CSR
     pub const MPMC: RvAddr = 0x7C6;
 
+    /// Internal Timer Counter 0 CSR
+    pub const MITCNT0: RvAddr = 0x7D2;
+
+    /// Internal Timer Bound 0 CSR
+    pub const MITB0: RvAddr = 0x7D3;
+
+    /// Internal Timer Control 0 CSR
+    pub const MITCTL0: RvAddr = 0x7D4;
+
+    /// Internal Timer Counter 1 CSR
+    pub const MITCNT1: RvAddr = 0x7D5;
+
+    /// Internal Timer Bound 1 CSR
+    pub const MITB1: RvAddr = 0x7D6;
+
+    /// Internal Timer Control 1 CSR
+    pub const MITCTL1: RvAddr = 0x7D7;
+
     /// Cycle Low Counter CSR
     pub const MCYCLE: RvAddr = 0xB00;
 
@@ -105,6 +124,8 @@ pub struct CsrFile {
     csrs: [Csr; CsrFile::CSR_COUNT],
     /// Timer
     timer: Timer,
+    /// Internal timers
+    internal_timers: InternalTimers,
 }
 
 impl CsrFile {
@@ -116,6 +137,7 @@ impl CsrFile {
         let mut csrs = Self {
             csrs: [Csr::new(0, 0); CsrFile::CSR_COUNT],
             timer: Timer::new(clock),
+            internal_timers: InternalTimers::new(clock),
         };
 
         csrs.reset();
@@ -139,6 +161,8 @@ impl CsrFile {
         self.csrs[Csr::MTVAL as usize] = Csr::new(0x0000_0000, 0xFFFF_FFFF);
         self.csrs[Csr::MIP as usize] = Csr::new(0x0000_0000, 0xFFFF_FFFF);
         self.csrs[Csr::MPMC as usize] = Csr::new(0x0000_0002, 0x0000_0002);
+        self.csrs[Csr::MITCTL0 as usize] = Csr::new(0x0000_0001, 0x0000_0007);
+        self.csrs[Csr::MITCTL1 as usize] = Csr::new(0x0000_0001, 0x0000_000F);
         self.csrs[Csr::MCYCLE as usize] = Csr::new(0x0000_0000, 0xFFFF_FFFF);
         self.csrs[Csr::MCYCLEH as usize] = Csr::new(0x0000_0000, 0xFFFF_FFFF);
         self.csrs[Csr::MINSTRET as usize] = Csr::new(0x0000_0000, 0xFFFF_FFFF);
@@ -164,7 +188,13 @@ impl CsrFile {
         let addr = addr as usize;
         const CSR_MAX: usize = CsrFile::CSR_COUNT - 1;
         match addr {
-            0..=CSR_MAX => Ok(self.csrs[addr].val),
+            0..=CSR_MAX => Ok(match addr as RvAddr {
+                Csr::MITCNT0 => self.internal_timers.count(0),
+                Csr::MITB0 => self.internal_timers.bound(0),
+                Csr::MITCNT1 => self.internal_timers.count(1),
+                Csr::MITB1 => self.internal_timers.bound(1),
+                _ => self.csrs[addr].val,
+            }),
             _ => Err(RvException::illegal_register()),
         }
     }
@@ -187,6 +217,16 @@ impl CsrFile {
                 let csr = &mut self.csrs[addr];
                 csr.val = (csr.val & !csr.mask) | (val & csr.mask);
 
+                match addr as RvAddr {
+                    Csr::MITCNT0 => self.internal_timers.set_count(0, val),
+                    Csr::MITB0 => self.internal_timers.set_bound(0, val),
+                    Csr::MITCTL0 => self.internal_timers.set_ctl(0, csr.val),
+                    Csr::MITCNT1 => self.internal_timers.set_count(1, val),
+                    Csr::MITB1 => self.internal_timers.set_bound(1, val),
+                    Csr::MITCTL1 => self.internal_timers.set_ctl(1, csr.val),
+                    _ => {}
+                }
+
                 if addr == Csr::MEIVT as usize {
                     self.timer
                         .schedule_action_in(0, TimerAction::SetExtIntVec { addr: csr.val });
@@ -230,6 +270,38 @@ impl CsrFile {
             _ => Err(RvException::illegal_register()),
         }
     }
+
+    /// Tell the internal timers whether the core is halted
+    pub(crate) fn set_halted(&mut self, halted: bool) {
+        self.internal_timers.set_halted(halted);
+    }
+
+    /// Set the interrupt pending bits of internal timer `timer` (and any
+    /// timer cascaded from it) when it reaches its bound.
+    pub(crate) fn internal_timer_expired(&mut self, timer: u8) {
+        let mip = self.internal_timers.expire(timer.into());
+        self.csrs[Csr::MIP as usize].val |= mip;
+    }
+
+    /// If an internal timer interrupt is pending and enabled in `mie`, clear
+    /// its pending bit and return its `mcause`. Timer 0 has priority over
+    /// timer 1.
+    pub(crate) fn take_internal_timer_int(&mut self) -> Option<u32> {
+        const MITINT0: u32 = 0x8000_001D;
+        const MITINT1: u32 = 0x8000_001C;
+
+        let mie = RvMIE(self.csrs[Csr::MIE as usize].val);
+        let mip = &mut self.csrs[Csr::MIP as usize].val;
+        if *mip & MIP_MITIP0 != 0 && mie.mitie0() == 1 {
+            *mip &= !MIP_MITIP0;
+            Some(MITINT0)
+        } else if *mip & MIP_MITIP1 != 0 && mie.mitie1() == 1 {
+            *mip &= !MIP_MITIP1;
+            Some(MITINT1)
+        } else {
+            None
+        }
+    }
 }
 
 #[cfg(test)]
@@ -269,4 +341,68 @@ mod tests {
         assert_eq!(csrs.write(Csr::MCOUNTINHIBIT, u32::MAX).ok(), Some(()));
         assert_eq!(csrs.read(Csr::MCOUNTINHIBIT).ok(), Some(0x0000_007D));
     }
+
+    #[test]
+    fn test_internal_timer_csrs() {
+        let clock = Clock::new();
+        let mut csrs = CsrFile::new(&clock);
+
+        assert_eq!(csrs.read(Csr::MITB0).ok(), Some(0xFFFF_FFFF));
+        assert_eq!(csrs.read(Csr::MITCTL0).ok(), Some(0x0000_0001));
+        assert_eq!(csrs.read(Csr::MITCTL1).ok(), Some(0x0000_0001));
+
+        clock.increment(10);
+        assert_eq!(csrs.read(Csr::MITCNT0).ok(), Some(10));
+        assert_eq!(csrs.read(Csr::MITCNT1).ok(), Some(10));
+
+        assert_eq!(csrs.write(Csr::MITCNT0, 100).ok(), Some(()));
+        clock.increment(5);
+        assert_eq!(csrs.read(Csr::MITCNT0).ok(), Some(105));
+
+        // Stop timer 0
+        assert_eq!(csrs.write(Csr::MITCTL0, 0).ok(), Some(()));
+        clock.increment(5);
+        assert_eq!(csrs.read(Csr::MITCNT0).ok(), Some(105));
+        assert_eq!(csrs.read(Csr::MITCNT1).ok(), Some(20));
+
+        // Only mitctl1 has the cascade bit
+        assert_eq!(csrs.write(Csr::MITCTL0, u32::MAX).ok(), Some(()));
+        assert_eq!(csrs.read(Csr::MITCTL0).ok(), Some(0x0000_0007));
+        assert_eq!(csrs.write(Csr::MITCTL1, u32::MAX).ok(), Some(()));
+        assert_eq!(csrs.read(Csr::MITCTL1).ok(), Some(0x0000_000F));
+    }
+
+    #[test]
+    fn test_internal_timer_expired() {
+        let clock = Clock::new();
+        let mut csrs = CsrFile::new(&clock);
+
+        assert_eq!(csrs.write(Csr::MITB0, 4).ok(), Some(()));
+        assert_eq!(csrs.write(Csr::MITCNT0, 0).ok(), Some(()));
+
+        assert!(!clock
+            .increment(3)
+            .contains(&TimerAction::InternalTimerExpired { timer: 0 }));
+        assert!(clock
+            .increment(1)
+            .contains(&TimerAction::InternalTimerExpired { timer: 0 }));
+        csrs.internal_timer_expired(0);
+        assert_eq!(csrs.read(Csr::MIP).ok(), Some(MIP_MITIP0));
+
+        // The interrupt stays pending until it is enabled
+        assert_eq!(csrs.take_internal_timer_int(), None);
+        let mut mie = RvMIE(0);
+        mie.set_mitie0(1);
+        assert_eq!(csrs.write(Csr::MIE, mie.0).ok(), Some(()));
+        assert_eq!(csrs.take_internal_timer_int(), Some(0x8000_001D));
+        assert_eq!(csrs.take_internal_timer_int(), None);
+        assert_eq!(csrs.read(Csr::MIP).ok(), Some(0));
+
+        // The counter restarts from 0 on the cycle after reaching the bound
+        clock.increment(1);
+        assert_eq!(csrs.read(Csr::MITCNT0).ok(), Some(0));
+        assert!(clock
+            .increment(4)
+            .contains(&TimerAction::InternalTimerExpired { timer: 0 }));
+    }
 }
@@ -61,7 +61,8 @@ impl<TBus: Bus> Cpu<TBus> {
 
         let instr = self.fetch()?;
         // Code coverage here.
-        self.code_coverage.log_execution(self.read_pc(), &instr);
+        let pc = self.read_pc();
+        self.code_coverage.log_execution(pc, &instr);
 
         match instr {
             Instr::Compressed(instr) => {
@@ -73,6 +74,7 @@ impl<TBus: Bus> Cpu<TBus> {
                 self.exec_instr32(instr, instr_tracer)?;
             }
         }
+        self.code_coverage.log_branch(pc, &instr, self.next_pc());
         self.write_pc(self.next_pc());
 
         self.is_execute_instr = false;
@@ -0,0 +1,200 @@
+/*++
+
+Licensed under the Apache-2.0 license.
+
+File Name:
+
+    internal_timer.rs
+
+Abstract:
+
+    File contains implementation of the VeeR EL2 internal timers.
+
+--*/
+
+use crate::types::RvMitCtl;
+use caliptra_emu_bus::{ActionHandle, Clock, Timer, TimerAction};
+
+/// Number of internal timers
+pub const INTERNAL_TIMER_COUNT: usize = 2;
+
+/// Internal timer 0 interrupt pending bit in `mip`
+pub const MIP_MITIP0: u32 = 1 << 29;
+
+/// Internal timer 1 interrupt pending bit in `mip`
+pub const MIP_MITIP1: u32 = 1 << 28;
+
+struct InternalTimer {
+    /// Counter value at `count_time`
+    count: u32,
+
+    /// The clock cycle `count` was latched at. May be in the future right
+    /// after the counter wraps, as the counter restarts from 0 on the cycle
+    /// after it reaches its bound.
+    count_time: u64,
+
+    /// Bound register (mitb)
+    bound: u32,
+
+    /// Control register (mitctl)
+    ctl: RvMitCtl,
+
+    /// Pending action for when the counter reaches its bound
+    expiry: Option<ActionHandle>,
+}
+
+/// The VeeR EL2 internal timers, `mitcnt0/1`, `mitb0/1` and `mitctl0/1`.
+///
+/// The counters are computed from the clock rather than incremented every
+/// cycle; when a counter reaches its bound, a
+/// [`TimerAction::InternalTimerExpired`] action fires and the CPU calls
+/// [`InternalTimers::expire`].
+pub struct InternalTimers {
+    timers: [InternalTimer; INTERNAL_TIMER_COUNT],
+    halted: bool,
+    timer: Timer,
+}
+
+impl InternalTimers {
+    /// Internal timer control reset value (counter enabled)
+    const MITCTL_RESET_VAL: u32 = 0x0000_0001;
+
+    /// Internal timer bound reset value
+    const MITB_RESET_VAL: u32 = 0xFFFF_FFFF;
+
+    /// Create the internal timers
+    pub fn new(clock: &Clock) -> Self {
+        let mut result = Self {
+            timers: [(); INTERNAL_TIMER_COUNT].map(|_| InternalTimer {
+                count: 0,
+                count_time: clock.now(),
+                bound: Self::MITB_RESET_VAL,
+                ctl: RvMitCtl(Self::MITCTL_RESET_VAL),
+                expiry: None,
+            }),
+            halted: false,
+            timer: Timer::new(clock),
+        };
+        for i in 0..INTERNAL_TIMER_COUNT {
+            result.reschedule(i);
+        }
+        result
+    }
+
+    /// Returns true if timer `i` is counting clock cycles
+    fn is_counting(&self, i: usize) -> bool {
+        let ctl = self.timers[i].ctl;
+        ctl.enable() == 1 && (!self.halted || ctl.halt_en() == 1) && !self.is_cascaded(i)
+    }
+
+    /// Returns true if timer `i` counts timer 0 expirations instead of cycles
+    fn is_cascaded(&self, i: usize) -> bool {
+        i == 1 && self.timers[i].ctl.cascade() == 1
+    }
+
+    /// Read the counter (mitcnt) of timer `i`
+    pub fn count(&self, i: usize) -> u32 {
+        let timer = &self.timers[i];
+        if self.is_counting(i) {
+            let elapsed = self.timer.now().saturating_sub(timer.count_time);
+            timer.count.wrapping_add(elapsed as u32)
+        } else {
+            timer.count
+        }
+    }
+
+    /// Read the bound (mitb) of timer `i`
+    pub fn bound(&self, i: usize) -> u32 {
+        self.timers[i].bound
+    }
+
+    /// Write the counter (mitcnt) of timer `i`
+    pub fn set_count(&mut self, i: usize, val: u32) {
+        let now = self.timer.now();
+        let timer = &mut self.timers[i];
+        timer.count = val;
+        timer.count_time = now;
+        self.reschedule(i);
+    }
+
+    /// Write the bound (mitb) of timer `i`
+    pub fn set_bound(&mut self, i: usize, val: u32) {
+        self.timers[i].bound = val;
+        self.reschedule(i);
+    }
+
+    /// Write the control register (mitctl) of timer `i`
+    pub fn set_ctl(&mut self, i: usize, val: u32) {
+        // Latch the current count, as the write may stop or start the counter
+        self.latch_all();
+        self.timers[i].ctl = RvMitCtl(val);
+        self.reschedule(i);
+    }
+
+    /// Tell the timers whether the core is halted; counters without
+    /// `halt_en` set stop counting while it is.
+    pub fn set_halted(&mut self, halted: bool) {
+        if self.halted == halted {
+            return;
+        }
+        self.latch_all();
+        self.halted = halted;
+        for i in 0..INTERNAL_TIMER_COUNT {
+            self.reschedule(i);
+        }
+    }
+
+    /// Called when the [`TimerAction::InternalTimerExpired`] action for timer
+    /// `i` fires. Restarts the counter and returns the `mip` bits of the
+    /// timers that reached their bound.
+    pub fn expire(&mut self, i: usize) -> u32 {
+        let now = self.timer.now();
+        let timer = &mut self.timers[i];
+        timer.expiry = None;
+        // The counter is reset to 0 on the cycle after it reaches its bound
+        timer.count = 0;
+        timer.count_time = now + 1;
+        self.reschedule(i);
+
+        let mut mip = if i == 0 { MIP_MITIP0 } else { MIP_MITIP1 };
+        if i == 0 && self.is_cascaded(1) && self.timers[1].ctl.enable() == 1 {
+            let timer1 = &mut self.timers[1];
+            if timer1.count >= timer1.bound {
+                timer1.count = 0;
+                mip |= MIP_MITIP1;
+            } else {
+                timer1.count += 1;
+            }
+        }
+        mip
+    }
+
+    fn latch_all(&mut self) {
+        let now = self.timer.now();
+        for i in 0..INTERNAL_TIMER_COUNT {
+            let count = self.count(i);
+            let timer = &mut self.timers[i];
+            timer.count = count;
+            timer.count_time = timer.count_time.max(now);
+        }
+    }
+
+    /// Schedule the action for when timer `i` reaches its bound
+    fn reschedule(&mut self, i: usize) {
+        if let Some(expiry) = self.timers[i].expiry.take() {
+            self.timer.cancel(expiry);
+        }
+        if !self.is_counting(i) {
+            return;
+        }
+        let now = self.timer.now();
+        let timer = &self.timers[i];
+        let count_time = timer.count_time.max(now);
+        let count = self.count(i);
+        let ticks = u64::from(timer.bound.saturating_sub(count)) + (count_time - now);
+        self.timers[i].expiry = Some(
+            self.timer
+                .schedule_action_in(ticks, TimerAction::InternalTimerExpired { timer: i as u8 }),
+        );
+    }
+}
@@ -15,6 +15,7 @@ Abstract:
 pub mod cpu;
 mod csr_file;
 mod instr;
+mod internal_timer;
 mod pic;
 mod types;
 pub mod xreg_file;
@@ -22,7 +23,10 @@ pub mod xreg_file;
 pub use cpu::StepAction;
 pub use cpu::WatchPtrHit;
 pub use cpu::WatchPtrKind;
-pub use cpu::{CodeRange, CoverageBitmaps, Cpu, ImageInfo, InstrTracer, StackInfo, StackRange};
+pub use cpu::{
+    BranchBitmaps, BranchCoverageBitmaps, CodeRange, CoverageBitmaps, Cpu, ImageInfo, InstrTracer,
+    StackInfo, StackRange,
+};
 pub use csr_file::CsrFile;
 pub use pic::{IntSource, Irq, Pic, PicMmioRegisters};
 pub use types::RvInstr;
@@ -1,15 +1,16 @@
 // Licensed under the Apache-2.0 license
 
-use std::cell::{Cell, RefCell};
+use std::cell::{Cell, RefCell, RefMut};
 use std::rc::Rc;
 
-use caliptra_emu_bus::{
-    Bus, BusError, Clock, ReadWriteRegister, ReadWriteRegisterArray, Register, Timer, TimerAction,
-};
-use caliptra_emu_derive::Bus;
+use caliptra_emu_bus::{Bus, BusError, Clock, Timer, TimerAction};
 use caliptra_emu_types::{RvAddr, RvData, RvSize};
-use tock_registers::interfaces::Readable;
-use tock_registers::register_bitfields;
+use tock_registers::{register_bitfields, LocalRegisterCopy};
+
+/// Register storage and bus dispatch generated from el2_pic_ctrl.rdl.
+mod el2_pic_ctrl {
+    include!(concat!(env!("OUT_DIR"), "/el2_pic_ctrl.rs"));
+}
 
 pub enum IntSource {
     DoeErr = 1,
@@ -77,34 +78,52 @@ impl Default for Pic {
     }
 }
 
-fn irq_id_from_addr(addr: u32) -> u8 {
-    u8::try_from((addr & 0x7f) / 4).unwrap()
+/// Number of interrupt ids the emulated PIC supports, including the reserved
+/// id 0.
+const NUM_IRQ_IDS: usize = 32;
+
+fn irq_id(index: usize) -> u8 {
+    u8::try_from(index).unwrap()
 }
 pub struct PicMmioRegisters {
     pic: Rc<PicImpl>,
     timer: Timer,
 }
 impl PicMmioRegisters {
+    #[cfg(test)]
     const MEIPL_OFFSET: RvAddr = 0x0000;
-    const MEIPL_MIN: RvAddr = Self::MEIPL_OFFSET + 4;
-    const MEIPL_MAX: RvAddr = Self::MEIPL_OFFSET + 31 * 4;
 
-    #[allow(dead_code)]
+    #[cfg(test)]
     const MEIP_OFFSET: RvAddr = 0x1000;
 
+    #[cfg(test)]
     const MEIE_OFFSET: RvAddr = 0x2000;
-    const MEIE_MIN: RvAddr = Self::MEIE_OFFSET + 4;
-    const MEIE_MAX: RvAddr = Self::MEIE_OFFSET + 31 * 4;
 
     const MPICCFG_OFFSET: RvAddr = 0x3000;
 
+    #[cfg(test)]
     const MEIGWCTRL_OFFSET: RvAddr = 0x4000;
-    const MEIGWCTRL_MIN: RvAddr = Self::MEIGWCTRL_OFFSET + 4;
-    const MEIGWCTRL_MAX: RvAddr = Self::MEIGWCTRL_OFFSET + 31 * 4;
 
+    #[cfg(test)]
     const MEIGWCLR_OFFSET: RvAddr = 0x5000;
-    const MEIGWCLR_MIN: RvAddr = Self::MEIGWCLR_OFFSET + 4;
-    const MEIGWCLR_MAX: RvAddr = Self::MEIGWCLR_OFFSET + 31 * 4;
+
+    /// Returns true if `addr` is in one of the per-interrupt register arrays
+    /// but not for an interrupt id the emulator supports. The first element
+    /// of each array is invalid (S=1..31).
+    fn is_unsupported_irq(addr: RvAddr) -> bool {
+        if addr == Self::MPICCFG_OFFSET {
+            return false;
+        }
+        let index = (addr & 0x0fff) as usize / 4;
+        index == 0 || index >= NUM_IRQ_IDS
+    }
+
+    fn regs(&self) -> PicRegs<'_> {
+        PicRegs {
+            pic: &self.pic,
+            regs: self.pic.regs.borrow_mut(),
+        }
+    }
 
     pub fn register_irq(&self, id: u8) -> Irq {
         Irq {
@@ -115,56 +134,17 @@ impl PicMmioRegisters {
 }
 impl Bus for PicMmioRegisters {
     fn read(&mut self, size: RvSize, addr: RvAddr) -> Result<RvData, BusError> {
-        match addr {
-            // The first element of each register array is invalid. (S=1..31)
-            Self::MEIPL_OFFSET
-            | Self::MEIE_OFFSET
-            | Self::MEIGWCTRL_OFFSET
-            | Self::MEIGWCLR_OFFSET => Err(BusError::LoadAccessFault),
-            Self::MEIGWCLR_MIN..=Self::MEIGWCLR_MAX => Ok(0),
-            _ => self.pic.regs.borrow_mut().read(size, addr),
+        if Self::is_unsupported_irq(addr) {
+            return Err(BusError::LoadAccessFault);
         }
+        el2_pic_ctrl::read(&mut self.regs(), size, addr)
     }
 
     fn write(&mut self, size: RvSize, addr: RvAddr, val: RvData) -> Result<(), BusError> {
-        match addr {
-            // The first element of each register array is invalid. (S=1..31)
-            Self::MEIPL_OFFSET
-            | Self::MEIE_OFFSET
-            | Self::MEIGWCTRL_OFFSET
-            | Self::MEIGWCLR_OFFSET => Err(BusError::StoreAccessFault),
-
-            Self::MEIGWCTRL_MIN..=Self::MEIGWCTRL_MAX => {
-                self.pic.regs.borrow_mut().write(size, addr, val)?;
-                self.pic.refresh_gateway(irq_id_from_addr(addr));
-                Ok(())
-            }
-
-            // meigwclrS: External Interrupt Gateway Clear Register
-            Self::MEIGWCLR_MIN..=Self::MEIGWCLR_MAX => {
-                let id = irq_id_from_addr(addr);
-                // Any write to this register will clear the pending bit in the gateway
-                self.pic.gw_pending_ff.set(id, false);
-                self.pic.refresh_gateway(id);
-                Ok(())
-            }
-            Self::MEIPL_MIN..=Self::MEIPL_MAX | Self::MPICCFG_OFFSET => {
-                self.pic.regs.borrow_mut().write(size, addr, val)?;
-                self.pic.refresh_order();
-                Ok(())
-            }
-            Self::MEIE_MIN..=Self::MEIE_MAX => {
-                let mut regs = self.pic.regs.borrow_mut();
-                regs.write(size, addr, val)?;
-                self.pic.refresh_enabled(&regs, irq_id_from_addr(addr));
-
-                Ok(())
-            }
-            _ => {
-                self.pic.regs.borrow_mut().write(size, addr, val)?;
-                Ok(())
-            }
+        if Self::is_unsupported_irq(addr) {
+            return Err(BusError::StoreAccessFault);
         }
+        el2_pic_ctrl::write(&mut self.regs(), size, addr, val)
     }
 
     fn poll(&mut self) {
@@ -239,40 +219,44 @@ register_bitfields! [
     ],
 ];
 
-#[derive(Bus)]
-struct PicImplRegs {
-    // External interrupt priority level register. Irq id #1 starts at
-    // meipl[1] (address 0x0004); meipl[0] is reserved.
-    #[peripheral(offset = 0x0000, mask = 0x0fff)]
-    meipl: ReadWriteRegisterArray<u32, 32, Meipl::Register>,
-
-    // External Interrupt Pending. Irq id #1 starts at meip[1]; meip[0] is
-    // reserved.
-    #[register(offset = 0x1000)]
-    meip: Bits32,
-
-    // External Interrupt Enabled. Irq id #1 starts at meie[1] (address 0x2004);
-    // meie[0] is reserved.
-    #[peripheral(offset = 0x2000, mask = 0x0fff)]
-    meie: ReadWriteRegisterArray<u32, 32, Meie::Register>,
-
-    #[register(offset = 0x3000)]
-    mpiccfg: ReadWriteRegister<u32, Mpiccfg::Register>,
-
-    // External Interrupt Gateway Configuration. Irq id #1 starts at
-    // meigwctrl[1] (address 0x4004); meigwctrl[0] is reserved.
-    #[peripheral(offset = 0x4000, mask = 0x0fff)]
-    meigwctrl: ReadWriteRegisterArray<u32, 32, Meigwctrl::Register>,
+/// The PIC's registers, borrowed for the duration of a bus access so the
+/// write hooks can update the interrupt state.
+struct PicRegs<'a> {
+    pic: &'a PicImpl,
+    regs: RefMut<'a, el2_pic_ctrl::Registers>,
 }
-impl PicImplRegs {
-    fn new() -> Self {
-        Self {
-            meipl: ReadWriteRegisterArray::new(0x0000_0000),
-            meip: Bits32::new(),
-            meie: ReadWriteRegisterArray::new(0x0000_0000),
-            mpiccfg: ReadWriteRegister::new(0x0000_0000),
-            meigwctrl: ReadWriteRegisterArray::new(0x0000_0000),
-        }
+impl el2_pic_ctrl::Peripheral for PicRegs<'_> {
+    fn regs(&self) -> &el2_pic_ctrl::Registers {
+        &self.regs
+    }
+    fn regs_mut(&mut self) -> &mut el2_pic_ctrl::Registers {
+        &mut self.regs
+    }
+
+    fn on_write_meipl(&mut self, _index: usize, _old: u32) {
+        self.pic.refresh_order(&self.regs);
+    }
+
+    fn on_write_meie(&mut self, index: usize, _old: u32) {
+        self.pic.refresh_enabled(&self.regs, irq_id(index));
+    }
+
+    fn on_write_mpiccfg(&mut self, _old: u32) {
+        self.pic.refresh_order(&self.regs);
+    }
+
+    fn on_write_meigwctrl(&mut self, index: usize, _old: u32) {
+        self.pic.refresh_gateway(&mut self.regs, irq_id(index));
+    }
+
+    // meigwclrS: External Interrupt Gateway Clear Register
+    fn on_write_meigwclr(&mut self, index: usize, _old: u32) {
+        // Any write to this register will clear the pending bit in the
+        // gateway; the register itself always reads as 0.
+        self.regs.meigwclr[index] = 0;
+        let id = irq_id(index);
+        self.pic.gw_pending_ff.set(id, false);
+        self.pic.refresh_gateway(&mut self.regs, id);
     }
 }
 
@@ -305,22 +289,6 @@ impl Bits32 {
         }
     }
 }
-impl Register for Bits32 {
-    const SIZE: usize = 4;
-
-    fn read(&self, _size: RvSize) -> Result<RvData, BusError> {
-        Ok(self.bits.get())
-    }
-
-    fn write(&mut self, size: RvSize, val: RvData) -> Result<(), BusError> {
-        if size != RvSize::Word {
-            return Err(BusError::StoreAccessFault);
-        }
-        self.bits.set(val);
-        Ok(())
-    }
-}
-
 #[derive(Clone, Copy, Default, Eq, Ord, PartialEq, PartialOrd)]
 struct IrqPriority {
     // The priority, xored such that the highest priority is always 0
@@ -329,7 +297,7 @@ struct IrqPriority {
 }
 
 struct PicImpl {
-    regs: RefCell<PicImplRegs>,
+    regs: RefCell<el2_pic_ctrl::Registers>,
 
     // levels.get(2) is true if the most recent call to Irq #2's set_level() was
     // high, false if it was low.
@@ -358,7 +326,7 @@ struct PicImpl {
 impl PicImpl {
     fn new() -> Self {
         let result = Self {
-            regs: RefCell::new(PicImplRegs::new()),
+            regs: RefCell::new(el2_pic_ctrl::Registers::default()),
 
             irq_levels: Bits32::new(),
             gw_pending_ff: Bits32::new(),
@@ -368,7 +336,7 @@ impl PicImpl {
             ordered_irq_pending: Bits32::new(),
             priority_xor: Cell::new(0),
         };
-        result.refresh_order();
+        result.refresh_order(&result.regs.borrow());
         result
     }
     fn highest_priority_irq(&self, prithresh: u8) -> Option<u8> {
@@ -385,11 +353,13 @@ impl PicImpl {
             None => None,
         }
     }
-    fn irq_set_level(&self, id: u8, mut is_high: bool) {
-        let regs = self.regs.borrow();
-
+    fn irq_set_level(&self, id: u8, is_high: bool) {
+        self.set_level(&mut self.regs.borrow_mut(), id, is_high);
+    }
+    fn set_level(&self, regs: &mut el2_pic_ctrl::Registers, id: u8, mut is_high: bool) {
         self.irq_levels.set(id, is_high);
-        let ctrl = regs.meigwctrl[id.into()];
+        let ctrl =
+            LocalRegisterCopy::<u32, Meigwctrl::Register>::new(regs.meigwctrl[usize::from(id)]);
         is_high ^= ctrl.is_set(Meigwctrl::POLARITY);
 
         if is_high {
@@ -399,27 +369,25 @@ impl PicImpl {
         if ctrl.matches_all(Meigwctrl::TYPE::EdgeTriggered) {
             is_high = self.gw_pending_ff.get(id)
         }
-        regs.meip.set(id, is_high);
-        self.set_ordered_irq_pending(&regs, id, is_high);
+        regs.meip[usize::from(id)] = u32::from(is_high);
+        self.set_ordered_irq_pending(regs, id, is_high);
     }
-    fn set_ordered_irq_pending(&self, regs: &PicImplRegs, id: u8, is_pending: bool) {
-        let enabled = regs.meie[usize::from(id)].is_set(Meie::INTEN);
+    fn set_ordered_irq_pending(&self, regs: &el2_pic_ctrl::Registers, id: u8, is_pending: bool) {
+        let enabled = LocalRegisterCopy::<u32, Meie::Register>::new(regs.meie[usize::from(id)])
+            .is_set(Meie::INTEN);
         self.ordered_irq_pending.set(
             self.id_to_order.get()[usize::from(id)],
             enabled && is_pending,
         );
     }
-    fn refresh_gateway(&self, id: u8) {
-        self.irq_set_level(id, self.irq_levels.get(id));
+    fn refresh_gateway(&self, regs: &mut el2_pic_ctrl::Registers, id: u8) {
+        self.set_level(regs, id, self.irq_levels.get(id));
     }
-    fn refresh_enabled(&self, regs: &PicImplRegs, id: u8) {
-        self.set_ordered_irq_pending(regs, id, regs.meip.get(id));
+    fn refresh_enabled(&self, regs: &el2_pic_ctrl::Registers, id: u8) {
+        self.set_ordered_irq_pending(regs, id, regs.meip[usize::from(id)] != 0);
     }
-    fn refresh_order(&self) {
-        let regs = self.regs.borrow();
-        let priority_xor = if regs
-            .mpiccfg
-            .reg
+    fn refresh_order(&self, regs: &el2_pic_ctrl::Registers) {
+        let priority_xor = if LocalRegisterCopy::<u32, Mpiccfg::Register>::new(regs.mpiccfg)
             .matches_all(Mpiccfg::PRIORITY_ORDER::Reverse)
         {
             0x00
@@ -431,7 +399,9 @@ impl PicImpl {
                 IrqPriority::default()
             } else {
                 IrqPriority {
-                    priority_xored: (regs.meipl[i].read(Meipl::PRIORITY) as u8) ^ priority_xor,
+                    priority_xored: (LocalRegisterCopy::<u32, Meipl::Register>::new(regs.meipl[i])
+                        .read(Meipl::PRIORITY) as u8)
+                        ^ priority_xor,
                     id: i as u8,
                 }
             }
@@ -446,7 +416,7 @@ impl PicImpl {
         self.priority_order.set(priorities);
         self.id_to_order.set(id_to_order);
         for i in 0..32u8 {
-            self.refresh_enabled(&regs, i);
+            self.refresh_enabled(regs, i);
         }
     }
 }
@@ -556,4 +526,55 @@ mod tests {
         irq2.set_level(false);
         assert_eq!(pic.highest_priority_irq(0), None);
     }
+
+    #[test]
+    fn test_edge_triggered_gateway() {
+        let pic = Pic::new();
+        let mut regs = pic.mmio_regs(&Clock::new());
+        let irq5 = pic.register_irq(5);
+
+        regs.write(RvSize::Word, PicMmioRegisters::MEIPL_OFFSET + 5 * 4, 1)
+            .unwrap();
+        regs.write(RvSize::Word, PicMmioRegisters::MEIE_OFFSET + 5 * 4, 1)
+            .unwrap();
+        // Edge-triggered, active-high
+        regs.write(RvSize::Word, PicMmioRegisters::MEIGWCTRL_OFFSET + 5 * 4, 2)
+            .unwrap();
+
+        irq5.set_level(true);
+        irq5.set_level(false);
+        // The gateway latched the edge
+        assert_eq!(pic.highest_priority_irq(0), Some(5));
+        assert_eq!(
+            regs.read(RvSize::Word, PicMmioRegisters::MEIP_OFFSET + 5 * 4),
+            Ok(1)
+        );
+
+        // Any write to meigwclr5 clears the latched edge
+        regs.write(
+            RvSize::Word,
+            PicMmioRegisters::MEIGWCLR_OFFSET + 5 * 4,
+            0xffff_ffff,
+        )
+        .unwrap();
+        assert_eq!(pic.highest_priority_irq(0), None);
+        assert_eq!(
+            regs.read(RvSize::Word, PicMmioRegisters::MEIP_OFFSET + 5 * 4),
+            Ok(0)
+        );
+        assert_eq!(
+            regs.read(RvSize::Word, PicMmioRegisters::MEIGWCLR_OFFSET + 5 * 4),
+            Ok(0)
+        );
+
+        // meip is read-only, and id 0 is reserved
+        assert_eq!(
+            regs.write(RvSize::Word, PicMmioRegisters::MEIP_OFFSET + 5 * 4, 1),
+            Err(BusError::StoreAccessFault)
+        );
+        assert_eq!(
+            regs.read(RvSize::Word, PicMmioRegisters::MEIPL_OFFSET),
+            Err(BusError::LoadAccessFault)
+        );
+    }
 }
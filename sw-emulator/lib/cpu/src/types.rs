@@ -637,6 +637,12 @@ bitfield! {
 
     /// Machine External Interrupt Enable
     pub u32, meie, set_meie: 11, 11;
+
+    /// Machine Internal Timer 1 Interrupt Enable (VeeR-specific)
+    pub u32, mitie1, set_mitie1: 28, 28;
+
+    /// Machine Internal Timer 0 Interrupt Enable (VeeR-specific)
+    pub u32, mitie0, set_mitie0: 29, 29;
 }
 
 bitfield! {
@@ -662,3 +668,21 @@ bitfield! {
     /// Control interrupt enable
     pub u32, haltie, _: 1, 1;
 }
+
+bitfield! {
+    #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+    /// Internal Timer Control Register (VeeR-specific)
+    pub struct RvMitCtl(u32);
+
+    /// Enable the counter
+    pub u32, enable, set_enable: 0, 0;
+
+    /// Keep counting while the core is halted
+    pub u32, halt_en, set_halt_en: 1, 1;
+
+    /// Keep counting while the core is paused
+    pub u32, pause_en, set_pause_en: 2, 2;
+
+    /// Increment only when internal timer 0 reaches its bound (mitctl1 only)
+    pub u32, cascade, set_cascade: 3, 3;
+}
@@ -116,7 +116,7 @@ static FIELD: ComponentMeta = ComponentMeta {
 static REG: ComponentMeta = ComponentMeta {
     ty: ComponentType::Reg,
     can_instantiate: true,
-    deep_subelement_types: &[&FIELD],
+    deep_subelement_types: &[&FIELD, &SIGNAL],
     properties: &[
         PropertyMeta{name: "regwidth", ty: PropertyType::U64, is_dynamic: true},
         PropertyMeta{name: "accesswidth", ty: PropertyType::U64, is_dynamic: true},
@@ -129,7 +129,7 @@ static REG: ComponentMeta = ComponentMeta {
 static MEM: ComponentMeta = ComponentMeta {
     ty: ComponentType::Mem,
     can_instantiate: true,
-    deep_subelement_types: &[],
+    deep_subelement_types: &[&REG, &FIELD],
     properties: &[
         PropertyMeta{name: "mementries", ty: PropertyType::U64, is_dynamic: true},
         PropertyMeta{name: "memwidth", ty: PropertyType::U64, is_dynamic: true},
@@ -153,7 +153,7 @@ static REGFILE: ComponentMeta = ComponentMeta {
 static ADDRMAP: ComponentMeta = ComponentMeta {
     ty: ComponentType::AddrMap,
     can_instantiate: true,
-    deep_subelement_types: &[&REG, &REGFILE, &FIELD, &SIGNAL],
+    deep_subelement_types: &[&REG, &REGFILE, &ADDRMAP, &MEM, &FIELD, &SIGNAL],
     properties: &[
         PropertyMeta{name: "alignment", ty: PropertyType::U64, is_dynamic: false},
         PropertyMeta{name: "sharedextbus", ty: PropertyType::Boolean, is_dynamic: false},
@@ -24,13 +24,15 @@ pub enum RdlError<'a> {
         value: Value,
     },
     NotImplemented,
-    MsbLessThanLsb,
     ComponentTypeCantBeInstantiated(ComponentType),
     RootCantBeInstantiated,
 
     DefaultPropertiesMustBeDefinedBeforeComponents,
     StrideIsLessThanElementSize,
     MultidimensionalFieldsNotSupported,
+    UnknownEnumName(String),
+    AliasesMustBeRegisters,
+    UserPropertiesMustBeDefinedInRootScope,
 }
 
 impl Error for RdlError<'_> {}
@@ -60,7 +62,6 @@ impl Display for RdlError<'_> {
                 "Expected property of type {expected_type:?}, found {value:?}"
             ),
             Self::NotImplemented => write!(f, "NOT IMPLEMENTED"),
-            Self::MsbLessThanLsb => write!(f, "msb less than lsb; big-endian not supported"),
             Self::StrideIsLessThanElementSize => write!(f, "stride is less than element size"),
             Self::DefaultPropertiesMustBeDefinedBeforeComponents => {
                 write!(f, "default properties must be defined before components")
@@ -72,6 +73,16 @@ impl Display for RdlError<'_> {
                 write!(f, "Component type {ty:?} can't be instantiated")
             }
             Self::RootCantBeInstantiated => write!(f, "Root can't be instantiated"),
+            Self::UnknownEnumName(s) => write!(f, "Unknown enum name {s:?}"),
+            Self::AliasesMustBeRegisters => {
+                write!(f, "Only registers can be aliased, and only by registers")
+            }
+            Self::UserPropertiesMustBeDefinedInRootScope => {
+                write!(
+                    f,
+                    "User-defined properties must be defined in the root scope"
+                )
+            }
         }
     }
 }
@@ -127,6 +127,7 @@ impl<'a> Iterator for Lexer<'a> {
                 Some('@') => Some(Token::At),
                 Some('#') => Some(Token::Hash),
                 Some(':') => Some(Token::Colon),
+                Some('|') => Some(Token::Pipe),
                 Some('`') => {
                     let keyword_start = iter.clone();
                     next_while(&mut iter, |ch| ch.is_ascii_alphabetic() || ch == '_');
@@ -217,7 +218,7 @@ mod test {
 
     #[test]
     fn test_foo() {
-        let tokens: Vec<Token> = Lexer::new("field 35\tiDentifier2_ 0x24\n\r 0xf00_bad 100_200 2'b01 5'o27 4'd9 16'h1caf 32'h3CAB_FFB0 /* ignore comment */ %= // line comment\n += \"string 1\" \"string\\\"2\" {}[]();:,.=@#reg field regfile addrmap signal enum mem constraint").take(37).collect();
+        let tokens: Vec<Token> = Lexer::new("field 35\tiDentifier2_ 0x24\n\r 0xf00_bad 100_200 2'b01 5'o27 4'd9 16'h1caf 32'h3CAB_FFB0 /* ignore comment */ %= // line comment\n += \"string 1\" \"string\\\"2\" {}[]();:,.=@#|reg field regfile addrmap signal enum mem constraint").take(38).collect();
         assert_eq!(
             tokens,
             vec![
@@ -249,6 +250,7 @@ mod test {
                 Token::Equals,
                 Token::At,
                 Token::Hash,
+                Token::Pipe,
                 Token::Reg,
                 Token::Field,
                 Token::RegFile,
@@ -75,11 +75,14 @@ pub use value::Value;
 
 pub use error::RdlError;
 pub use file_source::{FileSource, FsFileSource};
-pub use scope::{Instance, InstanceRef, ParentScope};
+pub use scope::{Instance, InstanceRef, ParentScope, UserPropertyDefinition};
 pub use value::AccessType;
 pub use value::AddressingType;
 pub use value::ComponentType;
 pub use value::EnumReference;
+pub use value::Interrupt;
+pub use value::InterruptType;
+pub use value::Reference;
 pub use value::ScopeType;
 
 pub use crate::bits::Bits;
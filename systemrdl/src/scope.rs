@@ -5,9 +5,10 @@ Licensed under the Apache-2.0 license.
 use std::collections::HashMap;
 use std::path::PathBuf;
 
-use crate::component_meta::PropertyMeta;
 use crate::file_source::FileSource;
-use crate::value::{AddressingType, ComponentType, InterruptType, PropertyType, ScopeType};
+use crate::value::{
+    AddressingType, ComponentType, Interrupt, InterruptType, PropertyType, ScopeType,
+};
 use crate::ParseError;
 use crate::{
     component_meta, token::Token, token_iter::TokenIter, Bits, FileParseError, RdlError, Result,
@@ -92,6 +93,90 @@ impl ParameterDefinition {
     }
 }
 
+/// The components a user-defined property with `component = all` can be
+/// assigned to.
+const ALL_PROPERTY_COMPONENTS: [ComponentType; 6] = [
+    ComponentType::Field,
+    ComponentType::Reg,
+    ComponentType::RegFile,
+    ComponentType::AddrMap,
+    ComponentType::Signal,
+    ComponentType::Mem,
+];
+
+/// A user-defined property, declared with
+/// `property name { type = ...; component = ...; default = ...; };`
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct UserPropertyDefinition {
+    pub ty: PropertyType,
+    pub components: Vec<ComponentType>,
+    pub default: Option<Value>,
+}
+impl UserPropertyDefinition {
+    fn parse<'a>(tokens: &mut TokenIter<'a>) -> Result<'a, (&'a str, Self)> {
+        tokens.expect(Token::Identifier("property"))?;
+        let name = tokens.expect_identifier()?;
+        if component_meta::default_property(ScopeType::Root, name).is_ok() {
+            return Err(RdlError::DuplicatePropertyName(name));
+        }
+        tokens.expect(Token::BraceOpen)?;
+        let mut ty = None;
+        let mut components = None;
+        let mut default = None;
+        while *tokens.peek(0) != Token::BraceClose {
+            let attribute = tokens.expect_identifier()?;
+            tokens.expect(Token::Equals)?;
+            match attribute {
+                "type" => {
+                    ty = Some(if component_keyword(tokens.peek(0)).is_ok() {
+                        tokens.next();
+                        PropertyType::Reference
+                    } else {
+                        PropertyType::parse_type(tokens)?
+                    });
+                }
+                "component" => components = Some(parse_component_list(tokens)?),
+                "default" => {
+                    // The value can only be parsed once the type is known
+                    let Some(ty) = ty else {
+                        return Err(RdlError::ExpectedPropertyNotFound("type"));
+                    };
+                    default = Some(ty.parse_or_lookup(tokens, None)?);
+                }
+                _ => return Err(RdlError::UnknownPropertyName(attribute)),
+            }
+            tokens.expect(Token::Semicolon)?;
+        }
+        tokens.expect(Token::BraceClose)?;
+        tokens.expect(Token::Semicolon)?;
+        Ok((
+            name,
+            Self {
+                ty: ty.ok_or(RdlError::ExpectedPropertyNotFound("type"))?,
+                components: components.ok_or(RdlError::ExpectedPropertyNotFound("component"))?,
+                default,
+            },
+        ))
+    }
+}
+
+fn parse_component_list<'a>(tokens: &mut TokenIter<'a>) -> Result<'a, Vec<ComponentType>> {
+    let mut result = vec![];
+    loop {
+        if *tokens.peek(0) == Token::Identifier("all") {
+            result.extend(ALL_PROPERTY_COMPONENTS);
+        } else {
+            result.push(component_keyword(tokens.peek(0))?);
+        }
+        tokens.next();
+        if *tokens.peek(0) != Token::Pipe {
+            break;
+        }
+        tokens.next();
+    }
+    Ok(result)
+}
+
 fn uses_property(ty: ScopeType, name: &str) -> bool {
     if component_meta::default_property(ty, name).is_ok() {
         return true;
@@ -110,6 +195,13 @@ pub struct Scope {
     pub default_properties: HashMap<String, Value>,
     pub properties: HashMap<String, Value>,
     pub dynamic_assignments: Vec<DynamicAssignment>,
+
+    // Values of user-defined properties assigned to this component
+    pub user_properties: HashMap<String, Value>,
+
+    // User-defined properties declared in this scope (only the root scope can
+    // declare them)
+    pub user_property_definitions: HashMap<String, UserPropertyDefinition>,
 }
 impl Scope {
     fn new(ty: ScopeType) -> Self {
@@ -161,6 +253,13 @@ impl Scope {
                         continue;
                     }
                     if component_meta::property(ty, prop_name).is_err() {
+                        let is_user_property = parent.is_some_and(|p| {
+                            lookup_user_property(p.scope, p.parent, prop_name)
+                                .is_some_and(|def| def.components.contains(&ty))
+                        });
+                        if is_user_property && !self.user_properties.contains_key(prop_name) {
+                            self.user_properties.insert(prop_name.into(), val.clone());
+                        }
                         continue;
                     }
                     self.properties.insert(prop_name.into(), val.clone());
@@ -177,39 +276,53 @@ impl Scope {
         }
     }
 
-    fn calculate_offsets(&mut self) -> Result<'static, ()> {
-        let addr_mode = AddressingType::Compact;
+    /// The number of bytes spanned by the addressable instances in this scope
+    fn size(&self) -> u64 {
+        self.instances
+            .iter()
+            .filter_map(|i| Some(i.offset? + i.total_size().ok()?))
+            .max()
+            .unwrap_or(0)
+    }
+
+    fn calculate_offsets(&mut self, parent_addr_mode: AddressingType) -> Result<'static, ()> {
+        let addr_mode = self
+            .property_val_opt("addressing")
+            .ok()
+            .flatten()
+            .unwrap_or(parent_addr_mode);
+
+        // Lay out the children first, as the size of regfiles and addrmaps
+        // depends on their contents.
+        for ty in self.types.values_mut() {
+            ty.calculate_offsets(addr_mode)?;
+        }
+        for el in self.instances.iter_mut() {
+            el.scope.calculate_offsets(addr_mode)?;
+        }
 
         let default_alignment = self.property_val_opt::<u64>("alignment").ok().flatten();
-        // Set reg offsets
+        // Set reg, regfile, addrmap and mem offsets
         let mut next_offset = 0;
         for instance in self.instances.iter_mut() {
-            if instance.scope.ty != ComponentType::Reg.into() {
+            if !instance.is_addressable() {
                 continue;
             }
-            let reg_width = instance
-                .scope
-                .property_val_opt("regwidth")
-                .ok()
-                .flatten()
-                .unwrap_or(32u64);
-            let access_width = instance
-                .scope
-                .property_val_opt("accesswidth")
-                .ok()
-                .flatten()
-                .unwrap_or(reg_width);
             let align = if let Some(next_alignment) = instance.next_alignment {
                 next_alignment
             } else if let Some(default_alignment) = default_alignment {
                 default_alignment
             } else {
                 match addr_mode {
-                    AddressingType::Compact => access_width / 8,
-                    AddressingType::RegAlign => instance.element_size(),
+                    AddressingType::Compact => instance.natural_alignment(),
+                    AddressingType::RegAlign if instance.scope.ty == ComponentType::Reg.into() => {
+                        instance.element_size()
+                    }
+                    AddressingType::RegAlign => instance.natural_alignment(),
                     AddressingType::FullAlign => instance.total_size()?,
                 }
-            };
+            }
+            .max(1);
             if instance.offset.is_none() {
                 if next_offset % align != 0 {
                     next_offset = ((next_offset / align) + 1) * align;
@@ -247,13 +360,68 @@ impl Scope {
                 next_offset = offset + field_width;
             }
         }
-        for ty in self.types.values_mut() {
-            ty.calculate_offsets()?;
+        Ok(())
+    }
+
+    /// If the next token is the name of a user-defined property that can be
+    /// assigned to components of type `ty`, returns its definition.
+    fn user_property(
+        &self,
+        tokens: &mut TokenIter<'_>,
+        parent: Option<&ParentScope<'_>>,
+        ty: ComponentType,
+    ) -> Option<UserPropertyDefinition> {
+        let Token::Identifier(name) = *tokens.peek(0) else {
+            return None;
+        };
+        if component_meta::property(ty, name).is_ok() {
+            return None;
         }
-        for el in self.instances.iter_mut() {
-            el.scope.calculate_offsets()?;
+        lookup_user_property(self, parent, name)
+            .filter(|def| def.components.contains(&ty))
+            .cloned()
+    }
+
+    // Parses `<primary> <reg type> <name> ...` after the `alias` keyword
+    fn parse_alias<'a>(
+        &self,
+        tokens: &mut TokenIter<'a>,
+        parent: Option<&ParentScope<'_>>,
+        parameters: Option<&ParameterScope<'_>>,
+    ) -> Result<'a, Instance> {
+        let primary_name = tokens.expect_identifier()?;
+        let Some(primary) = self.instances.iter().find(|i| i.name == primary_name) else {
+            return Err(RdlError::UnknownInstanceName(primary_name));
+        };
+        if primary.scope.ty != ComponentType::Reg.into() {
+            return Err(RdlError::AliasesMustBeRegisters);
         }
-        Ok(())
+
+        let (ty_scope, type_name) = if *tokens.peek(0) == Token::Reg {
+            tokens.next();
+            let parent_scope = ParentScope {
+                parent,
+                scope: self,
+            };
+            let mut ty_scope = Self::new(ComponentType::Reg.into());
+            tokens.expect(Token::BraceOpen)?;
+            ty_scope.parse(tokens, Some(&parent_scope), parameters)?;
+            ty_scope.set_property_defaults(Some(&parent_scope));
+            tokens.expect(Token::BraceClose)?;
+            (ty_scope, None)
+        } else {
+            let type_name = tokens.expect_identifier()?;
+            let ty_scope = lookup_typedef(self, parent, type_name)?.clone();
+            (ty_scope, Some(type_name.to_string()))
+        };
+        if ty_scope.ty != ComponentType::Reg.into() {
+            return Err(RdlError::AliasesMustBeRegisters);
+        }
+
+        let mut instance = Instance::parse(ty_scope, tokens, parameters)?;
+        instance.type_name = type_name;
+        instance.alias_of = Some(primary_name.into());
+        Ok(instance)
     }
 
     fn parse<'a>(
@@ -328,12 +496,40 @@ impl Scope {
                 tokens.expect(Token::Semicolon)?;
                 continue;
             }
+            if *tokens.peek(0) == Token::Identifier("property") {
+                if self.ty != ScopeType::Root {
+                    return Err(RdlError::UserPropertiesMustBeDefinedInRootScope);
+                }
+                let (name, definition) = UserPropertyDefinition::parse(tokens)?;
+                if self.user_property_definitions.contains_key(name) {
+                    return Err(RdlError::DuplicatePropertyName(name));
+                }
+                self.user_property_definitions
+                    .insert(name.into(), definition);
+                continue;
+            }
+            if *tokens.peek(0) == Token::Identifier("alias") {
+                tokens.next();
+                let instance = self.parse_alias(tokens, parent, parameters)?;
+                if self.instances.iter().any(|e| e.name == instance.name) {
+                    return Err(RdlError::DuplicateInstanceName(instance.name));
+                }
+                self.instances.push(instance);
+                tokens.expect(Token::Semicolon)?;
+                continue;
+            }
             if *tokens.peek(0) == Token::Identifier("default") {
                 tokens.next();
 
                 let prop = PropertyAssignment::parse(tokens, parameters, |prop_name| {
-                    component_meta::default_property(self.ty, prop_name)
+                    match component_meta::default_property(self.ty, prop_name) {
+                        Ok(meta) => Ok(meta.ty),
+                        Err(err) => lookup_user_property(self, parent, prop_name)
+                            .map(|def| def.ty)
+                            .ok_or(err),
+                    }
                 })?;
+                check_enum_reference(self, parent, &prop.value)?;
 
                 #[rustfmt::skip]
                 let prev_components_use_property =
@@ -355,6 +551,7 @@ impl Scope {
 
             if tokens.peek(0).is_identifier()
                 && (*tokens.peek(1) == Token::Equals || *tokens.peek(1) == Token::Semicolon)
+                || is_intr_assignment(tokens)
             {
                 match self.ty {
                     ScopeType::Component(ComponentType::Enum) => {
@@ -398,10 +595,21 @@ impl Scope {
                         let ScopeType::Component(ty) = self.ty else {
                             return Err(RdlError::CantSetPropertyInRootScope);
                         };
+                        if let Some(def) = self.user_property(tokens, parent, ty) {
+                            let assignment =
+                                PropertyAssignment::parse_user(tokens, parameters, &def)?;
+                            if self.user_properties.contains_key(assignment.prop_name) {
+                                return Err(RdlError::DuplicatePropertyName(assignment.prop_name));
+                            }
+                            self.user_properties
+                                .insert(assignment.prop_name.into(), assignment.value);
+                            continue;
+                        }
                         let assignment =
                             PropertyAssignment::parse(tokens, parameters, |prop_name| {
-                                component_meta::property(ty, prop_name)
+                                component_meta::property(ty, prop_name).map(|meta| meta.ty)
                             })?;
+                        check_enum_reference(self, parent, &assignment.value)?;
 
                         if self.properties.contains_key(assignment.prop_name) {
                             return Err(RdlError::DuplicatePropertyName(assignment.prop_name));
@@ -441,7 +649,7 @@ impl Scope {
     ) -> std::result::Result<Self, FileParseError<'a>> {
         let mut result = Self::parse_root_internal(file_source, input_files)?;
         result
-            .calculate_offsets()
+            .calculate_offsets(AddressingType::Compact)
             .map_err(|_| FileParseError::CouldNotCalculateOffsets)?;
         Ok(result)
     }
@@ -532,6 +740,35 @@ impl<'a> ParentScope<'a> {
     }
 }
 
+fn lookup_user_property<'b>(
+    mut scope: &'b Scope,
+    mut parent: Option<&'b ParentScope<'_>>,
+    name: &str,
+) -> Option<&'b UserPropertyDefinition> {
+    loop {
+        if let Some(result) = scope.user_property_definitions.get(name) {
+            return Some(result);
+        }
+        let parent_scope = parent?;
+        scope = parent_scope.scope;
+        parent = parent_scope.parent;
+    }
+}
+
+fn check_enum_reference<'a>(
+    scope: &Scope,
+    parent: Option<&ParentScope<'_>>,
+    value: &Value,
+) -> Result<'a, ()> {
+    let Value::EnumReference(name) = value else {
+        return Ok(());
+    };
+    match lookup_typedef(scope, parent, name) {
+        Ok(enm) if enm.ty == ComponentType::Enum.into() => Ok(()),
+        _ => Err(RdlError::UnknownEnumName(name.clone())),
+    }
+}
+
 fn lookup_typedef<'a, 'b>(
     mut scope: &'b Scope,
     mut parent: Option<&'b ParentScope<'_>>,
@@ -614,17 +851,85 @@ pub struct Instance {
     pub offset: Option<u64>,
     pub stride: Option<u64>,
     pub next_alignment: Option<u64>,
+    // If this is an alias register, the name of the primary register it aliases
+    pub alias_of: Option<String>,
+    // True if this field was declared as [lsb:msb]; the most significant bit
+    // of the field's value is the one at `offset`.
+    pub msb0: bool,
     pub scope: Scope,
 }
 impl Instance {
+    fn is_addressable(&self) -> bool {
+        matches!(
+            self.scope.ty,
+            ScopeType::Component(
+                ComponentType::Reg
+                    | ComponentType::RegFile
+                    | ComponentType::AddrMap
+                    | ComponentType::Mem
+            )
+        )
+    }
+
+    fn mem_entry_size(&self) -> u64 {
+        // According to section 11.1 of the SystemRDL 2.0 spec, the default memwidth is 32-bits
+        let width: u64 = self
+            .scope
+            .property_val_opt("memwidth")
+            .ok()
+            .flatten()
+            .unwrap_or(32);
+        ((width + 7) / 8).next_power_of_two()
+    }
+
+    /// The alignment of this instance when using compact addressing.
+    fn natural_alignment(&self) -> u64 {
+        match self.scope.ty {
+            ScopeType::Component(ComponentType::Reg) => {
+                let reg_width = self.element_size() * 8;
+                let access_width = self
+                    .scope
+                    .property_val_opt("accesswidth")
+                    .ok()
+                    .flatten()
+                    .unwrap_or(reg_width);
+                access_width / 8
+            }
+            ScopeType::Component(ComponentType::Mem) => self.mem_entry_size(),
+            _ => self
+                .scope
+                .instances
+                .iter()
+                .filter(|i| i.is_addressable())
+                .map(|i| i.natural_alignment())
+                .max()
+                .unwrap_or(1),
+        }
+    }
+
     pub fn element_size(&self) -> u64 {
-        let width = if let Ok(Some(w)) = self.scope.property_val_opt::<u64>("regwidth") {
-            w / 8
-        } else {
-            // According to section 10.1 of the SystemRDL 2.0 spec, the default regwidth is 32-bits
-            4
-        };
-        width
+        match self.scope.ty {
+            ScopeType::Component(ComponentType::Mem) => {
+                let entries: u64 = self
+                    .scope
+                    .property_val_opt("mementries")
+                    .ok()
+                    .flatten()
+                    .unwrap_or(0);
+                entries * self.mem_entry_size()
+            }
+            ScopeType::Component(ComponentType::RegFile | ComponentType::AddrMap) => {
+                self.scope.size()
+            }
+            _ => {
+                if let Ok(Some(w)) = self.scope.property_val_opt::<u64>("regwidth") {
+                    w / 8
+                } else {
+                    // According to section 10.1 of the SystemRDL 2.0 spec, the default regwidth is 32-bits
+                    4
+                }
+            }
+        }
     }
     pub fn total_size(&self) -> Result<'static, u64> {
         let stride = if let Some(stride) = self.stride {
@@ -664,11 +969,9 @@ impl Instance {
             let msb = expect_number(i, parameters)?;
             i.expect(Token::Colon)?;
             let lsb = expect_number(i, parameters)?;
-            if msb < lsb {
-                return Err(RdlError::MsbLessThanLsb);
-            }
-            result.dimension_sizes.push(msb - lsb + 1);
-            result.offset = Some(lsb);
+            result.msb0 = msb < lsb;
+            result.dimension_sizes.push(msb.abs_diff(lsb) + 1);
+            result.offset = Some(msb.min(lsb));
             i.expect(Token::BracketClose)?;
         } else {
             while *i.peek(0) == Token::BracketOpen {
@@ -965,6 +1268,238 @@ mod tests {
             root_scope
         );
     }
+
+    fn parse_error(src: &str) -> String {
+        let fs = MemFileSource::from_entries(&[("main.rdl".into(), src.into())]);
+        match Scope::parse_root(&fs, &["main.rdl".into()]) {
+            Err(FileParseError::Parse(e)) => e.error.to_string(),
+            other => panic!("Expected parse error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_array_and_mem_layout() {
+        let fs = MemFileSource::from_entries(&[(
+            "main.rdl".into(),
+            r#"
+            addrmap {
+                reg r_t { field {} f; };
+                regfile rf_t { r_t a; r_t b; };
+
+                r_t first;
+                rf_t rfs[2][3];
+                mem { mementries = 16; memwidth = 32; } ram;
+                r_t grid[2][4];
+                r_t last;
+                signal { signalwidth = 4; } irq;
+            } top;
+        "#
+            .into(),
+        )]);
+        let root_scope = Scope::parse_root(&fs, &["main.rdl".into()]).unwrap();
+        let top = &root_scope.instances[0].scope;
+        let offsets: Vec<(&str, Option<u64>)> = top
+            .instances
+            .iter()
+            .map(|i| (i.name.as_str(), i.offset))
+            .collect();
+        assert_eq!(
+            offsets,
+            vec![
+                ("first", Some(0)),
+                ("rfs", Some(4)),
+                ("ram", Some(52)),
+                ("grid", Some(116)),
+                ("last", Some(148)),
+                ("irq", None),
+            ]
+        );
+        let rfs = &top.instances[1];
+        assert_eq!(rfs.dimension_sizes, vec![2, 3]);
+        assert_eq!(rfs.element_size(), 8);
+        assert_eq!(rfs.total_size(), Ok(48));
+        assert_eq!(top.instances[2].element_size(), 64);
+    }
+
+    #[test]
+    fn test_msb0_fields() {
+        let fs = MemFileSource::from_entries(&[(
+            "main.rdl".into(),
+            r#"
+            reg {
+                field {} a[0:3];
+                field {} b[7:4];
+            } r;
+        "#
+            .into(),
+        )]);
+        let root_scope = Scope::parse_root(&fs, &["main.rdl".into()]).unwrap();
+        let fields = &root_scope.instances[0].scope.instances;
+        assert_eq!(
+            (fields[0].offset, &fields[0].dimension_sizes, fields[0].msb0),
+            (Some(0), &vec![4], true)
+        );
+        assert_eq!(
+            (fields[1].offset, &fields[1].dimension_sizes, fields[1].msb0),
+            (Some(4), &vec![4], false)
+        );
+    }
+
+    #[test]
+    fn test_alias() {
+        let fs = MemFileSource::from_entries(&[(
+            "main.rdl".into(),
+            r#"
+            addrmap {
+                reg ctrl_t { field {sw = rw;} en; };
+                ctrl_t ctrl;
+                alias ctrl ctrl_t ctrl_alias @0x10;
+                alias ctrl reg { field {sw = r;} en; } ctrl_ro @0x14;
+            } top;
+        "#
+            .into(),
+        )]);
+        let root_scope = Scope::parse_root(&fs, &["main.rdl".into()]).unwrap();
+        let regs = &root_scope.instances[0].scope.instances;
+        assert_eq!(regs[0].alias_of, None);
+        assert_eq!(regs[1].name, "ctrl_alias");
+        assert_eq!(regs[1].alias_of, Some("ctrl".into()));
+        assert_eq!(regs[1].type_name, Some("ctrl_t".into()));
+        assert_eq!(regs[1].offset, Some(0x10));
+        assert_eq!(regs[2].alias_of, Some("ctrl".into()));
+        assert_eq!(regs[2].type_name, None);
+        assert_eq!(
+            regs[2].scope.instances[0].scope.properties.get("sw"),
+            Some(&AccessType::R.into())
+        );
+
+        assert_eq!(
+            parse_error("addrmap { reg { field {} f; } a; alias b reg { field {} f; } c; } top;"),
+            "Unknown instance name \"b\""
+        );
+        assert_eq!(
+            parse_error("reg { field {} f; alias f reg { field {} g; } c; } r;"),
+            "Only registers can be aliased, and only by registers"
+        );
+    }
+
+    #[test]
+    fn test_interrupt_and_counter_properties() {
+        let fs = MemFileSource::from_entries(&[(
+            "main.rdl".into(),
+            r#"
+            reg {
+                field {level intr;} a;
+                field {posedge nonsticky intr;} b;
+                field {intr; stickybit = false;} c;
+                field {counter; incrvalue = 2; overflow;} d[4];
+            } r;
+        "#
+            .into(),
+        )]);
+        let root_scope = Scope::parse_root(&fs, &["main.rdl".into()]).unwrap();
+        let fields = &root_scope.instances[0].scope.instances;
+        let intr =
+            |i: usize| -> Option<Interrupt> { fields[i].scope.property_val_opt("intr").unwrap() };
+        assert_eq!(intr(0), Some(Interrupt::default()));
+        assert_eq!(
+            intr(1),
+            Some(Interrupt {
+                ty: InterruptType::PosEdge,
+                nonsticky: true,
+            })
+        );
+        assert_eq!(intr(2), Some(Interrupt::default()));
+        assert_eq!(
+            fields[2].scope.property_val_opt("stickybit"),
+            Ok(Some(false))
+        );
+        assert_eq!(fields[3].scope.property_val_opt("counter"), Ok(Some(true)));
+        assert_eq!(
+            fields[3].scope.property_val_opt("incrvalue"),
+            Ok(Some(2u64))
+        );
+    }
+
+    #[test]
+    fn test_user_properties() {
+        let fs = MemFileSource::from_entries(&[(
+            "main.rdl".into(),
+            r#"
+            property is_secret { type = boolean; component = reg | field; };
+            property owner { type = string; component = all; default = "soc"; };
+            property lock_reg { type = ref; component = reg; };
+
+            addrmap {
+                default owner = "mcu";
+                reg lock_t { field {} f; };
+                lock_t lock;
+                reg { is_secret; owner; lock_reg = lock; field {} f; } key;
+            } top;
+        "#
+            .into(),
+        )]);
+        let root_scope = Scope::parse_root(&fs, &["main.rdl".into()]).unwrap();
+        assert_eq!(
+            root_scope.user_property_definitions.get("owner"),
+            Some(&UserPropertyDefinition {
+                ty: PropertyType::String,
+                components: ALL_PROPERTY_COMPONENTS.to_vec(),
+                default: Some("soc".into()),
+            })
+        );
+        let top = &root_scope.instances[0].scope;
+        let lock = &top.instances[0];
+        assert_eq!(
+            lock.scope.user_properties,
+            HashMap::from([("owner".into(), "mcu".into())])
+        );
+        assert_eq!(
+            lock.scope.instances[0].scope.user_properties,
+            HashMap::from([("owner".into(), "mcu".into())])
+        );
+        let key = &top.instances[1];
+        assert_eq!(
+            key.scope.user_properties,
+            HashMap::from([
+                ("is_secret".into(), true.into()),
+                ("owner".into(), "soc".into()),
+                (
+                    "lock_reg".into(),
+                    crate::value::Reference::new(vec!["lock".into()]).into()
+                ),
+            ])
+        );
+        assert!(key.scope.properties.is_empty());
+
+        assert_eq!(
+            parse_error("addrmap { property p { type = boolean; component = reg; }; } top;"),
+            "User-defined properties must be defined in the root scope"
+        );
+        assert_eq!(
+            parse_error(
+                "property p { type = boolean; component = reg; }; \
+                 addrmap { mem { mementries = 1; p; } m; } top;"
+            ),
+            "Unknown property name \"p\""
+        );
+        assert_eq!(
+            parse_error("property desc { type = string; component = reg; };"),
+            "Dupicate property name \"desc\""
+        );
+    }
+
+    #[test]
+    fn test_unknown_enum() {
+        assert_eq!(
+            parse_error("reg { field {encode = missing_t;} f; } r;"),
+            "Unknown enum name \"missing_t\""
+        );
+        assert_eq!(
+            parse_error("reg { field not_an_enum_t {}; field {encode = not_an_enum_t;} f; } r;"),
+            "Unknown enum name \"not_an_enum_t\""
+        );
+    }
 }
 
 fn is_intr_modifier(token: &Token) -> bool {
@@ -974,60 +1509,94 @@ fn is_intr_modifier(token: &Token) -> bool {
     )
 }
 
+// Returns true if the next tokens are an intr property with modifiers, like
+// `posedge nonsticky intr`
+fn is_intr_assignment(tokens: &mut TokenIter) -> bool {
+    let mut i = 0;
+    while is_intr_modifier(tokens.peek(i)) {
+        i += 1;
+    }
+    i > 0 && *tokens.peek(i) == Token::Identifier("intr")
+}
+
 struct PropertyAssignment<'a> {
     prop_name: &'a str,
     value: Value,
 }
 
-static INTR_BOOL_PROPERTY: PropertyMeta = PropertyMeta {
-    name: "intr",
-    ty: PropertyType::Boolean,
-    is_dynamic: true,
-};
-fn intr_bool_property<'a>(_name: &str) -> Result<'a, &'static PropertyMeta> {
-    Ok(&INTR_BOOL_PROPERTY)
+fn intr_bool_property<'a>(_name: &str) -> Result<'a, PropertyType> {
+    Ok(PropertyType::Boolean)
 }
 
 impl<'a> PropertyAssignment<'a> {
     fn parse(
         tokens: &mut TokenIter<'a>,
         parameters: Option<&ParameterScope<'_>>,
-        meta_lookup_fn: impl Fn(&'a str) -> Result<'a, &'static PropertyMeta>,
+        type_lookup_fn: impl Fn(&'a str) -> Result<'a, PropertyType>,
     ) -> Result<'a, Self> {
-        if is_intr_modifier(tokens.peek(0)) && *tokens.peek(1) == Token::Identifier("intr") {
-            let intr_modifier = tokens.expect_identifier()?;
+        if is_intr_assignment(tokens) {
+            let mut intr = Interrupt::default();
+            while is_intr_modifier(tokens.peek(0)) {
+                match tokens.expect_identifier()? {
+                    "posedge" => intr.ty = InterruptType::PosEdge,
+                    "negedge" => intr.ty = InterruptType::NegEdge,
+                    "bothedge" => intr.ty = InterruptType::BothEdge,
+                    "nonsticky" => intr.nonsticky = true,
+                    _ => intr.ty = InterruptType::Level,
+                }
+            }
             // skip the bool tokens...
             PropertyAssignment::parse(tokens, parameters, intr_bool_property)?;
             return Ok(Self {
                 prop_name: "intr",
-                value: match intr_modifier {
-                    "posedge" => InterruptType::PosEdge.into(),
-                    "negedge" => InterruptType::NegEdge.into(),
-                    "bothedge" => InterruptType::BothEdge.into(),
-                    "level" => InterruptType::Level.into(),
-                    "nonsticky" => InterruptType::NonSticky.into(),
-                    _ => InterruptType::Level.into(),
-                },
+                value: intr.into(),
             });
         }
 
         let prop_name = tokens.expect_identifier()?;
-        let prop_meta = meta_lookup_fn(prop_name)?;
+        let prop_ty = type_lookup_fn(prop_name)?;
 
         let value = if *tokens.peek(0) == Token::Semicolon {
-            // This must be a boolean property set to true
-            if prop_meta.ty != PropertyType::Boolean
-                && prop_meta.ty != PropertyType::BooleanOrReference
-            {
-                return Err(RdlError::UnexpectedPropertyType {
-                    expected_type: prop_meta.ty,
-                    value: true.into(),
-                });
+            match prop_ty {
+                PropertyType::FieldInterrupt => Interrupt::default().into(),
+                // This must be a boolean property set to true
+                PropertyType::Boolean | PropertyType::BooleanOrReference => true.into(),
+                _ => {
+                    return Err(RdlError::UnexpectedPropertyType {
+                        expected_type: prop_ty,
+                        value: true.into(),
+                    })
+                }
             }
-            true.into()
         } else {
             tokens.expect(Token::Equals)?;
-            prop_meta.ty.parse_or_lookup(tokens, parameters)?
+            prop_ty.parse_or_lookup(tokens, parameters)?
+        };
+        tokens.expect(Token::Semicolon)?;
+        Ok(Self { prop_name, value })
+    }
+
+    fn parse_user(
+        tokens: &mut TokenIter<'a>,
+        parameters: Option<&ParameterScope<'_>>,
+        definition: &UserPropertyDefinition,
+    ) -> Result<'a, Self> {
+        let prop_name = tokens.expect_identifier()?;
+        let value = if *tokens.peek(0) == Token::Semicolon {
+            // Assigning without a value uses the default from the definition
+            match (&definition.default, definition.ty) {
+                (Some(default), _) => default.clone(),
+                (None, PropertyType::Boolean) => true.into(),
+                (None, ty) => {
+                    return Err(RdlError::UnexpectedPropertyType {
+                        expected_type: ty,
+                        value: true.into(),
+                    })
+                }
+            }
+        } else {
+            tokens.expect(Token::Equals)?;
+            definition.ty.parse_or_lookup(tokens, parameters)?
         };
         tokens.expect(Token::Semicolon)?;
         Ok(Self { prop_name, value })
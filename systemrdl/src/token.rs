@@ -28,6 +28,7 @@ pub enum Token<'a> {
     At,
     Colon,
     Hash,
+    Pipe,
 
     Pointer,
     PlusEqual,
@@ -22,7 +22,7 @@ pub enum Value {
     OnReadType(OnReadType),
     OnWriteType(OnWriteType),
     AddressingType(AddressingType),
-    InterruptType(InterruptType),
+    Interrupt(Interrupt),
 }
 impl Value {
     pub fn property_type(&self) -> PropertyType {
@@ -38,7 +38,7 @@ impl Value {
             Value::OnReadType(_) => PropertyType::OnReadType,
             Value::OnWriteType(_) => PropertyType::OnWriteType,
             Value::AddressingType(_) => PropertyType::AddressingType,
-            Value::InterruptType(_) => PropertyType::FieldInterrupt,
+            Value::Interrupt(_) => PropertyType::FieldInterrupt,
         }
     }
 }
@@ -102,9 +102,9 @@ impl From<AddressingType> for Value {
         Value::AddressingType(val)
     }
 }
-impl From<InterruptType> for Value {
-    fn from(val: InterruptType) -> Self {
-        Value::InterruptType(val)
+impl From<Interrupt> for Value {
+    fn from(val: Interrupt) -> Self {
+        Value::Interrupt(val)
     }
 }
 impl TryFrom<Value> for u64 {
@@ -179,6 +179,18 @@ impl TryFrom<Value> for AddressingType {
         }
     }
 }
+impl TryFrom<Value> for Interrupt {
+    type Error = RdlError<'static>;
+    fn try_from(value: Value) -> Result<'static, Self> {
+        match value {
+            Value::Interrupt(value) => Ok(value),
+            _ => Err(RdlError::UnexpectedPropertyType {
+                expected_type: PropertyType::FieldInterrupt,
+                value,
+            }),
+        }
+    }
+}
 impl TryFrom<Value> for AccessType {
     type Error = RdlError<'static>;
     fn try_from(value: Value) -> Result<'static, Self> {
@@ -223,6 +235,15 @@ impl Reference {
         Ok(Self { path, property })
     }
 }
+impl std::fmt::Display for Reference {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.path.join("."))?;
+        if let Some(property) = &self.property {
+            write!(f, "->{property}")?;
+        }
+        Ok(())
+    }
+}
 
 pub struct EnumReference(pub String);
 
@@ -271,14 +292,21 @@ pub enum AddressingType {
     FullAlign,
 }
 
-#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
 pub enum InterruptType {
     #[default]
     Level,
     PosEdge,
     NegEdge,
     BothEdge,
-    NonSticky,
+}
+
+/// The value of the `intr` field property, including its modifiers
+/// (`posedge intr`, `nonsticky intr`, ...)
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Interrupt {
+    pub ty: InterruptType,
+    pub nonsticky: bool,
 }
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
@@ -348,6 +376,7 @@ impl PropertyType {
             Token::Identifier("onreadtype") => Ok(PropertyType::OnReadType),
             Token::Identifier("onwritetype") => Ok(PropertyType::OnWriteType),
             Token::Identifier("precedencetype") => Ok(PropertyType::PrecedenceType),
+            Token::Identifier("ref") => Ok(PropertyType::Reference),
             unexpected => Err(RdlError::UnexpectedToken(unexpected)),
         }
     }
@@ -438,11 +467,16 @@ impl PropertyType {
                 _ => PropertyType::Bits.parse_or_lookup(tokens, parameters),
             },
             PropertyType::EnumReference => {
+                // The caller is responsible for checking that the enum exists
                 let ident = tokens.expect_identifier()?;
-                // TODO: ensure that enum exists?
                 Ok(Value::EnumReference(ident.into()))
             }
-            PropertyType::FieldInterrupt => todo!(),
+            PropertyType::FieldInterrupt => {
+                match PropertyType::Boolean.parse_or_lookup(tokens, parameters)? {
+                    Value::Bool(true) => Ok(Interrupt::default().into()),
+                    other => Ok(other),
+                }
+            }
             PropertyType::PrecedenceType => match tokens.next() {
                 Token::Identifier("hw") => Ok(PrecedenceType::Hw.into()),
                 Token::Identifier("sw") => Ok(PrecedenceType::Sw.into()),
@@ -87,13 +87,13 @@ pub fn redact_cert(der: &[u8], opts: RedactOpts) -> Vec<u8> {
     }
 
     if let Some(tcb_info) =
-        crate::x509::get_cert_extension(der, &crate::x509::DICE_MULTI_TCB_INFO_OID).unwrap()
+        caliptra_x509_verify::find_extension(der, &crate::x509::DICE_MULTI_TCB_INFO_OID).unwrap()
     {
         redact(&mut result, tcb_info);
     }
 
     if let Some(tcb_info) =
-        crate::x509::get_cert_extension(der, &crate::x509::DICE_TCB_INFO_OID).unwrap()
+        caliptra_x509_verify::find_extension(der, &crate::x509::DICE_TCB_INFO_OID).unwrap()
     {
         redact(&mut result, tcb_info);
     }
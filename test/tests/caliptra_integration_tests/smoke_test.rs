@@ -17,6 +17,7 @@ use caliptra_test::{
     swap_word_bytes, swap_word_bytes_inplace,
     x509::{DiceFwid, DiceTcbInfo},
 };
+use caliptra_x509_verify::{verify_cert_chain, DiceCertChain, VerifyOptions};
 use openssl::nid::Nid;
 use openssl::sha::{sha384, Sha384};
 use rand::rngs::StdRng;
@@ -514,9 +515,12 @@ fn smoke_test() {
     assert_eq!(
         rt_dice_tcb_info,
         Some(DiceTcbInfo {
-            vendor: None,
-            model: None,
+            vendor: Some("Caliptra".into()),
+            model: Some("RT".into()),
+            version: Some(format!("{:08X}", image.manifest.runtime.version)),
             svn: Some(0x100),
+            layer: Some(2),
+            index: Some(0),
             fwids: vec![DiceFwid {
                 // RT
                 hash_alg: asn1::oid!(2, 16, 840, 1, 101, 3, 4, 2, 2),
@@ -524,11 +528,38 @@ fn smoke_test() {
                     .as_bytes()
                     .to_vec(),
             },],
+            vendor_info: Some(image.manifest.runtime.revision.to_vec()),
             ty: Some(b"RT_INFO".to_vec()),
             ..Default::default()
         }),
     );
 
+    // The RT alias MultiTcbInfo carries the complete FMC entry that the
+    // ROM-built FMC alias certificate omits.
+    let rt_multi_tcb_info = DiceTcbInfo::find_multiple_in_cert(rt_alias_cert_der).unwrap();
+    assert_eq!(
+        rt_multi_tcb_info,
+        [DiceTcbInfo {
+            vendor: Some("Caliptra".into()),
+            model: Some("FMC".into()),
+            version: Some(format!("{:08X}", image.manifest.fmc.version)),
+            // This is from the SVN in the image (9)
+            svn: Some(0x109),
+            layer: Some(1),
+            index: Some(0),
+            fwids: vec![DiceFwid {
+                // FMC
+                hash_alg: asn1::oid!(2, 16, 840, 1, 101, 3, 4, 2, 2),
+                digest: swap_word_bytes(&image.manifest.fmc.digest)
+                    .as_bytes()
+                    .to_vec(),
+            },],
+            vendor_info: Some(image.manifest.fmc.revision.to_vec()),
+            ty: Some(b"FMC_INFO".to_vec()),
+            ..Default::default()
+        }]
+    );
+
     // Validate the rt-alias fields (this are redacted in the testdata because they can change):
     assert_eq!(
         rt_alias_cert
@@ -600,6 +631,20 @@ fn smoke_test() {
         );
     }
 
+    // Validate the chain end to end, as an external verifier would
+    let chain_der = [ldev_cert_der, fmc_alias_cert_der, rt_alias_cert_der].concat();
+    let chain = DiceCertChain::from_der(&chain_der, false, false).unwrap();
+    let verified = verify_cert_chain(
+        &chain,
+        &VerifyOptions {
+            idevid_pub_key: Some(&idevid_pubkey),
+            image: Some(&image),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    assert!(verified.anchored);
+
     assert!(!hw
         .soc_ifc()
         .cptra_hw_error_non_fatal()
@@ -664,9 +709,12 @@ fn smoke_test() {
     assert_eq!(
         rt_dice_tcb_info2,
         Some(DiceTcbInfo {
-            vendor: None,
-            model: None,
+            vendor: Some("Caliptra".into()),
+            model: Some("RT".into()),
+            version: Some(format!("{:08X}", image2.manifest.runtime.version)),
             svn: Some(0x100),
+            layer: Some(2),
+            index: Some(0),
             fwids: vec![DiceFwid {
                 // FMC
                 hash_alg: asn1::oid!(2, 16, 840, 1, 101, 3, 4, 2, 2),
@@ -674,6 +722,7 @@ fn smoke_test() {
                     .as_bytes()
                     .to_vec(),
             },],
+            vendor_info: Some(image2.manifest.runtime.revision.to_vec()),
             ty: Some(b"RT_INFO".to_vec()),
             ..Default::default()
         }),
@@ -1,5 +1,9 @@
 // Licensed under the Apache-2.0 license
-#[cfg(all(not(feature = "verilator"), not(feature = "fpga_realtime")))]
+#[cfg(all(
+    not(feature = "verilator"),
+    not(feature = "fpga_realtime"),
+    not(feature = "remote")
+))]
 #[test]
 fn test_emu_coverage() {
     use std::path::PathBuf;
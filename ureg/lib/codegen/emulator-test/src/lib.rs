@@ -0,0 +1,195 @@
+/*++
+
+Licensed under the Apache-2.0 license.
+
+File Name:
+
+    lib.rs
+
+Abstract:
+
+    Compiles the code ureg-codegen generates for emulator peripherals and
+    checks that register reads and writes are dispatched correctly.
+
+--*/
+
+pub mod test_periph {
+    include!(concat!(env!("OUT_DIR"), "/test_periph.rs"));
+}
+
+pub mod el2_pic_ctrl {
+    include!(concat!(env!("OUT_DIR"), "/el2_pic_ctrl.rs"));
+}
+
+#[cfg(test)]
+mod tests {
+    use caliptra_emu_bus::BusError;
+    use caliptra_emu_types::RvSize;
+
+    use super::*;
+
+    #[derive(Default)]
+    struct TestPeriph {
+        regs: test_periph::Registers,
+        busy: bool,
+        cmds: Vec<u32>,
+        started: Vec<usize>,
+    }
+    impl test_periph::Peripheral for TestPeriph {
+        fn regs(&self) -> &test_periph::Registers {
+            &self.regs
+        }
+        fn regs_mut(&mut self) -> &mut test_periph::Registers {
+            &mut self.regs
+        }
+
+        fn on_read_ctrl(&mut self) {
+            self.regs.ctrl = (self.regs.ctrl & !0x2) | (u32::from(self.busy) << 1);
+        }
+        fn on_write_cmd(&mut self, _old: u32) {
+            self.cmds.push(self.regs.cmd);
+        }
+        fn on_write_chan_start(&mut self, block_index: usize, _old: u32) {
+            if self.regs.chan[block_index].start & 1 != 0 {
+                self.started.push(block_index);
+            }
+        }
+    }
+
+    fn read(periph: &mut TestPeriph, addr: u32) -> Result<u32, BusError> {
+        test_periph::read(periph, RvSize::Word, addr)
+    }
+
+    fn write(periph: &mut TestPeriph, addr: u32, val: u32) -> Result<(), BusError> {
+        test_periph::write(periph, RvSize::Word, addr, val)
+    }
+
+    #[test]
+    fn test_reset_values() {
+        let mut periph = TestPeriph::default();
+        assert_eq!(read(&mut periph, 0x0), Ok(0x50));
+        assert_eq!(read(&mut periph, 0xc), Ok(0x1234));
+
+        // ORDER is msb0, so the bits of its reset value are reversed
+        assert_eq!(read(&mut periph, 0x40), Ok(0x800));
+    }
+
+    #[test]
+    fn test_access_types() {
+        let mut periph = TestPeriph::default();
+
+        // BUSY is read-only and comes from the read hook
+        assert_eq!(write(&mut periph, 0x0, 0xffff_ffff), Ok(()));
+        assert_eq!(periph.regs.ctrl, 0xf1);
+        periph.busy = true;
+        assert_eq!(read(&mut periph, 0x0), Ok(0xf3));
+
+        // The read-only alias shares CTRL's storage but only exposes EN
+        assert_eq!(read(&mut periph, 0x4), Ok(0x1));
+        assert_eq!(write(&mut periph, 0x4, 0), Err(BusError::StoreAccessFault));
+
+        // CMD is write-only and reports each write
+        assert_eq!(write(&mut periph, 0x8, 0xa5), Ok(()));
+        assert_eq!(write(&mut periph, 0x8, 0x5a), Ok(()));
+        assert_eq!(periph.cmds, vec![0xa5, 0x5a]);
+        assert_eq!(read(&mut periph, 0x8), Err(BusError::LoadAccessFault));
+
+        // ID is read-only
+        assert_eq!(write(&mut periph, 0xc, 0), Err(BusError::StoreAccessFault));
+        assert_eq!(read(&mut periph, 0xc), Ok(0x1234));
+    }
+
+    #[test]
+    fn test_arrays() {
+        let mut periph = TestPeriph::default();
+        for i in 0..4 {
+            assert_eq!(write(&mut periph, 0x10 + i * 4, 0x100 + i), Ok(()));
+        }
+        assert_eq!(periph.regs.data, [0x100, 0x101, 0x102, 0x103]);
+        assert_eq!(read(&mut periph, 0x18), Ok(0x102));
+        assert_eq!(read(&mut periph, 0x20), Err(BusError::LoadAccessFault));
+
+        // Sub-block array: CHAN[1].START, then CHAN[0].STATUS
+        assert_eq!(write(&mut periph, 0x108, 1), Ok(()));
+        assert_eq!(periph.started, vec![1]);
+        assert_eq!(periph.regs.chan[0].start, 0);
+        periph.regs.chan[0].status = 1;
+        assert_eq!(read(&mut periph, 0x104), Ok(1));
+        assert_eq!(
+            write(&mut periph, 0x104, 0),
+            Err(BusError::StoreAccessFault)
+        );
+        assert_eq!(read(&mut periph, 0x110), Err(BusError::LoadAccessFault));
+    }
+
+    #[test]
+    fn test_memories() {
+        let mut periph = TestPeriph::default();
+        assert_eq!(write(&mut periph, 0x200 + 7 * 4, 0xa5a5_a5a5), Ok(()));
+        assert_eq!(periph.regs.sram[7], 0xa5a5_a5a5);
+        assert_eq!(read(&mut periph, 0x21c), Ok(0xa5a5_a5a5));
+        assert_eq!(read(&mut periph, 0x220), Err(BusError::LoadAccessFault));
+    }
+
+    #[test]
+    fn test_bad_accesses() {
+        let mut periph = TestPeriph::default();
+        assert_eq!(read(&mut periph, 0x2), Err(BusError::LoadAccessFault));
+        assert_eq!(
+            test_periph::read(&mut periph, RvSize::HalfWord, 0x0),
+            Err(BusError::LoadAccessFault)
+        );
+        assert_eq!(
+            test_periph::write(&mut periph, RvSize::Byte, 0x0, 0),
+            Err(BusError::StoreAccessFault)
+        );
+        assert_eq!(
+            write(&mut periph, 0x1000, 0),
+            Err(BusError::StoreAccessFault)
+        );
+    }
+
+    #[derive(Default)]
+    struct Pic {
+        regs: el2_pic_ctrl::Registers,
+        cleared: Vec<usize>,
+    }
+    impl el2_pic_ctrl::Peripheral for Pic {
+        fn regs(&self) -> &el2_pic_ctrl::Registers {
+            &self.regs
+        }
+        fn regs_mut(&mut self) -> &mut el2_pic_ctrl::Registers {
+            &mut self.regs
+        }
+
+        fn on_write_meigwclr(&mut self, index: usize, _old: u32) {
+            self.cleared.push(index);
+        }
+    }
+
+    #[test]
+    fn test_pic() {
+        let mut pic = Pic::default();
+
+        // meipl5 only has 4 bits of priority
+        assert_eq!(
+            el2_pic_ctrl::write(&mut pic, RvSize::Word, 0x14, 0xff),
+            Ok(())
+        );
+        assert_eq!(el2_pic_ctrl::read(&mut pic, RvSize::Word, 0x14), Ok(0xf));
+
+        // meip is read-only
+        pic.regs.meip[3] = 1;
+        assert_eq!(el2_pic_ctrl::read(&mut pic, RvSize::Word, 0x100c), Ok(1));
+        assert_eq!(
+            el2_pic_ctrl::write(&mut pic, RvSize::Word, 0x100c, 0),
+            Err(BusError::StoreAccessFault)
+        );
+
+        assert_eq!(
+            el2_pic_ctrl::write(&mut pic, RvSize::Word, 0x5000 + 7 * 4, 0),
+            Ok(())
+        );
+        assert_eq!(pic.cleared, vec![7]);
+    }
+}
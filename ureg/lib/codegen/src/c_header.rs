@@ -0,0 +1,272 @@
+/*++
+Licensed under the Apache-2.0 license.
+--*/
+
+//! Generates C headers with the register offsets, reset values, field masks
+//! and shifts, and enum values of a register block.
+
+use std::fmt::Write;
+
+use ureg_schema::{Register, RegisterSubBlock, ValidatedRegisterBlock};
+
+use crate::snake_ident;
+
+/// Converts a schema name to an upper-case C identifier fragment.
+pub(crate) fn c_ident(name: &str) -> String {
+    snake_ident(name)
+        .to_string()
+        .trim_end_matches('_')
+        .to_ascii_uppercase()
+}
+
+/// Returns the first non-empty line of a schema comment.
+pub(crate) fn comment_summary(comment: &str) -> &str {
+    comment
+        .split("<br>")
+        .flat_map(str::lines)
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .unwrap_or("")
+}
+
+fn write_registers(out: &mut String, prefix: &str, registers: &[std::rc::Rc<Register>]) {
+    for reg in registers.iter() {
+        let reg_prefix = format!("{prefix}_{}", c_ident(&reg.name));
+        let summary = comment_summary(&reg.comment);
+        if !summary.is_empty() {
+            writeln!(out, "// {summary}").unwrap();
+        }
+        writeln!(out, "#define {reg_prefix} ({:#x}u)", reg.offset).unwrap();
+        writeln!(out, "#define {reg_prefix}_RESET ({:#x}u)", reg.default_val).unwrap();
+        if !reg.array_dimensions.is_empty() {
+            let len: u64 = reg.array_dimensions.iter().product();
+            writeln!(out, "#define {reg_prefix}_ARRAY_LEN ({len}u)").unwrap();
+        }
+        for field in reg.ty.fields.iter() {
+            let field_prefix = format!("{reg_prefix}_{}", c_ident(&field.name));
+            writeln!(out, "#define {field_prefix}_SHIFT ({})", field.position).unwrap();
+            writeln!(out, "#define {field_prefix}_MASK ({:#x}u)", field.mask()).unwrap();
+            if let Some(e) = &field.enum_type {
+                for variant in e.variants.iter() {
+                    writeln!(
+                        out,
+                        "#define {field_prefix}_{} ({}u)",
+                        c_ident(&variant.name),
+                        variant.value
+                    )
+                    .unwrap();
+                }
+            }
+        }
+        out.push('\n');
+    }
+}
+
+/// Generates a C header describing `block`. Every identifier is prefixed
+/// with the upper-case block name; register offsets are relative to the
+/// start of the block, and the absolute address of each instance is
+/// defined as `<INSTANCE>_BASE_ADDR`.
+pub fn generate_c_header(block: &ValidatedRegisterBlock) -> String {
+    let block = block.block();
+    let prefix = c_ident(&block.name);
+    let mut out = String::new();
+    writeln!(out, "#ifndef {prefix}_REGS_H").unwrap();
+    writeln!(out, "#define {prefix}_REGS_H").unwrap();
+    out.push('\n');
+
+    for instance in block.instances.iter() {
+        writeln!(
+            out,
+            "#define {}_BASE_ADDR ({:#x}u)",
+            c_ident(&instance.name),
+            instance.address
+        )
+        .unwrap();
+    }
+    if !block.instances.is_empty() {
+        out.push('\n');
+    }
+
+    write_registers(&mut out, &prefix, &block.registers);
+
+    for mem in block.memories.iter() {
+        let mem_prefix = format!("{prefix}_{}", c_ident(&mem.name));
+        let summary = comment_summary(&mem.comment);
+        if !summary.is_empty() {
+            writeln!(out, "// {summary}").unwrap();
+        }
+        writeln!(out, "#define {mem_prefix} ({:#x}u)", mem.offset).unwrap();
+        writeln!(out, "#define {mem_prefix}_ENTRIES ({}u)", mem.entries).unwrap();
+        writeln!(out, "#define {mem_prefix}_WIDTH ({})", mem.width).unwrap();
+        if !mem.array_dimensions.is_empty() {
+            let len: u64 = mem.array_dimensions.iter().product();
+            writeln!(out, "#define {mem_prefix}_ARRAY_LEN ({len}u)").unwrap();
+        }
+        out.push('\n');
+    }
+
+    // Signals aren't memory-mapped; only their shape is described.
+    for signal in block.signals.iter() {
+        let signal_prefix = format!("{prefix}_{}", c_ident(&signal.name));
+        let summary = comment_summary(&signal.comment);
+        if !summary.is_empty() {
+            writeln!(out, "// {summary}").unwrap();
+        }
+        writeln!(out, "#define {signal_prefix}_WIDTH ({})", signal.width).unwrap();
+        writeln!(
+            out,
+            "#define {signal_prefix}_ACTIVE_LOW ({})",
+            u8::from(signal.active_low)
+        )
+        .unwrap();
+        out.push('\n');
+    }
+
+    // TODO: Do this recursively
+    for sb in block.sub_blocks.iter() {
+        let sb_prefix = format!("{prefix}_{}", c_ident(&sb.block().name));
+        writeln!(
+            out,
+            "#define {sb_prefix}_OFFSET ({:#x}u)",
+            sb.start_offset()
+        )
+        .unwrap();
+        if let RegisterSubBlock::Array { stride, len, .. } = sb {
+            writeln!(out, "#define {sb_prefix}_STRIDE ({stride:#x}u)").unwrap();
+            writeln!(out, "#define {sb_prefix}_LEN ({len}u)").unwrap();
+        }
+        out.push('\n');
+        write_registers(&mut out, &sb_prefix, &sb.block().registers);
+    }
+
+    writeln!(out, "#endif // {prefix}_REGS_H").unwrap();
+    out
+}
+
+#[cfg(test)]
+mod generate_c_header_tests {
+    use std::rc::Rc;
+
+    use ureg_schema::{
+        Enum, EnumVariant, FieldType, Memory, RegisterBlock, RegisterBlockInstance, RegisterField,
+        RegisterType, Signal,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_generate_c_header() {
+        let ctrl = Rc::new(RegisterType {
+            name: Some("ctrl".into()),
+            fields: vec![
+                RegisterField {
+                    name: "go".into(),
+                    ty: FieldType::RW,
+                    width: 1,
+                    ..Default::default()
+                },
+                RegisterField {
+                    name: "mode".into(),
+                    ty: FieldType::RW,
+                    position: 4,
+                    width: 2,
+                    enum_type: Some(Rc::new(Enum {
+                        name: Some("Mode".into()),
+                        variants: vec![
+                            EnumVariant {
+                                name: "FAST".into(),
+                                value: 1,
+                            },
+                            EnumVariant {
+                                name: "SLOW".into(),
+                                value: 2,
+                            },
+                        ],
+                        bit_width: 2,
+                    })),
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        });
+        let block = RegisterBlock {
+            name: "foo_ctrl".into(),
+            instances: vec![RegisterBlockInstance {
+                name: "foo_ctrl_reg".into(),
+                address: 0x1000_0000,
+            }],
+            registers: vec![
+                Rc::new(Register {
+                    name: "CTRL".into(),
+                    comment: "Control register.<br>Starts the engine.".into(),
+                    default_val: 0x10,
+                    ty: ctrl.clone(),
+                    ..Default::default()
+                }),
+                Rc::new(Register {
+                    name: "DATA".into(),
+                    offset: 0x20,
+                    array_dimensions: vec![2, 4],
+                    ty: ctrl,
+                    ..Default::default()
+                }),
+            ],
+            memories: vec![Memory {
+                name: "SRAM".into(),
+                comment: "Scratch memory.".into(),
+                offset: 0x100,
+                entries: 64,
+                width: 32,
+                ..Default::default()
+            }],
+            signals: vec![Signal {
+                name: "rst_b".into(),
+                width: 1,
+                active_low: true,
+                ..Default::default()
+            }],
+            ..Default::default()
+        }
+        .validate_and_dedup()
+        .unwrap();
+
+        assert_eq!(
+            generate_c_header(&block),
+            "#ifndef FOO_CTRL_REGS_H
+#define FOO_CTRL_REGS_H
+
+#define FOO_CTRL_REG_BASE_ADDR (0x10000000u)
+
+// Control register.
+#define FOO_CTRL_CTRL (0x0u)
+#define FOO_CTRL_CTRL_RESET (0x10u)
+#define FOO_CTRL_CTRL_GO_SHIFT (0)
+#define FOO_CTRL_CTRL_GO_MASK (0x1u)
+#define FOO_CTRL_CTRL_MODE_SHIFT (4)
+#define FOO_CTRL_CTRL_MODE_MASK (0x30u)
+#define FOO_CTRL_CTRL_MODE_FAST (1u)
+#define FOO_CTRL_CTRL_MODE_SLOW (2u)
+
+#define FOO_CTRL_DATA (0x20u)
+#define FOO_CTRL_DATA_RESET (0x0u)
+#define FOO_CTRL_DATA_ARRAY_LEN (8u)
+#define FOO_CTRL_DATA_GO_SHIFT (0)
+#define FOO_CTRL_DATA_GO_MASK (0x1u)
+#define FOO_CTRL_DATA_MODE_SHIFT (4)
+#define FOO_CTRL_DATA_MODE_MASK (0x30u)
+#define FOO_CTRL_DATA_MODE_FAST (1u)
+#define FOO_CTRL_DATA_MODE_SLOW (2u)
+
+// Scratch memory.
+#define FOO_CTRL_SRAM (0x100u)
+#define FOO_CTRL_SRAM_ENTRIES (64u)
+#define FOO_CTRL_SRAM_WIDTH (32)
+
+#define FOO_CTRL_RST_B_WIDTH (1)
+#define FOO_CTRL_RST_B_ACTIVE_LOW (1)
+
+#endif // FOO_CTRL_REGS_H
+"
+        );
+    }
+}
@@ -0,0 +1,521 @@
+/*++
+Licensed under the Apache-2.0 license.
+--*/
+
+//! Generates the register storage and bus dispatch for an emulator
+//! peripheral from the same register block that the firmware register
+//! bindings come from, so the two can't disagree about the register map.
+
+use std::rc::Rc;
+
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use ureg_schema::{FieldType, Register, RegisterSubBlock, RegisterWidth, ValidatedRegisterBlock};
+
+use crate::{camel_ident, hex_literal, memory_registers, snake_ident};
+
+/// Which bits of a register are affected by each kind of software access.
+#[derive(Debug, Default, Eq, PartialEq)]
+struct AccessMasks {
+    read: u32,
+    write: u32,
+
+    // Bits cleared by writing 1 (W1C)
+    clear: u32,
+
+    // Bits set by writing 1 (W1S)
+    set: u32,
+
+    // Bits cleared by any write (WC, WRC)
+    write_clears: u32,
+}
+impl AccessMasks {
+    fn new(reg: &Register) -> Self {
+        let mut result = Self::default();
+        for field in reg.ty.fields.iter() {
+            let mask = field.mask() as u32;
+            if field.ty.can_read() {
+                result.read |= mask;
+            }
+            if field.ty.can_write() {
+                result.write |= mask;
+            }
+            if field.ty.can_clear() {
+                result.clear |= mask;
+            }
+            if field.ty.can_set() {
+                result.set |= mask;
+            }
+            if matches!(field.ty, FieldType::WC | FieldType::WRC) {
+                result.write_clears |= mask;
+            }
+        }
+        result
+    }
+
+    fn can_write(&self) -> bool {
+        (self.write | self.clear | self.set | self.write_clears) != 0
+    }
+
+    /// Returns an expression for the value read from a register holding `val`.
+    fn read_expr(&self, val: TokenStream) -> TokenStream {
+        if self.read == u32::MAX {
+            val
+        } else {
+            let mask = hex_literal(self.read.into());
+            quote! { #val & #mask }
+        }
+    }
+
+    /// Returns an expression for the new value of a register holding `old`
+    /// after software writes `val` to it.
+    fn write_expr(&self) -> TokenStream {
+        let mut result = match self.write {
+            0 => quote! { old },
+            u32::MAX => quote! { val },
+            mask => {
+                let mask = hex_literal(mask.into());
+                quote! { (old & !#mask) | (val & #mask) }
+            }
+        };
+        if self.clear != 0 {
+            let mask = hex_literal(self.clear.into());
+            result = quote! { (#result) & !(val & #mask) };
+        }
+        if self.set != 0 {
+            let mask = hex_literal(self.set.into());
+            result = quote! { (#result) | (val & #mask) };
+        }
+        if self.write_clears != 0 {
+            let mask = hex_literal(self.write_clears.into());
+            result = quote! { (#result) & !#mask };
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod access_masks_tests {
+    use super::*;
+
+    #[test]
+    fn test_write_expr() {
+        let masks = AccessMasks {
+            write: u32::MAX,
+            ..Default::default()
+        };
+        assert_eq!(masks.write_expr().to_string(), quote! { val }.to_string());
+
+        let masks = AccessMasks {
+            write: 0x0000_00ff,
+            clear: 0x0000_ff00,
+            set: 0x00ff_0000,
+            write_clears: 0xff00_0000,
+            ..Default::default()
+        };
+        assert_eq!(
+            masks.write_expr().to_string(),
+            quote! {
+                ((((old & !0xff) | (val & 0xff)) & !(val & 0xff00)) | (val & 0xff0000)) & !0xff000000
+            }
+            .to_string()
+        );
+    }
+}
+
+/// Where the registers being generated live relative to `Registers`.
+struct BlockContext {
+    /// Selects the block's storage from `Registers`, e.g. `.foo[block_index]`.
+    storage: TokenStream,
+
+    /// Prepended to the register name to form the hook names.
+    hook_prefix: String,
+
+    /// Prepended to the register name in the hook documentation.
+    name_prefix: String,
+
+    /// True if the block is one element of a sub-block array.
+    is_array: bool,
+}
+
+#[derive(Default)]
+struct BlockTokens {
+    fields: TokenStream,
+    defaults: TokenStream,
+    hooks: TokenStream,
+    read_arms: TokenStream,
+    write_arms: TokenStream,
+}
+
+fn element_count(reg: &Register) -> u64 {
+    reg.array_dimensions.iter().product()
+}
+
+/// Returns an expression for the offset of `addr` from `start`.
+fn offset_from(start: u64) -> TokenStream {
+    if start == 0 {
+        quote! { addr }
+    } else {
+        let start = hex_literal(start);
+        quote! { (addr - #start) }
+    }
+}
+
+fn doc_attr(comment: &str) -> TokenStream {
+    let comment = comment.replace("<br>", "\n");
+    if comment.is_empty() {
+        quote! {}
+    } else {
+        quote! { #[doc = #comment] }
+    }
+}
+
+/// Returns the number of bytes spanned by `registers`.
+fn registers_size(registers: &[Rc<Register>]) -> u64 {
+    registers
+        .iter()
+        .map(|reg| reg.offset + element_count(reg) * reg.ty.width.in_bytes())
+        .max()
+        .unwrap_or(0)
+}
+
+fn generate_block(registers: &[Rc<Register>], ctx: &BlockContext, tokens: &mut BlockTokens) {
+    for reg in registers.iter() {
+        assert!(
+            reg.ty.width == RegisterWidth::_32,
+            "emulator register {:?} must be 32 bits wide",
+            reg.name
+        );
+        // Aliases share the storage and side effects of the register they
+        // alias, but have their own access types.
+        let target = reg
+            .alias_of
+            .as_ref()
+            .and_then(|name| registers.iter().find(|r| &r.name == name))
+            .unwrap_or(reg);
+        let field_name = snake_ident(&target.name);
+        let read_hook = format_ident!("on_read_{}{}", ctx.hook_prefix, field_name);
+        let write_hook = format_ident!("on_write_{}{}", ctx.hook_prefix, field_name);
+        let count = element_count(target);
+        let is_array = !target.array_dimensions.is_empty();
+
+        let mut hook_params = TokenStream::new();
+        let mut hook_args = TokenStream::new();
+        if ctx.is_array {
+            hook_params.extend(quote! { _block_index: usize, });
+            hook_args.extend(quote! { block_index, });
+        }
+        if is_array {
+            hook_params.extend(quote! { _index: usize, });
+            hook_args.extend(quote! { index, });
+        }
+
+        if reg.alias_of.is_none() {
+            let doc = doc_attr(&reg.comment);
+            let default_val = hex_literal(reg.default_val);
+            let count_literal = hex_literal(count);
+            if is_array {
+                tokens.fields.extend(quote! {
+                    #doc
+                    pub #field_name: [u32; #count_literal],
+                });
+                tokens.defaults.extend(quote! {
+                    #field_name: [#default_val; #count_literal],
+                });
+            } else {
+                tokens.fields.extend(quote! {
+                    #doc
+                    pub #field_name: u32,
+                });
+                tokens.defaults.extend(quote! {
+                    #field_name: #default_val,
+                });
+            }
+            // Only declare the hooks software can trigger through this
+            // register or one of its aliases.
+            let masks: Vec<AccessMasks> = registers
+                .iter()
+                .filter(|r| r.name == reg.name || r.alias_of.as_ref() == Some(&reg.name))
+                .map(|r| AccessMasks::new(r))
+                .collect();
+            if masks.iter().any(|m| m.read != 0) {
+                let read_doc = format!(
+                    " Called before software reads `{}{}`, so the peripheral can update its value.",
+                    ctx.name_prefix, reg.name
+                );
+                tokens.hooks.extend(quote! {
+                    #[doc = #read_doc]
+                    fn #read_hook(&mut self, #hook_params) {}
+                });
+            }
+            if masks.iter().any(AccessMasks::can_write) {
+                let write_doc = format!(
+                    " Called after software writes `{}{}`, with the value it held before the write.",
+                    ctx.name_prefix, reg.name
+                );
+                tokens.hooks.extend(quote! {
+                    #[doc = #write_doc]
+                    fn #write_hook(&mut self, #hook_params _old: u32) {}
+                });
+            }
+        }
+
+        let storage = &ctx.storage;
+        let (pattern, index_stmt, element) = if is_array {
+            let start = hex_literal(reg.offset);
+            let end = hex_literal(reg.offset + (count - 1) * 4);
+            let offset = offset_from(reg.offset);
+            (
+                quote! { #start..=#end },
+                quote! { let index = #offset as usize / 4; },
+                quote! { #storage.#field_name[index] },
+            )
+        } else {
+            let offset = hex_literal(reg.offset);
+            (
+                quote! { #offset },
+                quote! {},
+                quote! { #storage.#field_name },
+            )
+        };
+
+        let masks = AccessMasks::new(reg);
+        if masks.read != 0 {
+            let val = masks.read_expr(quote! { periph.regs() #element });
+            tokens.read_arms.extend(quote! {
+                #pattern => {
+                    #index_stmt
+                    periph.#read_hook(#hook_args);
+                    Ok(#val)
+                }
+            });
+        }
+        if masks.can_write() {
+            let new_val = masks.write_expr();
+            tokens.write_arms.extend(quote! {
+                #pattern => {
+                    #index_stmt
+                    let regs = periph.regs_mut();
+                    let old = regs #element;
+                    regs #element = #new_val;
+                    periph.#write_hook(#hook_args old);
+                    Ok(())
+                }
+            });
+        }
+    }
+}
+
+fn generate_sub_block(
+    sb: &RegisterSubBlock,
+    struct_tokens: &mut TokenStream,
+    tokens: &mut BlockTokens,
+) {
+    let registers = &sb.block().registers;
+    if registers.is_empty() {
+        return;
+    }
+    let struct_name = format_ident!("{}Registers", camel_ident(&sb.block().name));
+    let field_name = snake_ident(&sb.block().name);
+    let start = sb.start_offset();
+    let (len, stride) = match sb {
+        RegisterSubBlock::Single { .. } => (None, registers_size(registers)),
+        RegisterSubBlock::Array { len, stride, .. } => (Some(*len), *stride),
+    };
+    let ctx = BlockContext {
+        storage: if len.is_some() {
+            quote! { .#field_name[block_index] }
+        } else {
+            quote! { .#field_name }
+        },
+        hook_prefix: format!("{field_name}_"),
+        name_prefix: format!("{}.", sb.block().name),
+        is_array: len.is_some(),
+    };
+    let mut sb_tokens = BlockTokens::default();
+    generate_block(registers, &ctx, &mut sb_tokens);
+
+    let BlockTokens {
+        fields,
+        defaults,
+        hooks,
+        read_arms,
+        write_arms,
+    } = sb_tokens;
+    struct_tokens.extend(quote! {
+        #[derive(Clone, Debug, Eq, PartialEq)]
+        pub struct #struct_name {
+            #fields
+        }
+        #[allow(clippy::derivable_impls)]
+        impl Default for #struct_name {
+            fn default() -> Self {
+                Self {
+                    #defaults
+                }
+            }
+        }
+    });
+    tokens.hooks.extend(hooks);
+
+    let comment = format!(" Registers of `{}`.", sb.block().name);
+    let start_literal = hex_literal(start);
+    let end = hex_literal(start + stride * len.unwrap_or(1) as u64 - 1);
+    let stride = hex_literal(stride);
+    let offset = offset_from(start);
+    let select_stmt = match len {
+        Some(len) => {
+            let len = hex_literal(len as u64);
+            tokens.fields.extend(quote! {
+                #[doc = #comment]
+                pub #field_name: [#struct_name; #len],
+            });
+            tokens.defaults.extend(quote! {
+                #field_name: core::array::from_fn(|_| Default::default()),
+            });
+            quote! {
+                let block_index = #offset as usize / #stride;
+                let addr = #offset % #stride;
+            }
+        }
+        None => {
+            tokens.fields.extend(quote! {
+                #[doc = #comment]
+                pub #field_name: #struct_name,
+            });
+            tokens.defaults.extend(quote! {
+                #field_name: Default::default(),
+            });
+            if start == 0 {
+                quote! {}
+            } else {
+                quote! {
+                    let addr = #offset;
+                }
+            }
+        }
+    };
+    tokens.read_arms.extend(quote! {
+        #start_literal..=#end => {
+            #select_stmt
+            match addr {
+                #read_arms
+                _ => Err(caliptra_emu_bus::BusError::LoadAccessFault),
+            }
+        }
+    });
+    tokens.write_arms.extend(quote! {
+        #start_literal..=#end => {
+            #select_stmt
+            match addr {
+                #write_arms
+                _ => Err(caliptra_emu_bus::BusError::StoreAccessFault),
+            }
+        }
+    });
+}
+
+/// Generates register storage, reset values and bus dispatch for an emulator
+/// peripheral implementing `block`.
+///
+/// The generated code contains:
+///
+/// * `Registers`, which holds the value of every register. Its `Default`
+///   implementation returns the reset values. Register arrays are flattened
+///   in row-major order. Memories are stored like register arrays, with one
+///   element per entry.
+/// * `Peripheral`, a trait with a hook for every kind of access (read or
+///   write) software can make to each register. The hooks do nothing by
+///   default; the peripheral overrides the ones for registers with side
+///   effects.
+/// * `read()` and `write()`, which a peripheral calls from its
+///   `caliptra_emu_bus::Bus` implementation. They dispatch on the offset from
+///   the start of the block, apply the access type of each field (writes to
+///   RO bits are ignored, W1C bits are cleared by writing 1, WO bits read as
+///   0, ...), and call the hooks. Accesses to registers with no readable or
+///   writable fields fault, like `ReadOnlyRegister` and `WriteOnlyRegister`.
+pub fn generate_emulator_code(block: &ValidatedRegisterBlock) -> TokenStream {
+    let mut tokens = BlockTokens::default();
+    let ctx = BlockContext {
+        storage: quote! {},
+        hook_prefix: String::new(),
+        name_prefix: String::new(),
+        is_array: false,
+    };
+    generate_block(&block.block().registers, &ctx, &mut tokens);
+    generate_block(&memory_registers(block.block()), &ctx, &mut tokens);
+
+    // TODO: Do this recursively
+    let mut subblock_struct_tokens = TokenStream::new();
+    for sb in block.block().sub_blocks.iter() {
+        generate_sub_block(sb, &mut subblock_struct_tokens, &mut tokens);
+    }
+
+    let BlockTokens {
+        fields,
+        defaults,
+        hooks,
+        read_arms,
+        write_arms,
+    } = tokens;
+    quote! {
+        /// The values held by the peripheral's registers. `Default` returns
+        /// the reset values.
+        #[derive(Clone, Debug, Eq, PartialEq)]
+        pub struct Registers {
+            #fields
+        }
+        #[allow(clippy::derivable_impls)]
+        impl Default for Registers {
+            fn default() -> Self {
+                Self {
+                    #defaults
+                }
+            }
+        }
+
+        #subblock_struct_tokens
+
+        /// Implemented by the emulator peripheral to provide the register
+        /// storage and the side effects of register accesses.
+        pub trait Peripheral {
+            fn regs(&self) -> &Registers;
+            fn regs_mut(&mut self) -> &mut Registers;
+
+            #hooks
+        }
+
+        /// Reads the register at `addr`, relative to the start of the
+        /// peripheral.
+        pub fn read(
+            periph: &mut impl Peripheral,
+            size: caliptra_emu_types::RvSize,
+            addr: caliptra_emu_types::RvAddr,
+        ) -> Result<caliptra_emu_types::RvData, caliptra_emu_bus::BusError> {
+            if size != caliptra_emu_types::RvSize::Word || addr % 4 != 0 {
+                return Err(caliptra_emu_bus::BusError::LoadAccessFault);
+            }
+            match addr {
+                #read_arms
+                _ => Err(caliptra_emu_bus::BusError::LoadAccessFault),
+            }
+        }
+
+        /// Writes `val` to the register at `addr`, relative to the start of
+        /// the peripheral.
+        pub fn write(
+            periph: &mut impl Peripheral,
+            size: caliptra_emu_types::RvSize,
+            addr: caliptra_emu_types::RvAddr,
+            val: caliptra_emu_types::RvData,
+        ) -> Result<(), caliptra_emu_bus::BusError> {
+            if size != caliptra_emu_types::RvSize::Word || addr % 4 != 0 {
+                return Err(caliptra_emu_bus::BusError::StoreAccessFault);
+            }
+            match addr {
+                #write_arms
+                _ => Err(caliptra_emu_bus::BusError::StoreAccessFault),
+            }
+        }
+    }
+}
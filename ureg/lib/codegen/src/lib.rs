@@ -7,10 +7,17 @@ use std::{collections::HashMap, rc::Rc, str::FromStr};
 use proc_macro2::{Ident, Literal, TokenStream};
 use quote::{format_ident, quote};
 use ureg_schema::{
-    Enum, EnumVariant, FieldType, Register, RegisterSubBlock, RegisterType, RegisterWidth,
-    ValidatedRegisterBlock,
+    Enum, EnumVariant, FieldType, Register, RegisterBlock, RegisterSubBlock, RegisterType,
+    RegisterWidth, ValidatedRegisterBlock,
 };
 
+mod c_header;
+mod emulator;
+mod svd;
+pub use c_header::generate_c_header;
+pub use emulator::generate_emulator_code;
+pub use svd::generate_svd;
+
 fn tweak_keywords(s: &str) -> &str {
     match s {
         "as" => "as_",
@@ -373,8 +380,25 @@ fn generate_register(reg: &RegisterType) -> TokenStream {
         let field_ident = snake_ident(&field.name);
         let position = Literal::u64_unsuffixed(field.position.into());
         let mask = hex_literal((1u64 << field.width) - 1);
-        let access_expr = quote! {
-            (self.0 >> #position) & #mask
+        // The bits of msb0 fields are stored in reverse order.
+        let reverse = field.msb0 && field.width > 1;
+        let reverse_shift =
+            Literal::u64_unsuffixed(reg.width.in_bytes() * 8 - u64::from(field.width));
+        let access_expr = if reverse {
+            quote! {
+                ((self.0 >> #position) & #mask).reverse_bits() >> #reverse_shift
+            }
+        } else {
+            quote! {
+                (self.0 >> #position) & #mask
+            }
+        };
+        let field_bits = |val: TokenStream| {
+            if reverse {
+                quote! { (#val.reverse_bits() >> #reverse_shift) }
+            } else {
+                val
+            }
         };
         let comment = &field.comment.replace("<br>", "\n");
         if field.ty.can_read() {
@@ -411,9 +435,12 @@ fn generate_register(reg: &RegisterType) -> TokenStream {
             if let Some(ref enum_type) = field.enum_type {
                 let enum_type_ident = camel_ident(enum_type.name.as_ref().unwrap());
                 let enum_selector_type = format_ident!("{}Selector", enum_type_ident);
+                let val = field_bits(quote! {
+                    #raw_type::from(f(super::enums::selector::#enum_selector_type()))
+                });
                 write_val_tokens.extend(quote! {
                     pub fn #field_ident(self, f: impl FnOnce(super::enums::selector::#enum_selector_type) -> super::enums::#enum_type_ident) -> Self {
-                        Self((self.0 & !(#mask << #position)) | (#raw_type::from(f(super::enums::selector::#enum_selector_type())) << #position))
+                        Self((self.0 & !(#mask << #position)) | (#val << #position))
                     }
                 });
             } else if field.width == 1 {
@@ -423,9 +450,10 @@ fn generate_register(reg: &RegisterType) -> TokenStream {
                     }
                 });
             } else {
+                let val = field_bits(quote! { (val & #mask) });
                 write_val_tokens.extend(quote! {
                     pub fn #field_ident(self, val: #raw_type) -> Self {
-                        Self((self.0 & !(#mask << #position)) | ((val & #mask) << #position))
+                        Self((self.0 & !(#mask << #position)) | (#val << #position))
                     }
                 });
             }
@@ -511,6 +539,82 @@ fn generate_register(reg: &RegisterType) -> TokenStream {
     result
 }
 
+#[cfg(test)]
+mod generate_register_tests {
+    use ureg_schema::RegisterField;
+
+    use crate::*;
+
+    #[test]
+    fn test_generate_register_msb0() {
+        let reg = RegisterType {
+            name: Some("ctrl".into()),
+            width: RegisterWidth::_32,
+            fields: vec![RegisterField {
+                name: "mode".into(),
+                ty: FieldType::RW,
+                position: 4,
+                width: 4,
+                msb0: true,
+                ..Default::default()
+            }],
+        };
+        assert_eq!(
+            generate_register(&reg).to_string(),
+            quote! {
+                #[derive(Clone, Copy)]
+                pub struct CtrlReadVal(u32);
+                impl CtrlReadVal {
+                    #[doc = ""]
+                    #[inline(always)]
+                    pub fn mode(&self) -> u32 {
+                        ((self.0 >> 4) & 0xf).reverse_bits() >> 28
+                    }
+                    /// Construct a WriteVal that can be used to modify the contents of this register value.
+                    #[inline(always)]
+                    pub fn modify(self) -> CtrlWriteVal {
+                        CtrlWriteVal(self.0)
+                    }
+                }
+                impl From<u32> for CtrlReadVal {
+                    #[inline(always)]
+                    fn from(val: u32) -> Self {
+                        Self(val)
+                    }
+                }
+                impl From<CtrlReadVal> for u32 {
+                    #[inline(always)]
+                    fn from(val: CtrlReadVal) -> u32 {
+                        val.0
+                    }
+                }
+                #[derive(Clone, Copy)]
+                pub struct CtrlWriteVal(u32);
+                impl CtrlWriteVal {
+                    #[doc = ""]
+                    #[inline(always)]
+                    pub fn mode(self, val: u32) -> Self {
+                        Self((self.0 & !(0xf << 4)) | (((val & 0xf).reverse_bits() >> 28) << 4))
+                    }
+                }
+                impl From<u32> for CtrlWriteVal {
+                    #[inline(always)]
+                    fn from(val: u32) -> Self {
+                        Self(val)
+                    }
+                }
+                impl From<CtrlWriteVal> for u32 {
+                    #[inline(always)]
+                    fn from(val: CtrlWriteVal) -> u32 {
+                        val.0
+                    }
+                }
+            }
+            .to_string()
+        );
+    }
+}
+
 fn generate_register_types<'a>(regs: impl Iterator<Item = &'a RegisterType>) -> TokenStream {
     let mut regs: Vec<_> = regs.collect();
     regs.sort_by_key(|e| &e.name);
@@ -551,6 +655,20 @@ fn read_write_types(t: &RegisterType, options: &OptionsInternal) -> (TokenStream
     }
 }
 
+/// Returns the memories of `block` as register arrays.
+pub(crate) fn memory_registers(block: &RegisterBlock) -> Vec<Rc<Register>> {
+    block
+        .memories
+        .iter()
+        .map(|mem| {
+            Rc::new(
+                mem.as_register()
+                    .unwrap_or_else(|| panic!("memory {:?} must have 32-bit entries", mem.name)),
+            )
+        })
+        .collect()
+}
+
 fn generate_array_type(
     mut remaining_dimensions: impl Iterator<Item = u64>,
     reg_type_tokens: TokenStream,
@@ -757,11 +875,13 @@ pub fn generate_code(block: &ValidatedRegisterBlock, options: Options) -> TokenS
 
     let mut instance_type_tokens = TokenStream::new();
 
-    if !block.block().registers.is_empty() {
+    let memory_registers = memory_registers(block.block());
+    if !block.block().registers.is_empty() || !memory_registers.is_empty() {
         let max_reg_width = block
             .block()
             .registers
             .iter()
+            .chain(memory_registers.iter())
             .map(|r| r.ty.width)
             .max()
             .unwrap();
@@ -829,6 +949,14 @@ pub fn generate_code(block: &ValidatedRegisterBlock, options: Options) -> TokenS
             "",
             &options,
         );
+        generate_block_registers(
+            &memory_registers,
+            &raw_ptr_type,
+            &mut meta_tokens,
+            &mut block_inner_tokens,
+            "",
+            &options,
+        );
 
         for sb in block.block().sub_blocks.iter() {
             // TODO: Do this recursively
@@ -864,7 +992,7 @@ pub fn generate_code(block: &ValidatedRegisterBlock, options: Options) -> TokenS
                         pub fn #subblock_fn_name(&self, index: usize) -> #subblock_name<&TMmio> {
                             assert!(index < #len);
                             #subblock_name{
-                                ptr: unsafe { self.ptr.add((#start_offset + index * #stride) / core::mem::size_of::<#raw_ptr_type>()) }
+                                ptr: unsafe { self.ptr.add((#start_offset + index * #stride) / core::mem::size_of::<#raw_ptr_type>()) },
                                 mmio: core::borrow::Borrow::borrow(&self.mmio),
                             }
                         }
@@ -0,0 +1,355 @@
+/*++
+Licensed under the Apache-2.0 license.
+--*/
+
+//! Generates a CMSIS-SVD device description from register blocks, for use
+//! by debuggers and SoC tooling.
+
+use std::fmt::Write;
+
+use ureg_schema::{FieldType, Register, RegisterBlock, RegisterSubBlock, ValidatedRegisterBlock};
+
+use crate::c_header::{c_ident, comment_summary};
+use crate::memory_registers;
+
+fn xml_escape(s: &str) -> String {
+    let mut result = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => result.push_str("&amp;"),
+            '<' => result.push_str("&lt;"),
+            '>' => result.push_str("&gt;"),
+            '"' => result.push_str("&quot;"),
+            '\'' => result.push_str("&apos;"),
+            c => result.push(c),
+        }
+    }
+    result
+}
+
+fn write_description(out: &mut String, indent: &str, comment: &str) {
+    let summary = comment_summary(comment);
+    if !summary.is_empty() {
+        writeln!(
+            out,
+            "{indent}<description>{}</description>",
+            xml_escape(summary)
+        )
+        .unwrap();
+    }
+}
+
+/// Returns the SVD `access` and `modifiedWriteValues` of a field type.
+fn field_access(ty: FieldType) -> (&'static str, Option<&'static str>) {
+    match ty {
+        FieldType::RW => ("read-write", None),
+        FieldType::RO => ("read-only", None),
+        FieldType::WO => ("write-only", None),
+        FieldType::WC | FieldType::WRC => ("write-only", Some("clear")),
+        FieldType::W1C => ("read-write", Some("oneToClear")),
+        FieldType::W1S => ("read-write", Some("oneToSet")),
+    }
+}
+
+fn register_access(reg: &Register) -> &'static str {
+    let can_read = reg.ty.fields.iter().any(|f| f.ty.can_read());
+    let can_write = reg.ty.fields.iter().any(|f| f.ty != FieldType::RO);
+    match (can_read, can_write) {
+        (false, true) => "write-only",
+        (true, false) => "read-only",
+        _ => "read-write",
+    }
+}
+
+/// Writes the `dim`, `dimIncrement` and `name` elements, using the SVD
+/// array syntax if `len` is set. Multi-dimensional register arrays are
+/// flattened.
+fn write_dim_name(out: &mut String, indent: &str, name: &str, len: Option<u64>, stride: u64) {
+    match len {
+        Some(len) => {
+            writeln!(out, "{indent}<dim>{len}</dim>").unwrap();
+            writeln!(out, "{indent}<dimIncrement>{stride:#x}</dimIncrement>").unwrap();
+            writeln!(out, "{indent}<name>{name}[%s]</name>").unwrap();
+        }
+        None => writeln!(out, "{indent}<name>{name}</name>").unwrap(),
+    }
+}
+
+fn write_register(out: &mut String, indent: &str, reg: &Register) {
+    let size = reg.ty.width.in_bytes();
+    let len = (!reg.array_dimensions.is_empty()).then(|| reg.array_dimensions.iter().product());
+    writeln!(out, "{indent}<register>").unwrap();
+    let inner = format!("{indent}  ");
+    write_dim_name(out, &inner, &c_ident(&reg.name), len, size);
+    write_description(out, &inner, &reg.comment);
+    if let Some(alias_of) = &reg.alias_of {
+        writeln!(
+            out,
+            "{inner}<alternateRegister>{}</alternateRegister>",
+            c_ident(alias_of)
+        )
+        .unwrap();
+    }
+    writeln!(
+        out,
+        "{inner}<addressOffset>{:#x}</addressOffset>",
+        reg.offset
+    )
+    .unwrap();
+    writeln!(out, "{inner}<size>{}</size>", size * 8).unwrap();
+    writeln!(out, "{inner}<access>{}</access>", register_access(reg)).unwrap();
+    writeln!(
+        out,
+        "{inner}<resetValue>{:#x}</resetValue>",
+        reg.default_val
+    )
+    .unwrap();
+    if !reg.ty.fields.is_empty() {
+        writeln!(out, "{inner}<fields>").unwrap();
+        for field in reg.ty.fields.iter() {
+            let (access, modified_write_values) = field_access(field.ty);
+            writeln!(out, "{inner}  <field>").unwrap();
+            writeln!(out, "{inner}    <name>{}</name>", c_ident(&field.name)).unwrap();
+            write_description(out, &format!("{inner}    "), &field.comment);
+            writeln!(out, "{inner}    <bitOffset>{}</bitOffset>", field.position).unwrap();
+            writeln!(out, "{inner}    <bitWidth>{}</bitWidth>", field.width).unwrap();
+            writeln!(out, "{inner}    <access>{access}</access>").unwrap();
+            if let Some(modified_write_values) = modified_write_values {
+                writeln!(
+                    out,
+                    "{inner}    <modifiedWriteValues>{modified_write_values}</modifiedWriteValues>"
+                )
+                .unwrap();
+            }
+            if let Some(e) = &field.enum_type {
+                writeln!(out, "{inner}    <enumeratedValues>").unwrap();
+                for variant in e.variants.iter() {
+                    writeln!(out, "{inner}      <enumeratedValue>").unwrap();
+                    writeln!(
+                        out,
+                        "{inner}        <name>{}</name>",
+                        c_ident(&variant.name)
+                    )
+                    .unwrap();
+                    writeln!(out, "{inner}        <value>{}</value>", variant.value).unwrap();
+                    writeln!(out, "{inner}      </enumeratedValue>").unwrap();
+                }
+                writeln!(out, "{inner}    </enumeratedValues>").unwrap();
+            }
+            writeln!(out, "{inner}  </field>").unwrap();
+        }
+        writeln!(out, "{inner}</fields>").unwrap();
+    }
+    writeln!(out, "{indent}</register>").unwrap();
+}
+
+/// Returns the number of bytes spanned by the registers and memories of
+/// `block`.
+fn block_size(block: &RegisterBlock) -> u64 {
+    let memories = memory_registers(block);
+    let registers = block.registers.iter().chain(memories.iter()).map(|reg| {
+        let len: u64 = reg.array_dimensions.iter().product();
+        reg.offset + len * reg.ty.width.in_bytes()
+    });
+    let sub_blocks = block.sub_blocks.iter().map(|sb| match sb {
+        RegisterSubBlock::Single {
+            block,
+            start_offset,
+        } => start_offset + block_size(block),
+        RegisterSubBlock::Array {
+            start_offset,
+            stride,
+            len,
+            ..
+        } => start_offset + stride * *len as u64,
+    });
+    registers.chain(sub_blocks).max().unwrap_or(0)
+}
+
+fn write_peripheral(out: &mut String, block: &RegisterBlock) {
+    let mut instances = block.instances.iter();
+    let Some(first) = instances.next() else {
+        return;
+    };
+    let first_name = c_ident(&first.name);
+    out.push_str("    <peripheral>\n");
+    writeln!(out, "      <name>{first_name}</name>").unwrap();
+    writeln!(out, "      <groupName>{}</groupName>", c_ident(&block.name)).unwrap();
+    writeln!(out, "      <baseAddress>{:#x}</baseAddress>", first.address).unwrap();
+    out.push_str("      <addressBlock>\n");
+    out.push_str("        <offset>0x0</offset>\n");
+    writeln!(out, "        <size>{:#x}</size>", block_size(block)).unwrap();
+    out.push_str("        <usage>registers</usage>\n");
+    out.push_str("      </addressBlock>\n");
+    out.push_str("      <registers>\n");
+    for reg in block.registers.iter().chain(memory_registers(block).iter()) {
+        write_register(out, "        ", reg);
+    }
+    // TODO: Do this recursively
+    for sb in block.sub_blocks.iter() {
+        let (len, stride) = match sb {
+            RegisterSubBlock::Single { .. } => (None, 0),
+            RegisterSubBlock::Array { len, stride, .. } => (Some(*len as u64), *stride),
+        };
+        out.push_str("        <cluster>\n");
+        write_dim_name(out, "          ", &c_ident(&sb.block().name), len, stride);
+        writeln!(
+            out,
+            "          <addressOffset>{:#x}</addressOffset>",
+            sb.start_offset()
+        )
+        .unwrap();
+        for reg in sb.block().registers.iter() {
+            write_register(out, "          ", reg);
+        }
+        out.push_str("        </cluster>\n");
+    }
+    out.push_str("      </registers>\n");
+    out.push_str("    </peripheral>\n");
+
+    for instance in instances {
+        writeln!(
+            out,
+            "    <peripheral derivedFrom=\"{first_name}\">\n      <name>{}</name>\n      <baseAddress>{:#x}</baseAddress>\n    </peripheral>",
+            c_ident(&instance.name),
+            instance.address
+        )
+        .unwrap();
+    }
+}
+
+/// Generates a CMSIS-SVD description of a device made up of `blocks`. Each
+/// instance of a block becomes a peripheral; blocks without instances are
+/// skipped.
+pub fn generate_svd<'a>(
+    device_name: &str,
+    blocks: impl IntoIterator<Item = &'a ValidatedRegisterBlock>,
+) -> String {
+    let mut out = String::new();
+    out.push_str("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+    out.push_str("<device schemaVersion=\"1.3\" xmlns:xs=\"http://www.w3.org/2001/XMLSchema-instance\" xs:noNamespaceSchemaLocation=\"CMSIS-SVD.xsd\">\n");
+    writeln!(out, "  <name>{}</name>", xml_escape(device_name)).unwrap();
+    out.push_str("  <version>1.0</version>\n");
+    out.push_str("  <addressUnitBits>8</addressUnitBits>\n");
+    out.push_str("  <width>32</width>\n");
+    out.push_str("  <size>32</size>\n");
+    out.push_str("  <peripherals>\n");
+    for block in blocks {
+        write_peripheral(&mut out, block.block());
+    }
+    out.push_str("  </peripherals>\n");
+    out.push_str("</device>\n");
+    out
+}
+
+#[cfg(test)]
+mod generate_svd_tests {
+    use std::rc::Rc;
+
+    use ureg_schema::{RegisterBlockInstance, RegisterField, RegisterType};
+
+    use super::*;
+
+    #[test]
+    fn test_generate_svd() {
+        let status = Rc::new(RegisterType {
+            name: Some("status".into()),
+            fields: vec![
+                RegisterField {
+                    name: "ready".into(),
+                    ty: FieldType::RO,
+                    width: 1,
+                    comment: "Engine is <idle>".into(),
+                    ..Default::default()
+                },
+                RegisterField {
+                    name: "error".into(),
+                    ty: FieldType::W1C,
+                    position: 1,
+                    width: 1,
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        });
+        let block = RegisterBlock {
+            name: "foo".into(),
+            instances: vec![
+                RegisterBlockInstance {
+                    name: "foo0".into(),
+                    address: 0x1000_0000,
+                },
+                RegisterBlockInstance {
+                    name: "foo1".into(),
+                    address: 0x1001_0000,
+                },
+            ],
+            registers: vec![Rc::new(Register {
+                name: "STATUS".into(),
+                offset: 0x4,
+                array_dimensions: vec![2],
+                ty: status,
+                ..Default::default()
+            })],
+            ..Default::default()
+        }
+        .validate_and_dedup()
+        .unwrap();
+
+        assert_eq!(
+            generate_svd("caliptra", [&block]),
+            r#"<?xml version="1.0" encoding="utf-8"?>
+<device schemaVersion="1.3" xmlns:xs="http://www.w3.org/2001/XMLSchema-instance" xs:noNamespaceSchemaLocation="CMSIS-SVD.xsd">
+  <name>caliptra</name>
+  <version>1.0</version>
+  <addressUnitBits>8</addressUnitBits>
+  <width>32</width>
+  <size>32</size>
+  <peripherals>
+    <peripheral>
+      <name>FOO0</name>
+      <groupName>FOO</groupName>
+      <baseAddress>0x10000000</baseAddress>
+      <addressBlock>
+        <offset>0x0</offset>
+        <size>0xc</size>
+        <usage>registers</usage>
+      </addressBlock>
+      <registers>
+        <register>
+          <dim>2</dim>
+          <dimIncrement>0x4</dimIncrement>
+          <name>STATUS[%s]</name>
+          <addressOffset>0x4</addressOffset>
+          <size>32</size>
+          <access>read-write</access>
+          <resetValue>0x0</resetValue>
+          <fields>
+            <field>
+              <name>READY</name>
+              <description>Engine is &lt;idle&gt;</description>
+              <bitOffset>0</bitOffset>
+              <bitWidth>1</bitWidth>
+              <access>read-only</access>
+            </field>
+            <field>
+              <name>ERROR</name>
+              <bitOffset>1</bitOffset>
+              <bitWidth>1</bitWidth>
+              <access>read-write</access>
+              <modifiedWriteValues>oneToClear</modifiedWriteValues>
+            </field>
+          </fields>
+        </register>
+      </registers>
+    </peripheral>
+    <peripheral derivedFrom="FOO0">
+      <name>FOO1</name>
+      <baseAddress>0x10010000</baseAddress>
+    </peripheral>
+  </peripherals>
+</device>
+"#
+        );
+    }
+}
@@ -3,7 +3,7 @@ Licensed under the Apache-2.0 license.
 --*/
 
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{BTreeMap, HashMap, HashSet},
     rc::Rc,
 };
 
@@ -25,12 +25,41 @@ pub struct RegisterField {
 
     /// The width of the field in bits
     pub width: u8,
+
+    /// True if the most significant bit of the field's value is at
+    /// `position` rather than at `position + width - 1`.
+    pub msb0: bool,
+
+    /// If this field is an interrupt, how it is triggered.
+    pub interrupt: Option<InterruptType>,
+
+    /// Once set by hardware, the field holds its value until software clears
+    /// it.
+    pub sticky: bool,
+
+    /// Hardware increments or decrements the field.
+    pub counter: bool,
+
+    /// Values of user-defined properties, keyed by property name.
+    pub user_properties: BTreeMap<String, PropertyValue>,
 }
 impl RegisterField {
     /// A mask of the bits of this field.
     pub fn mask(&self) -> u64 {
         ((1u64 << self.width) - 1) << self.position
     }
+
+    /// Returns the register bits that hold field value `val`. The bits of
+    /// msb0 fields are stored in reverse order.
+    pub fn reg_bits(&self, val: u64) -> u64 {
+        let val = val & ((1u64 << self.width) - 1);
+        let val = if self.msb0 && self.width > 0 {
+            val.reverse_bits() >> (64 - u32::from(self.width))
+        } else {
+            val
+        };
+        val << self.position
+    }
 }
 #[cfg(test)]
 mod registerfield_tests {
@@ -108,6 +137,24 @@ mod registerfield_tests {
             0x80000000
         );
     }
+
+    #[test]
+    fn test_reg_bits() {
+        let field = RegisterField {
+            position: 4,
+            width: 4,
+            ..Default::default()
+        };
+        assert_eq!(field.reg_bits(0x5), 0x50);
+        assert_eq!(field.reg_bits(0x15), 0x50);
+
+        let field = RegisterField {
+            msb0: true,
+            ..field
+        };
+        assert_eq!(field.reg_bits(0x5), 0xa0);
+        assert_eq!(field.reg_bits(0x1), 0x80);
+    }
 }
 
 /// Represents an memory-mapped I/O register.
@@ -123,6 +170,100 @@ pub struct Register {
     pub array_dimensions: Vec<u64>,
 
     pub ty: Rc<RegisterType>,
+
+    /// If this register is an alternate address for another register in the
+    /// same block, the name of that register.
+    pub alias_of: Option<String>,
+
+    /// Values of user-defined properties, keyed by property name.
+    pub user_properties: BTreeMap<String, PropertyValue>,
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum InterruptType {
+    #[default]
+    Level,
+    PosEdge,
+    NegEdge,
+    BothEdge,
+}
+
+/// The value of a user-defined property.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum PropertyValue {
+    Bool(bool),
+    U64(u64),
+    String(String),
+}
+
+/// A memory-mapped array of storage with no register fields.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Memory {
+    pub name: String,
+    pub comment: String,
+
+    /// The offset of the memory from the start of the register block.
+    pub offset: u64,
+
+    pub array_dimensions: Vec<u64>,
+
+    /// The number of entries in the memory.
+    pub entries: u64,
+
+    /// The width of each entry in bits.
+    pub width: u64,
+
+    /// How software can access the memory.
+    pub ty: FieldType,
+
+    /// Values of user-defined properties, keyed by property name.
+    pub user_properties: BTreeMap<String, PropertyValue>,
+}
+impl Memory {
+    /// Describes the memory as an array of registers with one entry each, so
+    /// code generators can expose it like any other register array. Returns
+    /// None if the entries aren't 32 bits wide.
+    pub fn as_register(&self) -> Option<Register> {
+        if self.width != 32 {
+            return None;
+        }
+        Some(Register {
+            name: self.name.clone(),
+            comment: self.comment.clone(),
+            offset: self.offset,
+            array_dimensions: self
+                .array_dimensions
+                .iter()
+                .copied()
+                .chain([self.entries])
+                .collect(),
+            ty: Rc::new(RegisterType {
+                name: Some(format!("{}_entry", self.name)),
+                width: RegisterWidth::_32,
+                fields: vec![RegisterField {
+                    name: "data".into(),
+                    ty: self.ty,
+                    width: 32,
+                    ..Default::default()
+                }],
+            }),
+            user_properties: self.user_properties.clone(),
+            ..Default::default()
+        })
+    }
+}
+
+/// A hardware signal declared alongside the registers of a block. Signals
+/// aren't memory-mapped.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Signal {
+    pub name: String,
+    pub comment: String,
+
+    /// The width of the signal in bits.
+    pub width: u64,
+
+    pub active_low: bool,
 }
 
 #[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
@@ -250,6 +391,12 @@ pub struct RegisterBlock {
 
     // Register types that are "owned" by this block (but might be used by other register blocks)
     pub declared_register_types: Vec<Rc<RegisterType>>,
+
+    pub memories: Vec<Memory>,
+    pub signals: Vec<Signal>,
+
+    /// Values of user-defined properties, keyed by property name.
+    pub user_properties: BTreeMap<String, PropertyValue>,
 }
 impl RegisterBlock {
     pub fn remove_enums(&mut self, register_fields: &[(&str, &str)]) {
@@ -307,6 +307,7 @@ impl ValidatedRegisterBlock {
                             array_dimensions: reg_spec.array_dimensions,
                             offset: reg_spec.min_offset - start_offset,
                             ty: reg_spec.ty,
+                            ..Default::default()
                         })
                     })
                     .collect(),
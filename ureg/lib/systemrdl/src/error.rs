@@ -36,6 +36,10 @@ pub enum Error {
         register_type_name: String,
         err: Box<Error>,
     },
+    MemoryError {
+        memory_name: String,
+        err: Box<Error>,
+    },
     EnumError {
         enum_name: String,
         err: Box<Error>,
@@ -52,6 +56,7 @@ impl Error {
             Self::FieldError { err, .. } => err.root_cause(),
             Self::RegisterError { err, .. } => err.root_cause(),
             Self::RegisterTypeError { err, .. } => err.root_cause(),
+            Self::MemoryError { err, .. } => err.root_cause(),
             Self::EnumError { err, .. } => err.root_cause(),
             Self::EnumVariantError { err, .. } => err.root_cause(),
             err => err,
@@ -89,6 +94,7 @@ impl Display for Error {
             } => {
                 write!(f, "reg_type {register_type_name:?} {err}")
             }
+            Self::MemoryError { memory_name, err } => write!(f, "mem {memory_name:?} {err}"),
             Self::EnumError { enum_name, err } => write!(f, "enum {enum_name:?} {err}"),
             Self::EnumVariantError { variant_name, err } => {
                 write!(f, "variant {variant_name:?} {err}")
@@ -6,6 +6,8 @@ mod error;
 pub use error::Error;
 use ureg::{RegisterSubBlock, RegisterType};
 
+use std::collections::BTreeMap;
+use std::fmt::Debug;
 use std::rc::Rc;
 
 use caliptra_systemrdl as systemrdl;
@@ -44,6 +46,45 @@ fn get_property_opt<T: TryFrom<systemrdl::Value, Error = RdlError<'static>>>(
     scope.property_val_opt::<T>(name).map_err(Error::RdlError)
 }
 
+// Returns the SystemRDL keyword for a value like `AccessType::Rw`
+fn rdl_keyword(val: impl Debug) -> String {
+    format!("{val:?}").to_ascii_lowercase()
+}
+
+fn translate_property_value(val: &systemrdl::Value) -> ureg::PropertyValue {
+    use systemrdl::Value;
+    match val {
+        Value::Bool(val) => ureg::PropertyValue::Bool(*val),
+        Value::U64(val) => ureg::PropertyValue::U64(*val),
+        Value::Bits(val) => ureg::PropertyValue::U64(val.val()),
+        Value::String(val) | Value::EnumReference(val) => ureg::PropertyValue::String(val.clone()),
+        Value::Reference(val) => ureg::PropertyValue::String(val.to_string()),
+        Value::PrecedenceType(val) => ureg::PropertyValue::String(rdl_keyword(val)),
+        Value::AccessType(val) => ureg::PropertyValue::String(rdl_keyword(val)),
+        Value::OnReadType(val) => ureg::PropertyValue::String(rdl_keyword(val)),
+        Value::OnWriteType(val) => ureg::PropertyValue::String(rdl_keyword(val)),
+        Value::AddressingType(val) => ureg::PropertyValue::String(rdl_keyword(val)),
+        Value::Interrupt(val) => ureg::PropertyValue::String(rdl_keyword(val.ty)),
+    }
+}
+
+fn translate_user_properties(scope: &systemrdl::Scope) -> BTreeMap<String, ureg::PropertyValue> {
+    scope
+        .user_properties
+        .iter()
+        .map(|(name, val)| (name.clone(), translate_property_value(val)))
+        .collect()
+}
+
+fn translate_interrupt_type(ty: systemrdl::InterruptType) -> ureg::InterruptType {
+    match ty {
+        systemrdl::InterruptType::Level => ureg::InterruptType::Level,
+        systemrdl::InterruptType::PosEdge => ureg::InterruptType::PosEdge,
+        systemrdl::InterruptType::NegEdge => ureg::InterruptType::NegEdge,
+        systemrdl::InterruptType::BothEdge => ureg::InterruptType::BothEdge,
+    }
+}
+
 fn expect_instance_type(scope: systemrdl::ParentScope, ty: ScopeType) -> Result<(), Error> {
     if scope.scope.ty != ty {
         Err(Error::UnexpectedScopeType {
@@ -136,6 +177,14 @@ fn translate_field(iref: systemrdl::InstanceRef) -> Result<ureg::RegisterField,
             None
         };
 
+    // `intr = false` leaves a boolean in place of the interrupt type
+    let interrupt: Option<systemrdl::Interrupt> =
+        inst.scope.property_val_opt("intr").ok().flatten();
+    // Interrupt fields are sticky unless marked nonsticky
+    let stickybit = get_property_opt(&inst.scope, "stickybit")?
+        .unwrap_or(interrupt.is_some_and(|intr| !intr.nonsticky));
+    let sticky = stickybit || get_property_opt(&inst.scope, "sticky")?.unwrap_or(false);
+
     let description: String = inst
         .scope
         .property_val_opt("desc")
@@ -152,6 +201,11 @@ fn translate_field(iref: systemrdl::InstanceRef) -> Result<ureg::RegisterField,
             .ok_or(Error::OffsetNotDefined)
             .map_err(wrap_err)? as u8,
         width: field_width(inst).map_err(wrap_err)? as u8,
+        msb0: inst.msb0,
+        interrupt: interrupt.map(|intr| translate_interrupt_type(intr.ty)),
+        sticky,
+        counter: get_property_opt(&inst.scope, "counter")?.unwrap_or(false),
+        user_properties: translate_user_properties(&inst.scope),
     };
 
     Ok(result)
@@ -184,17 +238,68 @@ fn translate_register(iref: systemrdl::InstanceRef) -> Result<ureg::Register, Er
             .offset
             .ok_or(Error::OffsetNotDefined)
             .map_err(wrap_err)?,
-        default_val: ty.fields.iter().fold(0, |reset, field| {
-            let width_mask = (1 << field.width) - 1;
-            reset | ((field.default_val & width_mask) << field.position)
-        }),
+        default_val: ty
+            .fields
+            .iter()
+            .fold(0, |reset, field| reset | field.reg_bits(field.default_val)),
         comment: unpad_description(&description),
         array_dimensions: inst.dimension_sizes.clone(),
         ty,
+        alias_of: inst.alias_of.clone(),
+        user_properties: translate_user_properties(&inst.scope),
     };
 
     Ok(result)
 }
+
+fn translate_memory(iref: systemrdl::InstanceRef) -> Result<ureg::Memory, Error> {
+    let wrap_err = |err: Error| Error::MemoryError {
+        memory_name: iref.instance.name.clone(),
+        err: Box::new(err),
+    };
+
+    expect_instance_type(iref.scope, ComponentType::Mem.into()).map_err(wrap_err)?;
+    let inst = iref.instance;
+
+    let description: String = inst
+        .scope
+        .property_val_opt("desc")
+        .unwrap()
+        .unwrap_or_default();
+    let access_ty: AccessType = get_property_opt(&inst.scope, "sw")?.unwrap_or_default();
+
+    Ok(ureg::Memory {
+        name: inst.name.clone(),
+        comment: unpad_description(&description),
+        offset: inst
+            .offset
+            .ok_or(Error::OffsetNotDefined)
+            .map_err(wrap_err)?,
+        array_dimensions: inst.dimension_sizes.clone(),
+        entries: inst
+            .scope
+            .property_val("mementries")
+            .map_err(|err| wrap_err(Error::RdlError(err)))?,
+        // According to section 11.1 of the SystemRDL 2.0 spec, the default memwidth is 32-bits
+        width: get_property_opt(&inst.scope, "memwidth")?.unwrap_or(32),
+        ty: translate_access_type(access_ty).map_err(wrap_err)?,
+        user_properties: translate_user_properties(&inst.scope),
+    })
+}
+
+fn translate_signal(inst: &systemrdl::Instance) -> Result<ureg::Signal, Error> {
+    let description: String = inst
+        .scope
+        .property_val_opt("desc")
+        .unwrap()
+        .unwrap_or_default();
+    Ok(ureg::Signal {
+        name: inst.name.clone(),
+        comment: unpad_description(&description),
+        width: get_property_opt(&inst.scope, "signalwidth")?.unwrap_or(1),
+        active_low: get_property_opt(&inst.scope, "activelow")?.unwrap_or(false),
+    })
+}
 fn translate_register_ty(
     type_name: Option<String>,
     scope: ParentScope,
@@ -257,6 +362,7 @@ fn translate_block(iref: InstanceRef) -> Result<RegisterBlock, Error> {
     let inst = iref.instance;
     let mut block = RegisterBlock {
         name: inst.name.clone(),
+        user_properties: translate_user_properties(&inst.scope),
         ..Default::default()
     };
     if let Some(addr) = inst.offset {
@@ -278,14 +384,41 @@ fn translate_block(iref: InstanceRef) -> Result<RegisterBlock, Error> {
                 .registers
                 .push(Rc::new(translate_register(child).map_err(wrap_err)?));
         }
-        if child.instance.scope.ty == ComponentType::RegFile.into() {
+        if child.instance.scope.ty == ComponentType::RegFile.into()
+            || child.instance.scope.ty == ComponentType::AddrMap.into()
+        {
             let Some(start_offset) = child.instance.offset else {
                 continue;
             };
-            block.sub_blocks.push(RegisterSubBlock::Single {
-                block: translate_block(child)?,
-                start_offset,
-            });
+            let sub_block = translate_block(child)?;
+            if child.instance.dimension_sizes.is_empty() {
+                block.sub_blocks.push(RegisterSubBlock::Single {
+                    block: sub_block,
+                    start_offset,
+                });
+            } else {
+                // Multidimensional arrays are laid out contiguously, so they
+                // can be flattened into a single array.
+                block.sub_blocks.push(RegisterSubBlock::Array {
+                    block: sub_block,
+                    start_offset,
+                    stride: child
+                        .instance
+                        .stride
+                        .unwrap_or(child.instance.element_size()),
+                    len: child.instance.dimension_sizes.iter().product::<u64>() as usize,
+                });
+            }
+        }
+        if child.instance.scope.ty == ComponentType::Mem.into() {
+            block
+                .memories
+                .push(translate_memory(child).map_err(wrap_err)?);
+        }
+        if child.instance.scope.ty == ComponentType::Signal.into() {
+            block
+                .signals
+                .push(translate_signal(child.instance).map_err(wrap_err)?);
         }
     }
     Ok(block)
@@ -299,3 +432,179 @@ pub fn translate_addrmap(addrmap: systemrdl::ParentScope) -> Result<Vec<Register
     }
     Ok(blocks)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    struct StrFileSource(&'static str);
+    impl systemrdl::FileSource for StrFileSource {
+        fn read_to_string(&self, _path: &Path) -> std::io::Result<&str> {
+            Ok(self.0)
+        }
+    }
+
+    // Translates the blocks instantiated in the `clp` addrmap of `src`
+    fn translate(src: &'static str) -> Vec<RegisterBlock> {
+        let fs = StrFileSource(src);
+        let root = systemrdl::Scope::parse_root(&fs, &["main.rdl".into()]).unwrap();
+        let root = root.as_parent();
+        translate_addrmap(root.lookup_typedef("clp").unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_translate_arrays() {
+        let blocks = translate(
+            r#"
+            addrmap blk_t {
+                reg { field {sw = r;} val[31:0]; } data[4];
+                regfile chan_t {
+                    reg { field {sw = rw;} go; } cmd;
+                    reg { field {sw = r;} busy; } status;
+                };
+                chan_t chan[2][3] @0x100;
+                mem { mementries = 16; } ram @0x200;
+            };
+            addrmap clp { blk_t blk @0x1000; };
+        "#,
+        );
+        assert_eq!(blocks.len(), 1);
+        let block = &blocks[0];
+        assert_eq!(block.name, "blk");
+        assert_eq!(block.instances[0].address, 0x1000);
+
+        assert_eq!(block.registers.len(), 1);
+        assert_eq!(block.registers[0].name, "data");
+        assert_eq!(block.registers[0].array_dimensions, vec![4]);
+
+        // Multidimensional regfile arrays are flattened
+        assert_eq!(block.sub_blocks.len(), 1);
+        let RegisterSubBlock::Array {
+            block: chan,
+            start_offset,
+            stride,
+            len,
+        } = &block.sub_blocks[0]
+        else {
+            panic!("expected a sub-block array: {:?}", block.sub_blocks[0]);
+        };
+        assert_eq!((*start_offset, *stride, *len), (0x100, 8, 6));
+        let names: Vec<&str> = chan.registers.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, vec!["cmd", "status"]);
+        assert_eq!(chan.registers[1].offset, 4);
+
+        assert_eq!(block.memories.len(), 1);
+        assert_eq!(block.memories[0].offset, 0x200);
+        assert_eq!(block.memories[0].entries, 16);
+        assert_eq!(block.memories[0].width, 32);
+    }
+
+    #[test]
+    fn test_translate_msb0_fields() {
+        let blocks = translate(
+            r#"
+            addrmap blk_t {
+                reg {
+                    field {sw = rw;} en;
+                    field {sw = rw;} mode[4:7] = 0x5;
+                    field {sw = r;} status[15:8];
+                } ctrl;
+            };
+            addrmap clp { blk_t blk @0x1000; };
+        "#,
+        );
+        let ctrl = &blocks[0].registers[0];
+        let fields: Vec<(&str, u8, u8, bool)> = ctrl
+            .ty
+            .fields
+            .iter()
+            .map(|f| (f.name.as_str(), f.position, f.width, f.msb0))
+            .collect();
+        assert_eq!(
+            fields,
+            vec![
+                ("en", 0, 1, false),
+                ("mode", 4, 4, true),
+                ("status", 8, 8, false),
+            ]
+        );
+        // The bits of mode's reset value are reversed: bit 4 is its MSB
+        assert_eq!(ctrl.default_val, 0xa0);
+    }
+
+    #[test]
+    fn test_translate_aliases() {
+        let blocks = translate(
+            r#"
+            addrmap blk_t {
+                reg ctrl_t { field {sw = rw;} en; };
+                ctrl_t ctrl;
+                alias ctrl ctrl_t ctrl_alias @0x10;
+                alias ctrl reg { field {sw = r;} en; } ctrl_ro @0x14;
+            };
+            addrmap clp { blk_t blk @0x1000; };
+        "#,
+        );
+        let regs: Vec<(&str, u64, Option<&str>)> = blocks[0]
+            .registers
+            .iter()
+            .map(|r| (r.name.as_str(), r.offset, r.alias_of.as_deref()))
+            .collect();
+        assert_eq!(
+            regs,
+            vec![
+                ("ctrl", 0, None),
+                ("ctrl_alias", 0x10, Some("ctrl")),
+                ("ctrl_ro", 0x14, Some("ctrl")),
+            ]
+        );
+        assert_eq!(blocks[0].registers[1].ty.name.as_deref(), Some("ctrl_t"));
+        assert_eq!(blocks[0].registers[2].ty.fields[0].ty, ureg::FieldType::RO);
+    }
+
+    #[test]
+    fn test_translate_nested_addrmaps() {
+        let blocks = translate(
+            r#"
+            addrmap inner_t {
+                reg { field {sw = rw;} f; } inner_reg;
+            };
+            addrmap blk_t {
+                reg { field {sw = rw;} f; } outer_reg;
+                inner_t inner @0x100;
+                inner_t inners[2] @0x200;
+            };
+            addrmap clp { blk_t blk @0x1000; };
+        "#,
+        );
+        assert_eq!(blocks.len(), 1);
+        let block = &blocks[0];
+        assert_eq!(block.registers.len(), 1);
+        assert_eq!(block.registers[0].name, "outer_reg");
+
+        assert_eq!(block.sub_blocks.len(), 2);
+        let RegisterSubBlock::Single {
+            block: inner,
+            start_offset,
+        } = &block.sub_blocks[0]
+        else {
+            panic!("expected a single sub-block: {:?}", block.sub_blocks[0]);
+        };
+        assert_eq!(*start_offset, 0x100);
+        assert_eq!(inner.name, "inner");
+        assert_eq!(inner.registers[0].name, "inner_reg");
+
+        let RegisterSubBlock::Array {
+            block: inners,
+            start_offset,
+            stride,
+            len,
+        } = &block.sub_blocks[1]
+        else {
+            panic!("expected a sub-block array: {:?}", block.sub_blocks[1]);
+        };
+        assert_eq!((*start_offset, *stride, *len), (0x200, 4, 2));
+        assert_eq!(inners.registers[0].name, "inner_reg");
+    }
+}
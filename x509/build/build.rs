@@ -24,6 +24,8 @@ mod tbs;
 #[cfg(feature = "generate_templates")]
 mod x509;
 
+#[cfg(feature = "generate_mldsa87_templates")]
+use x509::MlDsa87Algo;
 #[cfg(feature = "generate_templates")]
 use {
     code_gen::CodeGen,
@@ -40,6 +42,8 @@ fn main() {
 
         gen_init_devid_csr(out_dir);
         gen_local_devid_cert(out_dir);
+        #[cfg(feature = "generate_mldsa87_templates")]
+        gen_local_devid_cert_mldsa87(out_dir);
         gen_fmc_alias_cert(out_dir);
         gen_rt_alias_cert(out_dir);
     }
@@ -71,6 +75,19 @@ fn gen_local_devid_cert(out_dir: &str) {
     CodeGen::gen_code("LocalDevIdCertTbs", template, out_dir);
 }
 
+/// Generate ML-DSA-87 Local DeviceId Certificate Template
+#[cfg(feature = "generate_mldsa87_templates")]
+fn gen_local_devid_cert_mldsa87(out_dir: &str) {
+    let mut usage = KeyUsage::default();
+    usage.set_key_cert_sign(true);
+    let bldr = cert::CertTemplateBuilder::<MlDsa87Algo>::new()
+        .add_basic_constraints_ext(true, 4)
+        .add_key_usage_ext(usage)
+        .add_ueid_ext(&[0xFF; 17]);
+    let template = bldr.tbs_template("Caliptra 1.0 LDevID", "Caliptra 1.0 IDevID");
+    CodeGen::gen_code("LocalDevIdCertTbsMlDsa87", template, out_dir);
+}
+
 /// Generate FMC Alias Certificate Template
 #[cfg(feature = "generate_templates")]
 fn gen_fmc_alias_cert(out_dir: &str) {
@@ -121,6 +138,13 @@ fn gen_rt_alias_cert(out_dir: &str) {
                 hash_alg: asn1::oid!(/*sha384*/ 2, 16, 840, 1, 101, 3, 4, 2, 2),
                 digest: &[0xCD; 48],
             },
+        }])
+        .add_rt_dice_multi_tcb_info_ext(&[FwidParam {
+            name: "TCB_INFO_FMC_TCI",
+            fwid: Fwid {
+                hash_alg: asn1::oid!(/*sha384*/ 2, 16, 840, 1, 101, 3, 4, 2, 2),
+                digest: &[0xCE; 48],
+            },
         }]);
     let template = bldr.tbs_template("Caliptra 1.0 Rt Alias", "Caliptra 1.0 FMC Alias");
     CodeGen::gen_code("RtAliasCertTbs", template, out_dir);
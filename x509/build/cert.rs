@@ -137,9 +137,16 @@ impl<Algo: SigningAlgorithm> CertTemplateBuilder<Algo> {
 
     pub fn add_rt_dice_tcb_info_ext(mut self, fwids: &[FwidParam]) -> Self {
         let svn: u8 = 0xC1;
+        let version = "C2C2C2C2";
+        let vendor_info = [0xC3u8; 20];
 
         self.exts
-            .push(x509::make_rt_dice_tcb_info_ext(svn, fwids))
+            .push(x509::make_rt_dice_tcb_info_ext(
+                svn,
+                version,
+                &vendor_info,
+                fwids,
+            ))
             .unwrap();
 
         self.params.push(CertTemplateParam {
@@ -147,6 +154,16 @@ impl<Algo: SigningAlgorithm> CertTemplateBuilder<Algo> {
             needle: svn.to_be_bytes().to_vec(),
         });
 
+        self.params.push(CertTemplateParam {
+            tbs_param: TbsParam::new("tcb_info_rt_version", 0, version.len()),
+            needle: version.as_bytes().to_vec(),
+        });
+
+        self.params.push(CertTemplateParam {
+            tbs_param: TbsParam::new("tcb_info_rt_vendor_info", 0, vendor_info.len()),
+            needle: vendor_info.to_vec(),
+        });
+
         for fwid in fwids.iter() {
             self.params.push(CertTemplateParam {
                 tbs_param: TbsParam::new(fwid.name, 0, fwid.fwid.digest.len()),
@@ -157,6 +174,45 @@ impl<Algo: SigningAlgorithm> CertTemplateBuilder<Algo> {
         self
     }
 
+    pub fn add_rt_dice_multi_tcb_info_ext(mut self, fmc_fwids: &[FwidParam]) -> Self {
+        let svn: u8 = 0xC4;
+        let version = "C5C5C5C5";
+        let vendor_info = [0xC6u8; 20];
+
+        self.exts
+            .push(x509::make_rt_dice_multi_tcb_info_ext(
+                svn,
+                version,
+                &vendor_info,
+                fmc_fwids,
+            ))
+            .unwrap();
+
+        self.params.push(CertTemplateParam {
+            tbs_param: TbsParam::new("tcb_info_fmc_svn", 0, std::mem::size_of_val(&svn)),
+            needle: svn.to_be_bytes().to_vec(),
+        });
+
+        self.params.push(CertTemplateParam {
+            tbs_param: TbsParam::new("tcb_info_fmc_version", 0, version.len()),
+            needle: version.as_bytes().to_vec(),
+        });
+
+        self.params.push(CertTemplateParam {
+            tbs_param: TbsParam::new("tcb_info_fmc_vendor_info", 0, vendor_info.len()),
+            needle: vendor_info.to_vec(),
+        });
+
+        for fwid in fmc_fwids.iter() {
+            self.params.push(CertTemplateParam {
+                tbs_param: TbsParam::new(fwid.name, 0, fwid.fwid.digest.len()),
+                needle: fwid.fwid.digest.to_vec(),
+            });
+        }
+
+        self
+    }
+
     /// Add Subject Key Id Extension
     ///
     /// # Arguments
@@ -45,6 +45,16 @@ const TCG_UEID_OID: &str = "2.23.133.5.4.4";
 const TCG_TCB_INFO_OID: &str = "2.23.133.5.4.1";
 const TCG_MULTI_TCB_INFO_OID: &str = "2.23.133.5.4.5";
 
+const TCB_INFO_VENDOR: &str = "Caliptra";
+const TCB_INFO_FMC_INFO_MODEL: &str = "FMC";
+const TCB_INFO_RT_INFO_MODEL: &str = "RT";
+
+// DICE layers of the TCBs described in the RT alias certificate. The FMC alias
+// TcbInfo is built by the frozen ROM and keeps its original layout, so the
+// complete FMC entry is carried in the RT alias MultiTcbInfo instead.
+const TCB_INFO_FMC_INFO_LAYER: u64 = 1;
+const TCB_INFO_RT_INFO_LAYER: u64 = 2;
+
 #[derive(asn1::Asn1Write)]
 struct TcbInfo<'a> {
     #[implicit(0)]
@@ -176,6 +186,73 @@ impl SigningAlgorithm for EcdsaSha384Algo {
     }
 }
 
+/// ML-DSA-87 Asymmetric Key Pair
+///
+/// Requires OpenSSL 3.5 or newer, hence the `generate_mldsa87_templates`
+/// feature.
+#[cfg(feature = "generate_mldsa87_templates")]
+pub struct MlDsa87AsymKey {
+    priv_key: PKey<Private>,
+    pub_key: Vec<u8>,
+}
+
+#[cfg(feature = "generate_mldsa87_templates")]
+impl AsymKey for MlDsa87AsymKey {
+    /// Retrieve Private Key
+    fn priv_key(&self) -> &PKey<Private> {
+        &self.priv_key
+    }
+
+    /// Retrieve Public Key
+    fn pub_key(&self) -> &[u8] {
+        &self.pub_key
+    }
+}
+
+#[cfg(feature = "generate_mldsa87_templates")]
+impl Default for MlDsa87AsymKey {
+    /// Returns the "default value" for a type.
+    fn default() -> Self {
+        // PKCS#8 PrivateKeyInfo carrying a seed-only id-ml-dsa-87 private key
+        const PKCS8_SEED_PREFIX: [u8; 22] = [
+            0x30, 0x34, 0x02, 0x01, 0x00, 0x30, 0x0B, 0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65,
+            0x03, 0x04, 0x03, 0x13, 0x04, 0x22, 0x80, 0x20,
+        ];
+        let mut seed = [0u8; 32];
+        openssl::rand::rand_bytes(&mut seed).unwrap();
+        let der = [&PKCS8_SEED_PREFIX[..], &seed].concat();
+        let priv_key = PKey::private_key_from_pkcs8(&der).unwrap();
+        let pub_key = priv_key.raw_public_key().unwrap();
+        Self { priv_key, pub_key }
+    }
+}
+
+/// Identity digest for algorithms that consume the message directly
+#[cfg(feature = "generate_mldsa87_templates")]
+pub struct NoDigest {}
+
+#[cfg(feature = "generate_mldsa87_templates")]
+impl Digest for NoDigest {
+    /// Retrieve the algorithm
+    fn algo() -> MessageDigest {
+        MessageDigest::null()
+    }
+}
+
+#[cfg(feature = "generate_mldsa87_templates")]
+#[derive(Default)]
+pub struct MlDsa87Algo {}
+
+#[cfg(feature = "generate_mldsa87_templates")]
+impl SigningAlgorithm for MlDsa87Algo {
+    type AsymKey = MlDsa87AsymKey;
+    type Digest = NoDigest;
+
+    fn gen_key(&self) -> Self::AsymKey {
+        Self::AsymKey::default()
+    }
+}
+
 bitfield::bitfield! {
     #[derive(Default, Debug, PartialEq, Eq, Clone, Copy)]
     /// Key Usage
@@ -357,20 +434,25 @@ pub fn make_fmc_dice_tcb_info_ext(
 }
 
 // Make a tcg-dice-TcbInfo extension
-pub fn make_rt_dice_tcb_info_ext(svn: u8, fwids: &[FwidParam]) -> X509Extension {
+pub fn make_rt_dice_tcb_info_ext(
+    svn: u8,
+    version: &str,
+    vendor_info: &[u8],
+    fwids: &[FwidParam],
+) -> X509Extension {
     let wide_svn = fixed_width_svn(svn);
     let asn1_fwids: Vec<&Fwid> = fwids.iter().map(|f| &f.fwid).collect();
 
     let rt_info = TcbInfo {
-        vendor: None,
-        model: None,
-        version: None,
+        vendor: Some(asn1::Utf8String::new(TCB_INFO_VENDOR)),
+        model: Some(asn1::Utf8String::new(TCB_INFO_RT_INFO_MODEL)),
+        version: Some(asn1::Utf8String::new(version)),
         svn: Some(wide_svn.into()),
-        layer: None,
-        index: None,
+        layer: Some(TCB_INFO_RT_INFO_LAYER),
+        index: Some(0),
         fwids: Some(asn1::SequenceOfWriter::new(&asn1_fwids)),
         flags: None,
-        vendor_info: None,
+        vendor_info: Some(vendor_info),
         tcb_type: Some(b"RT_INFO"),
         flags_mask: None,
     };
@@ -381,6 +463,38 @@ pub fn make_rt_dice_tcb_info_ext(svn: u8, fwids: &[FwidParam]) -> X509Extension
     X509Extension::new_from_der(&oid, false, &der).unwrap()
 }
 
+// Make a tcg-dice-MultiTcbInfo extension describing the FMC layer
+pub fn make_rt_dice_multi_tcb_info_ext(
+    fmc_svn: u8,
+    fmc_version: &str,
+    fmc_vendor_info: &[u8],
+    fmc_fwids: &[FwidParam],
+) -> X509Extension {
+    let wide_svn = fixed_width_svn(fmc_svn);
+    let asn1_fwids: Vec<&Fwid> = fmc_fwids.iter().map(|f| &f.fwid).collect();
+
+    let fmc_info = TcbInfo {
+        vendor: Some(asn1::Utf8String::new(TCB_INFO_VENDOR)),
+        model: Some(asn1::Utf8String::new(TCB_INFO_FMC_INFO_MODEL)),
+        version: Some(asn1::Utf8String::new(fmc_version)),
+        svn: Some(wide_svn.into()),
+        layer: Some(TCB_INFO_FMC_INFO_LAYER),
+        index: Some(0),
+        fwids: Some(asn1::SequenceOfWriter::new(&asn1_fwids)),
+        flags: None,
+        vendor_info: Some(fmc_vendor_info),
+        tcb_type: Some(b"FMC_INFO"),
+        flags_mask: None,
+    };
+
+    let tcb_infos = asn1::SequenceOfWriter::new(vec![&fmc_info]);
+
+    let der = asn1::write_single(&tcb_infos).unwrap();
+    let der = Asn1OctetString::new_from_bytes(&der).unwrap();
+    let oid = Asn1Object::from_str(TCG_MULTI_TCB_INFO_OID).unwrap();
+    X509Extension::new_from_der(&oid, false, &der).unwrap()
+}
+
 /// Retrieve the TBS from DER encoded vector
 ///
 /// Note: Rust OpenSSL binding is missing the extensions to retrieve TBS portion of the X509
@@ -408,8 +522,9 @@ pub fn get_tbs(der: Vec<u8>) -> Vec<u8> {
         0..=0x7F => der[tbs_len_offset] as usize + 2,
         0x81 => (der[tbs_len_offset + 1]) as usize + 3,
         0x82 => {
-            (((der[tbs_len_offset + 1]) as usize) << u8::BITS)
-                | (((der[tbs_len_offset + 2]) as usize) + 4)
+            ((((der[tbs_len_offset + 1]) as usize) << u8::BITS)
+                | ((der[tbs_len_offset + 2]) as usize))
+                + 4
         }
         _ => panic!("Invalid DER Length"),
     };
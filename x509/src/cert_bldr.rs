@@ -9,11 +9,14 @@ File Name:
 Abstract:
 
     X509 API to construct Certificate or Certificate Signing Request
-    from "To Be Signed" blob and ECDSA-384 Signature.
+    from "To Be Signed" blob and ECDSA-384 or ML-DSA-87 Signature.
 
 --*/
 
+use crate::der::DerWriter;
+
 pub type Ecdsa384CsrBuilder<'a> = Ecdsa384CertBuilder<'a>;
+pub type MlDsa87CsrBuilder<'a> = MlDsa87CertBuilder<'a>;
 
 /// MAX Signature length
 const MAX_ECDSA384_SIG_LEN: usize = 108;
@@ -283,3 +286,91 @@ impl<'a> Ecdsa384CertBuilder<'a> {
         Some(1 + len_bytes + len)
     }
 }
+
+/// ML-DSA-87 Signature
+#[derive(Debug)]
+pub struct MlDsa87Signature {
+    /// Encoded signature
+    pub sig: [u8; Self::SIG_LEN],
+}
+
+impl Default for MlDsa87Signature {
+    /// Returns the "default value" for a type.
+    fn default() -> Self {
+        Self {
+            sig: [0u8; Self::SIG_LEN],
+        }
+    }
+}
+
+impl MlDsa87Signature {
+    /// ML-DSA-87 signature length
+    pub const SIG_LEN: usize = 4627;
+}
+
+/// ML-DSA-87 Certificate Builder
+///
+/// The signature is borrowed rather than copied to keep the builder off the
+/// stack; it is emitted as-is into the signature BIT STRING.
+#[derive(Debug)]
+pub struct MlDsa87CertBuilder<'a> {
+    /// DER encoded To be signed portion
+    tbs: &'a [u8],
+
+    /// Signature
+    sig: &'a MlDsa87Signature,
+
+    /// Length of the signed Cert/CSR
+    len: usize,
+}
+
+impl<'a> MlDsa87CertBuilder<'a> {
+    // DER Encoded Sequence with id-ml-dsa-87 OID
+    const OID_DER: [u8; 13] = [
+        0x30, 0x0B, 0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x03, 0x13,
+    ];
+
+    /// Create an instance of `MlDsa87CertBuilder`
+    ///
+    /// # Arguments
+    ///
+    /// * `tbs` - DER encoded To be signed portion
+    /// * `sig` - Signature of the To be signed portion
+    pub fn new(tbs: &'a [u8], sig: &'a MlDsa87Signature) -> Option<Self> {
+        let len = DerWriter::tlv_len(Self::contents_len(tbs.len())?)?;
+        Some(Self { tbs, sig, len })
+    }
+
+    /// Build the Certificate or Certificate Signing Request
+    ///
+    /// # Arguments
+    ///
+    /// * `buf` - Buffer to construct the certificate in
+    pub fn build(&self, buf: &mut [u8]) -> Option<usize> {
+        if buf.len() < self.len {
+            None?;
+        }
+
+        let mut w = DerWriter::new(buf);
+        w.write_header(DER_SEQ_TAG, Self::contents_len(self.tbs.len())?)?;
+        w.write_raw(self.tbs)?;
+        w.write_raw(&Self::OID_DER)?;
+        w.write_bit_string(DER_BIT_STR_TAG, &self.sig.sig, 0)?;
+        Some(w.len())
+    }
+
+    /// Return the length of Certificate or Certificate Signing Request
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    // Length of the certificate sequence contents
+    fn contents_len(tbs_len: usize) -> Option<usize> {
+        // Signature BIT STRING carries one extra byte for the unused bit count
+        let sig_der_len = DerWriter::tlv_len(MlDsa87Signature::SIG_LEN + 1)?;
+        tbs_len
+            .checked_add(Self::OID_DER.len())?
+            .checked_add(sig_der_len)
+    }
+}
@@ -0,0 +1,243 @@
+/*++
+
+Licensed under the Apache-2.0 license.
+
+File Name:
+
+    der.rs
+
+Abstract:
+
+    Minimal DER encoder writing into a caller supplied buffer.
+
+--*/
+
+/// Largest number of constructed elements in a document written with
+/// [`DerWriter::encode`]
+const MAX_NESTED: usize = 64;
+
+/// DER writer over a fixed buffer
+///
+/// Documents containing constructed elements are written with
+/// [`DerWriter::encode`], which runs the caller's encoder twice: a first pass
+/// only measures, recording the content length of each constructed element,
+/// and the second pass writes every header with its final length. No heap is
+/// used, the buffer only needs to hold the final encoding and at most
+/// [`DerWriter::MAX_NESTED`] constructed elements are supported per document.
+pub struct DerWriter<'a> {
+    buf: &'a mut [u8],
+    pos: usize,
+
+    /// Set during the measuring pass, when nothing is written to `buf`
+    measuring: bool,
+
+    /// Content lengths of the constructed elements, in the order they start
+    nested_lens: [u16; MAX_NESTED],
+
+    /// Number of entries of `nested_lens` recorded by the measuring pass
+    nested_count: usize,
+
+    /// Index of the next constructed element
+    next_nested: usize,
+}
+
+impl<'a> DerWriter<'a> {
+    pub const TAG_BOOLEAN: u8 = 0x01;
+    pub const TAG_INTEGER: u8 = 0x02;
+    pub const TAG_BIT_STRING: u8 = 0x03;
+    pub const TAG_OCTET_STRING: u8 = 0x04;
+    pub const TAG_OID: u8 = 0x06;
+    pub const TAG_UTF8_STRING: u8 = 0x0C;
+    pub const TAG_PRINTABLE_STRING: u8 = 0x13;
+    pub const TAG_GENERALIZED_TIME: u8 = 0x18;
+    pub const TAG_SEQUENCE: u8 = 0x30;
+    pub const TAG_SET: u8 = 0x31;
+
+    /// Largest content length of a single element
+    pub const MAX_LEN: usize = 0xFFFF;
+
+    /// Largest number of constructed elements in a document
+    pub const MAX_NESTED: usize = MAX_NESTED;
+
+    /// Create a writer that starts at the beginning of `buf`
+    ///
+    /// Only primitive elements and headers can be written; use
+    /// [`DerWriter::encode`] for documents with constructed elements.
+    pub fn new(buf: &'a mut [u8]) -> Self {
+        Self {
+            buf,
+            pos: 0,
+            measuring: false,
+            nested_lens: [0; MAX_NESTED],
+            nested_count: 0,
+            next_nested: 0,
+        }
+    }
+
+    /// Encode the elements written by `f` into `buf`
+    ///
+    /// `f` is called twice and must write the same elements both times.
+    ///
+    /// # Returns
+    ///
+    /// Length of the encoding, or `None` if it does not fit in `buf`
+    pub fn encode(buf: &mut [u8], f: impl Fn(&mut DerWriter) -> Option<()>) -> Option<usize> {
+        let mut w = DerWriter::new(&mut []);
+        w.measuring = true;
+        f(&mut w)?;
+        let len = w.pos;
+        if len > buf.len() {
+            return None;
+        }
+
+        let mut w = DerWriter {
+            buf,
+            nested_count: w.next_nested,
+            nested_lens: w.nested_lens,
+            ..DerWriter::new(&mut [])
+        };
+        f(&mut w)?;
+        (w.pos == len).then_some(len)
+    }
+
+    /// Number of bytes written so far
+    pub fn len(&self) -> usize {
+        self.pos
+    }
+
+    /// Check if nothing has been written yet
+    pub fn is_empty(&self) -> bool {
+        self.pos == 0
+    }
+
+    /// Encoded bytes written so far
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.buf[..self.pos]
+    }
+
+    /// Size of an element with `len` bytes of contents, including its header
+    pub fn tlv_len(len: usize) -> Option<usize> {
+        Self::len_size(len)?.checked_add(len)?.checked_add(1)
+    }
+
+    /// Size of the DER encoding of `len`
+    fn len_size(len: usize) -> Option<usize> {
+        match len {
+            0..=0x7F => Some(1),
+            0x80..=0xFF => Some(2),
+            0x100..=Self::MAX_LEN => Some(3),
+            _ => None,
+        }
+    }
+
+    /// Write pre-encoded bytes
+    pub fn write_raw(&mut self, data: &[u8]) -> Option<()> {
+        let end = self.pos.checked_add(data.len())?;
+        if !self.measuring {
+            self.buf.get_mut(self.pos..end)?.copy_from_slice(data);
+        }
+        self.pos = end;
+        Some(())
+    }
+
+    fn write_byte(&mut self, byte: u8) -> Option<()> {
+        self.write_raw(&[byte])
+    }
+
+    fn write_len(&mut self, len: usize) -> Option<()> {
+        match Self::len_size(len)? {
+            1 => self.write_byte(len as u8),
+            2 => self.write_raw(&[0x81, len as u8]),
+            _ => self.write_raw(&[0x82, (len >> 8) as u8, len as u8]),
+        }
+    }
+
+    /// Write the tag and length of an element whose contents follow
+    pub fn write_header(&mut self, tag: u8, len: usize) -> Option<()> {
+        self.write_byte(tag)?;
+        self.write_len(len)
+    }
+
+    /// Write a primitive element with the given tag and contents
+    pub fn write_tlv(&mut self, tag: u8, value: &[u8]) -> Option<()> {
+        self.write_header(tag, value.len())?;
+        self.write_raw(value)
+    }
+
+    /// Write a constructed element whose contents are produced by `f`
+    ///
+    /// Only supported by writers passed in by [`DerWriter::encode`].
+    pub fn write_nested(&mut self, tag: u8, f: impl FnOnce(&mut Self) -> Option<()>) -> Option<()> {
+        let index = self.next_nested;
+        self.next_nested += 1;
+
+        if self.measuring {
+            // Tag and length come first, but only the contents are measured
+            let start = self.pos;
+            f(self)?;
+            let len = self.pos - start;
+            *self.nested_lens.get_mut(index)? = u16::try_from(len).ok()?;
+            self.pos = self.pos.checked_add(Self::len_size(len)?)?.checked_add(1)?;
+            return Some(());
+        }
+
+        if index >= self.nested_count {
+            return None;
+        }
+        let len = usize::from(self.nested_lens[index]);
+        self.write_header(tag, len)?;
+        let start = self.pos;
+        f(self)?;
+        (self.pos - start == len).then_some(())
+    }
+
+    /// Write a SEQUENCE whose contents are produced by `f`
+    pub fn write_sequence(&mut self, f: impl FnOnce(&mut Self) -> Option<()>) -> Option<()> {
+        self.write_nested(Self::TAG_SEQUENCE, f)
+    }
+
+    /// Write an object identifier from its encoded contents
+    pub fn write_oid(&mut self, oid: &[u8]) -> Option<()> {
+        self.write_tlv(Self::TAG_OID, oid)
+    }
+
+    /// Write a BOOLEAN
+    pub fn write_bool(&mut self, val: bool) -> Option<()> {
+        self.write_tlv(Self::TAG_BOOLEAN, &[if val { 0xFF } else { 0x00 }])
+    }
+
+    /// Write a big-endian unsigned integer with the given tag
+    ///
+    /// Leading zeros are stripped and a zero byte is prepended when the most
+    /// significant bit is set, so the value always encodes as non-negative.
+    pub fn write_uint(&mut self, tag: u8, val: &[u8]) -> Option<()> {
+        let clz = val.iter().take_while(|b| **b == 0).count();
+        let val = match val.get(clz..) {
+            Some(v) if !v.is_empty() => v,
+            _ => &[0],
+        };
+        let pad = val[0] > 0x7F;
+        self.write_byte(tag)?;
+        self.write_len(val.len() + usize::from(pad))?;
+        if pad {
+            self.write_byte(0)?;
+        }
+        self.write_raw(val)
+    }
+
+    /// Write a `u32` as an unsigned integer with the given tag
+    pub fn write_u32(&mut self, tag: u8, val: u32) -> Option<()> {
+        self.write_uint(tag, &val.to_be_bytes())
+    }
+
+    /// Write a BIT STRING with the given tag
+    pub fn write_bit_string(&mut self, tag: u8, val: &[u8], unused_bits: u8) -> Option<()> {
+        if unused_bits > 7 || (val.is_empty() && unused_bits != 0) {
+            return None;
+        }
+        self.write_byte(tag)?;
+        self.write_len(val.len().checked_add(1)?)?;
+        self.write_byte(unused_bits)?;
+        self.write_raw(val)
+    }
+}
@@ -0,0 +1,207 @@
+/*++
+
+Licensed under the Apache-2.0 license.
+
+File Name:
+
+    fmc_alias_csr.rs
+
+Abstract:
+
+    FMC Alias Certificate Signing Request related code.
+
+--*/
+
+use crate::{CsrTbsBuilder, Extension, KeyUsage, Name, PublicKey};
+
+/// FMC Alias CSR `To Be Signed` parameters
+pub struct FmcAliasCsrTbsParams<'a> {
+    pub ueid: &'a [u8; 17usize],
+    pub public_key: &'a [u8; 97usize],
+    pub subject_sn: &'a [u8; 64usize],
+}
+
+impl<'a> FmcAliasCsrTbsParams<'a> {
+    pub const UEID_LEN: usize = 17usize;
+    pub const PUBLIC_KEY_LEN: usize = 97usize;
+    pub const SUBJECT_SN_LEN: usize = 64usize;
+}
+
+/// FMC Alias CSR `To Be Signed` portion
+///
+/// Built with `CsrTbsBuilder` rather than a generated template. All
+/// parameters are fixed size, so the encoding is always `TBS_LEN` bytes.
+pub struct FmcAliasCsrTbs {
+    tbs: [u8; Self::TBS_LEN],
+}
+
+impl FmcAliasCsrTbs {
+    pub const TBS_LEN: usize = 325usize;
+
+    const SUBJECT_CN: &'static str = "Caliptra 1.0 FMC Alias";
+
+    /// Create an instance of `FmcAliasCsrTbs`
+    ///
+    /// # Arguments
+    ///
+    /// * `params` - Parameters
+    ///
+    /// # Returns
+    ///
+    /// `None` if the encoding is not `TBS_LEN` bytes long
+    pub fn new(params: &FmcAliasCsrTbsParams) -> Option<Self> {
+        let extensions = [
+            Extension::BasicConstraints {
+                ca: true,
+                path_len: Some(3),
+            },
+            Extension::KeyUsage(KeyUsage::KEY_CERT_SIGN),
+            Extension::Ueid(params.ueid),
+        ];
+        let bldr = CsrTbsBuilder {
+            subject: Name {
+                common_name: Self::SUBJECT_CN,
+                serial_number: params.subject_sn,
+            },
+            public_key: PublicKey::Ecc384(params.public_key),
+            extensions: &extensions,
+        };
+
+        let mut tbs = [0u8; Self::TBS_LEN];
+        if bldr.build(&mut tbs)? != Self::TBS_LEN {
+            return None;
+        }
+        Some(Self { tbs })
+    }
+
+    /// Returns the `To Be Signed` portion
+    pub fn tbs(&self) -> &[u8] {
+        &self.tbs
+    }
+}
+
+#[cfg(all(test, target_family = "unix"))]
+mod tests {
+    use openssl::nid::Nid;
+    use openssl::sha::Sha384;
+    use openssl::{ecdsa::EcdsaSig, x509::X509Req};
+
+    use x509_parser::cri_attributes::ParsedCriAttribute;
+    use x509_parser::extensions::ParsedExtension;
+    use x509_parser::oid_registry::asn1_rs::oid;
+    use x509_parser::prelude::{FromDer, X509CertificationRequest};
+
+    use super::*;
+    use crate::test_util::tests::*;
+    use crate::{Ecdsa384CsrBuilder, Ecdsa384Signature};
+
+    const TEST_UEID: &[u8] = &[0xAB; FmcAliasCsrTbsParams::UEID_LEN];
+
+    fn make_test_csr(subject_key: &Ecc384AsymKey) -> FmcAliasCsrTbs {
+        let params = FmcAliasCsrTbsParams {
+            public_key: &subject_key.pub_key().try_into().unwrap(),
+            subject_sn: &subject_key.hex_str().into_bytes().try_into().unwrap(),
+            ueid: &TEST_UEID.try_into().unwrap(),
+        };
+
+        FmcAliasCsrTbs::new(&params).unwrap()
+    }
+
+    fn sign_csr(csr: &FmcAliasCsrTbs, key: &Ecc384AsymKey) -> Vec<u8> {
+        let mut sha = Sha384::new();
+        sha.update(csr.tbs());
+        let sig = EcdsaSig::sign(&sha.finish(), &key.priv_key().ec_key().unwrap()).unwrap();
+
+        let ecdsa_sig = Ecdsa384Signature {
+            r: sig.r().to_vec_padded(48).unwrap().try_into().unwrap(),
+            s: sig.s().to_vec_padded(48).unwrap().try_into().unwrap(),
+        };
+
+        let builder = Ecdsa384CsrBuilder::new(csr.tbs(), &ecdsa_sig).unwrap();
+        let mut buf = vec![0u8; builder.len()];
+        builder.build(&mut buf).unwrap();
+        buf
+    }
+
+    #[test]
+    fn test_csr_signing() {
+        let key = Ecc384AsymKey::default();
+        let csr = make_test_csr(&key);
+        let buf = sign_csr(&csr, &key);
+
+        let req: X509Req = X509Req::from_der(&buf).unwrap();
+        assert!(req.verify(&req.public_key().unwrap()).unwrap());
+        assert!(req.verify(key.priv_key()).unwrap());
+
+        let entry = |nid| {
+            req.subject_name()
+                .entries_by_nid(nid)
+                .next()
+                .unwrap()
+                .data()
+                .as_slice()
+                .to_vec()
+        };
+        assert_eq!(entry(Nid::COMMONNAME), b"Caliptra 1.0 FMC Alias");
+        assert_eq!(entry(Nid::SERIALNUMBER), key.hex_str().into_bytes());
+    }
+
+    #[test]
+    fn test_extensions() {
+        let key = Ecc384AsymKey::default();
+        let csr = make_test_csr(&key);
+        let buf = sign_csr(&csr, &key);
+
+        let (_, parsed_csr) = X509CertificationRequest::from_der(&buf).unwrap();
+
+        let requested_extensions = parsed_csr
+            .certification_request_info
+            .iter_attributes()
+            .find_map(|attr| {
+                if let ParsedCriAttribute::ExtensionRequest(requested) = attr.parsed_attribute() {
+                    Some(&requested.extensions)
+                } else {
+                    None
+                }
+            })
+            .unwrap();
+
+        // BasicConstraints
+        let bc_ext = requested_extensions
+            .iter()
+            .find(|ext| matches!(ext.parsed_extension(), ParsedExtension::BasicConstraints(_)))
+            .unwrap();
+        let ParsedExtension::BasicConstraints(bc) = bc_ext.parsed_extension() else {
+            panic!("Extension is not BasicConstraints");
+        };
+
+        assert!(bc_ext.critical);
+        assert!(bc.ca);
+        assert_eq!(bc.path_len_constraint, Some(3));
+
+        // KeyUsage
+        let ku_ext = requested_extensions
+            .iter()
+            .find(|ext| matches!(ext.parsed_extension(), ParsedExtension::KeyUsage(_)))
+            .unwrap();
+
+        assert!(ku_ext.critical);
+
+        // UEID
+        let ueid_ext = requested_extensions
+            .iter()
+            .find(|ext| {
+                if let ParsedExtension::UnsupportedExtension { oid } = ext.parsed_extension() {
+                    oid == &oid!(2.23.133 .5 .4 .4)
+                } else {
+                    false
+                }
+            })
+            .unwrap();
+        assert!(!ueid_ext.critical);
+        assert_eq!(
+            ueid_ext.value,
+            [&[0x30, 0x13, 0x04, 0x11], TEST_UEID].concat()
+        );
+    }
+}
@@ -0,0 +1,165 @@
+/*++
+
+Licensed under the Apache-2.0 license.
+
+File Name:
+
+    ldevid_cert_mldsa87.rs
+
+Abstract:
+
+    ML-DSA-87 Local Device ID Certificate related code.
+
+--*/
+
+// Note: All the necessary code is auto generated
+#[cfg(feature = "generate_mldsa87_templates")]
+include!(concat!(
+    env!("OUT_DIR"),
+    "/local_dev_id_cert_tbs_ml_dsa_87.rs"
+));
+#[cfg(not(feature = "generate_mldsa87_templates"))]
+include! {"../build/local_dev_id_cert_tbs_ml_dsa_87.rs"}
+
+#[cfg(all(test, target_family = "unix"))]
+mod tests {
+    use openssl::sign::Signer;
+    use openssl::x509::X509;
+
+    use x509_parser::nom::Parser;
+    use x509_parser::oid_registry::asn1_rs::oid;
+    use x509_parser::oid_registry::Oid;
+    use x509_parser::prelude::X509CertificateParser;
+    use x509_parser::x509::X509Version;
+
+    use super::*;
+    use crate::test_util::tests::*;
+    use crate::{MlDsa87CertBuilder, MlDsa87Signature, NotAfter, NotBefore};
+
+    const TEST_UEID: &[u8] = &[0xAB; LocalDevIdCertTbsMlDsa87Params::UEID_LEN];
+
+    fn make_test_cert(
+        subject_key: &MlDsa87AsymKey,
+        issuer_key: &MlDsa87AsymKey,
+    ) -> LocalDevIdCertTbsMlDsa87 {
+        let params = LocalDevIdCertTbsMlDsa87Params {
+            serial_number: &[0xABu8; LocalDevIdCertTbsMlDsa87Params::SERIAL_NUMBER_LEN],
+            public_key: &subject_key.pub_key().try_into().unwrap(),
+            subject_sn: &subject_key
+                .hex_str()
+                .into_bytes()
+                .as_slice()
+                .try_into()
+                .unwrap(),
+            issuer_sn: &issuer_key.hex_str().into_bytes().try_into().unwrap(),
+            ueid: &TEST_UEID.try_into().unwrap(),
+            subject_key_id: &subject_key.sha1(),
+            authority_key_id: &issuer_key.sha1(),
+            not_before: &NotBefore::default().value,
+            not_after: &NotAfter::default().value,
+        };
+
+        LocalDevIdCertTbsMlDsa87::new(&params)
+    }
+
+    fn sign_test_cert(cert: &LocalDevIdCertTbsMlDsa87, issuer_key: &MlDsa87AsymKey) -> Vec<u8> {
+        let sig = cert
+            .sign(|b| Signer::new_without_digest(issuer_key.priv_key())?.sign_oneshot_to_vec(b))
+            .unwrap();
+
+        let mldsa_sig = MlDsa87Signature {
+            sig: sig.try_into().unwrap(),
+        };
+
+        let builder = MlDsa87CertBuilder::new(cert.tbs(), &mldsa_sig).unwrap();
+        let mut buf = vec![0u8; builder.len()];
+        assert_eq!(builder.build(&mut buf), Some(buf.len()));
+        buf
+    }
+
+    #[test]
+    fn test_cert_signing() {
+        let subject_key = MlDsa87AsymKey::default();
+        let issuer_key = MlDsa87AsymKey::default();
+        let cert = make_test_cert(&subject_key, &issuer_key);
+
+        assert_ne!(cert.tbs(), LocalDevIdCertTbsMlDsa87::TBS_TEMPLATE);
+        assert_eq!(
+            &cert.tbs()[LocalDevIdCertTbsMlDsa87::PUBLIC_KEY_OFFSET
+                ..LocalDevIdCertTbsMlDsa87::PUBLIC_KEY_OFFSET
+                    + LocalDevIdCertTbsMlDsa87::PUBLIC_KEY_LEN],
+            subject_key.pub_key(),
+        );
+        assert_eq!(
+            &cert.tbs()[LocalDevIdCertTbsMlDsa87::SUBJECT_KEY_ID_OFFSET
+                ..LocalDevIdCertTbsMlDsa87::SUBJECT_KEY_ID_OFFSET
+                    + LocalDevIdCertTbsMlDsa87::SUBJECT_KEY_ID_LEN],
+            subject_key.sha1(),
+        );
+        assert_eq!(
+            &cert.tbs()[LocalDevIdCertTbsMlDsa87::AUTHORITY_KEY_ID_OFFSET
+                ..LocalDevIdCertTbsMlDsa87::AUTHORITY_KEY_ID_OFFSET
+                    + LocalDevIdCertTbsMlDsa87::AUTHORITY_KEY_ID_LEN],
+            issuer_key.sha1(),
+        );
+
+        let buf = sign_test_cert(&cert, &issuer_key);
+
+        let cert: X509 = X509::from_der(&buf).unwrap();
+        assert_eq!(cert.signature_algorithm().object().to_string(), "ML-DSA-87");
+        assert_eq!(
+            cert.public_key().unwrap().raw_public_key().unwrap(),
+            subject_key.pub_key()
+        );
+        assert!(cert.verify(issuer_key.priv_key()).unwrap());
+        assert!(!cert.verify(subject_key.priv_key()).unwrap());
+    }
+
+    #[test]
+    fn test_extensions() {
+        let subject_key = MlDsa87AsymKey::default();
+        let issuer_key = MlDsa87AsymKey::default();
+        let cert = make_test_cert(&subject_key, &issuer_key);
+        let buf = sign_test_cert(&cert, &issuer_key);
+
+        let mut parser = X509CertificateParser::new().with_deep_parse_extensions(true);
+        let parsed_cert = match parser.parse(&buf) {
+            Ok((_, parsed_cert)) => parsed_cert,
+            Err(e) => panic!("x509 parsing failed: {:?}", e),
+        };
+
+        assert_eq!(parsed_cert.version(), X509Version::V3);
+
+        // Basic checks on standard extensions
+        let basic_constraints = parsed_cert.basic_constraints().unwrap().unwrap();
+        assert!(basic_constraints.critical);
+        assert!(basic_constraints.value.ca);
+
+        let key_usage = parsed_cert.key_usage().unwrap().unwrap();
+        assert!(key_usage.critical);
+
+        let ext_map = parsed_cert.extensions_map().unwrap();
+
+        const UEID_OID: Oid = oid!(2.23.133 .5 .4 .4);
+        assert!(!ext_map[&UEID_OID].critical);
+    }
+
+    #[test]
+    #[cfg(feature = "generate_mldsa87_templates")]
+    fn test_ldevid_mldsa87_template() {
+        let manual_template = std::fs::read(std::path::Path::new(
+            "./build/local_dev_id_cert_tbs_ml_dsa_87.rs",
+        ))
+        .unwrap();
+        let auto_generated_template = std::fs::read(std::path::Path::new(concat!(
+            env!("OUT_DIR"),
+            "/local_dev_id_cert_tbs_ml_dsa_87.rs"
+        )))
+        .unwrap();
+        if auto_generated_template != manual_template {
+            panic!(
+                "Auto-generated ML-DSA-87 LDevID Certificate template is not equal to the manual template."
+            )
+        }
+    }
+}
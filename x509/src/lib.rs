@@ -15,17 +15,31 @@ Abstract:
 #![cfg_attr(not(feature = "std"), no_std)]
 
 mod cert_bldr;
+mod der;
 mod fmc_alias_cert;
+mod fmc_alias_csr;
 mod idevid_csr;
 mod ldevid_cert;
+mod ldevid_cert_mldsa87;
 mod rt_alias_cert;
+mod tbs_bldr;
 mod test_util;
 
-pub use cert_bldr::{Ecdsa384CertBuilder, Ecdsa384CsrBuilder, Ecdsa384Signature};
+pub use cert_bldr::{
+    Ecdsa384CertBuilder, Ecdsa384CsrBuilder, Ecdsa384Signature, MlDsa87CertBuilder,
+    MlDsa87CsrBuilder, MlDsa87Signature,
+};
+pub use der::DerWriter;
 pub use fmc_alias_cert::{FmcAliasCertTbs, FmcAliasCertTbsParams};
+pub use fmc_alias_csr::{FmcAliasCsrTbs, FmcAliasCsrTbsParams};
 pub use idevid_csr::{InitDevIdCsrTbs, InitDevIdCsrTbsParams};
 pub use ldevid_cert::{LocalDevIdCertTbs, LocalDevIdCertTbsParams};
+pub use ldevid_cert_mldsa87::{LocalDevIdCertTbsMlDsa87, LocalDevIdCertTbsMlDsa87Params};
 pub use rt_alias_cert::{RtAliasCertTbs, RtAliasCertTbsParams};
+pub use tbs_bldr::{
+    CertTbsBuilder, CsrTbsBuilder, DiceFwid, DiceTcbInfo, Extension, GeneralName, KeyUsage, Name,
+    PublicKey, SignatureAlgorithm,
+};
 use zeroize::Zeroize;
 
 pub const NOT_BEFORE: &str = "20230101000000Z";
@@ -58,3 +72,66 @@ impl Default for NotAfter {
         nf
     }
 }
+
+impl NotAfter {
+    /// Create a `NotAfter` from a `YYYYMMDD` date, ending at 23:59:59Z
+    ///
+    /// Returns `None` if `date` is zero or is not a valid calendar date.
+    pub fn from_yyyymmdd(date: u32) -> Option<Self> {
+        let year = date / 10000;
+        let month = (date / 100) % 100;
+        let day = date % 100;
+        if !(1..=9999).contains(&year) || !(1..=12).contains(&month) {
+            return None;
+        }
+        let leap_year = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+        let days_in_month = match month {
+            2 if leap_year => 29,
+            2 => 28,
+            4 | 6 | 9 | 11 => 30,
+            _ => 31,
+        };
+        if !(1..=days_in_month).contains(&day) {
+            return None;
+        }
+
+        let mut nf = NotAfter::default();
+        for (i, digit) in nf.value[..8].iter_mut().rev().enumerate() {
+            *digit = b'0' + ((date / 10u32.pow(i as u32)) % 10) as u8;
+        }
+        Some(nf)
+    }
+}
+
+#[cfg(all(test, target_family = "unix"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_not_after_from_yyyymmdd() {
+        assert_eq!(
+            &NotAfter::from_yyyymmdd(20301231).unwrap().value,
+            b"20301231235959Z"
+        );
+        assert_eq!(
+            &NotAfter::from_yyyymmdd(5_0101).unwrap().value,
+            b"00050101235959Z"
+        );
+        assert!(NotAfter::from_yyyymmdd(0).is_none());
+        assert!(NotAfter::from_yyyymmdd(20301301).is_none());
+        assert!(NotAfter::from_yyyymmdd(20301200).is_none());
+        assert!(NotAfter::from_yyyymmdd(100000101).is_none());
+    }
+
+    #[test]
+    fn test_not_after_from_yyyymmdd_rejects_impossible_dates() {
+        assert!(NotAfter::from_yyyymmdd(20300231).is_none());
+        assert!(NotAfter::from_yyyymmdd(20300431).is_none());
+        assert!(NotAfter::from_yyyymmdd(20300229).is_none());
+        assert!(NotAfter::from_yyyymmdd(21000229).is_none());
+        assert!(NotAfter::from_yyyymmdd(20320229).is_some());
+        assert!(NotAfter::from_yyyymmdd(20000229).is_some());
+        assert!(NotAfter::from_yyyymmdd(20300131).is_some());
+        assert!(NotAfter::from_yyyymmdd(20300430).is_some());
+    }
+}
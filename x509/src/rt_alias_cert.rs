@@ -59,6 +59,12 @@ mod tests {
             .unwrap(),
             tcb_info_rt_svn: &[0xE3],
             tcb_info_rt_tci: &[0xEFu8; RtAliasCertTbsParams::TCB_INFO_RT_TCI_LEN],
+            tcb_info_rt_version: b"0A0B0C0D",
+            tcb_info_rt_vendor_info: &[0xE4u8; RtAliasCertTbsParams::TCB_INFO_RT_VENDOR_INFO_LEN],
+            tcb_info_fmc_svn: &[0xE5],
+            tcb_info_fmc_tci: &[0xE6u8; RtAliasCertTbsParams::TCB_INFO_FMC_TCI_LEN],
+            tcb_info_fmc_version: b"01020304",
+            tcb_info_fmc_vendor_info: &[0xE7u8; RtAliasCertTbsParams::TCB_INFO_FMC_VENDOR_INFO_LEN],
             not_before: &NotBefore::default().value,
             not_after: &NotAfter::default().value,
         };
@@ -114,6 +120,40 @@ mod tests {
                 ..RtAliasCertTbs::TCB_INFO_RT_TCI_OFFSET + RtAliasCertTbs::TCB_INFO_RT_TCI_LEN],
             params.tcb_info_rt_tci,
         );
+        assert_eq!(
+            &cert.tbs()[RtAliasCertTbs::TCB_INFO_RT_VERSION_OFFSET
+                ..RtAliasCertTbs::TCB_INFO_RT_VERSION_OFFSET
+                    + RtAliasCertTbs::TCB_INFO_RT_VERSION_LEN],
+            params.tcb_info_rt_version,
+        );
+        assert_eq!(
+            &cert.tbs()[RtAliasCertTbs::TCB_INFO_RT_VENDOR_INFO_OFFSET
+                ..RtAliasCertTbs::TCB_INFO_RT_VENDOR_INFO_OFFSET
+                    + RtAliasCertTbs::TCB_INFO_RT_VENDOR_INFO_LEN],
+            params.tcb_info_rt_vendor_info,
+        );
+        assert_eq!(
+            &cert.tbs()[RtAliasCertTbs::TCB_INFO_FMC_SVN_OFFSET
+                ..RtAliasCertTbs::TCB_INFO_FMC_SVN_OFFSET + RtAliasCertTbs::TCB_INFO_FMC_SVN_LEN],
+            params.tcb_info_fmc_svn,
+        );
+        assert_eq!(
+            &cert.tbs()[RtAliasCertTbs::TCB_INFO_FMC_TCI_OFFSET
+                ..RtAliasCertTbs::TCB_INFO_FMC_TCI_OFFSET + RtAliasCertTbs::TCB_INFO_FMC_TCI_LEN],
+            params.tcb_info_fmc_tci,
+        );
+        assert_eq!(
+            &cert.tbs()[RtAliasCertTbs::TCB_INFO_FMC_VERSION_OFFSET
+                ..RtAliasCertTbs::TCB_INFO_FMC_VERSION_OFFSET
+                    + RtAliasCertTbs::TCB_INFO_FMC_VERSION_LEN],
+            params.tcb_info_fmc_version,
+        );
+        assert_eq!(
+            &cert.tbs()[RtAliasCertTbs::TCB_INFO_FMC_VENDOR_INFO_OFFSET
+                ..RtAliasCertTbs::TCB_INFO_FMC_VENDOR_INFO_OFFSET
+                    + RtAliasCertTbs::TCB_INFO_FMC_VENDOR_INFO_LEN],
+            params.tcb_info_fmc_vendor_info,
+        );
 
         let ecdsa_sig = crate::Ecdsa384Signature {
             r: TryInto::<[u8; 48]>::try_into(sig.r().to_vec_padded(48).unwrap()).unwrap(),
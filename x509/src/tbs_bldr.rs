@@ -0,0 +1,1002 @@
+/*++
+
+Licensed under the Apache-2.0 license.
+
+File Name:
+
+    tbs_bldr.rs
+
+Abstract:
+
+    X509 API to construct the "To Be Signed" portion of a Certificate or
+    Certificate Signing Request from structured fields.
+
+    Unlike the generated templates, the set of extensions and the key and
+    signature algorithms are chosen at runtime. The output is meant to be
+    signed and then wrapped with `Ecdsa384CertBuilder` or
+    `MlDsa87CertBuilder` (and their CSR aliases).
+
+--*/
+
+use crate::der::DerWriter;
+
+/// ecdsa-with-SHA384 (1.2.840.10045.4.3.3)
+const ECDSA_SHA384_OID: &[u8] = &[0x2A, 0x86, 0x48, 0xCE, 0x3D, 0x04, 0x03, 0x03];
+
+/// id-ecPublicKey (1.2.840.10045.2.1)
+const EC_PUB_KEY_OID: &[u8] = &[0x2A, 0x86, 0x48, 0xCE, 0x3D, 0x02, 0x01];
+
+/// secp384r1 (1.3.132.0.34)
+const SECP384R1_OID: &[u8] = &[0x2B, 0x81, 0x04, 0x00, 0x22];
+
+/// id-ml-dsa-87 (2.16.840.1.101.3.4.3.19)
+const ML_DSA_87_OID: &[u8] = &[0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x03, 0x13];
+
+/// id-at-commonName (2.5.4.3)
+const COMMON_NAME_OID: &[u8] = &[0x55, 0x04, 0x03];
+
+/// id-at-serialNumber (2.5.4.5)
+const SERIAL_NUMBER_OID: &[u8] = &[0x55, 0x04, 0x05];
+
+/// id-ce-basicConstraints (2.5.29.19)
+const BASIC_CONSTRAINTS_OID: &[u8] = &[0x55, 0x1D, 0x13];
+
+/// id-ce-keyUsage (2.5.29.15)
+const KEY_USAGE_OID: &[u8] = &[0x55, 0x1D, 0x0F];
+
+/// id-ce-subjectAltName (2.5.29.17)
+const SUBJECT_ALT_NAME_OID: &[u8] = &[0x55, 0x1D, 0x11];
+
+/// id-ce-subjectKeyIdentifier (2.5.29.14)
+const SUBJECT_KEY_ID_OID: &[u8] = &[0x55, 0x1D, 0x0E];
+
+/// id-ce-authorityKeyIdentifier (2.5.29.35)
+const AUTHORITY_KEY_ID_OID: &[u8] = &[0x55, 0x1D, 0x23];
+
+/// tcg-dice-Ueid (2.23.133.5.4.4)
+const TCG_UEID_OID: &[u8] = &[0x67, 0x81, 0x05, 0x05, 0x04, 0x04];
+
+/// tcg-dice-TcbInfo (2.23.133.5.4.1)
+const TCG_TCB_INFO_OID: &[u8] = &[0x67, 0x81, 0x05, 0x05, 0x04, 0x01];
+
+/// tcg-dice-MultiTcbInfo (2.23.133.5.4.5)
+const TCG_MULTI_TCB_INFO_OID: &[u8] = &[0x67, 0x81, 0x05, 0x05, 0x04, 0x05];
+
+/// pkcs-9-at-extensionRequest (1.2.840.113549.1.9.14)
+const EXTENSION_REQUEST_OID: &[u8] = &[0x2A, 0x86, 0x48, 0x86, 0xF7, 0x0D, 0x01, 0x09, 0x0E];
+
+/// X509 v3 version number
+const X509_V3: u32 = 2;
+
+/// PKCS#10 version number
+const CSR_V1: u32 = 0;
+
+/// Key Usage bits
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct KeyUsage(pub u16);
+
+impl KeyUsage {
+    pub const DIGITAL_SIGNATURE: Self = Self(1 << 0);
+    pub const NON_REPUDIATION: Self = Self(1 << 1);
+    pub const KEY_ENCIPHERMENT: Self = Self(1 << 2);
+    pub const DATA_ENCIPHERMENT: Self = Self(1 << 3);
+    pub const KEY_AGREEMENT: Self = Self(1 << 4);
+    pub const KEY_CERT_SIGN: Self = Self(1 << 5);
+    pub const CRL_SIGN: Self = Self(1 << 6);
+    pub const ENCIPHER_ONLY: Self = Self(1 << 7);
+    pub const DECIPHER_ONLY: Self = Self(1 << 8);
+
+    /// Encode as a named bit list, dropping trailing zero bits
+    fn write(&self, w: &mut DerWriter) -> Option<()> {
+        let bits = self.0 & 0x1FF;
+        if bits == 0 {
+            return w.write_bit_string(DerWriter::TAG_BIT_STRING, &[], 0);
+        }
+
+        // Bit 0 is the most significant bit of the first byte
+        let reversed = bits.reverse_bits().to_be_bytes();
+        let last = 15 - bits.leading_zeros() as usize;
+        let len = last / 8 + 1;
+        let unused = (7 - last % 8) as u8;
+        w.write_bit_string(DerWriter::TAG_BIT_STRING, &reversed[..len], unused)
+    }
+}
+
+impl core::ops::BitOr for KeyUsage {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+/// Distinguished Name
+///
+/// Encoded the same way as the generated templates: a common name followed
+/// by a serial number attribute.
+#[derive(Debug, Clone, Copy)]
+pub struct Name<'a> {
+    /// Common Name
+    pub common_name: &'a str,
+
+    /// Serial Number (hex string)
+    pub serial_number: &'a [u8],
+}
+
+impl<'a> Name<'a> {
+    fn write(&self, w: &mut DerWriter) -> Option<()> {
+        w.write_sequence(|w| {
+            w.write_nested(DerWriter::TAG_SET, |w| {
+                w.write_sequence(|w| {
+                    w.write_oid(COMMON_NAME_OID)?;
+                    w.write_tlv(DerWriter::TAG_UTF8_STRING, self.common_name.as_bytes())
+                })
+            })?;
+            w.write_nested(DerWriter::TAG_SET, |w| {
+                w.write_sequence(|w| {
+                    w.write_oid(SERIAL_NUMBER_OID)?;
+                    w.write_tlv(DerWriter::TAG_PRINTABLE_STRING, self.serial_number)
+                })
+            })
+        })
+    }
+}
+
+/// Subject Alternative Name entry
+#[derive(Debug, Clone, Copy)]
+pub enum GeneralName<'a> {
+    /// otherName with a type OID (encoded contents) and a DER encoded value
+    OtherName { type_id: &'a [u8], value: &'a [u8] },
+
+    /// rfc822Name
+    Rfc822Name(&'a str),
+
+    /// dNSName
+    DnsName(&'a str),
+
+    /// uniformResourceIdentifier
+    Uri(&'a str),
+}
+
+impl<'a> GeneralName<'a> {
+    fn write(&self, w: &mut DerWriter) -> Option<()> {
+        match self {
+            GeneralName::OtherName { type_id, value } => w.write_nested(0xA0, |w| {
+                w.write_oid(type_id)?;
+                w.write_nested(0xA0, |w| w.write_raw(value))
+            }),
+            GeneralName::Rfc822Name(name) => w.write_tlv(0x81, name.as_bytes()),
+            GeneralName::DnsName(name) => w.write_tlv(0x82, name.as_bytes()),
+            GeneralName::Uri(uri) => w.write_tlv(0x86, uri.as_bytes()),
+        }
+    }
+}
+
+/// Firmware identifier of a DICE TCB Info
+#[derive(Debug, Clone, Copy)]
+pub struct DiceFwid<'a> {
+    /// Hash algorithm OID (encoded contents)
+    pub hash_alg: &'a [u8],
+
+    /// Digest
+    pub digest: &'a [u8],
+}
+
+/// TCG DICE TCB Info
+///
+/// Fields left as `None` (or empty `fwids`) are omitted from the encoding.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct DiceTcbInfo<'a> {
+    pub vendor: Option<&'a str>,
+    pub model: Option<&'a str>,
+    pub version: Option<&'a [u8]>,
+    pub svn: Option<u32>,
+    pub layer: Option<u32>,
+    pub index: Option<u32>,
+    pub fwids: &'a [DiceFwid<'a>],
+    pub flags: Option<u32>,
+    pub vendor_info: Option<&'a [u8]>,
+    pub tcb_type: Option<&'a [u8]>,
+    pub flags_mask: Option<u32>,
+}
+
+impl<'a> DiceTcbInfo<'a> {
+    fn write(&self, w: &mut DerWriter) -> Option<()> {
+        w.write_sequence(|w| {
+            if let Some(vendor) = self.vendor {
+                w.write_tlv(0x80, vendor.as_bytes())?;
+            }
+            if let Some(model) = self.model {
+                w.write_tlv(0x81, model.as_bytes())?;
+            }
+            if let Some(version) = self.version {
+                w.write_tlv(0x82, version)?;
+            }
+            if let Some(svn) = self.svn {
+                w.write_u32(0x83, svn)?;
+            }
+            if let Some(layer) = self.layer {
+                w.write_u32(0x84, layer)?;
+            }
+            if let Some(index) = self.index {
+                w.write_u32(0x85, index)?;
+            }
+            if !self.fwids.is_empty() {
+                w.write_nested(0xA6, |w| {
+                    for fwid in self.fwids {
+                        w.write_sequence(|w| {
+                            w.write_oid(fwid.hash_alg)?;
+                            w.write_tlv(DerWriter::TAG_OCTET_STRING, fwid.digest)
+                        })?;
+                    }
+                    Some(())
+                })?;
+            }
+            if let Some(flags) = self.flags {
+                w.write_bit_string(0x87, &flags.to_be_bytes(), 0)?;
+            }
+            if let Some(vendor_info) = self.vendor_info {
+                w.write_tlv(0x88, vendor_info)?;
+            }
+            if let Some(tcb_type) = self.tcb_type {
+                w.write_tlv(0x89, tcb_type)?;
+            }
+            if let Some(flags_mask) = self.flags_mask {
+                w.write_bit_string(0x8A, &flags_mask.to_be_bytes(), 0)?;
+            }
+            Some(())
+        })
+    }
+}
+
+/// X509 v3 Extension
+#[derive(Debug, Clone, Copy)]
+pub enum Extension<'a> {
+    /// Basic Constraints (critical)
+    BasicConstraints { ca: bool, path_len: Option<u32> },
+
+    /// Key Usage (critical)
+    KeyUsage(KeyUsage),
+
+    /// TCG DICE UEID
+    Ueid(&'a [u8]),
+
+    /// Subject Alternative Name
+    SubjectAltName(&'a [GeneralName<'a>]),
+
+    /// TCG DICE TCB Info
+    TcbInfo(&'a DiceTcbInfo<'a>),
+
+    /// TCG DICE Multi TCB Info
+    MultiTcbInfo(&'a [DiceTcbInfo<'a>]),
+
+    /// Subject Key Identifier
+    SubjectKeyId(&'a [u8]),
+
+    /// Authority Key Identifier (key identifier only)
+    AuthorityKeyId(&'a [u8]),
+
+    /// Any other extension, with its OID (encoded contents) and DER value
+    Custom {
+        oid: &'a [u8],
+        critical: bool,
+        value: &'a [u8],
+    },
+}
+
+impl<'a> Extension<'a> {
+    fn oid(&self) -> &'a [u8] {
+        match self {
+            Extension::BasicConstraints { .. } => BASIC_CONSTRAINTS_OID,
+            Extension::KeyUsage(_) => KEY_USAGE_OID,
+            Extension::Ueid(_) => TCG_UEID_OID,
+            Extension::SubjectAltName(_) => SUBJECT_ALT_NAME_OID,
+            Extension::TcbInfo(_) => TCG_TCB_INFO_OID,
+            Extension::MultiTcbInfo(_) => TCG_MULTI_TCB_INFO_OID,
+            Extension::SubjectKeyId(_) => SUBJECT_KEY_ID_OID,
+            Extension::AuthorityKeyId(_) => AUTHORITY_KEY_ID_OID,
+            Extension::Custom { oid, .. } => oid,
+        }
+    }
+
+    fn critical(&self) -> bool {
+        match self {
+            Extension::BasicConstraints { .. } | Extension::KeyUsage(_) => true,
+            Extension::Custom { critical, .. } => *critical,
+            _ => false,
+        }
+    }
+
+    fn write_value(&self, w: &mut DerWriter) -> Option<()> {
+        match self {
+            Extension::BasicConstraints { ca, path_len } => w.write_sequence(|w| {
+                if *ca {
+                    w.write_bool(true)?;
+                }
+                if let Some(path_len) = path_len {
+                    w.write_u32(DerWriter::TAG_INTEGER, *path_len)?;
+                }
+                Some(())
+            }),
+            Extension::KeyUsage(usage) => usage.write(w),
+            Extension::Ueid(ueid) => {
+                w.write_sequence(|w| w.write_tlv(DerWriter::TAG_OCTET_STRING, ueid))
+            }
+            Extension::SubjectAltName(names) => w.write_sequence(|w| {
+                for name in names.iter() {
+                    name.write(w)?;
+                }
+                Some(())
+            }),
+            Extension::TcbInfo(info) => info.write(w),
+            Extension::MultiTcbInfo(infos) => w.write_sequence(|w| {
+                for info in infos.iter() {
+                    info.write(w)?;
+                }
+                Some(())
+            }),
+            Extension::SubjectKeyId(key_id) => w.write_tlv(DerWriter::TAG_OCTET_STRING, key_id),
+            Extension::AuthorityKeyId(key_id) => w.write_sequence(|w| w.write_tlv(0x80, key_id)),
+            Extension::Custom { value, .. } => w.write_raw(value),
+        }
+    }
+
+    fn write(&self, w: &mut DerWriter) -> Option<()> {
+        w.write_sequence(|w| {
+            w.write_oid(self.oid())?;
+            if self.critical() {
+                w.write_bool(true)?;
+            }
+            w.write_nested(DerWriter::TAG_OCTET_STRING, |w| self.write_value(w))
+        })
+    }
+}
+
+fn write_extensions(w: &mut DerWriter, extensions: &[Extension]) -> Option<()> {
+    w.write_sequence(|w| {
+        for ext in extensions {
+            ext.write(w)?;
+        }
+        Some(())
+    })
+}
+
+/// Signature algorithm used by the issuer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignatureAlgorithm {
+    /// ecdsa-with-SHA384
+    EcdsaSha384,
+
+    /// id-ml-dsa-87 (pure, no parameters)
+    MlDsa87,
+}
+
+impl SignatureAlgorithm {
+    fn write(&self, w: &mut DerWriter) -> Option<()> {
+        w.write_sequence(|w| match self {
+            SignatureAlgorithm::EcdsaSha384 => w.write_oid(ECDSA_SHA384_OID),
+            SignatureAlgorithm::MlDsa87 => w.write_oid(ML_DSA_87_OID),
+        })
+    }
+}
+
+/// Subject public key
+#[derive(Debug, Clone, Copy)]
+pub enum PublicKey<'a> {
+    /// ECC P-384 key as an uncompressed SEC1 point
+    Ecc384(&'a [u8; 97]),
+
+    /// ML-DSA-87 encoded public key
+    MlDsa87(&'a [u8; 2592]),
+}
+
+impl<'a> PublicKey<'a> {
+    fn write(&self, w: &mut DerWriter) -> Option<()> {
+        w.write_sequence(|w| {
+            let key: &[u8] = match self {
+                PublicKey::Ecc384(key) => {
+                    w.write_sequence(|w| {
+                        w.write_oid(EC_PUB_KEY_OID)?;
+                        w.write_oid(SECP384R1_OID)
+                    })?;
+                    *key
+                }
+                PublicKey::MlDsa87(key) => {
+                    w.write_sequence(|w| w.write_oid(ML_DSA_87_OID))?;
+                    *key
+                }
+            };
+            w.write_bit_string(DerWriter::TAG_BIT_STRING, key, 0)
+        })
+    }
+}
+
+/// Certificate `To Be Signed` builder
+#[derive(Debug, Clone, Copy)]
+pub struct CertTbsBuilder<'a> {
+    /// Serial Number (big-endian unsigned integer)
+    pub serial_number: &'a [u8],
+
+    /// Algorithm the issuer signs the certificate with
+    pub signature_algorithm: SignatureAlgorithm,
+
+    /// Issuer Name
+    pub issuer: Name<'a>,
+
+    /// Validity Not Before (GeneralizedTime)
+    pub not_before: &'a [u8; 15],
+
+    /// Validity Not After (GeneralizedTime)
+    pub not_after: &'a [u8; 15],
+
+    /// Subject Name
+    pub subject: Name<'a>,
+
+    /// Subject Public Key
+    pub public_key: PublicKey<'a>,
+
+    /// Extensions, encoded in the given order
+    pub extensions: &'a [Extension<'a>],
+}
+
+impl<'a> CertTbsBuilder<'a> {
+    /// Build the `To Be Signed` portion of the certificate
+    ///
+    /// # Arguments
+    ///
+    /// * `buf` - Output buffer
+    ///
+    /// # Returns
+    ///
+    /// Length of the encoded TBS, or `None` if it does not fit in `buf`
+    pub fn build(&self, buf: &mut [u8]) -> Option<usize> {
+        DerWriter::encode(buf, |w| {
+            w.write_sequence(|w| {
+                w.write_nested(0xA0, |w| w.write_u32(DerWriter::TAG_INTEGER, X509_V3))?;
+                w.write_uint(DerWriter::TAG_INTEGER, self.serial_number)?;
+                self.signature_algorithm.write(w)?;
+                self.issuer.write(w)?;
+                w.write_sequence(|w| {
+                    w.write_tlv(DerWriter::TAG_GENERALIZED_TIME, self.not_before)?;
+                    w.write_tlv(DerWriter::TAG_GENERALIZED_TIME, self.not_after)
+                })?;
+                self.subject.write(w)?;
+                self.public_key.write(w)?;
+                if !self.extensions.is_empty() {
+                    w.write_nested(0xA3, |w| write_extensions(w, self.extensions))?;
+                }
+                Some(())
+            })
+        })
+    }
+}
+
+/// Certificate Signing Request `To Be Signed` builder
+#[derive(Debug, Clone, Copy)]
+pub struct CsrTbsBuilder<'a> {
+    /// Subject Name
+    pub subject: Name<'a>,
+
+    /// Subject Public Key
+    pub public_key: PublicKey<'a>,
+
+    /// Requested extensions, encoded in the given order
+    pub extensions: &'a [Extension<'a>],
+}
+
+impl<'a> CsrTbsBuilder<'a> {
+    /// Build the `To Be Signed` portion of the CSR
+    ///
+    /// # Arguments
+    ///
+    /// * `buf` - Output buffer
+    ///
+    /// # Returns
+    ///
+    /// Length of the encoded TBS, or `None` if it does not fit in `buf`
+    pub fn build(&self, buf: &mut [u8]) -> Option<usize> {
+        DerWriter::encode(buf, |w| {
+            w.write_sequence(|w| {
+                w.write_u32(DerWriter::TAG_INTEGER, CSR_V1)?;
+                self.subject.write(w)?;
+                self.public_key.write(w)?;
+                w.write_nested(0xA0, |w| {
+                    if self.extensions.is_empty() {
+                        return Some(());
+                    }
+                    w.write_sequence(|w| {
+                        w.write_oid(EXTENSION_REQUEST_OID)?;
+                        w.write_nested(DerWriter::TAG_SET, |w| write_extensions(w, self.extensions))
+                    })
+                })
+            })
+        })
+    }
+}
+
+#[cfg(all(test, target_family = "unix"))]
+mod tests {
+    use openssl::ecdsa::EcdsaSig;
+    use openssl::sha::Sha384;
+    use openssl::x509::{X509Req, X509};
+
+    use x509_parser::certification_request::X509CertificationRequest;
+    use x509_parser::extensions::{GeneralName as ParsedName, ParsedExtension};
+    use x509_parser::nom::Parser;
+    use x509_parser::oid_registry::asn1_rs::oid;
+    use x509_parser::oid_registry::Oid;
+    use x509_parser::prelude::{FromDer, X509CertificateParser};
+
+    use super::*;
+    use crate::test_util::tests::*;
+    use crate::{
+        Ecdsa384CertBuilder, Ecdsa384CsrBuilder, Ecdsa384Signature, InitDevIdCsrTbs,
+        InitDevIdCsrTbsParams, LocalDevIdCertTbs, LocalDevIdCertTbsParams, MlDsa87CertBuilder,
+        MlDsa87CsrBuilder, MlDsa87Signature, NotAfter, NotBefore, RtAliasCertTbs,
+        RtAliasCertTbsParams,
+    };
+
+    const ML_DSA_87: Oid = oid!(2.16.840 .1 .101 .3 .4 .3 .19);
+
+    /// sha384 (2.16.840.1.101.3.4.2.2)
+    const SHA384_OID: &[u8] = &[0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x02];
+
+    const TEST_UEID: [u8; 17] = [0xAB; 17];
+    const TEST_SERIAL: [u8; 20] = [0x3C; 20];
+
+    fn sign(tbs: &[u8], key: &Ecc384AsymKey) -> Ecdsa384Signature {
+        let mut sha = Sha384::new();
+        sha.update(tbs);
+        let sig = EcdsaSig::sign(&sha.finish(), &key.priv_key().ec_key().unwrap()).unwrap();
+        Ecdsa384Signature {
+            r: sig.r().to_vec_padded(48).unwrap().try_into().unwrap(),
+            s: sig.s().to_vec_padded(48).unwrap().try_into().unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_matches_ldevid_template() {
+        let subject_key = Ecc384AsymKey::default();
+        let issuer_key = Ecc384AsymKey::default();
+        let subject_sn: [u8; 64] = subject_key.hex_str().into_bytes().try_into().unwrap();
+        let issuer_sn: [u8; 64] = issuer_key.hex_str().into_bytes().try_into().unwrap();
+        let public_key: [u8; 97] = subject_key.pub_key().try_into().unwrap();
+
+        let template = LocalDevIdCertTbs::new(&LocalDevIdCertTbsParams {
+            serial_number: &TEST_SERIAL,
+            public_key: &public_key,
+            subject_sn: &subject_sn,
+            issuer_sn: &issuer_sn,
+            ueid: &TEST_UEID,
+            subject_key_id: &subject_key.sha1(),
+            authority_key_id: &issuer_key.sha1(),
+            not_before: &NotBefore::default().value,
+            not_after: &NotAfter::default().value,
+        });
+
+        let builder = CertTbsBuilder {
+            serial_number: &TEST_SERIAL,
+            signature_algorithm: SignatureAlgorithm::EcdsaSha384,
+            issuer: Name {
+                common_name: "Caliptra 1.0 IDevID",
+                serial_number: &issuer_sn,
+            },
+            not_before: &NotBefore::default().value,
+            not_after: &NotAfter::default().value,
+            subject: Name {
+                common_name: "Caliptra 1.0 LDevID",
+                serial_number: &subject_sn,
+            },
+            public_key: PublicKey::Ecc384(&public_key),
+            extensions: &[
+                Extension::BasicConstraints {
+                    ca: true,
+                    path_len: Some(4),
+                },
+                Extension::KeyUsage(KeyUsage::KEY_CERT_SIGN),
+                Extension::Ueid(&TEST_UEID),
+                Extension::SubjectKeyId(&subject_key.sha1()),
+                Extension::AuthorityKeyId(&issuer_key.sha1()),
+            ],
+        };
+
+        let mut buf = [0u8; 1024];
+        let len = builder.build(&mut buf).unwrap();
+        assert_eq!(&buf[..len], template.tbs());
+    }
+
+    #[test]
+    fn test_matches_rt_alias_template() {
+        let subject_key = Ecc384AsymKey::default();
+        let issuer_key = Ecc384AsymKey::default();
+        let subject_sn: [u8; 64] = subject_key.hex_str().into_bytes().try_into().unwrap();
+        let issuer_sn: [u8; 64] = issuer_key.hex_str().into_bytes().try_into().unwrap();
+        let public_key: [u8; 97] = subject_key.pub_key().try_into().unwrap();
+        let tci = [0xCD; 48];
+        let vendor_info = [0xEF; 20];
+        let version = *b"00010002";
+        let fmc_tci = [0xAB; 48];
+        let fmc_vendor_info = [0x12; 20];
+        let fmc_version = *b"00030004";
+
+        let template = RtAliasCertTbs::new(&RtAliasCertTbsParams {
+            serial_number: &TEST_SERIAL,
+            public_key: &public_key,
+            subject_sn: &subject_sn,
+            issuer_sn: &issuer_sn,
+            ueid: &TEST_UEID,
+            subject_key_id: &subject_key.sha1(),
+            authority_key_id: &issuer_key.sha1(),
+            not_before: &NotBefore::default().value,
+            not_after: &NotAfter::default().value,
+            tcb_info_rt_tci: &tci,
+            tcb_info_rt_vendor_info: &vendor_info,
+            tcb_info_rt_version: &version,
+            tcb_info_rt_svn: &[5],
+            tcb_info_fmc_tci: &fmc_tci,
+            tcb_info_fmc_vendor_info: &fmc_vendor_info,
+            tcb_info_fmc_version: &fmc_version,
+            tcb_info_fmc_svn: &[3],
+        });
+
+        let tcb_info = DiceTcbInfo {
+            vendor: Some("Caliptra"),
+            model: Some("RT"),
+            version: Some(&version),
+            // The template encodes the SVN with a fixed two byte width
+            svn: Some(0x100 | 5),
+            layer: Some(2),
+            index: Some(0),
+            fwids: &[DiceFwid {
+                hash_alg: SHA384_OID,
+                digest: &tci,
+            }],
+            vendor_info: Some(&vendor_info),
+            tcb_type: Some(b"RT_INFO"),
+            ..Default::default()
+        };
+        let fmc_tcb_info = DiceTcbInfo {
+            vendor: Some("Caliptra"),
+            model: Some("FMC"),
+            version: Some(&fmc_version),
+            svn: Some(0x100 | 3),
+            layer: Some(1),
+            index: Some(0),
+            fwids: &[DiceFwid {
+                hash_alg: SHA384_OID,
+                digest: &fmc_tci,
+            }],
+            vendor_info: Some(&fmc_vendor_info),
+            tcb_type: Some(b"FMC_INFO"),
+            ..Default::default()
+        };
+
+        let builder = CertTbsBuilder {
+            serial_number: &TEST_SERIAL,
+            signature_algorithm: SignatureAlgorithm::EcdsaSha384,
+            issuer: Name {
+                common_name: "Caliptra 1.0 FMC Alias",
+                serial_number: &issuer_sn,
+            },
+            not_before: &NotBefore::default().value,
+            not_after: &NotAfter::default().value,
+            subject: Name {
+                common_name: "Caliptra 1.0 Rt Alias",
+                serial_number: &subject_sn,
+            },
+            public_key: PublicKey::Ecc384(&public_key),
+            extensions: &[
+                Extension::BasicConstraints {
+                    ca: true,
+                    path_len: Some(2),
+                },
+                Extension::KeyUsage(KeyUsage::DIGITAL_SIGNATURE | KeyUsage::KEY_CERT_SIGN),
+                Extension::Ueid(&TEST_UEID),
+                Extension::TcbInfo(&tcb_info),
+                Extension::MultiTcbInfo(&[fmc_tcb_info]),
+                Extension::SubjectKeyId(&subject_key.sha1()),
+                Extension::AuthorityKeyId(&issuer_key.sha1()),
+            ],
+        };
+
+        let mut buf = [0u8; 1024];
+        let len = builder.build(&mut buf).unwrap();
+        assert_eq!(&buf[..len], template.tbs());
+    }
+
+    #[test]
+    fn test_matches_idevid_csr_template() {
+        let key = Ecc384AsymKey::default();
+        let subject_sn: [u8; 64] = key.hex_str().into_bytes().try_into().unwrap();
+        let public_key: [u8; 97] = key.pub_key().try_into().unwrap();
+
+        let template = InitDevIdCsrTbs::new(&InitDevIdCsrTbsParams {
+            public_key: &public_key,
+            subject_sn: &subject_sn,
+            ueid: &TEST_UEID,
+        });
+
+        let builder = CsrTbsBuilder {
+            subject: Name {
+                common_name: "Caliptra 1.0 IDevID",
+                serial_number: &subject_sn,
+            },
+            public_key: PublicKey::Ecc384(&public_key),
+            extensions: &[
+                Extension::BasicConstraints {
+                    ca: true,
+                    path_len: Some(5),
+                },
+                Extension::KeyUsage(KeyUsage::KEY_CERT_SIGN),
+                Extension::Ueid(&TEST_UEID),
+            ],
+        };
+
+        let mut buf = [0u8; 1024];
+        let len = builder.build(&mut buf).unwrap();
+        assert_eq!(&buf[..len], template.tbs());
+
+        let sig = sign(&buf[..len], &key);
+        let csr_bldr = Ecdsa384CsrBuilder::new(&buf[..len], &sig).unwrap();
+        let mut csr = vec![0u8; csr_bldr.len()];
+        csr_bldr.build(&mut csr).unwrap();
+        let req = X509Req::from_der(&csr).unwrap();
+        assert!(req.verify(key.priv_key()).unwrap());
+    }
+
+    #[test]
+    fn test_optional_extensions() {
+        const CUSTOM_OID: Oid = oid!(1.3.6 .1 .4 .1 .311 .99);
+        let subject_key = Ecc384AsymKey::default();
+        let issuer_key = Ecc384AsymKey::default();
+        let subject_sn: [u8; 64] = subject_key.hex_str().into_bytes().try_into().unwrap();
+        let issuer_sn: [u8; 64] = issuer_key.hex_str().into_bytes().try_into().unwrap();
+        let public_key: [u8; 97] = subject_key.pub_key().try_into().unwrap();
+
+        // Serial with the MSB set must gain a leading zero
+        let serial = [0x80; 20];
+        let custom_value = [0x04, 0x03, 0x01, 0x02, 0x03];
+        let fmc_info = DiceTcbInfo {
+            model: Some("FMC"),
+            svn: Some(0x80),
+            fwids: &[DiceFwid {
+                hash_alg: SHA384_OID,
+                digest: &[0x11; 48],
+            }],
+            flags: Some(0x8000_0000),
+            ..Default::default()
+        };
+        let rt_info = DiceTcbInfo {
+            model: Some("RT"),
+            layer: Some(2),
+            ..Default::default()
+        };
+
+        let builder = CertTbsBuilder {
+            serial_number: &serial,
+            signature_algorithm: SignatureAlgorithm::EcdsaSha384,
+            issuer: Name {
+                common_name: "Caliptra Test Issuer",
+                serial_number: &issuer_sn,
+            },
+            not_before: &NotBefore::default().value,
+            not_after: &NotAfter::default().value,
+            subject: Name {
+                common_name: "Caliptra Test Subject",
+                serial_number: &subject_sn,
+            },
+            public_key: PublicKey::Ecc384(&public_key),
+            extensions: &[
+                Extension::BasicConstraints {
+                    ca: false,
+                    path_len: None,
+                },
+                Extension::KeyUsage(KeyUsage::DIGITAL_SIGNATURE | KeyUsage::DECIPHER_ONLY),
+                Extension::SubjectAltName(&[
+                    GeneralName::DnsName("caliptra.example.com"),
+                    GeneralName::Uri("urn:caliptra:test"),
+                ]),
+                Extension::MultiTcbInfo(&[fmc_info, rt_info]),
+                Extension::Custom {
+                    oid: &[0x2B, 0x06, 0x01, 0x04, 0x01, 0x82, 0x37, 0x63],
+                    critical: false,
+                    value: &custom_value,
+                },
+            ],
+        };
+
+        let mut tbs = [0u8; 1024];
+        let len = builder.build(&mut tbs).unwrap();
+        let sig = sign(&tbs[..len], &issuer_key);
+        let cert_bldr = Ecdsa384CertBuilder::new(&tbs[..len], &sig).unwrap();
+        let mut der = vec![0u8; cert_bldr.len()];
+        cert_bldr.build(&mut der).unwrap();
+
+        let cert = X509::from_der(&der).unwrap();
+        assert!(cert.verify(issuer_key.priv_key()).unwrap());
+
+        let mut parser = X509CertificateParser::new().with_deep_parse_extensions(true);
+        let (_, parsed) = parser.parse(&der).unwrap();
+        assert_eq!(parsed.raw_serial(), &[&[0u8][..], &serial].concat()[..]);
+
+        let bc = parsed.basic_constraints().unwrap().unwrap();
+        assert!(!bc.value.ca);
+        assert_eq!(bc.value.path_len_constraint, None);
+
+        let ku = parsed.key_usage().unwrap().unwrap();
+        assert!(ku.value.digital_signature());
+        assert!(ku.value.decipher_only());
+        assert!(!ku.value.key_cert_sign());
+
+        let san = parsed.subject_alternative_name().unwrap().unwrap();
+        assert_eq!(
+            san.value.general_names,
+            vec![
+                ParsedName::DNSName("caliptra.example.com"),
+                ParsedName::URI("urn:caliptra:test"),
+            ]
+        );
+
+        let ext_map = parsed.extensions_map().unwrap();
+        let custom = ext_map[&CUSTOM_OID];
+        assert!(!custom.critical);
+        assert_eq!(custom.value, custom_value);
+        assert!(matches!(
+            custom.parsed_extension(),
+            ParsedExtension::UnsupportedExtension { .. }
+        ));
+        assert!(ext_map.contains_key(&oid!(2.23.133 .5 .4 .5)));
+    }
+
+    #[test]
+    fn test_buffer_too_small() {
+        let key = Ecc384AsymKey::default();
+        let subject_sn: [u8; 64] = key.hex_str().into_bytes().try_into().unwrap();
+        let public_key: [u8; 97] = key.pub_key().try_into().unwrap();
+        let builder = CsrTbsBuilder {
+            subject: Name {
+                common_name: "Caliptra 1.0 IDevID",
+                serial_number: &subject_sn,
+            },
+            public_key: PublicKey::Ecc384(&public_key),
+            extensions: &[Extension::Ueid(&TEST_UEID)],
+        };
+
+        let mut buf = [0u8; 1024];
+        let len = builder.build(&mut buf).unwrap();
+        assert_eq!(builder.build(&mut buf[..len]), Some(len));
+        assert_eq!(builder.build(&mut buf[..len - 1]), None);
+        assert_eq!(builder.build(&mut buf[..len / 2]), None);
+    }
+
+    fn mldsa87_test_key() -> [u8; 2592] {
+        core::array::from_fn(|i| i as u8)
+    }
+
+    #[test]
+    fn test_mldsa87_issued_cert() {
+        let subject_key = Ecc384AsymKey::default();
+        let subject_sn: [u8; 64] = subject_key.hex_str().into_bytes().try_into().unwrap();
+        let issuer_sn = [b'A'; 64];
+        let public_key: [u8; 97] = subject_key.pub_key().try_into().unwrap();
+
+        // ECC-384 subject certified by an ML-DSA-87 issuer
+        let builder = CertTbsBuilder {
+            serial_number: &TEST_SERIAL,
+            signature_algorithm: SignatureAlgorithm::MlDsa87,
+            issuer: Name {
+                common_name: "Caliptra 1.0 IDevID",
+                serial_number: &issuer_sn,
+            },
+            not_before: &NotBefore::default().value,
+            not_after: &NotAfter::default().value,
+            subject: Name {
+                common_name: "Caliptra 1.0 LDevID",
+                serial_number: &subject_sn,
+            },
+            public_key: PublicKey::Ecc384(&public_key),
+            extensions: &[Extension::KeyUsage(KeyUsage::KEY_CERT_SIGN)],
+        };
+
+        let mut tbs = [0u8; 1024];
+        let len = builder.build(&mut tbs).unwrap();
+        let sig = MlDsa87Signature {
+            sig: core::array::from_fn(|i| (i * 7) as u8),
+        };
+        let cert_bldr = MlDsa87CertBuilder::new(&tbs[..len], &sig).unwrap();
+        let mut der = vec![0u8; cert_bldr.len()];
+        assert_eq!(cert_bldr.build(&mut der), Some(der.len()));
+        assert_eq!(cert_bldr.build(&mut der[..len]), None);
+
+        let mut parser = X509CertificateParser::new();
+        let (rem, parsed) = parser.parse(&der).unwrap();
+        assert!(rem.is_empty());
+        assert_eq!(parsed.signature_algorithm.algorithm, ML_DSA_87);
+        assert!(parsed.signature_algorithm.parameters.is_none());
+        assert_eq!(parsed.tbs_certificate.signature.algorithm, ML_DSA_87);
+        assert_eq!(parsed.signature_value.data, &sig.sig[..]);
+        assert_eq!(parsed.public_key().subject_public_key.data, &public_key[..]);
+    }
+
+    #[test]
+    fn test_mldsa87_subject_hybrid_chain() {
+        let issuer_key = Ecc384AsymKey::default();
+        let issuer_sn: [u8; 64] = issuer_key.hex_str().into_bytes().try_into().unwrap();
+        let subject_sn = [b'B'; 64];
+        let mldsa_key = mldsa87_test_key();
+
+        // ML-DSA-87 subject certified by an ECC-384 issuer
+        let builder = CertTbsBuilder {
+            serial_number: &TEST_SERIAL,
+            signature_algorithm: SignatureAlgorithm::EcdsaSha384,
+            issuer: Name {
+                common_name: "Caliptra 1.0 IDevID",
+                serial_number: &issuer_sn,
+            },
+            not_before: &NotBefore::default().value,
+            not_after: &NotAfter::default().value,
+            subject: Name {
+                common_name: "Caliptra 1.0 LDevID",
+                serial_number: &subject_sn,
+            },
+            public_key: PublicKey::MlDsa87(&mldsa_key),
+            extensions: &[
+                Extension::BasicConstraints {
+                    ca: true,
+                    path_len: Some(4),
+                },
+                Extension::KeyUsage(KeyUsage::KEY_CERT_SIGN),
+            ],
+        };
+
+        let mut tbs = [0u8; 4096];
+        let len = builder.build(&mut tbs).unwrap();
+        let sig = sign(&tbs[..len], &issuer_key);
+        let cert_bldr = Ecdsa384CertBuilder::new(&tbs[..len], &sig).unwrap();
+        let mut der = vec![0u8; cert_bldr.len()];
+        cert_bldr.build(&mut der).unwrap();
+
+        let cert = X509::from_der(&der).unwrap();
+        assert!(cert.verify(issuer_key.priv_key()).unwrap());
+
+        let mut parser = X509CertificateParser::new();
+        let (_, parsed) = parser.parse(&der).unwrap();
+        let spki = parsed.public_key();
+        assert_eq!(spki.algorithm.algorithm, ML_DSA_87);
+        assert!(spki.algorithm.parameters.is_none());
+        assert_eq!(spki.subject_public_key.data, &mldsa_key[..]);
+    }
+
+    #[test]
+    fn test_mldsa87_csr() {
+        let subject_sn = [b'C'; 64];
+        let mldsa_key = mldsa87_test_key();
+        let builder = CsrTbsBuilder {
+            subject: Name {
+                common_name: "Caliptra 1.0 IDevID",
+                serial_number: &subject_sn,
+            },
+            public_key: PublicKey::MlDsa87(&mldsa_key),
+            extensions: &[Extension::Ueid(&TEST_UEID)],
+        };
+
+        let mut tbs = [0u8; 4096];
+        let len = builder.build(&mut tbs).unwrap();
+        let sig = MlDsa87Signature::default();
+        let csr_bldr = MlDsa87CsrBuilder::new(&tbs[..len], &sig).unwrap();
+        let mut der = vec![0u8; csr_bldr.len()];
+        csr_bldr.build(&mut der).unwrap();
+
+        let (rem, csr) = X509CertificationRequest::from_der(&der).unwrap();
+        assert!(rem.is_empty());
+        assert_eq!(csr.signature_algorithm.algorithm, ML_DSA_87);
+        assert_eq!(
+            csr.certification_request_info
+                .subject_pki
+                .subject_public_key
+                .data,
+            &mldsa_key[..]
+        );
+        assert_eq!(csr.signature_value.data.len(), MlDsa87Signature::SIG_LEN);
+    }
+}
@@ -69,4 +69,51 @@ pub mod tests {
             }
         }
     }
+
+    pub struct MlDsa87AsymKey {
+        priv_key: PKey<Private>,
+        pub_key: Vec<u8>,
+    }
+
+    impl MlDsa87AsymKey {
+        pub fn priv_key(&self) -> &PKey<Private> {
+            &self.priv_key
+        }
+
+        pub fn pub_key(&self) -> &[u8] {
+            &self.pub_key
+        }
+
+        pub fn sha256(&self) -> [u8; 32] {
+            let mut sha = Sha256::new();
+            sha.update(self.pub_key());
+            sha.finish()
+        }
+
+        pub fn sha1(&self) -> [u8; 20] {
+            let mut sha = Sha1::new();
+            sha.update(self.pub_key());
+            sha.finish()
+        }
+
+        pub fn hex_str(&self) -> String {
+            hex::encode(self.sha256()).to_uppercase()
+        }
+    }
+
+    impl Default for MlDsa87AsymKey {
+        fn default() -> Self {
+            // PKCS#8 PrivateKeyInfo carrying a seed-only id-ml-dsa-87 private key
+            const PKCS8_SEED_PREFIX: [u8; 22] = [
+                0x30, 0x34, 0x02, 0x01, 0x00, 0x30, 0x0B, 0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65,
+                0x03, 0x04, 0x03, 0x13, 0x04, 0x22, 0x80, 0x20,
+            ];
+            let mut seed = [0u8; 32];
+            openssl::rand::rand_bytes(&mut seed).unwrap();
+            let der = [&PKCS8_SEED_PREFIX[..], &seed].concat();
+            let priv_key = PKey::private_key_from_pkcs8(&der).unwrap();
+            let pub_key = priv_key.raw_public_key().unwrap();
+            Self { priv_key, pub_key }
+        }
+    }
 }
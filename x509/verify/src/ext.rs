@@ -0,0 +1,136 @@
+/*++
+
+Licensed under the Apache-2.0 license.
+
+File Name:
+
+    ext.rs
+
+Abstract:
+
+    File contains helpers for extracting X509 extensions from DER certificates.
+
+--*/
+
+use asn1::ObjectIdentifier;
+
+pub const BASIC_CONSTRAINTS_OID: ObjectIdentifier = asn1::oid!(2, 5, 29, 19);
+pub const KEY_USAGE_OID: ObjectIdentifier = asn1::oid!(2, 5, 29, 15);
+
+/// Key Usage keyCertSign bit (bit 5, counted from the most significant bit)
+const KEY_USAGE_KEY_CERT_SIGN: u8 = 0x80 >> 5;
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct BasicConstraints {
+    pub ca: bool,
+    pub path_len: Option<u32>,
+}
+
+/// Extracts the DER bytes of an extension from x509 certificate bytes
+/// (`cert_der`) with the provided `oid`.
+pub fn find_extension<'a>(
+    cert_der: &'a [u8],
+    oid: &ObjectIdentifier,
+) -> Result<Option<&'a [u8]>, asn1::ParseError> {
+    asn1::parse(cert_der, |d| {
+        d.read_element::<asn1::Sequence>()?.parse(|d| {
+            let result = d.read_element::<asn1::Sequence>()?.parse(|d| {
+                d.read_explicit_element::<Option<u32>>(0)?; // version
+                d.read_element::<asn1::BigInt>()?; // serial-number
+                d.read_element::<asn1::Sequence>()?; // signature
+                d.read_element::<asn1::Sequence>()?; // name
+                d.read_element::<asn1::Sequence>()?; // validity
+                d.read_element::<asn1::Sequence>()?; // subject
+                d.read_element::<asn1::Sequence>()?; // subjectPublicKeyInfo
+                d.read_optional_implicit_element::<asn1::BitString>(1)?; // issuerUniqueID
+                d.read_optional_implicit_element::<asn1::BitString>(2)?; // subjectUniqueId
+                let Some(exts) = d.read_optional_explicit_element::<asn1::Sequence>(3)? else {
+                    return Ok(None);
+                };
+                exts.parse(|d| {
+                    let mut result = None;
+                    while !d.is_empty() {
+                        let found_result = d.read_element::<asn1::Sequence>()?.parse(|d| {
+                            let item_oid = d.read_element::<ObjectIdentifier>()?;
+                            d.read_element::<Option<bool>>()?; // critical
+                            let value = d.read_element::<&[u8]>()?;
+                            if &item_oid == oid {
+                                Ok(Some(value))
+                            } else {
+                                Ok(None)
+                            }
+                        })?;
+                        if let Some(found_result) = found_result {
+                            if result.is_some() {
+                                // The extension was found more than once
+                                return Err(asn1::ParseError::new(asn1::ParseErrorKind::ExtraData));
+                            }
+                            result = Some(found_result);
+                        }
+                    }
+                    Ok(result)
+                })
+            })?;
+            d.read_element::<asn1::Sequence>()?; // signatureAlgorithm
+            d.read_element::<asn1::BitString>()?; // signatureValue
+            Ok(result)
+        })
+    })
+}
+
+/// Parse the BasicConstraints extension of `cert_der`, if present
+pub fn basic_constraints(cert_der: &[u8]) -> Result<Option<BasicConstraints>, asn1::ParseError> {
+    let Some(ext_der) = find_extension(cert_der, &BASIC_CONSTRAINTS_OID)? else {
+        return Ok(None);
+    };
+    asn1::parse(ext_der, |d| {
+        d.read_element::<asn1::Sequence>()?.parse(|d| {
+            Ok(BasicConstraints {
+                ca: d.read_element::<Option<bool>>()?.unwrap_or(false),
+                path_len: d.read_element::<Option<u32>>()?,
+            })
+        })
+    })
+    .map(Some)
+}
+
+/// Returns true if the KeyUsage extension of `cert_der` allows certificate signing
+pub fn key_usage_allows_cert_sign(cert_der: &[u8]) -> Result<bool, asn1::ParseError> {
+    let Some(ext_der) = find_extension(cert_der, &KEY_USAGE_OID)? else {
+        return Ok(false);
+    };
+    let usage = asn1::parse_single::<asn1::BitString>(ext_der)?;
+    Ok(usage
+        .as_bytes()
+        .first()
+        .map_or(false, |b| b & KEY_USAGE_KEY_CERT_SIGN != 0))
+}
+
+/// Split a concatenation of DER certificates into the individual certificates
+pub fn split_der_chain(chain_der: &[u8]) -> Result<Vec<&[u8]>, asn1::ParseError> {
+    asn1::parse(chain_der, |d| {
+        let mut result = vec![];
+        while !d.is_empty() {
+            result.push(d.read_element::<asn1::Tlv>()?.full_data());
+        }
+        Ok(result)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_extension() {
+        let cert = include_bytes!(
+            "../../../test/tests/caliptra_integration_tests/smoke_testdata/ldevid_cert.der"
+        );
+
+        assert_eq!(find_extension(cert, &asn1::oid!(5, 3)), Ok(None));
+        assert_eq!(
+            find_extension(cert, &asn1::oid!(2, 5, 29, 15)),
+            Ok(Some([0x03, 0x02, 0x02, 0x04].as_slice()))
+        );
+    }
+}
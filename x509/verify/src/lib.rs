@@ -0,0 +1,785 @@
+/*++
+
+Licensed under the Apache-2.0 license.
+
+File Name:
+
+    lib.rs
+
+Abstract:
+
+    Host-side validation of the Caliptra DICE certificate chain.
+
+    The chain (IDevID -> LDevID -> FMC Alias -> RT Alias -> DPE leaf) is
+    checked end to end: issuer/subject linkage, signatures, key identifiers,
+    BasicConstraints path lengths and the TCG DICE TcbInfo extensions. When an
+    `ImageBundle` is provided, the FWIDs in the FMC and RT alias certificates
+    are compared with the digests in the image manifest.
+
+--*/
+
+mod ext;
+pub mod tcb_info;
+
+use std::fmt;
+
+use caliptra_image_types::{ImageBundle, ImageTocEntry};
+use openssl::{
+    pkey::{PKey, Public},
+    x509::X509,
+};
+
+pub use ext::{basic_constraints, find_extension, split_der_chain, BasicConstraints};
+pub use tcb_info::{DiceFwid, DiceTcbInfo};
+
+pub const TCB_INFO_TYPE_DEVICE_INFO: &[u8] = b"DEVICE_INFO";
+pub const TCB_INFO_TYPE_FMC_INFO: &[u8] = b"FMC_INFO";
+pub const TCB_INFO_TYPE_RT_INFO: &[u8] = b"RT_INFO";
+
+/// Bit set in the TcbInfo SVN to keep the encoding fixed-width.
+const TCB_INFO_SVN_FIXED_WIDTH: u32 = 1 << 8;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum CertLayer {
+    IDevId,
+    LDevId,
+    FmcAlias,
+    RtAlias,
+    DpeLeaf,
+}
+
+impl fmt::Display for CertLayer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            CertLayer::IDevId => "IDevID",
+            CertLayer::LDevId => "LDevID",
+            CertLayer::FmcAlias => "FMC Alias",
+            CertLayer::RtAlias => "RT Alias",
+            CertLayer::DpeLeaf => "DPE leaf",
+        };
+        f.write_str(name)
+    }
+}
+
+#[derive(Debug)]
+pub enum VerifyError {
+    /// The chain could not be split into certificates.
+    MalformedChain(asn1::ParseError),
+    /// The chain does not have the expected number of certificates.
+    UnexpectedCertCount(usize),
+    /// A certificate could not be parsed.
+    MalformedCert(CertLayer),
+    /// An extension in a certificate could not be parsed.
+    MalformedExtension(CertLayer, asn1::ParseError),
+    /// The issuer name does not match the subject name of the issuing certificate.
+    IssuerNameMismatch(CertLayer),
+    /// The signature does not verify against the issuer public key.
+    SignatureInvalid(CertLayer),
+    /// The certificate's public key is not the trusted key for its layer.
+    UntrustedKey(CertLayer),
+    /// The certificate has no SubjectKeyIdentifier.
+    MissingSubjectKeyId(CertLayer),
+    /// The certificate has no AuthorityKeyIdentifier.
+    MissingAuthorityKeyId(CertLayer),
+    /// The AuthorityKeyIdentifier does not match the issuer SubjectKeyIdentifier.
+    AuthorityKeyIdMismatch(CertLayer),
+    /// The issuing certificate is not a CA.
+    IssuerNotCa(CertLayer),
+    /// The issuing certificate does not allow certificate signing.
+    IssuerMissingKeyCertSign(CertLayer),
+    /// The certificate is followed by more intermediate CAs than its path length allows.
+    PathLenExceeded(CertLayer),
+    /// A required TcbInfo entry is missing.
+    MissingTcbInfo(CertLayer, &'static [u8]),
+    /// A TcbInfo FWID uses an unexpected hash algorithm.
+    UnexpectedFwidAlgorithm(CertLayer),
+    /// A TcbInfo FWID does not match the image digest.
+    FwidMismatch(CertLayer),
+    /// A TcbInfo SVN does not match the image manifest.
+    SvnMismatch(CertLayer),
+    /// A TcbInfo version does not match the image manifest.
+    VersionMismatch(CertLayer),
+    /// An OpenSSL operation failed.
+    OpenSsl(openssl::error::ErrorStack),
+}
+
+impl fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MalformedChain(e) => write!(f, "malformed certificate chain: {e:?}"),
+            Self::UnexpectedCertCount(n) => write!(f, "unexpected certificate count {n}"),
+            Self::MalformedCert(l) => write!(f, "{l}: malformed certificate"),
+            Self::MalformedExtension(l, e) => write!(f, "{l}: malformed extension: {e:?}"),
+            Self::IssuerNameMismatch(l) => write!(f, "{l}: issuer name mismatch"),
+            Self::SignatureInvalid(l) => write!(f, "{l}: invalid signature"),
+            Self::UntrustedKey(l) => write!(f, "{l}: public key is not trusted"),
+            Self::MissingSubjectKeyId(l) => write!(f, "{l}: missing subject key identifier"),
+            Self::MissingAuthorityKeyId(l) => write!(f, "{l}: missing authority key identifier"),
+            Self::AuthorityKeyIdMismatch(l) => write!(f, "{l}: authority key identifier mismatch"),
+            Self::IssuerNotCa(l) => write!(f, "{l}: issuer is not a CA"),
+            Self::IssuerMissingKeyCertSign(l) => write!(f, "{l}: issuer lacks keyCertSign"),
+            Self::PathLenExceeded(l) => write!(f, "{l}: path length constraint exceeded"),
+            Self::MissingTcbInfo(l, ty) => {
+                write!(f, "{l}: missing TcbInfo {}", String::from_utf8_lossy(ty))
+            }
+            Self::UnexpectedFwidAlgorithm(l) => write!(f, "{l}: unexpected FWID hash algorithm"),
+            Self::FwidMismatch(l) => write!(f, "{l}: FWID does not match image digest"),
+            Self::SvnMismatch(l) => write!(f, "{l}: SVN does not match image manifest"),
+            Self::VersionMismatch(l) => write!(f, "{l}: version does not match image manifest"),
+            Self::OpenSsl(e) => write!(f, "openssl error: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for VerifyError {}
+
+impl From<openssl::error::ErrorStack> for VerifyError {
+    fn from(e: openssl::error::ErrorStack) -> Self {
+        Self::OpenSsl(e)
+    }
+}
+
+/// Caliptra DICE certificate chain
+pub struct DiceCertChain {
+    pub idevid: Option<X509>,
+    pub ldevid: X509,
+    pub fmc_alias: X509,
+    pub rt_alias: X509,
+    pub dpe_leaf: Option<X509>,
+}
+
+impl DiceCertChain {
+    /// Parse a concatenation of DER certificates, such as the output of
+    /// `GET_CERT_CHAIN` or DPE `GetCertificateChain`
+    ///
+    /// # Arguments
+    ///
+    /// * `chain_der` - Concatenated DER certificates, root first
+    /// * `has_idevid` - The chain starts with the IDevID certificate
+    /// * `has_dpe_leaf` - The chain ends with a DPE leaf certificate
+    pub fn from_der(
+        chain_der: &[u8],
+        has_idevid: bool,
+        has_dpe_leaf: bool,
+    ) -> Result<Self, VerifyError> {
+        let certs = split_der_chain(chain_der).map_err(VerifyError::MalformedChain)?;
+        let expected = 3 + usize::from(has_idevid) + usize::from(has_dpe_leaf);
+        if certs.len() != expected {
+            return Err(VerifyError::UnexpectedCertCount(certs.len()));
+        }
+
+        let mut certs = certs.into_iter();
+        let mut next = |layer| {
+            X509::from_der(certs.next().ok_or(VerifyError::MalformedCert(layer))?)
+                .map_err(|_| VerifyError::MalformedCert(layer))
+        };
+        Ok(Self {
+            idevid: has_idevid.then(|| next(CertLayer::IDevId)).transpose()?,
+            ldevid: next(CertLayer::LDevId)?,
+            fmc_alias: next(CertLayer::FmcAlias)?,
+            rt_alias: next(CertLayer::RtAlias)?,
+            dpe_leaf: has_dpe_leaf.then(|| next(CertLayer::DpeLeaf)).transpose()?,
+        })
+    }
+
+    /// Certificates in the chain, root first
+    pub fn certs(&self) -> Vec<(CertLayer, &X509)> {
+        let mut certs = vec![];
+        if let Some(idevid) = &self.idevid {
+            certs.push((CertLayer::IDevId, idevid));
+        }
+        certs.push((CertLayer::LDevId, &self.ldevid));
+        certs.push((CertLayer::FmcAlias, &self.fmc_alias));
+        certs.push((CertLayer::RtAlias, &self.rt_alias));
+        if let Some(dpe_leaf) = &self.dpe_leaf {
+            certs.push((CertLayer::DpeLeaf, dpe_leaf));
+        }
+        certs
+    }
+}
+
+/// Chain verification options
+#[derive(Default)]
+pub struct VerifyOptions<'a> {
+    /// Trusted IDevID public key. If the chain contains the IDevID
+    /// certificate, its key must match; otherwise the key is used to verify
+    /// the LDevID certificate.
+    pub idevid_pub_key: Option<&'a PKey<Public>>,
+
+    /// Public key of the CA that issued the IDevID certificate, used to
+    /// verify the IDevID certificate when the chain contains it.
+    pub idevid_ca_pub_key: Option<&'a PKey<Public>>,
+
+    /// Image whose FMC and runtime digests and SVNs are compared with the
+    /// TcbInfo extensions.
+    pub image: Option<&'a ImageBundle>,
+}
+
+/// DICE TcbInfo entries extracted from a verified chain
+#[derive(Debug, Default)]
+pub struct VerifiedChain {
+    /// True if the chain was verified up to a trusted IDevID key or IDevID
+    /// CA key. Without either, the chain is only checked to be consistent.
+    pub anchored: bool,
+    pub fmc_tcb_infos: Vec<DiceTcbInfo>,
+    pub rt_tcb_info: DiceTcbInfo,
+    /// Complete FMC entry from the RT alias MultiTcbInfo. Certificates
+    /// issued by older FMCs don't carry it.
+    pub rt_fmc_tcb_info: Option<DiceTcbInfo>,
+}
+
+/// Verify a Caliptra DICE certificate chain end to end
+///
+/// # Arguments
+///
+/// * `chain` - Certificate chain
+/// * `opts` - Verification options
+///
+/// # Returns
+///
+/// * `VerifiedChain` - TcbInfo extensions from the FMC and RT alias certificates
+pub fn verify_cert_chain(
+    chain: &DiceCertChain,
+    opts: &VerifyOptions,
+) -> Result<VerifiedChain, VerifyError> {
+    let certs = chain.certs();
+
+    for (i, pair) in certs.windows(2).enumerate() {
+        let (issuer_layer, issuer) = pair[0];
+        let (layer, cert) = pair[1];
+        verify_issued_by(layer, cert, issuer_layer, issuer)?;
+
+        // Number of intermediate CAs that follow the issuer, excluding the
+        // end-entity certificate.
+        let following_intermediates = certs.len() - i - 2;
+        let bc = basic_constraints(&issuer.to_der()?)
+            .map_err(|e| VerifyError::MalformedExtension(issuer_layer, e))?
+            .unwrap_or_default();
+        if bc
+            .path_len
+            .map_or(false, |len| following_intermediates > len as usize)
+        {
+            return Err(VerifyError::PathLenExceeded(issuer_layer));
+        }
+    }
+
+    let anchored = match &chain.idevid {
+        Some(idevid) => {
+            let mut anchored = false;
+            if let Some(idevid_ca_pub_key) = opts.idevid_ca_pub_key {
+                if !idevid.verify(idevid_ca_pub_key)? {
+                    return Err(VerifyError::SignatureInvalid(CertLayer::IDevId));
+                }
+                anchored = true;
+            }
+            if let Some(idevid_pub_key) = opts.idevid_pub_key {
+                if !idevid.public_key()?.public_eq(idevid_pub_key) {
+                    return Err(VerifyError::UntrustedKey(CertLayer::IDevId));
+                }
+                anchored = true;
+            }
+            anchored
+        }
+        None => match opts.idevid_pub_key {
+            Some(idevid_pub_key) => {
+                if !chain.ldevid.verify(idevid_pub_key)? {
+                    return Err(VerifyError::SignatureInvalid(CertLayer::LDevId));
+                }
+                true
+            }
+            None => false,
+        },
+    };
+
+    let fmc_der = chain.fmc_alias.to_der()?;
+    let fmc_tcb_infos = DiceTcbInfo::find_multiple_in_cert(&fmc_der)
+        .map_err(|e| VerifyError::MalformedExtension(CertLayer::FmcAlias, e))?;
+    for ty in [TCB_INFO_TYPE_DEVICE_INFO, TCB_INFO_TYPE_FMC_INFO] {
+        if !fmc_tcb_infos.iter().any(|t| t.is_type(ty)) {
+            return Err(VerifyError::MissingTcbInfo(CertLayer::FmcAlias, ty));
+        }
+    }
+
+    let rt_der = chain.rt_alias.to_der()?;
+    let rt_tcb_info = DiceTcbInfo::find_single_in_cert(&rt_der)
+        .map_err(|e| VerifyError::MalformedExtension(CertLayer::RtAlias, e))?
+        .filter(|t| t.is_type(TCB_INFO_TYPE_RT_INFO))
+        .ok_or(VerifyError::MissingTcbInfo(
+            CertLayer::RtAlias,
+            TCB_INFO_TYPE_RT_INFO,
+        ))?;
+    let rt_fmc_tcb_info = DiceTcbInfo::find_multiple_in_cert(&rt_der)
+        .map_err(|e| VerifyError::MalformedExtension(CertLayer::RtAlias, e))?
+        .into_iter()
+        .find(|t| t.is_type(TCB_INFO_TYPE_FMC_INFO));
+
+    let layer_tcb_infos = fmc_tcb_infos
+        .iter()
+        .map(|t| (CertLayer::FmcAlias, t))
+        .chain([(CertLayer::RtAlias, &rt_tcb_info)])
+        .chain(rt_fmc_tcb_info.iter().map(|t| (CertLayer::RtAlias, t)));
+    for (layer, tcb_info) in layer_tcb_infos {
+        if tcb_info
+            .fwids
+            .iter()
+            .any(|fwid| fwid.hash_alg != tcb_info::SHA384_OID)
+        {
+            return Err(VerifyError::UnexpectedFwidAlgorithm(layer));
+        }
+    }
+
+    if let Some(image) = opts.image {
+        let fmc_info = fmc_tcb_infos
+            .iter()
+            .find(|t| t.is_type(TCB_INFO_TYPE_FMC_INFO))
+            .ok_or(VerifyError::MissingTcbInfo(
+                CertLayer::FmcAlias,
+                TCB_INFO_TYPE_FMC_INFO,
+            ))?;
+        verify_tcb_info_matches(CertLayer::FmcAlias, fmc_info, &image.manifest.fmc)?;
+        verify_tcb_info_matches(CertLayer::RtAlias, &rt_tcb_info, &image.manifest.runtime)?;
+        if let Some(rt_fmc_tcb_info) = &rt_fmc_tcb_info {
+            verify_tcb_info_matches(CertLayer::RtAlias, rt_fmc_tcb_info, &image.manifest.fmc)?;
+        }
+    }
+
+    Ok(VerifiedChain {
+        anchored,
+        fmc_tcb_infos,
+        rt_tcb_info,
+        rt_fmc_tcb_info,
+    })
+}
+
+/// Verify that `cert` was issued by `issuer`
+fn verify_issued_by(
+    layer: CertLayer,
+    cert: &X509,
+    issuer_layer: CertLayer,
+    issuer: &X509,
+) -> Result<(), VerifyError> {
+    if cert.issuer_name().to_der()? != issuer.subject_name().to_der()? {
+        return Err(VerifyError::IssuerNameMismatch(layer));
+    }
+
+    if !cert.verify(&issuer.public_key()?)? {
+        return Err(VerifyError::SignatureInvalid(layer));
+    }
+
+    let issuer_ski = issuer
+        .subject_key_id()
+        .ok_or(VerifyError::MissingSubjectKeyId(issuer_layer))?;
+    let aki = cert
+        .authority_key_id()
+        .ok_or(VerifyError::MissingAuthorityKeyId(layer))?;
+    if aki.as_slice() != issuer_ski.as_slice() {
+        return Err(VerifyError::AuthorityKeyIdMismatch(layer));
+    }
+
+    let issuer_der = issuer.to_der()?;
+    let bc = basic_constraints(&issuer_der)
+        .map_err(|e| VerifyError::MalformedExtension(issuer_layer, e))?;
+    if !bc.map_or(false, |bc| bc.ca) {
+        return Err(VerifyError::IssuerNotCa(issuer_layer));
+    }
+    if !ext::key_usage_allows_cert_sign(&issuer_der)
+        .map_err(|e| VerifyError::MalformedExtension(issuer_layer, e))?
+    {
+        return Err(VerifyError::IssuerMissingKeyCertSign(issuer_layer));
+    }
+
+    Ok(())
+}
+
+/// Verify that a TcbInfo entry describes the image with `digest` and `svn`
+fn verify_tcb_info_matches(
+    layer: CertLayer,
+    tcb_info: &DiceTcbInfo,
+    toc: &ImageTocEntry,
+) -> Result<(), VerifyError> {
+    // Image digests are stored as big-endian words in the manifest.
+    let digest: Vec<u8> = toc.digest.iter().flat_map(|w| w.to_be_bytes()).collect();
+    if !tcb_info.fwids.iter().any(|fwid| fwid.digest == digest) {
+        return Err(VerifyError::FwidMismatch(layer));
+    }
+    if tcb_info.svn != Some(TCB_INFO_SVN_FIXED_WIDTH | toc.svn) {
+        return Err(VerifyError::SvnMismatch(layer));
+    }
+    // Older ROMs do not encode the version.
+    if tcb_info
+        .version
+        .as_ref()
+        .map_or(false, |version| *version != format!("{:08X}", toc.version))
+    {
+        return Err(VerifyError::VersionMismatch(layer));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use openssl::{
+        asn1::{Asn1Integer, Asn1Object, Asn1OctetString, Asn1Time},
+        bn::BigNum,
+        ec::{EcGroup, EcKey},
+        hash::MessageDigest,
+        nid::Nid,
+        pkey::Private,
+        x509::{
+            extension::{
+                AuthorityKeyIdentifier, BasicConstraints as BcExt, KeyUsage, SubjectKeyIdentifier,
+            },
+            X509Builder, X509Extension, X509NameBuilder,
+        },
+    };
+
+    const FMC_DIGEST: [u32; 12] = [0x11111111; 12];
+    const RT_DIGEST: [u32; 12] = [0x22222222; 12];
+    const FMC_SVN: u32 = 9;
+    const RT_SVN: u32 = 3;
+
+    fn gen_key() -> PKey<Private> {
+        let group = EcGroup::from_curve_name(Nid::SECP384R1).unwrap();
+        PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap()
+    }
+
+    fn der_tlv(tag: u8, content: &[u8]) -> Vec<u8> {
+        let mut result = vec![tag];
+        if content.len() < 0x80 {
+            result.push(content.len() as u8);
+        } else {
+            result.push(0x81);
+            result.push(content.len() as u8);
+        }
+        result.extend_from_slice(content);
+        result
+    }
+
+    fn tcb_info_der(svn: u32, digest: &[u32], ty: &[u8]) -> Vec<u8> {
+        let digest: Vec<u8> = digest.iter().flat_map(|w| w.to_be_bytes()).collect();
+        let sha384_oid = [0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x02];
+        let fwid = der_tlv(
+            0x30,
+            &[der_tlv(0x06, &sha384_oid), der_tlv(0x04, &digest)].concat(),
+        );
+        let svn = (TCB_INFO_SVN_FIXED_WIDTH | svn) as u16;
+        der_tlv(
+            0x30,
+            &[
+                der_tlv(0x83, &svn.to_be_bytes()),
+                der_tlv(0xa6, &fwid),
+                der_tlv(0x89, ty),
+            ]
+            .concat(),
+        )
+    }
+
+    fn make_ext(oid: &str, der: &[u8]) -> X509Extension {
+        let oid = Asn1Object::from_str(oid).unwrap();
+        let der = Asn1OctetString::new_from_bytes(der).unwrap();
+        X509Extension::new_from_der(&oid, false, &der).unwrap()
+    }
+
+    fn build_cert(
+        cn: &str,
+        key: &PKey<Private>,
+        issuer: Option<(&X509, &PKey<Private>)>,
+        path_len: Option<u32>,
+        exts: Vec<X509Extension>,
+    ) -> X509 {
+        let mut builder = X509Builder::new().unwrap();
+        builder.set_version(2).unwrap();
+        let serial = Asn1Integer::from_bn(&BigNum::from_u32(1).unwrap()).unwrap();
+        builder.set_serial_number(&serial).unwrap();
+
+        let mut name = X509NameBuilder::new().unwrap();
+        name.append_entry_by_text("CN", cn).unwrap();
+        let name = name.build();
+        builder.set_subject_name(&name).unwrap();
+        match issuer {
+            Some((issuer_cert, _)) => builder.set_issuer_name(issuer_cert.subject_name()),
+            None => builder.set_issuer_name(&name),
+        }
+        .unwrap();
+        builder.set_pubkey(key).unwrap();
+        builder
+            .set_not_before(&Asn1Time::from_str("20230101000000Z").unwrap())
+            .unwrap();
+        builder
+            .set_not_after(&Asn1Time::from_str("99991231235959Z").unwrap())
+            .unwrap();
+
+        let bc = match path_len {
+            Some(path_len) => BcExt::new().critical().ca().pathlen(path_len).build(),
+            None => BcExt::new().critical().build(),
+        };
+        builder.append_extension(bc.unwrap()).unwrap();
+        let ku = match path_len {
+            Some(_) => KeyUsage::new().critical().key_cert_sign().build(),
+            None => KeyUsage::new().critical().digital_signature().build(),
+        };
+        builder.append_extension(ku.unwrap()).unwrap();
+
+        let issuer_cert = issuer.map(|(cert, _)| cert);
+        let ski = SubjectKeyIdentifier::new()
+            .build(&builder.x509v3_context(issuer_cert, None))
+            .unwrap();
+        builder.append_extension(ski).unwrap();
+        if issuer_cert.is_some() {
+            let aki = AuthorityKeyIdentifier::new()
+                .keyid(true)
+                .build(&builder.x509v3_context(issuer_cert, None))
+                .unwrap();
+            builder.append_extension(aki).unwrap();
+        }
+        for ext in exts {
+            builder.append_extension(ext).unwrap();
+        }
+
+        let signing_key = issuer.map_or(key, |(_, key)| key);
+        builder.sign(signing_key, MessageDigest::sha384()).unwrap();
+        builder.build()
+    }
+
+    fn build_chain(ldevid_path_len: u32) -> DiceCertChain {
+        let idevid_key = gen_key();
+        let ldevid_key = gen_key();
+        let fmc_key = gen_key();
+        let rt_key = gen_key();
+        let leaf_key = gen_key();
+
+        let idevid = build_cert("IDevID", &idevid_key, None, Some(4), vec![]);
+        let ldevid = build_cert(
+            "LDevID",
+            &ldevid_key,
+            Some((&idevid, &idevid_key)),
+            Some(ldevid_path_len),
+            vec![],
+        );
+        let multi_tcb_info = der_tlv(
+            0x30,
+            &[
+                tcb_info_der(7, &[0x33333333; 12], TCB_INFO_TYPE_DEVICE_INFO),
+                tcb_info_der(FMC_SVN, &FMC_DIGEST, TCB_INFO_TYPE_FMC_INFO),
+            ]
+            .concat(),
+        );
+        let fmc_alias = build_cert(
+            "FMC Alias",
+            &fmc_key,
+            Some((&ldevid, &ldevid_key)),
+            Some(2),
+            vec![make_ext("2.23.133.5.4.5", &multi_tcb_info)],
+        );
+        let rt_alias = build_cert(
+            "RT Alias",
+            &rt_key,
+            Some((&fmc_alias, &fmc_key)),
+            Some(1),
+            vec![
+                make_ext(
+                    "2.23.133.5.4.1",
+                    &tcb_info_der(RT_SVN, &RT_DIGEST, TCB_INFO_TYPE_RT_INFO),
+                ),
+                make_ext(
+                    "2.23.133.5.4.5",
+                    &der_tlv(
+                        0x30,
+                        &tcb_info_der(FMC_SVN, &FMC_DIGEST, TCB_INFO_TYPE_FMC_INFO),
+                    ),
+                ),
+            ],
+        );
+        let dpe_leaf = build_cert(
+            "DPE Leaf",
+            &leaf_key,
+            Some((&rt_alias, &rt_key)),
+            None,
+            vec![],
+        );
+
+        DiceCertChain {
+            idevid: Some(idevid),
+            ldevid,
+            fmc_alias,
+            rt_alias,
+            dpe_leaf: Some(dpe_leaf),
+        }
+    }
+
+    fn image() -> ImageBundle {
+        let mut image = ImageBundle::default();
+        image.manifest.fmc.digest = FMC_DIGEST;
+        image.manifest.fmc.svn = FMC_SVN;
+        image.manifest.runtime.digest = RT_DIGEST;
+        image.manifest.runtime.svn = RT_SVN;
+        image
+    }
+
+    #[test]
+    fn test_verify_chain() {
+        let chain = build_chain(3);
+        let image = image();
+        let verified = verify_cert_chain(
+            &chain,
+            &VerifyOptions {
+                image: Some(&image),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        // The IDevID certificate alone doesn't anchor the chain
+        assert!(!verified.anchored);
+        assert_eq!(verified.fmc_tcb_infos.len(), 2);
+        assert_eq!(verified.rt_tcb_info.svn, Some(0x100 | RT_SVN));
+        assert_eq!(
+            verified.rt_fmc_tcb_info.and_then(|t| t.svn),
+            Some(0x100 | FMC_SVN)
+        );
+    }
+
+    #[test]
+    fn test_from_der() {
+        let chain = build_chain(3);
+        let der: Vec<u8> = chain
+            .certs()
+            .iter()
+            .flat_map(|(_, cert)| cert.to_der().unwrap())
+            .collect();
+
+        let parsed = DiceCertChain::from_der(&der, true, true).unwrap();
+        assert_eq!(
+            parsed.rt_alias.to_der().unwrap(),
+            chain.rt_alias.to_der().unwrap()
+        );
+        assert!(matches!(
+            DiceCertChain::from_der(&der, false, false),
+            Err(VerifyError::UnexpectedCertCount(5))
+        ));
+    }
+
+    #[test]
+    fn test_anchored_idevid() {
+        let chain = build_chain(3);
+        let idevid = chain.idevid.as_ref().unwrap();
+        let idevid_pub_key = idevid.public_key().unwrap();
+        let wrong_key = PKey::public_key_from_der(&gen_key().public_key_to_der().unwrap()).unwrap();
+
+        for opts in [
+            VerifyOptions {
+                idevid_pub_key: Some(&idevid_pub_key),
+                ..Default::default()
+            },
+            // The test IDevID is self-signed
+            VerifyOptions {
+                idevid_ca_pub_key: Some(&idevid_pub_key),
+                ..Default::default()
+            },
+        ] {
+            assert!(verify_cert_chain(&chain, &opts).unwrap().anchored);
+        }
+
+        assert!(matches!(
+            verify_cert_chain(
+                &chain,
+                &VerifyOptions {
+                    idevid_pub_key: Some(&wrong_key),
+                    ..Default::default()
+                },
+            ),
+            Err(VerifyError::UntrustedKey(CertLayer::IDevId))
+        ));
+        assert!(matches!(
+            verify_cert_chain(
+                &chain,
+                &VerifyOptions {
+                    idevid_ca_pub_key: Some(&wrong_key),
+                    ..Default::default()
+                },
+            ),
+            Err(VerifyError::SignatureInvalid(CertLayer::IDevId))
+        ));
+    }
+
+    #[test]
+    fn test_unanchored_ldevid() {
+        let mut chain = build_chain(3);
+        let idevid = chain.idevid.take().unwrap();
+        let verified = verify_cert_chain(&chain, &VerifyOptions::default()).unwrap();
+        assert!(!verified.anchored);
+
+        let idevid_pub_key = idevid.public_key().unwrap();
+        let verified = verify_cert_chain(
+            &chain,
+            &VerifyOptions {
+                idevid_pub_key: Some(&idevid_pub_key),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert!(verified.anchored);
+
+        let wrong_key = PKey::public_key_from_der(&gen_key().public_key_to_der().unwrap()).unwrap();
+        assert!(matches!(
+            verify_cert_chain(
+                &chain,
+                &VerifyOptions {
+                    idevid_pub_key: Some(&wrong_key),
+                    ..Default::default()
+                },
+            ),
+            Err(VerifyError::SignatureInvalid(CertLayer::LDevId))
+        ));
+    }
+
+    #[test]
+    fn test_wrong_issuer() {
+        let mut chain = build_chain(3);
+        let other = build_chain(3);
+        chain.rt_alias = other.rt_alias;
+        assert!(matches!(
+            verify_cert_chain(&chain, &VerifyOptions::default()),
+            Err(VerifyError::SignatureInvalid(CertLayer::RtAlias))
+        ));
+    }
+
+    #[test]
+    fn test_path_len_exceeded() {
+        let chain = build_chain(0);
+        assert!(matches!(
+            verify_cert_chain(&chain, &VerifyOptions::default()),
+            Err(VerifyError::PathLenExceeded(CertLayer::LDevId))
+        ));
+    }
+
+    #[test]
+    fn test_fwid_mismatch() {
+        let chain = build_chain(3);
+        let mut image = image();
+        image.manifest.runtime.digest = [0x44444444; 12];
+        assert!(matches!(
+            verify_cert_chain(
+                &chain,
+                &VerifyOptions {
+                    image: Some(&image),
+                    ..Default::default()
+                },
+            ),
+            Err(VerifyError::FwidMismatch(CertLayer::RtAlias))
+        ));
+
+        let mut image = self::image();
+        image.manifest.fmc.svn = FMC_SVN + 1;
+        assert!(matches!(
+            verify_cert_chain(
+                &chain,
+                &VerifyOptions {
+                    image: Some(&image),
+                    ..Default::default()
+                },
+            ),
+            Err(VerifyError::SvnMismatch(CertLayer::FmcAlias))
+        ));
+    }
+}